@@ -59,6 +59,7 @@ fn bench_regex_only(c: &mut Criterion) {
 fn bench_safety_layer(c: &mut Criterion) {
     let config = ironclaw::config::SafetyConfig {
         max_output_length: 100_000,
+        tool_output_limits: std::collections::HashMap::new(),
         injection_check_enabled: true,
         #[cfg(feature = "zkproxy")]
         zkproxy: ironclaw::zkproxy::ZkProxyConfig::default(),
@@ -0,0 +1,4 @@
+-- The generic 64-char hex pattern was replaced in the leak detector by
+-- entropy and context scoring, since it flagged every SHA-256 digest.
+-- Drop the copy seeded by V2 so stored patterns match the detector.
+DELETE FROM leak_detection_patterns WHERE name = 'high_entropy_hex';
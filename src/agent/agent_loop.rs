@@ -18,7 +18,7 @@ use crate::agent::self_repair::{DefaultSelfRepair, RepairResult, SelfRepair};
 use crate::agent::session_manager::SessionManager;
 use crate::agent::submission::{Submission, SubmissionParser, SubmissionResult};
 use crate::agent::{HeartbeatConfig as AgentHeartbeatConfig, Router, Scheduler};
-use crate::channels::{ChannelManager, IncomingMessage, OutgoingResponse, StatusUpdate};
+use crate::channels::{ChannelManager, IncomingMessage, OutgoingResponse, Reaction, StatusUpdate};
 use crate::config::{AgentConfig, HeartbeatConfig, RoutineConfig, SkillsConfig};
 use crate::context::ContextManager;
 use crate::db::Database;
@@ -27,6 +27,7 @@ use crate::extensions::ExtensionManager;
 use crate::hooks::HookRegistry;
 use crate::llm::LlmProvider;
 use crate::safety::SafetyLayer;
+use crate::shutdown::ShutdownHandle;
 use crate::skills::SkillRegistry;
 use crate::tools::ToolRegistry;
 use crate::workspace::Workspace;
@@ -88,6 +89,7 @@ pub struct Agent {
     pub(super) heartbeat_config: Option<HeartbeatConfig>,
     pub(super) hygiene_config: Option<crate::config::HygieneConfig>,
     pub(super) routine_config: Option<RoutineConfig>,
+    pub(super) shutdown: Option<ShutdownHandle>,
 }
 
 impl Agent {
@@ -133,9 +135,18 @@ impl Agent {
             heartbeat_config,
             hygiene_config,
             routine_config,
+            shutdown: None,
         }
     }
 
+    /// Stop reading messages when `shutdown` is triggered and count each
+    /// turn as in flight. Closing the channels is then left to the shutdown
+    /// coordinator.
+    pub fn with_shutdown(mut self, shutdown: ShutdownHandle) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
     // Convenience accessors
 
     pub(super) fn store(&self) -> Option<&Arc<dyn Database>> {
@@ -151,6 +162,15 @@ impl Agent {
         self.deps.cheap_llm.as_ref().unwrap_or(&self.deps.llm)
     }
 
+    /// Provider for the next conversational turn: the cheap one while the
+    /// cost guard asks to stretch the daily budget, otherwise the main one.
+    pub(super) async fn active_llm(&self) -> &Arc<dyn LlmProvider> {
+        match &self.deps.cheap_llm {
+            Some(cheap) if self.cost_guard().should_downgrade().await => cheap,
+            _ => &self.deps.llm,
+        }
+    }
+
     pub(super) fn safety(&self) -> &Arc<SafetyLayer> {
         &self.deps.safety
     }
@@ -323,7 +343,8 @@ impl Agent {
             if hb_config.enabled {
                 if let Some(workspace) = self.workspace() {
                     let config = AgentHeartbeatConfig::default()
-                        .with_interval(std::time::Duration::from_secs(hb_config.interval_secs));
+                        .with_interval(std::time::Duration::from_secs(hb_config.interval_secs))
+                        .with_sampling(self.config.sampling.heartbeat);
 
                     // Set up notification channel
                     let (notify_tx, mut notify_rx) =
@@ -396,14 +417,17 @@ impl Agent {
                     let (notify_tx, mut notify_rx) =
                         tokio::sync::mpsc::channel::<OutgoingResponse>(32);
 
-                    let engine = Arc::new(RoutineEngine::new(
-                        rt_config.clone(),
-                        Arc::clone(store),
-                        self.llm().clone(),
-                        Arc::clone(workspace),
-                        notify_tx,
-                        Some(self.scheduler.clone()),
-                    ));
+                    let engine = Arc::new(
+                        RoutineEngine::new(
+                            rt_config.clone(),
+                            Arc::clone(store),
+                            self.llm().clone(),
+                            Arc::clone(workspace),
+                            notify_tx,
+                            Some(self.scheduler.clone()),
+                        )
+                        .with_sampling(self.config.sampling.routine),
+                    );
 
                     // Register routine tools
                     self.deps
@@ -471,6 +495,12 @@ impl Agent {
         // Main message loop
         tracing::info!("Agent {} ready and listening", self.config.name);
 
+        let shutdown_token = self
+            .shutdown
+            .as_ref()
+            .map(|s| s.token())
+            .unwrap_or_default();
+
         loop {
             let message = tokio::select! {
                 biased;
@@ -478,6 +508,10 @@ impl Agent {
                     tracing::info!("Ctrl+C received, shutting down...");
                     break;
                 }
+                _ = shutdown_token.cancelled() => {
+                    tracing::info!("Shutdown requested, no longer accepting messages");
+                    break;
+                }
                 msg = message_stream.next() => {
                     match msg {
                         Some(m) => m,
@@ -489,7 +523,19 @@ impl Agent {
                 }
             };
 
-            match self.handle_message(&message).await {
+            let _turn = self.shutdown.as_ref().map(|s| s.turns().begin());
+
+            // Acknowledge receipt, then mark the outcome (best-effort).
+            let _ = self.channels.react(&message, Reaction::Processing).await;
+            let result = self.handle_message(&message).await;
+            let outcome = if result.is_ok() {
+                Reaction::Done
+            } else {
+                Reaction::Error
+            };
+            let _ = self.channels.react(&message, outcome).await;
+
+            match result {
                 Ok(Some(response)) if !response.is_empty() => {
                     // Hook: BeforeOutbound — allow hooks to modify or suppress outbound
                     let event = crate::hooks::HookEvent::Outbound {
@@ -582,7 +628,9 @@ impl Agent {
             cron_handle.abort();
         }
         self.scheduler.stop_all().await;
-        self.channels.shutdown_all().await?;
+        if self.shutdown.is_none() {
+            self.channels.shutdown_all().await?;
+        }
 
         Ok(())
     }
@@ -682,9 +730,17 @@ impl Agent {
             Submission::Compact => self.process_compact(session, thread_id).await,
             Submission::Clear => self.process_clear(session, thread_id).await,
             Submission::NewThread => self.process_new_thread(message).await,
+            Submission::Branch { at_turn } => {
+                self.process_branch(message, session, thread_id, at_turn)
+                    .await
+            }
             Submission::Heartbeat => self.process_heartbeat().await,
             Submission::Summarize => self.process_summarize(session, thread_id).await,
             Submission::Suggest => self.process_suggest(session, thread_id).await,
+            Submission::MaxIterations { change } => {
+                self.process_max_iterations(session, change).await
+            }
+            Submission::ReadOnly { enabled } => self.process_read_only(session, enabled).await,
             Submission::Quit => return Ok(None),
             Submission::SwitchThread { thread_id: target } => {
                 self.process_switch_thread(message, target).await
@@ -727,6 +783,19 @@ impl Agent {
             SubmissionResult::Ok { message } => Ok(message),
             SubmissionResult::Error { message } => Ok(Some(format!("Error: {}", message))),
             SubmissionResult::Interrupted => Ok(Some("Interrupted.".into())),
+            SubmissionResult::GaveUp { reason, content } => {
+                tracing::info!(%reason, "Turn ended without finishing the task");
+                // Tell the channel why, so it can mark the reply as unfinished.
+                let _ = self
+                    .channels
+                    .send_status(
+                        &message.channel,
+                        StatusUpdate::Status(format!("Stopped early: {reason}")),
+                        &message.metadata,
+                    )
+                    .await;
+                Ok(Some(content))
+            }
             SubmissionResult::NeedApproval {
                 request_id,
                 tool_name,
@@ -9,7 +9,7 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::agent::session::Session;
-use crate::agent::submission::SubmissionResult;
+use crate::agent::submission::{IterationLimitChange, SubmissionResult};
 use crate::agent::{Agent, MessageIntent};
 use crate::channels::{IncomingMessage, StatusUpdate};
 use crate::error::Error;
@@ -229,7 +229,7 @@ impl Agent {
         };
 
         let runner = crate::agent::HeartbeatRunner::new(
-            crate::agent::HeartbeatConfig::default(),
+            crate::agent::HeartbeatConfig::default().with_sampling(self.config.sampling.heartbeat),
             crate::workspace::hygiene::HygieneConfig::default(),
             workspace.clone(),
             self.llm().clone(),
@@ -253,6 +253,60 @@ impl Agent {
         }
     }
 
+    /// Show or change the session's tool iteration limit.
+    pub(super) async fn process_max_iterations(
+        &self,
+        session: Arc<Mutex<Session>>,
+        change: Option<IterationLimitChange>,
+    ) -> Result<SubmissionResult, Error> {
+        if let Some(change) = change {
+            let limit = match change {
+                IterationLimitChange::Set(limit) => Some(limit),
+                IterationLimitChange::Reset => None,
+            };
+            if !self.set_max_tool_iterations(&session, limit).await {
+                return Ok(SubmissionResult::error(format!(
+                    "Tool iteration limit can be at most {}.",
+                    self.session_iteration_ceiling()
+                )));
+            }
+        }
+        let limit = self.max_tool_iterations(&session).await;
+        let source = if session.lock().await.max_tool_iterations.is_some() {
+            "this session"
+        } else {
+            "default"
+        };
+        Ok(SubmissionResult::response(format!(
+            "Tool iteration limit: {} ({})",
+            limit, source
+        )))
+    }
+
+    /// Show or change the session's read-only mode.
+    pub(super) async fn process_read_only(
+        &self,
+        session: Arc<Mutex<Session>>,
+        enabled: Option<bool>,
+    ) -> Result<SubmissionResult, Error> {
+        if let Some(enabled) = enabled
+            && !self.set_read_only(&session, enabled).await
+        {
+            return Ok(SubmissionResult::error(
+                "Read-only mode is set by the agent configuration and can't be turned off.",
+            ));
+        }
+        let state = if self.read_only_mode(&session).await {
+            "on"
+        } else {
+            "off"
+        };
+        Ok(SubmissionResult::response(format!(
+            "Read-only mode: {}",
+            state
+        )))
+    }
+
     /// Summarize the current thread's conversation.
     pub(super) async fn process_summarize(
         &self,
@@ -292,7 +346,7 @@ impl Agent {
 
         let request = crate::llm::CompletionRequest::new(context)
             .with_max_tokens(512)
-            .with_temperature(0.3);
+            .with_sampling(self.config.sampling.summary);
 
         let reasoning = Reasoning::new(self.llm().clone(), self.safety().clone());
         match reasoning.complete(request).await {
@@ -340,7 +394,7 @@ impl Agent {
 
         let request = crate::llm::CompletionRequest::new(context)
             .with_max_tokens(512)
-            .with_temperature(0.5);
+            .with_sampling(self.config.sampling.planning);
 
         let reasoning = Reasoning::new(self.llm().clone(), self.safety().clone());
         match reasoning.complete(request).await {
@@ -382,6 +436,7 @@ impl Agent {
                 "  /interrupt        Stop current operation\n",
                 "  /new              New conversation thread\n",
                 "  /thread <id>      Switch to thread\n",
+                "  /branch [turn]    Branch thread at a turn\n",
                 "  /resume <id>      Resume from checkpoint\n",
                 "\n",
                 "Skills:\n",
@@ -392,6 +447,8 @@ impl Agent {
                 "  /heartbeat        Run heartbeat check\n",
                 "  /summarize        Summarize current thread\n",
                 "  /suggest          Suggest next steps\n",
+                "  /iterations [n|reset]  Show or set this session's tool call limit\n",
+                "  /readonly [on|off]     Show or set read-only mode for this session\n",
                 "\n",
                 "  /quit             Exit",
             ))),
@@ -12,7 +12,7 @@ use chrono::Utc;
 use crate::agent::context_monitor::{CompactionStrategy, ContextBreakdown};
 use crate::agent::session::Thread;
 use crate::error::Error;
-use crate::llm::{ChatMessage, CompletionRequest, LlmProvider, Reasoning};
+use crate::llm::{ChatMessage, CompletionRequest, LlmProvider, Reasoning, SamplingProfile};
 use crate::safety::SafetyLayer;
 use crate::workspace::Workspace;
 
@@ -35,12 +35,23 @@ pub struct CompactionResult {
 pub struct ContextCompactor {
     llm: Arc<dyn LlmProvider>,
     safety: Arc<SafetyLayer>,
+    sampling: SamplingProfile,
 }
 
 impl ContextCompactor {
     /// Create a new context compactor.
     pub fn new(llm: Arc<dyn LlmProvider>, safety: Arc<SafetyLayer>) -> Self {
-        Self { llm, safety }
+        Self {
+            llm,
+            safety,
+            sampling: SamplingProfile::Balanced,
+        }
+    }
+
+    /// Set the sampling profile for summary generation.
+    pub fn with_sampling(mut self, sampling: SamplingProfile) -> Self {
+        self.sampling = sampling;
+        self
     }
 
     /// Compact a thread's context using the given strategy.
@@ -231,7 +242,7 @@ Be brief but capture all important details. Use bullet points."#,
 
         let request = CompletionRequest::new(request_messages)
             .with_max_tokens(1024)
-            .with_temperature(0.3);
+            .with_sampling(self.sampling);
 
         let reasoning = Reasoning::new(self.llm.clone(), self.safety.clone());
         let (text, _) = reasoning.complete(request).await?;
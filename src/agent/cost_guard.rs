@@ -21,6 +21,9 @@ pub struct CostGuardConfig {
     pub max_cost_per_day_cents: Option<u64>,
     /// Maximum LLM calls per hour. None = unlimited.
     pub max_actions_per_hour: Option<u64>,
+    /// Fraction of `max_cost_per_day_cents` (0.0-1.0) after which requests
+    /// go to the cheap model for the rest of the day. None = never.
+    pub auto_downgrade_at_budget_fraction: Option<f64>,
 }
 
 /// Error returned when a cost limit is exceeded.
@@ -76,6 +79,10 @@ pub struct CostGuard {
     /// Flag set when daily budget is exceeded to short-circuit checks.
     budget_exceeded: AtomicBool,
 
+    /// Whether the last `should_downgrade` answer was yes, so the switch in
+    /// either direction is logged once.
+    downgraded: AtomicBool,
+
     /// Per-model token usage since startup.
     model_tokens: Mutex<HashMap<String, ModelTokens>>,
 }
@@ -96,6 +103,7 @@ impl CostGuard {
             }),
             action_window: Mutex::new(VecDeque::new()),
             budget_exceeded: AtomicBool::new(false),
+            downgraded: AtomicBool::new(false),
             model_tokens: Mutex::new(HashMap::new()),
         }
     }
@@ -232,6 +240,36 @@ impl CostGuard {
         }
     }
 
+    /// Whether requests should use the cheap model to stretch the rest of
+    /// today's budget.
+    ///
+    /// True once today's spend reaches `auto_downgrade_at_budget_fraction`
+    /// of `max_cost_per_day_cents`, and false again after the daily counter
+    /// rolls over.
+    pub async fn should_downgrade(&self) -> bool {
+        let (Some(fraction), Some(limit_cents)) = (
+            self.config.auto_downgrade_at_budget_fraction,
+            self.config.max_cost_per_day_cents,
+        ) else {
+            return false;
+        };
+
+        let spent = self.daily_spend().await;
+        let downgrade = to_cents(spent) as f64 >= limit_cents as f64 * fraction;
+        let was_downgraded = self.downgraded.swap(downgrade, Ordering::Relaxed);
+        if downgrade && !was_downgraded {
+            tracing::warn!(
+                "Daily spend ${:.2} reached {:.0}% of ${:.2} budget, switching to the cheap model",
+                spent,
+                fraction * 100.0,
+                Decimal::from(limit_cents) / dec!(100)
+            );
+        } else if !downgrade && was_downgraded {
+            tracing::info!("Daily budget reset, switching back to the primary model");
+        }
+        downgrade
+    }
+
     /// Number of actions in the current hourly window.
     pub async fn actions_this_hour(&self) -> u64 {
         let mut window = self.action_window.lock().await;
@@ -246,6 +284,13 @@ impl CostGuard {
     pub async fn model_usage(&self) -> HashMap<String, ModelTokens> {
         self.model_tokens.lock().await.clone()
     }
+
+    /// Move the daily counter back a day, as if midnight UTC had passed.
+    #[cfg(test)]
+    async fn roll_back_day(&self) {
+        let mut daily = self.daily_cost.lock().await;
+        daily.reset_date -= chrono::Duration::days(1);
+    }
 }
 
 /// Convert a Decimal USD amount to whole cents (truncated).
@@ -277,6 +322,7 @@ mod tests {
         let guard = CostGuard::new(CostGuardConfig {
             max_cost_per_day_cents: Some(1), // $0.01 limit
             max_actions_per_hour: None,
+            auto_downgrade_at_budget_fraction: None,
         });
 
         // First call allowed
@@ -303,6 +349,7 @@ mod tests {
         let guard = CostGuard::new(CostGuardConfig {
             max_cost_per_day_cents: None,
             max_actions_per_hour: Some(3),
+            auto_downgrade_at_budget_fraction: None,
         });
 
         // First 3 actions allowed
@@ -402,4 +449,40 @@ mod tests {
         // Costs should differ since models have different pricing
         assert_ne!(gpt.cost, claude.cost);
     }
+
+    #[tokio::test]
+    async fn test_downgrade_at_budget_fraction() {
+        let guard = CostGuard::new(CostGuardConfig {
+            max_cost_per_day_cents: Some(100),
+            max_actions_per_hour: None,
+            auto_downgrade_at_budget_fraction: Some(0.8),
+        });
+        let rate = Some((dec!(0.0001), Decimal::ZERO));
+
+        // 70 cents of a $1 budget.
+        guard.record_llm_call("gpt-4o", 7_000, 0, rate).await;
+        assert!(!guard.should_downgrade().await);
+
+        // 85 cents: past 80%, but still allowed.
+        guard.record_llm_call("gpt-4o", 1_500, 0, rate).await;
+        assert!(guard.should_downgrade().await);
+        assert!(guard.check_allowed().await.is_ok());
+
+        // A new day reverts to the primary model.
+        guard.roll_back_day().await;
+        assert!(!guard.should_downgrade().await);
+    }
+
+    #[tokio::test]
+    async fn test_no_downgrade_without_config() {
+        let guard = CostGuard::new(CostGuardConfig {
+            max_cost_per_day_cents: Some(100),
+            max_actions_per_hour: None,
+            auto_downgrade_at_budget_fraction: None,
+        });
+        guard
+            .record_llm_call("gpt-4o", 9_000, 0, Some((dec!(0.0001), Decimal::ZERO)))
+            .await;
+        assert!(!guard.should_downgrade().await);
+    }
 }
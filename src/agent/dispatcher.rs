@@ -5,16 +5,24 @@
 
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use tokio::sync::Mutex;
 use tokio::task::JoinSet;
 use uuid::Uuid;
 
 use crate::agent::Agent;
+use crate::agent::loop_guard::GiveUpReason;
 use crate::agent::session::{PendingApproval, Session, ThreadState};
-use crate::channels::{IncomingMessage, StatusUpdate};
+use crate::agent::transcript::{redact_str, redact_value};
+use crate::channels::{
+    ChannelManager, IncomingMessage, ReasoningTrace, StatusUpdate, TracedToolCall,
+};
 use crate::context::JobContext;
 use crate::error::Error;
-use crate::llm::{ChatMessage, Reasoning, ReasoningContext, RespondResult};
+use crate::llm::{
+    ChatMessage, Reasoning, ReasoningContext, RespondResult, StreamSink, ToolCall, UsageTracker,
+};
+use crate::tools::ordering::{PlannedCall, execution_waves, resolve_depends_on, take_depends_on};
 
 /// Result of the agentic loop execution.
 pub(super) enum AgenticLoopResult {
@@ -25,13 +33,57 @@ pub(super) enum AgenticLoopResult {
         /// The pending approval request to store.
         pending: PendingApproval,
     },
+    /// Stopped without finishing the task.
+    GaveUp {
+        /// Why the loop stopped.
+        reason: GiveUpReason,
+        /// The model's final text answer, if it gave one.
+        partial: Option<String>,
+    },
+}
+
+impl AgenticLoopResult {
+    /// The reply that ends the turn, with the reason if the loop gave up,
+    /// or the pending approval the turn is waiting on.
+    pub(super) fn into_reply(self) -> Result<(String, Option<GiveUpReason>), PendingApproval> {
+        match self {
+            Self::Response(text) => Ok((text, None)),
+            Self::GaveUp { reason, partial } => {
+                Ok((reason.reply(partial.as_deref()), Some(reason)))
+            }
+            Self::NeedApproval { pending } => Err(pending),
+        }
+    }
+}
+
+/// Sends reply text to the originating channel as `StatusUpdate::StreamChunk`
+/// while the LLM is still producing it.
+struct ChannelStreamSink {
+    channels: Arc<ChannelManager>,
+    channel: String,
+    metadata: serde_json::Value,
+}
+
+#[async_trait]
+impl StreamSink for ChannelStreamSink {
+    async fn text_delta(&self, delta: &str) {
+        let _ = self
+            .channels
+            .send_status(
+                &self.channel,
+                StatusUpdate::StreamChunk(delta.to_string()),
+                &self.metadata,
+            )
+            .await;
+    }
 }
 
 impl Agent {
     /// Run the agentic loop: call LLM, execute tools, repeat until text response.
     ///
-    /// Returns `AgenticLoopResult::Response` on completion, or
-    /// `AgenticLoopResult::NeedApproval` if a tool requires user approval.
+    /// Returns `AgenticLoopResult::Response` on completion,
+    /// `AgenticLoopResult::NeedApproval` if a tool requires user approval, or
+    /// `AgenticLoopResult::GaveUp` if the turn stopped without finishing.
     ///
     pub(super) async fn run_agentic_loop(
         &self,
@@ -102,10 +154,23 @@ impl Agent {
             None
         };
 
-        let mut reasoning = Reasoning::new(self.llm().clone(), self.safety().clone())
+        // Token usage and spend across every LLM call in this turn.
+        let turn_usage = UsageTracker::new();
+
+        let llm = self.active_llm().await.clone();
+        let format = self.channels.format_capabilities(&message.channel).await;
+        let mut reasoning = Reasoning::new(llm.clone(), self.safety().clone())
             .with_channel(message.channel.clone())
-            .with_model_name(self.llm().active_model_name())
-            .with_group_chat(is_group_chat);
+            .with_format_capabilities(format)
+            .with_model_name(llm.active_model_name())
+            .with_group_chat(is_group_chat)
+            .with_usage_tracker(turn_usage.clone())
+            .with_sampling_profiles(self.config.sampling)
+            .with_stream_sink(Arc::new(ChannelStreamSink {
+                channels: Arc::clone(&self.channels),
+                channel: message.channel.clone(),
+                metadata: message.metadata.clone(),
+            }));
         if let Some(prompt) = system_prompt {
             reasoning = reasoning.with_system_prompt(prompt);
         }
@@ -119,12 +184,23 @@ impl Agent {
         // Create a JobContext for tool execution (chat doesn't have a real job)
         let job_ctx = JobContext::with_user(&message.user_id, "chat", "Interactive chat session");
 
-        let max_tool_iterations = self.config.max_tool_iterations;
+        let max_tool_iterations = self.max_tool_iterations(&session).await;
         // Force a text-only response on the last iteration to guarantee termination
         // instead of hard-erroring. The penultimate iteration also gets a nudge
         // message so the LLM knows it should wrap up.
         let force_text_at = max_tool_iterations;
         let nudge_at = max_tool_iterations.saturating_sub(1);
+        // Reject a call repeated too often in the turn instead of letting it
+        // run until the iteration limit. Picks up the counts from before an
+        // approval when the turn resumes.
+        let mut loop_guard = {
+            let mut sess = session.lock().await;
+            sess.threads
+                .get_mut(&thread_id)
+                .and_then(|thread| thread.last_turn_mut())
+                .map(|turn| std::mem::take(&mut turn.loop_guard))
+                .unwrap_or_default()
+        };
         let mut iteration = 0;
         loop {
             iteration += 1;
@@ -164,6 +240,21 @@ impl Agent {
             // Inject a nudge message when approaching the iteration limit so the
             // LLM is aware it should produce a final answer on the next turn.
             if iteration == nudge_at {
+                tracing::warn!(
+                    iteration,
+                    max_tool_iterations,
+                    "Approaching the tool iteration limit"
+                );
+                let _ = self
+                    .channels
+                    .send_status(
+                        &message.channel,
+                        StatusUpdate::Status(format!(
+                            "Approaching the tool iteration limit ({iteration} of {max_tool_iterations})"
+                        )),
+                        &message.metadata,
+                    )
+                    .await;
                 context_messages.push(ChatMessage::system(
                     "You are approaching the tool call limit. \
                      Provide your best final answer on the next response \
@@ -172,10 +263,31 @@ impl Agent {
                 ));
             }
 
-            let force_text = iteration >= force_text_at;
+            // A model that repeats a call even after it was rejected, or
+            // keeps calling a tool it can't use or that keeps failing, is stuck: take its tools
+            // away and ask for an answer now.
+            let mut give_up = loop_guard.give_up_reason();
+            if let Some(reason) = &give_up {
+                tracing::warn!(iteration, %reason, "Agentic loop giving up, forcing a text response");
+                context_messages.push(ChatMessage::system(format!(
+                    "Stop calling tools ({reason}). Tools are unavailable for \
+                     this response: answer with the information you have."
+                )));
+            } else if iteration >= force_text_at {
+                give_up = Some(GiveUpReason::IterationLimit {
+                    limit: max_tool_iterations,
+                });
+            }
+            let force_text = give_up.is_some();
 
-            // Refresh tool definitions each iteration so newly built tools become visible
-            let tool_defs = self.tools().tool_definitions().await;
+            // Refresh tool definitions each iteration so newly built tools become visible.
+            // In read-only mode the model only sees tools that can't change state.
+            let read_only = self.read_only_mode(&session).await;
+            let tool_defs = if read_only {
+                self.tools().read_only_tool_definitions().await
+            } else {
+                self.tools().tool_definitions().await
+            };
 
             // Apply trust-based tool attenuation if skills are active.
             let tool_defs = if !active_skills.is_empty() {
@@ -254,14 +366,14 @@ impl Agent {
             };
 
             // Record cost and track token usage
-            let model_name = self.llm().active_model_name();
+            let model_name = llm.active_model_name();
             let call_cost = self
                 .cost_guard()
                 .record_llm_call(
                     &model_name,
                     output.usage.input_tokens,
                     output.usage.output_tokens,
-                    Some(self.llm().cost_per_token()),
+                    Some(llm.cost_per_token()),
                 )
                 .await;
             tracing::debug!(
@@ -273,12 +385,35 @@ impl Agent {
 
             match output.result {
                 RespondResult::Text(text) => {
+                    let totals = turn_usage.totals();
+                    tracing::debug!(
+                        llm_calls = totals.calls,
+                        input_tokens = totals.input_tokens,
+                        output_tokens = totals.output_tokens,
+                        "Turn used ${:.6}",
+                        totals.cost,
+                    );
+                    if let Some(reason) = give_up {
+                        return Ok(AgenticLoopResult::GaveUp {
+                            reason,
+                            partial: Some(text),
+                        });
+                    }
                     return Ok(AgenticLoopResult::Response(text));
                 }
                 RespondResult::ToolCalls {
-                    tool_calls,
+                    mut tool_calls,
                     content,
                 } => {
+                    if let Some(trace) =
+                        self.reasoning_trace_status(iteration, content.as_deref(), &tool_calls)
+                    {
+                        let _ = self
+                            .channels
+                            .send_status(&message.channel, trace, &message.metadata)
+                            .await;
+                    }
+
                     // Add the assistant message with tool_calls to context.
                     // OpenAI protocol requires this before tool-result messages.
                     context_messages.push(ChatMessage::assistant_with_tool_calls(
@@ -311,6 +446,14 @@ impl Agent {
                         }
                     }
 
+                    // Turn positional `_depends_on` hints into tool call IDs
+                    // so they stay valid for calls deferred behind an approval.
+                    resolve_depends_on(
+                        tool_calls
+                            .iter_mut()
+                            .map(|tc| (tc.id.as_str(), &mut tc.arguments)),
+                    );
+
                     // === Phase 1: Preflight (sequential) ===
                     // Walk tool_calls checking approval and hooks. Classify
                     // each tool as Rejected (by hook) or Runnable. Stop at the
@@ -326,6 +469,7 @@ impl Agent {
                     }
                     let mut preflight: Vec<(crate::llm::ToolCall, PreflightOutcome)> = Vec::new();
                     let mut runnable: Vec<(usize, crate::llm::ToolCall)> = Vec::new();
+                    let mut planned: Vec<PlannedCall> = Vec::new();
                     let mut approval_needed: Option<(
                         usize,
                         crate::llm::ToolCall,
@@ -334,6 +478,26 @@ impl Agent {
 
                     for (idx, original_tc) in tool_calls.iter().enumerate() {
                         let mut tc = original_tc.clone();
+                        let depends_on = take_depends_on(&mut tc.arguments);
+
+                        // Read-only mode: reject a call to a tool the model
+                        // was never offered.
+                        if let Err(e) = check_read_only(self.tools(), read_only, &tc.name).await {
+                            loop_guard.record_unavailable(&tc.name);
+                            preflight.push((tc, PreflightOutcome::Rejected(e.to_string())));
+                            continue;
+                        }
+
+                        if loop_guard.record(&tc.name, &tc.arguments) {
+                            tracing::warn!(
+                                tool = %tc.name,
+                                iteration,
+                                "Rejecting repeated identical tool call"
+                            );
+                            let reason = loop_guard.message(&tc.name);
+                            preflight.push((tc, PreflightOutcome::Rejected(reason)));
+                            continue;
+                        }
 
                         // Hook: BeforeToolCall (runs before approval so hooks can
                         // modify parameters — approval is checked on final params)
@@ -400,130 +564,145 @@ impl Agent {
                             }
                         }
 
+                        planned.push(
+                            PlannedCall::new(
+                                self.tools(),
+                                &tc.id,
+                                &tc.name,
+                                &tc.arguments,
+                                depends_on,
+                            )
+                            .await,
+                        );
                         let preflight_idx = preflight.len();
                         preflight.push((tc.clone(), PreflightOutcome::Runnable));
                         runnable.push((preflight_idx, tc));
                     }
 
                     // === Phase 2: Parallel execution ===
-                    // Execute runnable tools and slot results back by preflight
-                    // index so Phase 3 can iterate in original order.
-                    let mut exec_results: Vec<Option<Result<String, Error>>> =
+                    // Execute runnable tools wave by wave: tools in a wave are
+                    // independent and run in parallel, while a tool that
+                    // depends on another waits for a later wave. Results are
+                    // slotted back by preflight index so Phase 3 can iterate
+                    // in original order.
+                    let mut exec_results: Vec<Option<Result<ChatToolOutput, Error>>> =
                         (0..preflight.len()).map(|_| None).collect();
 
-                    if runnable.len() <= 1 {
-                        // Single tool (or none): execute inline
-                        for (pf_idx, tc) in &runnable {
-                            let _ = self
-                                .channels
-                                .send_status(
-                                    &message.channel,
-                                    StatusUpdate::ToolStarted {
-                                        name: tc.name.clone(),
-                                    },
-                                    &message.metadata,
-                                )
-                                .await;
-
-                            let result = self
-                                .execute_chat_tool(&tc.name, &tc.arguments, &job_ctx)
-                                .await;
-
-                            let _ = self
-                                .channels
-                                .send_status(
-                                    &message.channel,
-                                    StatusUpdate::ToolCompleted {
-                                        name: tc.name.clone(),
-                                        success: result.is_ok(),
-                                    },
-                                    &message.metadata,
-                                )
-                                .await;
-
-                            exec_results[*pf_idx] = Some(result);
-                        }
-                    } else {
-                        // Multiple tools: execute in parallel via JoinSet
-                        let mut join_set = JoinSet::new();
-
-                        for (pf_idx, tc) in &runnable {
-                            let pf_idx = *pf_idx;
-                            let tools = self.tools().clone();
-                            let safety = self.safety().clone();
-                            let channels = self.channels.clone();
-                            let job_ctx = job_ctx.clone();
-                            let tc = tc.clone();
-                            let channel = message.channel.clone();
-                            let metadata = message.metadata.clone();
-
-                            join_set.spawn(async move {
-                                let _ = channels
+                    for wave in execution_waves(&planned) {
+                        let batch: Vec<&(usize, crate::llm::ToolCall)> =
+                            wave.iter().map(|&i| &runnable[i]).collect();
+                        if batch.len() <= 1 {
+                            // Single tool: execute inline
+                            for (pf_idx, tc) in batch {
+                                let _ = self
+                                    .channels
                                     .send_status(
-                                        &channel,
+                                        &message.channel,
                                         StatusUpdate::ToolStarted {
                                             name: tc.name.clone(),
                                         },
-                                        &metadata,
+                                        &message.metadata,
                                     )
                                     .await;
 
-                                let result = execute_chat_tool_standalone(
-                                    &tools,
-                                    &safety,
-                                    &tc.name,
-                                    &tc.arguments,
-                                    &job_ctx,
-                                )
-                                .await;
+                                let result = self
+                                    .execute_chat_tool(&tc.name, &tc.arguments, &job_ctx)
+                                    .await;
 
-                                let _ = channels
-                                    .send_status(
-                                        &channel,
-                                        StatusUpdate::ToolCompleted {
-                                            name: tc.name.clone(),
-                                            success: result.is_ok(),
-                                        },
-                                        &metadata,
+                                let completed = StatusUpdate::ToolCompleted {
+                                    name: tc.name.clone(),
+                                    success: result.is_ok(),
+                                };
+                                let _ = self.channels.react_to_status(message, &completed).await;
+                                let _ = self
+                                    .channels
+                                    .send_status(&message.channel, completed, &message.metadata)
+                                    .await;
+
+                                exec_results[*pf_idx] = Some(result);
+                            }
+                        } else {
+                            // Multiple tools: execute in parallel via JoinSet
+                            let mut join_set = JoinSet::new();
+
+                            for (pf_idx, tc) in &batch {
+                                let pf_idx = *pf_idx;
+                                let tools = self.tools().clone();
+                                let safety = self.safety().clone();
+                                let channels = self.channels.clone();
+                                let job_ctx = job_ctx.clone();
+                                let tc = tc.clone();
+                                let message = message.clone();
+
+                                join_set.spawn(async move {
+                                    let _ = channels
+                                        .send_status(
+                                            &message.channel,
+                                            StatusUpdate::ToolStarted {
+                                                name: tc.name.clone(),
+                                            },
+                                            &message.metadata,
+                                        )
+                                        .await;
+
+                                    let result = execute_chat_tool_standalone(
+                                        &tools,
+                                        &safety,
+                                        &tc.name,
+                                        &tc.arguments,
+                                        &job_ctx,
                                     )
                                     .await;
 
-                                (pf_idx, result)
-                            });
-                        }
+                                    let completed = StatusUpdate::ToolCompleted {
+                                        name: tc.name.clone(),
+                                        success: result.is_ok(),
+                                    };
+                                    let _ = channels.react_to_status(&message, &completed).await;
+                                    let _ = channels
+                                        .send_status(&message.channel, completed, &message.metadata)
+                                        .await;
 
-                        while let Some(join_result) = join_set.join_next().await {
-                            match join_result {
-                                Ok((pf_idx, result)) => {
-                                    exec_results[pf_idx] = Some(result);
-                                }
-                                Err(e) => {
-                                    if e.is_panic() {
-                                        tracing::error!("Chat tool execution task panicked: {}", e);
-                                    } else {
-                                        tracing::error!(
-                                            "Chat tool execution task cancelled: {}",
-                                            e
-                                        );
+                                    (pf_idx, result)
+                                });
+                            }
+
+                            while let Some(join_result) = join_set.join_next().await {
+                                match join_result {
+                                    Ok((pf_idx, result)) => {
+                                        exec_results[pf_idx] = Some(result);
+                                    }
+                                    Err(e) => {
+                                        if e.is_panic() {
+                                            tracing::error!(
+                                                "Chat tool execution task panicked: {}",
+                                                e
+                                            );
+                                        } else {
+                                            tracing::error!(
+                                                "Chat tool execution task cancelled: {}",
+                                                e
+                                            );
+                                        }
                                     }
                                 }
                             }
-                        }
 
-                        // Fill panicked slots with error results
-                        for (runnable_idx, (pf_idx, tc)) in runnable.iter().enumerate() {
-                            if exec_results[*pf_idx].is_none() {
-                                tracing::error!(
-                                    tool = %tc.name,
-                                    runnable_idx,
-                                    "Filling failed task slot with error"
-                                );
-                                exec_results[*pf_idx] =
-                                    Some(Err(crate::error::ToolError::ExecutionFailed {
-                                        name: tc.name.clone(),
-                                        reason: "Task failed during execution".to_string(),
-                                    }
-                                    .into()));
+                            // Fill panicked slots with error results
+                            for (runnable_idx, (pf_idx, tc)) in batch.iter().enumerate() {
+                                if exec_results[*pf_idx].is_none() {
+                                    tracing::error!(
+                                        tool = %tc.name,
+                                        runnable_idx,
+                                        "Filling failed task slot with error"
+                                    );
+                                    exec_results[*pf_idx] =
+                                        Some(Err(crate::error::ToolError::ExecutionFailed {
+                                            name: tc.name.clone(),
+                                            reason: "Task failed during execution".to_string(),
+                                        }
+                                        .into()));
+                                }
                             }
                         }
                     }
@@ -551,14 +730,17 @@ impl Agent {
                             }
                             PreflightOutcome::Runnable => {
                                 // Retrieve the execution result for this slot
-                                let tool_result =
+                                let (tool_result, partial) = split_partial(
                                     exec_results[pf_idx].take().unwrap_or_else(|| {
                                         Err(crate::error::ToolError::ExecutionFailed {
                                             name: tc.name.clone(),
                                             reason: "No result available".to_string(),
                                         }
                                         .into())
-                                    });
+                                    }),
+                                );
+
+                                loop_guard.record_outcome(&tc.name, &tool_result);
 
                                 // Send ToolResult preview
                                 if let Ok(ref output) = tool_result
@@ -583,14 +765,7 @@ impl Agent {
                                     if let Some(thread) = sess.threads.get_mut(&thread_id)
                                         && let Some(turn) = thread.last_turn_mut()
                                     {
-                                        match &tool_result {
-                                            Ok(output) => {
-                                                turn.record_tool_result(serde_json::json!(output));
-                                            }
-                                            Err(e) => {
-                                                turn.record_tool_error(e.to_string());
-                                            }
-                                        }
+                                        turn.record_tool_outcome(&tool_result, partial);
                                     }
                                 }
 
@@ -624,18 +799,8 @@ impl Agent {
                                 }
 
                                 // Sanitize and add tool result to context
-                                let result_content = match tool_result {
-                                    Ok(output) => {
-                                        let sanitized =
-                                            self.safety().sanitize_tool_output(&tc.name, &output);
-                                        self.safety().wrap_for_llm(
-                                            &tc.name,
-                                            &sanitized.content,
-                                            sanitized.was_modified,
-                                        )
-                                    }
-                                    Err(e) => format!("Error: {}", e),
-                                };
+                                let result_content =
+                                    self.tool_result_content(&tc.name, &tool_result).await;
 
                                 context_messages.push(ChatMessage::tool_result(
                                     &tc.id,
@@ -663,6 +828,16 @@ impl Agent {
                             deferred_tool_calls: tool_calls[approval_idx + 1..].to_vec(),
                         };
 
+                        // Keep the counts for when the turn resumes.
+                        {
+                            let mut sess = session.lock().await;
+                            if let Some(thread) = sess.threads.get_mut(&thread_id)
+                                && let Some(turn) = thread.last_turn_mut()
+                            {
+                                turn.loop_guard = loop_guard;
+                            }
+                        }
+
                         return Ok(AgenticLoopResult::NeedApproval { pending });
                     }
                 }
@@ -676,9 +851,100 @@ impl Agent {
         tool_name: &str,
         params: &serde_json::Value,
         job_ctx: &JobContext,
-    ) -> Result<String, Error> {
+    ) -> Result<ChatToolOutput, Error> {
         execute_chat_tool_standalone(self.tools(), self.safety(), tool_name, params, job_ctx).await
     }
+
+    /// Whether read-only mode is on for this session. `AgentConfig::read_only`
+    /// always wins; otherwise the session's own setting applies.
+    pub(super) async fn read_only_mode(&self, session: &Arc<Mutex<Session>>) -> bool {
+        self.config.read_only || session.lock().await.read_only.unwrap_or(false)
+    }
+
+    /// Turn read-only mode on or off for the session. Returns `false`, and
+    /// changes nothing, when asked to turn off a mode the config imposes.
+    pub(super) async fn set_read_only(&self, session: &Arc<Mutex<Session>>, enabled: bool) -> bool {
+        if !enabled && self.config.read_only {
+            return false;
+        }
+        session.lock().await.read_only = Some(enabled);
+        true
+    }
+
+    /// Tool iteration limit for the session: its own override if set,
+    /// otherwise `AgentConfig::max_tool_iterations`.
+    pub(super) async fn max_tool_iterations(&self, session: &Arc<Mutex<Session>>) -> usize {
+        session
+            .lock()
+            .await
+            .max_tool_iterations
+            .unwrap_or(self.config.max_tool_iterations)
+    }
+
+    /// Highest tool iteration limit a session may pick: the admin-set
+    /// `max_session_tool_iterations`, else the configured limit.
+    pub(super) fn session_iteration_ceiling(&self) -> usize {
+        self.config
+            .max_session_tool_iterations
+            .unwrap_or(self.config.max_tool_iterations)
+    }
+
+    /// Override the tool iteration limit for the session, or go back to the
+    /// configured one with `None`. Takes effect on the next turn. Returns
+    /// `false`, and changes nothing, when the limit is above
+    /// [`Self::session_iteration_ceiling`].
+    pub(super) async fn set_max_tool_iterations(
+        &self,
+        session: &Arc<Mutex<Session>>,
+        limit: Option<usize>,
+    ) -> bool {
+        if limit.is_some_and(|limit| limit > self.session_iteration_ceiling()) {
+            return false;
+        }
+        session.lock().await.max_tool_iterations = limit;
+        true
+    }
+
+    /// Redacted [`ReasoningTrace`] status for one tool-calling step, or
+    /// `None` when reasoning traces are turned off.
+    pub(super) fn reasoning_trace_status(
+        &self,
+        iteration: usize,
+        content: Option<&str>,
+        tool_calls: &[ToolCall],
+    ) -> Option<StatusUpdate> {
+        if !self.config.reasoning_trace {
+            return None;
+        }
+        let detector = self.safety().leak_detector();
+        let rationale =
+            content.map(|text| redact_str(detector, text).unwrap_or_else(|| text.to_string()));
+        let tools = tool_calls
+            .iter()
+            .map(|tc| {
+                let mut arguments = tc.arguments.clone();
+                redact_value(detector, &mut arguments);
+                TracedToolCall {
+                    name: tc.name.clone(),
+                    arguments,
+                }
+            })
+            .collect();
+        Some(StatusUpdate::ReasoningTrace(ReasoningTrace {
+            iteration,
+            rationale,
+            tools,
+        }))
+    }
+
+    /// Build the tool-result message content for a finished tool call.
+    pub(super) async fn tool_result_content(
+        &self,
+        tool_name: &str,
+        result: &Result<String, Error>,
+    ) -> String {
+        tool_result_content_standalone(self.tools(), self.safety(), tool_name, result).await
+    }
 }
 
 /// Execute a chat tool without requiring `&Agent`.
@@ -692,7 +958,7 @@ pub(super) async fn execute_chat_tool_standalone(
     tool_name: &str,
     params: &serde_json::Value,
     job_ctx: &crate::context::JobContext,
-) -> Result<String, Error> {
+) -> Result<ChatToolOutput, Error> {
     let tool = tools
         .get(tool_name)
         .await
@@ -700,15 +966,16 @@ pub(super) async fn execute_chat_tool_standalone(
             name: tool_name.to_string(),
         })?;
 
+    // Enforce the tool's rate limit before doing any work; a throttled call
+    // comes back to the model as a tool error it can plan around.
+    tools
+        .check_rate_limit(&job_ctx.user_id, tool.as_ref())
+        .await?;
+
     // Validate tool parameters
     let validation = safety.validator().validate_tool_params(params);
-    if !validation.is_valid {
-        let details = validation
-            .errors
-            .iter()
-            .map(|e| format!("{}: {}", e.field, e.message))
-            .collect::<Vec<_>>()
-            .join("; ");
+    if !validation.is_valid() {
+        let details = validation.describe_errors();
         return Err(crate::error::ToolError::InvalidParameters {
             name: tool_name.to_string(),
             reason: format!("Invalid tool parameters: {}", details),
@@ -730,6 +997,11 @@ pub(super) async fn execute_chat_tool_standalone(
     })
     .await;
     let elapsed = start.elapsed();
+    crate::observability::prometheus::record_tool_call(
+        tool_name,
+        elapsed,
+        matches!(result, Ok(Ok(_))),
+    );
 
     match &result {
         Ok(Ok(output)) => {
@@ -765,20 +1037,134 @@ pub(super) async fn execute_chat_tool_standalone(
             name: tool_name.to_string(),
             timeout,
         })?
-        .map_err(|e| crate::error::ToolError::ExecutionFailed {
-            name: tool_name.to_string(),
-            reason: e.to_string(),
+        .map_err(|e| match e {
+            crate::tools::ToolError::Timeout(timeout) => crate::error::ToolError::Timeout {
+                name: tool_name.to_string(),
+                timeout,
+            },
+            crate::tools::ToolError::RateLimited(retry_after) => {
+                crate::error::ToolError::RateLimited {
+                    name: tool_name.to_string(),
+                    retry_after,
+                }
+            }
+            other => crate::error::ToolError::ExecutionFailed {
+                name: tool_name.to_string(),
+                reason: other.to_string(),
+            },
         })?;
 
-    serde_json::to_string_pretty(&result.result).map_err(|e| {
+    let content = serde_json::to_string_pretty(&result.result).map_err(|e| {
         crate::error::ToolError::ExecutionFailed {
             name: tool_name.to_string(),
             reason: format!("Failed to serialize result: {}", e),
         }
-        .into()
+    })?;
+    Ok(ChatToolOutput {
+        content,
+        partial: result.is_partial(),
     })
 }
 
+/// A chat tool call's serialized output.
+#[derive(Debug)]
+pub(super) struct ChatToolOutput {
+    /// The tool result, serialized for the LLM.
+    pub content: String,
+    /// Whether the tool could only partially produce the result.
+    pub partial: bool,
+}
+
+/// Split a chat tool result into the text passed on to the LLM and whether
+/// the turn should record it as partial.
+pub(super) fn split_partial(
+    result: Result<ChatToolOutput, Error>,
+) -> (Result<String, Error>, bool) {
+    match result {
+        Ok(output) => (Ok(output.content), output.partial),
+        Err(e) => (Err(e), false),
+    }
+}
+
+/// Sanitize a tool result and wrap it for the LLM.
+///
+/// Results from tools that declare a result schema are checked against it
+/// and attached as JSON, unless sanitization had to alter them; everything
+/// else is passed as text.
+pub(super) async fn tool_result_content_standalone(
+    tools: &crate::tools::ToolRegistry,
+    safety: &crate::safety::SafetyLayer,
+    tool_name: &str,
+    result: &Result<String, Error>,
+) -> String {
+    let output = match result {
+        Ok(output) => output,
+        Err(Error::Tool(crate::error::ToolError::RateLimited {
+            name,
+            retry_after: Some(retry_after),
+        })) => {
+            return format!(
+                "Error: Tool {} is throttled (rate limit reached). Try again in {} seconds, \
+                 or continue without it.",
+                name,
+                retry_after.as_secs().max(1)
+            );
+        }
+        Err(e) => return format!("Error: {}", e),
+    };
+
+    let sanitized = safety.sanitize_tool_output(tool_name, output);
+    wrap_tool_output(tools, safety, tool_name, output, &sanitized).await
+}
+
+/// Wrap a sanitized tool output for the LLM, checking it against the tool's
+/// declared result schema when sanitization left it untouched.
+pub(super) async fn wrap_tool_output(
+    tools: &crate::tools::ToolRegistry,
+    safety: &crate::safety::SafetyLayer,
+    tool_name: &str,
+    output: &str,
+    sanitized: &crate::safety::SanitizedOutput,
+) -> String {
+    if !sanitized.was_modified
+        && let Some(schema) = tools.get(tool_name).await.and_then(|t| t.result_schema())
+        && let Ok(value) = serde_json::from_str::<serde_json::Value>(output)
+    {
+        let errors = crate::tools::validate_result(&schema, &value);
+        if !errors.is_empty() {
+            tracing::warn!(
+                tool = %tool_name,
+                errors = ?errors,
+                "Tool result does not match its declared schema"
+            );
+        }
+        return safety.wrap_structured_for_llm(tool_name, &value, &errors);
+    }
+
+    safety.wrap_for_llm(tool_name, &sanitized.content, sanitized.was_modified)
+}
+
+/// Reject a call to a tool that may change state while read-only mode is on.
+///
+/// Unknown tools pass here and fail with `NotFound` when executed.
+pub(super) async fn check_read_only(
+    tools: &crate::tools::ToolRegistry,
+    read_only: bool,
+    tool_name: &str,
+) -> Result<(), Error> {
+    if read_only
+        && let Some(tool) = tools.get(tool_name).await
+        && !tool.is_read_only()
+    {
+        return Err(crate::error::ToolError::Disabled {
+            name: tool_name.to_string(),
+            reason: "not available in read-only mode".to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
 /// Parsed auth result fields for emitting StatusUpdate::AuthRequired.
 pub(super) struct ParsedAuthData {
     pub(super) auth_url: Option<String>,
@@ -889,6 +1275,7 @@ mod tests {
 
     use async_trait::async_trait;
     use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
 
     use crate::agent::agent_loop::{Agent, AgentDeps};
     use crate::agent::cost_guard::{CostGuard, CostGuardConfig};
@@ -948,16 +1335,26 @@ mod tests {
 
     /// Build a minimal `Agent` for unit testing (no DB, no workspace, no extensions).
     fn make_test_agent() -> Agent {
+        make_test_agent_with(None, CostGuardConfig::default())
+    }
+
+    fn make_test_agent_with(
+        cheap_llm: Option<Arc<dyn LlmProvider>>,
+        cost_guard: CostGuardConfig,
+    ) -> Agent {
+        make_test_agent_with_llm(Arc::new(StaticLlmProvider), cheap_llm, cost_guard)
+    }
+
+    fn make_test_agent_with_llm(
+        llm: Arc<dyn LlmProvider>,
+        cheap_llm: Option<Arc<dyn LlmProvider>>,
+        cost_guard: CostGuardConfig,
+    ) -> Agent {
         let deps = AgentDeps {
             store: None,
-            llm: Arc::new(StaticLlmProvider),
-            cheap_llm: None,
-            safety: Arc::new(SafetyLayer::new(&SafetyConfig {
-                max_output_length: 100_000,
-                injection_check_enabled: true,
-                #[cfg(feature = "zkproxy")]
-                zkproxy: crate::zkproxy::ZkProxyConfig::default(),
-            })),
+            llm,
+            cheap_llm,
+            safety: Arc::new(SafetyLayer::new(&SafetyConfig::default())),
             tools: Arc::new(ToolRegistry::new()),
             workspace: None,
             extension_manager: None,
@@ -965,7 +1362,7 @@ mod tests {
             skill_catalog: None,
             skills_config: SkillsConfig::default(),
             hooks: Arc::new(HookRegistry::new()),
-            cost_guard: Arc::new(CostGuard::new(CostGuardConfig::default())),
+            cost_guard: Arc::new(CostGuard::new(cost_guard)),
         };
 
         Agent::new(
@@ -981,8 +1378,15 @@ mod tests {
                 allow_local_tools: false,
                 max_cost_per_day_cents: None,
                 max_actions_per_hour: None,
+                auto_downgrade_at_budget_fraction: None,
                 max_tool_iterations: 50,
+                max_session_tool_iterations: None,
                 auto_approve_tools: false,
+                transcript_path: None,
+                sampling: crate::llm::SamplingProfiles::default(),
+                read_only: false,
+                tool_rate_limits: std::collections::HashMap::new(),
+                reasoning_trace: false,
             },
             deps,
             Arc::new(ChannelManager::new()),
@@ -1000,6 +1404,53 @@ mod tests {
         let _agent = make_test_agent();
     }
 
+    #[tokio::test]
+    async fn test_budget_pressure_switches_to_cheap_llm() {
+        let cheap: Arc<dyn LlmProvider> =
+            Arc::new(crate::testing::StubLlm::new("ok").with_model_name("cheap-mock"));
+        let agent = make_test_agent_with(
+            Some(cheap),
+            CostGuardConfig {
+                max_cost_per_day_cents: Some(100),
+                max_actions_per_hour: None,
+                auto_downgrade_at_budget_fraction: Some(0.5),
+            },
+        );
+        assert_eq!(agent.active_llm().await.model_name(), "static-mock");
+
+        // 40 cents: still under half the budget.
+        let rate = Some((dec!(0.0001), Decimal::ZERO));
+        agent
+            .cost_guard()
+            .record_llm_call("static-mock", 4_000, 0, rate)
+            .await;
+        assert_eq!(agent.active_llm().await.model_name(), "static-mock");
+
+        // 60 cents: past the threshold.
+        agent
+            .cost_guard()
+            .record_llm_call("static-mock", 2_000, 0, rate)
+            .await;
+        assert_eq!(agent.active_llm().await.model_name(), "cheap-mock");
+    }
+
+    #[tokio::test]
+    async fn test_budget_pressure_without_cheap_llm_keeps_primary() {
+        let agent = make_test_agent_with(
+            None,
+            CostGuardConfig {
+                max_cost_per_day_cents: Some(100),
+                max_actions_per_hour: None,
+                auto_downgrade_at_budget_fraction: Some(0.5),
+            },
+        );
+        agent
+            .cost_guard()
+            .record_llm_call("static-mock", 9_000, 0, Some((dec!(0.0001), Decimal::ZERO)))
+            .await;
+        assert_eq!(agent.active_llm().await.model_name(), "static-mock");
+    }
+
     #[test]
     fn test_auto_approved_tool_is_respected() {
         let _agent = make_test_agent();
@@ -1077,11 +1528,13 @@ mod tests {
                     id: "call_2".to_string(),
                     name: "http".to_string(),
                     arguments: serde_json::json!({"url": "https://example.com"}),
+                    call_id: None,
                 },
                 ToolCall {
                     id: "call_3".to_string(),
                     name: "echo".to_string(),
                     arguments: serde_json::json!({"message": "done"}),
+                    call_id: None,
                 },
             ],
         };
@@ -1199,10 +1652,8 @@ mod tests {
         registry.register(std::sync::Arc::new(EchoTool)).await;
 
         let safety = SafetyLayer::new(&SafetyConfig {
-            max_output_length: 100_000,
             injection_check_enabled: false,
-            #[cfg(feature = "zkproxy")]
-            zkproxy: crate::zkproxy::ZkProxyConfig::default(),
+            ..Default::default()
         });
 
         let job_ctx = JobContext::with_user("test", "chat", "test session");
@@ -1218,7 +1669,61 @@ mod tests {
 
         assert!(result.is_ok());
         let output = result.unwrap();
-        assert!(output.contains("hello"));
+        assert!(output.content.contains("hello"));
+        assert!(!output.partial);
+    }
+
+    #[tokio::test]
+    async fn test_execute_chat_tool_standalone_throttles_rate_limited_tool() {
+        use crate::clock::MockClock;
+        use crate::config::SafetyConfig;
+        use crate::context::JobContext;
+        use crate::safety::SafetyLayer;
+        use crate::tools::ToolRateLimitConfig;
+
+        let clock = Arc::new(MockClock::new());
+        let registry = ToolRegistry::new()
+            .with_rate_limits(std::collections::HashMap::from([(
+                "echo".to_string(),
+                ToolRateLimitConfig::new(1, 100),
+            )]))
+            .with_clock(clock.clone());
+        registry.register_builtin_tools();
+
+        let safety = SafetyLayer::new(&SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        });
+        let job_ctx = JobContext::with_user("test", "chat", "test session");
+        let echo = serde_json::json!({"message": "hello"});
+
+        let first =
+            super::execute_chat_tool_standalone(&registry, &safety, "echo", &echo, &job_ctx).await;
+        assert!(first.is_ok());
+
+        let (second, _) = super::split_partial(
+            super::execute_chat_tool_standalone(&registry, &safety, "echo", &echo, &job_ctx).await,
+        );
+        let content =
+            super::tool_result_content_standalone(&registry, &safety, "echo", &second).await;
+        assert!(content.contains("throttled"), "{}", content);
+        assert!(content.contains("60 seconds"), "{}", content);
+
+        // Tools without a limit are unaffected.
+        let time = super::execute_chat_tool_standalone(
+            &registry,
+            &safety,
+            "time",
+            &serde_json::json!({"operation": "now"}),
+            &job_ctx,
+        )
+        .await;
+        assert!(time.is_ok());
+
+        clock.advance(Duration::from_secs(60));
+        let later =
+            super::execute_chat_tool_standalone(&registry, &safety, "echo", &echo, &job_ctx).await;
+        assert!(later.is_ok());
     }
 
     #[tokio::test]
@@ -1230,10 +1735,8 @@ mod tests {
 
         let registry = ToolRegistry::new();
         let safety = SafetyLayer::new(&SafetyConfig {
-            max_output_length: 100_000,
             injection_check_enabled: false,
-            #[cfg(feature = "zkproxy")]
-            zkproxy: crate::zkproxy::ZkProxyConfig::default(),
+            ..Default::default()
         });
         let job_ctx = JobContext::with_user("test", "chat", "test session");
 
@@ -1249,6 +1752,103 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Tool with a declared result schema, for result-content tests.
+    struct WeatherTool;
+
+    #[async_trait]
+    impl crate::tools::Tool for WeatherTool {
+        fn name(&self) -> &str {
+            "weather"
+        }
+
+        fn description(&self) -> &str {
+            "Current weather for a city"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+
+        fn result_schema(&self) -> Option<serde_json::Value> {
+            Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string"},
+                    "temp_c": {"type": "number"}
+                },
+                "required": ["city", "temp_c"]
+            }))
+        }
+
+        async fn execute(
+            &self,
+            _params: serde_json::Value,
+            _ctx: &crate::context::JobContext,
+        ) -> Result<crate::tools::ToolOutput, crate::tools::ToolError> {
+            Ok(crate::tools::ToolOutput::success(
+                serde_json::json!({"city": "Lisbon", "temp_c": 21.5}),
+                Duration::from_millis(1),
+            ))
+        }
+    }
+
+    fn test_safety() -> SafetyLayer {
+        SafetyLayer::new(&SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_structured_result_attached_as_json() {
+        let registry = ToolRegistry::new();
+        registry.register(Arc::new(WeatherTool)).await;
+        let safety = test_safety();
+
+        let result: Result<String, Error> = Ok(serde_json::to_string_pretty(
+            &serde_json::json!({"city": "Lisbon", "temp_c": 21.5}),
+        )
+        .unwrap());
+        let content =
+            super::tool_result_content_standalone(&registry, &safety, "weather", &result).await;
+
+        assert!(content.contains("format=\"json\" schema_valid=\"true\""));
+        assert!(content.contains(r#"{"city":"Lisbon","temp_c":21.5}"#));
+        assert!(!content.contains("does not match"));
+    }
+
+    #[tokio::test]
+    async fn test_structured_result_schema_mismatch_flagged() {
+        let registry = ToolRegistry::new();
+        registry.register(Arc::new(WeatherTool)).await;
+        let safety = test_safety();
+
+        let result: Result<String, Error> =
+            Ok(serde_json::json!({"city": "Lisbon", "temp_c": "warm"}).to_string());
+        let content =
+            super::tool_result_content_standalone(&registry, &safety, "weather", &result).await;
+
+        assert!(content.contains("schema_valid=\"false\""));
+        assert!(content.contains("Result does not match the declared schema"));
+        assert!(content.contains("$.temp_c: expected number, got string"));
+    }
+
+    #[tokio::test]
+    async fn test_result_without_schema_stays_text() {
+        let registry = ToolRegistry::new();
+        registry
+            .register(Arc::new(crate::tools::builtin::EchoTool))
+            .await;
+        let safety = test_safety();
+
+        let result: Result<String, Error> = Ok("\"hello\"".to_string());
+        let content =
+            super::tool_result_content_standalone(&registry, &safety, "echo", &result).await;
+
+        assert!(content.starts_with("<tool_output name=\"echo\" sanitized=\"false\">"));
+        assert!(!content.contains("format=\"json\""));
+    }
+
     // ---- compact_messages_for_retry tests ----
 
     use super::compact_messages_for_retry;
@@ -1269,6 +1869,7 @@ mod tests {
                     id: "call_1".to_string(),
                     name: "echo".to_string(),
                     arguments: serde_json::json!({"message": "hi"}),
+                    call_id: None,
                 }],
             ),
             ChatMessage::tool_result("call_1", "echo", "hi"),
@@ -1361,11 +1962,13 @@ mod tests {
                         id: "c1".to_string(),
                         name: "http".to_string(),
                         arguments: serde_json::json!({}),
+                        call_id: None,
                     },
                     ToolCall {
                         id: "c2".to_string(),
                         name: "echo".to_string(),
                         arguments: serde_json::json!({}),
+                        call_id: None,
                     },
                 ],
             ),
@@ -1399,6 +2002,7 @@ mod tests {
                     id: "c1".to_string(),
                     name: "echo".to_string(),
                     arguments: serde_json::json!({}),
+                    call_id: None,
                 }],
             ),
             ChatMessage::tool_result("c1", "echo", "done"),
@@ -1422,4 +2026,423 @@ mod tests {
             .count();
         assert_eq!(nudge_count, 1);
     }
+
+    /// LLM that makes the same call to `tool` whenever it is offered tools
+    /// and answers with text otherwise, counting every call.
+    struct ToolLoopLlm {
+        tool: &'static str,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ToolLoopLlm {
+        fn calling(tool: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                tool,
+                calls: std::sync::atomic::AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for ToolLoopLlm {
+        fn model_name(&self) -> &str {
+            "tool-loop-mock"
+        }
+
+        fn cost_per_token(&self) -> (Decimal, Decimal) {
+            (Decimal::ZERO, Decimal::ZERO)
+        }
+
+        async fn complete(
+            &self,
+            _request: CompletionRequest,
+        ) -> Result<CompletionResponse, crate::error::LlmError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(CompletionResponse {
+                content: "Here is what I found so far.".to_string(),
+                input_tokens: 0,
+                output_tokens: 0,
+                finish_reason: FinishReason::Stop,
+            })
+        }
+
+        async fn complete_with_tools(
+            &self,
+            _request: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, crate::error::LlmError> {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ToolCompletionResponse {
+                content: None,
+                tool_calls: vec![ToolCall {
+                    id: format!("call_{n}"),
+                    name: self.tool.to_string(),
+                    arguments: serde_json::json!({"message": "again"}),
+                    call_id: None,
+                }],
+                input_tokens: 0,
+                output_tokens: 0,
+                finish_reason: FinishReason::ToolUse,
+            })
+        }
+    }
+
+    /// Run one chat turn against `llm` with the built-in tools registered.
+    async fn run_tool_loop(
+        llm: Arc<ToolLoopLlm>,
+        max_tool_iterations: Option<usize>,
+    ) -> super::AgenticLoopResult {
+        let agent = make_test_agent_with_llm(llm, None, CostGuardConfig::default());
+        agent.tools().register_builtin_tools();
+
+        let session = Arc::new(tokio::sync::Mutex::new(Session::new("user")));
+        let thread_id = session.lock().await.create_thread().id;
+        assert!(
+            agent
+                .set_max_tool_iterations(&session, max_tool_iterations)
+                .await
+        );
+
+        let message = crate::channels::IncomingMessage::new("test", "user", "keep going");
+        agent
+            .run_agentic_loop(
+                &message,
+                session,
+                thread_id,
+                vec![crate::llm::ChatMessage::user("keep going")],
+            )
+            .await
+            .expect("loop ends cleanly")
+    }
+
+    #[tokio::test]
+    async fn test_session_iteration_limit_overrides_config() {
+        let agent = make_test_agent();
+        let session = Arc::new(tokio::sync::Mutex::new(Session::new("user")));
+        assert_eq!(agent.max_tool_iterations(&session).await, 50);
+
+        assert!(agent.set_max_tool_iterations(&session, Some(5)).await);
+        assert_eq!(agent.max_tool_iterations(&session).await, 5);
+
+        assert!(agent.set_max_tool_iterations(&session, None).await);
+        assert_eq!(agent.max_tool_iterations(&session).await, 50);
+    }
+
+    #[tokio::test]
+    async fn test_session_iteration_limit_capped_by_config() {
+        let mut agent = make_test_agent();
+        let session = Arc::new(tokio::sync::Mutex::new(Session::new("user")));
+
+        assert!(!agent.set_max_tool_iterations(&session, Some(51)).await);
+        assert_eq!(agent.max_tool_iterations(&session).await, 50);
+        assert!(agent.set_max_tool_iterations(&session, Some(50)).await);
+
+        agent.config.max_session_tool_iterations = Some(200);
+        assert!(agent.set_max_tool_iterations(&session, Some(200)).await);
+        assert_eq!(agent.max_tool_iterations(&session).await, 200);
+        assert!(!agent.set_max_tool_iterations(&session, Some(201)).await);
+    }
+
+    #[tokio::test]
+    async fn test_session_cannot_lift_configured_read_only() {
+        let mut agent = make_test_agent();
+        let session = Arc::new(tokio::sync::Mutex::new(Session::new("user")));
+        assert!(!agent.read_only_mode(&session).await);
+
+        assert!(agent.set_read_only(&session, true).await);
+        assert!(agent.read_only_mode(&session).await);
+        assert!(agent.set_read_only(&session, false).await);
+        assert!(!agent.read_only_mode(&session).await);
+
+        agent.config.read_only = true;
+        assert!(agent.read_only_mode(&session).await);
+        assert!(!agent.set_read_only(&session, false).await);
+        // Even a stored override can't lift the configured mode.
+        session.lock().await.read_only = Some(false);
+        assert!(agent.read_only_mode(&session).await);
+    }
+
+    #[tokio::test]
+    async fn test_hitting_iteration_limit_gives_up() {
+        use super::{AgenticLoopResult, GiveUpReason};
+
+        let llm = ToolLoopLlm::calling("echo");
+        let result = run_tool_loop(llm.clone(), Some(3)).await;
+
+        let AgenticLoopResult::GaveUp { reason, partial } = result else {
+            panic!("expected the turn to give up at the iteration limit");
+        };
+        assert_eq!(reason, GiveUpReason::IterationLimit { limit: 3 });
+        assert_eq!(partial.as_deref(), Some("Here is what I found so far."));
+        // Two tool-calling iterations, then the forced text-only one; the
+        // configured limit of 50 is ignored.
+        assert_eq!(llm.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_identical_tool_calls_give_up_as_loop() {
+        use super::{AgenticLoopResult, GiveUpReason};
+
+        let llm = ToolLoopLlm::calling("echo");
+        let result = run_tool_loop(llm.clone(), None).await;
+
+        let AgenticLoopResult::GaveUp { reason, partial } = result else {
+            panic!("expected the turn to give up once the loop was detected");
+        };
+        assert_eq!(
+            reason,
+            GiveUpReason::LoopDetected {
+                tool: "echo".to_string()
+            }
+        );
+        assert_eq!(partial.as_deref(), Some("Here is what I found so far."));
+        // Two calls run, the third is rejected, the fourth (ignoring the
+        // rejection) is rejected too, then the fifth request has no tools.
+        // Far below the configured limit of 50.
+        assert_eq!(llm.calls(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_repeats_before_an_approval_count_after_it() {
+        use crate::agent::session::PendingApproval;
+        use crate::agent::submission::SubmissionResult;
+
+        let llm = ToolLoopLlm::calling("echo");
+        let agent = make_test_agent_with_llm(llm.clone(), None, CostGuardConfig::default());
+        agent.tools().register_builtin_tools();
+
+        // The model already made this call twice in the turn; the second one
+        // paused for approval.
+        let arguments = serde_json::json!({"message": "again"});
+        let session = Arc::new(tokio::sync::Mutex::new(Session::new("user")));
+        let request_id = uuid::Uuid::new_v4();
+        let thread_id = {
+            let mut sess = session.lock().await;
+            let thread = sess.create_thread();
+            let turn = thread.start_turn("keep going");
+            turn.loop_guard.record("echo", &arguments);
+            turn.loop_guard.record("echo", &arguments);
+            thread.await_approval(PendingApproval {
+                request_id,
+                tool_name: "echo".to_string(),
+                parameters: arguments.clone(),
+                description: "Echo a message".to_string(),
+                tool_call_id: "call_0".to_string(),
+                context_messages: vec![crate::llm::ChatMessage::user("keep going")],
+                deferred_tool_calls: Vec::new(),
+            });
+            thread.id
+        };
+
+        let message = crate::channels::IncomingMessage::new("test", "user", "yes");
+        let result = agent
+            .process_approval(&message, session, thread_id, Some(request_id), true, false)
+            .await
+            .expect("approval is processed");
+
+        let SubmissionResult::GaveUp { reason, .. } = result else {
+            panic!("expected the resumed turn to give up on the repeated call");
+        };
+        assert_eq!(
+            reason,
+            super::GiveUpReason::LoopDetected {
+                tool: "echo".to_string()
+            }
+        );
+        // The first call after resuming is the third and is rejected, the
+        // next one gives up. A fresh count would have allowed two more.
+        assert_eq!(llm.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_calling_missing_tool_gives_up_as_unavailable() {
+        use super::{AgenticLoopResult, GiveUpReason};
+
+        let llm = ToolLoopLlm::calling("no_such_tool");
+        let result = run_tool_loop(llm.clone(), None).await;
+
+        let AgenticLoopResult::GaveUp { reason, .. } = result else {
+            panic!("expected the turn to give up on the missing tool");
+        };
+        assert_eq!(
+            reason,
+            GiveUpReason::ToolUnavailable {
+                tool: "no_such_tool".to_string()
+            }
+        );
+        // Two failed calls, then the forced text-only request.
+        assert_eq!(llm.calls(), 3);
+    }
+
+    fn trace_calls(key: &str) -> Vec<crate::llm::ToolCall> {
+        vec![crate::llm::ToolCall {
+            id: "call_1".to_string(),
+            name: "http".to_string(),
+            arguments: serde_json::json!({
+                "url": "https://api.example.com",
+                "headers": { "Authorization": format!("Bearer {key}") },
+            }),
+            call_id: None,
+        }]
+    }
+
+    #[test]
+    fn test_reasoning_trace_suppressed_by_default() {
+        let agent = make_test_agent();
+        let calls = trace_calls("sk-proj-abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGH");
+        assert!(
+            agent
+                .reasoning_trace_status(1, Some("Fetching the data"), &calls)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_reasoning_trace_emitted_and_redacted_in_verbose_mode() {
+        let mut agent = make_test_agent();
+        agent.config.reasoning_trace = true;
+        let key = "sk-proj-abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGH";
+        let rationale = format!("The user gave me {key}, so I'll call the API with it.");
+
+        let Some(StatusUpdate::ReasoningTrace(trace)) =
+            agent.reasoning_trace_status(2, Some(&rationale), &trace_calls(key))
+        else {
+            panic!("expected a reasoning trace in verbose mode");
+        };
+
+        assert_eq!(trace.iteration, 2);
+        assert_eq!(trace.tools.len(), 1);
+        assert_eq!(trace.tools[0].name, "http");
+        assert_eq!(trace.tools[0].arguments["url"], "https://api.example.com");
+        let rendered = serde_json::to_string(&trace).expect("trace serializes");
+        assert!(!rendered.contains(key), "secret leaked: {rendered}");
+        assert!(rendered.contains("[REDACTED]"));
+        assert!(
+            trace
+                .rationale
+                .as_deref()
+                .is_some_and(|r| r.starts_with("The user gave me [REDACTED]"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode_rejects_forced_mutating_tool() {
+        use super::check_read_only;
+
+        let registry = ToolRegistry::new();
+        registry.register_builtin_tools();
+
+        // echo only reads; http can send requests with side effects.
+        assert!(check_read_only(&registry, true, "echo").await.is_ok());
+        let err = check_read_only(&registry, true, "http")
+            .await
+            .expect_err("http must be rejected in read-only mode");
+        assert!(err.to_string().contains("read-only"));
+        assert!(check_read_only(&registry, false, "http").await.is_ok());
+        // Unknown tools are left to fail with NotFound on execution.
+        assert!(check_read_only(&registry, true, "nope").await.is_ok());
+    }
+
+    /// A channel that records the emoji it reacts with.
+    struct ReactingChannel {
+        reactions: Arc<std::sync::Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl crate::channels::Channel for ReactingChannel {
+        fn name(&self) -> &str {
+            "reacting"
+        }
+
+        async fn start(
+            &self,
+        ) -> Result<crate::channels::MessageStream, crate::error::ChannelError> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &crate::channels::IncomingMessage,
+            _response: crate::channels::OutgoingResponse,
+        ) -> Result<(), crate::error::ChannelError> {
+            Ok(())
+        }
+
+        async fn react(
+            &self,
+            _msg: &crate::channels::IncomingMessage,
+            reaction: crate::channels::Reaction,
+        ) -> Result<(), crate::error::ChannelError> {
+            self.reactions.lock().unwrap().push(reaction.emoji());
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), crate::error::ChannelError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approved_and_deferred_tools_react_on_completion() {
+        use crate::agent::session::PendingApproval;
+        use crate::channels::Reaction;
+
+        let agent = make_test_agent();
+        agent.tools().register_builtin_tools();
+        let reactions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        agent
+            .channels
+            .add(Box::new(ReactingChannel {
+                reactions: Arc::clone(&reactions),
+            }))
+            .await;
+
+        let session = Arc::new(tokio::sync::Mutex::new(Session::new("user")));
+        let thread_id = {
+            let mut sess = session.lock().await;
+            let thread = sess.create_thread();
+            thread.start_turn("say hi three times");
+            thread.id
+        };
+        let echo = |id: &str| ToolCall {
+            id: id.to_string(),
+            name: "echo".to_string(),
+            arguments: serde_json::json!({"message": "hi"}),
+            call_id: None,
+        };
+        let request_id = uuid::Uuid::new_v4();
+        session
+            .lock()
+            .await
+            .threads
+            .get_mut(&thread_id)
+            .unwrap()
+            .await_approval(PendingApproval {
+                request_id,
+                tool_name: "echo".to_string(),
+                parameters: serde_json::json!({"message": "hi"}),
+                description: "Echo a message".to_string(),
+                tool_call_id: "call_0".to_string(),
+                context_messages: vec![crate::llm::ChatMessage::user("say hi three times")],
+                // Two deferred calls run in parallel, one would run alone.
+                deferred_tool_calls: vec![echo("call_1"), echo("call_2")],
+            });
+
+        let message = crate::channels::IncomingMessage::new("reacting", "user", "yes");
+        agent
+            .process_approval(&message, session, thread_id, Some(request_id), true, false)
+            .await
+            .expect("approval is processed");
+
+        let done = Reaction::Done.emoji();
+        let reactions = reactions.lock().unwrap();
+        assert_eq!(
+            reactions.iter().filter(|r| **r == done).count(),
+            3,
+            "every completed tool reacts: {reactions:?}"
+        );
+    }
 }
@@ -29,7 +29,7 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 
 use crate::channels::OutgoingResponse;
-use crate::llm::{ChatMessage, CompletionRequest, LlmProvider, Reasoning};
+use crate::llm::{ChatMessage, CompletionRequest, LlmProvider, Reasoning, SamplingProfile};
 use crate::safety::SafetyLayer;
 use crate::workspace::Workspace;
 use crate::workspace::hygiene::HygieneConfig;
@@ -47,6 +47,8 @@ pub struct HeartbeatConfig {
     pub notify_user_id: Option<String>,
     /// Channel to notify on heartbeat findings.
     pub notify_channel: Option<String>,
+    /// Sampling profile for the heartbeat check.
+    pub sampling: SamplingProfile,
 }
 
 impl Default for HeartbeatConfig {
@@ -57,6 +59,7 @@ impl Default for HeartbeatConfig {
             max_failures: 3,
             notify_user_id: None,
             notify_channel: None,
+            sampling: SamplingProfile::Deterministic,
         }
     }
 }
@@ -68,6 +71,12 @@ impl HeartbeatConfig {
         self
     }
 
+    /// Set the sampling profile for the heartbeat check.
+    pub fn with_sampling(mut self, sampling: SamplingProfile) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     /// Disable heartbeat.
     pub fn disabled(mut self) -> Self {
         self.enabled = false;
@@ -260,7 +269,7 @@ impl HeartbeatRunner {
 
         let request = CompletionRequest::new(messages)
             .with_max_tokens(max_tokens)
-            .with_temperature(0.3);
+            .with_sampling(self.config.sampling);
 
         let reasoning = Reasoning::new(self.llm.clone(), self.safety.clone());
         let (content, _usage) = match reasoning.complete(request).await {
@@ -297,6 +306,7 @@ impl HeartbeatRunner {
             metadata: serde_json::json!({
                 "source": "heartbeat",
             }),
+            urgent: false,
         };
 
         if let Err(e) = tx.send(response).await {
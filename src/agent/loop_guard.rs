@@ -0,0 +1,336 @@
+//! Detection of an agentic loop that is not getting anywhere.
+//!
+//! A model that keeps calling the same tool with the same arguments gets
+//! the same result every time, so each repeat only burns an iteration and
+//! its cost. [`LoopGuard`] counts the calls made during a turn and flags
+//! the one that repeats an earlier call too often, even with other calls
+//! in between (A, B, A, B, ...), so the dispatcher can
+//! reject it and tell the model to change course instead of waiting for
+//! `max_tool_iterations` to stop the turn. A model that repeats the call
+//! anyway, keeps calling a tool that is missing or disabled, or keeps
+//! calling a tool that fails, has a [`GiveUpReason`] and is made to answer
+//! in text.
+
+use std::collections::HashMap;
+
+use crate::error::{Error, ToolError};
+
+/// Identical calls allowed in a turn before the next one is rejected.
+pub(crate) const MAX_REPEATED_TOOL_CALLS: usize = 3;
+
+/// Calls to a missing or disabled tool allowed in a turn before it gives up.
+pub(crate) const MAX_UNAVAILABLE_TOOL_CALLS: usize = 2;
+
+/// Failed calls to one tool allowed in a turn before it gives up.
+pub(crate) const MAX_FAILED_TOOL_CALLS: usize = 3;
+
+/// Why the agent stopped working on a turn without finishing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GiveUpReason {
+    /// The turn used all of its tool iterations.
+    IterationLimit { limit: usize },
+    /// The model kept repeating one tool call after it was rejected.
+    LoopDetected { tool: String },
+    /// The model kept calling a tool that does not exist or is disabled.
+    ToolUnavailable { tool: String },
+    /// Every call to a tool kept failing.
+    ToolFailing { tool: String, failures: usize },
+}
+
+impl GiveUpReason {
+    /// User-facing reply for a turn that gave up: the model's partial
+    /// answer, if any, followed by why the turn stopped, so a truncated
+    /// answer can't be mistaken for a finished one.
+    pub fn reply(&self, partial: Option<&str>) -> String {
+        let notice = match self {
+            Self::IterationLimit { limit } => format!(
+                "Iteration limit reached: stopped after {limit} tool iterations. \
+                 Ask me to continue, or raise the limit with /iterations <n>."
+            ),
+            Self::LoopDetected { tool } => format!(
+                "Stopped early: I kept repeating the same call to {tool}. \
+                 Try rephrasing the request or giving me more detail."
+            ),
+            Self::ToolUnavailable { tool } => format!(
+                "Stopped early: the {tool} tool is not available. \
+                 Enable it, or ask for something I can do without it."
+            ),
+            Self::ToolFailing { tool, failures } => format!(
+                "Stopped early: {tool} failed {failures} times. \
+                 Check the error above, or ask for something I can do without it."
+            ),
+        };
+        match partial.map(str::trim_end).filter(|p| !p.trim().is_empty()) {
+            Some(partial) => format!("{partial}\n\n{notice}"),
+            None => notice,
+        }
+    }
+}
+
+impl std::fmt::Display for GiveUpReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IterationLimit { limit } => write!(f, "iteration limit of {limit} reached"),
+            Self::LoopDetected { tool } => write!(f, "repeated the same call to {tool}"),
+            Self::ToolUnavailable { tool } => write!(f, "tool {tool} is not available"),
+            Self::ToolFailing { tool, failures } => {
+                write!(f, "tool {tool} failed {failures} times")
+            }
+        }
+    }
+}
+
+/// Counts identical tool calls, calls to unavailable tools, and failed
+/// calls within one turn. Kept on the turn, so counts survive the pause
+/// for an approval.
+#[derive(Debug, Clone)]
+pub(crate) struct LoopGuard {
+    limit: usize,
+    /// Calls seen so far, keyed by tool name and canonical arguments.
+    calls: HashMap<(String, String), usize>,
+    /// Tool and repeat count of the most recent call.
+    last: Option<(String, usize)>,
+    /// First tool whose call was repeated after it was rejected.
+    stuck: Option<String>,
+    unavailable: HashMap<String, usize>,
+    failures: HashMap<String, usize>,
+}
+
+impl LoopGuard {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            calls: HashMap::new(),
+            last: None,
+            stuck: None,
+            unavailable: HashMap::new(),
+            failures: HashMap::new(),
+        }
+    }
+
+    /// Record a call, in the order the model made it. Returns `true` once
+    /// the same tool has been called with the same arguments `limit` times
+    /// in the turn, and for every identical call after that.
+    pub(crate) fn record(&mut self, tool_name: &str, arguments: &serde_json::Value) -> bool {
+        let key = (tool_name.to_string(), canonical_json(arguments));
+        let count = self.calls.entry(key).or_default();
+        *count += 1;
+        let count = *count;
+        if count > self.limit && self.stuck.is_none() {
+            self.stuck = Some(tool_name.to_string());
+        }
+        self.last = Some((tool_name.to_string(), count));
+        count >= self.limit
+    }
+
+    /// Record a call that failed because the tool is missing or disabled.
+    pub(crate) fn record_unavailable(&mut self, tool_name: &str) {
+        *self.unavailable.entry(tool_name.to_string()).or_default() += 1;
+    }
+
+    /// Record a call that ran and returned an error.
+    pub(crate) fn record_failure(&mut self, tool_name: &str) {
+        *self.failures.entry(tool_name.to_string()).or_default() += 1;
+    }
+
+    /// Record how a call that ran ended.
+    pub(crate) fn record_outcome<T>(&mut self, tool_name: &str, outcome: &Result<T, Error>) {
+        match outcome {
+            Err(Error::Tool(ToolError::NotFound { .. } | ToolError::Disabled { .. })) => {
+                self.record_unavailable(tool_name)
+            }
+            Err(_) => self.record_failure(tool_name),
+            Ok(_) => {}
+        }
+    }
+
+    /// Whether the model repeated a call again after it was rejected.
+    pub(crate) fn is_stuck(&self) -> bool {
+        self.stuck.is_some()
+    }
+
+    /// Why the turn should stop calling tools, if it should.
+    pub(crate) fn give_up_reason(&self) -> Option<GiveUpReason> {
+        if let Some(tool) = &self.stuck {
+            return Some(GiveUpReason::LoopDetected { tool: tool.clone() });
+        }
+        let mut unavailable: Vec<_> = self
+            .unavailable
+            .iter()
+            .filter(|(_, count)| **count >= MAX_UNAVAILABLE_TOOL_CALLS)
+            .map(|(tool, _)| tool.clone())
+            .collect();
+        unavailable.sort();
+        if let Some(tool) = unavailable.into_iter().next() {
+            return Some(GiveUpReason::ToolUnavailable { tool });
+        }
+        let mut failing: Vec<_> = self
+            .failures
+            .iter()
+            .filter(|(_, count)| **count >= MAX_FAILED_TOOL_CALLS)
+            .collect();
+        failing.sort();
+        failing
+            .into_iter()
+            .next()
+            .map(|(tool, failures)| GiveUpReason::ToolFailing {
+                tool: tool.clone(),
+                failures: *failures,
+            })
+    }
+
+    /// Tool result sent to the model in place of a rejected repeat.
+    pub(crate) fn message(&self, tool_name: &str) -> String {
+        let repeats = self.last.as_ref().map_or(0, |(_, count)| *count);
+        format!(
+            "You've made this call to {tool_name} with the same arguments {repeats} times in \
+             this turn and it was not run again; its result will not change. Try a different \
+             approach, or answer with what you have."
+        )
+    }
+}
+
+impl Default for LoopGuard {
+    fn default() -> Self {
+        Self::new(MAX_REPEATED_TOOL_CALLS)
+    }
+}
+
+/// Serialize `value` with object keys sorted at every level, so argument
+/// objects that differ only in key order compare equal.
+fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::String(key.clone()),
+                        canonical_json(value)
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn identical_calls_trip_at_the_limit() {
+        let mut guard = LoopGuard::new(3);
+        let args = json!({"path": "/tmp/a", "lines": [1, 2]});
+        assert!(!guard.record("read_file", &args));
+        assert!(!guard.record("read_file", &args));
+        assert!(guard.record("read_file", &args));
+        assert!(!guard.is_stuck());
+        // Further repeats stay rejected, and mean the model is stuck.
+        assert!(guard.record("read_file", &args));
+        assert!(guard.is_stuck());
+        assert!(guard.message("read_file").contains("4 times in this turn"));
+        assert_eq!(
+            guard.give_up_reason(),
+            Some(GiveUpReason::LoopDetected {
+                tool: "read_file".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn unavailable_tool_gives_up_after_repeated_failures() {
+        let mut guard = LoopGuard::new(3);
+        guard.record_unavailable("shell");
+        assert_eq!(guard.give_up_reason(), None);
+        guard.record_unavailable("shell");
+        assert_eq!(
+            guard.give_up_reason(),
+            Some(GiveUpReason::ToolUnavailable {
+                tool: "shell".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn failing_tool_gives_up_after_repeated_failures() {
+        let mut guard = LoopGuard::new(3);
+        guard.record_failure("http");
+        guard.record_failure("http");
+        guard.record_failure("shell");
+        assert_eq!(guard.give_up_reason(), None);
+        guard.record_failure("http");
+        let reason = guard.give_up_reason();
+        assert_eq!(
+            reason,
+            Some(GiveUpReason::ToolFailing {
+                tool: "http".to_string(),
+                failures: 3
+            })
+        );
+        assert!(reason.unwrap().reply(None).contains("http failed 3 times"));
+    }
+
+    #[test]
+    fn give_up_reply_keeps_partial_answer() {
+        let reason = GiveUpReason::IterationLimit { limit: 5 };
+        assert_eq!(
+            reason.reply(Some("Found two of three files.\n")),
+            "Found two of three files.\n\nIteration limit reached: stopped after 5 tool \
+             iterations. Ask me to continue, or raise the limit with /iterations <n>."
+        );
+        assert!(reason.reply(None).starts_with("Iteration limit reached"));
+        assert!(
+            reason
+                .reply(Some("  "))
+                .starts_with("Iteration limit reached")
+        );
+    }
+
+    #[test]
+    fn key_order_does_not_matter() {
+        let mut guard = LoopGuard::new(2);
+        assert!(!guard.record("http", &json!({"url": "u", "opts": {"a": 1, "b": 2}})));
+        assert!(guard.record("http", &json!({"opts": {"b": 2, "a": 1}, "url": "u"})));
+    }
+
+    #[test]
+    fn varied_calls_do_not_trip() {
+        let mut guard = LoopGuard::new(2);
+        assert!(!guard.record("read_file", &json!({"path": "a"})));
+        assert!(!guard.record("read_file", &json!({"path": "b"})));
+        assert!(!guard.record("list_dir", &json!({"path": "b"})));
+        assert!(!guard.record("list_dir", &json!({"path": "c"})));
+    }
+
+    #[test]
+    fn alternating_calls_trip() {
+        let mut guard = LoopGuard::new(2);
+        let a = json!({"path": "a"});
+        let b = json!({"path": "b"});
+        assert!(!guard.record("read_file", &a));
+        assert!(!guard.record("list_dir", &b));
+        assert!(guard.record("read_file", &a));
+        assert!(guard.message("read_file").contains("2 times in this turn"));
+        assert!(guard.record("list_dir", &b));
+        assert!(!guard.is_stuck());
+        assert!(guard.record("read_file", &a));
+        assert_eq!(
+            guard.give_up_reason(),
+            Some(GiveUpReason::LoopDetected {
+                tool: "read_file".to_string()
+            })
+        );
+    }
+}
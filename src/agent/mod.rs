@@ -18,6 +18,7 @@ pub mod cost_guard;
 mod dispatcher;
 mod heartbeat;
 pub mod job_monitor;
+mod loop_guard;
 mod router;
 pub mod routine;
 pub mod routine_engine;
@@ -28,6 +29,7 @@ mod session_manager;
 pub mod submission;
 pub mod task;
 mod thread_ops;
+pub mod transcript;
 pub mod undo;
 pub mod worker;
 
@@ -36,6 +38,7 @@ pub use agent_loop::{Agent, AgentDeps};
 pub use compaction::{CompactionResult, ContextCompactor};
 pub use context_monitor::{CompactionStrategy, ContextBreakdown, ContextMonitor};
 pub use heartbeat::{HeartbeatConfig, HeartbeatResult, HeartbeatRunner, spawn_heartbeat};
+pub use loop_guard::GiveUpReason;
 pub use router::{MessageIntent, Router};
 pub use routine::{Routine, RoutineAction, RoutineRun, Trigger};
 pub use routine_engine::RoutineEngine;
@@ -43,7 +46,8 @@ pub use scheduler::Scheduler;
 pub use self_repair::{BrokenTool, RepairResult, RepairTask, SelfRepair, StuckJob};
 pub use session::{PendingApproval, PendingAuth, Session, Thread, ThreadState, Turn, TurnState};
 pub use session_manager::SessionManager;
-pub use submission::{Submission, SubmissionParser, SubmissionResult};
+pub use submission::{IterationLimitChange, Submission, SubmissionParser, SubmissionResult};
 pub use task::{Task, TaskContext, TaskHandler, TaskOutput};
+pub(crate) use thread_ops::persist_branch;
 pub use undo::{Checkpoint, UndoManager};
 pub use worker::{Worker, WorkerDeps};
@@ -27,7 +27,7 @@ use crate::channels::{IncomingMessage, OutgoingResponse};
 use crate::config::RoutineConfig;
 use crate::db::Database;
 use crate::error::RoutineError;
-use crate::llm::{ChatMessage, CompletionRequest, FinishReason, LlmProvider};
+use crate::llm::{ChatMessage, CompletionRequest, FinishReason, LlmProvider, SamplingProfile};
 use crate::workspace::Workspace;
 
 /// The routine execution engine.
@@ -44,6 +44,8 @@ pub struct RoutineEngine {
     event_cache: Arc<RwLock<Vec<(Uuid, Routine, Regex)>>>,
     /// Scheduler for dispatching jobs (FullJob mode).
     scheduler: Option<Arc<Scheduler>>,
+    /// Sampling profile for lightweight routine calls.
+    sampling: SamplingProfile,
 }
 
 impl RoutineEngine {
@@ -64,9 +66,16 @@ impl RoutineEngine {
             running_count: Arc::new(AtomicUsize::new(0)),
             event_cache: Arc::new(RwLock::new(Vec::new())),
             scheduler,
+            sampling: SamplingProfile::Deterministic,
         }
     }
 
+    /// Set the sampling profile for lightweight routine calls.
+    pub fn with_sampling(mut self, sampling: SamplingProfile) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     /// Refresh the in-memory event trigger cache from DB.
     pub async fn refresh_event_cache(&self) {
         match self.store.list_event_routines().await {
@@ -231,6 +240,7 @@ impl RoutineEngine {
             notify_tx: self.notify_tx.clone(),
             running_count: self.running_count.clone(),
             scheduler: self.scheduler.clone(),
+            sampling: self.sampling,
         };
 
         tokio::spawn(async move {
@@ -263,6 +273,7 @@ impl RoutineEngine {
             notify_tx: self.notify_tx.clone(),
             running_count: self.running_count.clone(),
             scheduler: self.scheduler.clone(),
+            sampling: self.sampling,
         };
 
         // Record the run in DB, then spawn execution
@@ -310,6 +321,7 @@ struct EngineContext {
     notify_tx: mpsc::Sender<OutgoingResponse>,
     running_count: Arc<AtomicUsize>,
     scheduler: Option<Arc<Scheduler>>,
+    sampling: SamplingProfile,
 }
 
 /// Execute a routine run. Handles both lightweight and full_job modes.
@@ -536,7 +548,7 @@ async fn execute_lightweight(
 
     let request = CompletionRequest::new(messages)
         .with_max_tokens(effective_max_tokens)
-        .with_temperature(0.3);
+        .with_sampling(ctx.sampling);
 
     let response = ctx
         .llm
@@ -605,6 +617,7 @@ async fn send_notification(
             "routine_name": routine_name,
             "status": status.to_string(),
         }),
+        urgent: false,
     };
 
     if let Err(e) = tx.send(response).await {
@@ -169,6 +169,7 @@ impl Scheduler {
                 hooks: self.hooks.clone(),
                 timeout: self.config.job_timeout,
                 use_planning: self.config.use_planning,
+                sampling: self.config.sampling,
             };
             let worker = Worker::new(job_id, deps);
 
@@ -408,15 +409,14 @@ impl Scheduler {
             .into());
         }
 
+        tools
+            .check_rate_limit(&job_ctx.user_id, tool.as_ref())
+            .await?;
+
         // Validate tool parameters
         let validation = safety.validator().validate_tool_params(&params);
-        if !validation.is_valid {
-            let details = validation
-                .errors
-                .iter()
-                .map(|e| format!("{}: {}", e.field, e.message))
-                .collect::<Vec<_>>()
-                .join("; ");
+        if !validation.is_valid() {
+            let details = validation.describe_errors();
             return Err(crate::error::ToolError::InvalidParameters {
                 name: tool_name.to_string(),
                 reason: format!("Invalid tool parameters: {}", details),
@@ -9,6 +9,7 @@
 //! - Interrupt: Cancel the current turn mid-execution
 //! - Compaction: Summarize old turns to save context
 //! - Resume: Continue from a saved checkpoint
+//! - Branch: Copy a thread up to a turn to explore an alternative
 
 use std::collections::{HashMap, HashSet};
 
@@ -16,7 +17,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::agent::loop_guard::LoopGuard;
+use crate::error::Error;
 use crate::llm::{ChatMessage, ToolCall};
+use crate::tools::ToolResultStatus;
 
 /// A session containing one or more threads.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +42,13 @@ pub struct Session {
     /// Tools that have been auto-approved for this session ("always approve").
     #[serde(default)]
     pub auto_approved_tools: HashSet<String>,
+    /// Turns read-only mode on for this session. Can't lift a read-only
+    /// mode set by `AgentConfig::read_only`.
+    #[serde(default)]
+    pub read_only: Option<bool>,
+    /// Overrides `AgentConfig::max_tool_iterations` for this session when set.
+    #[serde(default)]
+    pub max_tool_iterations: Option<usize>,
 }
 
 impl Session {
@@ -53,6 +64,8 @@ impl Session {
             last_active_at: now,
             metadata: serde_json::Value::Null,
             auto_approved_tools: HashSet::new(),
+            read_only: None,
+            max_tool_iterations: None,
         }
     }
 
@@ -112,6 +125,19 @@ impl Session {
             false
         }
     }
+
+    /// Branch a thread at a turn and make the branch active.
+    ///
+    /// Returns the new thread's ID, or `None` if the thread doesn't exist
+    /// or has fewer than `at_turn` turns.
+    pub fn branch_thread(&mut self, thread_id: Uuid, at_turn: usize) -> Option<Uuid> {
+        let branch = self.threads.get(&thread_id)?.branch(at_turn)?;
+        let branch_id = branch.id;
+        self.threads.insert(branch_id, branch);
+        self.active_thread = Some(branch_id);
+        self.last_active_at = Utc::now();
+        Some(branch_id)
+    }
 }
 
 /// State of a thread.
@@ -324,6 +350,24 @@ impl Thread {
         messages
     }
 
+    /// Copy the first `at_turn` turns into a new, independent thread.
+    ///
+    /// The branch starts idle with a fresh ID, so its next message becomes
+    /// an alternative to turn `at_turn` of this thread. Its metadata records
+    /// where it came from. Returns `None` if `at_turn` is past the end.
+    pub fn branch(&self, at_turn: usize) -> Option<Thread> {
+        if at_turn > self.turns.len() {
+            return None;
+        }
+        let mut branch = Thread::new(self.session_id);
+        branch.turns = self.turns[..at_turn].to_vec();
+        branch.metadata = serde_json::json!({
+            "branched_from": self.id,
+            "branch_turn": at_turn,
+        });
+        Some(branch)
+    }
+
     /// Truncate turns to a specific count (keeping most recent).
     pub fn truncate_turns(&mut self, keep: usize) {
         if self.turns.len() > keep {
@@ -403,6 +447,9 @@ pub struct Turn {
     pub completed_at: Option<DateTime<Utc>>,
     /// Error message (if failed).
     pub error: Option<String>,
+    /// Repeated, unavailable and failed calls seen so far in this turn.
+    #[serde(skip)]
+    pub(crate) loop_guard: LoopGuard,
 }
 
 impl Turn {
@@ -417,6 +464,7 @@ impl Turn {
             started_at: Utc::now(),
             completed_at: None,
             error: None,
+            loop_guard: LoopGuard::default(),
         }
     }
 
@@ -447,6 +495,7 @@ impl Turn {
             parameters: params,
             result: None,
             error: None,
+            status: None,
         });
     }
 
@@ -454,6 +503,15 @@ impl Turn {
     pub fn record_tool_result(&mut self, result: serde_json::Value) {
         if let Some(call) = self.tool_calls.last_mut() {
             call.result = Some(result);
+            call.status = Some(ToolResultStatus::Success);
+        }
+    }
+
+    /// Record a result the tool could only partially produce.
+    pub fn record_tool_partial(&mut self, result: serde_json::Value) {
+        if let Some(call) = self.tool_calls.last_mut() {
+            call.result = Some(result);
+            call.status = Some(ToolResultStatus::Partial);
         }
     }
 
@@ -461,6 +519,22 @@ impl Turn {
     pub fn record_tool_error(&mut self, error: impl Into<String>) {
         if let Some(call) = self.tool_calls.last_mut() {
             call.error = Some(error.into());
+            call.status = Some(ToolResultStatus::Error { retryable: false });
+        }
+    }
+
+    /// Record the outcome of an executed tool call, classifying failures.
+    /// `partial` marks a successful output the tool could not finish.
+    pub fn record_tool_outcome(&mut self, outcome: &Result<String, Error>, partial: bool) {
+        match outcome {
+            Ok(output) if partial => self.record_tool_partial(serde_json::json!(output)),
+            Ok(output) => self.record_tool_result(serde_json::json!(output)),
+            Err(e) => {
+                if let Some(call) = self.tool_calls.last_mut() {
+                    call.error = Some(e.to_string());
+                    call.status = Some(ToolResultStatus::from_error(e));
+                }
+            }
         }
     }
 }
@@ -476,6 +550,9 @@ pub struct TurnToolCall {
     pub result: Option<serde_json::Value>,
     /// Error from the tool (if failed).
     pub error: Option<String>,
+    /// How the call ended; `None` while it is still running.
+    #[serde(default)]
+    pub status: Option<ToolResultStatus>,
 }
 
 #[cfg(test)]
@@ -525,6 +602,52 @@ mod tests {
 
         assert_eq!(turn.tool_calls.len(), 1);
         assert!(turn.tool_calls[0].result.is_some());
+        assert_eq!(turn.tool_calls[0].status, Some(ToolResultStatus::Success));
+    }
+
+    #[test]
+    fn test_turn_tool_outcome_status() {
+        let mut turn = Turn::new(0, "Test input");
+
+        turn.record_tool_call("http", serde_json::json!({}));
+        assert_eq!(turn.tool_calls[0].status, None);
+        turn.record_tool_outcome(
+            &Err(crate::error::ToolError::RateLimited {
+                name: "http".to_string(),
+                retry_after: None,
+            }
+            .into()),
+            false,
+        );
+
+        turn.record_tool_call("shell", serde_json::json!({}));
+        turn.record_tool_outcome(
+            &Err(crate::error::ToolError::Timeout {
+                name: "shell".to_string(),
+                timeout: std::time::Duration::from_secs(5),
+            }
+            .into()),
+            false,
+        );
+
+        turn.record_tool_call("search", serde_json::json!({}));
+        turn.record_tool_outcome(&Ok("first page".to_string()), true);
+
+        turn.record_tool_call("echo", serde_json::json!({}));
+        turn.record_tool_outcome(&Ok("done".to_string()), false);
+
+        let statuses: Vec<_> = turn.tool_calls.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(ToolResultStatus::Error { retryable: true }),
+                Some(ToolResultStatus::Timeout),
+                Some(ToolResultStatus::Partial),
+                Some(ToolResultStatus::Success),
+            ]
+        );
+        assert!(turn.tool_calls[0].error.is_some());
+        assert!(turn.tool_calls[3].result.is_some());
     }
 
     #[test]
@@ -749,6 +872,68 @@ mod tests {
         assert_eq!(session.threads.len(), 1);
     }
 
+    #[test]
+    fn test_branch_shares_history_then_diverges() {
+        let mut session = Session::new("user-1");
+        let original_id = session.create_thread().id;
+        {
+            let thread = session.threads.get_mut(&original_id).unwrap();
+            for i in 0..3 {
+                thread.start_turn(format!("question {}", i));
+                thread.complete_turn(format!("answer {}", i));
+            }
+        }
+
+        let branch_id = session.branch_thread(original_id, 2).unwrap();
+        assert_ne!(branch_id, original_id);
+        assert_eq!(session.active_thread, Some(branch_id));
+
+        let branch = &session.threads[&branch_id];
+        assert_eq!(branch.state, ThreadState::Idle);
+        assert_eq!(branch.metadata["branched_from"], original_id.to_string());
+        assert_eq!(branch.metadata["branch_turn"], 2);
+        let contents =
+            |t: &Thread| -> Vec<String> { t.messages().into_iter().map(|m| m.content).collect() };
+        assert_eq!(
+            contents(branch),
+            contents(&session.threads[&original_id])[..4].to_vec()
+        );
+
+        // Continuing the branch leaves the original untouched, and vice versa.
+        let branch = session.threads.get_mut(&branch_id).unwrap();
+        branch.start_turn("alternative question");
+        branch.complete_turn("alternative answer");
+        let original = session.threads.get_mut(&original_id).unwrap();
+        original.start_turn("question 3");
+        original.complete_turn("answer 3");
+
+        let branch = &session.threads[&branch_id];
+        let original = &session.threads[&original_id];
+        assert_eq!(branch.turns.len(), 3);
+        assert_eq!(branch.turns[2].user_input, "alternative question");
+        assert_eq!(original.turns.len(), 4);
+        assert_eq!(original.turns[2].user_input, "question 2");
+    }
+
+    #[test]
+    fn test_branch_out_of_range() {
+        let mut session = Session::new("user-1");
+        let thread = session.create_thread();
+        thread.start_turn("only question");
+        thread.complete_turn("only answer");
+        let thread_id = thread.id;
+
+        assert!(session.branch_thread(thread_id, 2).is_none());
+        assert!(session.branch_thread(Uuid::new_v4(), 0).is_none());
+        assert_eq!(session.active_thread, Some(thread_id));
+
+        // Branching at 0 gives an empty thread; at the end copies everything.
+        let empty = session.branch_thread(thread_id, 0).unwrap();
+        assert!(session.threads[&empty].turns.is_empty());
+        let full = session.branch_thread(thread_id, 1).unwrap();
+        assert_eq!(session.threads[&full].turns.len(), 1);
+    }
+
     #[test]
     fn test_truncate_turns() {
         let mut thread = Thread::new(Uuid::new_v4());
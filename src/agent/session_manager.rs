@@ -185,6 +185,36 @@ impl SessionManager {
         }
     }
 
+    /// Point an external conversation at a different thread.
+    ///
+    /// Subsequent `resolve_thread` calls with the same key return `thread_id`,
+    /// which must already exist in the user's session.
+    pub async fn bind_thread(
+        &self,
+        user_id: &str,
+        channel: &str,
+        external_thread_id: Option<&str>,
+        thread_id: Uuid,
+    ) {
+        let key = ThreadKey {
+            user_id: user_id.to_string(),
+            channel: channel.to_string(),
+            external_thread_id: external_thread_id.map(String::from),
+        };
+
+        {
+            let mut thread_map = self.thread_map.write().await;
+            thread_map.insert(key, thread_id);
+        }
+
+        {
+            let mut undo_managers = self.undo_managers.write().await;
+            undo_managers
+                .entry(thread_id)
+                .or_insert_with(|| Arc::new(Mutex::new(UndoManager::new())));
+        }
+    }
+
     /// Get undo manager for a thread.
     pub async fn get_undo_manager(&self, thread_id: Uuid) -> Arc<Mutex<UndoManager>> {
         // Fast path
@@ -632,6 +662,22 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_bind_thread_redirects_resolution() {
+        let manager = SessionManager::new();
+
+        let (session, original) = manager.resolve_thread("user-bind", "repl", None).await;
+        let branch = {
+            let mut sess = session.lock().await;
+            sess.branch_thread(original, 0).unwrap()
+        };
+
+        manager.bind_thread("user-bind", "repl", None, branch).await;
+
+        let (_, resolved) = manager.resolve_thread("user-bind", "repl", None).await;
+        assert_eq!(resolved, branch);
+    }
+
     #[tokio::test]
     async fn test_multiple_threads_per_user() {
         let manager = SessionManager::new();
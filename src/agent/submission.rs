@@ -6,6 +6,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::agent::GiveUpReason;
+
 /// Parses user input into Submission types.
 pub struct SubmissionParser;
 
@@ -40,6 +42,40 @@ impl SubmissionParser {
         if lower == "/suggest" {
             return Submission::Suggest;
         }
+        // /iterations [n|reset] - show or change this session's tool iteration limit
+        if lower == "/iterations" {
+            return Submission::MaxIterations { change: None };
+        }
+        if let Some(rest) = lower.strip_prefix("/iterations ") {
+            let rest = rest.trim();
+            if rest == "reset" {
+                return Submission::MaxIterations {
+                    change: Some(IterationLimitChange::Reset),
+                };
+            }
+            if let Ok(limit) = rest.parse::<usize>()
+                && limit > 0
+            {
+                return Submission::MaxIterations {
+                    change: Some(IterationLimitChange::Set(limit)),
+                };
+            }
+        }
+        // /readonly [on|off] - show or change this session's read-only mode
+        match lower.as_str() {
+            "/readonly" => return Submission::ReadOnly { enabled: None },
+            "/readonly on" => {
+                return Submission::ReadOnly {
+                    enabled: Some(true),
+                };
+            }
+            "/readonly off" => {
+                return Submission::ReadOnly {
+                    enabled: Some(false),
+                };
+            }
+            _ => {}
+        }
         if lower == "/thread new" || lower == "/new" {
             return Submission::NewThread;
         }
@@ -117,6 +153,18 @@ impl SubmissionParser {
             }
         }
 
+        // /branch [turn] - branch the current thread
+        if lower == "/branch" {
+            return Submission::Branch { at_turn: None };
+        }
+        if let Some(rest) = lower.strip_prefix("/branch ")
+            && let Ok(at_turn) = rest.trim().parse::<usize>()
+        {
+            return Submission::Branch {
+                at_turn: Some(at_turn),
+            };
+        }
+
         // /resume <uuid> - resume from checkpoint
         if let Some(rest) = lower.strip_prefix("/resume ")
             && let Ok(id) = Uuid::parse_str(rest.trim())
@@ -220,6 +268,12 @@ pub enum Submission {
     /// Create a new thread.
     NewThread,
 
+    /// Copy the current thread up to a turn into a new thread and switch to it.
+    Branch {
+        /// Number of turns to keep; `None` keeps the whole thread.
+        at_turn: Option<usize>,
+    },
+
     /// Trigger a manual heartbeat check.
     Heartbeat,
 
@@ -229,6 +283,18 @@ pub enum Submission {
     /// Suggest next steps based on the current thread.
     Suggest,
 
+    /// Show or change this session's tool iteration limit.
+    MaxIterations {
+        /// The change to make; `None` only shows the current limit.
+        change: Option<IterationLimitChange>,
+    },
+
+    /// Show or change this session's read-only mode.
+    ReadOnly {
+        /// The mode to switch to; `None` only shows the current one.
+        enabled: Option<bool>,
+    },
+
     /// Quit the agent. Bypasses thread-state checks.
     Quit,
 
@@ -242,6 +308,15 @@ pub enum Submission {
     },
 }
 
+/// Change to a session's tool iteration limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IterationLimitChange {
+    /// Use this limit for the rest of the session.
+    Set(usize),
+    /// Go back to the configured limit.
+    Reset,
+}
+
 impl Submission {
     /// Create a user input submission.
     pub fn user_input(content: impl Into<String>) -> Self {
@@ -310,9 +385,12 @@ impl Submission {
                 | Self::Redo
                 | Self::Clear
                 | Self::NewThread
+                | Self::Branch { .. }
                 | Self::Heartbeat
                 | Self::Summarize
                 | Self::Suggest
+                | Self::MaxIterations { .. }
+                | Self::ReadOnly { .. }
                 | Self::SystemCommand { .. }
         )
     }
@@ -353,9 +431,27 @@ pub enum SubmissionResult {
 
     /// Turn was interrupted.
     Interrupted,
+
+    /// Turn ended without finishing the task.
+    GaveUp {
+        /// Why the agent stopped.
+        reason: GiveUpReason,
+        /// The reply sent to the user, with any partial progress.
+        content: String,
+    },
 }
 
 impl SubmissionResult {
+    /// Result for a finished turn: a response, or `GaveUp` when the agent
+    /// stopped without finishing.
+    pub fn finished(content: impl Into<String>, gave_up: Option<GiveUpReason>) -> Self {
+        let content = content.into();
+        match gave_up {
+            Some(reason) => Self::GaveUp { reason, content },
+            None => Self::Response { content },
+        }
+    }
+
     /// Create a response result.
     pub fn response(content: impl Into<String>) -> Self {
         Self::Response {
@@ -459,6 +555,21 @@ mod tests {
         assert!(matches!(submission, Submission::SwitchThread { thread_id } if thread_id == uuid));
     }
 
+    #[test]
+    fn test_parser_branch() {
+        let submission = SubmissionParser::parse("/branch");
+        assert!(matches!(submission, Submission::Branch { at_turn: None }));
+
+        let submission = SubmissionParser::parse("/branch 3");
+        assert!(matches!(
+            submission,
+            Submission::Branch { at_turn: Some(3) }
+        ));
+
+        let submission = SubmissionParser::parse("/branch later");
+        assert!(matches!(submission, Submission::UserInput { .. }));
+    }
+
     #[test]
     fn test_parser_resume() {
         let uuid = Uuid::new_v4();
@@ -489,6 +600,74 @@ mod tests {
         assert!(matches!(submission, Submission::Suggest));
     }
 
+    #[test]
+    fn test_finished_result_reports_giving_up() {
+        assert!(matches!(
+            SubmissionResult::finished("done", None),
+            SubmissionResult::Response { content } if content == "done"
+        ));
+        let reason = GiveUpReason::ToolUnavailable {
+            tool: "shell".to_string(),
+        };
+        assert!(matches!(
+            SubmissionResult::finished("partial", Some(reason.clone())),
+            SubmissionResult::GaveUp { reason: r, content } if r == reason && content == "partial"
+        ));
+    }
+
+    #[test]
+    fn test_parser_max_iterations() {
+        assert!(matches!(
+            SubmissionParser::parse("/iterations"),
+            Submission::MaxIterations { change: None }
+        ));
+        assert!(matches!(
+            SubmissionParser::parse("/iterations 120"),
+            Submission::MaxIterations {
+                change: Some(IterationLimitChange::Set(120))
+            }
+        ));
+        assert!(matches!(
+            SubmissionParser::parse("/Iterations reset"),
+            Submission::MaxIterations {
+                change: Some(IterationLimitChange::Reset)
+            }
+        ));
+        // Zero and junk are not limits.
+        assert!(matches!(
+            SubmissionParser::parse("/iterations 0"),
+            Submission::UserInput { .. }
+        ));
+        assert!(matches!(
+            SubmissionParser::parse("/iterations lots"),
+            Submission::UserInput { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parser_read_only() {
+        assert!(matches!(
+            SubmissionParser::parse("/readonly"),
+            Submission::ReadOnly { enabled: None }
+        ));
+        assert!(matches!(
+            SubmissionParser::parse("/ReadOnly on"),
+            Submission::ReadOnly {
+                enabled: Some(true)
+            }
+        ));
+        assert!(matches!(
+            SubmissionParser::parse("/readonly off"),
+            Submission::ReadOnly {
+                enabled: Some(false)
+            }
+        ));
+        assert!(matches!(
+            SubmissionParser::parse("/readonly maybe"),
+            Submission::UserInput { .. }
+        ));
+    }
+
     #[test]
     fn test_parser_invalid_commands_become_user_input() {
         // Invalid UUID should become user input
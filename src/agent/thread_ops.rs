@@ -12,14 +12,17 @@ use uuid::Uuid;
 use crate::agent::Agent;
 use crate::agent::compaction::ContextCompactor;
 use crate::agent::dispatcher::{
-    AgenticLoopResult, check_auth_required, execute_chat_tool_standalone, parse_auth_result,
+    AgenticLoopResult, ChatToolOutput, check_auth_required, check_read_only,
+    execute_chat_tool_standalone, parse_auth_result, split_partial,
 };
-use crate::agent::session::{PendingApproval, Session, ThreadState};
+use crate::agent::session::{PendingApproval, Session, Thread, ThreadState};
 use crate::agent::submission::SubmissionResult;
 use crate::channels::{IncomingMessage, StatusUpdate};
 use crate::context::JobContext;
-use crate::error::Error;
+use crate::db::Database;
+use crate::error::{DatabaseError, Error};
 use crate::llm::ChatMessage;
+use crate::tools::ordering::{PlannedCall, execution_waves, take_depends_on};
 
 impl Agent {
     /// Hydrate a historical thread from DB into memory if not already present.
@@ -152,13 +155,8 @@ impl Agent {
 
         // Safety validation for user input
         let validation = self.safety().validate_input(content);
-        if !validation.is_valid {
-            let details = validation
-                .errors
-                .iter()
-                .map(|e| format!("{}: {}", e.field, e.message))
-                .collect::<Vec<_>>()
-                .join("; ");
+        if !validation.is_valid() {
+            let details = validation.describe_errors();
             return Ok(SubmissionResult::error(format!(
                 "Input rejected by safety validation: {}",
                 details
@@ -214,7 +212,8 @@ impl Agent {
                     )
                     .await;
 
-                let compactor = ContextCompactor::new(self.llm().clone(), self.safety().clone());
+                let compactor = ContextCompactor::new(self.llm().clone(), self.safety().clone())
+                    .with_sampling(self.config.sampling.summary);
                 if let Err(e) = compactor
                     .compact(thread, strategy, self.workspace().map(|w| w.as_ref()))
                     .await
@@ -291,8 +290,8 @@ impl Agent {
         }
 
         // Complete, fail, or request approval
-        match result {
-            Ok(AgenticLoopResult::Response(response)) => {
+        match result.map(AgenticLoopResult::into_reply) {
+            Ok(Ok((response, gave_up))) => {
                 // Hook: TransformResponse — allow hooks to modify or reject the final response
                 let response = {
                     let event = crate::hooks::HookEvent::ResponseTransform {
@@ -328,9 +327,9 @@ impl Agent {
                 self.persist_assistant_response(thread_id, &message.user_id, &response)
                     .await;
 
-                Ok(SubmissionResult::response(response))
+                Ok(SubmissionResult::finished(response, gave_up))
             }
-            Ok(AgenticLoopResult::NeedApproval { pending }) => {
+            Ok(Err(pending)) => {
                 // Store pending approval in thread and update state
                 let request_id = pending.request_id;
                 let tool_name = pending.tool_name.clone();
@@ -533,7 +532,8 @@ impl Agent {
                 crate::agent::context_monitor::CompactionStrategy::Summarize { keep_recent: 5 },
             );
 
-        let compactor = ContextCompactor::new(self.llm().clone(), self.safety().clone());
+        let compactor = ContextCompactor::new(self.llm().clone(), self.safety().clone())
+            .with_sampling(self.config.sampling.summary);
         match compactor
             .compact(thread, strategy, self.workspace().map(|w| w.as_ref()))
             .await
@@ -651,20 +651,25 @@ impl Agent {
                 )
                 .await;
 
-            let tool_result = self
-                .execute_chat_tool(&pending.tool_name, &pending.parameters, &job_ctx)
-                .await;
+            let read_only = self.read_only_mode(&session).await;
+            let (tool_result, partial) = split_partial(
+                match check_read_only(self.tools(), read_only, &pending.tool_name).await {
+                    Ok(()) => {
+                        self.execute_chat_tool(&pending.tool_name, &pending.parameters, &job_ctx)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                },
+            );
 
+            let completed = StatusUpdate::ToolCompleted {
+                name: pending.tool_name.clone(),
+                success: tool_result.is_ok(),
+            };
+            let _ = self.channels.react_to_status(message, &completed).await;
             let _ = self
                 .channels
-                .send_status(
-                    &message.channel,
-                    StatusUpdate::ToolCompleted {
-                        name: pending.tool_name.clone(),
-                        success: tool_result.is_ok(),
-                    },
-                    &message.metadata,
-                )
+                .send_status(&message.channel, completed, &message.metadata)
                 .await;
 
             if let Ok(ref output) = tool_result
@@ -693,14 +698,9 @@ impl Agent {
                 if let Some(thread) = sess.threads.get_mut(&thread_id)
                     && let Some(turn) = thread.last_turn_mut()
                 {
-                    match &tool_result {
-                        Ok(output) => {
-                            turn.record_tool_result(serde_json::json!(output));
-                        }
-                        Err(e) => {
-                            turn.record_tool_error(e.to_string());
-                        }
-                    }
+                    turn.record_tool_outcome(&tool_result, partial);
+                    turn.loop_guard
+                        .record_outcome(&pending.tool_name, &tool_result);
                 }
             }
 
@@ -722,19 +722,9 @@ impl Agent {
             }
 
             // Add tool result to context
-            let result_content = match tool_result {
-                Ok(output) => {
-                    let sanitized = self
-                        .safety()
-                        .sanitize_tool_output(&pending.tool_name, &output);
-                    self.safety().wrap_for_llm(
-                        &pending.tool_name,
-                        &sanitized.content,
-                        sanitized.was_modified,
-                    )
-                }
-                Err(e) => format!("Error: {}", e),
-            };
+            let result_content = self
+                .tool_result_content(&pending.tool_name, &tool_result)
+                .await;
 
             context_messages.push(ChatMessage::tool_result(
                 &pending.tool_call_id,
@@ -761,8 +751,11 @@ impl Agent {
 
             // === Phase 1: Preflight (sequential) ===
             // Walk deferred tools checking approval. Collect runnable
-            // tools; stop at the first that needs approval.
+            // tools; stop at the first that needs approval. Calls rejected
+            // by read-only mode get their error outcome up front.
             let mut runnable: Vec<crate::llm::ToolCall> = Vec::new();
+            let mut planned: Vec<PlannedCall> = Vec::new();
+            let mut outcomes: Vec<Option<Result<ChatToolOutput, Error>>> = Vec::new();
             let mut approval_needed: Option<(
                 usize,
                 crate::llm::ToolCall,
@@ -770,6 +763,31 @@ impl Agent {
             )> = None;
 
             for (idx, tc) in deferred_tool_calls.iter().enumerate() {
+                // Hints were resolved to call IDs when the response arrived.
+                let mut tc = tc.clone();
+                let depends_on = take_depends_on(&mut tc.arguments);
+
+                if let Err(e) = check_read_only(self.tools(), read_only, &tc.name).await {
+                    planned.push(PlannedCall {
+                        id: tc.id.clone(),
+                        ..Default::default()
+                    });
+                    runnable.push(tc);
+                    outcomes.push(Some(Err(e)));
+                    continue;
+                }
+
+                // Count the call toward the turn's repeats; the approved call
+                // was counted before the turn paused.
+                {
+                    let mut sess = session.lock().await;
+                    if let Some(thread) = sess.threads.get_mut(&thread_id)
+                        && let Some(turn) = thread.last_turn_mut()
+                    {
+                        turn.loop_guard.record(&tc.name, &tc.arguments);
+                    }
+                }
+
                 if let Some(tool) = self.tools().get(&tc.name).await {
                     use crate::tools::ApprovalRequirement;
                     let needs_approval = match tool.requires_approval(&tc.arguments) {
@@ -782,73 +800,80 @@ impl Agent {
                     };
 
                     if needs_approval {
-                        approval_needed = Some((idx, tc.clone(), tool));
+                        approval_needed = Some((idx, tc, tool));
                         break; // remaining tools stay deferred
                     }
                 }
 
-                runnable.push(tc.clone());
+                planned.push(
+                    PlannedCall::new(self.tools(), &tc.id, &tc.name, &tc.arguments, depends_on)
+                        .await,
+                );
+                runnable.push(tc);
+                outcomes.push(None);
             }
 
-            // === Phase 2: Parallel execution ===
-            let exec_results: Vec<(crate::llm::ToolCall, Result<String, Error>)> = if runnable.len()
-                <= 1
-            {
-                // Single tool (or none): execute inline
-                let mut results = Vec::new();
-                for tc in &runnable {
-                    let _ = self
-                        .channels
-                        .send_status(
-                            &message.channel,
-                            StatusUpdate::ToolStarted {
-                                name: tc.name.clone(),
-                            },
-                            &message.metadata,
-                        )
-                        .await;
+            // === Phase 2: Parallel execution, one wave at a time ===
+            // Calls in a wave are independent; a wave starts once every
+            // call it depends on has finished.
+            for wave in execution_waves(&planned) {
+                let wave: Vec<usize> = wave
+                    .into_iter()
+                    .filter(|&i| outcomes[i].is_none())
+                    .collect();
+                if wave.len() <= 1 {
+                    // Single tool: execute inline
+                    for &idx in &wave {
+                        let tc = &runnable[idx];
+                        let _ = self
+                            .channels
+                            .send_status(
+                                &message.channel,
+                                StatusUpdate::ToolStarted {
+                                    name: tc.name.clone(),
+                                },
+                                &message.metadata,
+                            )
+                            .await;
 
-                    let result = self
-                        .execute_chat_tool(&tc.name, &tc.arguments, &job_ctx)
-                        .await;
+                        let result = self
+                            .execute_chat_tool(&tc.name, &tc.arguments, &job_ctx)
+                            .await;
 
-                    let _ = self
-                        .channels
-                        .send_status(
-                            &message.channel,
-                            StatusUpdate::ToolCompleted {
-                                name: tc.name.clone(),
-                                success: result.is_ok(),
-                            },
-                            &message.metadata,
-                        )
-                        .await;
+                        let completed = StatusUpdate::ToolCompleted {
+                            name: tc.name.clone(),
+                            success: result.is_ok(),
+                        };
+                        let _ = self.channels.react_to_status(message, &completed).await;
+                        let _ = self
+                            .channels
+                            .send_status(&message.channel, completed, &message.metadata)
+                            .await;
 
-                    results.push((tc.clone(), result));
+                        outcomes[idx] = Some(result);
+                    }
+                    continue;
                 }
-                results
-            } else {
+
                 // Multiple tools: execute in parallel via JoinSet
                 let mut join_set = JoinSet::new();
-                let runnable_count = runnable.len();
 
-                for (spawn_idx, tc) in runnable.iter().enumerate() {
+                for &idx in &wave {
                     let tools = self.tools().clone();
                     let safety = self.safety().clone();
                     let channels = self.channels.clone();
                     let job_ctx = job_ctx.clone();
-                    let tc = tc.clone();
-                    let channel = message.channel.clone();
-                    let metadata = message.metadata.clone();
+                    let tc = runnable[idx].clone();
+                    let message = message.clone();
 
                     join_set.spawn(async move {
                         let _ = channels
                             .send_status(
-                                &channel,
+                                &message.channel,
                                 StatusUpdate::ToolStarted {
                                     name: tc.name.clone(),
                                 },
-                                &metadata,
+                                &message.metadata,
                             )
                             .await;
 
@@ -861,28 +886,23 @@ impl Agent {
                         )
                         .await;
 
+                        let completed = StatusUpdate::ToolCompleted {
+                            name: tc.name.clone(),
+                            success: result.is_ok(),
+                        };
+                        let _ = channels.react_to_status(&message, &completed).await;
                         let _ = channels
-                            .send_status(
-                                &channel,
-                                StatusUpdate::ToolCompleted {
-                                    name: tc.name.clone(),
-                                    success: result.is_ok(),
-                                },
-                                &metadata,
-                            )
+                            .send_status(&message.channel, completed, &message.metadata)
                             .await;
 
-                        (spawn_idx, tc, result)
+                        (idx, result)
                     });
                 }
 
-                // Collect and reorder by original index
-                let mut ordered: Vec<Option<(crate::llm::ToolCall, Result<String, Error>)>> =
-                    (0..runnable_count).map(|_| None).collect();
                 while let Some(join_result) = join_set.join_next().await {
                     match join_result {
-                        Ok((idx, tc, result)) => {
-                            ordered[idx] = Some((tc, result));
+                        Ok((idx, result)) => {
+                            outcomes[idx] = Some(result);
                         }
                         Err(e) => {
                             if e.is_panic() {
@@ -893,31 +913,30 @@ impl Agent {
                         }
                     }
                 }
+            }
 
-                // Fill panicked slots with error results
-                ordered
-                    .into_iter()
-                    .enumerate()
-                    .map(|(i, opt)| {
-                        opt.unwrap_or_else(|| {
-                            let tc = runnable[i].clone();
-                            let err: Error = crate::error::ToolError::ExecutionFailed {
-                                name: tc.name.clone(),
-                                reason: "Task failed during execution".to_string(),
-                            }
-                            .into();
-                            (tc, Err(err))
-                        })
-                    })
-                    .collect()
-            };
+            // Fill panicked slots with error results, keeping original order
+            let exec_results: Vec<(crate::llm::ToolCall, Result<String, Error>, bool)> = runnable
+                .into_iter()
+                .zip(outcomes)
+                .map(|(tc, outcome)| {
+                    let (result, partial) = split_partial(outcome.unwrap_or_else(|| {
+                        Err(crate::error::ToolError::ExecutionFailed {
+                            name: tc.name.clone(),
+                            reason: "Task failed during execution".to_string(),
+                        }
+                        .into())
+                    }));
+                    (tc, result, partial)
+                })
+                .collect();
 
             // === Phase 3: Post-flight (sequential, in original order) ===
             // Process all results before any conditional return so every
             // tool result is recorded in the session audit trail.
             let mut deferred_auth: Option<String> = None;
 
-            for (tc, deferred_result) in exec_results {
+            for (tc, deferred_result, partial) in exec_results {
                 if let Ok(ref output) = deferred_result
                     && !output.is_empty()
                 {
@@ -940,10 +959,8 @@ impl Agent {
                     if let Some(thread) = sess.threads.get_mut(&thread_id)
                         && let Some(turn) = thread.last_turn_mut()
                     {
-                        match &deferred_result {
-                            Ok(output) => turn.record_tool_result(serde_json::json!(output)),
-                            Err(e) => turn.record_tool_error(e.to_string()),
-                        }
+                        turn.record_tool_outcome(&deferred_result, partial);
+                        turn.loop_guard.record_outcome(&tc.name, &deferred_result);
                     }
                 }
 
@@ -964,17 +981,7 @@ impl Agent {
                     deferred_auth = Some(instructions);
                 }
 
-                let deferred_content = match deferred_result {
-                    Ok(output) => {
-                        let sanitized = self.safety().sanitize_tool_output(&tc.name, &output);
-                        self.safety().wrap_for_llm(
-                            &tc.name,
-                            &sanitized.content,
-                            sanitized.was_modified,
-                        )
-                    }
-                    Err(e) => format!("Error: {}", e),
-                };
+                let deferred_content = self.tool_result_content(&tc.name, &deferred_result).await;
 
                 context_messages.push(ChatMessage::tool_result(&tc.id, &tc.name, deferred_content));
             }
@@ -1037,8 +1044,8 @@ impl Agent {
                 .get_mut(&thread_id)
                 .ok_or_else(|| Error::from(crate::error::JobError::NotFound { id: thread_id }))?;
 
-            match result {
-                Ok(AgenticLoopResult::Response(response)) => {
+            match result.map(AgenticLoopResult::into_reply) {
+                Ok(Ok((response, gave_up))) => {
                     thread.complete_turn(&response);
                     // User message already persisted at turn start; save assistant response
                     self.persist_assistant_response(thread_id, &message.user_id, &response)
@@ -1051,11 +1058,9 @@ impl Agent {
                             &message.metadata,
                         )
                         .await;
-                    Ok(SubmissionResult::response(response))
+                    Ok(SubmissionResult::finished(response, gave_up))
                 }
-                Ok(AgenticLoopResult::NeedApproval {
-                    pending: new_pending,
-                }) => {
+                Ok(Err(new_pending)) => {
                     let request_id = new_pending.request_id;
                     let tool_name = new_pending.tool_name.clone();
                     let description = new_pending.description.clone();
@@ -1329,6 +1334,68 @@ impl Agent {
         }
     }
 
+    pub(super) async fn process_branch(
+        &self,
+        message: &IncomingMessage,
+        session: Arc<Mutex<Session>>,
+        thread_id: Uuid,
+        at_turn: Option<usize>,
+    ) -> Result<SubmissionResult, Error> {
+        let branch = {
+            let mut sess = session.lock().await;
+            let Some(turn_count) = sess.threads.get(&thread_id).map(|t| t.turns.len()) else {
+                return Ok(SubmissionResult::error("Thread not found."));
+            };
+            let at_turn = at_turn.unwrap_or(turn_count);
+            match sess
+                .branch_thread(thread_id, at_turn)
+                .and_then(|id| sess.threads.get(&id).cloned())
+            {
+                Some(branch) => branch,
+                None => {
+                    return Ok(SubmissionResult::error(format!(
+                        "Cannot branch at turn {}: thread has {} turn(s).",
+                        at_turn, turn_count
+                    )));
+                }
+            }
+        };
+
+        // A channel without external thread ids continues in the branch. One
+        // with them keeps its current thread where it is; the branch is
+        // addressed by its own id, which is returned below.
+        match message.thread_id.as_deref() {
+            None => {
+                self.session_manager
+                    .bind_thread(&message.user_id, &message.channel, None, branch.id)
+                    .await;
+            }
+            Some(_) => {
+                self.session_manager
+                    .register_thread(
+                        &message.user_id,
+                        &message.channel,
+                        branch.id,
+                        Arc::clone(&session),
+                    )
+                    .await;
+            }
+        }
+
+        if let Some(store) = self.store()
+            && let Err(e) =
+                persist_branch(store.as_ref(), &message.channel, &message.user_id, &branch).await
+        {
+            tracing::warn!("Failed to persist branch {}: {}", branch.id, e);
+        }
+
+        Ok(SubmissionResult::ok_with_message(format!(
+            "Branched at turn {} into thread {}",
+            branch.turns.len(),
+            branch.id
+        )))
+    }
+
     pub(super) async fn process_resume(
         &self,
         session: Arc<Mutex<Session>>,
@@ -1354,3 +1421,40 @@ impl Agent {
         }
     }
 }
+
+/// Write a branched thread's copied history to the database.
+///
+/// The branch gets its own conversation row, tagged with the thread it came
+/// from, so it shows up in the thread list and survives restarts.
+pub(crate) async fn persist_branch(
+    store: &dyn Database,
+    channel: &str,
+    user_id: &str,
+    branch: &Thread,
+) -> Result<(), DatabaseError> {
+    store
+        .ensure_conversation(branch.id, channel, user_id, None)
+        .await?;
+    store
+        .update_conversation_metadata_field(branch.id, "thread_type", &serde_json::json!("thread"))
+        .await?;
+    if let Some(fields) = branch.metadata.as_object() {
+        for (key, value) in fields {
+            store
+                .update_conversation_metadata_field(branch.id, key, value)
+                .await?;
+        }
+    }
+
+    for turn in &branch.turns {
+        store
+            .add_conversation_message(branch.id, "user", &turn.user_input)
+            .await?;
+        if let Some(ref response) = turn.response {
+            store
+                .add_conversation_message(branch.id, "assistant", response)
+                .await?;
+        }
+    }
+    Ok(())
+}
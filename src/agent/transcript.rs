@@ -0,0 +1,836 @@
+//! Replayable transcripts of agent runs.
+//!
+//! A [`TranscriptRecorder`] appends one JSON object per line for every
+//! inbound message, LLM request and response, tool call and outbound
+//! response. Recording is wired in from two places: [`TranscriptHook`]
+//! observes the inbound/tool/outbound hook points, and [`TranscriptLlm`]
+//! wraps the LLM providers. The recorder is also a [`SafetyObserver`], so
+//! safety-layer decisions (redacted or blocked tool output, rejected input)
+//! are recorded as they happen. Every string is run through the
+//! [`LeakDetector`] before it is written, so API keys and tokens that pass
+//! through a run do not end up in the file.
+//!
+//! [`replay`] feeds the recorded inbound messages back into an agent whose
+//! LLM answers with the recorded responses, in order. Tools still execute
+//! for real; only the model is stubbed.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Agent, AgentDeps};
+use crate::channels::{Channel, ChannelManager, IncomingMessage, MessageStream, OutgoingResponse};
+use crate::config::AgentConfig;
+use crate::error::{ChannelError, Error, LlmError};
+use crate::hooks::{Hook, HookContext, HookError, HookEvent, HookOutcome, HookPoint};
+use crate::llm::{
+    ChatMessage, CircuitSnapshot, CompletionRequest, CompletionResponse, CompletionStream,
+    FinishReason, LlmProvider, ModelMetadata, StreamEvent, ToolCall, ToolCompletionRequest,
+    ToolCompletionResponse,
+};
+use crate::safety::{LeakDetector, SafetyDecision, SafetyObserver};
+
+/// Name of the channel replayed messages arrive on.
+const REPLAY_CHANNEL: &str = "replay";
+
+/// One step of an agent run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEvent {
+    /// A user message accepted by the agent.
+    Inbound {
+        channel: String,
+        user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+        content: String,
+    },
+    /// A request sent to the LLM.
+    LlmRequest {
+        model: String,
+        messages: Vec<ChatMessage>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tools: Vec<String>,
+    },
+    /// The LLM's answer to the preceding request.
+    LlmResponse {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tool_calls: Vec<ToolCall>,
+        finish_reason: FinishReason,
+        input_tokens: u32,
+        output_tokens: u32,
+    },
+    /// The LLM request failed.
+    LlmError { message: String },
+    /// A tool call about to be executed.
+    ToolCall {
+        tool_name: String,
+        parameters: serde_json::Value,
+        context: String,
+    },
+    /// The safety layer modified or rejected content.
+    Safety {
+        check: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        subject: String,
+        blocked: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reasons: Vec<String>,
+    },
+    /// A response about to be sent back to the user.
+    Outbound {
+        channel: String,
+        user_id: String,
+        content: String,
+    },
+}
+
+/// A timestamped line of a transcript file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: TranscriptEvent,
+}
+
+/// Errors reading a transcript back.
+#[derive(Debug, thiserror::Error)]
+pub enum TranscriptError {
+    #[error("Failed to read transcript {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid transcript entry on line {line}: {source}")]
+    Parse {
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+/// Appends redacted [`TranscriptEntry`] lines to a file.
+pub struct TranscriptRecorder {
+    path: PathBuf,
+    file: Mutex<File>,
+    detector: LeakDetector,
+}
+
+impl TranscriptRecorder {
+    /// Open `path` for appending, creating it if needed.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            detector: LeakDetector::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Flush written entries to disk.
+    pub fn flush(&self) -> std::io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.flush()?;
+        file.sync_data()
+    }
+
+    /// Redact and append `event`. Write failures are logged, never returned:
+    /// a broken transcript must not break the run it is recording.
+    pub fn record(&self, event: TranscriptEvent) {
+        let entry = TranscriptEntry {
+            at: Utc::now(),
+            event,
+        };
+        let mut value = match serde_json::to_value(&entry) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Failed to serialize transcript entry: {}", e);
+                return;
+            }
+        };
+        redact_value(&self.detector, &mut value);
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{value}") {
+            tracing::warn!(path = %self.path.display(), "Failed to write transcript: {}", e);
+        }
+    }
+}
+
+impl SafetyObserver for TranscriptRecorder {
+    fn on_decision(&self, decision: &SafetyDecision) {
+        self.record(TranscriptEvent::Safety {
+            check: decision.check.to_string(),
+            subject: decision.subject.clone(),
+            blocked: decision.blocked,
+            reasons: decision.reasons.clone(),
+        });
+    }
+}
+
+/// Replace every leak-detector match inside the strings of `value`.
+pub(crate) fn redact_value(detector: &LeakDetector, value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some(redacted) = redact_str(detector, s) {
+                *s = redacted;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                redact_value(detector, item);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for item in map.values_mut() {
+                redact_value(detector, item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Redact all matches in `s`, including ones the detector would block
+/// rather than redact, or `None` if `s` is clean.
+pub(crate) fn redact_str(detector: &LeakDetector, s: &str) -> Option<String> {
+    let scan = detector.scan(s);
+    if scan.is_clean() {
+        return None;
+    }
+    Some(scan.redact_all(s))
+}
+
+/// Read every entry of a transcript file.
+pub fn read_transcript(path: impl AsRef<Path>) -> Result<Vec<TranscriptEntry>, TranscriptError> {
+    let path = path.as_ref();
+    let io_err = |source| TranscriptError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = File::open(path).map_err(io_err)?;
+
+    let mut entries = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(io_err)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(|source| TranscriptError::Parse {
+            line: idx + 1,
+            source,
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Records inbound messages, tool calls and outbound responses.
+pub struct TranscriptHook {
+    recorder: Arc<TranscriptRecorder>,
+}
+
+impl TranscriptHook {
+    pub fn new(recorder: Arc<TranscriptRecorder>) -> Self {
+        Self { recorder }
+    }
+}
+
+#[async_trait]
+impl Hook for TranscriptHook {
+    fn name(&self) -> &str {
+        "builtin.transcript"
+    }
+
+    fn hook_points(&self) -> &[HookPoint] {
+        &[
+            HookPoint::BeforeInbound,
+            HookPoint::BeforeToolCall,
+            HookPoint::BeforeOutbound,
+        ]
+    }
+
+    async fn execute(
+        &self,
+        event: &HookEvent,
+        _ctx: &HookContext,
+    ) -> Result<HookOutcome, HookError> {
+        let event = match event {
+            HookEvent::Inbound {
+                user_id,
+                channel,
+                content,
+                thread_id,
+            } => TranscriptEvent::Inbound {
+                channel: channel.clone(),
+                user_id: user_id.clone(),
+                thread_id: thread_id.clone(),
+                content: content.clone(),
+            },
+            HookEvent::ToolCall {
+                tool_name,
+                parameters,
+                context,
+                ..
+            } => TranscriptEvent::ToolCall {
+                tool_name: tool_name.clone(),
+                parameters: parameters.clone(),
+                context: context.clone(),
+            },
+            HookEvent::Outbound {
+                user_id,
+                channel,
+                content,
+                ..
+            } => TranscriptEvent::Outbound {
+                channel: channel.clone(),
+                user_id: user_id.clone(),
+                content: content.clone(),
+            },
+            _ => return Ok(HookOutcome::ok()),
+        };
+        self.recorder.record(event);
+        Ok(HookOutcome::ok())
+    }
+}
+
+/// LLM provider wrapper that records every request and response.
+pub struct TranscriptLlm {
+    inner: Arc<dyn LlmProvider>,
+    recorder: Arc<TranscriptRecorder>,
+}
+
+impl TranscriptLlm {
+    pub fn new(inner: Arc<dyn LlmProvider>, recorder: Arc<TranscriptRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+
+    fn record_request(&self, model: Option<&str>, messages: &[ChatMessage], tools: Vec<String>) {
+        self.recorder.record(TranscriptEvent::LlmRequest {
+            model: self.inner.effective_model_name(model),
+            messages: messages.to_vec(),
+            tools,
+        });
+    }
+
+    fn record_error(&self, error: &LlmError) {
+        self.recorder.record(TranscriptEvent::LlmError {
+            message: error.to_string(),
+        });
+    }
+}
+
+#[async_trait]
+impl LlmProvider for TranscriptLlm {
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        self.inner.cost_per_token()
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        self.record_request(request.model.as_deref(), &request.messages, Vec::new());
+        match self.inner.complete(request).await {
+            Ok(response) => {
+                self.recorder.record(TranscriptEvent::LlmResponse {
+                    content: Some(response.content.clone()),
+                    tool_calls: Vec::new(),
+                    finish_reason: response.finish_reason,
+                    input_tokens: response.input_tokens,
+                    output_tokens: response.output_tokens,
+                });
+                Ok(response)
+            }
+            Err(e) => {
+                self.record_error(&e);
+                Err(e)
+            }
+        }
+    }
+
+    async fn complete_with_tools(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        let tools = request.tools.iter().map(|t| t.name.clone()).collect();
+        self.record_request(request.model.as_deref(), &request.messages, tools);
+        match self.inner.complete_with_tools(request).await {
+            Ok(response) => {
+                self.recorder.record(TranscriptEvent::LlmResponse {
+                    content: response.content.clone(),
+                    tool_calls: response.tool_calls.clone(),
+                    finish_reason: response.finish_reason,
+                    input_tokens: response.input_tokens,
+                    output_tokens: response.output_tokens,
+                });
+                Ok(response)
+            }
+            Err(e) => {
+                self.record_error(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// The streamed reply is recorded as one `LlmResponse` once it ends.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let tools = request.tools.iter().map(|t| t.name.clone()).collect();
+        self.record_request(request.model.as_deref(), &request.messages, tools);
+        let stream = match self.inner.complete_stream(request).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                self.record_error(&e);
+                return Err(e);
+            }
+        };
+
+        let recorder = Arc::clone(&self.recorder);
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        Ok(Box::pin(stream.inspect(move |event| match event {
+            Ok(StreamEvent::TextDelta(delta)) => content.push_str(delta),
+            Ok(StreamEvent::ToolCall(call)) => tool_calls.push(call.clone()),
+            Ok(StreamEvent::Done {
+                input_tokens,
+                output_tokens,
+                finish_reason,
+            }) => recorder.record(TranscriptEvent::LlmResponse {
+                content: (!content.is_empty()).then(|| std::mem::take(&mut content)),
+                tool_calls: std::mem::take(&mut tool_calls),
+                finish_reason: *finish_reason,
+                input_tokens: *input_tokens,
+                output_tokens: *output_tokens,
+            }),
+            Err(e) => recorder.record(TranscriptEvent::LlmError {
+                message: e.to_string(),
+            }),
+        })))
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.inner.list_models().await
+    }
+
+    async fn model_metadata(&self) -> Result<ModelMetadata, LlmError> {
+        self.inner.model_metadata().await
+    }
+
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        self.inner.effective_model_name(requested_model)
+    }
+
+    fn active_model_name(&self) -> String {
+        self.inner.active_model_name()
+    }
+
+    fn set_model(&self, model: &str) -> Result<(), LlmError> {
+        self.inner.set_model(model)
+    }
+
+    fn circuit_snapshot(&self) -> Option<CircuitSnapshot> {
+        self.inner.circuit_snapshot()
+    }
+}
+
+/// A recorded LLM outcome.
+enum Scripted {
+    Response(ToolCompletionResponse),
+    Error(String),
+}
+
+/// LLM provider that answers with the responses of a transcript, in order.
+pub struct ReplayLlm {
+    model: String,
+    script: Mutex<VecDeque<Scripted>>,
+}
+
+impl ReplayLlm {
+    pub fn from_entries(entries: &[TranscriptEntry]) -> Self {
+        let mut model = String::from("replay");
+        let mut script = VecDeque::new();
+        for entry in entries {
+            match &entry.event {
+                TranscriptEvent::LlmRequest { model: m, .. } if script.is_empty() => {
+                    model = m.clone();
+                }
+                TranscriptEvent::LlmResponse {
+                    content,
+                    tool_calls,
+                    finish_reason,
+                    input_tokens,
+                    output_tokens,
+                } => script.push_back(Scripted::Response(ToolCompletionResponse {
+                    content: content.clone(),
+                    tool_calls: tool_calls.clone(),
+                    input_tokens: *input_tokens,
+                    output_tokens: *output_tokens,
+                    finish_reason: *finish_reason,
+                })),
+                TranscriptEvent::LlmError { message } => {
+                    script.push_back(Scripted::Error(message.clone()))
+                }
+                _ => {}
+            }
+        }
+        Self {
+            model,
+            script: Mutex::new(script),
+        }
+    }
+
+    fn next(&self) -> Result<ToolCompletionResponse, LlmError> {
+        let next = self
+            .script
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front();
+        match next {
+            Some(Scripted::Response(response)) => Ok(response),
+            Some(Scripted::Error(reason)) => Err(LlmError::RequestFailed {
+                provider: "replay".to_string(),
+                reason,
+            }),
+            None => Err(LlmError::RequestFailed {
+                provider: "replay".to_string(),
+                reason: "transcript has no more recorded responses".to_string(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ReplayLlm {
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        (Decimal::ZERO, Decimal::ZERO)
+    }
+
+    async fn complete(&self, _request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        let response = self.next()?;
+        Ok(CompletionResponse {
+            content: response.content.unwrap_or_default(),
+            input_tokens: response.input_tokens,
+            output_tokens: response.output_tokens,
+            finish_reason: response.finish_reason,
+        })
+    }
+
+    async fn complete_with_tools(
+        &self,
+        _request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        self.next()
+    }
+}
+
+/// Yields the recorded inbound messages, then `/quit`, and captures replies.
+struct ReplayChannel {
+    messages: Vec<IncomingMessage>,
+    responses: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Channel for ReplayChannel {
+    fn name(&self) -> &str {
+        REPLAY_CHANNEL
+    }
+
+    async fn start(&self) -> Result<MessageStream, ChannelError> {
+        Ok(Box::pin(stream::iter(self.messages.clone())))
+    }
+
+    async fn respond(
+        &self,
+        _msg: &IncomingMessage,
+        response: OutgoingResponse,
+    ) -> Result<(), ChannelError> {
+        self.responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(response.content);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), ChannelError> {
+        Ok(())
+    }
+}
+
+/// Re-run the inbound messages of a transcript through a fresh agent and
+/// return the responses it sends, in order.
+///
+/// `deps.llm` and `deps.cheap_llm` are replaced by a [`ReplayLlm`] built
+/// from `entries`. All messages arrive on a single `replay` channel so
+/// their recorded order is kept even if they came from several channels.
+pub async fn replay(
+    entries: &[TranscriptEntry],
+    config: AgentConfig,
+    mut deps: AgentDeps,
+) -> Result<Vec<String>, Error> {
+    let mut messages: Vec<IncomingMessage> = entries
+        .iter()
+        .filter_map(|entry| match &entry.event {
+            TranscriptEvent::Inbound {
+                user_id,
+                thread_id,
+                content,
+                ..
+            } => {
+                let msg = IncomingMessage::new(REPLAY_CHANNEL, user_id, content);
+                Some(match thread_id {
+                    Some(thread_id) => msg.with_thread(thread_id),
+                    None => msg,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+    let quit_user = messages
+        .last()
+        .map(|m| m.user_id.clone())
+        .unwrap_or_else(|| "default".to_string());
+    messages.push(IncomingMessage::new(REPLAY_CHANNEL, quit_user, "/quit"));
+
+    deps.llm = Arc::new(ReplayLlm::from_entries(entries));
+    deps.cheap_llm = None;
+
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let channels = Arc::new(ChannelManager::new());
+    channels
+        .add(Box::new(ReplayChannel {
+            messages,
+            responses: Arc::clone(&responses),
+        }))
+        .await;
+
+    Agent::new(config, deps, channels, None, None, None, None, None)
+        .run()
+        .await?;
+
+    let responses = responses.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    Ok(responses)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::agent::cost_guard::{CostGuard, CostGuardConfig};
+    use crate::config::{SafetyConfig, SkillsConfig};
+    use crate::hooks::HookRegistry;
+    use crate::safety::SafetyLayer;
+    use crate::tools::ToolRegistry;
+
+    /// Answers every request with the same text.
+    struct FixedLlm(&'static str);
+
+    #[async_trait]
+    impl LlmProvider for FixedLlm {
+        fn model_name(&self) -> &str {
+            "fixed"
+        }
+
+        fn cost_per_token(&self) -> (Decimal, Decimal) {
+            (Decimal::ZERO, Decimal::ZERO)
+        }
+
+        async fn complete(
+            &self,
+            _request: CompletionRequest,
+        ) -> Result<CompletionResponse, LlmError> {
+            Ok(CompletionResponse {
+                content: self.0.to_string(),
+                input_tokens: 3,
+                output_tokens: 2,
+                finish_reason: FinishReason::Stop,
+            })
+        }
+
+        async fn complete_with_tools(
+            &self,
+            _request: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, LlmError> {
+            Ok(ToolCompletionResponse {
+                content: Some(self.0.to_string()),
+                tool_calls: Vec::new(),
+                input_tokens: 3,
+                output_tokens: 2,
+                finish_reason: FinishReason::Stop,
+            })
+        }
+    }
+
+    fn config() -> AgentConfig {
+        AgentConfig {
+            name: "transcript-test".to_string(),
+            max_parallel_jobs: 1,
+            job_timeout: Duration::from_secs(60),
+            stuck_threshold: Duration::from_secs(60),
+            repair_check_interval: Duration::from_secs(3600),
+            max_repair_attempts: 1,
+            use_planning: false,
+            session_idle_timeout: Duration::from_secs(300),
+            allow_local_tools: false,
+            max_cost_per_day_cents: None,
+            max_actions_per_hour: None,
+            auto_downgrade_at_budget_fraction: None,
+            max_tool_iterations: 5,
+            max_session_tool_iterations: None,
+            auto_approve_tools: false,
+            transcript_path: None,
+            sampling: crate::llm::SamplingProfiles::default(),
+            read_only: false,
+            tool_rate_limits: std::collections::HashMap::new(),
+            reasoning_trace: false,
+        }
+    }
+
+    fn deps(llm: Arc<dyn LlmProvider>, hooks: Arc<HookRegistry>) -> AgentDeps {
+        AgentDeps {
+            store: None,
+            llm,
+            cheap_llm: None,
+            safety: Arc::new(SafetyLayer::new(&SafetyConfig::default())),
+            tools: Arc::new(ToolRegistry::new()),
+            workspace: None,
+            extension_manager: None,
+            skill_registry: None,
+            skill_catalog: None,
+            skills_config: SkillsConfig::default(),
+            hooks,
+            cost_guard: Arc::new(CostGuard::new(CostGuardConfig::default())),
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_run_replays_to_the_same_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.jsonl");
+        let recorder = Arc::new(TranscriptRecorder::create(&path).unwrap());
+
+        let hooks = Arc::new(HookRegistry::new());
+        hooks
+            .register(Arc::new(TranscriptHook::new(Arc::clone(&recorder))))
+            .await;
+        let llm = Arc::new(TranscriptLlm::new(
+            Arc::new(FixedLlm("The answer is 42.")),
+            Arc::clone(&recorder),
+        ));
+
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let channels = Arc::new(ChannelManager::new());
+        channels
+            .add(Box::new(ReplayChannel {
+                messages: vec![
+                    IncomingMessage::new(REPLAY_CHANNEL, "alice", "What is the answer?"),
+                    IncomingMessage::new(REPLAY_CHANNEL, "alice", "/quit"),
+                ],
+                responses: Arc::clone(&responses),
+            }))
+            .await;
+        Agent::new(
+            config(),
+            deps(llm, hooks),
+            channels,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .run()
+        .await
+        .unwrap();
+        let original = responses.lock().unwrap().clone();
+        assert_eq!(original, vec!["The answer is 42."]);
+
+        let entries = read_transcript(&path).unwrap();
+        assert!(entries.iter().any(|e| matches!(
+            &e.event,
+            TranscriptEvent::Inbound { content, .. } if content == "What is the answer?"
+        )));
+        assert!(
+            entries
+                .iter()
+                .any(|e| matches!(e.event, TranscriptEvent::LlmResponse { .. }))
+        );
+
+        // The replaying agent's own LLM would answer differently.
+        let replayed = replay(
+            &entries,
+            config(),
+            deps(
+                Arc::new(FixedLlm("something else")),
+                Arc::new(HookRegistry::new()),
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(replayed, original);
+    }
+
+    #[test]
+    fn secrets_are_redacted_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.jsonl");
+        let recorder = TranscriptRecorder::create(&path).unwrap();
+        let key = "sk-proj-abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGH";
+
+        recorder.record(TranscriptEvent::ToolCall {
+            tool_name: "http".to_string(),
+            parameters: serde_json::json!({ "headers": { "Authorization": format!("Bearer {key}") } }),
+            context: "chat".to_string(),
+        });
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains(key), "secret written to transcript: {raw}");
+        assert!(raw.contains("[REDACTED]"));
+        assert_eq!(read_transcript(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn safety_decisions_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.jsonl");
+        let recorder = Arc::new(TranscriptRecorder::create(&path).unwrap());
+        let safety = deps(Arc::new(FixedLlm("unused")), Arc::new(HookRegistry::new())).safety;
+        safety.set_observer(recorder);
+
+        let key = "sk-proj-abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGH";
+        safety.sanitize_tool_output("http", "plain output");
+        safety.sanitize_tool_output("http", &format!("token={key}"));
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains(key), "secret written to transcript: {raw}");
+        let entries = read_transcript(&path).unwrap();
+        assert_eq!(entries.len(), 1, "{raw}");
+        assert!(matches!(
+            &entries[0].event,
+            TranscriptEvent::Safety { check, subject, reasons, .. }
+                if check == "tool_output" && subject == "http" && !reasons.is_empty()
+        ));
+    }
+}
@@ -7,6 +7,8 @@ use tokio::sync::mpsc;
 use tokio::task::JoinSet;
 use uuid::Uuid;
 
+use crate::agent::GiveUpReason;
+use crate::agent::dispatcher::wrap_tool_output;
 use crate::agent::scheduler::WorkerMessage;
 use crate::agent::task::TaskOutput;
 use crate::context::{ContextManager, JobState};
@@ -14,11 +16,12 @@ use crate::db::Database;
 use crate::error::Error;
 use crate::hooks::HookRegistry;
 use crate::llm::{
-    ActionPlan, ChatMessage, LlmProvider, Reasoning, ReasoningContext, RespondResult, ToolSelection,
+    ActionPlan, ChatMessage, LlmProvider, Reasoning, ReasoningContext, RespondResult,
+    SamplingProfiles, ToolSelection,
 };
 use crate::safety::SafetyLayer;
 use crate::tools::ToolRegistry;
-use crate::tools::rate_limiter::RateLimitResult;
+use crate::tools::ordering::{PlannedCall, execution_waves, resolve_depends_on, take_depends_on};
 
 /// Shared dependencies for worker execution.
 ///
@@ -34,6 +37,7 @@ pub struct WorkerDeps {
     pub hooks: Arc<HookRegistry>,
     pub timeout: Duration,
     pub use_planning: bool,
+    pub sampling: SamplingProfiles,
 }
 
 /// Worker that executes a single job.
@@ -130,7 +134,8 @@ impl Worker {
         let job_ctx = self.context_manager().get_context(self.job_id).await?;
 
         // Create reasoning engine
-        let reasoning = Reasoning::new(self.llm().clone(), self.safety().clone());
+        let reasoning = Reasoning::new(self.llm().clone(), self.safety().clone())
+            .with_sampling_profiles(self.deps.sampling);
 
         // Build initial reasoning context (tool definitions refreshed each iteration in execution_loop)
         let mut reason_ctx = ReasoningContext::new().with_job(&job_ctx.description);
@@ -156,9 +161,13 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
         .await;
 
         match result {
-            Ok(Ok(())) => {
+            Ok(Ok(None)) => {
                 tracing::info!("Worker for job {} completed successfully", self.job_id);
             }
+            Ok(Ok(Some(reason))) => {
+                tracing::warn!(%reason, "Worker for job {} gave up", self.job_id);
+                self.mark_stuck(&reason.to_string()).await?;
+            }
             Ok(Err(e)) => {
                 tracing::error!("Worker for job {} failed: {}", self.job_id, e);
                 self.mark_failed(&e.to_string()).await?;
@@ -172,12 +181,14 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
         Ok(())
     }
 
+    /// Work on the job until it completes, is stopped, or gives up.
+    /// Returns why it gave up, if it did.
     async fn execution_loop(
         &self,
         rx: &mut mpsc::Receiver<WorkerMessage>,
         reasoning: &Reasoning,
         reason_ctx: &mut ReasoningContext,
-    ) -> Result<(), Error> {
+    ) -> Result<Option<GiveUpReason>, Error> {
         const MAX_WORKER_ITERATIONS: usize = 500;
         let max_iterations = self
             .context_manager()
@@ -240,7 +251,8 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
 
         // If we have a plan, execute it
         if let Some(ref plan) = plan {
-            return self.execute_plan(rx, reasoning, reason_ctx, plan).await;
+            self.execute_plan(rx, reasoning, reason_ctx, plan).await?;
+            return Ok(None);
         }
 
         // Otherwise, use direct tool selection loop
@@ -250,7 +262,7 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
                 match msg {
                     WorkerMessage::Stop => {
                         tracing::debug!("Worker for job {} received stop signal", self.job_id);
-                        return Ok(());
+                        return Ok(None);
                     }
                     WorkerMessage::Ping => {
                         tracing::trace!("Worker for job {} received ping", self.job_id);
@@ -264,13 +276,14 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
                 && ctx.state == JobState::Cancelled
             {
                 tracing::info!("Worker for job {} detected cancellation", self.job_id);
-                return Ok(());
+                return Ok(None);
             }
 
             iteration += 1;
             if iteration > max_iterations {
-                self.mark_stuck("Maximum iterations exceeded").await?;
-                return Ok(());
+                return Ok(Some(GiveUpReason::IterationLimit {
+                    limit: max_iterations,
+                }));
             }
 
             // Refresh tool definitions so newly built tools become visible
@@ -291,7 +304,7 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
                         // (not tool output) can trigger this.
                         if crate::util::llm_signals_completion(&response) {
                             self.mark_completed().await?;
-                            return Ok(());
+                            return Ok(None);
                         }
 
                         // Add assistant response to context
@@ -400,50 +413,76 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
 
     /// Execute multiple tools in parallel using a JoinSet.
     ///
-    /// Each task is tagged with its original index so results are returned
-    /// in the same order as `selections`, regardless of completion order.
+    /// Calls are grouped into waves by their `_depends_on` hints and the
+    /// resources they touch (see [`crate::tools::ordering`]); each wave runs
+    /// in parallel once the previous one has finished. Each task is tagged
+    /// with its original index so results are returned in the same order as
+    /// `selections`, regardless of completion order.
     async fn execute_tools_parallel(&self, selections: &[ToolSelection]) -> Vec<ToolExecResult> {
         let count = selections.len();
 
-        // Short-circuit for single tool: execute directly without JoinSet overhead
-        if count <= 1 {
-            let mut results = Vec::with_capacity(count);
-            for selection in selections {
-                let result = Self::execute_tool_inner(
-                    &self.deps,
-                    self.job_id,
+        let mut params: Vec<serde_json::Value> =
+            selections.iter().map(|s| s.parameters.clone()).collect();
+        resolve_depends_on(
+            selections
+                .iter()
+                .map(|s| s.tool_call_id.as_str())
+                .zip(params.iter_mut()),
+        );
+        let mut planned = Vec::with_capacity(count);
+        for (selection, params) in selections.iter().zip(params.iter_mut()) {
+            let depends_on = take_depends_on(params);
+            planned.push(
+                PlannedCall::new(
+                    &self.deps.tools,
+                    &selection.tool_call_id,
                     &selection.tool_name,
-                    &selection.parameters,
+                    params,
+                    depends_on,
                 )
-                .await;
-                results.push(ToolExecResult { result });
-            }
-            return results;
+                .await,
+            );
         }
 
-        let mut join_set = JoinSet::new();
+        let mut results: Vec<Option<ToolExecResult>> = (0..count).map(|_| None).collect();
+        for wave in execution_waves(&planned) {
+            // Short-circuit for single tool: execute directly without JoinSet overhead
+            if wave.len() <= 1 {
+                for &idx in &wave {
+                    let result = Self::execute_tool_inner(
+                        &self.deps,
+                        self.job_id,
+                        &selections[idx].tool_name,
+                        &params[idx],
+                    )
+                    .await;
+                    results[idx] = Some(ToolExecResult { result });
+                }
+                continue;
+            }
 
-        for (idx, selection) in selections.iter().enumerate() {
-            let deps = self.deps.clone();
-            let job_id = self.job_id;
-            let tool_name = selection.tool_name.clone();
-            let params = selection.parameters.clone();
-            join_set.spawn(async move {
-                let result = Self::execute_tool_inner(&deps, job_id, &tool_name, &params).await;
-                (idx, ToolExecResult { result })
-            });
-        }
+            let mut join_set = JoinSet::new();
+
+            for &idx in &wave {
+                let deps = self.deps.clone();
+                let job_id = self.job_id;
+                let tool_name = selections[idx].tool_name.clone();
+                let params = params[idx].clone();
+                join_set.spawn(async move {
+                    let result = Self::execute_tool_inner(&deps, job_id, &tool_name, &params).await;
+                    (idx, ToolExecResult { result })
+                });
+            }
 
-        // Collect and reorder by original index
-        let mut results: Vec<Option<ToolExecResult>> = (0..count).map(|_| None).collect();
-        while let Some(join_result) = join_set.join_next().await {
-            match join_result {
-                Ok((idx, exec_result)) => results[idx] = Some(exec_result),
-                Err(e) => {
-                    if e.is_panic() {
-                        tracing::error!("Tool execution task panicked: {}", e);
-                    } else {
-                        tracing::error!("Tool execution task cancelled: {}", e);
+            while let Some(join_result) = join_set.join_next().await {
+                match join_result {
+                    Ok((idx, exec_result)) => results[idx] = Some(exec_result),
+                    Err(e) => {
+                        if e.is_panic() {
+                            tracing::error!("Tool execution task panicked: {}", e);
+                        } else {
+                            tracing::error!("Tool execution task cancelled: {}", e);
+                        }
                     }
                 }
             }
@@ -492,19 +531,9 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
         let job_ctx = deps.context_manager.get_context(job_id).await?;
 
         // Check per-tool rate limit before running hooks or executing (cheaper check first)
-        if let Some(config) = tool.rate_limit_config()
-            && let RateLimitResult::Limited { retry_after, .. } = deps
-                .tools
-                .rate_limiter()
-                .check_and_record(&job_ctx.user_id, tool_name, &config)
-                .await
-        {
-            return Err(crate::error::ToolError::RateLimited {
-                name: tool_name.to_string(),
-                retry_after: Some(retry_after),
-            }
-            .into());
-        }
+        deps.tools
+            .check_rate_limit(&job_ctx.user_id, tool.as_ref())
+            .await?;
 
         // Run BeforeToolCall hook
         let params = {
@@ -553,13 +582,8 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
 
         // Validate tool parameters
         let validation = deps.safety.validator().validate_tool_params(&params);
-        if !validation.is_valid {
-            let details = validation
-                .errors
-                .iter()
-                .map(|e| format!("{}: {}", e.field, e.message))
-                .collect::<Vec<_>>()
-                .join("; ");
+        if !validation.is_valid() {
+            let details = validation.describe_errors();
             return Err(crate::error::ToolError::InvalidParameters {
                 name: tool_name.to_string(),
                 reason: format!("Invalid tool parameters: {}", details),
@@ -731,12 +755,16 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
                     .safety()
                     .sanitize_tool_output(&selection.tool_name, &output);
 
-                // Add to context
-                let wrapped = self.safety().wrap_for_llm(
+                // Add to context, checked against the tool's result schema
+                // the same way the chat dispatcher does.
+                let wrapped = wrap_tool_output(
+                    self.tools(),
+                    self.safety(),
                     &selection.tool_name,
-                    &sanitized.content,
-                    sanitized.was_modified,
-                );
+                    &output,
+                    &sanitized,
+                )
+                .await;
 
                 reason_ctx.messages.push(ChatMessage::tool_result(
                     &selection.tool_call_id,
@@ -1025,6 +1053,48 @@ mod tests {
         }
     }
 
+    /// Records when each call starts and finishes, keyed by tool name.
+    type SpanLog = Arc<std::sync::Mutex<Vec<(String, tokio::time::Instant, tokio::time::Instant)>>>;
+
+    struct SpanTool {
+        tool_name: String,
+        delay: Duration,
+        spans: SpanLog,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for SpanTool {
+        fn name(&self) -> &str {
+            &self.tool_name
+        }
+        fn description(&self) -> &str {
+            "Test tool that records its start and finish times"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+        async fn execute(
+            &self,
+            _params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            let start = tokio::time::Instant::now();
+            tokio::time::sleep(self.delay).await;
+            let end = tokio::time::Instant::now();
+            self.spans
+                .lock()
+                .unwrap()
+                .push((self.tool_name.clone(), start, end));
+            Ok(ToolOutput::text(
+                format!("done_{}", self.tool_name),
+                end - start,
+            ))
+        }
+        fn requires_sanitization(&self) -> bool {
+            false
+        }
+    }
+
     /// Stub LLM provider (never called in these tests).
     struct StubLlm;
 
@@ -1052,6 +1122,10 @@ mod tests {
 
     /// Build a Worker wired to a ToolRegistry containing the given tools.
     async fn make_worker(tools: Vec<Arc<dyn Tool>>) -> Worker {
+        make_worker_with_llm(tools, Arc::new(StubLlm)).await
+    }
+
+    async fn make_worker_with_llm(tools: Vec<Arc<dyn Tool>>, llm: Arc<dyn LlmProvider>) -> Worker {
         let registry = ToolRegistry::new();
         for t in tools {
             registry.register(t).await;
@@ -1062,18 +1136,17 @@ mod tests {
 
         let deps = WorkerDeps {
             context_manager: cm,
-            llm: Arc::new(StubLlm),
+            llm,
             safety: Arc::new(SafetyLayer::new(&SafetyConfig {
-                max_output_length: 100_000,
                 injection_check_enabled: false,
-                #[cfg(feature = "zkproxy")]
-                zkproxy: crate::zkproxy::ZkProxyConfig::default(),
+                ..Default::default()
             })),
             tools: Arc::new(registry),
             store: None,
             hooks: Arc::new(crate::hooks::HookRegistry::new()),
             timeout: Duration::from_secs(30),
             use_planning: false,
+            sampling: SamplingProfiles::default(),
         };
 
         Worker::new(job_id, deps)
@@ -1250,6 +1323,65 @@ mod tests {
         assert!(results[2].result.as_ref().unwrap().contains("done_tool_c"));
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_dependent_call_waits_for_its_dependency() {
+        // slow_2 depends on slow_0, so it starts only after slow_0 finishes,
+        // while slow_1 still runs alongside slow_0.
+        let spans: SpanLog = Arc::default();
+        let tools: Vec<Arc<dyn Tool>> = (0..3)
+            .map(|i| {
+                Arc::new(SpanTool {
+                    tool_name: format!("slow_{}", i),
+                    delay: Duration::from_millis(200),
+                    spans: Arc::clone(&spans),
+                }) as Arc<dyn Tool>
+            })
+            .collect();
+
+        let worker = make_worker(tools).await;
+
+        let selections: Vec<ToolSelection> = (0..3)
+            .map(|i| ToolSelection {
+                tool_name: format!("slow_{}", i),
+                parameters: if i == 2 {
+                    serde_json::json!({"_depends_on": [0]})
+                } else {
+                    serde_json::json!({})
+                },
+                reasoning: String::new(),
+                alternatives: vec![],
+                tool_call_id: format!("call_{}", i),
+            })
+            .collect();
+
+        let results = worker.execute_tools_parallel(&selections).await;
+
+        assert_eq!(results.len(), 3);
+        for r in &results {
+            assert!(r.result.is_ok(), "Tool should succeed");
+        }
+
+        let spans = spans.lock().unwrap();
+        let span = |name: &str| {
+            spans
+                .iter()
+                .find(|(n, _, _)| n == name)
+                .map(|(_, start, end)| (*start, *end))
+                .unwrap()
+        };
+        let (start_0, end_0) = span("slow_0");
+        let (start_1, end_1) = span("slow_1");
+        let (start_2, _) = span("slow_2");
+        assert!(
+            start_2 >= end_0,
+            "Dependent call started before its dependency finished"
+        );
+        assert!(
+            start_1 < end_0 && start_0 < end_1,
+            "Independent calls were serialized"
+        );
+    }
+
     #[tokio::test]
     async fn test_missing_tool_produces_error_not_panic() {
         // If a tool doesn't exist, the result slot should contain an error.
@@ -1270,4 +1402,126 @@ mod tests {
             "Missing tool should produce an error, not a panic"
         );
     }
+    /// Tool whose output does not match its declared result schema.
+    struct MislabeledTool;
+
+    #[async_trait::async_trait]
+    impl Tool for MislabeledTool {
+        fn name(&self) -> &str {
+            "weather"
+        }
+        fn description(&self) -> &str {
+            "Current weather for a city"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+        fn result_schema(&self) -> Option<serde_json::Value> {
+            Some(serde_json::json!({
+                "type": "object",
+                "properties": {"temp_c": {"type": "number"}},
+                "required": ["temp_c"]
+            }))
+        }
+        async fn execute(
+            &self,
+            _params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            Ok(ToolOutput::success(
+                serde_json::json!({"temp_c": "warm"}),
+                Duration::from_millis(1),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_result_checked_against_result_schema() {
+        let worker = make_worker(vec![Arc::new(MislabeledTool)]).await;
+        let selection = ToolSelection {
+            tool_name: "weather".into(),
+            parameters: serde_json::json!({}),
+            reasoning: String::new(),
+            alternatives: vec![],
+            tool_call_id: "call_w".into(),
+        };
+
+        let mut results = worker
+            .execute_tools_parallel(std::slice::from_ref(&selection))
+            .await;
+        let mut reason_ctx = ReasoningContext::new();
+        worker
+            .process_tool_result(&mut reason_ctx, &selection, results.remove(0).result)
+            .await
+            .unwrap();
+
+        let content = &reason_ctx.messages.last().unwrap().content;
+        assert!(content.contains("format=\"json\""), "{}", content);
+        assert!(content.contains("schema_valid=\"false\""), "{}", content);
+    }
+
+    /// LLM that selects the same tool on every call.
+    struct AlwaysToolLlm;
+
+    #[async_trait::async_trait]
+    impl LlmProvider for AlwaysToolLlm {
+        fn model_name(&self) -> &str {
+            "always-tool"
+        }
+        fn cost_per_token(&self) -> (rust_decimal::Decimal, rust_decimal::Decimal) {
+            (rust_decimal::Decimal::ZERO, rust_decimal::Decimal::ZERO)
+        }
+        async fn complete(
+            &self,
+            _req: CompletionRequest,
+        ) -> Result<CompletionResponse, crate::error::LlmError> {
+            unimplemented!("stub")
+        }
+        async fn complete_with_tools(
+            &self,
+            _req: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, crate::error::LlmError> {
+            Ok(ToolCompletionResponse {
+                content: None,
+                tool_calls: vec![crate::llm::ToolCall {
+                    id: "call_1".to_string(),
+                    name: "noop".to_string(),
+                    arguments: serde_json::json!({}),
+                    call_id: None,
+                }],
+                input_tokens: 0,
+                output_tokens: 0,
+                finish_reason: crate::llm::FinishReason::ToolUse,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_gives_up_at_iteration_limit() {
+        let worker = make_worker_with_llm(
+            vec![Arc::new(SlowTool {
+                tool_name: "noop".into(),
+                delay: Duration::ZERO,
+            })],
+            Arc::new(AlwaysToolLlm),
+        )
+        .await;
+        worker
+            .context_manager()
+            .update_context(worker.job_id, |ctx| {
+                ctx.metadata = serde_json::json!({"max_iterations": 2});
+            })
+            .await
+            .unwrap();
+
+        let reasoning = Reasoning::new(worker.llm().clone(), worker.safety().clone());
+        let mut reason_ctx = ReasoningContext::new();
+        let (_tx, mut rx) = mpsc::channel(1);
+        let outcome = worker
+            .execution_loop(&mut rx, &reasoning, &mut reason_ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome, Some(GiveUpReason::IterationLimit { limit: 2 }));
+    }
 }
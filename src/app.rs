@@ -10,13 +10,13 @@
 use std::sync::Arc;
 
 use crate::channels::web::log_layer::LogBroadcaster;
-use crate::config::Config;
+use crate::config::{Config, OutboundCredentialMode};
 use crate::context::ContextManager;
 use crate::db::Database;
 use crate::extensions::ExtensionManager;
 use crate::hooks::HookRegistry;
-use crate::llm::{LlmProvider, SessionManager};
-use crate::safety::SafetyLayer;
+use crate::llm::{CredentialGuardProvider, LlmProvider, ObservedProvider, SessionManager};
+use crate::safety::{LeakDetector, SafetyLayer};
 use crate::secrets::SecretsStore;
 use crate::skills::SkillRegistry;
 use crate::skills::catalog::SkillCatalog;
@@ -26,6 +26,10 @@ use crate::tools::wasm::SharedCredentialRegistry;
 use crate::tools::wasm::WasmToolRuntime;
 use crate::workspace::{EmbeddingProvider, Workspace};
 
+/// Documents kept in the workspace read cache. Identity files and MEMORY.md
+/// are re-read for every system prompt; this comfortably covers them.
+const WORKSPACE_READ_CACHE: usize = 64;
+
 /// Fully initialized application components, ready for channel wiring
 /// and agent construction.
 pub struct AppComponents {
@@ -172,6 +176,11 @@ impl AppBuilder {
                     self.pg_pool = Some(pg.pool());
                 }
 
+                let metrics_pool = pg.pool();
+                crate::observability::prometheus::register_collector(move || {
+                    crate::db::postgres::PoolMetrics::from_pool(&metrics_pool).record();
+                });
+
                 Arc::new(pg) as Arc<dyn Database>
             }
             #[cfg(not(feature = "postgres"))]
@@ -202,6 +211,21 @@ impl AppBuilder {
         }
 
         self.session.attach_store(db.clone(), "default").await;
+        if self.config.llm.nearai.session_store == crate::config::SessionStoreKind::Postgres {
+            #[cfg(feature = "postgres")]
+            self.session
+                .set_session_store(crate::llm::select_session_store(
+                    self.config.llm.nearai.session_store,
+                    &self.config.llm.nearai.session_path,
+                    self.pg_pool.as_ref(),
+                    "default",
+                ))
+                .await;
+            #[cfg(not(feature = "postgres"))]
+            tracing::warn!(
+                "NEARAI_SESSION_STORE=postgres needs the postgres feature; keeping the session file"
+            );
+        }
 
         // Fire-and-forget housekeeping — no need to block startup.
         let db_cleanup = db.clone();
@@ -293,14 +317,60 @@ impl AppBuilder {
     /// Phase 3: Initialize LLM provider chain.
     ///
     /// Delegates to `build_provider_chain` which applies all decorators
-    /// (retry, smart routing, failover, circuit breaker, response cache).
+    /// (retry, smart routing, failover, circuit breaker, response cache),
+    /// then scans outgoing requests for credentials and reports calls to the
+    /// configured observability backend.
     #[allow(clippy::type_complexity)]
     pub fn init_llm(
         &self,
     ) -> Result<(Arc<dyn LlmProvider>, Option<Arc<dyn LlmProvider>>), anyhow::Error> {
-        let (llm, cheap_llm) =
-            crate::llm::build_provider_chain(&self.config.llm, self.session.clone())?;
-        Ok((llm, cheap_llm))
+        // Only the semantic response cache needs embeddings this early.
+        let cache_embeddings = self
+            .config
+            .llm
+            .nearai
+            .response_cache_semantic_threshold
+            .and_then(|_| {
+                self.config
+                    .embeddings
+                    .create_provider(&self.config.llm, self.session.clone())
+            });
+        let (llm, cheap_llm) = crate::llm::build_provider_chain(
+            &self.config.llm,
+            self.session.clone(),
+            cache_embeddings,
+        )?;
+
+        let observer: Arc<dyn crate::observability::Observer> = Arc::from(
+            crate::observability::create_observer(&self.config.observability),
+        );
+        let provider = self.config.llm.backend.to_string();
+        let mode = self.config.safety.outbound_credentials;
+        // A bad leak-pattern file is reported (or fatal) in `init_tools`.
+        let detector = self
+            .config
+            .safety
+            .leak_patterns_file
+            .as_deref()
+            .and_then(|path| LeakDetector::from_file(path).ok())
+            .unwrap_or_default()
+            .with_cloud_credentials(self.config.safety.cloud_credential_patterns);
+        let detector = Arc::new(detector);
+        let wrap = |llm: Arc<dyn LlmProvider>| -> Arc<dyn LlmProvider> {
+            let llm: Arc<dyn LlmProvider> = if mode == OutboundCredentialMode::Off {
+                llm
+            } else {
+                Arc::new(
+                    CredentialGuardProvider::new(llm, mode).with_detector(Arc::clone(&detector)),
+                )
+            };
+            Arc::new(ObservedProvider::new(
+                llm,
+                provider.clone(),
+                Arc::clone(&observer),
+            ))
+        };
+        Ok((wrap(llm), cheap_llm.map(wrap)))
     }
 
     /// Phase 4: Initialize safety, tools, embeddings, and workspace.
@@ -316,18 +386,20 @@ impl AppBuilder {
         ),
         anyhow::Error,
     > {
-        let safety = Arc::new(SafetyLayer::new(&self.config.safety));
+        let safety = Arc::new(
+            SafetyLayer::try_new(&self.config.safety)
+                .map_err(|e| anyhow::anyhow!("Safety layer misconfigured: {}", e))?,
+        );
         tracing::info!("Safety layer initialized");
 
         // Initialize tool registry with credential injection support
         let credential_registry = Arc::new(SharedCredentialRegistry::new());
+        let registry =
+            ToolRegistry::new().with_rate_limits(self.config.agent.tool_rate_limits.clone());
         let tools = if let Some(ref ss) = self.secrets_store {
-            Arc::new(
-                ToolRegistry::new()
-                    .with_credentials(Arc::clone(&credential_registry), Arc::clone(ss)),
-            )
+            Arc::new(registry.with_credentials(Arc::clone(&credential_registry), Arc::clone(ss)))
         } else {
-            Arc::new(ToolRegistry::new())
+            Arc::new(registry)
         };
         tools.register_builtin_tools();
 
@@ -335,7 +407,7 @@ impl AppBuilder {
         let embeddings = self
             .config
             .embeddings
-            .create_provider(&self.config.llm.nearai.base_url, self.session.clone());
+            .create_provider(&self.config.llm, self.session.clone());
 
         // Warn if libSQL backend is used with non-1536 embedding dimension.
         if self.config.database.backend == crate::config::DatabaseBackend::LibSql
@@ -354,7 +426,8 @@ impl AppBuilder {
 
         // Register memory tools if database is available
         let workspace = if let Some(ref db) = self.db {
-            let mut ws = Workspace::new_with_db("default", db.clone());
+            let mut ws =
+                Workspace::new_with_db("default", db.clone()).with_read_cache(WORKSPACE_READ_CACHE);
             if let Some(ref emb) = embeddings {
                 ws = ws.with_embeddings(emb.clone());
             }
@@ -611,7 +684,7 @@ impl AppBuilder {
             Arc::new(InMemorySecretsStore::new(crypto))
         };
         let extension_manager = {
-            let manager = Arc::new(ExtensionManager::new(
+            let mut manager = ExtensionManager::new(
                 Arc::clone(&mcp_session_manager),
                 ext_secrets,
                 Arc::clone(tools),
@@ -623,7 +696,12 @@ impl AppBuilder {
                 "default".to_string(),
                 self.db.clone(),
                 catalog_entries.clone(),
-            ));
+            );
+            manager = manager.with_download_limits(self.config.wasm.download_limits());
+            if let Some(key) = self.config.wasm.install_public_key {
+                manager = manager.with_install_public_key(key);
+            }
+            let manager = Arc::new(manager);
             tools.register_extension_tools(Arc::clone(&manager));
             tracing::info!("Extension manager initialized with in-chat discovery tools");
             Some(manager)
@@ -711,6 +789,10 @@ impl AppBuilder {
             crate::agent::cost_guard::CostGuardConfig {
                 max_cost_per_day_cents: self.config.agent.max_cost_per_day_cents,
                 max_actions_per_hour: self.config.agent.max_actions_per_hour,
+                auto_downgrade_at_budget_fraction: self
+                    .config
+                    .agent
+                    .auto_downgrade_at_budget_fraction,
             },
         ));
 
@@ -0,0 +1,280 @@
+//! Bounded inbound buffer with a configurable overflow policy.
+//!
+//! Each channel hands its messages to the agent through its own stream. When
+//! the agent falls behind, that stream's buffer fills and the channel either
+//! stalls (REPL input thread) or drops messages in ways that differ per
+//! channel. [`bounded`] puts one explicit buffer in front of a channel's
+//! stream so what happens on overflow is chosen by [`OverflowPolicy`] and
+//! counted in [`OverflowStats`].
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+use crate::channels::{IncomingMessage, MessageStream};
+use crate::config::{BackpressureConfig, OverflowPolicy};
+
+/// Why [`InboundQueue::push`] did not accept a message.
+#[derive(Debug)]
+pub enum Overflow {
+    /// The buffer was full and the policy is [`OverflowPolicy::Reject`].
+    Rejected(IncomingMessage),
+    /// The receiving stream was dropped.
+    Closed(IncomingMessage),
+}
+
+/// Messages lost to overflow on one channel.
+#[derive(Debug, Default)]
+pub struct OverflowStats {
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl OverflowStats {
+    /// Messages discarded by `DropOldest` or `DropNewest`.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Messages refused by `Reject`.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+struct Shared {
+    name: String,
+    capacity: usize,
+    policy: OverflowPolicy,
+    queue: Mutex<VecDeque<IncomingMessage>>,
+    item_ready: Notify,
+    space_ready: Notify,
+    sender_closed: AtomicBool,
+    receiver_closed: AtomicBool,
+    stats: Arc<OverflowStats>,
+}
+
+/// Sending half of a [`bounded`] buffer.
+pub struct InboundQueue {
+    shared: Arc<Shared>,
+}
+
+/// Create a buffer for the channel `name`. Messages pushed into the returned
+/// queue come out of the returned stream in order; the stream ends once the
+/// queue is dropped and drained.
+pub fn bounded(name: &str, config: BackpressureConfig) -> (InboundQueue, MessageStream) {
+    let shared = Arc::new(Shared {
+        name: name.to_string(),
+        capacity: config.buffer.max(1),
+        policy: config.policy,
+        queue: Mutex::new(VecDeque::new()),
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+        sender_closed: AtomicBool::new(false),
+        receiver_closed: AtomicBool::new(false),
+        stats: Arc::new(OverflowStats::default()),
+    });
+
+    let receiver = Receiver {
+        shared: Arc::clone(&shared),
+    };
+    let stream = futures::stream::unfold(receiver, |receiver| async move {
+        loop {
+            // Read the flag first so a final push is never missed.
+            let closed = receiver.shared.sender_closed.load(Ordering::Acquire);
+            let next = receiver
+                .shared
+                .queue
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .pop_front();
+            if let Some(msg) = next {
+                receiver.shared.space_ready.notify_one();
+                return Some((msg, receiver));
+            }
+            if closed {
+                return None;
+            }
+            receiver.shared.item_ready.notified().await;
+        }
+    });
+
+    (InboundQueue { shared }, Box::pin(stream))
+}
+
+impl InboundQueue {
+    /// Buffer `msg`, applying the overflow policy when the buffer is full.
+    ///
+    /// With `Block` this waits for room; the drop policies always succeed
+    /// (the lost message is counted); `Reject` hands the message back.
+    pub async fn push(&self, msg: IncomingMessage) -> Result<(), Overflow> {
+        let shared = &self.shared;
+        loop {
+            if shared.receiver_closed.load(Ordering::Acquire) {
+                return Err(Overflow::Closed(msg));
+            }
+            {
+                let mut queue = shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+                if queue.len() < shared.capacity {
+                    queue.push_back(msg);
+                    drop(queue);
+                    shared.item_ready.notify_one();
+                    return Ok(());
+                }
+                match shared.policy {
+                    OverflowPolicy::Block => {}
+                    OverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                        queue.push_back(msg);
+                        drop(queue);
+                        shared.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!(
+                            channel = %shared.name,
+                            "Inbound buffer full, dropped oldest message"
+                        );
+                        shared.item_ready.notify_one();
+                        return Ok(());
+                    }
+                    OverflowPolicy::DropNewest => {
+                        shared.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!(
+                            channel = %shared.name,
+                            "Inbound buffer full, dropped incoming message"
+                        );
+                        return Ok(());
+                    }
+                    OverflowPolicy::Reject => {
+                        shared.stats.rejected.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!(
+                            channel = %shared.name,
+                            "Inbound buffer full, rejected incoming message"
+                        );
+                        return Err(Overflow::Rejected(msg));
+                    }
+                }
+            }
+            shared.space_ready.notified().await;
+        }
+    }
+
+    /// Overflow counters for this buffer.
+    pub fn stats(&self) -> Arc<OverflowStats> {
+        Arc::clone(&self.shared.stats)
+    }
+}
+
+impl Drop for InboundQueue {
+    fn drop(&mut self) {
+        self.shared.sender_closed.store(true, Ordering::Release);
+        self.shared.item_ready.notify_one();
+    }
+}
+
+struct Receiver {
+    shared: Arc<Shared>,
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::Release);
+        self.shared.space_ready.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use super::*;
+
+    fn queue(policy: OverflowPolicy) -> (InboundQueue, MessageStream) {
+        bounded("test", BackpressureConfig { buffer: 2, policy })
+    }
+
+    fn msg(text: &str) -> IncomingMessage {
+        IncomingMessage::new("test", "alice", text)
+    }
+
+    async fn fill(queue: &InboundQueue, texts: &[&str]) -> Vec<bool> {
+        let mut accepted = Vec::new();
+        for text in texts {
+            accepted.push(queue.push(msg(text)).await.is_ok());
+        }
+        accepted
+    }
+
+    async fn drain(queue: InboundQueue, stream: MessageStream) -> Vec<String> {
+        drop(queue);
+        stream.map(|m| m.content).collect().await
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_room() {
+        let (queue, mut stream) = queue(OverflowPolicy::Block);
+        fill(&queue, &["a", "b"]).await;
+
+        let queue = Arc::new(queue);
+        let pusher = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move { queue.push(msg("c")).await.is_ok() })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!pusher.is_finished(), "push should block on a full buffer");
+
+        assert_eq!(stream.next().await.map(|m| m.content).as_deref(), Some("a"));
+        assert!(pusher.await.unwrap());
+
+        let queue = Arc::into_inner(queue).unwrap();
+        assert_eq!(drain(queue, stream).await, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_the_latest() {
+        let (queue, stream) = queue(OverflowPolicy::DropOldest);
+        assert_eq!(fill(&queue, &["a", "b", "c"]).await, vec![true; 3]);
+        let stats = queue.stats();
+
+        assert_eq!(drain(queue, stream).await, vec!["b", "c"]);
+        assert_eq!(stats.dropped(), 1);
+        assert_eq!(stats.rejected(), 0);
+    }
+
+    #[tokio::test]
+    async fn drop_newest_keeps_the_earliest() {
+        let (queue, stream) = queue(OverflowPolicy::DropNewest);
+        assert_eq!(fill(&queue, &["a", "b", "c"]).await, vec![true; 3]);
+        let stats = queue.stats();
+
+        assert_eq!(drain(queue, stream).await, vec!["a", "b"]);
+        assert_eq!(stats.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn reject_hands_the_message_back() {
+        let (queue, stream) = queue(OverflowPolicy::Reject);
+        fill(&queue, &["a", "b"]).await;
+
+        match queue.push(msg("c")).await {
+            Err(Overflow::Rejected(m)) => assert_eq!(m.content, "c"),
+            other => panic!("expected Rejected, got {other:?}"),
+        }
+        let stats = queue.stats();
+        assert_eq!(drain(queue, stream).await, vec!["a", "b"]);
+        assert_eq!(stats.rejected(), 1);
+        assert_eq!(stats.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn push_fails_once_the_stream_is_gone() {
+        let (queue, stream) = queue(OverflowPolicy::Block);
+        drop(stream);
+        assert!(matches!(
+            queue.push(msg("a")).await,
+            Err(Overflow::Closed(_))
+        ));
+    }
+}
@@ -5,8 +5,10 @@ use std::pin::Pin;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use futures::Stream;
+use serde::Serialize;
 use uuid::Uuid;
 
+use crate::channels::split;
 use crate::error::ChannelError;
 
 /// A message received from an external channel.
@@ -26,10 +28,18 @@ pub struct IncomingMessage {
     pub thread_id: Option<String>,
     /// When the message was received.
     pub received_at: DateTime<Utc>,
-    /// Channel-specific metadata.
+    /// Channel-specific metadata: a JSON object once any key has been set,
+    /// `Null` otherwise.
     pub metadata: serde_json::Value,
 }
 
+/// Metadata key for the channel's own ID of the message, used for edits
+/// and reactions.
+pub const META_MESSAGE_ID: &str = "message_id";
+
+/// Metadata key for the channel's ID of the message this one replies to.
+pub const META_REPLY_TO: &str = "reply_to";
+
 impl IncomingMessage {
     /// Create a new incoming message.
     pub fn new(
@@ -66,6 +76,48 @@ impl IncomingMessage {
         self.user_name = Some(name.into());
         self
     }
+
+    /// Set a single metadata key, keeping the others.
+    ///
+    /// Metadata that is not already an object is replaced.
+    pub fn with_meta(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        if !self.metadata.is_object() {
+            self.metadata = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(map) = self.metadata.as_object_mut() {
+            map.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    /// Read a string metadata value.
+    pub fn meta_str(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).and_then(|v| v.as_str())
+    }
+
+    /// Set the channel's own ID for this message.
+    pub fn with_message_id(self, id: impl Into<String>) -> Self {
+        self.with_meta(META_MESSAGE_ID, id.into())
+    }
+
+    /// The channel's own ID for this message, if it reported one.
+    pub fn message_id(&self) -> Option<&str> {
+        self.meta_str(META_MESSAGE_ID)
+    }
+
+    /// Record the channel's ID of the message this one replies to.
+    pub fn with_reply_to(self, id: impl Into<String>) -> Self {
+        self.with_meta(META_REPLY_TO, id.into())
+    }
+
+    /// The channel's ID of the message this one replies to.
+    pub fn reply_to(&self) -> Option<&str> {
+        self.meta_str(META_REPLY_TO)
+    }
 }
 
 /// Stream of incoming messages.
@@ -80,6 +132,8 @@ pub struct OutgoingResponse {
     pub thread_id: Option<String>,
     /// Channel-specific metadata for the response.
     pub metadata: serde_json::Value,
+    /// Deliver immediately, even during quiet hours.
+    pub urgent: bool,
 }
 
 impl OutgoingResponse {
@@ -89,6 +143,7 @@ impl OutgoingResponse {
             content: content.into(),
             thread_id: None,
             metadata: serde_json::Value::Null,
+            urgent: false,
         }
     }
 
@@ -97,6 +152,74 @@ impl OutgoingResponse {
         self.thread_id = Some(thread_id.into());
         self
     }
+
+    /// Mark the response as urgent so it bypasses quiet hours.
+    pub fn urgent(mut self) -> Self {
+        self.urgent = true;
+        self
+    }
+}
+
+/// What a channel can render, so responses can be formatted and split to fit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatCapabilities {
+    /// Markdown emphasis, lists and links render (in some dialect).
+    pub supports_markdown: bool,
+    /// Longest message the channel accepts, in characters. `None` means no limit.
+    pub max_message_length: Option<usize>,
+    /// Fenced code blocks render as code.
+    pub supports_code_blocks: bool,
+    /// Markdown tables render as tables.
+    pub supports_tables: bool,
+}
+
+impl FormatCapabilities {
+    /// Plain text only, no length limit.
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// Full markdown (code blocks and tables), no length limit.
+    pub fn markdown() -> Self {
+        Self {
+            supports_markdown: true,
+            max_message_length: None,
+            supports_code_blocks: true,
+            supports_tables: true,
+        }
+    }
+
+    /// Well-known limits of the chat platforms behind WASM channels.
+    pub fn for_platform(name: &str) -> Option<Self> {
+        let (max_message_length, supports_tables) = match name {
+            "telegram" => (4096, false),
+            "discord" => (2000, false),
+            "slack" => (40_000, false),
+            "whatsapp" => (4096, false),
+            _ => return None,
+        };
+        Some(Self {
+            supports_markdown: true,
+            max_message_length: Some(max_message_length),
+            supports_code_blocks: true,
+            supports_tables,
+        })
+    }
+
+    /// Split `content` into parts that each fit `max_message_length`, see
+    /// [`split::split_message`]. With `numbered`, multi-part messages get an
+    /// `(i/n)` header on each part.
+    pub fn split_message(&self, content: &str, numbered: bool) -> Vec<String> {
+        let Some(max) = self.max_message_length.filter(|&m| m > 0) else {
+            return vec![content.to_string()];
+        };
+        let parts = split::split_message(content, max, true);
+        if !numbered || parts.len() < 2 {
+            return parts;
+        }
+        let max = max.saturating_sub(split::NUMBERING_RESERVE).max(1);
+        split::number_parts(split::split_message(content, max, true))
+    }
 }
 
 /// Status update types for showing agent activity.
@@ -140,6 +263,85 @@ pub enum StatusUpdate {
         success: bool,
         message: String,
     },
+    /// What the model decided on one step, for channels that show their
+    /// work in a verbose or debug mode. Only sent when the agent is
+    /// configured to trace its reasoning.
+    ReasoningTrace(ReasoningTrace),
+}
+
+/// Structured record of one step of the agentic loop: the model's stated
+/// reasoning and the tools it selected. Secrets are redacted before the
+/// trace is sent to a channel.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReasoningTrace {
+    /// Agentic loop iteration, starting at 1.
+    pub iteration: usize,
+    /// Text the model sent alongside its tool calls: the steps it is
+    /// considering and why it picked these tools.
+    pub rationale: Option<String>,
+    /// Tools selected on this step, in call order.
+    pub tools: Vec<TracedToolCall>,
+}
+
+/// A tool call selected on a [`ReasoningTrace`] step.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TracedToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+impl ReasoningTrace {
+    /// Plain-text rendering for channels without structured display.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!("Step {}", self.iteration)];
+        if let Some(rationale) = self
+            .rationale
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+        {
+            lines.push(rationale.to_string());
+        }
+        for tool in &self.tools {
+            lines.push(format!("-> {}({})", tool.name, tool.arguments));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Lightweight acknowledgement attached to an incoming message, e.g. an
+/// emoji reaction on Slack, Discord or Telegram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reaction {
+    /// The message was received and is being worked on.
+    Processing,
+    /// The work finished successfully.
+    Done,
+    /// The work failed.
+    Error,
+}
+
+impl Reaction {
+    /// Reaction for a status update, if it marks progress or an outcome.
+    pub fn for_status(status: &StatusUpdate) -> Option<Self> {
+        match status {
+            StatusUpdate::Thinking(_) | StatusUpdate::ToolStarted { .. } => {
+                Some(Reaction::Processing)
+            }
+            StatusUpdate::ToolCompleted { success: true, .. } => Some(Reaction::Done),
+            StatusUpdate::ToolCompleted { success: false, .. } => Some(Reaction::Error),
+            _ => None,
+        }
+    }
+
+    /// Suggested emoji for channels that react with unicode emoji.
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Reaction::Processing => "👀",
+            Reaction::Done => "✅",
+            Reaction::Error => "❌",
+        }
+    }
 }
 
 /// Trait for message channels.
@@ -181,6 +383,16 @@ pub trait Channel: Send + Sync {
         Ok(())
     }
 
+    /// React to an incoming message to acknowledge it or signal progress.
+    ///
+    /// Channels with emoji reactions should replace any earlier reaction the
+    /// agent left on `msg`.
+    ///
+    /// Default implementation does nothing (for channels without reactions).
+    async fn react(&self, _msg: &IncomingMessage, _reaction: Reaction) -> Result<(), ChannelError> {
+        Ok(())
+    }
+
     /// Send a proactive message without a prior incoming message.
     ///
     /// Used for alerts, heartbeat notifications, and other agent-initiated communication.
@@ -195,6 +407,13 @@ pub trait Channel: Send + Sync {
         Ok(())
     }
 
+    /// Describe what this channel can render.
+    ///
+    /// Default implementation reports plain text with no length limit.
+    fn format_capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities::plain()
+    }
+
     /// Check if the channel is healthy.
     async fn health_check(&self) -> Result<(), ChannelError>;
 
@@ -203,3 +422,195 @@ pub trait Channel: Send + Sync {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_message_has_sane_defaults() {
+        let msg = IncomingMessage::new("repl", "user", "hello");
+        assert_eq!(msg.channel, "repl");
+        assert_eq!(msg.user_id, "user");
+        assert_eq!(msg.content, "hello");
+        assert!(msg.user_name.is_none());
+        assert!(msg.thread_id.is_none());
+        assert!(msg.metadata.is_null());
+        assert!(msg.message_id().is_none());
+        assert!(msg.reply_to().is_none());
+        assert_ne!(msg.id, IncomingMessage::new("repl", "user", "hello").id);
+    }
+
+    #[test]
+    fn thread_and_metadata_round_trip() {
+        let msg = IncomingMessage::new("telegram", "42", "hi")
+            .with_thread("chat-7")
+            .with_message_id("1001")
+            .with_reply_to("998")
+            .with_meta("chat_type", "group");
+
+        let copy = msg.clone();
+        assert_eq!(copy.thread_id.as_deref(), Some("chat-7"));
+        assert_eq!(copy.message_id(), Some("1001"));
+        assert_eq!(copy.reply_to(), Some("998"));
+        assert_eq!(copy.meta_str("chat_type"), Some("group"));
+        assert_eq!(copy.metadata[META_MESSAGE_ID], "1001");
+
+        // Setting a key keeps existing object metadata, replaces anything else.
+        let msg = IncomingMessage::new("signal", "u", "x")
+            .with_metadata(serde_json::json!({"signal_target": "+1"}))
+            .with_reply_to("5");
+        assert_eq!(msg.meta_str("signal_target"), Some("+1"));
+        let msg = msg
+            .with_metadata(serde_json::json!("raw"))
+            .with_meta("k", 1);
+        assert_eq!(msg.metadata, serde_json::json!({"k": 1}));
+    }
+
+    /// A channel that only overrides the required methods.
+    struct PlainChannel;
+
+    #[async_trait]
+    impl Channel for PlainChannel {
+        fn name(&self) -> &str {
+            "plain"
+        }
+
+        async fn start(&self) -> Result<MessageStream, ChannelError> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &IncomingMessage,
+            _response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), ChannelError> {
+            Ok(())
+        }
+    }
+
+    /// A channel that records the emoji it reacts with.
+    #[derive(Default)]
+    struct ReactingChannel {
+        reactions: std::sync::Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl Channel for ReactingChannel {
+        fn name(&self) -> &str {
+            "reacting"
+        }
+
+        async fn start(&self) -> Result<MessageStream, ChannelError> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &IncomingMessage,
+            _response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn react(
+            &self,
+            _msg: &IncomingMessage,
+            reaction: Reaction,
+        ) -> Result<(), ChannelError> {
+            self.reactions.lock().unwrap().push(reaction.emoji());
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), ChannelError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn default_react_is_a_noop() {
+        let msg = IncomingMessage::new("plain", "alice", "hi");
+        assert!(PlainChannel.react(&msg, Reaction::Processing).await.is_ok());
+        assert!(PlainChannel.react(&msg, Reaction::Error).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn status_updates_map_to_reactions() {
+        let channel = ReactingChannel::default();
+        let msg = IncomingMessage::new("reacting", "alice", "run the tests");
+        let statuses = [
+            StatusUpdate::Thinking("Planning".to_string()),
+            StatusUpdate::Status("ignored".to_string()),
+            StatusUpdate::ToolCompleted {
+                name: "shell".to_string(),
+                success: true,
+            },
+            StatusUpdate::ToolCompleted {
+                name: "shell".to_string(),
+                success: false,
+            },
+        ];
+        for status in &statuses {
+            if let Some(reaction) = Reaction::for_status(status) {
+                channel.react(&msg, reaction).await.unwrap();
+            }
+        }
+        assert_eq!(*channel.reactions.lock().unwrap(), vec!["👀", "✅", "❌"]);
+    }
+
+    #[test]
+    fn plain_channel_reports_no_formatting() {
+        let caps = PlainChannel.format_capabilities();
+        assert!(!caps.supports_markdown);
+        assert!(!caps.supports_code_blocks);
+        assert!(!caps.supports_tables);
+        assert_eq!(caps.max_message_length, None);
+    }
+
+    #[test]
+    fn split_message_without_limit_is_unchanged() {
+        let text = "a".repeat(10_000);
+        assert_eq!(
+            FormatCapabilities::plain().split_message(&text, true),
+            vec![text]
+        );
+    }
+
+    #[test]
+    fn numbered_split_parts_still_fit() {
+        let caps = FormatCapabilities {
+            max_message_length: Some(20),
+            ..FormatCapabilities::plain()
+        };
+        let content = "first paragraph\n\nsecond one is longer";
+        assert_eq!(
+            caps.split_message(content, false),
+            vec!["first paragraph\n\n", "second one is longer"]
+        );
+
+        let parts = caps.split_message(content, true);
+        assert!(parts.len() > 2);
+        assert!(parts[0].starts_with(&format!("(1/{})\n", parts.len())));
+        assert!(parts.iter().all(|p| p.chars().count() <= 20));
+    }
+
+    #[test]
+    fn reasoning_trace_summary_lists_rationale_and_tools() {
+        let trace = ReasoningTrace {
+            iteration: 3,
+            rationale: Some("  Need the time first.\n".to_string()),
+            tools: vec![TracedToolCall {
+                name: "time".to_string(),
+                arguments: serde_json::json!({"operation": "now"}),
+            }],
+        };
+        assert_eq!(
+            trace.summary(),
+            "Step 3\nNeed the time first.\n-> time({\"operation\":\"now\"})"
+        );
+    }
+}
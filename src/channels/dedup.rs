@@ -0,0 +1,197 @@
+//! Drop inbound messages redelivered by a channel.
+//!
+//! Webhook-based channels retry deliveries they think failed, so the same
+//! message can arrive twice. Messages carrying a channel-provided
+//! [`META_MESSAGE_ID`] are remembered for a TTL window, and a second arrival
+//! within the window is dropped. Messages without an id always pass.
+//!
+//! Message ids are often only unique within a conversation (Telegram numbers
+//! messages per chat), so the id is scoped by the chat or thread it arrived
+//! in. A bot-wide `update_id`, when the channel reports one, is used as is.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::channels::{IncomingMessage, META_MESSAGE_ID};
+
+/// Metadata key for a channel-wide delivery id (Telegram's `update_id`).
+const META_UPDATE_ID: &str = "update_id";
+
+/// Metadata key for the chat a message arrived in.
+const META_CHAT_ID: &str = "chat_id";
+
+/// `(channel, scope, id)`: a message id is only compared within its scope.
+type DedupKey = (String, String, String);
+
+#[derive(Default)]
+struct Seen {
+    at: HashMap<DedupKey, Instant>,
+    /// Keys in admission order, so expiry only touches the stale front.
+    order: VecDeque<(Instant, DedupKey)>,
+}
+
+pub struct InboundDedup {
+    ttl: Duration,
+    seen: Mutex<Seen>,
+    dropped: AtomicU64,
+}
+
+impl InboundDedup {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: Mutex::new(Seen::default()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Record `msg` and return whether it should be processed. Returns
+    /// `false` for a message id already seen in the same channel and chat
+    /// within the TTL.
+    pub fn admit(&self, msg: &IncomingMessage, now: Instant) -> bool {
+        let Some(key) = message_key(msg) else {
+            return true;
+        };
+
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        while let Some((at, _)) = seen.order.front() {
+            if now.duration_since(*at) < self.ttl {
+                break;
+            }
+            if let Some((at, key)) = seen.order.pop_front()
+                && seen.at.get(&key) == Some(&at)
+            {
+                seen.at.remove(&key);
+            }
+        }
+
+        if seen.at.contains_key(&key) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(
+                channel = %msg.channel,
+                scope = %key.1,
+                message_id = %key.2,
+                "Dropping duplicate inbound message"
+            );
+            return false;
+        }
+        seen.at.insert(key.clone(), now);
+        seen.order.push_back((now, key));
+        true
+    }
+
+    /// Number of duplicates dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Metadata values some channels report as numbers, others as strings.
+fn metadata_str(msg: &IncomingMessage, key: &str) -> Option<String> {
+    match msg.metadata.get(key)? {
+        serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// The dedup key for `msg`, or `None` when the channel gave no id.
+fn message_key(msg: &IncomingMessage) -> Option<DedupKey> {
+    if let Some(update_id) = metadata_str(msg, META_UPDATE_ID) {
+        return Some((msg.channel.clone(), String::new(), update_id));
+    }
+    let id = metadata_str(msg, META_MESSAGE_ID)?;
+    let scope = metadata_str(msg, META_CHAT_ID)
+        .map(|chat| format!("chat:{chat}"))
+        .or_else(|| msg.thread_id.as_ref().map(|t| format!("thread:{t}")))
+        .unwrap_or_else(|| format!("user:{}", msg.user_id));
+    Some((msg.channel.clone(), scope, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(channel: &str, id: &str) -> IncomingMessage {
+        IncomingMessage::new(channel, "alice", "hi").with_message_id(id)
+    }
+
+    #[test]
+    fn repeated_id_within_window_is_dropped() {
+        let dedup = InboundDedup::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        assert!(dedup.admit(&msg("telegram", "1"), t0));
+        assert!(!dedup.admit(&msg("telegram", "1"), t0 + Duration::from_secs(5)));
+        assert!(dedup.admit(&msg("telegram", "2"), t0 + Duration::from_secs(5)));
+        // Same id on another channel is a different message.
+        assert!(dedup.admit(&msg("slack", "1"), t0 + Duration::from_secs(5)));
+        assert_eq!(dedup.dropped(), 1);
+    }
+
+    #[test]
+    fn window_expires() {
+        let dedup = InboundDedup::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        assert!(dedup.admit(&msg("telegram", "1"), t0));
+        assert!(dedup.admit(&msg("telegram", "1"), t0 + Duration::from_secs(61)));
+        assert_eq!(dedup.dropped(), 0);
+    }
+
+    #[test]
+    fn messages_without_id_always_pass() {
+        let dedup = InboundDedup::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+        let plain = IncomingMessage::new("repl", "u", "hi");
+        assert!(dedup.admit(&plain, t0));
+        assert!(dedup.admit(&plain, t0));
+
+        let numeric = IncomingMessage::new("telegram", "u", "hi")
+            .with_metadata(serde_json::json!({ "message_id": 42 }));
+        assert!(dedup.admit(&numeric, t0));
+        assert!(!dedup.admit(&numeric, t0));
+    }
+
+    #[test]
+    fn message_ids_are_scoped_by_chat() {
+        let dedup = InboundDedup::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+        let in_chat = |chat: i64| {
+            IncomingMessage::new("telegram", "u", "hi")
+                .with_metadata(serde_json::json!({ "chat_id": chat, "message_id": 7 }))
+        };
+
+        assert!(dedup.admit(&in_chat(1), t0));
+        // Telegram numbers messages per chat: same id, different chat.
+        assert!(dedup.admit(&in_chat(2), t0));
+        assert!(!dedup.admit(&in_chat(1), t0));
+
+        let update = |update_id: i64, chat: i64| {
+            IncomingMessage::new("telegram", "u", "hi").with_metadata(
+                serde_json::json!({ "update_id": update_id, "chat_id": chat, "message_id": 1 }),
+            )
+        };
+        assert!(dedup.admit(&update(100, 1), t0));
+        assert!(dedup.admit(&update(101, 1), t0));
+        assert!(!dedup.admit(&update(100, 1), t0));
+    }
+
+    #[test]
+    fn expired_ids_are_evicted_in_order() {
+        let dedup = InboundDedup::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        for i in 0..100 {
+            assert!(dedup.admit(&msg("telegram", &i.to_string()), t0));
+        }
+        assert!(dedup.admit(&msg("telegram", "late"), t0 + Duration::from_secs(30)));
+        assert!(dedup.admit(&msg("telegram", "next"), t0 + Duration::from_secs(61)));
+
+        let seen = dedup.seen.lock().unwrap();
+        assert_eq!(seen.at.len(), 2);
+        assert_eq!(seen.order.len(), 2);
+    }
+}
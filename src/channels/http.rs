@@ -18,6 +18,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
 use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse};
+use crate::clock::{Clock, system_clock};
 use crate::config::HttpConfig;
 use crate::error::ChannelError;
 
@@ -38,11 +39,21 @@ struct HttpChannelState {
     user_id: String,
     /// Rate limiting state.
     rate_limit: tokio::sync::Mutex<RateLimitState>,
+    /// Time source for the rate-limit window. Behind a lock so it can be
+    /// replaced after [`HttpChannel::routes`] has shared the state.
+    clock: std::sync::RwLock<Arc<dyn Clock>>,
+}
+
+impl HttpChannelState {
+    fn now(&self) -> std::time::Instant {
+        self.clock.read().unwrap_or_else(|e| e.into_inner()).now()
+    }
 }
 
 #[derive(Debug)]
 struct RateLimitState {
-    window_start: std::time::Instant,
+    /// Start of the current window; `None` until the first request.
+    window_start: Option<std::time::Instant>,
     request_count: u32,
 }
 
@@ -75,13 +86,21 @@ impl HttpChannel {
                 webhook_secret,
                 user_id,
                 rate_limit: tokio::sync::Mutex::new(RateLimitState {
-                    window_start: std::time::Instant::now(),
+                    window_start: None,
                     request_count: 0,
                 }),
+                clock: std::sync::RwLock::new(system_clock()),
             }),
         }
     }
 
+    /// Read time from `clock` instead of the system clock. Also applies to
+    /// routers already returned by [`Self::routes`].
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        *self.state.clock.write().unwrap_or_else(|e| e.into_inner()) = clock;
+        self
+    }
+
     /// Return the channel's axum routes with state applied.
     ///
     /// The returned `Router` shares the same `Arc<HttpChannelState>` that
@@ -110,6 +129,10 @@ struct WebhookRequest {
     content: String,
     /// Optional thread ID for conversation tracking.
     thread_id: Option<String>,
+    /// Optional client-side message ID. Retries with the same ID are
+    /// delivered once.
+    #[serde(default)]
+    message_id: Option<String>,
     /// Optional webhook secret for authentication.
     secret: Option<String>,
     /// Whether to wait for a synchronous response.
@@ -147,8 +170,11 @@ async fn webhook_handler(
     // Rate limiting
     {
         let mut limiter = state.rate_limit.lock().await;
-        if limiter.window_start.elapsed() >= std::time::Duration::from_secs(60) {
-            limiter.window_start = std::time::Instant::now();
+        let now = state.now();
+        if limiter.window_start.is_none_or(|start| {
+            now.saturating_duration_since(start) >= std::time::Duration::from_secs(60)
+        }) {
+            limiter.window_start = Some(now);
             limiter.request_count = 0;
         }
         limiter.request_count += 1;
@@ -211,11 +237,14 @@ async fn webhook_handler(
         );
     }
 
-    let msg = IncomingMessage::new("http", &state.user_id, &req.content).with_metadata(
+    let mut msg = IncomingMessage::new("http", &state.user_id, &req.content).with_metadata(
         serde_json::json!({
             "wait_for_response": req.wait_for_response,
         }),
     );
+    if let Some(message_id) = &req.message_id {
+        msg = msg.with_message_id(message_id);
+    }
 
     if let Some(thread_id) = &req.thread_id {
         let msg = msg.with_thread(thread_id);
@@ -442,4 +471,34 @@ mod tests {
         let resp = app.oneshot(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
     }
+
+    #[tokio::test]
+    async fn webhook_rate_limit_window_follows_clock() {
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let channel = test_channel(Some("correct-secret"));
+        // The clock may be swapped in after the router is built.
+        let app = channel.routes();
+        let channel = channel.with_clock(clock.clone());
+        let _stream = channel.start().await.unwrap();
+        let post = || {
+            let body = serde_json::json!({ "content": "hello", "secret": "wrong" });
+            Request::builder()
+                .method("POST")
+                .uri("/webhook")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+
+        for _ in 0..MAX_REQUESTS_PER_MINUTE {
+            let resp = app.clone().oneshot(post()).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        }
+        let resp = app.clone().oneshot(post()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        clock.advance(std::time::Duration::from_secs(60));
+        let resp = app.oneshot(post()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
 }
@@ -2,12 +2,59 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 use futures::stream;
 use tokio::sync::{RwLock, mpsc};
 
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+use crate::channels::backpressure::{Overflow, OverflowStats, bounded};
+use crate::channels::dedup::InboundDedup;
+use crate::channels::quiet_hours::{QuietDecision, QuietHours};
+use crate::channels::throttle::{BroadcastThrottle, ThrottleDecision};
+use crate::channels::{
+    Channel, FormatCapabilities, IncomingMessage, MessageStream, OutgoingResponse, Reaction,
+    StatusUpdate,
+};
+use crate::config::{BackpressureConfig, BroadcastThrottleConfig, StartupHealthCheck};
 use crate::error::ChannelError;
+use crate::observability::prometheus;
+
+/// Reply sent when a channel's inbound buffer is full and its policy is
+/// `reject`.
+const REJECTED_REPLY: &str =
+    "I'm still working through earlier messages. Please try again shortly.";
+
+/// Result of running every channel's health check.
+#[derive(Debug, Default)]
+pub struct ChannelReadiness {
+    /// Channels that passed, sorted by name.
+    pub healthy: Vec<String>,
+    /// Channels that failed, with the reported error, sorted by name.
+    pub unhealthy: Vec<(String, ChannelError)>,
+}
+
+impl ChannelReadiness {
+    /// Whether every channel passed.
+    pub fn is_ready(&self) -> bool {
+        self.unhealthy.is_empty()
+    }
+
+    /// One-line summary, e.g. `2/3 channels healthy; unhealthy: signal (...)`.
+    pub fn summary(&self) -> String {
+        let total = self.healthy.len() + self.unhealthy.len();
+        let mut out = format!("{}/{} channels healthy", self.healthy.len(), total);
+        if !self.unhealthy.is_empty() {
+            let failed: Vec<String> = self
+                .unhealthy
+                .iter()
+                .map(|(name, err)| format!("{} ({})", name, err))
+                .collect();
+            out.push_str("; unhealthy: ");
+            out.push_str(&failed.join(", "));
+        }
+        out
+    }
+}
 
 /// Manages multiple input channels and merges their message streams.
 ///
@@ -18,6 +65,20 @@ pub struct ChannelManager {
     inject_tx: mpsc::Sender<IncomingMessage>,
     /// Taken once in `start_all()` and merged into the stream.
     inject_rx: tokio::sync::Mutex<Option<mpsc::Receiver<IncomingMessage>>>,
+    /// Health self-test policy applied at the end of `start_all()`.
+    startup_health_check: StartupHealthCheck,
+    /// Per-user coalescing of proactive broadcasts.
+    throttle: Arc<BroadcastThrottle>,
+    /// Do-not-disturb window for non-urgent broadcasts.
+    quiet_hours: Option<Arc<QuietHours>>,
+    /// Drops redelivered inbound messages.
+    dedup: Option<Arc<InboundDedup>>,
+    /// Per-channel inbound buffer and overflow policy (`"*"` applies to all).
+    backpressure: HashMap<String, BackpressureConfig>,
+    /// Overflow counters of the buffered channels.
+    overflow_stats: std::sync::Mutex<HashMap<String, Arc<OverflowStats>>>,
+    /// Number the parts of messages split to fit a channel's length limit.
+    number_split_messages: bool,
 }
 
 impl ChannelManager {
@@ -28,9 +89,117 @@ impl ChannelManager {
             channels: Arc::new(RwLock::new(HashMap::new())),
             inject_tx,
             inject_rx: tokio::sync::Mutex::new(Some(inject_rx)),
+            startup_health_check: StartupHealthCheck::default(),
+            throttle: Arc::new(BroadcastThrottle::default()),
+            quiet_hours: None,
+            dedup: None,
+            backpressure: HashMap::new(),
+            overflow_stats: std::sync::Mutex::new(HashMap::new()),
+            number_split_messages: false,
         }
     }
 
+    /// Set what `start_all()` does when a channel fails its health check.
+    pub fn with_startup_health_check(mut self, policy: StartupHealthCheck) -> Self {
+        self.startup_health_check = policy;
+        self
+    }
+
+    /// Throttle proactive broadcasts per channel (`"*"` applies to all).
+    ///
+    /// Broadcasts to the same user within a channel's window are held and
+    /// delivered together when the window closes.
+    pub fn with_broadcast_throttle(
+        mut self,
+        configs: HashMap<String, BroadcastThrottleConfig>,
+    ) -> Self {
+        self.throttle = Arc::new(BroadcastThrottle::new(configs));
+        self
+    }
+
+    /// Queue non-urgent broadcasts during quiet hours and deliver them once
+    /// the window closes.
+    pub fn with_quiet_hours(mut self, quiet_hours: QuietHours) -> Self {
+        self.quiet_hours = Some(Arc::new(quiet_hours));
+        self
+    }
+
+    /// Drop inbound messages whose channel message id was already seen on
+    /// the same channel within `ttl`. A zero `ttl` disables deduplication.
+    pub fn with_inbound_dedup(mut self, ttl: std::time::Duration) -> Self {
+        self.dedup = (!ttl.is_zero()).then(|| Arc::new(InboundDedup::new(ttl)));
+        self
+    }
+
+    /// Number of duplicate inbound messages dropped so far.
+    pub fn inbound_duplicates_dropped(&self) -> u64 {
+        self.dedup.as_ref().map_or(0, |d| d.dropped())
+    }
+
+    /// Buffer each channel's inbound messages and apply its overflow policy
+    /// when the agent falls behind. Channels without an entry (and no `"*"`
+    /// entry) are left unbuffered.
+    pub fn with_backpressure(mut self, configs: HashMap<String, BackpressureConfig>) -> Self {
+        self.backpressure = configs;
+        self
+    }
+
+    /// Overflow counters for a buffered channel.
+    pub fn overflow_stats(&self, channel_name: &str) -> Option<Arc<OverflowStats>> {
+        self.overflow_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(channel_name)
+            .cloned()
+    }
+
+    /// Put `stream` behind the channel's configured buffer, if any.
+    ///
+    /// A task moves messages from the channel into the buffer. Messages
+    /// rejected by the `reject` policy get a short busy reply.
+    fn apply_backpressure(&self, name: &str, stream: MessageStream) -> MessageStream {
+        let Some(config) = self
+            .backpressure
+            .get(name)
+            .or_else(|| self.backpressure.get("*"))
+        else {
+            return stream;
+        };
+
+        let (queue, buffered) = bounded(name, *config);
+        self.overflow_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_string(), queue.stats());
+
+        let channels = Arc::clone(&self.channels);
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            let mut stream = stream;
+            while let Some(msg) = stream.next().await {
+                match queue.push(msg).await {
+                    Ok(()) => {}
+                    Err(Overflow::Rejected(msg)) => {
+                        let channels = channels.read().await;
+                        if let Some(channel) = channels.get(&msg.channel) {
+                            let _ = channel
+                                .respond(&msg, OutgoingResponse::text(REJECTED_REPLY))
+                                .await;
+                        }
+                    }
+                    Err(Overflow::Closed(_)) => break,
+                }
+            }
+        });
+        buffered
+    }
+
+    /// Prefix each part of a split message with `(i/n)`.
+    pub fn with_split_numbering(mut self, enabled: bool) -> Self {
+        self.number_split_messages = enabled;
+        self
+    }
+
     /// Get a clone of the injection sender.
     ///
     /// Background tasks (like job monitors) use this to push messages into the
@@ -53,7 +222,7 @@ impl ChannelManager {
     /// the agent loop.
     pub async fn hot_add(&self, channel: Box<dyn Channel>) -> Result<(), ChannelError> {
         let name = channel.name().to_string();
-        let stream = channel.start().await?;
+        let stream = self.apply_backpressure(&name, channel.start().await?);
 
         // Register for respond/broadcast/send_status
         self.channels.write().await.insert(name.clone(), channel);
@@ -87,7 +256,7 @@ impl ChannelManager {
             match channel.start().await {
                 Ok(stream) => {
                     tracing::info!("Started channel: {}", name);
-                    streams.push(stream);
+                    streams.push(self.apply_backpressure(name, stream));
                 }
                 Err(e) => {
                     tracing::error!("Failed to start channel {}: {}", name, e);
@@ -103,6 +272,10 @@ impl ChannelManager {
             });
         }
 
+        // Catch misconfigured tokens/webhooks now rather than on first use.
+        drop(channels);
+        self.run_startup_health_check().await?;
+
         // Take the injection receiver (can only be taken once)
         if let Some(inject_rx) = self.inject_rx.lock().await.take() {
             let inject_stream = tokio_stream::wrappers::ReceiverStream::new(inject_rx);
@@ -111,11 +284,24 @@ impl ChannelManager {
         }
 
         // Merge all streams into one
-        let merged = stream::select_all(streams);
-        Ok(Box::pin(merged))
+        use futures::StreamExt;
+        let merged = stream::select_all(streams)
+            .inspect(|msg| prometheus::record_channel_message(&msg.channel, "inbound"));
+        match &self.dedup {
+            Some(dedup) => {
+                let dedup = Arc::clone(dedup);
+                Ok(Box::pin(merged.filter(move |msg| {
+                    std::future::ready(dedup.admit(msg, Instant::now()))
+                })))
+            }
+            None => Ok(Box::pin(merged)),
+        }
     }
 
     /// Send a response to a specific channel.
+    ///
+    /// Responses longer than the channel's `max_message_length` are sent as
+    /// several consecutive messages.
     pub async fn respond(
         &self,
         msg: &IncomingMessage,
@@ -123,7 +309,11 @@ impl ChannelManager {
     ) -> Result<(), ChannelError> {
         let channels = self.channels.read().await;
         if let Some(channel) = channels.get(&msg.channel) {
-            channel.respond(msg, response).await
+            for part in split_response(channel.as_ref(), response, self.number_split_messages) {
+                channel.respond(msg, part).await?;
+            }
+            prometheus::record_channel_message(&msg.channel, "outbound");
+            Ok(())
         } else {
             Err(ChannelError::SendFailed {
                 name: msg.channel.clone(),
@@ -132,6 +322,34 @@ impl ChannelManager {
         }
     }
 
+    /// React to `msg` on the channel it came from.
+    ///
+    /// Best-effort like `send_status`: a missing channel is ignored.
+    pub async fn react(
+        &self,
+        msg: &IncomingMessage,
+        reaction: Reaction,
+    ) -> Result<(), ChannelError> {
+        let channels = self.channels.read().await;
+        if let Some(channel) = channels.get(&msg.channel) {
+            channel.react(msg, reaction).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// React to `msg` with the reaction matching `status`, if any.
+    pub async fn react_to_status(
+        &self,
+        msg: &IncomingMessage,
+        status: &StatusUpdate,
+    ) -> Result<(), ChannelError> {
+        match Reaction::for_status(status) {
+            Some(reaction) => self.react(msg, reaction).await,
+            None => Ok(()),
+        }
+    }
+
     /// Send a status update to a specific channel.
     ///
     /// The metadata contains channel-specific routing info (e.g., Telegram chat_id)
@@ -153,7 +371,9 @@ impl ChannelManager {
 
     /// Broadcast a message to a specific user on a specific channel.
     ///
-    /// Used for proactive notifications like heartbeat alerts.
+    /// Used for proactive notifications like heartbeat alerts. Subject to
+    /// quiet hours and the broadcast throttle: a held message returns `Ok(())`
+    /// and is delivered when quiet hours end or the channel's window closes.
     pub async fn broadcast(
         &self,
         channel_name: &str,
@@ -162,7 +382,18 @@ impl ChannelManager {
     ) -> Result<(), ChannelError> {
         let channels = self.channels.read().await;
         if let Some(channel) = channels.get(channel_name) {
-            channel.broadcast(user_id, response).await
+            match self.gate_broadcast(channel_name, user_id, response) {
+                Some(response) => {
+                    send_broadcast(
+                        channel.as_ref(),
+                        user_id,
+                        response,
+                        self.number_split_messages,
+                    )
+                    .await
+                }
+                None => Ok(()),
+            }
         } else {
             Err(ChannelError::SendFailed {
                 name: channel_name.to_string(),
@@ -183,13 +414,141 @@ impl ChannelManager {
         let mut results = Vec::new();
 
         for (name, channel) in channels.iter() {
-            let result = channel.broadcast(user_id, response.clone()).await;
+            let result = match self.gate_broadcast(name, user_id, response.clone()) {
+                Some(response) => {
+                    send_broadcast(
+                        channel.as_ref(),
+                        user_id,
+                        response,
+                        self.number_split_messages,
+                    )
+                    .await
+                }
+                None => Ok(()),
+            };
             results.push((name.clone(), result));
         }
 
         results
     }
 
+    /// Pass `response` through quiet hours and then the throttle, returning
+    /// it if it should be sent now.
+    fn gate_broadcast(
+        &self,
+        channel_name: &str,
+        user_id: &str,
+        response: OutgoingResponse,
+    ) -> Option<OutgoingResponse> {
+        let response = self.defer_for_quiet_hours(channel_name, user_id, response)?;
+        self.throttle_broadcast(channel_name, user_id, response)
+    }
+
+    /// Queue `response` if quiet hours are in effect, returning it if it
+    /// should go out now. When it is the first one queued for this user, a
+    /// task is spawned to deliver the queue once quiet hours end.
+    fn defer_for_quiet_hours(
+        &self,
+        channel_name: &str,
+        user_id: &str,
+        response: OutgoingResponse,
+    ) -> Option<OutgoingResponse> {
+        let Some(quiet_hours) = &self.quiet_hours else {
+            return Some(response);
+        };
+        match quiet_hours.offer(channel_name, user_id, response) {
+            QuietDecision::Send(response) => Some(response),
+            QuietDecision::Queued => None,
+            QuietDecision::Schedule(release_at) => {
+                let delay = (release_at - quiet_hours.now())
+                    .to_std()
+                    .unwrap_or_default();
+                let channels = Arc::clone(&self.channels);
+                let quiet_hours = Arc::clone(quiet_hours);
+                let channel_name = channel_name.to_string();
+                let user_id = user_id.to_string();
+                let numbered = self.number_split_messages;
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let queued = quiet_hours.release(&channel_name, &user_id);
+                    let channels = channels.read().await;
+                    let Some(channel) = channels.get(&channel_name) else {
+                        return;
+                    };
+                    for response in queued {
+                        if let Err(e) =
+                            send_broadcast(channel.as_ref(), &user_id, response, numbered).await
+                        {
+                            tracing::warn!(
+                                "Failed to deliver broadcast queued during quiet hours on {}: {}",
+                                channel_name,
+                                e
+                            );
+                        }
+                    }
+                });
+                None
+            }
+        }
+    }
+
+    /// Pass `response` through the throttle, returning it if it should be
+    /// sent now. When it starts a new batch, a task is spawned to deliver the
+    /// batch once the window closes.
+    fn throttle_broadcast(
+        &self,
+        channel_name: &str,
+        user_id: &str,
+        response: OutgoingResponse,
+    ) -> Option<OutgoingResponse> {
+        // Read through tokio's clock so the window follows paused test time.
+        match self.throttle.offer(
+            channel_name,
+            user_id,
+            response,
+            tokio::time::Instant::now().into_std(),
+        ) {
+            ThrottleDecision::Send(response) => Some(response),
+            ThrottleDecision::Held => None,
+            ThrottleDecision::Schedule(flush_at) => {
+                let channels = Arc::clone(&self.channels);
+                let throttle = Arc::clone(&self.throttle);
+                let channel_name = channel_name.to_string();
+                let user_id = user_id.to_string();
+                let numbered = self.number_split_messages;
+                tokio::spawn(async move {
+                    tokio::time::sleep_until(flush_at.into()).await;
+                    let now = tokio::time::Instant::now().into_std();
+                    let Some(response) = throttle.flush(&channel_name, &user_id, now) else {
+                        return;
+                    };
+                    let channels = channels.read().await;
+                    if let Some(channel) = channels.get(&channel_name)
+                        && let Err(e) =
+                            send_broadcast(channel.as_ref(), &user_id, response, numbered).await
+                    {
+                        tracing::warn!(
+                            "Failed to deliver throttled broadcast on {}: {}",
+                            channel_name,
+                            e
+                        );
+                    }
+                });
+                None
+            }
+        }
+    }
+
+    /// What the named channel can render. Unknown channels report plain text.
+    pub async fn format_capabilities(&self, channel_name: &str) -> FormatCapabilities {
+        self.channels
+            .read()
+            .await
+            .get(channel_name)
+            .map(|c| c.format_capabilities())
+            .unwrap_or_default()
+    }
+
     /// Check health of all channels.
     pub async fn health_check_all(&self) -> HashMap<String, Result<(), ChannelError>> {
         let channels = self.channels.read().await;
@@ -202,6 +561,50 @@ impl ChannelManager {
         results
     }
 
+    /// Run every channel's health check and collect the outcome.
+    pub async fn self_test(&self) -> ChannelReadiness {
+        let mut results: Vec<_> = self.health_check_all().await.into_iter().collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut readiness = ChannelReadiness::default();
+        for (name, result) in results {
+            match result {
+                Ok(()) => readiness.healthy.push(name),
+                Err(e) => readiness.unhealthy.push((name, e)),
+            }
+        }
+        readiness
+    }
+
+    /// Apply the startup health policy: log the self-test result and, under
+    /// [`StartupHealthCheck::Fail`], shut everything down on any failure.
+    async fn run_startup_health_check(&self) -> Result<(), ChannelError> {
+        if self.startup_health_check == StartupHealthCheck::Off {
+            return Ok(());
+        }
+
+        let readiness = self.self_test().await;
+        if readiness.is_ready() {
+            tracing::info!("Channel self-test: {}", readiness.summary());
+            return Ok(());
+        }
+
+        match self.startup_health_check {
+            StartupHealthCheck::Fail => {
+                tracing::error!("Channel self-test failed: {}", readiness.summary());
+                self.shutdown_all().await?;
+                Err(ChannelError::StartupFailed {
+                    name: "self-test".to_string(),
+                    reason: readiness.summary(),
+                })
+            }
+            _ => {
+                tracing::warn!("Channel self-test: {}", readiness.summary());
+                Ok(())
+            }
+        }
+    }
+
     /// Shutdown all channels.
     pub async fn shutdown_all(&self) -> Result<(), ChannelError> {
         let channels = self.channels.read().await;
@@ -219,8 +622,407 @@ impl ChannelManager {
     }
 }
 
+/// Split `response` into pieces that fit the channel's message length limit.
+/// Every piece keeps the original thread and metadata.
+fn split_response(
+    channel: &dyn Channel,
+    response: OutgoingResponse,
+    numbered: bool,
+) -> Vec<OutgoingResponse> {
+    let parts = channel
+        .format_capabilities()
+        .split_message(&response.content, numbered);
+    if parts.len() == 1 {
+        return vec![response];
+    }
+    parts
+        .into_iter()
+        .map(|content| OutgoingResponse {
+            content,
+            ..response.clone()
+        })
+        .collect()
+}
+
+async fn send_broadcast(
+    channel: &dyn Channel,
+    user_id: &str,
+    response: OutgoingResponse,
+    numbered: bool,
+) -> Result<(), ChannelError> {
+    for part in split_response(channel, response, numbered) {
+        channel.broadcast(user_id, part).await?;
+    }
+    Ok(())
+}
+
 impl Default for ChannelManager {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    /// Channel with a fixed health-check outcome.
+    struct ProbeChannel {
+        name: &'static str,
+        healthy: bool,
+    }
+
+    #[async_trait]
+    impl Channel for ProbeChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn start(&self) -> Result<MessageStream, ChannelError> {
+            Ok(Box::pin(stream::pending::<IncomingMessage>()))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &IncomingMessage,
+            _response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), ChannelError> {
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(ChannelError::HealthCheckFailed {
+                    name: format!("{}: invalid bot token", self.name),
+                })
+            }
+        }
+    }
+
+    async fn manager_with(
+        policy: StartupHealthCheck,
+        channels: &[(&'static str, bool)],
+    ) -> ChannelManager {
+        let manager = ChannelManager::new().with_startup_health_check(policy);
+        for &(name, healthy) in channels {
+            manager.add(Box::new(ProbeChannel { name, healthy })).await;
+        }
+        manager
+    }
+
+    #[tokio::test]
+    async fn self_test_reports_aggregate_readiness() {
+        let manager = manager_with(
+            StartupHealthCheck::Warn,
+            &[("repl", true), ("telegram", false), ("gateway", true)],
+        )
+        .await;
+
+        let readiness = manager.self_test().await;
+        assert!(!readiness.is_ready());
+        assert_eq!(readiness.healthy, vec!["gateway", "repl"]);
+        assert_eq!(readiness.unhealthy.len(), 1);
+        assert_eq!(readiness.unhealthy[0].0, "telegram");
+        assert_eq!(
+            readiness.summary(),
+            "2/3 channels healthy; unhealthy: telegram \
+             (Channel health check failed: telegram: invalid bot token)"
+        );
+    }
+
+    #[tokio::test]
+    async fn unhealthy_channel_fails_startup_under_fail_policy() {
+        let manager = manager_with(
+            StartupHealthCheck::Fail,
+            &[("repl", true), ("telegram", false)],
+        )
+        .await;
+
+        let err = match manager.start_all().await {
+            Ok(_) => panic!("startup should fail when a channel is unhealthy"),
+            Err(e) => e,
+        };
+        assert!(matches!(err, ChannelError::StartupFailed { .. }));
+        assert!(err.to_string().contains("telegram"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn unhealthy_channel_only_warns_under_warn_policy() {
+        let manager = manager_with(
+            StartupHealthCheck::Warn,
+            &[("repl", true), ("telegram", false)],
+        )
+        .await;
+        assert!(manager.start_all().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn healthy_channels_pass_fail_policy() {
+        let manager = manager_with(
+            StartupHealthCheck::Fail,
+            &[("repl", true), ("gateway", true)],
+        )
+        .await;
+        assert!(manager.start_all().await.is_ok());
+        assert!(manager.self_test().await.is_ready());
+    }
+
+    /// Channel that records every broadcast it delivers.
+    struct RecordingChannel {
+        sent: Arc<std::sync::Mutex<Vec<String>>>,
+        format: FormatCapabilities,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "telegram"
+        }
+
+        async fn start(&self) -> Result<MessageStream, ChannelError> {
+            Ok(Box::pin(stream::pending::<IncomingMessage>()))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &IncomingMessage,
+            _response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn broadcast(
+            &self,
+            _user_id: &str,
+            response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            self.sent.lock().unwrap().push(response.content);
+            Ok(())
+        }
+
+        fn format_capabilities(&self) -> FormatCapabilities {
+            self.format
+        }
+
+        async fn health_check(&self) -> Result<(), ChannelError> {
+            Ok(())
+        }
+    }
+
+    async fn throttled_manager(
+        window: Duration,
+    ) -> (ChannelManager, Arc<std::sync::Mutex<Vec<String>>>) {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager = ChannelManager::new().with_broadcast_throttle(HashMap::from([(
+            "telegram".to_string(),
+            BroadcastThrottleConfig {
+                window,
+                digest: true,
+            },
+        )]));
+        manager
+            .add(Box::new(RecordingChannel {
+                sent: Arc::clone(&sent),
+                format: FormatCapabilities::plain(),
+            }))
+            .await;
+        (manager, sent)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rapid_broadcasts_are_coalesced() {
+        let (manager, sent) = throttled_manager(Duration::from_millis(100)).await;
+
+        for i in 1..=4 {
+            let response = OutgoingResponse::text(format!("job {} done", i));
+            manager
+                .broadcast("telegram", "alice", response)
+                .await
+                .unwrap();
+        }
+        // The first goes out at once; the rest wait for the window.
+        assert_eq!(sent.lock().unwrap().len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(
+            sent[1],
+            "3 notifications:\n\njob 2 done\n\njob 3 done\n\njob 4 done"
+        );
+    }
+
+    async fn quiet_manager(
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> (ChannelManager, Arc<std::sync::Mutex<Vec<String>>>) {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let quiet_hours = QuietHours::new(crate::config::QuietHoursConfig {
+            start: chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: chrono::NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            utc_offset: chrono::FixedOffset::east_opt(0).unwrap(),
+            digest: true,
+        })
+        .with_clock(Arc::new(move || now));
+        let manager = ChannelManager::new().with_quiet_hours(quiet_hours);
+        manager
+            .add(Box::new(RecordingChannel {
+                sent: Arc::clone(&sent),
+                format: FormatCapabilities::plain(),
+            }))
+            .await;
+        (manager, sent)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn broadcasts_during_quiet_hours_are_queued() {
+        use chrono::TimeZone;
+
+        // An hour before quiet hours end.
+        let now = chrono::Utc.with_ymd_and_hms(2025, 3, 14, 6, 0, 0).unwrap();
+        let (manager, sent) = quiet_manager(now).await;
+
+        for text in ["job 1 done", "job 2 done"] {
+            manager
+                .broadcast("telegram", "alice", OutgoingResponse::text(text))
+                .await
+                .unwrap();
+        }
+        manager
+            .broadcast(
+                "telegram",
+                "alice",
+                OutgoingResponse::text("disk full").urgent(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), vec!["disk full"]);
+
+        tokio::time::sleep(Duration::from_secs(3599)).await;
+        assert_eq!(*sent.lock().unwrap(), vec!["disk full"]);
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec!["disk full", "2 notifications:\n\njob 1 done\n\njob 2 done"]
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcasts_outside_quiet_hours_are_delivered() {
+        use chrono::TimeZone;
+
+        let now = chrono::Utc.with_ymd_and_hms(2025, 3, 14, 12, 0, 0).unwrap();
+        let (manager, sent) = quiet_manager(now).await;
+
+        manager
+            .broadcast("telegram", "alice", OutgoingResponse::text("hi"))
+            .await
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), vec!["hi"]);
+    }
+
+    /// Channel whose stream replays a fixed list of messages.
+    struct ReplayChannel {
+        messages: Vec<IncomingMessage>,
+    }
+
+    #[async_trait]
+    impl Channel for ReplayChannel {
+        fn name(&self) -> &str {
+            "webhook"
+        }
+
+        async fn start(&self) -> Result<MessageStream, ChannelError> {
+            Ok(Box::pin(stream::iter(self.messages.clone())))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &IncomingMessage,
+            _response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), ChannelError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn redelivered_messages_are_dropped() {
+        use futures::StreamExt;
+
+        let msg = |id: &str, text: &str| {
+            IncomingMessage::new("webhook", "alice", text).with_message_id(id)
+        };
+        let manager = ChannelManager::new()
+            .with_startup_health_check(StartupHealthCheck::Off)
+            .with_inbound_dedup(Duration::from_secs(60));
+        manager
+            .add(Box::new(ReplayChannel {
+                messages: vec![msg("1", "first"), msg("1", "first"), msg("2", "second")],
+            }))
+            .await;
+
+        let stream = manager.start_all().await.unwrap();
+        let received: Vec<String> = stream.take(2).map(|m| m.content).collect().await;
+        assert_eq!(received, vec!["first", "second"]);
+        assert_eq!(manager.inbound_duplicates_dropped(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spaced_out_broadcasts_pass_through() {
+        let (manager, sent) = throttled_manager(Duration::from_millis(30)).await;
+
+        for _ in 0..3 {
+            let results = manager
+                .broadcast_all("alice", OutgoingResponse::text("heartbeat"))
+                .await;
+            assert!(results.iter().all(|(_, r)| r.is_ok()));
+            tokio::time::sleep(Duration::from_millis(60)).await;
+        }
+        assert_eq!(*sent.lock().unwrap(), vec!["heartbeat"; 3]);
+    }
+
+    #[tokio::test]
+    async fn long_broadcasts_are_split_to_fit_the_channel() {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager = ChannelManager::new();
+        manager
+            .add(Box::new(RecordingChannel {
+                sent: Arc::clone(&sent),
+                format: FormatCapabilities {
+                    max_message_length: Some(12),
+                    ..FormatCapabilities::plain()
+                },
+            }))
+            .await;
+
+        assert_eq!(
+            manager
+                .format_capabilities("telegram")
+                .await
+                .max_message_length,
+            Some(12)
+        );
+        manager
+            .broadcast(
+                "telegram",
+                "alice",
+                OutgoingResponse::text("build finished\n\nall tests pass"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec!["build ", "finished\n\n", "all tests ", "pass"]
+        );
+    }
+}
@@ -27,19 +27,30 @@
 //! WASM channels allow dynamic loading of channel implementations at runtime.
 //! See the [`wasm`] module for details.
 
+mod backpressure;
 mod channel;
+mod dedup;
 mod http;
 mod manager;
+mod quiet_hours;
 mod repl;
 mod signal;
+mod split;
+mod throttle;
 pub mod wasm;
 pub mod web;
 mod webhook_server;
 
-pub use channel::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+pub use backpressure::OverflowStats;
+pub use channel::{
+    Channel, FormatCapabilities, IncomingMessage, META_MESSAGE_ID, META_REPLY_TO, MessageStream,
+    OutgoingResponse, Reaction, ReasoningTrace, StatusUpdate, TracedToolCall,
+};
 pub use http::HttpChannel;
-pub use manager::ChannelManager;
+pub use manager::{ChannelManager, ChannelReadiness};
+pub use quiet_hours::QuietHours;
 pub use repl::ReplChannel;
 pub use signal::SignalChannel;
+pub use split::{NUMBERING_RESERVE, number_parts, split_message};
 pub use web::GatewayChannel;
 pub use webhook_server::{WebhookServer, WebhookServerConfig};
@@ -0,0 +1,241 @@
+//! Quiet hours (do-not-disturb) for proactive broadcasts.
+//!
+//! During the configured daily window, non-urgent broadcasts are queued per
+//! `(channel, user)` instead of delivered. When the window closes the queue
+//! is released, either message by message or merged into one digest.
+//! Responses marked [`OutgoingResponse::urgent`] always go straight through.
+//!
+//! Like the broadcast throttle, this only makes decisions; `ChannelManager`
+//! does the sending and schedules the release.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::channels::OutgoingResponse;
+use crate::channels::throttle::coalesce;
+use crate::config::QuietHoursConfig;
+
+/// Source of the current time, injectable for tests.
+pub type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// What to do with an offered broadcast.
+#[derive(Debug)]
+pub enum QuietDecision {
+    /// Outside quiet hours (or urgent): deliver this response now.
+    Send(OutgoingResponse),
+    /// Queued as the first message for this user; the caller must call
+    /// [`QuietHours::release`] at the given time.
+    Schedule(DateTime<Utc>),
+    /// Queued behind messages whose release is already scheduled.
+    Queued,
+}
+
+/// Quiet-hours state shared by all channels of a `ChannelManager`.
+pub struct QuietHours {
+    config: QuietHoursConfig,
+    clock: Clock,
+    queues: Mutex<HashMap<(String, String), Vec<OutgoingResponse>>>,
+}
+
+impl QuietHours {
+    pub fn new(config: QuietHoursConfig) -> Self {
+        Self {
+            config,
+            clock: Arc::new(Utc::now),
+            queues: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the wall clock, e.g. with a fixed time in tests.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The current time according to this instance's clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        (self.clock)()
+    }
+
+    /// Whether `at` falls inside the quiet window.
+    pub fn is_quiet(&self, at: DateTime<Utc>) -> bool {
+        let QuietHoursConfig { start, end, .. } = self.config;
+        let local = at.with_timezone(&self.config.utc_offset).time();
+        if start <= end {
+            start <= local && local < end
+        } else {
+            local >= start || local < end
+        }
+    }
+
+    /// When the quiet window containing `at` closes, or `None` if `at` is
+    /// outside quiet hours.
+    pub fn window_end(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if !self.is_quiet(at) {
+            return None;
+        }
+        let local = at.with_timezone(&self.config.utc_offset);
+        let mut end = local.date_naive().and_time(self.config.end);
+        if end <= local.naive_local() {
+            end += Duration::days(1);
+        }
+        self.config
+            .utc_offset
+            .from_local_datetime(&end)
+            .single()
+            .map(|end| end.with_timezone(&Utc))
+    }
+
+    /// Decide whether `response` goes out now or waits for quiet hours to end.
+    pub fn offer(&self, channel: &str, user_id: &str, response: OutgoingResponse) -> QuietDecision {
+        if response.urgent {
+            return QuietDecision::Send(response);
+        }
+        let Some(end) = self.window_end(self.now()) else {
+            return QuietDecision::Send(response);
+        };
+
+        let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = queues
+            .entry((channel.to_string(), user_id.to_string()))
+            .or_default();
+        queue.push(response);
+        if queue.len() == 1 {
+            QuietDecision::Schedule(end)
+        } else {
+            QuietDecision::Queued
+        }
+    }
+
+    /// Take everything queued for `(channel, user_id)`, in arrival order, or
+    /// as a single digest when configured.
+    pub fn release(&self, channel: &str, user_id: &str) -> Vec<OutgoingResponse> {
+        let queued = self
+            .queues
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(channel.to_string(), user_id.to_string()))
+            .unwrap_or_default();
+        if self.config.digest && queued.len() > 1 {
+            vec![coalesce(queued, true)]
+        } else {
+            queued
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveTime};
+
+    use super::*;
+
+    fn config(start: u32, end: u32, offset_hours: i32, digest: bool) -> QuietHoursConfig {
+        QuietHoursConfig {
+            start: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+            utc_offset: FixedOffset::east_opt(offset_hours * 3600).unwrap(),
+            digest,
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 14, hour, minute, 0).unwrap()
+    }
+
+    fn fixed(time: DateTime<Utc>) -> Clock {
+        Arc::new(move || time)
+    }
+
+    #[test]
+    fn window_spanning_midnight() {
+        let quiet = QuietHours::new(config(22, 7, 0, false));
+        assert!(quiet.is_quiet(at(23, 0)));
+        assert!(quiet.is_quiet(at(3, 0)));
+        assert!(!quiet.is_quiet(at(7, 0)));
+        assert!(!quiet.is_quiet(at(12, 0)));
+
+        assert_eq!(
+            quiet.window_end(at(23, 0)),
+            Some(Utc.with_ymd_and_hms(2025, 3, 15, 7, 0, 0).unwrap())
+        );
+        assert_eq!(quiet.window_end(at(3, 0)), Some(at(7, 0)));
+        assert_eq!(quiet.window_end(at(12, 0)), None);
+    }
+
+    #[test]
+    fn window_is_in_local_time() {
+        // 22:00-07:00 at UTC+2 is 20:00-05:00 UTC.
+        let quiet = QuietHours::new(config(22, 7, 2, false));
+        assert!(quiet.is_quiet(at(21, 0)));
+        assert!(!quiet.is_quiet(at(6, 0)));
+        assert_eq!(
+            quiet.window_end(at(21, 0)),
+            Some(Utc.with_ymd_and_hms(2025, 3, 15, 5, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn broadcast_during_quiet_hours_is_queued() {
+        let quiet = QuietHours::new(config(22, 7, 0, false)).with_clock(fixed(at(23, 30)));
+
+        let release_at = match quiet.offer("telegram", "alice", OutgoingResponse::text("a")) {
+            QuietDecision::Schedule(end) => end,
+            other => panic!("expected Schedule, got {other:?}"),
+        };
+        assert_eq!(
+            release_at,
+            Utc.with_ymd_and_hms(2025, 3, 15, 7, 0, 0).unwrap()
+        );
+        assert!(matches!(
+            quiet.offer("telegram", "alice", OutgoingResponse::text("b")),
+            QuietDecision::Queued
+        ));
+
+        let released: Vec<String> = quiet
+            .release("telegram", "alice")
+            .into_iter()
+            .map(|r| r.content)
+            .collect();
+        assert_eq!(released, vec!["a", "b"]);
+        assert!(quiet.release("telegram", "alice").is_empty());
+    }
+
+    #[test]
+    fn broadcast_outside_quiet_hours_is_delivered() {
+        let quiet = QuietHours::new(config(22, 7, 0, false)).with_clock(fixed(at(12, 0)));
+        assert!(matches!(
+            quiet.offer("telegram", "alice", OutgoingResponse::text("hi")),
+            QuietDecision::Send(_)
+        ));
+    }
+
+    #[test]
+    fn urgent_broadcast_bypasses_quiet_hours() {
+        let quiet = QuietHours::new(config(22, 7, 0, false)).with_clock(fixed(at(23, 0)));
+        assert!(matches!(
+            quiet.offer(
+                "telegram",
+                "alice",
+                OutgoingResponse::text("down!").urgent()
+            ),
+            QuietDecision::Send(_)
+        ));
+    }
+
+    #[test]
+    fn queued_broadcasts_can_be_released_as_a_digest() {
+        let quiet = QuietHours::new(config(22, 7, 0, true)).with_clock(fixed(at(23, 0)));
+        quiet.offer("telegram", "alice", OutgoingResponse::text("job 1 done"));
+        quiet.offer("telegram", "alice", OutgoingResponse::text("job 2 done"));
+
+        let released = quiet.release("telegram", "alice");
+        assert_eq!(released.len(), 1);
+        assert_eq!(
+            released[0].content,
+            "2 notifications:\n\njob 1 done\n\njob 2 done"
+        );
+    }
+}
@@ -14,13 +14,16 @@
 //! - `/clear` - Clear the conversation
 //! - `/compact` - Compact the context
 //! - `/new` - Start a new thread
+//! - `/branch [turn]` - Copy the thread up to a turn into a new thread
+//! - `/paste` - Collect lines until a lone `.` (or Ctrl+D) and send them as one message
 //! - `yes`/`no`/`always` - Respond to tool approval prompts
 //! - `Esc` - Interrupt current operation
 
 use std::borrow::Cow;
 use std::io::{self, Write};
-use std::sync::Arc;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use rustyline::completion::Completer;
@@ -28,22 +31,31 @@ use rustyline::config::Config;
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
-use rustyline::validate::Validator;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::{
     Cmd as ReadlineCmd, CompletionType, ConditionalEventHandler, Editor, Event, EventContext,
     EventHandler, Helper, KeyCode, KeyEvent, Modifiers, RepeatCount,
 };
 use termimad::MadSkin;
+use tokio::io::AsyncReadExt;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 
 use crate::agent::truncate_for_preview;
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
-use crate::error::ChannelError;
+use crate::channels::{
+    Channel, FormatCapabilities, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate,
+};
+use crate::error::{ChannelError, WorkspaceError};
+use crate::tools::builtin::validate_path;
+use crate::workspace::{Workspace, paths};
 
 /// Max characters for tool result previews in the terminal.
 const CLI_TOOL_RESULT_MAX: usize = 200;
 
+/// Largest file or memory document the approval card reads to show a
+/// diff; bigger ones fall back to the raw parameters.
+const MAX_DIFF_FILE_BYTES: u64 = 256 * 1024;
+
 /// Max characters for thinking/status messages in the terminal.
 const CLI_STATUS_MAX: usize = 200;
 
@@ -70,8 +82,12 @@ const SLASH_COMMANDS: &[&str] = &[
     "/heartbeat",
     "/summarize",
     "/suggest",
+    "/iterations",
+    "/readonly",
     "/thread",
+    "/branch",
     "/resume",
+    "/paste",
 ];
 
 /// Rustyline helper for slash-command tab completion.
@@ -122,9 +138,63 @@ impl Highlighter for ReplHelper {
     }
 }
 
-impl Validator for ReplHelper {}
+impl Validator for ReplHelper {
+    /// Keep reading lines while a code fence is open, so a fenced snippet
+    /// typed or pasted at the prompt goes out as one message.
+    fn validate(&self, ctx: &mut ValidationContext<'_>) -> rustyline::Result<ValidationResult> {
+        if has_open_fence(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
 impl Helper for ReplHelper {}
 
+/// Whether `input` opens a ``` or ~~~ code block it never closes.
+fn has_open_fence(input: &str) -> bool {
+    let mut open: Option<&str> = None;
+    for line in input.lines() {
+        let Some(marker) = fence_marker(line) else {
+            continue;
+        };
+        match open {
+            Some(fence) if marker.starts_with(fence) && line.trim() == marker => open = None,
+            Some(_) => {}
+            None => open = Some(marker),
+        }
+    }
+    open.is_some()
+}
+
+/// Line that ends paste mode.
+const PASTE_SENTINEL: &str = ".";
+
+/// Lines collected in `/paste` mode, kept verbatim (indentation and blank
+/// lines included) until the sentinel or Ctrl+D.
+#[derive(Default)]
+struct PasteBuffer {
+    lines: Vec<String>,
+}
+
+impl PasteBuffer {
+    /// Add a line; on the sentinel, returns the collected message.
+    fn push(&mut self, line: &str) -> Option<String> {
+        if line.trim_end() == PASTE_SENTINEL {
+            return Some(self.finish());
+        }
+        self.lines.push(line.to_string());
+        None
+    }
+
+    /// Everything collected so far, with surrounding blank lines dropped.
+    fn finish(&mut self) -> String {
+        let text = std::mem::take(&mut self.lines).join("\n");
+        text.trim_matches('\n').to_string()
+    }
+}
+
 struct EscInterruptHandler {
     triggered: Arc<AtomicBool>,
 }
@@ -157,44 +227,356 @@ fn make_skin() -> MadSkin {
     skin
 }
 
+/// Incremental markdown renderer for streamed responses.
+///
+/// Prose is held back until a blank line ends the block, then rendered
+/// with termimad. Inside a ``` or ~~~ fence, each finished line is printed
+/// right away with code-block styling and no markdown interpretation, so
+/// `*` or `#` in code stay as typed.
+struct StreamRenderer {
+    skin: MadSkin,
+    width: usize,
+    /// Text after the last newline, not yet classified.
+    partial: String,
+    /// Prose lines waiting for a block boundary.
+    block: String,
+    /// The opening fence marker while inside a code block.
+    fence: Option<String>,
+}
+
+impl StreamRenderer {
+    fn new(width: usize) -> Self {
+        Self {
+            skin: make_skin(),
+            width,
+            partial: String::new(),
+            block: String::new(),
+            fence: None,
+        }
+    }
+
+    /// Feed a chunk; returns whatever can be printed now.
+    fn push(&mut self, chunk: &str) -> String {
+        self.partial.push_str(chunk);
+        let mut out = String::new();
+        while let Some(newline) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=newline).collect();
+            self.line(line.trim_end_matches(['\n', '\r']), &mut out);
+        }
+        out
+    }
+
+    /// Flush everything still buffered at the end of the stream.
+    fn finish(&mut self) -> String {
+        let mut out = String::new();
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            self.line(&line, &mut out);
+        }
+        self.flush_block(&mut out);
+        self.fence = None;
+        out
+    }
+
+    fn line(&mut self, line: &str, out: &mut String) {
+        let marker = fence_marker(line);
+        match &self.fence {
+            Some(open) => {
+                if marker.is_some_and(|m| m.starts_with(open.as_str()))
+                    && line.trim().chars().all(|c| c == '`' || c == '~')
+                {
+                    self.fence = None;
+                } else {
+                    out.push_str(&format!("  \x1b[32m{line}\x1b[0m\n"));
+                }
+            }
+            None => {
+                if let Some(marker) = marker {
+                    self.flush_block(out);
+                    self.fence = Some(marker.to_string());
+                } else if line.trim().is_empty() {
+                    self.flush_block(out);
+                    out.push('\n');
+                } else {
+                    self.block.push_str(line);
+                    self.block.push('\n');
+                }
+            }
+        }
+    }
+
+    fn flush_block(&mut self, out: &mut String) {
+        if self.block.is_empty() {
+            return;
+        }
+        let block = std::mem::take(&mut self.block);
+        let text = termimad::FmtText::from(&self.skin, &block, Some(self.width));
+        out.push_str(&text.to_string());
+    }
+}
+
+/// The fence marker (three or more backticks or tildes) a line opens or
+/// closes a code block with, if any.
+fn fence_marker(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
 /// Format JSON params as `key: value` lines for the approval card.
+///
+/// Nothing is truncated: what is shown is exactly what will run. Keys are
+/// cyan, strings green, numbers yellow, booleans and null magenta. Nested
+/// values are pretty-printed and multi-line strings (shell scripts, file
+/// contents) are shown quoted and JSON-escaped like other strings, but
+/// broken after each `\n`, so trailing newlines and `\r` stay visible.
+/// Control characters are escaped so a parameter can't rewrite the card
+/// with terminal escape sequences.
 fn format_json_params(params: &serde_json::Value, indent: &str) -> String {
+    let mut lines = Vec::new();
     match params {
         serde_json::Value::Object(map) => {
-            let mut lines = Vec::new();
             for (key, value) in map {
-                let val_str = match value {
-                    serde_json::Value::String(s) => {
-                        let display = if s.len() > 120 { &s[..120] } else { s };
-                        format!("\x1b[32m\"{display}\"\x1b[0m")
+                let key = format!("\x1b[36m{}\x1b[0m", escape_control(key));
+                match value {
+                    serde_json::Value::String(s) if s.contains('\n') => {
+                        lines.push(format!("{indent}{key}:"));
+                        let mut pieces: Vec<&str> = s.split_inclusive('\n').collect();
+                        if s.ends_with('\n') {
+                            pieces.push("");
+                        }
+                        let last = pieces.len() - 1;
+                        for (i, piece) in pieces.into_iter().enumerate() {
+                            let open = if i == 0 { '"' } else { ' ' };
+                            let close = if i == last { "\"" } else { "" };
+                            lines.push(format!(
+                                "{indent}  \x1b[32m{open}{}{close}\x1b[0m",
+                                json_escape(piece)
+                            ));
+                        }
                     }
                     other => {
-                        let rendered = other.to_string();
-                        if rendered.len() > 120 {
-                            format!("{}...", &rendered[..120])
-                        } else {
-                            rendered
-                        }
+                        let mut value_lines = highlight_json(other).into_iter();
+                        let first = value_lines.next().unwrap_or_default();
+                        lines.push(format!("{indent}{key}: {first}"));
+                        lines.extend(value_lines.map(|l| format!("{indent}{l}")));
                     }
-                };
-                lines.push(format!("{indent}\x1b[36m{key}\x1b[0m: {val_str}"));
+                }
+            }
+        }
+        other => lines.extend(
+            highlight_json(other)
+                .into_iter()
+                .map(|l| format!("{indent}{l}")),
+        ),
+    }
+    lines.join("\n")
+}
+
+/// Pretty-print `value` as colored JSON lines.
+fn highlight_json(value: &serde_json::Value) -> Vec<String> {
+    fn push_entry(lines: &mut Vec<String>, label: String, value: &serde_json::Value, last: bool) {
+        let mut value_lines = highlight_json(value).into_iter();
+        let first = value_lines.next().unwrap_or_default();
+        lines.push(format!("  {label}{first}"));
+        lines.extend(value_lines.map(|l| format!("  {l}")));
+        if !last && let Some(end) = lines.last_mut() {
+            end.push(',');
+        }
+    }
+
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            let mut lines = vec!["{".to_string()];
+            for (i, (key, item)) in map.iter().enumerate() {
+                // JSON encoding already escapes control characters.
+                let key = serde_json::Value::String(key.clone());
+                let label = format!("\x1b[36m{key}\x1b[0m: ");
+                push_entry(&mut lines, label, item, i + 1 == map.len());
+            }
+            lines.push("}".to_string());
+            lines
+        }
+        serde_json::Value::Array(items) if !items.is_empty() => {
+            let mut lines = vec!["[".to_string()];
+            for (i, item) in items.iter().enumerate() {
+                push_entry(&mut lines, String::new(), item, i + 1 == items.len());
+            }
+            lines.push("]".to_string());
+            lines
+        }
+        serde_json::Value::Object(_) => vec!["{}".to_string()],
+        serde_json::Value::Array(_) => vec!["[]".to_string()],
+        serde_json::Value::String(_) => vec![format!("\x1b[32m{value}\x1b[0m")],
+        serde_json::Value::Number(n) => vec![format!("\x1b[33m{n}\x1b[0m")],
+        serde_json::Value::Bool(_) | serde_json::Value::Null => {
+            vec![format!("\x1b[35m{value}\x1b[0m")]
+        }
+    }
+}
+
+/// The change a file-writing tool call would make, as `(old, new)` file
+/// contents, given the file's `current` contents if it exists.
+///
+/// Returns `None` for tools that don't write files and for malformed
+/// params, so the approval card falls back to showing the raw parameters.
+/// When `apply_patch` can't be matched against the file the diff is
+/// between its search and replacement strings.
+fn proposed_file_change(
+    tool_name: &str,
+    params: &serde_json::Value,
+    current: Option<String>,
+) -> Option<(String, String)> {
+    let param = |name: &str| params.get(name).and_then(|v| v.as_str());
+    match tool_name {
+        "write_file" => Some((current.unwrap_or_default(), param("content")?.to_string())),
+        "apply_patch" => {
+            let old_string = param("old_string")?;
+            let new_string = param("new_string")?;
+            let replace_all = params
+                .get("replace_all")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            match current {
+                Some(current) if !old_string.is_empty() && current.contains(old_string) => {
+                    let patched = if replace_all {
+                        current.replace(old_string, new_string)
+                    } else {
+                        current.replacen(old_string, new_string, 1)
+                    };
+                    Some((current, patched))
+                }
+                _ => Some((old_string.to_string(), new_string.to_string())),
             }
-            lines.join("\n")
         }
-        other => {
-            let pretty = serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string());
-            let truncated = if pretty.len() > 300 {
-                format!("{}...", &pretty[..300])
+        "memory_write" => {
+            let content = param("content")?;
+            let target = param("target").unwrap_or("daily_log");
+            let append = params
+                .get("append")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true);
+            let current = current.unwrap_or_default();
+            // Mirrors `MemoryWriteTool`: daily log entries are always
+            // appended with a timestamp, MEMORY.md entries are separated
+            // by a blank line.
+            let (entry, separator) = match target {
+                "daily_log" => (
+                    format!("[{}] {content}", chrono::Utc::now().format("%H:%M:%S")),
+                    "\n",
+                ),
+                "memory" => (content.to_string(), "\n\n"),
+                _ => (content.to_string(), "\n"),
+            };
+            let new = if (append || target == "daily_log") && !current.is_empty() {
+                format!("{current}{separator}{entry}")
             } else {
-                pretty
+                entry
             };
-            truncated
-                .lines()
-                .map(|l| format!("{indent}\x1b[90m{l}\x1b[0m"))
-                .collect::<Vec<_>>()
-                .join("\n")
+            Some((current, new))
         }
+        _ => None,
+    }
+}
+
+/// Workspace document a `memory_write` call writes to.
+fn memory_write_path(params: &serde_json::Value) -> String {
+    match params
+        .get("target")
+        .and_then(|v| v.as_str())
+        .unwrap_or("daily_log")
+    {
+        "memory" => paths::MEMORY.to_string(),
+        "heartbeat" => paths::HEARTBEAT.to_string(),
+        "daily_log" => format!("daily/{}.md", chrono::Utc::now().format("%Y-%m-%d")),
+        path => path.to_string(),
+    }
+}
+
+/// Contents of the file at `path` for the approval diff: `Some(None)` if
+/// nothing is there yet, `None` if it can't be shown because it is not a
+/// regular file, is larger than [`MAX_DIFF_FILE_BYTES`], or isn't UTF-8.
+async fn read_for_diff(path: &Path) -> Option<Option<String>> {
+    let metadata = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Some(None),
+        Err(_) => return None,
+    };
+    if !metadata.is_file() || metadata.len() > MAX_DIFF_FILE_BYTES {
+        return None;
+    }
+    // The file may have grown since the stat.
+    let mut bytes = Vec::new();
+    tokio::fs::File::open(path)
+        .await
+        .ok()?
+        .take(MAX_DIFF_FILE_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .await
+        .ok()?;
+    if bytes.len() as u64 > MAX_DIFF_FILE_BYTES {
+        return None;
+    }
+    String::from_utf8(bytes).ok().map(Some)
+}
+
+/// Format the change from `old` to `new` as a unified diff for the
+/// approval card: removed lines red, added lines green, hunk headers cyan.
+/// `old_label` is `/dev/null` for a file that doesn't exist yet.
+fn format_unified_diff(
+    old_label: &str,
+    new_label: &str,
+    old: &str,
+    new: &str,
+    indent: &str,
+) -> String {
+    let diff = similar::TextDiff::from_lines(old, new);
+    let mut lines = vec![
+        format!("{indent}\x1b[1m--- {}\x1b[0m", escape_control(old_label)),
+        format!("{indent}\x1b[1m+++ {}\x1b[0m", escape_control(new_label)),
+    ];
+    let unified = diff.unified_diff();
+    let mut hunks = unified.iter_hunks().peekable();
+    if hunks.peek().is_none() {
+        lines.push(format!("{indent}\x1b[90m(no changes)\x1b[0m"));
+    }
+    for hunk in hunks {
+        lines.push(format!("{indent}\x1b[36m{}\x1b[0m", hunk.header()));
+        for change in hunk.iter_changes() {
+            let text = escape_control(change.value().trim_end_matches(['\n', '\r']));
+            lines.push(match change.tag() {
+                similar::ChangeTag::Delete => format!("{indent}\x1b[31m-{text}\x1b[0m"),
+                similar::ChangeTag::Insert => format!("{indent}\x1b[32m+{text}\x1b[0m"),
+                similar::ChangeTag::Equal => format!("{indent} {text}"),
+            });
+        }
+    }
+    lines.join("\n")
+}
+
+/// `text` as the inside of a JSON string literal, without the quotes.
+fn json_escape(text: &str) -> String {
+    let quoted = serde_json::Value::String(text.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Escape control characters (including ESC) so they print as text.
+fn escape_control(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(char::is_control) {
+        return Cow::Borrowed(text);
     }
+    Cow::Owned(
+        text.chars()
+            .map(|c| {
+                if c.is_control() {
+                    c.escape_default().to_string()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect(),
+    )
 }
 
 /// REPL channel with line editing and markdown rendering.
@@ -205,8 +587,12 @@ pub struct ReplChannel {
     debug_mode: Arc<AtomicBool>,
     /// Whether we're currently streaming (chunks have been printed without a trailing newline).
     is_streaming: Arc<AtomicBool>,
+    /// Renders streamed chunks as markdown, block by block.
+    stream_renderer: Arc<Mutex<StreamRenderer>>,
     /// When true, the one-liner startup banner is suppressed (boot screen shown instead).
     suppress_banner: Arc<AtomicBool>,
+    /// Workspace `memory_write` approval cards diff against, if any.
+    workspace: Option<Arc<Workspace>>,
 }
 
 impl ReplChannel {
@@ -216,7 +602,9 @@ impl ReplChannel {
             single_message: None,
             debug_mode: Arc::new(AtomicBool::new(false)),
             is_streaming: Arc::new(AtomicBool::new(false)),
+            stream_renderer: Arc::new(Mutex::new(StreamRenderer::new(80))),
             suppress_banner: Arc::new(AtomicBool::new(false)),
+            workspace: None,
         }
     }
 
@@ -226,10 +614,18 @@ impl ReplChannel {
             single_message: Some(message),
             debug_mode: Arc::new(AtomicBool::new(false)),
             is_streaming: Arc::new(AtomicBool::new(false)),
+            stream_renderer: Arc::new(Mutex::new(StreamRenderer::new(80))),
             suppress_banner: Arc::new(AtomicBool::new(false)),
+            workspace: None,
         }
     }
 
+    /// Show `memory_write` approvals as a diff against this workspace.
+    pub fn with_workspace(mut self, workspace: Arc<Workspace>) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
     /// Suppress the one-liner startup banner (boot screen will be shown instead).
     pub fn suppress_banner(&self) {
         self.suppress_banner.store(true, Ordering::Relaxed);
@@ -238,6 +634,41 @@ impl ReplChannel {
     fn is_debug(&self) -> bool {
         self.debug_mode.load(Ordering::Relaxed)
     }
+
+    /// The change a file- or memory-writing call would make, as a unified
+    /// diff for the approval card. `None` means show the raw parameters:
+    /// the tool doesn't write files, or the current contents can't be read.
+    async fn approval_diff(
+        &self,
+        tool_name: &str,
+        params: &serde_json::Value,
+        indent: &str,
+    ) -> Option<String> {
+        let (label, current) = match tool_name {
+            "write_file" | "apply_patch" => {
+                // Resolved like the registered file tools, which have no
+                // base directory.
+                let path = params.get("path")?.as_str()?;
+                let resolved = validate_path(path, None).ok()?;
+                let current = read_for_diff(&resolved).await?;
+                (resolved.display().to_string(), current)
+            }
+            "memory_write" => {
+                let path = memory_write_path(params);
+                let current = match self.workspace.as_ref()?.read(&path).await {
+                    Ok(doc) if doc.content.len() as u64 <= MAX_DIFF_FILE_BYTES => Some(doc.content),
+                    Err(WorkspaceError::DocumentNotFound { .. }) => None,
+                    _ => return None,
+                };
+                (path, current)
+            }
+            _ => return None,
+        };
+        let is_new = current.is_none() && tool_name != "apply_patch";
+        let (old, new) = proposed_file_change(tool_name, params, current)?;
+        let old_label = if is_new { "/dev/null" } else { &label };
+        Some(format_unified_diff(old_label, &label, &old, &new, indent))
+    }
 }
 
 impl Default for ReplChannel {
@@ -259,6 +690,7 @@ fn print_help() {
     println!("  {h}Commands{r}");
     println!("  {c}/help{r}              {d}show this help{r}");
     println!("  {c}/debug{r}             {d}toggle verbose output{r}");
+    println!("  {c}/model{r} [name]      {d}show or switch the active model{r}");
     println!("  {c}/quit{r} {c}/exit{r}        {d}exit the repl{r}");
     println!();
     println!("  {h}Conversation{r}");
@@ -267,6 +699,8 @@ fn print_help() {
     println!("  {c}/clear{r}             {d}clear conversation{r}");
     println!("  {c}/compact{r}           {d}compact context window{r}");
     println!("  {c}/new{r}               {d}new conversation thread{r}");
+    println!("  {c}/branch{r} [turn]     {d}branch thread at a turn{r}");
+    println!("  {c}/paste{r}             {d}send several lines as one message (end with .){r}");
     println!("  {c}/interrupt{r}         {d}stop current operation{r}");
     println!("  {c}esc{r}                {d}stop current operation{r}");
     println!();
@@ -343,8 +777,12 @@ impl Channel for ReplChannel {
                 println!();
             }
 
+            let mut paste: Option<PasteBuffer> = None;
+
             loop {
-                let prompt = if debug_mode.load(Ordering::Relaxed) {
+                let prompt = if paste.is_some() {
+                    "\x1b[90m\u{2026}\x1b[0m "
+                } else if debug_mode.load(Ordering::Relaxed) {
                     "\x1b[33m[debug]\x1b[0m \x1b[1;36m\u{203A}\x1b[0m "
                 } else {
                     "\x1b[1;36m\u{203A}\x1b[0m "
@@ -352,6 +790,21 @@ impl Channel for ReplChannel {
 
                 match rl.readline(prompt) {
                     Ok(line) => {
+                        if let Some(buffer) = paste.as_mut() {
+                            let Some(text) = buffer.push(&line) else {
+                                continue;
+                            };
+                            paste = None;
+                            if !text.is_empty()
+                                && tx
+                                    .blocking_send(IncomingMessage::new("repl", "default", &text))
+                                    .is_err()
+                            {
+                                break;
+                            }
+                            continue;
+                        }
+
                         let line = line.trim();
                         if line.is_empty() {
                             continue;
@@ -371,6 +824,13 @@ impl Channel for ReplChannel {
                                 print_help();
                                 continue;
                             }
+                            "/paste" => {
+                                println!(
+                                    "\x1b[90mpaste mode: end with a line containing only . or Ctrl+D, Ctrl+C cancels\x1b[0m"
+                                );
+                                paste = Some(PasteBuffer::default());
+                                continue;
+                            }
                             "/debug" => {
                                 let current = debug_mode.load(Ordering::Relaxed);
                                 debug_mode.store(!current, Ordering::Relaxed);
@@ -390,7 +850,12 @@ impl Channel for ReplChannel {
                         }
                     }
                     Err(ReadlineError::Interrupted) => {
-                        if esc_interrupt_triggered_for_thread.swap(false, Ordering::Relaxed) {
+                        let esc = esc_interrupt_triggered_for_thread.swap(false, Ordering::Relaxed);
+                        if paste.take().is_some() {
+                            println!("\x1b[90mpaste cancelled\x1b[0m");
+                            continue;
+                        }
+                        if esc {
                             // Esc: interrupt current operation and keep REPL open.
                             let msg = IncomingMessage::new("repl", "default", "/interrupt");
                             if tx.blocking_send(msg).is_err() {
@@ -404,6 +869,18 @@ impl Channel for ReplChannel {
                         }
                     }
                     Err(ReadlineError::Eof) => {
+                        // Ctrl+D in paste mode sends what was collected
+                        if let Some(mut buffer) = paste.take() {
+                            let text = buffer.finish();
+                            if !text.is_empty()
+                                && tx
+                                    .blocking_send(IncomingMessage::new("repl", "default", &text))
+                                    .is_err()
+                            {
+                                break;
+                            }
+                            continue;
+                        }
                         // Ctrl+D: send /quit so the agent loop runs graceful shutdown
                         let msg = IncomingMessage::new("repl", "default", "/quit");
                         let _ = tx.blocking_send(msg);
@@ -433,8 +910,14 @@ impl Channel for ReplChannel {
             .unwrap_or(80);
 
         // If we were streaming, the content was already printed via StreamChunk.
-        // Just finish the line and reset.
+        // Flush what the renderer still holds, finish the line and reset.
         if self.is_streaming.swap(false, Ordering::Relaxed) {
+            let rest = self
+                .stream_renderer
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .finish();
+            print!("{rest}");
             println!();
             println!();
             return Ok(());
@@ -481,16 +964,29 @@ impl Channel for ReplChannel {
             }
             StatusUpdate::StreamChunk(chunk) => {
                 // Print separator on the false-to-true transition
+                let mut renderer = self
+                    .stream_renderer
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner());
                 if !self.is_streaming.swap(true, Ordering::Relaxed) {
                     let width = crossterm::terminal::size()
                         .map(|(w, _)| w as usize)
                         .unwrap_or(80);
                     let sep_width = width.min(80);
                     eprintln!("\x1b[90m{}\x1b[0m", "\u{2500}".repeat(sep_width));
+                    *renderer = StreamRenderer::new(width);
                 }
-                print!("{chunk}");
+                print!("{}", renderer.push(&chunk));
                 let _ = io::stdout().flush();
             }
+            StatusUpdate::ReasoningTrace(trace) => {
+                if debug {
+                    for line in trace.summary().lines() {
+                        let display = truncate_for_preview(line, CLI_STATUS_MAX);
+                        eprintln!("  \x1b[90m\u{2502} {display}\x1b[0m");
+                    }
+                }
+            }
             StatusUpdate::JobStarted {
                 job_id,
                 title,
@@ -546,9 +1042,14 @@ impl Channel for ReplChannel {
                 eprintln!("  \u{2502}");
 
                 // Params
-                let param_lines = format_json_params(&parameters, "  \u{2502}   ");
-                // The format_json_params already includes the indent prefix
-                // but we need to handle the case where each line already starts with it
+                // File-writing tools show the change they would make to the
+                // file on disk; everything else shows its full parameters,
+                // never truncated. Each line carries the card's left border.
+                let indent = "  \u{2502}   ";
+                let param_lines = match self.approval_diff(&tool_name, &parameters, indent).await {
+                    Some(diff) => diff,
+                    None => format_json_params(&parameters, indent),
+                };
                 for line in param_lines.lines() {
                     eprintln!("{line}");
                 }
@@ -608,6 +1109,11 @@ impl Channel for ReplChannel {
         Ok(())
     }
 
+    fn format_capabilities(&self) -> FormatCapabilities {
+        // Rendered with termimad, which handles code blocks and tables.
+        FormatCapabilities::markdown()
+    }
+
     async fn health_check(&self) -> Result<(), ChannelError> {
         Ok(())
     }
@@ -616,3 +1122,237 @@ impl Channel for ReplChannel {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip_ansi(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn approval_params_are_not_truncated() {
+        let command = format!("echo {} && rm -rf /tmp/x", "a".repeat(300));
+        let params = serde_json::json!({ "command": command, "timeout": 30 });
+        let rendered = strip_ansi(&format_json_params(&params, "| "));
+        assert_eq!(rendered, format!("| command: \"{command}\"\n| timeout: 30"));
+    }
+
+    #[test]
+    fn approval_params_show_multiline_strings_and_nesting() {
+        let params = serde_json::json!({
+            "content": "line one\nline two",
+            "options": { "append": false, "tags": ["a", null] },
+        });
+        let rendered = strip_ansi(&format_json_params(&params, ""));
+        assert_eq!(
+            rendered,
+            "content:\n  \"line one\\n\n   line two\"\n\
+             options: {\n  \"append\": false,\n  \"tags\": [\n    \"a\",\n    null\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn approval_params_keep_trailing_newlines_and_carriage_returns() {
+        let params = serde_json::json!({ "content": "a\r\nb\n\n" });
+        let rendered = strip_ansi(&format_json_params(&params, ""));
+        assert_eq!(rendered, "content:\n  \"a\\r\\n\n   b\\n\n   \\n\n   \"");
+    }
+
+    #[test]
+    fn approval_params_highlight_by_type_and_escape_terminal_codes() {
+        let params = serde_json::json!({ "n": 1, "s": "x", "b": true, "m": "ok\n\x1b[2Khidden" });
+        let rendered = format_json_params(&params, "");
+        assert!(rendered.contains("\x1b[36mn\x1b[0m: \x1b[33m1\x1b[0m"));
+        assert!(rendered.contains("\x1b[32m\"x\"\x1b[0m"));
+        assert!(rendered.contains("\x1b[35mtrue\x1b[0m"));
+        // The escape in the multi-line string is printed, not interpreted.
+        assert!(rendered.contains("\\u001b[2Khidden"));
+        assert!(!rendered.contains("\x1b[2K"));
+    }
+
+    #[test]
+    fn approval_diff_for_patch_shows_changed_lines_in_context() {
+        let params = serde_json::json!({
+            "path": "src/lib.rs",
+            "old_string": "let b = 2;",
+            "new_string": "let b = 3;",
+        });
+        let current = "let a = 1;\nlet b = 2;\nlet c = 3;\n".to_string();
+        let (old, new) = proposed_file_change("apply_patch", &params, Some(current))
+            .expect("apply_patch is a file-writing tool");
+        let rendered = format_unified_diff("src/lib.rs", "src/lib.rs", &old, &new, "| ");
+        assert!(rendered.contains("\x1b[31m-let b = 2;\x1b[0m"));
+        assert!(rendered.contains("\x1b[32m+let b = 3;\x1b[0m"));
+        assert_eq!(
+            strip_ansi(&rendered),
+            "| --- src/lib.rs\n| +++ src/lib.rs\n| @@ -1,3 +1,3 @@\n\
+             |  let a = 1;\n| -let b = 2;\n| +let b = 3;\n|  let c = 3;"
+        );
+    }
+
+    #[test]
+    fn approval_diff_for_new_file_and_unmatched_patch() {
+        let params = serde_json::json!({ "path": "notes.md", "content": "hello\n" });
+        let (old, new) = proposed_file_change("write_file", &params, None)
+            .expect("write_file is a file-writing tool");
+        let rendered = strip_ansi(&format_unified_diff(
+            "/dev/null",
+            "notes.md",
+            &old,
+            &new,
+            "",
+        ));
+        assert!(rendered.starts_with("--- /dev/null\n+++ notes.md\n"));
+        assert!(rendered.ends_with("\n+hello"));
+
+        // A patch that doesn't match the file diffs its own strings.
+        let params = serde_json::json!({ "path": "a", "old_string": "x", "new_string": "y" });
+        let change = proposed_file_change("apply_patch", &params, Some("z".to_string()));
+        assert_eq!(change, Some(("x".to_string(), "y".to_string())));
+
+        let params = serde_json::json!({ "path": "a", "command": "ls" });
+        assert_eq!(proposed_file_change("shell", &params, None), None);
+    }
+
+    #[tokio::test]
+    async fn approval_diff_reads_only_small_regular_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "old\n").unwrap();
+        let repl = ReplChannel::new();
+
+        let params = serde_json::json!({ "path": file.to_str().unwrap(), "content": "new\n" });
+        let diff = repl.approval_diff("write_file", &params, "").await.unwrap();
+        assert!(strip_ansi(&diff).contains("\n-old\n+new"));
+
+        // A directory, or a file over the cap, falls back to the raw params.
+        let params = serde_json::json!({ "path": dir.path().to_str().unwrap(), "content": "x" });
+        assert_eq!(repl.approval_diff("write_file", &params, "").await, None);
+        std::fs::write(&file, "a".repeat(MAX_DIFF_FILE_BYTES as usize + 1)).unwrap();
+        let params = serde_json::json!({ "path": file.to_str().unwrap(), "content": "x" });
+        assert_eq!(repl.approval_diff("write_file", &params, "").await, None);
+
+        // Without a workspace there is nothing to diff memory writes against.
+        let params = serde_json::json!({ "target": "memory", "content": "x" });
+        assert_eq!(repl.approval_diff("memory_write", &params, "").await, None);
+    }
+
+    #[test]
+    fn approval_diff_for_memory_write_follows_target_and_append() {
+        let current = Some("- likes tea".to_string());
+        let params = serde_json::json!({ "target": "memory", "content": "- likes jazz" });
+        let (old, new) = proposed_file_change("memory_write", &params, current.clone()).unwrap();
+        assert_eq!(old, "- likes tea");
+        assert_eq!(new, "- likes tea\n\n- likes jazz");
+
+        let params = serde_json::json!({
+            "target": "projects/a.md",
+            "content": "rewritten",
+            "append": false,
+        });
+        let (_, new) = proposed_file_change("memory_write", &params, current).unwrap();
+        assert_eq!(new, "rewritten");
+        assert_eq!(memory_write_path(&params), "projects/a.md");
+    }
+
+    #[test]
+    fn paste_buffer_collects_until_sentinel() {
+        let mut buffer = PasteBuffer::default();
+        assert_eq!(buffer.push(""), None);
+        assert_eq!(buffer.push("fn main() {"), None);
+        assert_eq!(buffer.push("    println!(\"hi\");"), None);
+        assert_eq!(buffer.push(""), None);
+        assert_eq!(buffer.push("}"), None);
+        assert_eq!(
+            buffer.push(".").as_deref(),
+            Some("fn main() {\n    println!(\"hi\");\n\n}")
+        );
+        // The buffer starts over for the next paste.
+        assert_eq!(buffer.push(".").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn paste_buffer_finish_flushes_without_sentinel() {
+        let mut buffer = PasteBuffer::default();
+        buffer.push("line one");
+        buffer.push(". not the sentinel");
+        assert_eq!(buffer.finish(), "line one\n. not the sentinel");
+    }
+
+    #[test]
+    fn open_fence_keeps_input_incomplete() {
+        assert!(!has_open_fence("hello"));
+        assert!(has_open_fence("look at this:\n```rust\nfn x() {}"));
+        assert!(!has_open_fence("```rust\nfn x() {}\n```"));
+        // A shorter or different marker does not close the block.
+        assert!(has_open_fence("````\n```\n"));
+        assert!(has_open_fence("~~~\n```"));
+        assert!(!has_open_fence("~~~\n```\n~~~"));
+    }
+
+    #[test]
+    fn repl_reports_markdown_support() {
+        let caps = ReplChannel::new().format_capabilities();
+        assert!(caps.supports_markdown);
+        assert!(caps.supports_code_blocks);
+        assert!(caps.supports_tables);
+        assert_eq!(caps.max_message_length, None);
+    }
+
+    #[test]
+    fn stream_renderer_leaves_fenced_code_uninterpreted() {
+        let mut renderer = StreamRenderer::new(80);
+        let mut out = String::new();
+        // Chunk boundaries fall mid-fence and mid-line.
+        for chunk in [
+            "Intro\n\n``",
+            "`rust\nlet x = **y**;\n# not a head",
+            "er\n```\n",
+        ] {
+            out.push_str(&renderer.push(chunk));
+        }
+        out.push_str(&renderer.finish());
+
+        assert!(out.contains("Intro"));
+        assert!(out.contains("  \x1b[32mlet x = **y**;\x1b[0m\n"));
+        assert!(out.contains("  \x1b[32m# not a header\x1b[0m\n"));
+        assert!(!out.contains("```"));
+    }
+
+    #[test]
+    fn stream_renderer_holds_prose_until_block_ends() {
+        let mut renderer = StreamRenderer::new(80);
+        assert_eq!(renderer.push("Some **bold"), "");
+        assert_eq!(renderer.push("** text\n"), "");
+        let out = renderer.push("\n");
+        assert!(out.contains("bold"));
+        assert!(!out.contains("**"));
+
+        // Whatever is left is flushed at the end, even without a newline.
+        assert_eq!(renderer.push("tail"), "");
+        assert!(renderer.finish().contains("tail"));
+    }
+
+    #[test]
+    fn fence_markers() {
+        assert_eq!(fence_marker("```rust"), Some("```"));
+        assert_eq!(fence_marker("  ~~~~"), Some("~~~~"));
+        assert_eq!(fence_marker("``inline``"), None);
+        assert_eq!(fence_marker("text ```"), None);
+    }
+}
@@ -0,0 +1,296 @@
+//! Splitting long responses for length-limited channels.
+//!
+//! Telegram, Discord and friends reject (or silently truncate) messages over
+//! a fixed length. [`split_message`] cuts a response into parts that fit,
+//! preferring paragraph breaks, then line breaks, then spaces, and keeping
+//! fenced code blocks whole whenever they fit in one part. A code block that
+//! is itself too long is closed at the end of one part and reopened (with the
+//! same language tag) at the start of the next, so every part renders on its
+//! own.
+
+/// Markdown code fence marker.
+const FENCE: &str = "```";
+
+/// Characters added to close a code block cut between two parts (`\n` + fence).
+const FENCE_CLOSE_LEN: usize = 4;
+
+/// Characters reserved for the `(i/n)` header added by [`number_parts`].
+pub const NUMBERING_RESERVE: usize = "(99/99)\n".len();
+
+/// Split `content` into parts of at most `max_len` characters.
+///
+/// With `prefer_boundaries`, each cut is made at the best boundary in the
+/// part: after a paragraph or a complete code block, then at a line end
+/// outside a code block, then at a line end inside one, then after a space.
+/// Without it, parts are cut at exactly `max_len` characters.
+///
+/// Unless a code block has to be cut, concatenating the parts gives back
+/// `content` exactly. A `max_len` of zero disables splitting.
+pub fn split_message(content: &str, max_len: usize, prefer_boundaries: bool) -> Vec<String> {
+    if max_len == 0 || content.chars().count() <= max_len {
+        return vec![content.to_string()];
+    }
+
+    let mut parts = Vec::new();
+    let mut rest = content;
+    // Opening fence line of a code block carried over from the previous part.
+    let mut reopen: Option<String> = None;
+
+    while !rest.is_empty() {
+        let prefix = reopen
+            .take()
+            .map(|opener| format!("{}\n", opener))
+            .unwrap_or_default();
+        let start_fence = prefix.strip_suffix('\n');
+        let budget = if prefix.is_empty() {
+            max_len
+        } else {
+            max_len
+                .saturating_sub(prefix.chars().count())
+                .max(FENCE_CLOSE_LEN + 1)
+        };
+
+        if rest.chars().count() <= budget {
+            parts.push(format!("{}{}", prefix, rest));
+            break;
+        }
+
+        let mut cut = find_cut(rest, budget, prefer_boundaries, start_fence);
+        let mut open = open_fence_after(&rest[..cut], start_fence);
+        if open.is_some() {
+            // Leave room to close the block at the end of this part.
+            cut = find_cut(
+                rest,
+                budget.saturating_sub(FENCE_CLOSE_LEN).max(1),
+                prefer_boundaries,
+                start_fence,
+            );
+            open = open_fence_after(&rest[..cut], start_fence);
+        }
+
+        let (head, tail) = rest.split_at(cut);
+        let mut part = format!("{}{}", prefix, head);
+        if let Some(opener) = open {
+            if !part.ends_with('\n') {
+                part.push('\n');
+            }
+            part.push_str(FENCE);
+            reopen = Some(opener);
+        }
+        parts.push(part);
+        rest = tail;
+    }
+
+    parts
+}
+
+/// Prefix each part with an `(i/n)` header on its own line. A single part is
+/// returned unchanged.
+///
+/// Split with `max_len - NUMBERING_RESERVE` to leave room for the header.
+pub fn number_parts(parts: Vec<String>) -> Vec<String> {
+    let total = parts.len();
+    if total < 2 {
+        return parts;
+    }
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part)| format!("({}/{})\n{}", i + 1, total, part))
+        .collect()
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with(FENCE)
+}
+
+/// The opening fence line of the code block still open at the end of `text`,
+/// given the block (if any) open at its start.
+fn open_fence_after(text: &str, start: Option<&str>) -> Option<String> {
+    let mut open = start.map(str::to_string);
+    for line in text.split_inclusive('\n') {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if is_fence(line) {
+            open = match open {
+                Some(_) => None,
+                None => Some(line.trim_start().to_string()),
+            };
+        }
+    }
+    open
+}
+
+/// Byte offset at which to end the next part: at most `max_chars` characters
+/// into `text`, and always past at least one character.
+fn find_cut(text: &str, max_chars: usize, prefer_boundaries: bool, start: Option<&str>) -> usize {
+    let hard_end = text
+        .char_indices()
+        .nth(max_chars)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    if !prefer_boundaries {
+        return hard_end;
+    }
+
+    // Best cut per priority: 3 = paragraph end or after a code block,
+    // 2 = line end, 1 = line end inside a code block, 0 = after a space.
+    let mut best: [Option<usize>; 4] = [None; 4];
+    let mut in_fence = start.is_some();
+    let mut offset = 0;
+    for line in text[..hard_end].split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        if !in_fence {
+            for (i, c) in line.char_indices() {
+                if c == ' ' && line_start + i + 1 < offset {
+                    best[0] = Some(line_start + i + 1);
+                }
+            }
+        }
+        if !line.ends_with('\n') {
+            break;
+        }
+
+        let fence = is_fence(line);
+        let priority = match (in_fence, fence) {
+            // Closing fence: the block ends here.
+            (true, true) => 3,
+            (true, false) => 1,
+            (false, _) if line.trim().is_empty() => 3,
+            (false, _) => 2,
+        };
+        if fence {
+            in_fence = !in_fence;
+        }
+        // Cutting right after an opening fence would strand it.
+        if !(fence && in_fence) {
+            best[priority] = Some(offset);
+        }
+    }
+
+    best.iter()
+        .rev()
+        .flatten()
+        .copied()
+        .find(|&cut| cut > 0)
+        .unwrap_or(hard_end)
+        .max(first_char_len(text))
+}
+
+fn first_char_len(text: &str) -> usize {
+    text.chars().next().map_or(0, char::len_utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_fits(parts: &[String], max_len: usize) {
+        for part in parts {
+            assert!(
+                part.chars().count() <= max_len,
+                "part of {} chars exceeds {}: {:?}",
+                part.chars().count(),
+                max_len,
+                part
+            );
+        }
+    }
+
+    #[test]
+    fn short_message_is_a_single_part() {
+        assert_eq!(split_message("hello", 10, true), vec!["hello"]);
+        assert_eq!(split_message("hello", 0, true), vec!["hello"]);
+    }
+
+    #[test]
+    fn splits_at_paragraph_boundaries_and_reassembles() {
+        let paragraphs = [
+            "First paragraph with a few words.",
+            "Second paragraph, also short.",
+            "Third one closes it out.",
+        ];
+        let content = paragraphs.join("\n\n");
+        let parts = split_message(&content, 70, true);
+
+        assert_eq!(
+            parts,
+            vec![
+                "First paragraph with a few words.\n\nSecond paragraph, also short.\n\n",
+                "Third one closes it out.",
+            ]
+        );
+        assert_fits(&parts, 70);
+        assert_eq!(parts.concat(), content);
+    }
+
+    #[test]
+    fn falls_back_to_lines_then_words() {
+        let content = "one two three four five six seven eight nine ten";
+        let parts = split_message(content, 20, true);
+        assert_eq!(
+            parts,
+            vec!["one two three four ", "five six seven ", "eight nine ten"]
+        );
+        assert_eq!(parts.concat(), content);
+
+        let content = "line one\nline two\nline three";
+        let parts = split_message(content, 18, true);
+        assert_eq!(parts, vec!["line one\nline two\n", "line three"]);
+    }
+
+    #[test]
+    fn never_splits_inside_a_code_block_that_fits() {
+        let content = "Here is the fix:\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\nThat should do it.";
+        for max_len in [50, 60, 80] {
+            let parts = split_message(content, max_len, true);
+            assert_fits(&parts, max_len);
+            assert_eq!(parts.concat(), content, "max_len {}", max_len);
+            for part in &parts {
+                let fences = part.lines().filter(|l| is_fence(l)).count();
+                assert_eq!(fences % 2, 0, "unbalanced fence in {:?}", part);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_code_block_is_closed_and_reopened() {
+        let body: String = (0..20).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        let content = format!("```rust\n{}```", body);
+        let parts = split_message(&content, 80, true);
+
+        assert!(parts.len() > 1);
+        assert_fits(&parts, 80);
+        for part in &parts {
+            assert!(part.starts_with("```rust\n"), "{:?}", part);
+            assert!(part.ends_with("```"), "{:?}", part);
+        }
+        // Stripping the added fences gives back the original code.
+        let code: String = parts
+            .iter()
+            .map(|p| {
+                p.trim_start_matches("```rust\n")
+                    .trim_end_matches("```")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(code, body);
+    }
+
+    #[test]
+    fn hard_split_without_boundaries_respects_char_boundaries() {
+        let parts = split_message("ééééé", 3, false);
+        assert_eq!(parts, vec!["ééé", "éé"]);
+
+        let parts = split_message("one two three", 5, false);
+        assert_eq!(parts, vec!["one t", "wo th", "ree"]);
+    }
+
+    #[test]
+    fn numbering_prefixes_each_part() {
+        let parts = number_parts(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(parts, vec!["(1/2)\na", "(2/2)\nb"]);
+        assert_eq!(number_parts(vec!["only".to_string()]), vec!["only"]);
+    }
+}
@@ -0,0 +1,259 @@
+//! Per-user throttle for proactive broadcasts.
+//!
+//! Bursts of notifications (a batch of job completions, a flapping routine)
+//! would otherwise reach the user as a burst of messages. For each
+//! `(channel, user)` pair the throttle lets the first message through, holds
+//! anything that arrives within the configured window, and releases the held
+//! messages as one delivery when the window closes: either merged into a
+//! digest or reduced to the most recent message.
+//!
+//! The throttle only makes decisions; `ChannelManager` does the sending and
+//! schedules the flush.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::channels::OutgoingResponse;
+use crate::config::BroadcastThrottleConfig;
+
+/// Channel key that applies to every channel without its own entry.
+const ANY_CHANNEL: &str = "*";
+
+/// What to do with an offered broadcast.
+#[derive(Debug)]
+pub enum ThrottleDecision {
+    /// Deliver this response now.
+    Send(OutgoingResponse),
+    /// Held as the first message of a new batch; the caller must call
+    /// [`BroadcastThrottle::flush`] at the given instant.
+    Schedule(Instant),
+    /// Held in a batch whose flush is already scheduled.
+    Held,
+}
+
+#[derive(Default)]
+struct Slot {
+    last_sent: Option<Instant>,
+    pending: Vec<OutgoingResponse>,
+}
+
+/// Throttle state shared by all channels of a `ChannelManager`.
+#[derive(Default)]
+pub struct BroadcastThrottle {
+    configs: HashMap<String, BroadcastThrottleConfig>,
+    slots: Mutex<HashMap<(String, String), Slot>>,
+}
+
+impl BroadcastThrottle {
+    pub fn new(configs: HashMap<String, BroadcastThrottleConfig>) -> Self {
+        Self {
+            configs,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn config_for(&self, channel: &str) -> Option<&BroadcastThrottleConfig> {
+        self.configs
+            .get(channel)
+            .or_else(|| self.configs.get(ANY_CHANNEL))
+            .filter(|c| !c.window.is_zero())
+    }
+
+    /// Decide whether `response` goes out now or waits for the window to close.
+    pub fn offer(
+        &self,
+        channel: &str,
+        user_id: &str,
+        response: OutgoingResponse,
+        now: Instant,
+    ) -> ThrottleDecision {
+        let Some(config) = self.config_for(channel) else {
+            return ThrottleDecision::Send(response);
+        };
+
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = slots
+            .entry((channel.to_string(), user_id.to_string()))
+            .or_default();
+
+        if !slot.pending.is_empty() {
+            slot.pending.push(response);
+            return ThrottleDecision::Held;
+        }
+
+        match slot.last_sent {
+            Some(last) if now.duration_since(last) < config.window => {
+                slot.pending.push(response);
+                ThrottleDecision::Schedule(last + config.window)
+            }
+            _ => {
+                slot.last_sent = Some(now);
+                ThrottleDecision::Send(response)
+            }
+        }
+    }
+
+    /// Release the messages held for `(channel, user_id)` as one response.
+    ///
+    /// Returns `None` if nothing is waiting. The released message starts a
+    /// new window, so anything arriving right after it is held again.
+    pub fn flush(&self, channel: &str, user_id: &str, now: Instant) -> Option<OutgoingResponse> {
+        let digest = self.config_for(channel).is_some_and(|c| c.digest);
+
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = slots.get_mut(&(channel.to_string(), user_id.to_string()))?;
+        let pending = std::mem::take(&mut slot.pending);
+        if pending.is_empty() {
+            return None;
+        }
+        slot.last_sent = Some(now);
+        Some(coalesce(pending, digest))
+    }
+}
+
+/// Merge held messages into one. Routing (thread, metadata) follows the most
+/// recent message.
+pub(super) fn coalesce(mut pending: Vec<OutgoingResponse>, digest: bool) -> OutgoingResponse {
+    let count = pending.len();
+    let Some(mut latest) = pending.pop() else {
+        return OutgoingResponse::text(String::new());
+    };
+    if count == 1 {
+        return latest;
+    }
+
+    latest.content = if digest {
+        let mut parts: Vec<String> = pending.into_iter().map(|r| r.content).collect();
+        parts.push(latest.content);
+        format!("{} notifications:\n\n{}", count, parts.join("\n\n"))
+    } else {
+        format!(
+            "{}\n\n({} earlier notification(s) suppressed)",
+            latest.content,
+            count - 1
+        )
+    };
+    latest
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn throttle(channel: &str, window_secs: u64, digest: bool) -> BroadcastThrottle {
+        BroadcastThrottle::new(HashMap::from([(
+            channel.to_string(),
+            BroadcastThrottleConfig {
+                window: Duration::from_secs(window_secs),
+                digest,
+            },
+        )]))
+    }
+
+    fn content(decision: ThrottleDecision) -> Option<String> {
+        match decision {
+            ThrottleDecision::Send(r) => Some(r.content),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn rapid_broadcasts_are_coalesced_into_a_digest() {
+        let throttle = throttle("telegram", 60, true);
+        let t0 = Instant::now();
+        let msg = OutgoingResponse::text;
+
+        assert_eq!(
+            content(throttle.offer("telegram", "alice", msg("job 1 done"), t0)).as_deref(),
+            Some("job 1 done")
+        );
+        let flush_at = match throttle.offer(
+            "telegram",
+            "alice",
+            msg("job 2 done"),
+            t0 + Duration::from_secs(1),
+        ) {
+            ThrottleDecision::Schedule(at) => at,
+            other => panic!("expected Schedule, got {other:?}"),
+        };
+        assert_eq!(flush_at, t0 + Duration::from_secs(60));
+        assert!(matches!(
+            throttle.offer(
+                "telegram",
+                "alice",
+                msg("job 3 done"),
+                t0 + Duration::from_secs(2)
+            ),
+            ThrottleDecision::Held
+        ));
+
+        // Three broadcasts become two deliveries.
+        let digest = throttle.flush("telegram", "alice", flush_at).unwrap();
+        assert_eq!(
+            digest.content,
+            "2 notifications:\n\njob 2 done\n\njob 3 done"
+        );
+        assert!(throttle.flush("telegram", "alice", flush_at).is_none());
+    }
+
+    #[test]
+    fn without_digest_only_the_latest_is_delivered() {
+        let throttle = throttle("telegram", 60, false);
+        let t0 = Instant::now();
+        for (i, text) in ["a", "b", "c"].into_iter().enumerate() {
+            throttle.offer(
+                "telegram",
+                "alice",
+                OutgoingResponse::text(text),
+                t0 + Duration::from_secs(i as u64),
+            );
+        }
+        let flushed = throttle
+            .flush("telegram", "alice", t0 + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            flushed.content,
+            "c\n\n(1 earlier notification(s) suppressed)"
+        );
+    }
+
+    #[test]
+    fn spaced_out_broadcasts_pass_through() {
+        let throttle = throttle("telegram", 60, true);
+        let t0 = Instant::now();
+        for i in 0..3u64 {
+            let at = t0 + Duration::from_secs(i * 61);
+            assert!(
+                content(throttle.offer("telegram", "alice", OutgoingResponse::text("hi"), at))
+                    .is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn users_and_unconfigured_channels_are_independent() {
+        let throttle = throttle("telegram", 60, true);
+        let t0 = Instant::now();
+        let msg = || OutgoingResponse::text("hi");
+
+        assert!(content(throttle.offer("telegram", "alice", msg(), t0)).is_some());
+        assert!(content(throttle.offer("telegram", "bob", msg(), t0)).is_some());
+        // No throttle configured for this channel.
+        for _ in 0..3 {
+            assert!(content(throttle.offer("slack", "alice", msg(), t0)).is_some());
+        }
+    }
+
+    #[test]
+    fn wildcard_applies_to_every_channel() {
+        let throttle = throttle("*", 60, true);
+        let t0 = Instant::now();
+        let msg = || OutgoingResponse::text("hi");
+
+        assert!(content(throttle.offer("signal", "alice", msg(), t0)).is_some());
+        assert!(content(throttle.offer("signal", "alice", msg(), t0)).is_none());
+    }
+}
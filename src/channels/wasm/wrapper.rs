@@ -48,7 +48,9 @@ use crate::channels::wasm::host::{
 use crate::channels::wasm::router::RegisteredEndpoint;
 use crate::channels::wasm::runtime::{PreparedChannelModule, WasmChannelRuntime};
 use crate::channels::wasm::schema::ChannelConfig;
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+use crate::channels::{
+    Channel, FormatCapabilities, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate,
+};
 use crate::error::ChannelError;
 use crate::pairing::PairingStore;
 use crate::safety::LeakDetector;
@@ -2006,6 +2008,10 @@ impl Channel for WasmChannel {
         self.handle_status_update(status, metadata).await
     }
 
+    fn format_capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities::for_platform(&self.name).unwrap_or_default()
+    }
+
     async fn health_check(&self) -> Result<(), ChannelError> {
         // Check if we have an active message sender
         if self.message_tx.read().await.is_some() {
@@ -2109,6 +2115,10 @@ impl Channel for SharedWasmChannel {
         self.inner.send_status(status, metadata).await
     }
 
+    fn format_capabilities(&self) -> FormatCapabilities {
+        self.inner.format_capabilities()
+    }
+
     async fn health_check(&self) -> Result<(), ChannelError> {
         self.inner.health_check().await
     }
@@ -2271,6 +2281,11 @@ fn status_to_wit(status: &StatusUpdate, metadata: &serde_json::Value) -> wit_cha
             },
             metadata_json,
         },
+        StatusUpdate::ReasoningTrace(trace) => wit_channel::StatusUpdate {
+            status: wit_channel::StatusType::Status,
+            message: truncate_status_text(&trace.summary(), 280),
+            metadata_json,
+        },
         StatusUpdate::AuthCompleted {
             extension_name,
             success,
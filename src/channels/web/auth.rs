@@ -1,5 +1,7 @@
 //! Bearer token authentication middleware for the web gateway.
 
+use std::sync::Arc;
+
 use axum::{
     extract::{Request, State},
     http::{HeaderMap, StatusCode},
@@ -8,55 +10,175 @@ use axum::{
 };
 use subtle::ConstantTimeEq;
 
+use crate::config::GatewayApiKey;
+
 /// Shared auth state injected via axum middleware state.
 #[derive(Clone)]
 pub struct AuthState {
     pub token: String,
+    /// Extra per-user API keys accepted alongside `token`.
+    pub api_keys: Arc<Vec<GatewayApiKey>>,
+}
+
+impl AuthState {
+    pub fn new(token: String) -> Self {
+        Self {
+            token,
+            api_keys: Arc::new(Vec::new()),
+        }
+    }
+
+    pub fn with_api_keys(mut self, api_keys: Vec<GatewayApiKey>) -> Self {
+        self.api_keys = Arc::new(api_keys);
+        self
+    }
+
+    /// Match a presented credential. Every key is compared in constant time
+    /// and all of them are checked, so timing doesn't reveal which matched.
+    fn authenticate(&self, presented: &str) -> Option<Authenticated> {
+        let presented = presented.as_bytes();
+        let mut result = None;
+        if bool::from(presented.ct_eq(self.token.as_bytes())) {
+            result = Some(Authenticated::GatewayToken);
+        }
+        for api_key in self.api_keys.iter() {
+            if bool::from(presented.ct_eq(api_key.key.as_bytes())) && result.is_none() {
+                result = Some(Authenticated::ApiKey(AuthenticatedUser {
+                    user_id: api_key.user_id.clone(),
+                }));
+            }
+        }
+        result
+    }
+}
+
+enum Authenticated {
+    GatewayToken,
+    ApiKey(AuthenticatedUser),
+}
+
+/// Request extension set when a request authenticated with a per-user API key.
+///
+/// Handlers that attribute work to a user should prefer this over the
+/// gateway-wide `user_id`. Absent when the main gateway token was used.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub user_id: String,
+}
+
+fn presented_token<'a>(headers: &'a HeaderMap, query: Option<&'a str>) -> Option<&'a str> {
+    if let Some(auth_header) = headers.get("authorization")
+        && let Ok(value) = auth_header.to_str()
+        && let Some(token) = value.strip_prefix("Bearer ")
+    {
+        return Some(token);
+    }
+    query?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
 }
 
 /// Auth middleware that validates bearer token from header or query param.
 ///
 /// SSE connections can't set headers from `EventSource`, so we also accept
-/// `?token=xxx` as a query parameter.
+/// `?token=xxx` as a query parameter. Per-user API keys are accepted the same
+/// way and attach an [`AuthenticatedUser`] extension to the request.
 pub async fn auth_middleware(
     State(auth): State<AuthState>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Response {
-    // Try Authorization header first (constant-time comparison)
-    if let Some(auth_header) = headers.get("authorization")
-        && let Ok(value) = auth_header.to_str()
-        && let Some(token) = value.strip_prefix("Bearer ")
-        && bool::from(token.as_bytes().ct_eq(auth.token.as_bytes()))
-    {
-        return next.run(request).await;
-    }
+    let authenticated =
+        presented_token(&headers, request.uri().query()).and_then(|t| auth.authenticate(t));
 
-    // Fall back to query parameter for SSE EventSource (constant-time comparison)
-    if let Some(query) = request.uri().query() {
-        for pair in query.split('&') {
-            if let Some(token) = pair.strip_prefix("token=")
-                && bool::from(token.as_bytes().ct_eq(auth.token.as_bytes()))
-            {
-                return next.run(request).await;
-            }
+    match authenticated {
+        Some(Authenticated::GatewayToken) => next.run(request).await,
+        Some(Authenticated::ApiKey(user)) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
         }
+        None => (StatusCode::UNAUTHORIZED, "Invalid or missing auth token").into_response(),
     }
-
-    (StatusCode::UNAUTHORIZED, "Invalid or missing auth token").into_response()
 }
 
 #[cfg(test)]
 mod tests {
+    use axum::{Extension, Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
     use super::*;
 
+    fn test_router() -> Router {
+        let auth = AuthState::new("gateway-token".to_string()).with_api_keys(vec![GatewayApiKey {
+            key: "alice-key".to_string(),
+            user_id: "alice".to_string(),
+        }]);
+        let protected = Router::new()
+            .route(
+                "/api/whoami",
+                get(|user: Option<Extension<AuthenticatedUser>>| async move {
+                    user.map(|Extension(u)| u.user_id)
+                        .unwrap_or_else(|| "gateway".to_string())
+                }),
+            )
+            .route_layer(middleware::from_fn_with_state(auth, auth_middleware));
+        Router::new()
+            .route("/livez", get(|| async { "ok" }))
+            .merge(protected)
+    }
+
+    async fn send(router: Router, uri: &str, bearer: Option<&str>) -> (StatusCode, String) {
+        let mut req = axum::http::Request::builder().uri(uri);
+        if let Some(token) = bearer {
+            req = req.header("authorization", format!("Bearer {token}"));
+        }
+        let resp = router
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), 1024).await.unwrap();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
     #[test]
     fn test_auth_state_clone() {
-        let state = AuthState {
-            token: "test-token".to_string(),
-        };
+        let state = AuthState::new("test-token".to_string());
         let cloned = state.clone();
         assert_eq!(cloned.token, "test-token");
     }
+
+    #[tokio::test]
+    async fn valid_api_key_passes_with_mapped_user() {
+        let (status, body) = send(test_router(), "/api/whoami", Some("alice-key")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "alice");
+
+        let (status, body) = send(test_router(), "/api/whoami?token=alice-key", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "alice");
+    }
+
+    #[tokio::test]
+    async fn gateway_token_passes_without_user_extension() {
+        let (status, body) = send(test_router(), "/api/whoami", Some("gateway-token")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "gateway");
+    }
+
+    #[tokio::test]
+    async fn invalid_or_missing_key_is_unauthorized() {
+        let (status, _) = send(test_router(), "/api/whoami", Some("bogus")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(test_router(), "/api/whoami", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn livez_is_exempt_from_auth() {
+        let (status, body) = send(test_router(), "/livez", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ok");
+    }
 }
@@ -0,0 +1,314 @@
+//! Cross-origin request policy for the web gateway.
+//!
+//! Unlike `tower_http::cors::CorsLayer`, which only omits response headers for
+//! unknown origins, this middleware rejects them outright with 403 so a
+//! disallowed page can't trigger side effects through "simple" requests that
+//! skip preflight.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+const ALLOWED_METHODS: &str = "GET, POST, PUT, DELETE";
+const ALLOWED_HEADERS: &str = "Content-Type, Authorization";
+const PREFLIGHT_MAX_AGE_SECS: &str = "600";
+
+/// A single entry in the allowed-origins list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OriginPattern {
+    /// `*` — any origin.
+    Any,
+    /// Exact origin, e.g. `https://app.example.com`.
+    Exact(String),
+    /// Any subdomain, e.g. `https://*.example.com`. Stored as the scheme
+    /// prefix (`https://`) and the required suffix (`.example.com`).
+    Subdomain { scheme: String, suffix: String },
+}
+
+impl OriginPattern {
+    fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim().trim_end_matches('/').to_ascii_lowercase();
+        if raw.is_empty() {
+            return None;
+        }
+        if raw == "*" {
+            return Some(Self::Any);
+        }
+        if let Some((scheme, rest)) = raw.split_once("://")
+            && let Some(domain) = rest.strip_prefix("*.")
+        {
+            if domain.is_empty() {
+                return None;
+            }
+            return Some(Self::Subdomain {
+                scheme: format!("{scheme}://"),
+                suffix: format!(".{domain}"),
+            });
+        }
+        Some(Self::Exact(raw))
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(exact) => exact == origin,
+            Self::Subdomain { scheme, suffix } => origin
+                .strip_prefix(scheme.as_str())
+                .and_then(|host| host.strip_suffix(suffix.as_str()))
+                .is_some_and(|label| !label.is_empty() && !label.contains('/')),
+        }
+    }
+}
+
+/// Allowed-origins policy applied by [`cors_middleware`].
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    patterns: Vec<OriginPattern>,
+}
+
+impl CorsPolicy {
+    /// Build a policy from configured origins plus the gateway's own origins
+    /// (`http://<bind_ip>:<port>` and `http://localhost:<port>`).
+    ///
+    /// Invalid or empty entries are skipped with a warning.
+    pub fn new(bound_addr: SocketAddr, allowed_origins: &[String]) -> Self {
+        let mut patterns = vec![
+            OriginPattern::Exact(format!("http://{}", bound_addr)),
+            OriginPattern::Exact(format!("http://localhost:{}", bound_addr.port())),
+        ];
+        for raw in allowed_origins {
+            match OriginPattern::parse(raw) {
+                Some(pattern) => patterns.push(pattern),
+                None => tracing::warn!(origin = %raw, "Ignoring invalid CORS origin"),
+            }
+        }
+        Self { patterns }
+    }
+
+    /// Whether a cross-origin request from `origin` may proceed.
+    pub fn allows(&self, origin: &str) -> bool {
+        let origin = origin.to_ascii_lowercase();
+        self.patterns.iter().any(|p| p.matches(&origin))
+    }
+}
+
+/// Whether `origin` refers to the host the request was sent to, which browsers
+/// still label with an `Origin` header on same-origin POSTs.
+fn is_same_origin(origin: &str, headers: &HeaderMap) -> bool {
+    let Some(host) = headers.get(header::HOST).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let origin = origin.to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .is_some_and(|h| h == host)
+}
+
+fn apply_cors_headers(headers: &mut HeaderMap, origin: HeaderValue) {
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+        HeaderValue::from_static("true"),
+    );
+    headers.append(header::VARY, HeaderValue::from_static("origin"));
+}
+
+/// Reject requests from disallowed origins and answer CORS preflights.
+///
+/// Requests without an `Origin` header (curl, SDKs, same-origin GETs) pass
+/// through untouched.
+pub async fn cors_middleware(
+    State(policy): State<Arc<CorsPolicy>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(origin) = request.headers().get(header::ORIGIN).cloned() else {
+        return next.run(request).await;
+    };
+    let origin_str = origin.to_str().unwrap_or_default();
+
+    if !policy.allows(origin_str) && !is_same_origin(origin_str, request.headers()) {
+        tracing::debug!(origin = %origin_str, "Rejected cross-origin request");
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    if request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        apply_cors_headers(headers, origin);
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static(ALLOWED_METHODS),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static(ALLOWED_HEADERS),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from_static(PREFLIGHT_MAX_AGE_SECS),
+        );
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    apply_cors_headers(response.headers_mut(), origin);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, middleware, routing::post};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn test_policy(origins: &[&str]) -> CorsPolicy {
+        let addr: SocketAddr = "127.0.0.1:3000".parse().unwrap();
+        let origins: Vec<String> = origins.iter().map(|s| s.to_string()).collect();
+        CorsPolicy::new(addr, &origins)
+    }
+
+    fn test_router(policy: CorsPolicy) -> Router {
+        Router::new()
+            .route("/api/chat/send", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                Arc::new(policy),
+                cors_middleware,
+            ))
+    }
+
+    #[test]
+    fn default_policy_is_same_origin_only() {
+        let policy = test_policy(&[]);
+        assert!(policy.allows("http://127.0.0.1:3000"));
+        assert!(policy.allows("http://localhost:3000"));
+        assert!(!policy.allows("http://localhost:4000"));
+        assert!(!policy.allows("https://evil.example.com"));
+        assert!(!policy.allows("null"));
+    }
+
+    #[test]
+    fn exact_origins_match_case_insensitively() {
+        let policy = test_policy(&["https://App.Example.com/"]);
+        assert!(policy.allows("https://app.example.com"));
+        assert!(!policy.allows("http://app.example.com"));
+        assert!(!policy.allows("https://app.example.com:8443"));
+    }
+
+    #[test]
+    fn wildcard_matches_subdomains_only() {
+        let policy = test_policy(&["https://*.example.com"]);
+        assert!(policy.allows("https://app.example.com"));
+        assert!(policy.allows("https://a.b.example.com"));
+        assert!(!policy.allows("https://example.com"));
+        assert!(!policy.allows("https://evilexample.com"));
+        assert!(!policy.allows("http://app.example.com"));
+    }
+
+    #[test]
+    fn star_allows_everything_and_blanks_are_skipped() {
+        assert!(test_policy(&["*"]).allows("https://anything.test"));
+        let policy = test_policy(&["", "  ", "https://*."]);
+        assert_eq!(policy.patterns.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn allowed_origin_gets_cors_headers() {
+        let router = test_router(test_policy(&["https://app.example.com"]));
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/api/chat/send")
+            .header(header::ORIGIN, "https://app.example.com")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+        assert_eq!(
+            resp.headers()[header::ACCESS_CONTROL_ALLOW_CREDENTIALS],
+            "true"
+        );
+    }
+
+    #[tokio::test]
+    async fn disallowed_origin_is_rejected() {
+        let router = test_router(test_policy(&["https://app.example.com"]));
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/api/chat/send")
+            .header(header::ORIGIN, "https://evil.example.com")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert!(
+            !resp
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+    }
+
+    #[tokio::test]
+    async fn preflight_is_answered_without_hitting_handler() {
+        let router = test_router(test_policy(&["https://*.example.com"]));
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api/chat/send")
+            .header(header::ORIGIN, "https://app.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            resp.headers()[header::ACCESS_CONTROL_ALLOW_METHODS],
+            ALLOWED_METHODS
+        );
+        assert_eq!(
+            resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn same_host_origin_and_missing_origin_pass() {
+        let router = test_router(test_policy(&[]));
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/api/chat/send")
+            .header(header::HOST, "192.168.1.5:3000")
+            .header(header::ORIGIN, "http://192.168.1.5:3000")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            router.clone().oneshot(req).await.unwrap().status(),
+            StatusCode::OK
+        );
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/api/chat/send")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(router.oneshot(req).await.unwrap().status(), StatusCode::OK);
+    }
+}
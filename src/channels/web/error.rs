@@ -0,0 +1,306 @@
+//! Structured JSON error responses for the web gateway.
+//!
+//! Every 4xx/5xx response leaves the gateway as
+//!
+//! ```json
+//! { "error": { "code": "rate_limited", "message": "...", "request_id": "..." } }
+//! ```
+//!
+//! Handlers can return [`ApiError`] to pick a specific `code`. Legacy
+//! handlers that still return `(StatusCode, String)` are rewritten by
+//! [`error_envelope_middleware`], which derives the code from the status.
+//! Responses that are already JSON (e.g. the OpenAI-compatible API's own
+//! error shape) are passed through unchanged.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+/// Header carrying the correlation id, echoed on every response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Largest client-supplied request id that is reused rather than replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Upper bound on plain-text error bodies read back for re-wrapping.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// A handler error with a stable machine-readable `code`.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    /// Extra machine-readable context, emitted as `error.details`.
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorEnvelope<'a> {
+    error: ErrorBody<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody<'a> {
+    code: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<&'a serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Build an error whose code is derived from `status`.
+    pub fn from_status(status: StatusCode, message: impl Into<String>) -> Self {
+        Self::new(status, code_for_status(status), message)
+    }
+
+    fn envelope_json(&self, request_id: Option<&str>) -> String {
+        let envelope = ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code,
+                message: &self.message,
+                request_id,
+                details: self.details.as_ref(),
+            },
+        };
+        serde_json::to_string(&envelope).unwrap_or_else(|_| {
+            r#"{"error":{"code":"internal_error","message":"failed to encode error"}}"#.to_string()
+        })
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        // The middleware fills in the request id; carry the error along so it
+        // doesn't have to re-parse the body.
+        let body = self.envelope_json(None);
+        let mut response = (
+            self.status,
+            [(header::CONTENT_TYPE, "application/json")],
+            body,
+        )
+            .into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self::from_status(status, message)
+    }
+}
+
+/// Stable code for errors that only carry an HTTP status.
+pub fn code_for_status(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::REQUEST_TIMEOUT => "request_timeout",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::NOT_IMPLEMENTED => "not_implemented",
+        StatusCode::BAD_GATEWAY => "bad_gateway",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        StatusCode::GATEWAY_TIMEOUT => "gateway_timeout",
+        s if s.is_server_error() => "internal_error",
+        _ => "error",
+    }
+}
+
+/// Reuse the caller's `X-Request-Id` when it is short and printable,
+/// otherwise mint a fresh one.
+fn request_id_for(request: &Request) -> String {
+    request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+fn is_json(headers: &axum::http::HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"))
+}
+
+/// Tag every response with a request id and wrap error bodies in the JSON
+/// envelope.
+pub async fn error_envelope_middleware(request: Request, next: Next) -> Response {
+    let request_id = request_id_for(&request);
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let api_error = match parts.extensions.remove::<ApiError>() {
+        Some(err) => err,
+        None if is_json(&parts.headers) => return Response::from_parts(parts, body),
+        None => {
+            let message = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+                Ok(bytes) if !bytes.is_empty() => String::from_utf8_lossy(&bytes).into_owned(),
+                _ => status
+                    .canonical_reason()
+                    .unwrap_or("Request failed")
+                    .to_string(),
+            };
+            ApiError::from_status(status, message)
+        }
+    };
+
+    if status.is_server_error() {
+        tracing::warn!(
+            request_id = %request_id,
+            code = api_error.code,
+            status = status.as_u16(),
+            "Gateway request failed: {}",
+            api_error.message
+        );
+    }
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    Response::from_parts(
+        parts,
+        Body::from(api_error.envelope_json(Some(&request_id))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Json, Router, middleware, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn call(router: Router, uri: &str, request_id: Option<&str>) -> Response {
+        let mut req = axum::http::Request::builder().uri(uri);
+        if let Some(id) = request_id {
+            req = req.header(REQUEST_ID_HEADER, id);
+        }
+        router
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), 64 * 1024)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn test_router() -> Router {
+        Router::new()
+            .route(
+                "/llm",
+                get(|| async {
+                    Err::<(), _>(ApiError::new(
+                        StatusCode::TOO_MANY_REQUESTS,
+                        "rate_limited",
+                        "Too many requests for nearai",
+                    ))
+                }),
+            )
+            .route(
+                "/legacy",
+                get(|| async {
+                    Err::<(), _>((StatusCode::NOT_FOUND, "Job not found".to_string()))
+                }),
+            )
+            .route(
+                "/json",
+                get(|| async {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({"error": {"type": "openai"}})),
+                    )
+                }),
+            )
+            .route("/ok", get(|| async { "fine" }))
+            .layer(middleware::from_fn(error_envelope_middleware))
+    }
+
+    #[tokio::test]
+    async fn typed_error_produces_envelope_with_code() {
+        let resp = call(test_router(), "/llm", Some("req-123")).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers()[REQUEST_ID_HEADER], "req-123");
+
+        let body = body_json(resp).await;
+        assert_eq!(body["error"]["code"], "rate_limited");
+        assert_eq!(body["error"]["request_id"], "req-123");
+        assert!(
+            body["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("nearai")
+        );
+    }
+
+    #[tokio::test]
+    async fn plain_text_error_is_wrapped() {
+        let resp = call(test_router(), "/legacy", None).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let request_id = resp.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let body = body_json(resp).await;
+        assert_eq!(body["error"]["code"], "not_found");
+        assert_eq!(body["error"]["message"], "Job not found");
+        assert_eq!(body["error"]["request_id"], request_id.as_str());
+    }
+
+    #[tokio::test]
+    async fn json_errors_and_successes_pass_through() {
+        let resp = call(test_router(), "/json", None).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(resp).await["error"]["type"], "openai");
+
+        let resp = call(test_router(), "/ok", None).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key(REQUEST_ID_HEADER));
+    }
+}
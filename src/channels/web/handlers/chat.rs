@@ -158,21 +158,27 @@ pub async fn chat_auth_token_handler(
         // Clear auth mode on the active thread
         clear_auth_mode(&state).await;
 
-        state.sse.broadcast(SseEvent::AuthCompleted {
-            extension_name: req.extension_name,
-            success: true,
-            message: msg.clone(),
-        });
+        state.sse.broadcast(
+            &state.user_id,
+            SseEvent::AuthCompleted {
+                extension_name: req.extension_name,
+                success: true,
+                message: msg.clone(),
+            },
+        );
 
         Ok(Json(ActionResponse::ok(msg)))
     } else {
         // Re-emit auth_required for retry
-        state.sse.broadcast(SseEvent::AuthRequired {
-            extension_name: req.extension_name.clone(),
-            instructions: result.instructions.clone(),
-            auth_url: result.auth_url.clone(),
-            setup_url: result.setup_url.clone(),
-        });
+        state.sse.broadcast(
+            &state.user_id,
+            SseEvent::AuthRequired {
+                extension_name: req.extension_name.clone(),
+                instructions: result.instructions.clone(),
+                auth_url: result.auth_url.clone(),
+                setup_url: result.setup_url.clone(),
+            },
+        );
         Ok(Json(ActionResponse::fail(
             result
                 .instructions
@@ -206,7 +212,7 @@ pub async fn clear_auth_mode(state: &GatewayState) {
 pub async fn chat_events_handler(
     State(state): State<Arc<GatewayState>>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    state.sse.subscribe().ok_or((
+    state.sse.subscribe(&state.user_id).ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Too many connections".to_string(),
     ))
@@ -241,7 +247,10 @@ pub async fn chat_ws_handler(
             "WebSocket origin not allowed".to_string(),
         ));
     }
-    Ok(ws.on_upgrade(move |socket| crate::channels::web::ws::handle_ws_connection(socket, state)))
+    let user_id = state.user_id.clone();
+    Ok(ws.on_upgrade(move |socket| {
+        crate::channels::web::ws::handle_ws_connection(socket, state, user_id)
+    }))
 }
 
 #[derive(Deserialize)]
@@ -341,6 +350,7 @@ pub async fn chat_history_handler(
                         name: tc.name.clone(),
                         has_result: tc.result.is_some(),
                         has_error: tc.error.is_some(),
+                        status: tc.status,
                     })
                     .collect(),
             })
@@ -77,8 +77,17 @@ pub async fn extensions_install_handler(
         _ => None,
     });
 
+    let options = crate::extensions::InstallOptions {
+        integrity: crate::extensions::ArtifactIntegrity::new(req.sha256, req.signature),
+        capabilities_integrity: crate::extensions::ArtifactIntegrity::new(
+            req.capabilities_sha256,
+            req.capabilities_signature,
+        ),
+        approve_capability_changes: req.approve_capability_changes,
+    };
+
     match ext_mgr
-        .install(&req.name, req.url.as_deref(), kind_hint)
+        .install(&req.name, req.url.as_deref(), kind_hint, &options)
         .await
     {
         Ok(result) => Ok(Json(ActionResponse::ok(result.message))),
@@ -34,6 +34,7 @@ pub async fn health_handler() -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "healthy",
         channel: "gateway",
+        llm_circuit: None,
     })
 }
 
@@ -0,0 +1,167 @@
+//! `Idempotency-Key` support for message submission.
+//!
+//! A client that retries `POST /api/chat/send` after a dropped response sends
+//! the same key again; within the TTL it gets the original `message_id` back
+//! and the message is not re-queued. Keys are scoped to the user and thread,
+//! so the same key on a different thread is a different submission.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+/// Header clients use to tag a submission.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// How long a key is remembered.
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(600);
+
+/// Longest key accepted; longer values are rejected by the handler.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ScopedKey {
+    user_id: String,
+    thread_id: Option<String>,
+    key: String,
+}
+
+struct Entry {
+    message_id: Uuid,
+    inserted_at: Instant,
+}
+
+/// Outcome of [`IdempotencyStore::reserve`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reservation {
+    /// First time this key was seen; the caller should process the message.
+    New(Uuid),
+    /// The key was already used; return this `message_id` without processing.
+    Duplicate(Uuid),
+}
+
+/// In-memory map of recent idempotency keys to the message they produced.
+pub struct IdempotencyStore {
+    entries: Mutex<HashMap<ScopedKey, Entry>>,
+    ttl: Duration,
+}
+
+impl IdempotencyStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Atomically claim `key` for `message_id`, or return the id it was
+    /// already claimed for. Expired keys are purged on each call.
+    pub fn reserve(
+        &self,
+        user_id: &str,
+        thread_id: Option<&str>,
+        key: &str,
+        message_id: Uuid,
+    ) -> Reservation {
+        let scoped = ScopedKey {
+            user_id: user_id.to_string(),
+            thread_id: thread_id.map(str::to_string),
+            key: key.to_string(),
+        };
+        let now = Instant::now();
+        let mut entries = match self.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        entries.retain(|_, e| now.duration_since(e.inserted_at) < self.ttl);
+
+        if let Some(existing) = entries.get(&scoped) {
+            return Reservation::Duplicate(existing.message_id);
+        }
+        entries.insert(
+            scoped,
+            Entry {
+                message_id,
+                inserted_at: now,
+            },
+        );
+        Reservation::New(message_id)
+    }
+
+    /// Forget a reservation, e.g. because the message could not be queued and
+    /// a retry should be processed normally.
+    pub fn release(&self, user_id: &str, thread_id: Option<&str>, key: &str) {
+        let scoped = ScopedKey {
+            user_id: user_id.to_string(),
+            thread_id: thread_id.map(str::to_string),
+            key: key.to_string(),
+        };
+        let mut entries = match self.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        entries.remove(&scoped);
+    }
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_key_returns_original_id() {
+        let store = IdempotencyStore::default();
+        let first = Uuid::new_v4();
+        assert_eq!(
+            store.reserve("u", Some("t"), "k", first),
+            Reservation::New(first)
+        );
+        assert_eq!(
+            store.reserve("u", Some("t"), "k", Uuid::new_v4()),
+            Reservation::Duplicate(first)
+        );
+    }
+
+    #[test]
+    fn keys_are_scoped_by_thread_and_user() {
+        let store = IdempotencyStore::default();
+        store.reserve("u", Some("t1"), "k", Uuid::new_v4());
+        assert!(matches!(
+            store.reserve("u", Some("t2"), "k", Uuid::new_v4()),
+            Reservation::New(_)
+        ));
+        assert!(matches!(
+            store.reserve("other", Some("t1"), "k", Uuid::new_v4()),
+            Reservation::New(_)
+        ));
+        assert!(matches!(
+            store.reserve("u", None, "k", Uuid::new_v4()),
+            Reservation::New(_)
+        ));
+    }
+
+    #[test]
+    fn expired_and_released_keys_can_be_reused() {
+        let store = IdempotencyStore::new(Duration::ZERO);
+        store.reserve("u", None, "k", Uuid::new_v4());
+        assert!(matches!(
+            store.reserve("u", None, "k", Uuid::new_v4()),
+            Reservation::New(_)
+        ));
+
+        let store = IdempotencyStore::default();
+        store.reserve("u", None, "k", Uuid::new_v4());
+        store.release("u", None, "k");
+        assert!(matches!(
+            store.reserve("u", None, "k", Uuid::new_v4()),
+            Reservation::New(_)
+        ));
+    }
+}
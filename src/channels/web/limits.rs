@@ -0,0 +1,146 @@
+//! Request-size and request-time limits for the web gateway.
+//!
+//! Body size is enforced by axum's `DefaultBodyLimit` (413 on overflow). The
+//! per-request timeout lives here so long-lived streaming endpoints can opt
+//! out: SSE and WebSocket handlers return quickly, but a proxy or client that
+//! treats them like ordinary requests must not be cut off mid-stream.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Default maximum request body size (1 MB).
+pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Default per-request timeout. Generous because `/v1/chat/completions`
+/// waits on the LLM.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Whether a path serves a long-lived stream (SSE or WebSocket) and so is
+/// exempt from the request timeout.
+pub fn is_streaming_path(path: &str) -> bool {
+    path == "/api/chat/ws" || (path.starts_with("/api/") && path.ends_with("/events"))
+}
+
+/// Fail requests that take longer than `timeout` with 408, including time
+/// spent receiving the body.
+pub async fn timeout_middleware(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_streaming_path(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let path = request.uri().path().to_string();
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(
+                path = %path,
+                timeout_secs = timeout.as_secs_f64(),
+                "Gateway request timed out"
+            );
+            (StatusCode::REQUEST_TIMEOUT, "Request timed out").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Json, Router,
+        body::Body,
+        extract::DefaultBodyLimit,
+        middleware,
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn test_router(timeout: Duration, max_body: usize) -> Router {
+        async fn slow() -> &'static str {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            "done"
+        }
+        Router::new()
+            .route("/api/slow", get(slow))
+            .route("/api/chat/events", get(slow))
+            .route(
+                "/api/echo",
+                post(|Json(v): Json<serde_json::Value>| async move { Json(v) }),
+            )
+            .layer(DefaultBodyLimit::max(max_body))
+            .layer(middleware::from_fn_with_state(timeout, timeout_middleware))
+    }
+
+    #[test]
+    fn streaming_paths_are_recognized() {
+        assert!(is_streaming_path("/api/chat/events"));
+        assert!(is_streaming_path("/api/logs/events"));
+        assert!(is_streaming_path("/api/jobs/123/events"));
+        assert!(is_streaming_path("/api/chat/ws"));
+        assert!(!is_streaming_path("/api/chat/send"));
+        assert!(!is_streaming_path("/v1/chat/completions"));
+    }
+
+    #[tokio::test]
+    async fn oversized_body_is_rejected_with_413() {
+        let router = test_router(DEFAULT_REQUEST_TIMEOUT, 32);
+        let body = serde_json::json!({"content": "x".repeat(256)}).to_string();
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/echo")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn small_body_passes() {
+        let router = test_router(DEFAULT_REQUEST_TIMEOUT, 1024);
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/echo")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"ok":true}"#))
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn slow_request_times_out_with_408() {
+        let router = test_router(Duration::from_millis(50), 1024);
+        let req = Request::builder()
+            .uri("/api/slow")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn streaming_endpoint_is_exempt_from_timeout() {
+        let router = test_router(Duration::from_millis(50), 1024);
+        let req = Request::builder()
+            .uri("/api/chat/events")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = router.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -15,7 +15,11 @@
 //! ```
 
 pub mod auth;
+pub mod cors;
+pub mod error;
 pub(crate) mod handlers;
+pub mod idempotency;
+pub mod limits;
 pub mod log_layer;
 pub mod openai_compat;
 pub mod server;
@@ -28,10 +32,13 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
 use tokio_stream::wrappers::ReceiverStream;
 
 use crate::agent::SessionManager;
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+use crate::channels::{
+    Channel, FormatCapabilities, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate,
+};
 use crate::config::GatewayConfig;
 use crate::db::Database;
 use crate::error::ChannelError;
@@ -90,6 +97,7 @@ impl GatewayChannel {
             skill_registry: None,
             skill_catalog: None,
             chat_rate_limiter: server::RateLimiter::new(30, 60),
+            idempotency: idempotency::IdempotencyStore::default(),
             registry_entries: Vec::new(),
             cost_guard: None,
             startup_time: std::time::Instant::now(),
@@ -123,6 +131,7 @@ impl GatewayChannel {
             skill_registry: self.state.skill_registry.clone(),
             skill_catalog: self.state.skill_catalog.clone(),
             chat_rate_limiter: server::RateLimiter::new(30, 60),
+            idempotency: idempotency::IdempotencyStore::default(),
             registry_entries: self.state.registry_entries.clone(),
             cost_guard: self.state.cost_guard.clone(),
             startup_time: self.state.startup_time,
@@ -236,6 +245,16 @@ impl GatewayChannel {
     }
 }
 
+/// Record the sender in the message's metadata. Status updates only carry
+/// the metadata, and `send_status` uses it to route events to that user.
+fn tag_owner(mut msg: IncomingMessage) -> IncomingMessage {
+    if !msg.metadata.is_object() {
+        msg.metadata = serde_json::json!({});
+    }
+    msg.metadata["user_id"] = serde_json::Value::String(msg.user_id.clone());
+    msg
+}
+
 #[async_trait]
 impl Channel for GatewayChannel {
     fn name(&self) -> &str {
@@ -256,9 +275,21 @@ impl Channel for GatewayChannel {
                 ),
             })?;
 
-        server::start_server(addr, self.state.clone(), self.auth_token.clone()).await?;
-
-        Ok(Box::pin(ReceiverStream::new(rx)))
+        let options = server::ServerOptions {
+            cors_allowed_origins: self.config.cors_allowed_origins.clone(),
+            api_keys: self.config.api_keys.clone(),
+            max_body_bytes: self.config.max_body_bytes,
+            request_timeout: std::time::Duration::from_secs(self.config.request_timeout_secs),
+        };
+        server::start_server_with_options(
+            addr,
+            self.state.clone(),
+            self.auth_token.clone(),
+            options,
+        )
+        .await?;
+
+        Ok(Box::pin(ReceiverStream::new(rx).map(tag_owner)))
     }
 
     async fn respond(
@@ -268,10 +299,13 @@ impl Channel for GatewayChannel {
     ) -> Result<(), ChannelError> {
         let thread_id = msg.thread_id.clone().unwrap_or_default();
 
-        self.state.sse.broadcast(SseEvent::Response {
-            content: response.content,
-            thread_id,
-        });
+        self.state.sse.broadcast(
+            &msg.user_id,
+            SseEvent::Response {
+                content: response.content,
+                thread_id,
+            },
+        );
 
         Ok(())
     }
@@ -285,6 +319,10 @@ impl Channel for GatewayChannel {
             .get("thread_id")
             .and_then(|v| v.as_str())
             .map(String::from);
+        let user_id = metadata
+            .get("user_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&self.state.user_id);
         let event = match status {
             StatusUpdate::Thinking(msg) => SseEvent::Thinking {
                 message: msg,
@@ -312,6 +350,10 @@ impl Channel for GatewayChannel {
                 message: msg,
                 thread_id: thread_id.clone(),
             },
+            StatusUpdate::ReasoningTrace(trace) => SseEvent::ReasoningTrace {
+                trace,
+                thread_id: thread_id.clone(),
+            },
             StatusUpdate::JobStarted {
                 job_id,
                 title,
@@ -356,22 +398,29 @@ impl Channel for GatewayChannel {
             },
         };
 
-        self.state.sse.broadcast(event);
+        self.state.sse.broadcast(user_id, event);
         Ok(())
     }
 
     async fn broadcast(
         &self,
-        _user_id: &str,
+        user_id: &str,
         response: OutgoingResponse,
     ) -> Result<(), ChannelError> {
-        self.state.sse.broadcast(SseEvent::Response {
-            content: response.content,
-            thread_id: String::new(),
-        });
+        self.state.sse.broadcast(
+            user_id,
+            SseEvent::Response {
+                content: response.content,
+                thread_id: String::new(),
+            },
+        );
         Ok(())
     }
 
+    fn format_capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities::markdown()
+    }
+
     async fn health_check(&self) -> Result<(), ChannelError> {
         if self.state.msg_tx.read().await.is_some() {
             Ok(())
@@ -15,11 +15,12 @@ use axum::{
         sse::{Event, KeepAlive, Sse},
     },
 };
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
 use crate::llm::{
-    ChatMessage, CompletionRequest, FinishReason, Role, ToolCall, ToolCompletionRequest,
-    ToolDefinition,
+    ChatMessage, CompletionRequest, FinishReason, Role, StreamEvent, ToolCall,
+    ToolCompletionRequest, ToolDefinition,
 };
 
 use super::server::GatewayState;
@@ -37,6 +38,12 @@ pub struct OpenAiChatRequest {
     #[serde(default)]
     pub temperature: Option<f32>,
     #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    #[serde(default)]
     pub max_tokens: Option<u32>,
     #[serde(default)]
     pub stream: Option<bool>,
@@ -231,6 +238,7 @@ pub fn convert_messages(messages: &[OpenAiMessage]) -> Result<Vec<ChatMessage>,
                                 name: tc.function.name.clone(),
                                 arguments: serde_json::from_str(&tc.function.arguments)
                                     .unwrap_or(serde_json::Value::Object(Default::default())),
+                                call_id: None,
                             })
                             .collect();
                         Ok(ChatMessage::assistant_with_tool_calls(
@@ -265,6 +273,7 @@ pub fn convert_tools(tools: &[OpenAiTool]) -> Vec<ToolDefinition> {
                 .parameters
                 .clone()
                 .unwrap_or(serde_json::json!({"type": "object", "properties": {}})),
+            result_schema: None,
         })
         .collect()
 }
@@ -480,6 +489,15 @@ pub async fn chat_completions_handler(
         if let Some(t) = req.temperature {
             tool_req = tool_req.with_temperature(t);
         }
+        if let Some(p) = req.top_p {
+            tool_req = tool_req.with_top_p(p);
+        }
+        if let Some(p) = req.frequency_penalty {
+            tool_req = tool_req.with_frequency_penalty(p);
+        }
+        if let Some(p) = req.presence_penalty {
+            tool_req = tool_req.with_presence_penalty(p);
+        }
         if let Some(mt) = req.max_tokens {
             tool_req = tool_req.with_max_tokens(mt);
         }
@@ -530,6 +548,15 @@ pub async fn chat_completions_handler(
         if let Some(t) = req.temperature {
             comp_req = comp_req.with_temperature(t);
         }
+        if let Some(p) = req.top_p {
+            comp_req = comp_req.with_top_p(p);
+        }
+        if let Some(p) = req.frequency_penalty {
+            comp_req = comp_req.with_frequency_penalty(p);
+        }
+        if let Some(p) = req.presence_penalty {
+            comp_req = comp_req.with_presence_penalty(p);
+        }
         if let Some(mt) = req.max_tokens {
             comp_req = comp_req.with_max_tokens(mt);
         }
@@ -569,11 +596,12 @@ pub async fn chat_completions_handler(
 
 /// Handle streaming responses.
 ///
-/// The current `LlmProvider` returns complete responses (no streaming method).
-/// We execute the LLM call first, then simulate chunked delivery by splitting
-/// the response into word-boundary chunks. This ensures LLM failures return
-/// proper HTTP errors instead of SSE error events. True token streaming can be
-/// added later by extending `LlmProvider` with a `complete_stream()` method.
+/// Requests with tools go through `LlmProvider::complete_stream()`, and text
+/// is forwarded as the provider produces it. Plain completions have no
+/// streaming method, so they run to completion and the response is split
+/// into word-boundary chunks. Either way the provider call is made before
+/// the SSE stream starts, so a failure to reach it is a proper HTTP error
+/// instead of an SSE error event.
 async fn handle_streaming(
     llm: Arc<dyn crate::llm::LlmProvider>,
     req: OpenAiChatRequest,
@@ -586,12 +614,10 @@ async fn handle_streaming(
     let id = chat_completion_id();
     let created = unix_timestamp();
 
-    // Execute the LLM call before starting the SSE stream.
-    // Since streaming is simulated (LlmProvider returns complete responses),
-    // this lets us return proper HTTP errors on failure.
+    // Start the LLM call before the SSE stream so failures are HTTP errors.
     enum LlmResult {
         Simple(crate::llm::CompletionResponse),
-        WithTools(crate::llm::ToolCompletionResponse),
+        WithTools(crate::llm::CompletionStream),
     }
 
     let llm_result = if has_tools {
@@ -600,6 +626,15 @@ async fn handle_streaming(
         if let Some(t) = req.temperature {
             tool_req = tool_req.with_temperature(t);
         }
+        if let Some(p) = req.top_p {
+            tool_req = tool_req.with_top_p(p);
+        }
+        if let Some(p) = req.frequency_penalty {
+            tool_req = tool_req.with_frequency_penalty(p);
+        }
+        if let Some(p) = req.presence_penalty {
+            tool_req = tool_req.with_presence_penalty(p);
+        }
         if let Some(mt) = req.max_tokens {
             tool_req = tool_req.with_max_tokens(mt);
         }
@@ -608,16 +643,21 @@ async fn handle_streaming(
         {
             tool_req = tool_req.with_tool_choice(choice);
         }
-        LlmResult::WithTools(
-            llm.complete_with_tools(tool_req)
-                .await
-                .map_err(map_llm_error)?,
-        )
+        LlmResult::WithTools(llm.complete_stream(tool_req).await.map_err(map_llm_error)?)
     } else {
         let mut comp_req = CompletionRequest::new(messages).with_model(req.model);
         if let Some(t) = req.temperature {
             comp_req = comp_req.with_temperature(t);
         }
+        if let Some(p) = req.top_p {
+            comp_req = comp_req.with_top_p(p);
+        }
+        if let Some(p) = req.frequency_penalty {
+            comp_req = comp_req.with_frequency_penalty(p);
+        }
+        if let Some(p) = req.presence_penalty {
+            comp_req = comp_req.with_presence_penalty(p);
+        }
         if let Some(mt) = req.max_tokens {
             comp_req = comp_req.with_max_tokens(mt);
         }
@@ -652,30 +692,45 @@ async fn handle_streaming(
         let _ = tx.send(Ok(Event::default().data(data))).await;
 
         match llm_result {
-            LlmResult::WithTools(resp) => {
-                // Stream content chunks
-                if let Some(ref content) = resp.content {
-                    stream_content_chunks(&tx, &id, created, &model_name, content).await;
-                }
-
-                // Stream tool calls
-                if !resp.tool_calls.is_empty() {
-                    let deltas: Vec<OpenAiToolCallDelta> = resp
-                        .tool_calls
-                        .iter()
-                        .enumerate()
-                        .map(|(i, tc)| OpenAiToolCallDelta {
-                            index: i as u32,
-                            id: Some(tc.id.clone()),
-                            call_type: Some("function".to_string()),
-                            function: Some(OpenAiToolCallFunctionDelta {
-                                name: Some(tc.name.clone()),
-                                arguments: Some(
-                                    serde_json::to_string(&tc.arguments).unwrap_or_default(),
-                                ),
-                            }),
-                        })
-                        .collect();
+            LlmResult::WithTools(mut stream) => {
+                let mut tool_index = 0u32;
+                while let Some(event) = stream.next().await {
+                    let delta = match event {
+                        Ok(StreamEvent::TextDelta(text)) => OpenAiDelta {
+                            role: None,
+                            content: Some(text),
+                            tool_calls: None,
+                        },
+                        Ok(StreamEvent::ToolCall(tc)) => {
+                            let delta = OpenAiToolCallDelta {
+                                index: tool_index,
+                                id: Some(tc.id),
+                                call_type: Some("function".to_string()),
+                                function: Some(OpenAiToolCallFunctionDelta {
+                                    name: Some(tc.name),
+                                    arguments: Some(
+                                        serde_json::to_string(&tc.arguments).unwrap_or_default(),
+                                    ),
+                                }),
+                            };
+                            tool_index += 1;
+                            OpenAiDelta {
+                                role: None,
+                                content: None,
+                                tool_calls: Some(vec![delta]),
+                            }
+                        }
+                        Ok(StreamEvent::Done { finish_reason, .. }) => {
+                            send_finish_chunk(&tx, &id, created, &model_name, finish_reason).await;
+                            break;
+                        }
+                        Err(e) => {
+                            // Headers are already sent; end the stream without
+                            // a finish chunk so the client sees it as cut off.
+                            tracing::warn!(error = %e, "LLM stream failed mid-response");
+                            break;
+                        }
+                    };
 
                     let chunk = OpenAiChatChunk {
                         id: id.clone(),
@@ -684,20 +739,15 @@ async fn handle_streaming(
                         model: model_name.clone(),
                         choices: vec![OpenAiChunkChoice {
                             index: 0,
-                            delta: OpenAiDelta {
-                                role: None,
-                                content: None,
-                                tool_calls: Some(deltas),
-                            },
+                            delta,
                             finish_reason: None,
                         }],
                     };
                     let data = serde_json::to_string(&chunk).unwrap_or_default();
-                    let _ = tx.send(Ok(Event::default().data(data))).await;
+                    if tx.send(Ok(Event::default().data(data))).await.is_err() {
+                        return;
+                    }
                 }
-
-                // Final chunk with finish_reason
-                send_finish_chunk(&tx, &id, created, &model_name, resp.finish_reason).await;
             }
             LlmResult::Simple(resp) => {
                 stream_content_chunks(&tx, &id, created, &model_name, &resp.content).await;
@@ -714,7 +764,7 @@ async fn handle_streaming(
     let mut response = sse.into_response();
     response.headers_mut().insert(
         "x-ironclaw-streaming",
-        HeaderValue::from_static("simulated"),
+        HeaderValue::from_static(if has_tools { "provider" } else { "simulated" }),
     );
     Ok(response)
 }
@@ -956,6 +1006,7 @@ mod tests {
             id: "call_abc".to_string(),
             name: "search".to_string(),
             arguments: serde_json::json!({"query": "rust"}),
+            call_id: None,
         }];
 
         let converted = convert_tool_calls_to_openai(&calls);
@@ -1001,6 +1052,7 @@ mod tests {
         let req: OpenAiChatRequest = serde_json::from_str(json).unwrap();
         assert_eq!(req.stream, Some(true));
         assert_eq!(req.temperature, Some(0.7));
+        assert_eq!(req.top_p, None);
     }
 
     #[test]
@@ -21,19 +21,25 @@ use axum::{
 use serde::Deserialize;
 use tokio::sync::{mpsc, oneshot};
 use tokio_stream::StreamExt;
-use tower_http::cors::{AllowHeaders, CorsLayer};
 use tower_http::set_header::SetResponseHeaderLayer;
 use uuid::Uuid;
 
 use crate::agent::SessionManager;
 use crate::channels::IncomingMessage;
-use crate::channels::web::auth::{AuthState, auth_middleware};
+use crate::channels::web::auth::{AuthState, AuthenticatedUser, auth_middleware};
+use crate::channels::web::cors::{CorsPolicy, cors_middleware};
+use crate::channels::web::error::{ApiError, error_envelope_middleware};
 use crate::channels::web::handlers::skills::{
     skills_install_handler, skills_list_handler, skills_remove_handler, skills_search_handler,
 };
+use crate::channels::web::idempotency::{
+    IDEMPOTENCY_KEY_HEADER, IdempotencyStore, MAX_IDEMPOTENCY_KEY_LEN, Reservation,
+};
+use crate::channels::web::limits::{self, timeout_middleware};
 use crate::channels::web::log_layer::LogBroadcaster;
 use crate::channels::web::sse::SseManager;
 use crate::channels::web::types::*;
+use crate::clock::{Clock, system_clock};
 use crate::db::Database;
 use crate::extensions::ExtensionManager;
 use crate::orchestrator::job_manager::ContainerJobManager;
@@ -57,34 +63,42 @@ pub type PromptQueue = Arc<
 pub struct RateLimiter {
     /// Requests remaining in the current window.
     remaining: AtomicU64,
-    /// Epoch second when the current window started.
+    /// Seconds after `epoch` when the current window started.
     window_start: AtomicU64,
     /// Maximum requests per window.
     max_requests: u64,
     /// Window duration in seconds.
     window_secs: u64,
+    clock: Arc<dyn Clock>,
+    epoch: std::time::Instant,
 }
 
 impl RateLimiter {
     pub fn new(max_requests: u64, window_secs: u64) -> Self {
+        let clock = system_clock();
         Self {
             remaining: AtomicU64::new(max_requests),
-            window_start: AtomicU64::new(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-            ),
+            window_start: AtomicU64::new(0),
             max_requests,
             window_secs,
+            epoch: clock.now(),
+            clock,
         }
     }
 
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.epoch = clock.now();
+        self.clock = clock;
+        self
+    }
+
     /// Try to consume one request. Returns `true` if allowed, `false` if rate limited.
     pub fn check(&self) -> bool {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
+        let now = self
+            .clock
+            .now()
+            .saturating_duration_since(self.epoch)
             .as_secs();
 
         let window = self.window_start.load(Ordering::Relaxed);
@@ -151,6 +165,8 @@ pub struct GatewayState {
     pub skill_catalog: Option<Arc<crate::skills::catalog::SkillCatalog>>,
     /// Rate limiter for chat endpoints (30 messages per 60 seconds).
     pub chat_rate_limiter: RateLimiter,
+    /// Recent `Idempotency-Key`s seen by `/api/chat/send`.
+    pub idempotency: IdempotencyStore,
     /// Registry catalog entries for the available extensions API.
     /// Populated at startup from `registry/` manifests, independent of extension manager.
     pub registry_entries: Vec<crate::extensions::RegistryEntry>,
@@ -160,6 +176,32 @@ pub struct GatewayState {
     pub startup_time: std::time::Instant,
 }
 
+/// Optional knobs for [`start_server_with_options`].
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Extra origins allowed to make cross-origin requests, beyond the
+    /// gateway's own. Entries are exact (`https://app.example.com`),
+    /// subdomain wildcards (`https://*.example.com`), or `*`.
+    pub cors_allowed_origins: Vec<String>,
+    /// Per-user API keys accepted in addition to the gateway token.
+    pub api_keys: Vec<crate::config::GatewayApiKey>,
+    /// Maximum request body size in bytes; larger bodies get 413.
+    pub max_body_bytes: usize,
+    /// Per-request timeout (408 on expiry). SSE/WebSocket routes are exempt.
+    pub request_timeout: std::time::Duration,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            cors_allowed_origins: Vec::new(),
+            api_keys: Vec::new(),
+            max_body_bytes: limits::DEFAULT_MAX_BODY_BYTES,
+            request_timeout: limits::DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
 /// Start the gateway HTTP server.
 ///
 /// Returns the actual bound `SocketAddr` (useful when binding to port 0).
@@ -167,6 +209,16 @@ pub async fn start_server(
     addr: SocketAddr,
     state: Arc<GatewayState>,
     auth_token: String,
+) -> Result<SocketAddr, crate::error::ChannelError> {
+    start_server_with_options(addr, state, auth_token, ServerOptions::default()).await
+}
+
+/// Start the gateway HTTP server with explicit [`ServerOptions`].
+pub async fn start_server_with_options(
+    addr: SocketAddr,
+    state: Arc<GatewayState>,
+    auth_token: String,
+    options: ServerOptions,
 ) -> Result<SocketAddr, crate::error::ChannelError> {
     let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
         crate::error::ChannelError::StartupFailed {
@@ -183,10 +235,12 @@ pub async fn start_server(
             })?;
 
     // Public routes (no auth)
-    let public = Router::new().route("/api/health", get(health_handler));
+    let public = Router::new()
+        .route("/livez", get(livez_handler))
+        .route("/api/health", get(health_handler));
 
     // Protected routes (require auth)
-    let auth_state = AuthState { token: auth_token };
+    let auth_state = AuthState::new(auth_token).with_api_keys(options.api_keys);
     let protected = Router::new()
         // Chat
         .route("/api/chat/send", post(chat_send_handler))
@@ -198,6 +252,7 @@ pub async fn start_server(
         .route("/api/chat/history", get(chat_history_handler))
         .route("/api/chat/threads", get(chat_threads_handler))
         .route("/api/chat/thread/new", post(chat_new_thread_handler))
+        .route("/api/chat/thread/branch", post(chat_branch_thread_handler))
         // Memory
         .route("/api/memory/tree", get(memory_tree_handler))
         .route("/api/memory/list", get(memory_list_handler))
@@ -234,6 +289,10 @@ pub async fn start_server(
             "/api/extensions/{name}/remove",
             post(extensions_remove_handler),
         )
+        .route(
+            "/api/extensions/{name}/revoke",
+            post(extensions_revoke_handler),
+        )
         .route(
             "/api/extensions/{name}/setup",
             get(extensions_setup_handler).post(extensions_setup_submit_handler),
@@ -278,6 +337,7 @@ pub async fn start_server(
         )
         // Gateway control plane
         .route("/api/gateway/status", get(gateway_status_handler))
+        .route("/metrics", get(metrics_handler))
         // OpenAI-compatible API
         .route(
             "/v1/chat/completions",
@@ -306,36 +366,22 @@ pub async fn start_server(
             auth_middleware,
         ));
 
-    // CORS: restrict to same-origin by default. Only localhost/127.0.0.1
-    // origins are allowed, since the gateway is a local-first service.
-    let cors = CorsLayer::new()
-        .allow_origin([
-            format!("http://{}:{}", addr.ip(), addr.port())
-                .parse()
-                .expect("valid origin"),
-            format!("http://localhost:{}", addr.port())
-                .parse()
-                .expect("valid origin"),
-        ])
-        .allow_methods([
-            axum::http::Method::GET,
-            axum::http::Method::POST,
-            axum::http::Method::PUT,
-            axum::http::Method::DELETE,
-        ])
-        .allow_headers(AllowHeaders::list([
-            header::CONTENT_TYPE,
-            header::AUTHORIZATION,
-        ]))
-        .allow_credentials(true);
+    // CORS: same-origin only by default (the bound address and localhost on
+    // the same port); operators can widen it via GATEWAY_CORS_ALLOWED_ORIGINS.
+    let cors = Arc::new(CorsPolicy::new(bound_addr, &options.cors_allowed_origins));
 
     let app = Router::new()
         .merge(public)
         .merge(statics)
         .merge(projects)
         .merge(protected)
-        .layer(DefaultBodyLimit::max(1024 * 1024)) // 1 MB max request body
-        .layer(cors)
+        .layer(DefaultBodyLimit::max(options.max_body_bytes))
+        .layer(middleware::from_fn_with_state(
+            options.request_timeout,
+            timeout_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(cors, cors_middleware))
+        .layer(middleware::from_fn(error_envelope_middleware))
         .layer(SetResponseHeaderLayer::if_not_present(
             header::X_CONTENT_TYPE_OPTIONS,
             header::HeaderValue::from_static("nosniff"),
@@ -408,27 +454,72 @@ async fn favicon_handler() -> impl IntoResponse {
 
 // --- Health ---
 
-async fn health_handler() -> Json<HealthResponse> {
+/// Liveness probe for orchestrators. Always 200 while the process serves HTTP.
+async fn livez_handler() -> &'static str {
+    "ok"
+}
+
+async fn health_handler(State(state): State<Arc<GatewayState>>) -> Json<HealthResponse> {
+    let llm_circuit = state
+        .llm_provider
+        .as_ref()
+        .and_then(|llm| llm.circuit_snapshot())
+        .map(CircuitBreakerInfo::from);
     Json(HealthResponse {
         status: "healthy",
         channel: "gateway",
+        llm_circuit,
     })
 }
 
+/// Prometheus scrape endpoint. Only populated when the `prometheus`
+/// observability backend is enabled.
+async fn metrics_handler() -> Result<impl IntoResponse, (StatusCode, String)> {
+    let text = crate::observability::prometheus::render().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Metrics are disabled; set OBSERVABILITY_BACKEND=prometheus".to_string(),
+    ))?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text))
+}
+
 // --- Chat handlers ---
 
+/// The user a request acts as: the API key's user if one was used, otherwise
+/// the gateway-wide user.
+fn request_user_id(
+    state: &GatewayState,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+) -> String {
+    user.map(|axum::Extension(u)| u.user_id)
+        .unwrap_or_else(|| state.user_id.clone())
+}
+
 async fn chat_send_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    headers: axum::http::HeaderMap,
     Json(req): Json<SendMessageRequest>,
 ) -> Result<(StatusCode, Json<SendMessageResponse>), (StatusCode, String)> {
-    if !state.chat_rate_limiter.check() {
-        return Err((
-            StatusCode::TOO_MANY_REQUESTS,
-            "Rate limit exceeded. Try again shortly.".to_string(),
-        ));
-    }
+    let idempotency_key = match headers.get(IDEMPOTENCY_KEY_HEADER) {
+        None => None,
+        Some(value) => match value.to_str().map(str::trim) {
+            Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => {
+                Some(key.to_string())
+            }
+            _ => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Idempotency-Key must be 1-{} visible ASCII characters",
+                        MAX_IDEMPOTENCY_KEY_LEN
+                    ),
+                ));
+            }
+        },
+    };
 
-    let mut msg = IncomingMessage::new("gateway", &state.user_id, &req.content);
+    let user_id = request_user_id(&state, user);
+    let mut msg = IncomingMessage::new("gateway", &user_id, &req.content);
 
     if let Some(ref thread_id) = req.thread_id {
         msg = msg.with_thread(thread_id);
@@ -437,6 +528,53 @@ async fn chat_send_handler(
 
     let msg_id = msg.id;
 
+    // A retried submission returns the original id without re-queueing (and
+    // without counting against the rate limit).
+    if let Some(ref key) = idempotency_key
+        && let Reservation::Duplicate(original_id) =
+            state
+                .idempotency
+                .reserve(&user_id, req.thread_id.as_deref(), key, msg_id)
+    {
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(SendMessageResponse {
+                message_id: original_id,
+                status: "accepted",
+            }),
+        ));
+    }
+
+    let result = queue_chat_message(&state, msg).await;
+    if result.is_err()
+        && let Some(ref key) = idempotency_key
+    {
+        state
+            .idempotency
+            .release(&user_id, req.thread_id.as_deref(), key);
+    }
+    result?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(SendMessageResponse {
+            message_id: msg_id,
+            status: "accepted",
+        }),
+    ))
+}
+
+async fn queue_chat_message(
+    state: &GatewayState,
+    msg: IncomingMessage,
+) -> Result<(), (StatusCode, String)> {
+    if !state.chat_rate_limiter.check() {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Rate limit exceeded. Try again shortly.".to_string(),
+        ));
+    }
+
     let tx_guard = state.msg_tx.read().await;
     let tx = tx_guard.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
@@ -448,19 +586,12 @@ async fn chat_send_handler(
             StatusCode::INTERNAL_SERVER_ERROR,
             "Channel closed".to_string(),
         )
-    })?;
-
-    Ok((
-        StatusCode::ACCEPTED,
-        Json(SendMessageResponse {
-            message_id: msg_id,
-            status: "accepted",
-        }),
-    ))
+    })
 }
 
 async fn chat_approval_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Json(req): Json<ApprovalRequest>,
 ) -> Result<(StatusCode, Json<SendMessageResponse>), (StatusCode, String)> {
     let (approved, always) = match req.action.as_str() {
@@ -496,7 +627,8 @@ async fn chat_approval_handler(
         )
     })?;
 
-    let mut msg = IncomingMessage::new("gateway", &state.user_id, content);
+    let user_id = request_user_id(&state, user);
+    let mut msg = IncomingMessage::new("gateway", &user_id, content);
 
     if let Some(ref thread_id) = req.thread_id {
         msg = msg.with_thread(thread_id);
@@ -531,6 +663,7 @@ async fn chat_approval_handler(
 /// The token never touches the LLM, chat history, or SSE stream.
 async fn chat_auth_token_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Json(req): Json<AuthTokenRequest>,
 ) -> Result<Json<ActionResponse>, (StatusCode, String)> {
     let ext_mgr = state.extension_manager.as_ref().ok_or((
@@ -558,23 +691,30 @@ async fn chat_auth_token_handler(
         };
 
         // Clear auth mode on the active thread
-        clear_auth_mode(&state).await;
-
-        state.sse.broadcast(SseEvent::AuthCompleted {
-            extension_name: req.extension_name,
-            success: true,
-            message: msg.clone(),
-        });
+        let user_id = request_user_id(&state, user);
+        clear_auth_mode(&state, &user_id).await;
+
+        state.sse.broadcast(
+            &user_id,
+            SseEvent::AuthCompleted {
+                extension_name: req.extension_name,
+                success: true,
+                message: msg.clone(),
+            },
+        );
 
         Ok(Json(ActionResponse::ok(msg)))
     } else {
         // Re-emit auth_required for retry
-        state.sse.broadcast(SseEvent::AuthRequired {
-            extension_name: req.extension_name.clone(),
-            instructions: result.instructions.clone(),
-            auth_url: result.auth_url.clone(),
-            setup_url: result.setup_url.clone(),
-        });
+        state.sse.broadcast(
+            &request_user_id(&state, user),
+            SseEvent::AuthRequired {
+                extension_name: req.extension_name.clone(),
+                instructions: result.instructions.clone(),
+                auth_url: result.auth_url.clone(),
+                setup_url: result.setup_url.clone(),
+            },
+        );
         Ok(Json(ActionResponse::fail(
             result
                 .instructions
@@ -586,16 +726,17 @@ async fn chat_auth_token_handler(
 /// Cancel an in-progress auth flow.
 async fn chat_auth_cancel_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Json(_req): Json<AuthCancelRequest>,
 ) -> Result<Json<ActionResponse>, (StatusCode, String)> {
-    clear_auth_mode(&state).await;
+    clear_auth_mode(&state, &request_user_id(&state, user)).await;
     Ok(Json(ActionResponse::ok("Auth cancelled")))
 }
 
-/// Clear pending auth mode on the active thread.
-pub async fn clear_auth_mode(state: &GatewayState) {
+/// Clear pending auth mode on `user_id`'s active thread.
+pub async fn clear_auth_mode(state: &GatewayState, user_id: &str) {
     if let Some(ref sm) = state.session_manager {
-        let session = sm.get_or_create_session(&state.user_id).await;
+        let session = sm.get_or_create_session(user_id).await;
         let mut sess = session.lock().await;
         if let Some(thread_id) = sess.active_thread
             && let Some(thread) = sess.threads.get_mut(&thread_id)
@@ -607,8 +748,17 @@ pub async fn clear_auth_mode(state: &GatewayState) {
 
 async fn chat_events_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    headers: axum::http::HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let sse = state.sse.subscribe().ok_or((
+    // EventSource sends the id of the last event it saw when it reconnects;
+    // resume from there so events produced during the gap are replayed.
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    let user_id = request_user_id(&state, user);
+    let sse = state.sse.subscribe_from(&user_id, last_event_id).ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Too many connections".to_string(),
     ))?;
@@ -622,6 +772,7 @@ async fn chat_ws_handler(
     headers: axum::http::HeaderMap,
     ws: WebSocketUpgrade,
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     // Validate Origin header to prevent cross-site WebSocket hijacking.
     // Require the header outright; browsers always send it for WS upgrades,
@@ -652,7 +803,10 @@ async fn chat_ws_handler(
             "WebSocket origin not allowed".to_string(),
         ));
     }
-    Ok(ws.on_upgrade(move |socket| crate::channels::web::ws::handle_ws_connection(socket, state)))
+    let user_id = request_user_id(&state, user);
+    Ok(ws.on_upgrade(move |socket| {
+        crate::channels::web::ws::handle_ws_connection(socket, state, user_id)
+    }))
 }
 
 #[derive(Deserialize)]
@@ -664,14 +818,16 @@ struct HistoryQuery {
 
 async fn chat_history_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Query(query): Query<HistoryQuery>,
 ) -> Result<Json<HistoryResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let session_manager = state.session_manager.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Session manager not available".to_string(),
     ))?;
 
-    let session = session_manager.get_or_create_session(&state.user_id).await;
+    let session = session_manager.get_or_create_session(&user_id).await;
     let sess = session.lock().await;
 
     let limit = query.limit.unwrap_or(50);
@@ -706,7 +862,7 @@ async fn chat_history_handler(
         && let Some(ref store) = state.store
     {
         let owned = store
-            .conversation_belongs_to_user(thread_id, &state.user_id)
+            .conversation_belongs_to_user(thread_id, &user_id)
             .await
             .unwrap_or(false);
         if !owned && !sess.threads.contains_key(&thread_id) {
@@ -754,6 +910,7 @@ async fn chat_history_handler(
                         name: tc.name.clone(),
                         has_result: tc.result.is_some(),
                         has_error: tc.error.is_some(),
+                        status: tc.status,
                     })
                     .collect(),
             })
@@ -837,25 +994,27 @@ fn build_turns_from_db_messages(messages: &[crate::history::ConversationMessage]
 
 async fn chat_threads_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<ThreadListResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let session_manager = state.session_manager.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Session manager not available".to_string(),
     ))?;
 
-    let session = session_manager.get_or_create_session(&state.user_id).await;
+    let session = session_manager.get_or_create_session(&user_id).await;
     let sess = session.lock().await;
 
     // Try DB first for persistent thread list
     if let Some(ref store) = state.store {
         // Auto-create assistant thread if it doesn't exist
         let assistant_id = store
-            .get_or_create_assistant_conversation(&state.user_id, "gateway")
+            .get_or_create_assistant_conversation(&user_id, "gateway")
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
         if let Ok(summaries) = store
-            .list_conversations_with_preview(&state.user_id, "gateway", 50)
+            .list_conversations_with_preview(&user_id, "gateway", 50)
             .await
         {
             let mut assistant_thread = None;
@@ -924,13 +1083,15 @@ async fn chat_threads_handler(
 
 async fn chat_new_thread_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<ThreadInfo>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let session_manager = state.session_manager.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Session manager not available".to_string(),
     ))?;
 
-    let session = session_manager.get_or_create_session(&state.user_id).await;
+    let session = session_manager.get_or_create_session(&user_id).await;
     let mut sess = session.lock().await;
     let thread = sess.create_thread();
     let thread_id = thread.id;
@@ -947,7 +1108,6 @@ async fn chat_new_thread_handler(
     // Persist the empty conversation row with thread_type metadata
     if let Some(ref store) = state.store {
         let store = Arc::clone(store);
-        let user_id = state.user_id.clone();
         tokio::spawn(async move {
             if let Err(e) = store
                 .ensure_conversation(thread_id, "gateway", &user_id, None)
@@ -968,22 +1128,174 @@ async fn chat_new_thread_handler(
     Ok(Json(info))
 }
 
+async fn chat_branch_thread_handler(
+    State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Json(req): Json<BranchThreadRequest>,
+) -> Result<Json<ThreadInfo>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
+    let session_manager = state.session_manager.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Session manager not available".to_string(),
+    ))?;
+
+    let session = session_manager.get_or_create_session(&user_id).await;
+    let mut sess = session.lock().await;
+
+    // Historical threads may only exist in the DB; load them so they can be
+    // branched like in-memory ones.
+    if !sess.threads.contains_key(&req.thread_id) {
+        let store = state
+            .store
+            .as_ref()
+            .ok_or((StatusCode::NOT_FOUND, "Thread not found".to_string()))?;
+        let owned = store
+            .conversation_belongs_to_user(req.thread_id, &user_id)
+            .await
+            .unwrap_or(false);
+        if !owned {
+            return Err((StatusCode::NOT_FOUND, "Thread not found".to_string()));
+        }
+        let messages = store
+            .list_conversation_messages(req.thread_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        let chat_messages = messages
+            .iter()
+            .filter_map(|m| match m.role.as_str() {
+                "user" => Some(crate::llm::ChatMessage::user(&m.content)),
+                "assistant" => Some(crate::llm::ChatMessage::assistant(&m.content)),
+                _ => None,
+            })
+            .collect();
+        let mut thread = crate::agent::Thread::with_id(req.thread_id, sess.id);
+        thread.restore_from_messages(chat_messages);
+        sess.threads.insert(req.thread_id, thread);
+        session_manager
+            .register_thread(&user_id, "gateway", req.thread_id, Arc::clone(&session))
+            .await;
+    }
+
+    let turn_count = sess
+        .threads
+        .get(&req.thread_id)
+        .map(|t| t.turns.len())
+        .unwrap_or(0);
+    let at_turn = req.at_turn.unwrap_or(turn_count);
+    let branch = sess
+        .branch_thread(req.thread_id, at_turn)
+        .and_then(|id| sess.threads.get(&id).cloned())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Cannot branch at turn {}: thread has {} turn(s)",
+                    at_turn, turn_count
+                ),
+            )
+        })?;
+    drop(sess);
+
+    session_manager
+        .register_thread(&user_id, "gateway", branch.id, Arc::clone(&session))
+        .await;
+
+    let info = ThreadInfo {
+        id: branch.id,
+        state: format!("{:?}", branch.state),
+        turn_count: branch.turns.len(),
+        created_at: branch.created_at.to_rfc3339(),
+        updated_at: branch.updated_at.to_rfc3339(),
+        title: None,
+        thread_type: Some("thread".to_string()),
+    };
+
+    if let Some(ref store) = state.store {
+        let store = Arc::clone(store);
+        tokio::spawn(async move {
+            if let Err(e) =
+                crate::agent::persist_branch(store.as_ref(), "gateway", &user_id, &branch).await
+            {
+                tracing::warn!("Failed to persist branch {}: {}", branch.id, e);
+            }
+        });
+    }
+
+    Ok(Json(info))
+}
+
 // --- Memory handlers ---
 
+/// The workspace a request reads and writes: the gateway's own for the
+/// gateway-wide user, otherwise the same storage scoped to the API key's user.
+fn request_workspace(
+    state: &GatewayState,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+) -> Result<Arc<Workspace>, (StatusCode, String)> {
+    let workspace = state.workspace.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Workspace not available".to_string(),
+    ))?;
+    let user_id = request_user_id(state, user);
+    if user_id == workspace.user_id() {
+        Ok(Arc::clone(workspace))
+    } else {
+        Ok(Arc::new(workspace.for_user(user_id)))
+    }
+}
+
 #[derive(Deserialize)]
 struct TreeQuery {
     #[allow(dead_code)]
     depth: Option<usize>,
 }
 
+/// Validate a user-supplied memory path and return it in canonical form.
+///
+/// Paths are relative to the memory root: absolute paths (`/x`, `C:\x`),
+/// `.`/`..` segments, backslashes and control characters are rejected with
+/// 400 so a request can never address anything outside it. Repeated slashes
+/// are collapsed. `allow_root` permits the empty path (used by listing).
+fn validate_memory_path(path: &str, allow_root: bool) -> Result<String, (StatusCode, String)> {
+    let invalid = |reason: &str| {
+        Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid memory path '{}': {}", path, reason),
+        ))
+    };
+
+    let trimmed = path.trim();
+    if trimmed.starts_with('/') || trimmed.as_bytes().get(1) == Some(&b':') {
+        return invalid("absolute paths are not allowed");
+    }
+    if trimmed.contains('\\') {
+        return invalid("backslashes are not allowed");
+    }
+    if trimmed.chars().any(char::is_control) {
+        return invalid("control characters are not allowed");
+    }
+
+    let mut segments = Vec::new();
+    for segment in trimmed.split('/') {
+        match segment {
+            "" => continue,
+            "." | ".." => return invalid("relative segments are not allowed"),
+            s => segments.push(s),
+        }
+    }
+
+    if segments.is_empty() && !allow_root {
+        return invalid("path is empty");
+    }
+    Ok(segments.join("/"))
+}
+
 async fn memory_tree_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Query(_query): Query<TreeQuery>,
 ) -> Result<Json<MemoryTreeResponse>, (StatusCode, String)> {
-    let workspace = state.workspace.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        "Workspace not available".to_string(),
-    ))?;
+    let workspace = request_workspace(&state, user)?;
 
     // Build tree from list_all (flat list of all paths)
     let all_paths = workspace
@@ -1026,16 +1338,14 @@ struct ListQuery {
 
 async fn memory_list_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Query(query): Query<ListQuery>,
 ) -> Result<Json<MemoryListResponse>, (StatusCode, String)> {
-    let workspace = state.workspace.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        "Workspace not available".to_string(),
-    ))?;
+    let workspace = request_workspace(&state, user)?;
 
-    let path = query.path.as_deref().unwrap_or("");
+    let path = validate_memory_path(query.path.as_deref().unwrap_or(""), true)?;
     let entries = workspace
-        .list(path)
+        .list(&path)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -1050,7 +1360,7 @@ async fn memory_list_handler(
         .collect();
 
     Ok(Json(MemoryListResponse {
-        path: path.to_string(),
+        path,
         entries: list_entries,
     }))
 }
@@ -1062,20 +1372,19 @@ struct ReadQuery {
 
 async fn memory_read_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Query(query): Query<ReadQuery>,
 ) -> Result<Json<MemoryReadResponse>, (StatusCode, String)> {
-    let workspace = state.workspace.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        "Workspace not available".to_string(),
-    ))?;
+    let workspace = request_workspace(&state, user)?;
 
+    let path = validate_memory_path(&query.path, false)?;
     let doc = workspace
-        .read(&query.path)
+        .read(&path)
         .await
         .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
 
     Ok(Json(MemoryReadResponse {
-        path: query.path,
+        path,
         content: doc.content,
         updated_at: Some(doc.updated_at.to_rfc3339()),
     }))
@@ -1083,32 +1392,79 @@ async fn memory_read_handler(
 
 async fn memory_write_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    headers: axum::http::HeaderMap,
     Json(req): Json<MemoryWriteRequest>,
-) -> Result<Json<MemoryWriteResponse>, (StatusCode, String)> {
-    let workspace = state.workspace.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        "Workspace not available".to_string(),
-    ))?;
+) -> Result<Json<MemoryWriteResponse>, ApiError> {
+    let workspace = request_workspace(&state, user)?;
+
+    let path = validate_memory_path(&req.path, false)?;
+
+    // Optimistic concurrency: the client names the version it read, either in
+    // the body or as an `If-Match` ETag, and the write only proceeds if the
+    // stored document still has that version. Check-then-write is not atomic,
+    // so this catches stale editors rather than guaranteeing serializability.
+    let expected = req.expected_updated_at.clone().or_else(|| {
+        headers
+            .get(header::IF_MATCH)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.trim()
+                    .trim_start_matches("W/")
+                    .trim_matches('"')
+                    .to_string()
+            })
+    });
+    if let Some(expected) = expected {
+        let expected = chrono::DateTime::parse_from_rfc3339(&expected)
+            .map_err(|_| {
+                ApiError::from_status(
+                    StatusCode::BAD_REQUEST,
+                    "expected_updated_at / If-Match must be an RFC 3339 timestamp",
+                )
+            })?
+            .with_timezone(&chrono::Utc);
+
+        let current = match workspace.read(&path).await {
+            Ok(doc) => Some(doc.updated_at),
+            Err(crate::error::WorkspaceError::DocumentNotFound { .. }) => None,
+            Err(e) => {
+                return Err(ApiError::from_status(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    e.to_string(),
+                ));
+            }
+        };
+        if current != Some(expected) {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "version_conflict",
+                format!("'{}' was modified since it was read", path),
+            )
+            .with_details(serde_json::json!({
+                "current_updated_at": current.map(|ts| ts.to_rfc3339()),
+            })));
+        }
+    }
 
-    workspace
-        .write(&req.path, &req.content)
+    let doc = workspace
+        .write(&path, &req.content)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(MemoryWriteResponse {
-        path: req.path,
+        path,
         status: "written",
+        updated_at: doc.updated_at.to_rfc3339(),
     }))
 }
 
 async fn memory_search_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Json(req): Json<MemorySearchRequest>,
 ) -> Result<Json<MemorySearchResponse>, (StatusCode, String)> {
-    let workspace = state.workspace.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        "Workspace not available".to_string(),
-    ))?;
+    let workspace = request_workspace(&state, user)?;
 
     let limit = req.limit.unwrap_or(10);
     let results = workspace
@@ -1132,7 +1488,9 @@ async fn memory_search_handler(
 
 async fn jobs_list_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<JobListResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -1140,14 +1498,14 @@ async fn jobs_list_handler(
 
     // Fetch sandbox jobs scoped to the authenticated user.
     let sandbox_jobs = store
-        .list_sandbox_jobs_for_user(&state.user_id)
+        .list_sandbox_jobs_for_user(&user_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     // Scope jobs to the authenticated user.
     let mut jobs: Vec<JobInfo> = sandbox_jobs
         .iter()
-        .filter(|j| j.user_id == state.user_id)
+        .filter(|j| j.user_id == user_id)
         .map(|j| {
             let ui_state = match j.status.as_str() {
                 "creating" => "pending",
@@ -1173,14 +1531,16 @@ async fn jobs_list_handler(
 
 async fn jobs_summary_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<JobSummaryResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
     ))?;
 
     let s = store
-        .sandbox_job_summary_for_user(&state.user_id)
+        .sandbox_job_summary_for_user(&user_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -1196,8 +1556,10 @@ async fn jobs_summary_handler(
 
 async fn jobs_detail_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<JobDetailResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let job_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid job ID".to_string()))?;
 
@@ -1205,7 +1567,7 @@ async fn jobs_detail_handler(
     if let Some(ref store) = state.store
         && let Ok(Some(job)) = store.get_sandbox_job(job_id).await
     {
-        if job.user_id != state.user_id {
+        if job.user_id != user_id {
             return Err((StatusCode::NOT_FOUND, "Job not found".to_string()));
         }
         let browse_id = std::path::Path::new(&job.project_dir)
@@ -1268,8 +1630,10 @@ async fn jobs_detail_handler(
 
 async fn jobs_cancel_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let job_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid job ID".to_string()))?;
 
@@ -1277,7 +1641,7 @@ async fn jobs_cancel_handler(
     if let Some(ref store) = state.store
         && let Ok(Some(job)) = store.get_sandbox_job(job_id).await
     {
-        if job.user_id != state.user_id {
+        if job.user_id != user_id {
             return Err((StatusCode::NOT_FOUND, "Job not found".to_string()));
         }
         if job.status == "running" || job.status == "creating" {
@@ -1310,8 +1674,10 @@ async fn jobs_cancel_handler(
 
 async fn jobs_restart_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -1331,7 +1697,7 @@ async fn jobs_restart_handler(
         .ok_or((StatusCode::NOT_FOUND, "Job not found".to_string()))?;
 
     // Scope to the authenticated user.
-    if old_job.user_id != state.user_id {
+    if old_job.user_id != user_id {
         return Err((StatusCode::NOT_FOUND, "Job not found".to_string()));
     }
 
@@ -1417,9 +1783,11 @@ async fn jobs_restart_handler(
 /// Submit a follow-up prompt to a running Claude Code sandbox job.
 async fn jobs_prompt_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
     Json(body): Json<serde_json::Value>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let prompt_queue = state.prompt_queue.as_ref().ok_or((
         StatusCode::NOT_IMPLEMENTED,
         "Claude Code not configured".to_string(),
@@ -1432,7 +1800,7 @@ async fn jobs_prompt_handler(
     // Verify user owns this job.
     if let Some(ref store) = state.store
         && !store
-            .sandbox_job_belongs_to_user(job_id, &state.user_id)
+            .sandbox_job_belongs_to_user(job_id, &user_id)
             .await
             .unwrap_or(false)
     {
@@ -1466,8 +1834,10 @@ async fn jobs_prompt_handler(
 /// Load persisted job events for a job (for history replay on page open).
 async fn jobs_events_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::NOT_IMPLEMENTED,
         "Database not available".to_string(),
@@ -1479,7 +1849,7 @@ async fn jobs_events_handler(
 
     // Verify user owns this job.
     if !store
-        .sandbox_job_belongs_to_user(job_id, &state.user_id)
+        .sandbox_job_belongs_to_user(job_id, &user_id)
         .await
         .unwrap_or(false)
     {
@@ -1518,9 +1888,11 @@ struct FilePathQuery {
 
 async fn job_files_list_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
     Query(query): Query<FilePathQuery>,
 ) -> Result<Json<ProjectFilesResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -1536,7 +1908,7 @@ async fn job_files_list_handler(
         .ok_or((StatusCode::NOT_FOUND, "Job not found".to_string()))?;
 
     // Verify user owns this job.
-    if job.user_id != state.user_id {
+    if job.user_id != user_id {
         return Err((StatusCode::NOT_FOUND, "Job not found".to_string()));
     }
 
@@ -1586,9 +1958,11 @@ async fn job_files_list_handler(
 
 async fn job_files_read_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
     Query(query): Query<FilePathQuery>,
 ) -> Result<Json<ProjectFileReadResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -1604,7 +1978,7 @@ async fn job_files_read_handler(
         .ok_or((StatusCode::NOT_FOUND, "Job not found".to_string()))?;
 
     // Verify user owns this job.
-    if job.user_id != state.user_id {
+    if job.user_id != user_id {
         return Err((StatusCode::NOT_FOUND, "Job not found".to_string()));
     }
 
@@ -1795,8 +2169,17 @@ async fn extensions_install_handler(
         _ => None,
     });
 
+    let options = crate::extensions::InstallOptions {
+        integrity: crate::extensions::ArtifactIntegrity::new(req.sha256, req.signature),
+        capabilities_integrity: crate::extensions::ArtifactIntegrity::new(
+            req.capabilities_sha256,
+            req.capabilities_signature,
+        ),
+        approve_capability_changes: req.approve_capability_changes,
+    };
+
     match ext_mgr
-        .install(&req.name, req.url.as_deref(), kind_hint)
+        .install(&req.name, req.url.as_deref(), kind_hint, &options)
         .await
     {
         Ok(result) => Ok(Json(ActionResponse::ok(result.message))),
@@ -1935,6 +2318,21 @@ async fn extensions_remove_handler(
     }
 }
 
+async fn extensions_revoke_handler(
+    State(state): State<Arc<GatewayState>>,
+    Path(name): Path<String>,
+) -> Result<Json<ActionResponse>, (StatusCode, String)> {
+    let ext_mgr = state.extension_manager.as_ref().ok_or((
+        StatusCode::NOT_IMPLEMENTED,
+        "Extension manager not available (secrets store required)".to_string(),
+    ))?;
+
+    match ext_mgr.revoke(&name).await {
+        Ok(message) => Ok(Json(ActionResponse::ok(message))),
+        Err(e) => Ok(Json(ActionResponse::fail(e.to_string()))),
+    }
+}
+
 async fn extensions_registry_handler(
     State(state): State<Arc<GatewayState>>,
     Query(params): Query<RegistrySearchQuery>,
@@ -2093,14 +2491,16 @@ async fn pairing_approve_handler(
 
 async fn routines_list_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<RoutineListResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
     ))?;
 
     let routines = store
-        .list_routines(&state.user_id)
+        .list_routines(&user_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -2111,14 +2511,16 @@ async fn routines_list_handler(
 
 async fn routines_summary_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<RoutineSummaryResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
     ))?;
 
     let routines = store
-        .list_routines(&state.user_id)
+        .list_routines(&user_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -2154,8 +2556,10 @@ async fn routines_summary_handler(
 
 async fn routines_detail_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<RoutineDetailResponse>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -2164,11 +2568,7 @@ async fn routines_detail_handler(
     let routine_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid routine ID".to_string()))?;
 
-    let routine = store
-        .get_routine(routine_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::NOT_FOUND, "Routine not found".to_string()))?;
+    let routine = owned_routine(store.as_ref(), routine_id, &user_id).await?;
 
     let runs = store
         .list_routine_runs(routine_id, 20)
@@ -2208,8 +2608,10 @@ async fn routines_detail_handler(
 
 async fn routines_trigger_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -2218,11 +2620,7 @@ async fn routines_trigger_handler(
     let routine_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid routine ID".to_string()))?;
 
-    let routine = store
-        .get_routine(routine_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::NOT_FOUND, "Routine not found".to_string()))?;
+    let routine = owned_routine(store.as_ref(), routine_id, &user_id).await?;
 
     // Send the routine prompt through the message pipeline as a manual trigger.
     let prompt = match &routine.action {
@@ -2233,7 +2631,7 @@ async fn routines_trigger_handler(
     };
 
     let content = format!("[routine:{}] {}", routine.name, prompt);
-    let msg = IncomingMessage::new("gateway", &state.user_id, content);
+    let msg = IncomingMessage::new("gateway", &user_id, content);
 
     let tx_guard = state.msg_tx.read().await;
     let tx = tx_guard.as_ref().ok_or((
@@ -2261,9 +2659,11 @@ struct ToggleRequest {
 
 async fn routines_toggle_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
     body: Option<Json<ToggleRequest>>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -2272,11 +2672,7 @@ async fn routines_toggle_handler(
     let routine_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid routine ID".to_string()))?;
 
-    let mut routine = store
-        .get_routine(routine_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::NOT_FOUND, "Routine not found".to_string()))?;
+    let mut routine = owned_routine(store.as_ref(), routine_id, &user_id).await?;
 
     // If a specific value was provided, use it; otherwise toggle.
     routine.enabled = match body {
@@ -2297,8 +2693,10 @@ async fn routines_toggle_handler(
 
 async fn routines_delete_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -2307,6 +2705,7 @@ async fn routines_delete_handler(
     let routine_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid routine ID".to_string()))?;
 
+    owned_routine(store.as_ref(), routine_id, &user_id).await?;
     let deleted = store
         .delete_routine(routine_id)
         .await
@@ -2324,8 +2723,10 @@ async fn routines_delete_handler(
 
 async fn routines_runs_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user_id = request_user_id(&state, user);
     let store = state.store.as_ref().ok_or((
         StatusCode::SERVICE_UNAVAILABLE,
         "Database not available".to_string(),
@@ -2334,6 +2735,7 @@ async fn routines_runs_handler(
     let routine_id = Uuid::parse_str(&id)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid routine ID".to_string()))?;
 
+    owned_routine(store.as_ref(), routine_id, &user_id).await?;
     let runs = store
         .list_routine_runs(routine_id, 50)
         .await
@@ -2358,6 +2760,20 @@ async fn routines_runs_handler(
     })))
 }
 
+/// Load a routine, reporting one owned by another user as not found.
+async fn owned_routine(
+    store: &dyn Database,
+    routine_id: Uuid,
+    user_id: &str,
+) -> Result<crate::agent::routine::Routine, (StatusCode, String)> {
+    store
+        .get_routine(routine_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .filter(|routine| routine.user_id == user_id)
+        .ok_or((StatusCode::NOT_FOUND, "Routine not found".to_string()))
+}
+
 /// Convert a Routine to the trimmed RoutineInfo for list display.
 fn routine_to_info(r: &crate::agent::routine::Routine) -> RoutineInfo {
     let (trigger_type, trigger_summary) = match &r.trigger {
@@ -2410,12 +2826,14 @@ fn routine_to_info(r: &crate::agent::routine::Routine) -> RoutineInfo {
 
 async fn settings_list_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<SettingsListResponse>, StatusCode> {
+    let user_id = request_user_id(&state, user);
     let store = state
         .store
         .as_ref()
         .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
-    let rows = store.list_settings(&state.user_id).await.map_err(|e| {
+    let rows = store.list_settings(&user_id).await.map_err(|e| {
         tracing::error!("Failed to list settings: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
@@ -2434,14 +2852,16 @@ async fn settings_list_handler(
 
 async fn settings_get_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(key): Path<String>,
 ) -> Result<Json<SettingResponse>, StatusCode> {
+    let user_id = request_user_id(&state, user);
     let store = state
         .store
         .as_ref()
         .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
     let row = store
-        .get_setting_full(&state.user_id, &key)
+        .get_setting_full(&user_id, &key)
         .await
         .map_err(|e| {
             tracing::error!("Failed to get setting '{}': {}", key, e);
@@ -2458,15 +2878,17 @@ async fn settings_get_handler(
 
 async fn settings_set_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(key): Path<String>,
     Json(body): Json<SettingWriteRequest>,
 ) -> Result<StatusCode, StatusCode> {
+    let user_id = request_user_id(&state, user);
     let store = state
         .store
         .as_ref()
         .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
     store
-        .set_setting(&state.user_id, &key, &body.value)
+        .set_setting(&user_id, &key, &body.value)
         .await
         .map_err(|e| {
             tracing::error!("Failed to set setting '{}': {}", key, e);
@@ -2478,31 +2900,32 @@ async fn settings_set_handler(
 
 async fn settings_delete_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path(key): Path<String>,
 ) -> Result<StatusCode, StatusCode> {
+    let user_id = request_user_id(&state, user);
     let store = state
         .store
         .as_ref()
         .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
-    store
-        .delete_setting(&state.user_id, &key)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to delete setting '{}': {}", key, e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    store.delete_setting(&user_id, &key).await.map_err(|e| {
+        tracing::error!("Failed to delete setting '{}': {}", key, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 async fn settings_export_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
 ) -> Result<Json<SettingsExportResponse>, StatusCode> {
+    let user_id = request_user_id(&state, user);
     let store = state
         .store
         .as_ref()
         .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
-    let settings = store.get_all_settings(&state.user_id).await.map_err(|e| {
+    let settings = store.get_all_settings(&user_id).await.map_err(|e| {
         tracing::error!("Failed to export settings: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
@@ -2512,14 +2935,16 @@ async fn settings_export_handler(
 
 async fn settings_import_handler(
     State(state): State<Arc<GatewayState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Json(body): Json<SettingsImportRequest>,
 ) -> Result<StatusCode, StatusCode> {
+    let user_id = request_user_id(&state, user);
     let store = state
         .store
         .as_ref()
         .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
     store
-        .set_all_settings(&state.user_id, &body.settings)
+        .set_all_settings(&user_id, &body.settings)
         .await
         .map_err(|e| {
             tracing::error!("Failed to import settings: {}", e);
@@ -2598,6 +3023,21 @@ struct GatewayStatusResponse {
 mod tests {
     use super::*;
 
+    #[test]
+    fn rate_limiter_window_follows_clock() {
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let limiter = RateLimiter::new(2, 60).with_clock(clock.clone());
+
+        assert!(limiter.check());
+        assert!(limiter.check());
+        assert!(!limiter.check());
+
+        clock.advance(std::time::Duration::from_secs(59));
+        assert!(!limiter.check());
+        clock.advance(std::time::Duration::from_secs(1));
+        assert!(limiter.check());
+    }
+
     #[test]
     fn test_build_turns_from_db_messages_complete() {
         let now = chrono::Utc::now();
@@ -2673,4 +3113,391 @@ mod tests {
         let turns = build_turns_from_db_messages(&[]);
         assert!(turns.is_empty());
     }
+
+    fn test_gateway_state(msg_tx: mpsc::Sender<IncomingMessage>) -> Arc<GatewayState> {
+        Arc::new(GatewayState {
+            msg_tx: tokio::sync::RwLock::new(Some(msg_tx)),
+            sse: SseManager::new(),
+            workspace: None,
+            session_manager: None,
+            log_broadcaster: None,
+            log_level_handle: None,
+            extension_manager: None,
+            tool_registry: None,
+            store: None,
+            job_manager: None,
+            prompt_queue: None,
+            user_id: "test".to_string(),
+            shutdown_tx: tokio::sync::RwLock::new(None),
+            ws_tracker: None,
+            llm_provider: None,
+            skill_registry: None,
+            skill_catalog: None,
+            chat_rate_limiter: RateLimiter::new(30, 60),
+            idempotency: IdempotencyStore::default(),
+            registry_entries: Vec::new(),
+            cost_guard: None,
+            startup_time: std::time::Instant::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_chat_branch_thread_handler() {
+        let (tx, _rx) = mpsc::channel(8);
+        let mut state = Arc::into_inner(test_gateway_state(tx)).unwrap();
+        let session_manager = Arc::new(SessionManager::new());
+        state.session_manager = Some(Arc::clone(&session_manager));
+        let state = Arc::new(state);
+
+        let session = session_manager.get_or_create_session("test").await;
+        let thread_id = {
+            let mut sess = session.lock().await;
+            let thread = sess.create_thread();
+            thread.start_turn("first");
+            thread.complete_turn("first reply");
+            thread.start_turn("second");
+            thread.complete_turn("second reply");
+            thread.id
+        };
+
+        let Json(info) = chat_branch_thread_handler(
+            State(state.clone()),
+            None,
+            Json(BranchThreadRequest {
+                thread_id,
+                at_turn: Some(1),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_ne!(info.id, thread_id);
+        assert_eq!(info.turn_count, 1);
+        {
+            let sess = session.lock().await;
+            assert_eq!(sess.active_thread, Some(info.id));
+            assert_eq!(sess.threads[&info.id].turns[0].user_input, "first");
+            assert_eq!(sess.threads[&thread_id].turns.len(), 2);
+        }
+        let (_, resolved) = session_manager
+            .resolve_thread("test", "gateway", Some(&info.id.to_string()))
+            .await;
+        assert_eq!(resolved, info.id);
+
+        let err = chat_branch_thread_handler(
+            State(state.clone()),
+            None,
+            Json(BranchThreadRequest {
+                thread_id,
+                at_turn: Some(5),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+
+        let err = chat_branch_thread_handler(
+            State(state),
+            None,
+            Json(BranchThreadRequest {
+                thread_id: Uuid::new_v4(),
+                at_turn: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_FOUND);
+    }
+
+    async fn send_with_key(state: &Arc<GatewayState>, key: &str) -> Uuid {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, key.parse().unwrap());
+        let req = SendMessageRequest {
+            content: "hello".to_string(),
+            thread_id: Some("thread-1".to_string()),
+        };
+        let (status, Json(resp)) =
+            chat_send_handler(State(state.clone()), None, headers, Json(req))
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        resp.message_id
+    }
+
+    #[tokio::test]
+    async fn test_chat_send_same_idempotency_key_processes_once() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let state = test_gateway_state(tx);
+
+        let first = send_with_key(&state, "retry-1").await;
+        let second = send_with_key(&state, "retry-1").await;
+        assert_eq!(first, second);
+
+        let queued = rx.try_recv().unwrap();
+        assert_eq!(queued.id, first);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chat_send_distinct_idempotency_keys_process_separately() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let state = test_gateway_state(tx);
+
+        let first = send_with_key(&state, "a").await;
+        let second = send_with_key(&state, "b").await;
+        assert_ne!(first, second);
+
+        assert_eq!(rx.try_recv().unwrap().id, first);
+        assert_eq!(rx.try_recv().unwrap().id, second);
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_llm_circuit_breaker() {
+        use crate::llm::{
+            ChatMessage, CircuitBreakerConfig, CircuitBreakerProvider, CompletionRequest,
+            LlmProvider,
+        };
+
+        let (tx, _rx) = mpsc::channel(1);
+        let mut state = test_gateway_state(tx);
+        let Json(health) = health_handler(State(Arc::clone(&state))).await;
+        assert!(health.llm_circuit.is_none());
+
+        let breaker = Arc::new(CircuitBreakerProvider::new(
+            Arc::new(crate::testing::StubLlm::failing("test")),
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                recovery_timeout: std::time::Duration::from_secs(60),
+                half_open_successes_needed: 1,
+                ..CircuitBreakerConfig::default()
+            },
+        ));
+        let _ = breaker
+            .complete(CompletionRequest::new(vec![ChatMessage::user("hello")]))
+            .await;
+        Arc::get_mut(&mut state).unwrap().llm_provider = Some(breaker);
+
+        let Json(health) = health_handler(State(state)).await;
+        let json = serde_json::to_value(&health).unwrap();
+        assert_eq!(json["llm_circuit"]["state"], "open");
+        assert_eq!(json["llm_circuit"]["consecutive_failures"], 1);
+        let next_probe = json["llm_circuit"]["next_probe_in_secs"].as_f64().unwrap();
+        assert!(next_probe > 0.0 && next_probe <= 60.0, "{next_probe}");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exports_llm_requests() {
+        use crate::llm::{ChatMessage, CompletionRequest, LlmProvider, ObservedProvider};
+        use crate::observability::{PrometheusObserver, prometheus};
+
+        prometheus::install().unwrap();
+        let llm = ObservedProvider::new(
+            Arc::new(crate::testing::StubLlm::new("hi")),
+            "nearai",
+            Arc::new(PrometheusObserver),
+        );
+        llm.complete(CompletionRequest::new(vec![ChatMessage::user("hello")]))
+            .await
+            .unwrap();
+        prometheus::register_collector(|| prometheus::record_db_pool(2, 1, 0, 5));
+
+        let response = metrics_handler().await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("text/plain"), "{content_type}");
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        // Every sample line is `name{labels} value`.
+        for line in text
+            .lines()
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "bad value: {line}");
+            let name = series.split('{').next().unwrap();
+            assert!(
+                name.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'),
+                "bad metric name: {line}"
+            );
+        }
+        assert!(text.contains("# TYPE llm_requests_total counter"), "{text}");
+        assert!(
+            text.lines().any(|l| l.starts_with("llm_requests_total{")
+                && l.contains(r#"provider="nearai""#)),
+            "{text}"
+        );
+        assert!(
+            text.contains(r#"db_pool_connections{state="in_use"} 2"#),
+            "{text}"
+        );
+        assert!(text.contains("db_pool_max_size 5"), "{text}");
+    }
+
+    #[test]
+    fn test_memory_path_rejects_traversal() {
+        for bad in [
+            "../../etc/passwd",
+            "notes/../../secret",
+            "./notes.md",
+            "/etc/passwd",
+            "C:\\Windows\\win.ini",
+            "notes\\..\\x",
+            "notes/\0.md",
+        ] {
+            let err = validate_memory_path(bad, false).unwrap_err();
+            assert_eq!(err.0, StatusCode::BAD_REQUEST, "{bad} should be rejected");
+        }
+        assert!(validate_memory_path("", false).is_err());
+    }
+
+    #[test]
+    fn test_memory_path_accepts_nested_paths() {
+        assert_eq!(
+            validate_memory_path("daily/2024-01-15.md", false).unwrap(),
+            "daily/2024-01-15.md"
+        );
+        assert_eq!(
+            validate_memory_path("projects//alpha/notes..md", false).unwrap(),
+            "projects/alpha/notes..md"
+        );
+        assert_eq!(validate_memory_path("", true).unwrap(), "");
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_memory_write_optimistic_concurrency() {
+        let (db, _dir) = crate::testing::test_db().await;
+        let (tx, _rx) = mpsc::channel(8);
+        let mut state = Arc::into_inner(test_gateway_state(tx)).unwrap();
+        state.workspace = Some(Arc::new(Workspace::new_with_db("test", db)));
+        let state = Arc::new(state);
+
+        let write = |content: &str, expected: Option<String>| MemoryWriteRequest {
+            path: "notes/todo.md".to_string(),
+            content: content.to_string(),
+            expected_updated_at: expected,
+        };
+        let no_headers = axum::http::HeaderMap::new;
+
+        let Json(first) = memory_write_handler(
+            State(state.clone()),
+            None,
+            no_headers(),
+            Json(write("v1", None)),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        // Versioned write against the current version succeeds.
+        let Json(second) = memory_write_handler(
+            State(state.clone()),
+            None,
+            no_headers(),
+            Json(write("v2", Some(first.updated_at.clone()))),
+        )
+        .await
+        .unwrap();
+        assert_ne!(second.updated_at, first.updated_at);
+
+        // A client still holding the first version conflicts.
+        let err = memory_write_handler(
+            State(state.clone()),
+            None,
+            no_headers(),
+            Json(write("stale", Some(first.updated_at.clone()))),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        assert_eq!(err.code, "version_conflict");
+        assert_eq!(
+            err.details.unwrap()["current_updated_at"],
+            second.updated_at.as_str()
+        );
+
+        // Same check via If-Match.
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(
+            header::IF_MATCH,
+            format!("\"{}\"", first.updated_at).parse().unwrap(),
+        );
+        let err = memory_write_handler(
+            State(state.clone()),
+            None,
+            headers,
+            Json(write("stale", None)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+
+        let doc = state
+            .workspace
+            .as_ref()
+            .unwrap()
+            .read("notes/todo.md")
+            .await
+            .unwrap();
+        assert_eq!(doc.content, "v2");
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_api_key_user_gets_own_memory_and_settings() {
+        let (db, _dir) = crate::testing::test_db().await;
+        let (tx, _rx) = mpsc::channel(8);
+        let mut state = Arc::into_inner(test_gateway_state(tx)).unwrap();
+        state.workspace = Some(Arc::new(Workspace::new_with_db("test", Arc::clone(&db))));
+        state.store = Some(db);
+        let state = Arc::new(state);
+        let alice = || {
+            Some(axum::Extension(AuthenticatedUser {
+                user_id: "alice".to_string(),
+            }))
+        };
+
+        memory_write_handler(
+            State(state.clone()),
+            alice(),
+            axum::http::HeaderMap::new(),
+            Json(MemoryWriteRequest {
+                path: "notes/private.md".to_string(),
+                content: "alice only".to_string(),
+                expected_updated_at: None,
+            }),
+        )
+        .await
+        .unwrap();
+        let read = |user| {
+            memory_read_handler(
+                State(state.clone()),
+                user,
+                Query(ReadQuery {
+                    path: "notes/private.md".to_string(),
+                }),
+            )
+        };
+        let Json(doc) = read(alice()).await.unwrap();
+        assert_eq!(doc.content, "alice only");
+        assert_eq!(read(None).await.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        settings_set_handler(
+            State(state.clone()),
+            alice(),
+            Path("theme".to_string()),
+            Json(SettingWriteRequest {
+                value: serde_json::json!("dark"),
+            }),
+        )
+        .await
+        .unwrap();
+        let get =
+            |user| settings_get_handler(State(state.clone()), user, Path("theme".to_string()));
+        assert!(get(alice()).await.is_ok());
+        assert_eq!(get(None).await.unwrap_err(), StatusCode::NOT_FOUND);
+    }
 }
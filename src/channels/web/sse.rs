@@ -1,8 +1,9 @@
 //! SSE connection manager for broadcasting events to browser tabs.
 
+use std::collections::{HashMap, VecDeque};
 use std::convert::Infallible;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use axum::response::sse::{Event, KeepAlive, Sse};
@@ -17,9 +18,107 @@ use crate::channels::web::types::SseEvent;
 /// Prevents resource exhaustion from connection flooding.
 const MAX_CONNECTIONS: u64 = 100;
 
+/// Number of recent events kept per thread for `Last-Event-ID` replay.
+const REPLAY_BUFFER_SIZE: usize = 256;
+
+/// Number of threads with a replay buffer. Past this the buffer of the
+/// thread that has been quiet the longest is dropped.
+const MAX_REPLAY_STREAMS: usize = 64;
+
+/// An event tagged with its position in the broadcast sequence and the
+/// user it belongs to.
+///
+/// Ids come from a single counter shared by all threads, so they are
+/// monotonic within every thread and one `Last-Event-ID` identifies a position
+/// in the combined stream. Heartbeats carry no id and are never replayed.
+/// Subscribers only see events whose `user_id` is their own.
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    pub id: Option<u64>,
+    pub user_id: String,
+    pub event: SseEvent,
+}
+
+/// Which replay buffer an event is kept in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ReplayKey {
+    Thread(String),
+    Job(String),
+    /// A user's events not tied to a thread or job, such as auth prompts.
+    User(String),
+}
+
+impl ReplayKey {
+    fn of(user_id: &str, event: &SseEvent) -> Self {
+        if let Some(thread_id) = event.thread_id() {
+            Self::Thread(thread_id.to_string())
+        } else if let Some(job_id) = event.job_id() {
+            Self::Job(job_id.to_string())
+        } else {
+            Self::User(user_id.to_string())
+        }
+    }
+}
+
+/// A buffered event: its id, owning user and the event itself.
+type ReplayEntry = (u64, String, SseEvent);
+
+struct ReplayState {
+    next_id: u64,
+    /// Recent events per thread, so a busy thread can't push a quiet one's
+    /// events out before its client reconnects.
+    buffers: HashMap<ReplayKey, VecDeque<ReplayEntry>>,
+}
+
+impl ReplayState {
+    fn push(&mut self, id: u64, user_id: &str, event: SseEvent) {
+        let key = ReplayKey::of(user_id, &event);
+        if !self.buffers.contains_key(&key) && self.buffers.len() >= MAX_REPLAY_STREAMS {
+            let quietest = self
+                .buffers
+                .iter()
+                .min_by_key(|(_, buffer)| buffer.back().map_or(0, |(id, _, _)| *id))
+                .map(|(key, _)| key.clone());
+            if let Some(quietest) = quietest {
+                self.buffers.remove(&quietest);
+            }
+        }
+        let buffer = self.buffers.entry(key).or_default();
+        if buffer.len() == REPLAY_BUFFER_SIZE {
+            buffer.pop_front();
+        }
+        buffer.push_back((id, user_id.to_string(), event));
+    }
+
+    /// `user_id`'s buffered events newer than `last_event_id`, across all
+    /// threads, in id order.
+    fn since(&self, user_id: &str, last_event_id: u64) -> Vec<SequencedEvent> {
+        let mut events: Vec<SequencedEvent> = self
+            .buffers
+            .values()
+            .flat_map(|buffer| {
+                buffer
+                    .iter()
+                    .filter(|(id, owner, _)| *id > last_event_id && owner == user_id)
+            })
+            .map(|(id, owner, event)| SequencedEvent {
+                id: Some(*id),
+                user_id: owner.clone(),
+                event: event.clone(),
+            })
+            .collect();
+        events.sort_by_key(|event| event.id);
+        events
+    }
+}
+
 /// Manages SSE broadcast to all connected browser tabs.
 pub struct SseManager {
-    tx: broadcast::Sender<SseEvent>,
+    tx: broadcast::Sender<SequencedEvent>,
+    /// Recent events for reconnect replay. Also serializes id assignment with
+    /// sending so a subscriber never sees a gap or duplicate at the seam
+    /// between replayed and live events.
+    replay: Mutex<ReplayState>,
     connection_count: Arc<AtomicU64>,
     max_connections: u64,
 }
@@ -31,15 +130,39 @@ impl SseManager {
         let (tx, _) = broadcast::channel(256);
         Self {
             tx,
+            replay: Mutex::new(ReplayState {
+                next_id: 1,
+                buffers: HashMap::new(),
+            }),
             connection_count: Arc::new(AtomicU64::new(0)),
             max_connections: MAX_CONNECTIONS,
         }
     }
 
-    /// Broadcast an event to all connected clients.
-    pub fn broadcast(&self, event: SseEvent) {
+    fn lock_replay(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        match self.replay.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Broadcast an event to every client connected as `user_id`.
+    pub fn broadcast(&self, user_id: &str, event: SseEvent) {
+        let mut replay = self.lock_replay();
+        let id = if matches!(event, SseEvent::Heartbeat) {
+            None
+        } else {
+            let id = replay.next_id;
+            replay.next_id += 1;
+            replay.push(id, user_id, event.clone());
+            Some(id)
+        };
         // Ignore send errors (no receivers is fine)
-        let _ = self.tx.send(event);
+        let _ = self.tx.send(SequencedEvent {
+            id,
+            user_id: user_id.to_string(),
+            event,
+        });
     }
 
     /// Get current number of active connections.
@@ -47,13 +170,8 @@ impl SseManager {
         self.connection_count.load(Ordering::Relaxed)
     }
 
-    /// Create a raw broadcast subscription for non-SSE consumers (e.g. WebSocket).
-    ///
-    /// Returns a stream of `SseEvent` values and increments/decrements the
-    /// connection counter on creation/drop, just like `subscribe()` does for SSE.
-    ///
-    /// Returns `None` if the maximum connection limit has been reached.
-    pub fn subscribe_raw(&self) -> Option<impl Stream<Item = SseEvent> + Send + 'static + use<>> {
+    /// Reserve a connection slot, or `None` if the limit has been reached.
+    fn acquire_slot(&self) -> Option<Arc<AtomicU64>> {
         // Atomically increment only if below the limit. This prevents
         // concurrent callers from overshooting max_connections.
         let counter = Arc::clone(&self.connection_count);
@@ -67,9 +185,36 @@ impl SseManager {
                 }
             })
             .ok()?;
-        let rx = self.tx.subscribe();
+        Some(counter)
+    }
+
+    /// Subscribe to `user_id`'s sequenced event stream, first replaying
+    /// their buffered events newer than `last_event_id` (if given).
+    ///
+    /// Returns `None` if the maximum connection limit has been reached.
+    pub fn subscribe_sequenced(
+        &self,
+        user_id: &str,
+        last_event_id: Option<u64>,
+    ) -> Option<impl Stream<Item = SequencedEvent> + Send + 'static + use<>> {
+        let counter = self.acquire_slot()?;
+
+        // Subscribe and snapshot the buffer under the same lock `broadcast`
+        // holds, so every event lands in exactly one of the two.
+        let (rx, replayed) = {
+            let replay = self.lock_replay();
+            let rx = self.tx.subscribe();
+            let replayed = match last_event_id {
+                Some(last) => replay.since(user_id, last),
+                None => Vec::new(),
+            };
+            (rx, replayed)
+        };
 
-        let stream = BroadcastStream::new(rx).filter_map(|result| result.ok());
+        let user_id = user_id.to_string();
+        let live = BroadcastStream::new(rx)
+            .filter_map(move |result| result.ok().filter(|sequenced| sequenced.user_id == user_id));
+        let stream = tokio_stream::iter(replayed).chain(live);
 
         Some(CountedStream {
             inner: stream,
@@ -77,29 +222,45 @@ impl SseManager {
         })
     }
 
-    /// Create a new SSE stream for a client connection.
+    /// Create a raw broadcast subscription to `user_id`'s events for
+    /// non-SSE consumers (e.g. WebSocket).
+    ///
+    /// Returns a stream of `SseEvent` values and increments/decrements the
+    /// connection counter on creation/drop, just like `subscribe()` does for SSE.
+    ///
+    /// Returns `None` if the maximum connection limit has been reached.
+    pub fn subscribe_raw(
+        &self,
+        user_id: &str,
+    ) -> Option<impl Stream<Item = SseEvent> + Send + 'static + use<>> {
+        Some(
+            self.subscribe_sequenced(user_id, None)?
+                .map(|sequenced| sequenced.event),
+        )
+    }
+
+    /// Create a new SSE stream of `user_id`'s events for a client connection.
     ///
     /// Returns `None` if the maximum connection limit has been reached.
     pub fn subscribe(
         &self,
+        user_id: &str,
     ) -> Option<Sse<impl Stream<Item = Result<Event, Infallible>> + Send + 'static + use<>>> {
-        // Atomically increment only if below the limit.
-        let counter = Arc::clone(&self.connection_count);
-        let max = self.max_connections;
-        counter
-            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
-                if current < max {
-                    Some(current + 1)
-                } else {
-                    None
-                }
-            })
-            .ok()?;
-        let rx = self.tx.subscribe();
+        self.subscribe_from(user_id, None)
+    }
 
-        let stream = BroadcastStream::new(rx)
-            .filter_map(|result| result.ok())
-            .map(|event| {
+    /// Create an SSE stream of `user_id`'s events that resumes after
+    /// `last_event_id`, the value of the client's `Last-Event-ID` header on
+    /// reconnect.
+    ///
+    /// Returns `None` if the maximum connection limit has been reached.
+    pub fn subscribe_from(
+        &self,
+        user_id: &str,
+        last_event_id: Option<u64>,
+    ) -> Option<Sse<impl Stream<Item = Result<Event, Infallible>> + Send + 'static + use<>>> {
+        let stream = self.subscribe_sequenced(user_id, last_event_id)?.map(
+            |SequencedEvent { id, event, .. }| {
                 let data = serde_json::to_string(&event).unwrap_or_default();
                 let event_type = match &event {
                     SseEvent::Response { .. } => "response",
@@ -109,6 +270,7 @@ impl SseManager {
                     SseEvent::ToolResult { .. } => "tool_result",
                     SseEvent::StreamChunk { .. } => "stream_chunk",
                     SseEvent::Status { .. } => "status",
+                    SseEvent::ReasoningTrace { .. } => "reasoning_trace",
                     SseEvent::ApprovalNeeded { .. } => "approval_needed",
                     SseEvent::AuthRequired { .. } => "auth_required",
                     SseEvent::AuthCompleted { .. } => "auth_completed",
@@ -121,17 +283,16 @@ impl SseManager {
                     SseEvent::JobResult { .. } => "job_result",
                     SseEvent::Heartbeat => "heartbeat",
                 };
-                Ok(Event::default().event(event_type).data(data))
-            });
-
-        // Wrap in a stream that decrements on drop
-        let counted_stream = CountedStream {
-            inner: stream,
-            counter,
-        };
+                let mut sse_event = Event::default().event(event_type).data(data);
+                if let Some(id) = id {
+                    sse_event = sse_event.id(id.to_string());
+                }
+                Ok(sse_event)
+            },
+        );
 
         Some(
-            Sse::new(counted_stream)
+            Sse::new(stream)
                 .keep_alive(KeepAlive::new().interval(Duration::from_secs(30)).text("")),
         )
     }
@@ -183,7 +344,7 @@ mod tests {
     fn test_broadcast_without_receivers() {
         let manager = SseManager::new();
         // Should not panic even with no receivers
-        manager.broadcast(SseEvent::Heartbeat);
+        manager.broadcast("alice", SseEvent::Heartbeat);
     }
 
     #[tokio::test]
@@ -191,14 +352,17 @@ mod tests {
         let manager = SseManager::new();
         let mut rx = BroadcastStream::new(manager.tx.subscribe());
 
-        manager.broadcast(SseEvent::Status {
-            message: "test".to_string(),
-            thread_id: None,
-        });
+        manager.broadcast(
+            "alice",
+            SseEvent::Status {
+                message: "test".to_string(),
+                thread_id: None,
+            },
+        );
 
         let event = rx.next().await;
         assert!(event.is_some());
-        let event = event.unwrap().unwrap();
+        let event = event.unwrap().unwrap().event;
         match event {
             SseEvent::Status { message, .. } => assert_eq!(message, "test"),
             _ => panic!("unexpected event type"),
@@ -208,14 +372,17 @@ mod tests {
     #[tokio::test]
     async fn test_subscribe_raw_receives_events() {
         let manager = SseManager::new();
-        let mut stream = Box::pin(manager.subscribe_raw().expect("should subscribe"));
+        let mut stream = Box::pin(manager.subscribe_raw("alice").expect("should subscribe"));
 
         assert_eq!(manager.connection_count(), 1);
 
-        manager.broadcast(SseEvent::Thinking {
-            message: "working".to_string(),
-            thread_id: None,
-        });
+        manager.broadcast(
+            "alice",
+            SseEvent::Thinking {
+                message: "working".to_string(),
+                thread_id: None,
+            },
+        );
 
         let event = stream.next().await.unwrap();
         match event {
@@ -228,7 +395,7 @@ mod tests {
     async fn test_subscribe_raw_decrements_on_drop() {
         let manager = SseManager::new();
         {
-            let _stream = Box::pin(manager.subscribe_raw().expect("should subscribe"));
+            let _stream = Box::pin(manager.subscribe_raw("alice").expect("should subscribe"));
             assert_eq!(manager.connection_count(), 1);
         }
         // Stream dropped, counter should decrement
@@ -238,11 +405,11 @@ mod tests {
     #[tokio::test]
     async fn test_subscribe_raw_multiple_subscribers() {
         let manager = SseManager::new();
-        let mut s1 = Box::pin(manager.subscribe_raw().expect("should subscribe"));
-        let mut s2 = Box::pin(manager.subscribe_raw().expect("should subscribe"));
+        let mut s1 = Box::pin(manager.subscribe_raw("alice").expect("should subscribe"));
+        let mut s2 = Box::pin(manager.subscribe_raw("alice").expect("should subscribe"));
         assert_eq!(manager.connection_count(), 2);
 
-        manager.broadcast(SseEvent::Heartbeat);
+        manager.broadcast("alice", SseEvent::Heartbeat);
 
         let e1 = s1.next().await.unwrap();
         let e2 = s2.next().await.unwrap();
@@ -260,12 +427,195 @@ mod tests {
         let mut manager = SseManager::new();
         manager.max_connections = 2; // Low limit for testing
 
-        let _s1 = Box::pin(manager.subscribe_raw().expect("first should succeed"));
-        let _s2 = Box::pin(manager.subscribe_raw().expect("second should succeed"));
+        let _s1 = Box::pin(
+            manager
+                .subscribe_raw("alice")
+                .expect("first should succeed"),
+        );
+        let _s2 = Box::pin(
+            manager
+                .subscribe_raw("alice")
+                .expect("second should succeed"),
+        );
         assert_eq!(manager.connection_count(), 2);
 
         // Third should be rejected
-        assert!(manager.subscribe_raw().is_none());
-        assert!(manager.subscribe().is_none());
+        assert!(manager.subscribe_raw("alice").is_none());
+        assert!(manager.subscribe("alice").is_none());
+    }
+
+    fn response(content: &str) -> SseEvent {
+        SseEvent::Response {
+            content: content.to_string(),
+            thread_id: "t1".to_string(),
+        }
+    }
+
+    fn status(message: &str) -> SseEvent {
+        SseEvent::Status {
+            message: message.to_string(),
+            thread_id: Some("t1".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_missed_events_in_order() {
+        let manager = SseManager::new();
+
+        // First connection sees event 1, then drops.
+        {
+            let mut stream = Box::pin(manager.subscribe_sequenced("alice", None).unwrap());
+            manager.broadcast("alice", response("first"));
+            let seen = stream.next().await.unwrap();
+            assert_eq!(seen.id, Some(1));
+        }
+
+        // Events produced while disconnected, including a heartbeat.
+        manager.broadcast("alice", status("thinking"));
+        manager.broadcast("alice", SseEvent::Heartbeat);
+        manager.broadcast("alice", response("second"));
+
+        let mut stream = Box::pin(manager.subscribe_sequenced("alice", Some(1)).unwrap());
+        let replayed_status = stream.next().await.unwrap();
+        assert_eq!(replayed_status.id, Some(2));
+        assert!(
+            matches!(replayed_status.event, SseEvent::Status { ref message, .. } if message == "thinking")
+        );
+        let replayed_response = stream.next().await.unwrap();
+        assert_eq!(replayed_response.id, Some(3));
+        assert!(
+            matches!(replayed_response.event, SseEvent::Response { ref content, .. } if content == "second")
+        );
+
+        // Live events continue after the replay without gaps.
+        manager.broadcast("alice", status("live"));
+        let live = stream.next().await.unwrap();
+        assert_eq!(live.id, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_only_see_their_own_users_events() {
+        let manager = SseManager::new();
+        manager.broadcast("bob", response("bob's old reply"));
+
+        // Neither the replay buffer nor the live stream leaks bob's events.
+        let mut alice = Box::pin(manager.subscribe_sequenced("alice", Some(0)).unwrap());
+        let mut alice_ws = Box::pin(manager.subscribe_raw("alice").unwrap());
+        manager.broadcast(
+            "bob",
+            SseEvent::ApprovalNeeded {
+                request_id: "r1".to_string(),
+                tool_name: "shell".to_string(),
+                description: "run".to_string(),
+                parameters: "{}".to_string(),
+                thread_id: Some("t2".to_string()),
+            },
+        );
+        manager.broadcast("alice", response("alice's reply"));
+
+        let seen = alice.next().await.unwrap();
+        assert_eq!(seen.user_id, "alice");
+        assert!(
+            matches!(seen.event, SseEvent::Response { ref content, .. } if content == "alice's reply")
+        );
+        assert!(matches!(
+            alice_ws.next().await.unwrap(),
+            SseEvent::Response { ref content, .. } if content == "alice's reply"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fresh_subscribe_does_not_replay() {
+        let manager = SseManager::new();
+        manager.broadcast("alice", response("old"));
+
+        let mut stream = Box::pin(manager.subscribe_sequenced("alice", None).unwrap());
+        manager.broadcast("alice", response("new"));
+        let event = stream.next().await.unwrap();
+        assert_eq!(event.id, Some(2));
+    }
+
+    #[test]
+    fn test_replay_buffer_is_bounded_and_skips_heartbeats() {
+        let manager = SseManager::new();
+        for i in 0..(REPLAY_BUFFER_SIZE + 10) {
+            manager.broadcast("alice", status(&i.to_string()));
+            manager.broadcast("alice", SseEvent::Heartbeat);
+        }
+        let replay = manager.lock_replay();
+        let buffer = &replay.buffers[&ReplayKey::Thread("t1".to_string())];
+        assert_eq!(replay.buffers.len(), 1);
+        assert_eq!(buffer.len(), REPLAY_BUFFER_SIZE);
+        assert_eq!(buffer.front().map(|(id, _, _)| *id), Some(11));
+        assert!(
+            buffer
+                .iter()
+                .all(|(_, _, e)| !matches!(e, SseEvent::Heartbeat))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_busy_thread_does_not_evict_quiet_thread() {
+        let manager = SseManager::new();
+        manager.broadcast(
+            "alice",
+            SseEvent::Response {
+                content: "quiet".to_string(),
+                thread_id: "t2".to_string(),
+            },
+        );
+        for i in 0..(REPLAY_BUFFER_SIZE * 2) {
+            manager.broadcast("alice", status(&i.to_string()));
+        }
+        manager.broadcast(
+            "alice",
+            SseEvent::JobStatus {
+                job_id: "j1".to_string(),
+                message: "running".to_string(),
+            },
+        );
+
+        // Replay merges the threads back into id order.
+        let mut stream = Box::pin(manager.subscribe_sequenced("alice", Some(0)).unwrap());
+        let first = stream.next().await.unwrap();
+        assert_eq!(first.id, Some(1));
+        assert_eq!(first.event.thread_id(), Some("t2"));
+        let second = stream.next().await.unwrap();
+        assert_eq!(second.id, Some(REPLAY_BUFFER_SIZE as u64 + 2));
+        assert_eq!(second.event.thread_id(), Some("t1"));
+    }
+
+    #[test]
+    fn test_replay_streams_are_bounded() {
+        let manager = SseManager::new();
+        for i in 0..=MAX_REPLAY_STREAMS {
+            manager.broadcast(
+                "alice",
+                SseEvent::Response {
+                    content: "hi".to_string(),
+                    thread_id: format!("t{i}"),
+                },
+            );
+        }
+        manager.broadcast(
+            "alice",
+            SseEvent::Response {
+                content: "again".to_string(),
+                thread_id: "t1".to_string(),
+            },
+        );
+
+        let replay = manager.lock_replay();
+        assert_eq!(replay.buffers.len(), MAX_REPLAY_STREAMS);
+        // The longest-quiet thread was dropped, not the one just written to.
+        assert!(
+            !replay
+                .buffers
+                .contains_key(&ReplayKey::Thread("t0".to_string()))
+        );
+        assert_eq!(
+            replay.buffers[&ReplayKey::Thread("t1".to_string())].len(),
+            2
+        );
     }
 }
@@ -95,7 +95,14 @@ function apiFetch(path, options) {
   return fetch(path, opts).then((res) => {
     if (!res.ok) {
       return res.text().then(function(body) {
-        throw new Error(body || (res.status + ' ' + res.statusText));
+        let message = body;
+        try {
+          const parsed = JSON.parse(body);
+          if (parsed && parsed.error && parsed.error.message) message = parsed.error.message;
+        } catch (e) {
+          // Not a JSON error envelope; show the raw body.
+        }
+        throw new Error(message || (res.status + ' ' + res.statusText));
       });
     }
     return res.json();
@@ -126,7 +133,7 @@ function connectSSE() {
   eventSource.addEventListener('response', (e) => {
     const data = JSON.parse(e.data);
     if (!isCurrentThread(data.thread_id)) return;
-    addMessage('assistant', data.content);
+    finishStreamingMessage(data.content);
     setStatus('');
     enableChatInput();
     // Refresh thread list so new titles appear after first message
@@ -152,6 +159,13 @@ function connectSSE() {
     setStatus('Tool ' + data.name + ' ' + icon);
   });
 
+  eventSource.addEventListener('reasoning_trace', (e) => {
+    const data = JSON.parse(e.data);
+    if (!isCurrentThread(data.thread_id)) return;
+    const tools = data.tools.map((t) => t.name).join(', ');
+    setStatus('Step ' + data.iteration + ': ' + (data.rationale || 'calling ' + tools), true);
+  });
+
   eventSource.addEventListener('stream_chunk', (e) => {
     const data = JSON.parse(e.data);
     if (!isCurrentThread(data.thread_id)) return;
@@ -361,20 +375,34 @@ function addMessage(role, content) {
   }
   container.appendChild(div);
   container.scrollTop = container.scrollHeight;
+  return div;
 }
 
+// Assistant message being filled by stream_chunk events for the current turn.
+let streamingMessage = null;
+
 function appendToLastAssistant(chunk) {
+  if (!streamingMessage || !streamingMessage.isConnected) {
+    streamingMessage = addMessage('assistant', chunk);
+    return;
+  }
+  const raw = (streamingMessage.getAttribute('data-raw') || '') + chunk;
+  streamingMessage.setAttribute('data-raw', raw);
+  streamingMessage.innerHTML = renderMarkdown(raw);
   const container = document.getElementById('chat-messages');
-  const messages = container.querySelectorAll('.message.assistant');
-  if (messages.length > 0) {
-    const last = messages[messages.length - 1];
-    const raw = (last.getAttribute('data-raw') || '') + chunk;
-    last.setAttribute('data-raw', raw);
-    last.innerHTML = renderMarkdown(raw);
-    container.scrollTop = container.scrollHeight;
+  container.scrollTop = container.scrollHeight;
+}
+
+// Show the final response, replacing the streamed draft if there is one.
+// The draft is raw model output; the response is what the agent settled on.
+function finishStreamingMessage(content) {
+  if (streamingMessage && streamingMessage.isConnected) {
+    streamingMessage.setAttribute('data-raw', content);
+    streamingMessage.innerHTML = renderMarkdown(content);
   } else {
-    addMessage('assistant', chunk);
+    addMessage('assistant', content);
   }
+  streamingMessage = null;
 }
 
 function setStatus(text, spinning) {
@@ -858,6 +886,7 @@ function switchTab(tab) {
 let memorySearchTimeout = null;
 let currentMemoryPath = null;
 let currentMemoryContent = null;
+let currentMemoryVersion = null;
 // Tree state: nested nodes persisted across renders
 // { name, path, is_dir, children: [] | null, expanded: bool, loaded: bool }
 let memoryTreeState = null;
@@ -981,6 +1010,7 @@ function readMemoryFile(path) {
 
   apiFetch('/api/memory/read?path=' + encodeURIComponent(path)).then((data) => {
     currentMemoryContent = data.content;
+    currentMemoryVersion = data.updated_at || null;
     const viewer = document.getElementById('memory-viewer');
     // Render markdown if it's a .md file
     if (path.endsWith('.md')) {
@@ -992,6 +1022,7 @@ function readMemoryFile(path) {
     }
   }).catch((err) => {
     currentMemoryContent = null;
+    currentMemoryVersion = null;
     document.getElementById('memory-viewer').innerHTML = '<div class="empty">Error: ' + escapeHtml(err.message) + '</div>';
   });
 }
@@ -1016,7 +1047,7 @@ function saveMemoryEdit() {
   const content = document.getElementById('memory-edit-textarea').value;
   apiFetch('/api/memory/write', {
     method: 'POST',
-    body: { path: currentMemoryPath, content: content },
+    body: { path: currentMemoryPath, content: content, expected_updated_at: currentMemoryVersion },
   }).then(() => {
     showToast('Saved ' + currentMemoryPath, 'success');
     cancelMemoryEdit();
@@ -1501,6 +1532,15 @@ function renderExtensionCard(ext) {
     actions.appendChild(configBtn);
   }
 
+  if (ext.authenticated) {
+    const revokeBtn = document.createElement('button');
+    revokeBtn.className = 'btn-ext remove';
+    revokeBtn.textContent = 'Revoke';
+    revokeBtn.title = 'Delete stored credentials';
+    revokeBtn.addEventListener('click', () => revokeExtension(ext.name));
+    actions.appendChild(revokeBtn);
+  }
+
   const removeBtn = document.createElement('button');
   removeBtn.className = 'btn-ext remove';
   removeBtn.textContent = 'Remove';
@@ -1557,6 +1597,20 @@ function removeExtension(name) {
     .catch((err) => showToast('Remove failed: ' + err.message, 'error'));
 }
 
+function revokeExtension(name) {
+  if (!confirm('Revoke stored credentials for "' + name + '"?')) return;
+  apiFetch('/api/extensions/' + encodeURIComponent(name) + '/revoke', { method: 'POST' })
+    .then((res) => {
+      if (!res.success) {
+        showToast('Revoke failed: ' + res.message, 'error');
+      } else {
+        showToast(res.message, 'success');
+      }
+      loadExtensions();
+    })
+    .catch((err) => showToast('Revoke failed: ' + err.message, 'error'));
+}
+
 function showConfigureModal(name) {
   apiFetch('/api/extensions/' + encodeURIComponent(name) + '/setup')
     .then((setup) => {
@@ -2672,15 +2726,29 @@ function installWasmExtension() {
     return;
   }
 
+  submitWasmInstall(name, url, false);
+}
+
+function submitWasmInstall(name, url, approveCapabilityChanges) {
   apiFetch('/api/extensions/install', {
     method: 'POST',
-    body: { name: name, url: url, kind: 'wasm_tool' },
+    body: {
+      name: name,
+      url: url,
+      kind: 'wasm_tool',
+      approve_capability_changes: approveCapabilityChanges,
+    },
   }).then(function(res) {
     if (res.success) {
       showToast('Installed ' + name, 'success');
       document.getElementById('wasm-install-name').value = '';
       document.getElementById('wasm-install-url').value = '';
       loadExtensions();
+    } else if (!approveCapabilityChanges && (res.message || '').indexOf('Approval required') === 0) {
+      // The update asks for new secrets or permissions; show them and retry if accepted.
+      if (confirm(res.message + '\n\nApprove these changes?')) {
+        submitWasmInstall(name, url, true);
+      }
     } else {
       showToast('Install failed: ' + (res.message || 'unknown error'), 'error');
     }
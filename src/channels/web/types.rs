@@ -39,6 +39,13 @@ pub struct ThreadListResponse {
     pub active_thread: Option<Uuid>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BranchThreadRequest {
+    pub thread_id: Uuid,
+    /// Number of turns to copy into the branch; omitted copies them all.
+    pub at_turn: Option<usize>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TurnInfo {
     pub turn_number: usize,
@@ -55,6 +62,8 @@ pub struct ToolCallInfo {
     pub name: String,
     pub has_result: bool,
     pub has_error: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<crate::tools::ToolResultStatus>,
 }
 
 #[derive(Debug, Serialize)]
@@ -125,6 +134,13 @@ pub enum SseEvent {
         #[serde(skip_serializing_if = "Option::is_none")]
         thread_id: Option<String>,
     },
+    #[serde(rename = "reasoning_trace")]
+    ReasoningTrace {
+        #[serde(flatten)]
+        trace: crate::channels::ReasoningTrace,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+    },
     #[serde(rename = "job_started")]
     JobStarted {
         job_id: String,
@@ -195,6 +211,38 @@ pub enum SseEvent {
     },
 }
 
+impl SseEvent {
+    /// The chat thread this event belongs to, if any.
+    pub fn thread_id(&self) -> Option<&str> {
+        match self {
+            Self::Response { thread_id, .. } => Some(thread_id),
+            Self::Thinking { thread_id, .. }
+            | Self::ToolStarted { thread_id, .. }
+            | Self::ToolCompleted { thread_id, .. }
+            | Self::ToolResult { thread_id, .. }
+            | Self::StreamChunk { thread_id, .. }
+            | Self::Status { thread_id, .. }
+            | Self::ReasoningTrace { thread_id, .. }
+            | Self::ApprovalNeeded { thread_id, .. }
+            | Self::Error { thread_id, .. } => thread_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The sandbox job this event belongs to, if any.
+    pub fn job_id(&self) -> Option<&str> {
+        match self {
+            Self::JobStarted { job_id, .. }
+            | Self::JobMessage { job_id, .. }
+            | Self::JobToolUse { job_id, .. }
+            | Self::JobToolResult { job_id, .. }
+            | Self::JobStatus { job_id, .. }
+            | Self::JobResult { job_id, .. } => Some(job_id),
+            _ => None,
+        }
+    }
+}
+
 // --- Memory ---
 
 #[derive(Debug, Serialize)]
@@ -233,12 +281,18 @@ pub struct MemoryReadResponse {
 pub struct MemoryWriteRequest {
     pub path: String,
     pub content: String,
+    /// `updated_at` from the client's last read. When set (here or via an
+    /// `If-Match` header), the write fails with 409 if the document changed.
+    #[serde(default)]
+    pub expected_updated_at: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct MemoryWriteResponse {
     pub path: String,
     pub status: &'static str,
+    /// Version of the document after this write.
+    pub updated_at: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -372,6 +426,17 @@ pub struct InstallExtensionRequest {
     pub name: String,
     pub url: Option<String>,
     pub kind: Option<String>,
+    /// Expected hex SHA-256 of the downloaded artifact.
+    pub sha256: Option<String>,
+    /// Hex Ed25519 signature of the artifact, checked against `WASM_INSTALL_PUBLIC_KEY`.
+    pub signature: Option<String>,
+    /// Expected hex SHA-256 of a separately downloaded capabilities file.
+    pub capabilities_sha256: Option<String>,
+    /// Hex Ed25519 signature of the capabilities file.
+    pub capabilities_signature: Option<String>,
+    /// Accept an update that grants the extension new secrets or permissions.
+    #[serde(default)]
+    pub approve_capability_changes: bool,
 }
 
 // --- Extension Setup ---
@@ -601,6 +666,7 @@ impl WsServerMessage {
             SseEvent::ToolResult { .. } => "tool_result",
             SseEvent::StreamChunk { .. } => "stream_chunk",
             SseEvent::Status { .. } => "status",
+            SseEvent::ReasoningTrace { .. } => "reasoning_trace",
             SseEvent::JobStarted { .. } => "job_started",
             SseEvent::ApprovalNeeded { .. } => "approval_needed",
             SseEvent::AuthRequired { .. } => "auth_required",
@@ -717,12 +783,61 @@ pub struct SettingsExportResponse {
 pub struct HealthResponse {
     pub status: &'static str,
     pub channel: &'static str,
+    /// Present when the LLM provider chain has a circuit breaker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_circuit: Option<CircuitBreakerInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CircuitBreakerInfo {
+    /// `closed`, `open` or `half_open`.
+    pub state: &'static str,
+    pub consecutive_failures: u32,
+    /// Seconds until a probe call is allowed; only set while open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_probe_in_secs: Option<f64>,
+}
+
+impl From<crate::llm::CircuitSnapshot> for CircuitBreakerInfo {
+    fn from(snapshot: crate::llm::CircuitSnapshot) -> Self {
+        Self {
+            state: snapshot.state.as_str(),
+            consecutive_failures: snapshot.consecutive_failures,
+            next_probe_in_secs: snapshot.time_until_probe.map(|d| d.as_secs_f64()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tool_call_info_status() {
+        use crate::tools::ToolResultStatus;
+
+        let info = ToolCallInfo {
+            name: "http".to_string(),
+            has_result: false,
+            has_error: true,
+            status: Some(ToolResultStatus::Error { retryable: true }),
+        };
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            json["status"],
+            serde_json::json!({"kind": "error", "retryable": true})
+        );
+
+        let pending = ToolCallInfo {
+            name: "http".to_string(),
+            has_result: false,
+            has_error: false,
+            status: None,
+        };
+        let json = serde_json::to_value(&pending).unwrap();
+        assert!(json.get("status").is_none());
+    }
+
     // ---- WsClientMessage deserialization tests ----
 
     #[test]
@@ -62,7 +62,7 @@ impl Default for WsConnectionTracker {
 ///
 /// When either task ends (client disconnect or broadcast closed), both are
 /// cleaned up.
-pub async fn handle_ws_connection(socket: WebSocket, state: Arc<GatewayState>) {
+pub async fn handle_ws_connection(socket: WebSocket, state: Arc<GatewayState>, user_id: String) {
     let (mut ws_sink, mut ws_stream) = socket.split();
 
     // Track connection
@@ -71,9 +71,9 @@ pub async fn handle_ws_connection(socket: WebSocket, state: Arc<GatewayState>) {
     }
     let tracker_for_drop = state.ws_tracker.clone();
 
-    // Subscribe to broadcast events (same source as SSE).
+    // Subscribe to this user's broadcast events (same source as SSE).
     // Reject if we've hit the connection limit.
-    let Some(raw_stream) = state.sse.subscribe_raw() else {
+    let Some(raw_stream) = state.sse.subscribe_raw(&user_id) else {
         tracing::warn!("WebSocket rejected: too many connections");
         // Decrement the WS tracker we already incremented above.
         if let Some(ref tracker) = tracker_for_drop {
@@ -117,7 +117,6 @@ pub async fn handle_ws_connection(socket: WebSocket, state: Arc<GatewayState>) {
     });
 
     // Receiver task: read client frames and route to agent
-    let user_id = state.user_id.clone();
     while let Some(Ok(frame)) = ws_stream.next().await {
         match frame {
             Message::Text(text) => {
@@ -254,24 +253,26 @@ async fn handle_client_message(
                                 extension_name, e
                             ),
                         };
-                        crate::channels::web::server::clear_auth_mode(state).await;
-                        state
-                            .sse
-                            .broadcast(crate::channels::web::types::SseEvent::AuthCompleted {
+                        crate::channels::web::server::clear_auth_mode(state, user_id).await;
+                        state.sse.broadcast(
+                            user_id,
+                            crate::channels::web::types::SseEvent::AuthCompleted {
                                 extension_name,
                                 success: true,
                                 message: msg,
-                            });
+                            },
+                        );
                     }
                     Ok(result) => {
-                        state
-                            .sse
-                            .broadcast(crate::channels::web::types::SseEvent::AuthRequired {
+                        state.sse.broadcast(
+                            user_id,
+                            crate::channels::web::types::SseEvent::AuthRequired {
                                 extension_name,
                                 instructions: result.instructions,
                                 auth_url: result.auth_url,
                                 setup_url: result.setup_url,
-                            });
+                            },
+                        );
                     }
                     Err(e) => {
                         let _ = direct_tx
@@ -290,7 +291,7 @@ async fn handle_client_message(
             }
         }
         WsClientMessage::AuthCancel { .. } => {
-            crate::channels::web::server::clear_auth_mode(state).await;
+            crate::channels::web::server::clear_auth_mode(state, user_id).await;
         }
         WsClientMessage::Ping => {
             let _ = direct_tx.send(WsServerMessage::Pong).await;
@@ -490,6 +491,7 @@ mod tests {
             skill_registry: None,
             skill_catalog: None,
             chat_rate_limiter: crate::channels::web::server::RateLimiter::new(30, 60),
+            idempotency: crate::channels::web::idempotency::IdempotencyStore::default(),
             registry_entries: Vec::new(),
             cost_guard: None,
             startup_time: std::time::Instant::now(),
@@ -165,15 +165,9 @@ async fn check_database() -> CheckResult {
 async fn try_pg_connect() -> Result<(), String> {
     let url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL not set".to_string())?;
 
-    let config = deadpool_postgres::Config {
-        url: Some(url),
-        ..Default::default()
-    };
-    let pool = config
-        .create_pool(
-            Some(deadpool_postgres::Runtime::Tokio1),
-            tokio_postgres::NoTls,
-        )
+    let pool_config = crate::config::DatabasePoolConfig::resolve(&Default::default())
+        .map_err(|e| e.to_string())?;
+    let pool = crate::db::postgres::create_pool(&url, &pool_config)
         .map_err(|e| format!("pool error: {e}"))?;
 
     let client = tokio::time::timeout(std::time::Duration::from_secs(5), pool.get())
@@ -0,0 +1,58 @@
+//! Database migration CLI command.
+//!
+//! Migrations also run automatically at startup; this command applies them
+//! on their own, e.g. from a deploy step before the agent is restarted.
+
+use crate::config::{DatabaseBackend, DatabaseConfig};
+use crate::settings::Settings;
+
+/// Apply pending schema migrations and report what changed.
+pub async fn run_migrate_command() -> anyhow::Result<()> {
+    let config = DatabaseConfig::resolve(&Settings::default())?;
+
+    match config.backend {
+        DatabaseBackend::LibSql => migrate_libsql(&config).await,
+        DatabaseBackend::Postgres => migrate_postgres(&config).await,
+    }
+}
+
+#[cfg(feature = "postgres")]
+async fn migrate_postgres(config: &DatabaseConfig) -> anyhow::Result<()> {
+    let store = crate::history::Store::new(config).await?;
+
+    let applied = store.migrate().await?;
+    if applied.is_empty() {
+        println!("Database schema is up to date.");
+    } else {
+        for m in &applied {
+            println!("  Applied V{}__{}", m.version, m.name);
+        }
+        println!("Applied {} migration(s).", applied.len());
+    }
+
+    if let Some(latest) = store.applied_migrations().await?.last() {
+        println!(
+            "Current schema version: V{}__{}",
+            latest.version, latest.name
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "postgres"))]
+async fn migrate_postgres(_config: &DatabaseConfig) -> anyhow::Result<()> {
+    anyhow::bail!("postgres feature not compiled in")
+}
+
+#[cfg(feature = "libsql")]
+async fn migrate_libsql(config: &DatabaseConfig) -> anyhow::Result<()> {
+    // The libSQL schema is a single idempotent script; connecting applies it.
+    crate::db::connect_from_config(config).await?;
+    println!("Database schema is up to date.");
+    Ok(())
+}
+
+#[cfg(not(feature = "libsql"))]
+async fn migrate_libsql(_config: &DatabaseConfig) -> anyhow::Result<()> {
+    anyhow::bail!("libsql feature not compiled in")
+}
@@ -8,6 +8,7 @@
 //! - Managing MCP servers (`mcp add`, `mcp auth`, `mcp list`, `mcp test`)
 //! - Querying workspace memory (`memory search`, `memory read`, `memory write`)
 //! - Managing OS service (`service install`, `service start`, `service stop`)
+//! - Applying database schema migrations (`migrate`)
 //! - Active health diagnostics (`doctor`)
 //! - Checking system health (`status`)
 
@@ -16,6 +17,7 @@ mod config;
 mod doctor;
 mod mcp;
 pub mod memory;
+mod migrate;
 pub mod oauth_defaults;
 mod pairing;
 mod registry;
@@ -31,6 +33,7 @@ pub use memory::MemoryCommand;
 #[cfg(feature = "postgres")]
 pub use memory::run_memory_command;
 pub use memory::run_memory_command_with_db;
+pub use migrate::run_migrate_command;
 pub use pairing::{PairingCommand, run_pairing_command, run_pairing_command_with_store};
 pub use registry::{RegistryCommand, run_registry_command};
 pub use service::{ServiceCommand, run_service_command};
@@ -84,6 +87,14 @@ pub enum Command {
         /// Reconfigure channels only
         #[arg(long)]
         channels_only: bool,
+
+        /// Walk through the prompts without saving anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Apply a TOML or JSON setup file instead of prompting
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["channels_only", "dry_run"])]
+        from_file: Option<std::path::PathBuf>,
     },
 
     /// Manage configuration settings
@@ -114,6 +125,9 @@ pub enum Command {
     #[command(subcommand)]
     Service(ServiceCommand),
 
+    /// Apply pending database schema migrations and exit
+    Migrate,
+
     /// Probe external dependencies and validate configuration
     Doctor,
 
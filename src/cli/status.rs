@@ -164,15 +164,8 @@ pub async fn run_status_command() -> anyhow::Result<()> {
 async fn check_database() -> anyhow::Result<()> {
     let url = std::env::var("DATABASE_URL").map_err(|_| anyhow::anyhow!("DATABASE_URL not set"))?;
 
-    let config: deadpool_postgres::Config = deadpool_postgres::Config {
-        url: Some(url),
-        ..Default::default()
-    };
-    let pool = config
-        .create_pool(
-            Some(deadpool_postgres::Runtime::Tokio1),
-            tokio_postgres::NoTls,
-        )
+    let pool_config = crate::config::DatabasePoolConfig::resolve(&Default::default())?;
+    let pool = crate::db::postgres::create_pool(&url, &pool_config)
         .map_err(|e| anyhow::anyhow!("pool error: {}", e))?;
 
     let client = tokio::time::timeout(std::time::Duration::from_secs(5), pool.get())
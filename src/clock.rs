@@ -0,0 +1,110 @@
+//! Injectable time source.
+//!
+//! Components with time-based state (failover cooldowns, circuit breaker
+//! recovery, response cache TTLs, tool rate limits) read the time through
+//! a [`Clock`] instead of calling `Instant::now()` directly. Production
+//! code uses [`SystemClock`]; tests swap in a [`MockClock`] and advance it
+//! by hand, so a 5-minute cooldown can be tested without waiting 5 minutes.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// A source of monotonic and wall-clock time.
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring durations.
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time, for timestamps.
+    fn now_utc(&self) -> DateTime<Utc>;
+}
+
+/// The real clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_utc(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Shared handle to the real clock, the default for every component.
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock that only moves when told to.
+///
+/// Starts at the real time of its creation; [`MockClock::advance`] moves
+/// both readings forward by the same amount.
+#[derive(Debug)]
+pub struct MockClock {
+    start: Instant,
+    start_utc: DateTime<Utc>,
+    offset: Mutex<Duration>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_utc: Utc::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move time forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        let mut offset = self.offset.lock().unwrap_or_else(|e| e.into_inner());
+        *offset += by;
+    }
+
+    /// Total time advanced since creation.
+    pub fn elapsed(&self) -> Duration {
+        *self.offset.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn now_utc(&self) -> DateTime<Utc> {
+        // Saturate rather than panic on absurdly large advances.
+        chrono::Duration::from_std(self.elapsed())
+            .ok()
+            .and_then(|offset| self.start_utc.checked_add_signed(offset))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_moves_only_when_advanced() {
+        let clock = MockClock::new();
+        let (before, before_utc) = (clock.now(), clock.now_utc());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), before);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now() - before, Duration::from_secs(90));
+        assert_eq!((clock.now_utc() - before_utc).num_seconds(), 90);
+        assert_eq!(clock.elapsed(), Duration::from_secs(90));
+    }
+}
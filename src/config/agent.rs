@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
-use crate::config::helpers::{parse_bool_env, parse_option_env, parse_optional_env};
+use crate::config::helpers::{optional_env, parse_bool_env, parse_option_env, parse_optional_env};
 use crate::error::ConfigError;
+use crate::llm::SamplingProfiles;
 use crate::settings::Settings;
+use crate::tools::ToolRateLimitConfig;
 
 /// Agent behavior configuration.
 #[derive(Debug, Clone)]
@@ -23,10 +27,30 @@ pub struct AgentConfig {
     pub max_cost_per_day_cents: Option<u64>,
     /// Maximum LLM/tool actions per hour. None = unlimited.
     pub max_actions_per_hour: Option<u64>,
+    /// Switch to the cheap model once this fraction of the daily budget is
+    /// spent. None = keep using the primary model until the limit.
+    pub auto_downgrade_at_budget_fraction: Option<f64>,
     /// Maximum tool-call iterations per agentic loop invocation. Default 50.
     pub max_tool_iterations: usize,
+    /// Highest limit a session may pick with `/iterations`. None = no
+    /// higher than `max_tool_iterations`.
+    pub max_session_tool_iterations: Option<usize>,
     /// When true, skip tool approval checks entirely. For benchmarks/CI.
     pub auto_approve_tools: bool,
+    /// Append a replayable JSONL transcript of the run to this file.
+    pub transcript_path: Option<PathBuf>,
+    /// Sampling profile used for each kind of LLM call.
+    pub sampling: SamplingProfiles,
+    /// Only offer read-only tools to the model and reject calls to any
+    /// other tool. Sessions can turn it on with `/readonly`, but not off
+    /// when it is set here.
+    pub read_only: bool,
+    /// Per-tool rate limits keyed by tool name, replacing the tool's own.
+    /// Each user gets the full allowance; calls are not pooled across users.
+    pub tool_rate_limits: HashMap<String, ToolRateLimitConfig>,
+    /// Send a redacted reasoning trace to the channel on every tool-calling
+    /// step, for channels that display it in a verbose mode.
+    pub reasoning_trace: bool,
 }
 
 impl AgentConfig {
@@ -61,14 +85,100 @@ impl AgentConfig {
             allow_local_tools: parse_bool_env("ALLOW_LOCAL_TOOLS", false)?,
             max_cost_per_day_cents: parse_option_env("MAX_COST_PER_DAY_CENTS")?,
             max_actions_per_hour: parse_option_env("MAX_ACTIONS_PER_HOUR")?,
+            auto_downgrade_at_budget_fraction: parse_budget_fraction()?,
             max_tool_iterations: parse_optional_env(
                 "AGENT_MAX_TOOL_ITERATIONS",
                 settings.agent.max_tool_iterations,
             )?,
+            max_session_tool_iterations: parse_option_env("AGENT_MAX_SESSION_TOOL_ITERATIONS")?,
             auto_approve_tools: parse_bool_env(
                 "AGENT_AUTO_APPROVE_TOOLS",
                 settings.agent.auto_approve_tools,
             )?,
+            transcript_path: parse_option_env("AGENT_TRANSCRIPT_PATH")?,
+            sampling: parse_sampling_profiles()?,
+            read_only: parse_bool_env("AGENT_READ_ONLY", false)?,
+            tool_rate_limits: optional_env("AGENT_TOOL_RATE_LIMITS")?
+                .map(|raw| parse_tool_rate_limits(&raw))
+                .transpose()
+                .map_err(|message| ConfigError::InvalidValue {
+                    key: "AGENT_TOOL_RATE_LIMITS".to_string(),
+                    message,
+                })?
+                .unwrap_or_default(),
+            reasoning_trace: parse_bool_env("AGENT_REASONING_TRACE", false)?,
         })
     }
 }
+
+/// Read `SAMPLING_PROFILE_<TASK>` overrides (`deterministic`, `balanced`
+/// or `creative`) on top of the default profile for each task.
+fn parse_sampling_profiles() -> Result<SamplingProfiles, ConfigError> {
+    let defaults = SamplingProfiles::default();
+    Ok(SamplingProfiles {
+        chat: parse_optional_env("SAMPLING_PROFILE_CHAT", defaults.chat)?,
+        planning: parse_optional_env("SAMPLING_PROFILE_PLANNING", defaults.planning)?,
+        evaluation: parse_optional_env("SAMPLING_PROFILE_EVALUATION", defaults.evaluation)?,
+        summary: parse_optional_env("SAMPLING_PROFILE_SUMMARY", defaults.summary)?,
+        heartbeat: parse_optional_env("SAMPLING_PROFILE_HEARTBEAT", defaults.heartbeat)?,
+        routine: parse_optional_env("SAMPLING_PROFILE_ROUTINE", defaults.routine)?,
+    })
+}
+
+/// Parse `tool:per_minute/per_hour` entries separated by commas, e.g.
+/// `web_search:10/200,http:30/500`.
+fn parse_tool_rate_limits(raw: &str) -> Result<HashMap<String, ToolRateLimitConfig>, String> {
+    let mut limits = HashMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once(':').and_then(|(tool, rates)| {
+            let tool = tool.trim();
+            let (per_minute, per_hour) = rates.split_once('/')?;
+            let per_minute = per_minute.trim().parse::<u32>().ok()?;
+            let per_hour = per_hour.trim().parse::<u32>().ok()?;
+            (!tool.is_empty()).then(|| {
+                (
+                    tool.to_string(),
+                    ToolRateLimitConfig::new(per_minute, per_hour),
+                )
+            })
+        });
+        let Some((tool, limit)) = parsed else {
+            return Err(format!(
+                "invalid entry '{}', expected tool:per_minute/per_hour",
+                entry
+            ));
+        };
+        limits.insert(tool, limit);
+    }
+    Ok(limits)
+}
+
+fn parse_budget_fraction() -> Result<Option<f64>, ConfigError> {
+    let fraction: Option<f64> = parse_option_env("AUTO_DOWNGRADE_AT_BUDGET_FRACTION")?;
+    match fraction {
+        Some(f) if !(f > 0.0 && f <= 1.0) => Err(ConfigError::InvalidValue {
+            key: "AUTO_DOWNGRADE_AT_BUDGET_FRACTION".to_string(),
+            message: format!("must be greater than 0 and at most 1, got {f}"),
+        }),
+        other => Ok(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tool_rate_limits() {
+        let limits = parse_tool_rate_limits("web_search:10/200, http : 30/500,,").unwrap();
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits["web_search"].requests_per_minute, 10);
+        assert_eq!(limits["web_search"].requests_per_hour, 200);
+        assert_eq!(limits["http"].requests_per_minute, 30);
+
+        assert!(parse_tool_rate_limits("").unwrap().is_empty());
+        assert!(parse_tool_rate_limits("http:30").is_err());
+        assert!(parse_tool_rate_limits("http:many/500").is_err());
+        assert!(parse_tool_rate_limits(":1/2").is_err());
+    }
+}
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use chrono::Offset;
 use secrecy::SecretString;
 
 use crate::config::helpers::{optional_env, parse_bool_env, parse_optional_env};
@@ -19,6 +21,214 @@ pub struct ChannelsConfig {
     pub wasm_channels_enabled: bool,
     /// Telegram owner user ID. When set, the bot only responds to this user.
     pub telegram_owner_id: Option<i64>,
+    /// What to do when a channel fails its health check at startup.
+    pub startup_health_check: StartupHealthCheck,
+    /// Per-channel broadcast throttles, keyed by channel name (`*` for all).
+    pub broadcast_throttle: HashMap<String, BroadcastThrottleConfig>,
+    /// Prefix each part of a split long message with `(i/n)`.
+    pub number_split_messages: bool,
+    /// Daily window during which non-urgent broadcasts are queued.
+    pub quiet_hours: Option<QuietHoursConfig>,
+    /// How long inbound message ids are remembered to drop redeliveries.
+    /// Zero disables deduplication.
+    pub inbound_dedup_ttl: std::time::Duration,
+    /// Per-channel inbound buffer and overflow policy, keyed by channel name
+    /// (`*` for all). Channels without an entry are not buffered.
+    pub backpressure: HashMap<String, BackpressureConfig>,
+}
+
+/// What to do with an inbound message when the channel's buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the agent to catch up (default).
+    #[default]
+    Block,
+    /// Discard the oldest buffered message to make room.
+    DropOldest,
+    /// Discard the incoming message.
+    DropNewest,
+    /// Discard the incoming message and tell the sender to retry.
+    Reject,
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "block" => Ok(Self::Block),
+            "drop_oldest" => Ok(Self::DropOldest),
+            "drop_newest" => Ok(Self::DropNewest),
+            "reject" => Ok(Self::Reject),
+            _ => Err(format!(
+                "invalid overflow policy '{}', expected 'block', 'drop_oldest', 'drop_newest', or 'reject'",
+                s
+            )),
+        }
+    }
+}
+
+/// Inbound buffering for one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackpressureConfig {
+    /// Messages buffered between the channel and the agent.
+    pub buffer: usize,
+    pub policy: OverflowPolicy,
+}
+
+/// Parse `channel:buffer[:policy]` entries separated by commas, e.g.
+/// `repl:32:drop_oldest,*:256`.
+pub(crate) fn parse_backpressure(raw: &str) -> Result<HashMap<String, BackpressureConfig>, String> {
+    let mut configs = HashMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut parts = entry.split(':').map(str::trim);
+        let channel = parts.next().filter(|c| !c.is_empty());
+        let buffer = parts
+            .next()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|&n| n > 0);
+        let (Some(channel), Some(buffer)) = (channel, buffer) else {
+            return Err(format!(
+                "invalid entry '{}', expected channel:buffer[:policy]",
+                entry
+            ));
+        };
+        let policy = parts
+            .next()
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        configs.insert(channel.to_string(), BackpressureConfig { buffer, policy });
+    }
+    Ok(configs)
+}
+
+/// Daily do-not-disturb window for proactive broadcasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHoursConfig {
+    /// Local time the window opens.
+    pub start: chrono::NaiveTime,
+    /// Local time the window closes. Earlier than `start` for windows that
+    /// span midnight; equal to `start` for no window at all.
+    pub end: chrono::NaiveTime,
+    /// Offset from UTC of the user's local time.
+    pub utc_offset: chrono::FixedOffset,
+    /// Deliver everything queued during the window as a single digest
+    /// instead of one message each.
+    pub digest: bool,
+}
+
+/// Parse a `HH:MM-HH:MM` local time range, e.g. `22:00-07:00`.
+pub(crate) fn parse_quiet_hours_range(
+    raw: &str,
+) -> Result<(chrono::NaiveTime, chrono::NaiveTime), String> {
+    let parse = |t: &str| chrono::NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+    match raw.split_once('-') {
+        Some((start, end)) => match (parse(start), parse(end)) {
+            (Some(start), Some(end)) => Ok((start, end)),
+            _ => Err(format!("invalid range '{}', expected HH:MM-HH:MM", raw)),
+        },
+        None => Err(format!("invalid range '{}', expected HH:MM-HH:MM", raw)),
+    }
+}
+
+/// Parse a UTC offset: `UTC`, `+HH`, `+HH:MM` or `-HH:MM`.
+pub(crate) fn parse_utc_offset(raw: &str) -> Result<chrono::FixedOffset, String> {
+    let raw = raw.trim();
+    let invalid = || format!("invalid UTC offset '{}', expected UTC or +HH:MM", raw);
+    if raw.eq_ignore_ascii_case("utc") {
+        return Ok(chrono::Utc.fix());
+    }
+    let (sign, rest) = match raw.split_at_checked(1) {
+        Some(("+", rest)) => (1, rest),
+        Some(("-", rest)) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let (Ok(hours), Ok(minutes)) = (hours.parse::<u8>(), minutes.parse::<u8>()) else {
+        return Err(invalid());
+    };
+    if hours > 14 || minutes >= 60 {
+        return Err(invalid());
+    }
+    let seconds = i32::from(hours) * 3600 + i32::from(minutes) * 60;
+    chrono::FixedOffset::east_opt(sign * seconds).ok_or_else(invalid)
+}
+
+/// Rate limit for proactive broadcasts to one user on one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BroadcastThrottleConfig {
+    /// At most one message per user is delivered per window; the rest wait
+    /// for the end of the window.
+    pub window: std::time::Duration,
+    /// Merge held messages into a single digest. Without this only the most
+    /// recent held message is delivered.
+    pub digest: bool,
+}
+
+/// Parse `channel:seconds[:digest]` entries separated by commas, e.g.
+/// `telegram:30:digest,*:10`.
+pub(crate) fn parse_broadcast_throttle(
+    raw: &str,
+) -> Result<HashMap<String, BroadcastThrottleConfig>, String> {
+    let mut throttles = HashMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut parts = entry.split(':').map(str::trim);
+        let channel = parts.next().filter(|c| !c.is_empty());
+        let secs = parts.next().and_then(|s| s.parse::<u64>().ok());
+        let (Some(channel), Some(secs)) = (channel, secs) else {
+            return Err(format!(
+                "invalid entry '{}', expected channel:seconds[:digest]",
+                entry
+            ));
+        };
+        let digest = match parts.next() {
+            None => false,
+            Some("digest") => true,
+            Some(other) => {
+                return Err(format!(
+                    "invalid option '{}' in '{}', expected 'digest'",
+                    other, entry
+                ));
+            }
+        };
+        throttles.insert(
+            channel.to_string(),
+            BroadcastThrottleConfig {
+                window: std::time::Duration::from_secs(secs),
+                digest,
+            },
+        );
+    }
+    Ok(throttles)
+}
+
+/// Policy for the channel health self-test run at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartupHealthCheck {
+    /// Skip the self-test.
+    Off,
+    /// Log unhealthy channels and keep running (default).
+    #[default]
+    Warn,
+    /// Abort startup if any channel is unhealthy.
+    Fail,
+}
+
+impl std::str::FromStr for StartupHealthCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" | "false" | "0" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "fail" | "strict" => Ok(Self::Fail),
+            _ => Err(format!(
+                "invalid startup health check policy '{}', expected 'off', 'warn', or 'fail'",
+                s
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +252,52 @@ pub struct GatewayConfig {
     /// Bearer token for authentication. Random hex generated at startup if unset.
     pub auth_token: Option<String>,
     pub user_id: String,
+    /// Extra origins allowed to call the gateway cross-origin (exact,
+    /// `https://*.example.com`, or `*`). Empty means same-origin only.
+    pub cors_allowed_origins: Vec<String>,
+    /// Additional bearer keys accepted alongside `auth_token`, each scoped to
+    /// its own user. Parsed from `GATEWAY_API_KEYS` (`key:user_id,...`).
+    pub api_keys: Vec<GatewayApiKey>,
+    /// Maximum request body size in bytes (default 1 MB).
+    pub max_body_bytes: usize,
+    /// Per-request timeout in seconds, excluding SSE/WebSocket (default 120).
+    pub request_timeout_secs: u64,
+}
+
+/// A gateway API key and the user its requests are attributed to.
+#[derive(Clone)]
+pub struct GatewayApiKey {
+    pub key: String,
+    pub user_id: String,
+}
+
+impl std::fmt::Debug for GatewayApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GatewayApiKey")
+            .field("key", &"[REDACTED]")
+            .field("user_id", &self.user_id)
+            .finish()
+    }
+}
+
+/// Parse `key:user_id` pairs separated by commas. A key without `:user_id`
+/// maps to `default_user`.
+pub(crate) fn parse_gateway_api_keys(raw: &str, default_user: &str) -> Vec<GatewayApiKey> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let (key, user_id) = match entry.split_once(':') {
+                Some((key, user)) if !user.trim().is_empty() => (key.trim(), user.trim()),
+                Some((key, _)) => (key.trim(), default_user),
+                None => (entry, default_user),
+            };
+            (!key.is_empty()).then(|| GatewayApiKey {
+                key: key.to_string(),
+                user_id: user_id.to_string(),
+            })
+        })
+        .collect()
 }
 
 /// Signal channel configuration (signal-cli daemon HTTP/JSON-RPC).
@@ -102,11 +358,26 @@ impl ChannelsConfig {
 
         let gateway_enabled = parse_bool_env("GATEWAY_ENABLED", true)?;
         let gateway = if gateway_enabled {
+            let user_id = optional_env("GATEWAY_USER_ID")?.unwrap_or_else(|| "default".to_string());
+            let api_keys = optional_env("GATEWAY_API_KEYS")?
+                .map(|raw| parse_gateway_api_keys(&raw, &user_id))
+                .unwrap_or_default();
             Some(GatewayConfig {
                 host: optional_env("GATEWAY_HOST")?.unwrap_or_else(|| "127.0.0.1".to_string()),
                 port: parse_optional_env("GATEWAY_PORT", 3000)?,
                 auth_token: optional_env("GATEWAY_AUTH_TOKEN")?,
-                user_id: optional_env("GATEWAY_USER_ID")?.unwrap_or_else(|| "default".to_string()),
+                user_id,
+                api_keys,
+                max_body_bytes: parse_optional_env("GATEWAY_MAX_BODY_BYTES", 1024 * 1024)?,
+                request_timeout_secs: parse_optional_env("GATEWAY_REQUEST_TIMEOUT_SECS", 120)?,
+                cors_allowed_origins: optional_env("GATEWAY_CORS_ALLOWED_ORIGINS")?
+                    .map(|s| {
+                        s.split(',')
+                            .map(|e| e.trim().to_string())
+                            .filter(|e| !e.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
             })
         } else {
             None
@@ -187,8 +458,64 @@ impl ChannelsConfig {
                     message: format!("must be an integer: {e}"),
                 })?
                 .or(settings.channels.telegram_owner_id),
+            startup_health_check: optional_env("CHANNEL_STARTUP_HEALTH_CHECK")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|message| ConfigError::InvalidValue {
+                    key: "CHANNEL_STARTUP_HEALTH_CHECK".to_string(),
+                    message,
+                })?
+                .unwrap_or_default(),
+            broadcast_throttle: optional_env("CHANNEL_BROADCAST_THROTTLE")?
+                .map(|raw| parse_broadcast_throttle(&raw))
+                .transpose()
+                .map_err(|message| ConfigError::InvalidValue {
+                    key: "CHANNEL_BROADCAST_THROTTLE".to_string(),
+                    message,
+                })?
+                .unwrap_or_default(),
+            number_split_messages: parse_bool_env("CHANNEL_NUMBER_SPLIT_MESSAGES", false)?,
+            quiet_hours: Self::resolve_quiet_hours()?,
+            inbound_dedup_ttl: std::time::Duration::from_secs(parse_optional_env(
+                "CHANNEL_INBOUND_DEDUP_SECS",
+                300,
+            )?),
+            backpressure: optional_env("CHANNEL_BACKPRESSURE")?
+                .map(|raw| parse_backpressure(&raw))
+                .transpose()
+                .map_err(|message| ConfigError::InvalidValue {
+                    key: "CHANNEL_BACKPRESSURE".to_string(),
+                    message,
+                })?
+                .unwrap_or_default(),
         })
     }
+
+    fn resolve_quiet_hours() -> Result<Option<QuietHoursConfig>, ConfigError> {
+        let Some(range) = optional_env("CHANNEL_QUIET_HOURS")? else {
+            return Ok(None);
+        };
+        let (start, end) =
+            parse_quiet_hours_range(&range).map_err(|message| ConfigError::InvalidValue {
+                key: "CHANNEL_QUIET_HOURS".to_string(),
+                message,
+            })?;
+        let utc_offset = optional_env("CHANNEL_QUIET_HOURS_UTC_OFFSET")?
+            .as_deref()
+            .map(parse_utc_offset)
+            .transpose()
+            .map_err(|message| ConfigError::InvalidValue {
+                key: "CHANNEL_QUIET_HOURS_UTC_OFFSET".to_string(),
+                message,
+            })?
+            .unwrap_or_else(|| chrono::Utc.fix());
+        Ok(Some(QuietHoursConfig {
+            start,
+            end,
+            utc_offset,
+            digest: parse_bool_env("CHANNEL_QUIET_HOURS_DIGEST", false)?,
+        }))
+    }
 }
 
 /// Get the default channels directory (~/.ironclaw/channels/).
@@ -198,3 +525,85 @@ fn default_channels_dir() -> PathBuf {
         .join(".ironclaw")
         .join("channels")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gateway_api_keys_with_user_mapping() {
+        let keys = parse_gateway_api_keys("k1:alice, k2 , k3:, :bob,,", "default");
+        let pairs: Vec<(&str, &str)> = keys
+            .iter()
+            .map(|k| (k.key.as_str(), k.user_id.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("k1", "alice"), ("k2", "default"), ("k3", "default")]
+        );
+    }
+
+    #[test]
+    fn gateway_api_key_debug_redacts_key() {
+        let key = GatewayApiKey {
+            key: "secret".to_string(),
+            user_id: "alice".to_string(),
+        };
+        let debug = format!("{key:?}");
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains("alice"));
+    }
+
+    #[test]
+    fn parses_broadcast_throttle_entries() {
+        let throttles = parse_broadcast_throttle("telegram:30:digest, *:10,").unwrap();
+        assert_eq!(
+            throttles["telegram"],
+            BroadcastThrottleConfig {
+                window: std::time::Duration::from_secs(30),
+                digest: true,
+            }
+        );
+        assert_eq!(throttles["*"].window, std::time::Duration::from_secs(10));
+        assert!(!throttles["*"].digest);
+
+        assert!(parse_broadcast_throttle("telegram").is_err());
+        assert!(parse_broadcast_throttle("telegram:soon").is_err());
+        assert!(parse_broadcast_throttle("telegram:5:merge").is_err());
+    }
+
+    #[test]
+    fn parses_backpressure_entries() {
+        let configs = parse_backpressure("repl:32:drop-oldest, *:256").unwrap();
+        assert_eq!(
+            configs["repl"],
+            BackpressureConfig {
+                buffer: 32,
+                policy: OverflowPolicy::DropOldest,
+            }
+        );
+        assert_eq!(configs["*"].policy, OverflowPolicy::Block);
+
+        assert!(parse_backpressure("repl").is_err());
+        assert!(parse_backpressure("repl:0").is_err());
+        assert!(parse_backpressure("repl:8:shed").is_err());
+    }
+
+    #[test]
+    fn parses_quiet_hours_range_and_offset() {
+        let (start, end) = parse_quiet_hours_range("22:00 - 07:30").unwrap();
+        assert_eq!(start, chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap());
+        assert_eq!(end, chrono::NaiveTime::from_hms_opt(7, 30, 0).unwrap());
+        assert!(parse_quiet_hours_range("22:00").is_err());
+        assert!(parse_quiet_hours_range("25:00-07:00").is_err());
+
+        assert_eq!(parse_utc_offset("UTC").unwrap().local_minus_utc(), 0);
+        assert_eq!(parse_utc_offset("+02").unwrap().local_minus_utc(), 7200);
+        assert_eq!(
+            parse_utc_offset("-05:30").unwrap().local_minus_utc(),
+            -19800
+        );
+        assert!(parse_utc_offset("0200").is_err());
+        assert!(parse_utc_offset("+02:75").is_err());
+    }
+}
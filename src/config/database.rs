@@ -1,9 +1,11 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use secrecy::{ExposeSecret, SecretString};
 
-use crate::config::helpers::{optional_env, parse_optional_env};
+use crate::config::helpers::{optional_env, parse_bool_env, parse_optional_env};
 use crate::error::ConfigError;
+use crate::settings::Settings;
 
 /// Which database backend to use.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -47,7 +49,7 @@ pub struct DatabaseConfig {
 
     // -- PostgreSQL fields --
     pub url: SecretString,
-    pub pool_size: usize,
+    pub pool: DatabasePoolConfig,
 
     // -- libSQL fields --
     /// Path to local libSQL database file (default: ~/.ironclaw/ironclaw.db).
@@ -59,7 +61,7 @@ pub struct DatabaseConfig {
 }
 
 impl DatabaseConfig {
-    pub(crate) fn resolve() -> Result<Self, ConfigError> {
+    pub(crate) fn resolve(settings: &Settings) -> Result<Self, ConfigError> {
         let backend: DatabaseBackend = if let Some(b) = optional_env("DATABASE_BACKEND")? {
             b.parse().map_err(|e| ConfigError::InvalidValue {
                 key: "DATABASE_BACKEND".to_string(),
@@ -85,7 +87,7 @@ impl DatabaseConfig {
                 hint: "Run 'ironclaw onboard' or set DATABASE_URL environment variable".to_string(),
             })?;
 
-        let pool_size = parse_optional_env("DATABASE_POOL_SIZE", 10)?;
+        let pool = DatabasePoolConfig::resolve(settings)?;
 
         let libsql_path = optional_env("LIBSQL_PATH")?.map(PathBuf::from).or_else(|| {
             if backend == DatabaseBackend::LibSql {
@@ -108,7 +110,7 @@ impl DatabaseConfig {
         Ok(Self {
             backend,
             url: SecretString::from(url),
-            pool_size,
+            pool,
             libsql_path,
             libsql_url,
             libsql_auth_token,
@@ -121,6 +123,87 @@ impl DatabaseConfig {
     }
 }
 
+/// PostgreSQL connection-pool sizing and health settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabasePoolConfig {
+    /// Maximum number of open connections.
+    pub max_size: usize,
+    /// How long a caller waits for a free connection. `None` waits forever.
+    pub wait_timeout: Option<Duration>,
+    /// How long opening a new connection may take. `None` waits forever.
+    pub create_timeout: Option<Duration>,
+    /// How long the health check on a reused connection may take.
+    pub recycle_timeout: Option<Duration>,
+    /// Run a test query on every reused connection before handing it out.
+    /// Catches connections the server dropped, at the cost of a round trip.
+    pub health_check: bool,
+}
+
+impl Default for DatabasePoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            wait_timeout: Some(Duration::from_secs(30)),
+            create_timeout: Some(Duration::from_secs(10)),
+            recycle_timeout: Some(Duration::from_secs(5)),
+            health_check: false,
+        }
+    }
+}
+
+impl DatabasePoolConfig {
+    pub(crate) fn resolve(settings: &Settings) -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+
+        let max_size = parse_optional_env(
+            "DATABASE_POOL_SIZE",
+            settings.database_pool_size.unwrap_or(defaults.max_size),
+        )?;
+        if max_size == 0 {
+            return Err(ConfigError::InvalidValue {
+                key: "DATABASE_POOL_SIZE".to_string(),
+                message: "must be at least 1".to_string(),
+            });
+        }
+
+        Ok(Self {
+            max_size,
+            wait_timeout: timeout_env(
+                "DATABASE_POOL_WAIT_TIMEOUT_SECS",
+                settings.database_pool_wait_timeout_secs,
+                defaults.wait_timeout,
+            )?,
+            create_timeout: timeout_env(
+                "DATABASE_POOL_CREATE_TIMEOUT_SECS",
+                settings.database_pool_create_timeout_secs,
+                defaults.create_timeout,
+            )?,
+            recycle_timeout: timeout_env(
+                "DATABASE_POOL_RECYCLE_TIMEOUT_SECS",
+                settings.database_pool_recycle_timeout_secs,
+                defaults.recycle_timeout,
+            )?,
+            health_check: parse_bool_env(
+                "DATABASE_POOL_HEALTH_CHECK",
+                settings
+                    .database_pool_health_check
+                    .unwrap_or(defaults.health_check),
+            )?,
+        })
+    }
+}
+
+/// Read a timeout in seconds, where `0` means no timeout.
+fn timeout_env(
+    key: &str,
+    setting: Option<u64>,
+    default: Option<Duration>,
+) -> Result<Option<Duration>, ConfigError> {
+    let default_secs = setting.unwrap_or_else(|| default.map_or(0, |d| d.as_secs()));
+    let secs: u64 = parse_optional_env(key, default_secs)?;
+    Ok((secs > 0).then(|| Duration::from_secs(secs)))
+}
+
 /// Default libSQL database path (~/.ironclaw/ironclaw.db).
 pub fn default_libsql_path() -> PathBuf {
     dirs::home_dir()
@@ -128,3 +211,73 @@ pub fn default_libsql_path() -> PathBuf {
         .join(".ironclaw")
         .join("ironclaw.db")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::helpers::ENV_MUTEX;
+
+    const POOL_ENV: [&str; 5] = [
+        "DATABASE_POOL_SIZE",
+        "DATABASE_POOL_WAIT_TIMEOUT_SECS",
+        "DATABASE_POOL_CREATE_TIMEOUT_SECS",
+        "DATABASE_POOL_RECYCLE_TIMEOUT_SECS",
+        "DATABASE_POOL_HEALTH_CHECK",
+    ];
+
+    fn clear_pool_env() {
+        // SAFETY: Only called under ENV_MUTEX in tests.
+        unsafe {
+            for key in POOL_ENV {
+                std::env::remove_var(key);
+            }
+        }
+    }
+
+    #[test]
+    fn pool_config_defaults_and_settings() {
+        let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+        clear_pool_env();
+
+        let config = DatabasePoolConfig::resolve(&Settings::default()).unwrap();
+        assert_eq!(config, DatabasePoolConfig::default());
+
+        let settings = Settings {
+            database_pool_size: Some(3),
+            database_pool_wait_timeout_secs: Some(0),
+            database_pool_health_check: Some(true),
+            ..Default::default()
+        };
+        let config = DatabasePoolConfig::resolve(&settings).unwrap();
+        assert_eq!(config.max_size, 3);
+        assert_eq!(config.wait_timeout, None);
+        assert!(config.health_check);
+    }
+
+    #[test]
+    fn pool_config_env_overrides_settings() {
+        let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+        clear_pool_env();
+        // SAFETY: Under ENV_MUTEX, no concurrent env access.
+        unsafe {
+            std::env::set_var("DATABASE_POOL_SIZE", "25");
+            std::env::set_var("DATABASE_POOL_CREATE_TIMEOUT_SECS", "2");
+        }
+
+        let settings = Settings {
+            database_pool_size: Some(3),
+            ..Default::default()
+        };
+        let config = DatabasePoolConfig::resolve(&settings).unwrap();
+        assert_eq!(config.max_size, 25);
+        assert_eq!(config.create_timeout, Some(Duration::from_secs(2)));
+
+        // SAFETY: Under ENV_MUTEX.
+        unsafe {
+            std::env::set_var("DATABASE_POOL_SIZE", "0");
+        }
+        assert!(DatabasePoolConfig::resolve(&settings).is_err());
+
+        clear_pool_env();
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use secrecy::{ExposeSecret, SecretString};
 
+use crate::config::LlmConfig;
 use crate::config::helpers::{optional_env, parse_bool_env, parse_optional_env};
 use crate::error::ConfigError;
 use crate::llm::SessionManager;
@@ -13,7 +14,7 @@ use crate::workspace::EmbeddingProvider;
 pub struct EmbeddingsConfig {
     /// Whether embeddings are enabled.
     pub enabled: bool,
-    /// Provider to use: "openai", "nearai", or "ollama"
+    /// Provider to use: "openai", "nearai", "ollama", or "openai_compatible"
     pub provider: String,
     /// OpenAI API key (for OpenAI provider).
     pub openai_api_key: Option<SecretString>,
@@ -92,11 +93,12 @@ impl EmbeddingsConfig {
     /// Create the appropriate embedding provider based on configuration.
     ///
     /// Returns `None` if embeddings are disabled or the required credentials
-    /// are missing. The `nearai_base_url` and `session` are needed only for
-    /// the NEAR AI provider but must be passed unconditionally.
+    /// are missing. The NEAR AI provider uses `llm.nearai.base_url` and
+    /// `session`; the OpenAI-compatible provider reuses the endpoint of
+    /// `llm.openai_compatible`.
     pub fn create_provider(
         &self,
-        nearai_base_url: &str,
+        llm: &LlmConfig,
         session: Arc<SessionManager>,
     ) -> Option<Arc<dyn EmbeddingProvider>> {
         if !self.enabled {
@@ -112,7 +114,7 @@ impl EmbeddingsConfig {
                     self.dimension,
                 );
                 Some(Arc::new(
-                    crate::workspace::NearAiEmbeddings::new(nearai_base_url, session)
+                    crate::workspace::NearAiEmbeddings::new(&llm.nearai.base_url, session)
                         .with_model(&self.model, self.dimension),
                 ))
             }
@@ -128,6 +130,25 @@ impl EmbeddingsConfig {
                         .with_model(&self.model, self.dimension),
                 ))
             }
+            "openai_compatible" => {
+                let Some(ref compat) = llm.openai_compatible else {
+                    tracing::warn!(
+                        "Embeddings configured for openai_compatible but \
+                         LLM_BACKEND is not openai_compatible"
+                    );
+                    return None;
+                };
+                tracing::info!(
+                    "Embeddings enabled via OpenAI-compatible endpoint (model: {}, url: {}, dim: {})",
+                    self.model,
+                    compat.base_url,
+                    self.dimension,
+                );
+                Some(Arc::new(
+                    crate::workspace::OpenAiCompatibleEmbeddings::from_llm_config(compat)
+                        .with_model(&self.model, self.dimension),
+                ))
+            }
             _ => {
                 if let Some(api_key) = self.openai_api_key() {
                     tracing::info!(
@@ -25,6 +25,8 @@ pub enum LlmBackend {
     OpenAiCompatible,
     /// Tinfoil private inference
     Tinfoil,
+    /// Direct Google Gemini API
+    Gemini,
 }
 
 impl std::str::FromStr for LlmBackend {
@@ -38,8 +40,9 @@ impl std::str::FromStr for LlmBackend {
             "ollama" => Ok(Self::Ollama),
             "openai_compatible" | "openai-compatible" | "compatible" => Ok(Self::OpenAiCompatible),
             "tinfoil" => Ok(Self::Tinfoil),
+            "gemini" | "google" => Ok(Self::Gemini),
             _ => Err(format!(
-                "invalid LLM backend '{}', expected one of: nearai, openai, anthropic, ollama, openai_compatible, tinfoil",
+                "invalid LLM backend '{}', expected one of: nearai, openai, anthropic, ollama, openai_compatible, tinfoil, gemini",
                 s
             )),
         }
@@ -55,6 +58,32 @@ impl std::fmt::Display for LlmBackend {
             Self::Ollama => write!(f, "ollama"),
             Self::OpenAiCompatible => write!(f, "openai_compatible"),
             Self::Tinfoil => write!(f, "tinfoil"),
+            Self::Gemini => write!(f, "gemini"),
+        }
+    }
+}
+
+/// Where the NEAR AI session token is persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionStoreKind {
+    /// `session_path` on local disk (default).
+    #[default]
+    File,
+    /// The PostgreSQL `settings` table, shared by every replica.
+    Postgres,
+}
+
+impl std::str::FromStr for SessionStoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(Self::File),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            _ => Err(format!(
+                "invalid session store '{}', expected 'file' or 'postgres'",
+                s
+            )),
         }
     }
 }
@@ -102,6 +131,13 @@ pub struct TinfoilConfig {
     pub model: String,
 }
 
+/// Configuration for direct Google Gemini API access.
+#[derive(Debug, Clone)]
+pub struct GeminiConfig {
+    pub api_key: SecretString,
+    pub model: String,
+}
+
 /// LLM provider configuration.
 ///
 /// NEAR AI remains the default backend. Users can switch to other providers
@@ -122,6 +158,15 @@ pub struct LlmConfig {
     pub openai_compatible: Option<OpenAiCompatibleConfig>,
     /// Tinfoil config (populated when backend=tinfoil)
     pub tinfoil: Option<TinfoilConfig>,
+    /// Gemini config (populated when backend=gemini)
+    pub gemini: Option<GeminiConfig>,
+    /// `User-Agent` sent with every LLM request.
+    /// Override with `LLM_USER_AGENT` (default: `ironclaw/<version>`).
+    pub user_agent: String,
+    /// HTTP(S) or SOCKS5 proxy for requests to rig-core backends (OpenAI,
+    /// Anthropic, Ollama, Gemini, Tinfoil, OpenAI-compatible), from
+    /// `LLM_PROXY_URL`, e.g. `http://proxy.corp:3128` or `socks5://127.0.0.1:1080`.
+    pub proxy_url: Option<String>,
 }
 
 /// NEAR AI configuration.
@@ -139,6 +184,8 @@ pub struct NearAiConfig {
     pub auth_base_url: String,
     /// Path to session file (default: ~/.ironclaw/session.json)
     pub session_path: PathBuf,
+    /// Where the session token is persisted (default: file).
+    pub session_store: SessionStoreKind,
     /// API key for NEAR AI Cloud. When set, uses API key auth; otherwise uses session token auth.
     pub api_key: Option<SecretString>,
     /// Optional fallback model for failover (default: None).
@@ -156,6 +203,12 @@ pub struct NearAiConfig {
     pub circuit_breaker_threshold: Option<u32>,
     /// How long (seconds) the circuit stays open before allowing a probe (default: 30).
     pub circuit_breaker_recovery_secs: u64,
+    /// Trip the circuit breaker on error rate instead of consecutive
+    /// failures: the percentage of the last `circuit_breaker_window` calls
+    /// that may fail before it opens. None = consecutive mode (default).
+    pub circuit_breaker_error_rate: Option<f64>,
+    /// Calls in the error-rate window (default: 20).
+    pub circuit_breaker_window: u32,
     /// Enable in-memory response caching for `complete()` calls.
     /// Saves tokens on repeated prompts within a session. Default: false.
     pub response_cache_enabled: bool,
@@ -163,6 +216,10 @@ pub struct NearAiConfig {
     pub response_cache_ttl_secs: u64,
     /// Max cached responses before LRU eviction (default: 1000).
     pub response_cache_max_entries: usize,
+    /// Cosine similarity at which a cached response answers a differently
+    /// worded prompt. Needs embeddings to be enabled. None = exact matching
+    /// only (default).
+    pub response_cache_semantic_threshold: Option<f32>,
     /// Cooldown duration in seconds for the failover provider (default: 300).
     /// When a provider accumulates enough consecutive failures it is skipped
     /// for this many seconds.
@@ -220,6 +277,7 @@ impl LlmConfig {
             session_path: optional_env("NEARAI_SESSION_PATH")?
                 .map(PathBuf::from)
                 .unwrap_or_else(default_session_path),
+            session_store: parse_optional_env("NEARAI_SESSION_STORE", SessionStoreKind::default())?,
             api_key: nearai_api_key,
             fallback_model: optional_env("NEARAI_FALLBACK_MODEL")?,
             max_retries: parse_optional_env("NEARAI_MAX_RETRIES", 3)?,
@@ -231,9 +289,32 @@ impl LlmConfig {
                     message: format!("must be a positive integer: {e}"),
                 })?,
             circuit_breaker_recovery_secs: parse_optional_env("CIRCUIT_BREAKER_RECOVERY_SECS", 30)?,
+            circuit_breaker_error_rate: optional_env("CIRCUIT_BREAKER_ERROR_RATE")?
+                .map(|s| {
+                    s.parse::<f64>()
+                        .ok()
+                        .filter(|rate| *rate > 0.0 && *rate < 100.0)
+                        .ok_or_else(|| ConfigError::InvalidValue {
+                            key: "CIRCUIT_BREAKER_ERROR_RATE".to_string(),
+                            message: format!("must be a percentage between 0 and 100, got '{s}'"),
+                        })
+                })
+                .transpose()?,
+            circuit_breaker_window: parse_optional_env("CIRCUIT_BREAKER_WINDOW", 20)?,
             response_cache_enabled: parse_optional_env("RESPONSE_CACHE_ENABLED", false)?,
             response_cache_ttl_secs: parse_optional_env("RESPONSE_CACHE_TTL_SECS", 3600)?,
             response_cache_max_entries: parse_optional_env("RESPONSE_CACHE_MAX_ENTRIES", 1000)?,
+            response_cache_semantic_threshold: optional_env("RESPONSE_CACHE_SEMANTIC_THRESHOLD")?
+                .map(|s| {
+                    s.parse::<f32>()
+                        .ok()
+                        .filter(|t| *t > 0.0 && *t <= 1.0)
+                        .ok_or_else(|| ConfigError::InvalidValue {
+                            key: "RESPONSE_CACHE_SEMANTIC_THRESHOLD".to_string(),
+                            message: format!("must be a similarity in (0, 1], got '{s}'"),
+                        })
+                })
+                .transpose()?,
             failover_cooldown_secs: parse_optional_env("LLM_FAILOVER_COOLDOWN_SECS", 300)?,
             failover_cooldown_threshold: parse_optional_env("LLM_FAILOVER_THRESHOLD", 3)?,
             smart_routing_cascade: parse_optional_env("SMART_ROUTING_CASCADE", true)?,
@@ -325,6 +406,31 @@ impl LlmConfig {
             None
         };
 
+        let gemini = if backend == LlmBackend::Gemini {
+            let api_key = optional_env("GEMINI_API_KEY")?
+                .map(SecretString::from)
+                .ok_or_else(|| ConfigError::MissingRequired {
+                    key: "GEMINI_API_KEY".to_string(),
+                    hint: "Set GEMINI_API_KEY when LLM_BACKEND=gemini".to_string(),
+                })?;
+            let model =
+                optional_env("GEMINI_MODEL")?.unwrap_or_else(|| "gemini-2.5-flash".to_string());
+            Some(GeminiConfig { api_key, model })
+        } else {
+            None
+        };
+
+        let user_agent =
+            optional_env("LLM_USER_AGENT")?.unwrap_or_else(crate::llm::default_user_agent);
+
+        let proxy_url = optional_env("LLM_PROXY_URL")?;
+        if let Some(ref url) = proxy_url {
+            reqwest::Proxy::all(url).map_err(|e| ConfigError::InvalidValue {
+                key: "LLM_PROXY_URL".to_string(),
+                message: format!("invalid proxy URL: {e}"),
+            })?;
+        }
+
         Ok(Self {
             backend,
             nearai,
@@ -333,6 +439,9 @@ impl LlmConfig {
             ollama,
             openai_compatible,
             tinfoil,
+            gemini,
+            user_agent,
+            proxy_url,
         })
     }
 }
@@ -480,6 +589,35 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn llm_proxy_url_is_validated() {
+        let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+        clear_openai_compatible_env();
+
+        // SAFETY: Under ENV_MUTEX.
+        unsafe {
+            std::env::set_var("LLM_PROXY_URL", "socks5://127.0.0.1:1080");
+        }
+        let cfg = LlmConfig::resolve(&Settings::default()).expect("resolve should succeed");
+        assert_eq!(cfg.proxy_url.as_deref(), Some("socks5://127.0.0.1:1080"));
+
+        // SAFETY: Under ENV_MUTEX.
+        unsafe {
+            std::env::set_var("LLM_PROXY_URL", "http://[bad");
+        }
+        let err = LlmConfig::resolve(&Settings::default());
+        assert!(
+            matches!(err, Err(ConfigError::InvalidValue { ref key, .. }) if key == "LLM_PROXY_URL")
+        );
+
+        // SAFETY: Under ENV_MUTEX.
+        unsafe {
+            std::env::remove_var("LLM_PROXY_URL");
+        }
+        let cfg = LlmConfig::resolve(&Settings::default()).expect("resolve should succeed");
+        assert!(cfg.proxy_url.is_none());
+    }
+
     #[test]
     fn test_extra_headers_value_with_colons() {
         // Values can contain colons (e.g., URLs)
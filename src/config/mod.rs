@@ -31,17 +31,22 @@ use crate::settings::Settings;
 // Re-export all public types so `crate::config::FooConfig` continues to work.
 pub use self::agent::AgentConfig;
 pub use self::builder::BuilderModeConfig;
-pub use self::channels::{ChannelsConfig, CliConfig, GatewayConfig, HttpConfig, SignalConfig};
-pub use self::database::{DatabaseBackend, DatabaseConfig, default_libsql_path};
+pub use self::channels::{
+    BackpressureConfig, BroadcastThrottleConfig, ChannelsConfig, CliConfig, GatewayApiKey,
+    GatewayConfig, HttpConfig, OverflowPolicy, QuietHoursConfig, SignalConfig, StartupHealthCheck,
+};
+pub use self::database::{
+    DatabaseBackend, DatabaseConfig, DatabasePoolConfig, default_libsql_path,
+};
 pub use self::embeddings::EmbeddingsConfig;
 pub use self::heartbeat::HeartbeatConfig;
 pub use self::hygiene::HygieneConfig;
 pub use self::llm::{
-    AnthropicDirectConfig, LlmBackend, LlmConfig, NearAiConfig, OllamaConfig,
-    OpenAiCompatibleConfig, OpenAiDirectConfig, TinfoilConfig,
+    AnthropicDirectConfig, GeminiConfig, LlmBackend, LlmConfig, NearAiConfig, OllamaConfig,
+    OpenAiCompatibleConfig, OpenAiDirectConfig, SessionStoreKind, TinfoilConfig,
 };
 pub use self::routines::RoutineConfig;
-pub use self::safety::SafetyConfig;
+pub use self::safety::{OutboundCredentialMode, SafetyConfig};
 pub use self::sandbox::{ClaudeCodeConfig, SandboxModeConfig};
 pub use self::secrets::SecretsConfig;
 pub use self::skills::SkillsConfig;
@@ -182,7 +187,7 @@ impl Config {
     /// Build config from settings (shared by from_env and from_db).
     async fn build(settings: &Settings) -> Result<Self, ConfigError> {
         Ok(Self {
-            database: DatabaseConfig::resolve()?,
+            database: DatabaseConfig::resolve(settings)?,
             llm: LlmConfig::resolve(settings)?,
             embeddings: EmbeddingsConfig::resolve(settings)?,
             tunnel: TunnelConfig::resolve(settings)?,
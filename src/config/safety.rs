@@ -1,11 +1,27 @@
-use crate::config::helpers::{parse_bool_env, parse_optional_env};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::helpers::{optional_env, parse_bool_env, parse_optional_env};
 use crate::error::ConfigError;
 
 /// Safety configuration.
 #[derive(Debug, Clone)]
 pub struct SafetyConfig {
     pub max_output_length: usize,
+    /// Per-tool overrides of `max_output_length`, keyed by tool name.
+    pub tool_output_limits: HashMap<String, usize>,
     pub injection_check_enabled: bool,
+    /// JSON or TOML file of policy rules added to the built-in policy.
+    pub policy_file: Option<PathBuf>,
+    /// JSON file of leak patterns added to the built-in ones.
+    pub leak_patterns_file: Option<PathBuf>,
+    /// Abort startup if a policy or leak-pattern file fails to load, instead
+    /// of warning and running with the built-in defaults.
+    pub strict_init: bool,
+    /// What to do with credentials found in outgoing LLM requests.
+    pub outbound_credentials: OutboundCredentialMode,
+    /// Include the built-in AWS, GCP, Azure and GitHub leak patterns.
+    pub cloud_credential_patterns: bool,
     #[cfg(feature = "zkproxy")]
     pub zkproxy: crate::zkproxy::ZkProxyConfig,
 }
@@ -14,9 +30,128 @@ impl SafetyConfig {
     pub(crate) fn resolve() -> Result<Self, ConfigError> {
         Ok(Self {
             max_output_length: parse_optional_env("SAFETY_MAX_OUTPUT_LENGTH", 100_000)?,
+            tool_output_limits: optional_env("SAFETY_TOOL_OUTPUT_LIMITS")?
+                .map(|raw| parse_tool_output_limits(&raw))
+                .transpose()
+                .map_err(|message| ConfigError::InvalidValue {
+                    key: "SAFETY_TOOL_OUTPUT_LIMITS".to_string(),
+                    message,
+                })?
+                .unwrap_or_default(),
             injection_check_enabled: parse_bool_env("SAFETY_INJECTION_CHECK_ENABLED", true)?,
+            policy_file: optional_env("SAFETY_POLICY_FILE")?.map(PathBuf::from),
+            leak_patterns_file: optional_env("SAFETY_LEAK_PATTERNS_FILE")?.map(PathBuf::from),
+            strict_init: parse_bool_env("SAFETY_STRICT_INIT", false)?,
+            outbound_credentials: parse_optional_env(
+                "SAFETY_OUTBOUND_CREDENTIALS",
+                OutboundCredentialMode::default(),
+            )?,
+            cloud_credential_patterns: parse_bool_env("SAFETY_CLOUD_CREDENTIAL_PATTERNS", true)?,
             #[cfg(feature = "zkproxy")]
             zkproxy: crate::zkproxy::ZkProxyConfig::from_env(),
         })
     }
+
+    /// Output limit for `tool_name`, and whether it came from a per-tool override.
+    pub fn output_limit_for(&self, tool_name: &str) -> (usize, bool) {
+        match self.tool_output_limits.get(tool_name) {
+            Some(&limit) => (limit, true),
+            None => (self.max_output_length, false),
+        }
+    }
+}
+
+impl Default for SafetyConfig {
+    /// The values `resolve` uses when no environment variable is set.
+    fn default() -> Self {
+        Self {
+            max_output_length: 100_000,
+            tool_output_limits: HashMap::new(),
+            injection_check_enabled: true,
+            policy_file: None,
+            leak_patterns_file: None,
+            strict_init: false,
+            outbound_credentials: OutboundCredentialMode::default(),
+            cloud_credential_patterns: true,
+            #[cfg(feature = "zkproxy")]
+            zkproxy: crate::zkproxy::ZkProxyConfig::default(),
+        }
+    }
+}
+
+/// How credentials found in outgoing LLM requests are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutboundCredentialMode {
+    /// Don't scan requests.
+    Off,
+    /// Log each finding and send the request unchanged (default).
+    #[default]
+    Warn,
+    /// Replace each finding with `[REDACTED]` before sending.
+    Redact,
+}
+
+impl std::str::FromStr for OutboundCredentialMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "redact" => Ok(Self::Redact),
+            _ => Err(format!(
+                "invalid outbound credential mode '{}', expected 'off', 'warn', or 'redact'",
+                s
+            )),
+        }
+    }
+}
+
+/// Parse `tool:bytes` entries separated by commas, e.g.
+/// `fetch_url:500000,shell:20000`.
+pub(crate) fn parse_tool_output_limits(raw: &str) -> Result<HashMap<String, usize>, String> {
+    let mut limits = HashMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once(':').and_then(|(tool, bytes)| {
+            let tool = tool.trim();
+            let bytes = bytes.trim().parse::<usize>().ok()?;
+            (!tool.is_empty()).then(|| (tool.to_string(), bytes))
+        });
+        let Some((tool, bytes)) = parsed else {
+            return Err(format!("invalid entry '{}', expected tool:bytes", entry));
+        };
+        limits.insert(tool, bytes);
+    }
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tool_output_limits() {
+        let limits = parse_tool_output_limits("fetch_url:500000, shell : 20000,,").unwrap();
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits["fetch_url"], 500_000);
+        assert_eq!(limits["shell"], 20_000);
+
+        assert!(parse_tool_output_limits("").unwrap().is_empty());
+        assert!(parse_tool_output_limits("shell").is_err());
+        assert!(parse_tool_output_limits("shell:lots").is_err());
+        assert!(parse_tool_output_limits(":100").is_err());
+    }
+
+    #[test]
+    fn parses_outbound_credential_mode() {
+        assert_eq!(
+            "Redact".parse::<OutboundCredentialMode>().unwrap(),
+            OutboundCredentialMode::Redact
+        );
+        assert_eq!(
+            "off".parse::<OutboundCredentialMode>().unwrap(),
+            OutboundCredentialMode::Off
+        );
+        assert!("block".parse::<OutboundCredentialMode>().is_err());
+    }
 }
@@ -21,6 +21,12 @@ pub struct WasmConfig {
     pub cache_compiled: bool,
     /// Directory for compiled module cache.
     pub cache_dir: Option<PathBuf>,
+    /// Ed25519 public key for verifying signed extension downloads.
+    pub install_public_key: Option<[u8; 32]>,
+    /// Maximum size of a downloaded extension artifact in bytes (default: 50 MB).
+    pub install_max_download_bytes: u64,
+    /// Time allowed for an extension download in seconds (default: 60).
+    pub install_download_timeout_secs: u64,
 }
 
 impl Default for WasmConfig {
@@ -33,6 +39,9 @@ impl Default for WasmConfig {
             default_fuel_limit: 10_000_000,
             cache_compiled: true,
             cache_dir: None,
+            install_public_key: None,
+            install_max_download_bytes: 50 * 1024 * 1024,
+            install_download_timeout_secs: 60,
         }
     }
 }
@@ -60,9 +69,35 @@ impl WasmConfig {
             default_fuel_limit: parse_optional_env("WASM_DEFAULT_FUEL_LIMIT", 10_000_000)?,
             cache_compiled: parse_bool_env("WASM_CACHE_COMPILED", true)?,
             cache_dir: optional_env("WASM_CACHE_DIR")?.map(PathBuf::from),
+            install_public_key: optional_env("WASM_INSTALL_PUBLIC_KEY")?
+                .map(|key| {
+                    crate::extensions::integrity::parse_public_key(&key).map_err(|message| {
+                        ConfigError::InvalidValue {
+                            key: "WASM_INSTALL_PUBLIC_KEY".to_string(),
+                            message,
+                        }
+                    })
+                })
+                .transpose()?,
+            install_max_download_bytes: parse_optional_env(
+                "WASM_INSTALL_MAX_DOWNLOAD_BYTES",
+                50 * 1024 * 1024,
+            )?,
+            install_download_timeout_secs: parse_optional_env(
+                "WASM_INSTALL_DOWNLOAD_TIMEOUT_SECS",
+                60,
+            )?,
         })
     }
 
+    /// Limits applied to extension downloads.
+    pub fn download_limits(&self) -> crate::extensions::download::DownloadLimits {
+        crate::extensions::download::DownloadLimits {
+            max_bytes: self.install_max_download_bytes,
+            timeout: Duration::from_secs(self.install_download_timeout_secs),
+        }
+    }
+
     /// Convert to WasmRuntimeConfig.
     pub fn to_runtime_config(&self) -> crate::tools::wasm::WasmRuntimeConfig {
         use crate::tools::wasm::{FuelConfig, ResourceLimits, WasmRuntimeConfig};
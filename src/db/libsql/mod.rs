@@ -399,6 +399,33 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_migrations_are_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("migrate.db"))
+            .await
+            .unwrap();
+        backend.run_migrations().await.unwrap();
+
+        let conn = backend.connect().await.unwrap();
+        conn.execute(
+            "INSERT INTO conversations (id, channel, user_id) VALUES ('c1', 'cli', 'u1')",
+            (),
+        )
+        .await
+        .unwrap();
+
+        // Re-running must neither fail nor drop existing data.
+        backend.run_migrations().await.unwrap();
+        let mut rows = conn
+            .query("SELECT COUNT(*) FROM conversations", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        let count: i64 = row.get(0).unwrap();
+        assert_eq!(count, 1);
+    }
+
     #[tokio::test]
     async fn test_busy_timeout_set_on_connect() {
         let backend = LibSqlBackend::new_memory().await.unwrap();
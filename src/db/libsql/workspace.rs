@@ -604,4 +604,23 @@ impl WorkspaceStore for LibSqlBackend {
 
         Ok(reciprocal_rank_fusion(fts_results, vector_results, config))
     }
+
+    async fn rebuild_search_index(&self) -> Result<(), WorkspaceError> {
+        let conn = self
+            .connect()
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: e.to_string(),
+            })?;
+        // External-content FTS5 table: 'rebuild' repopulates it from memory_chunks.
+        conn.execute(
+            "INSERT INTO memory_chunks_fts(memory_chunks_fts) VALUES('rebuild')",
+            (),
+        )
+        .await
+        .map_err(|e| WorkspaceError::SearchFailed {
+            reason: format!("FTS rebuild failed: {}", e),
+        })?;
+        Ok(())
+    }
 }
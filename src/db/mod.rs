@@ -390,6 +390,11 @@ pub trait WorkspaceStore: Send + Sync {
         embedding: Option<&[f32]>,
         config: &SearchConfig,
     ) -> Result<Vec<SearchResult>, WorkspaceError>;
+    /// Rebuild the full-text index over `memory_chunks` from scratch.
+    ///
+    /// The index is normally kept current on every chunk insert/delete; this
+    /// is the recovery path when it is suspected to be out of sync.
+    async fn rebuild_search_index(&self) -> Result<(), WorkspaceError>;
 }
 
 /// Backend-agnostic database supertrait.
@@ -410,4 +415,7 @@ pub trait Database:
 {
     /// Run schema migrations for this backend.
     async fn run_migrations(&self) -> Result<(), DatabaseError>;
+
+    /// Release pooled connections at shutdown. Default: nothing to do.
+    async fn close(&self) {}
 }
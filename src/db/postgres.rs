@@ -7,13 +7,16 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use deadpool_postgres::Pool;
+use deadpool_postgres::{
+    Config, CreatePoolError, ManagerConfig, Pool, PoolConfig, RecyclingMethod, Runtime, Timeouts,
+};
 use rust_decimal::Decimal;
+use tokio_postgres::NoTls;
 use uuid::Uuid;
 
 use crate::agent::BrokenTool;
 use crate::agent::routine::{Routine, RoutineRun, RunStatus};
-use crate::config::DatabaseConfig;
+use crate::config::{DatabaseConfig, DatabasePoolConfig};
 use crate::context::{ActionRecord, JobContext, JobState};
 use crate::db::{
     ConversationStore, Database, JobStore, RoutineStore, SandboxStore, SettingsStore,
@@ -28,6 +31,65 @@ use crate::workspace::{
     MemoryChunk, MemoryDocument, Repository, SearchConfig, SearchResult, WorkspaceEntry,
 };
 
+/// Build a connection pool for `url` with the given sizing and health
+/// settings. Connections are opened lazily, on first use.
+pub fn create_pool(url: &str, config: &DatabasePoolConfig) -> Result<Pool, CreatePoolError> {
+    let mut cfg = Config::new();
+    cfg.url = Some(url.to_string());
+    cfg.manager = Some(ManagerConfig {
+        recycling_method: if config.health_check {
+            RecyclingMethod::Verified
+        } else {
+            RecyclingMethod::Fast
+        },
+    });
+    cfg.pool = Some(PoolConfig {
+        max_size: config.max_size,
+        timeouts: Timeouts {
+            wait: config.wait_timeout,
+            create: config.create_timeout,
+            recycle: config.recycle_timeout,
+        },
+        ..Default::default()
+    });
+    cfg.create_pool(Some(Runtime::Tokio1), NoTls)
+}
+
+/// Point-in-time usage of a connection pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct PoolMetrics {
+    /// Configured maximum number of connections.
+    pub max_size: usize,
+    /// Connections checked out by callers.
+    pub in_use: usize,
+    /// Open connections sitting idle in the pool.
+    pub idle: usize,
+    /// Callers waiting for a connection.
+    pub waiters: usize,
+}
+
+impl PoolMetrics {
+    pub fn from_pool(pool: &Pool) -> Self {
+        let status = pool.status();
+        Self {
+            max_size: status.max_size,
+            in_use: status.size.saturating_sub(status.available),
+            idle: status.available,
+            waiters: status.waiting,
+        }
+    }
+
+    /// Publish these numbers as Prometheus gauges.
+    pub fn record(&self) {
+        crate::observability::prometheus::record_db_pool(
+            self.in_use,
+            self.idle,
+            self.waiters,
+            self.max_size,
+        );
+    }
+}
+
 /// PostgreSQL database backend.
 ///
 /// Wraps the existing `Store` (for history/conversations/jobs/routines/settings)
@@ -52,6 +114,11 @@ impl PgBackend {
     pub fn pool(&self) -> Pool {
         self.store.pool()
     }
+
+    /// Current connection-pool usage.
+    pub fn pool_metrics(&self) -> PoolMetrics {
+        PoolMetrics::from_pool(&self.store.pool())
+    }
 }
 
 // ==================== Database (supertrait) ====================
@@ -61,6 +128,10 @@ impl Database for PgBackend {
     async fn run_migrations(&self) -> Result<(), DatabaseError> {
         self.store.run_migrations().await
     }
+
+    async fn close(&self) {
+        self.store.pool().close();
+    }
 }
 
 // ==================== ConversationStore ====================
@@ -648,4 +719,64 @@ impl WorkspaceStore for PgBackend {
             .hybrid_search(user_id, agent_id, query, embedding, config)
             .await
     }
+
+    async fn rebuild_search_index(&self) -> Result<(), WorkspaceError> {
+        self.repo.rebuild_search_index().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_url() -> String {
+        std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://localhost/ironclaw_test".to_string())
+    }
+
+    #[test]
+    fn configured_pool_size_is_honored() {
+        let config = DatabasePoolConfig {
+            max_size: 3,
+            ..Default::default()
+        };
+        let pool = create_pool("postgres://localhost/unused", &config).unwrap();
+        let metrics = PoolMetrics::from_pool(&pool);
+        assert_eq!(metrics.max_size, 3);
+        assert_eq!(metrics.in_use, 0);
+        assert_eq!(metrics.idle, 0);
+        assert_eq!(metrics.waiters, 0);
+    }
+
+    #[tokio::test]
+    async fn metrics_reflect_acquired_connections() {
+        let config = DatabasePoolConfig {
+            max_size: 2,
+            health_check: true,
+            ..Default::default()
+        };
+        let pool = create_pool(&test_url(), &config).unwrap();
+        let first = match pool.get().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                eprintln!("skipping: database unavailable ({e})");
+                return;
+            }
+        };
+        let second = pool.get().await.unwrap();
+
+        let metrics = PoolMetrics::from_pool(&pool);
+        assert_eq!(metrics.in_use, 2);
+        assert_eq!(metrics.idle, 0);
+
+        drop(first);
+        let metrics = PoolMetrics::from_pool(&pool);
+        assert_eq!(metrics.in_use, 1);
+        assert_eq!(metrics.idle, 1);
+
+        drop(second);
+        let metrics = PoolMetrics::from_pool(&pool);
+        assert_eq!(metrics.in_use, 0);
+        assert_eq!(metrics.idle, 2);
+    }
 }
@@ -0,0 +1,303 @@
+//! Streaming downloads for extension artifacts.
+//!
+//! Installing a WASM tool or channel from a URL can take a while on a slow
+//! link. The body is read chunk by chunk so progress can be reported as it
+//! arrives, and the size cap and timeout are enforced on the stream itself
+//! rather than after the whole body has been buffered.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::extensions::ExtensionError;
+
+/// Default cap on a downloaded artifact (50 MB).
+pub const DEFAULT_MAX_DOWNLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Default time allowed for a whole download.
+pub const DEFAULT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Minimum number of new bytes between two progress reports.
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+/// Size and time limits applied to every extension download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadLimits {
+    pub max_bytes: u64,
+    pub timeout: Duration,
+}
+
+impl Default for DownloadLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_DOWNLOAD_BYTES,
+            timeout: DEFAULT_DOWNLOAD_TIMEOUT,
+        }
+    }
+}
+
+/// Progress of an in-flight extension download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Extension being installed.
+    pub name: String,
+    /// Bytes received so far.
+    pub downloaded: u64,
+    /// Expected size, when the server sent a Content-Length.
+    pub total: Option<u64>,
+}
+
+impl std::fmt::Display for DownloadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.total {
+            Some(total) if total > 0 => write!(
+                f,
+                "Downloading {}: {} of {} ({}%)",
+                self.name,
+                format_bytes(self.downloaded),
+                format_bytes(total),
+                self.downloaded.min(total) * 100 / total
+            ),
+            _ => write!(
+                f,
+                "Downloading {}: {}",
+                self.name,
+                format_bytes(self.downloaded)
+            ),
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+/// A response body that can be read incrementally.
+#[async_trait]
+pub trait DownloadSource: Send {
+    /// Size announced by the server, if any.
+    fn content_length(&self) -> Option<u64>;
+
+    /// Next chunk of the body, or `None` once it's complete.
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, ExtensionError>;
+}
+
+#[async_trait]
+impl DownloadSource for reqwest::Response {
+    fn content_length(&self) -> Option<u64> {
+        reqwest::Response::content_length(self)
+    }
+
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, ExtensionError> {
+        self.chunk()
+            .await
+            .map_err(|e| ExtensionError::DownloadFailed(e.to_string()))
+    }
+}
+
+/// Read `source` to the end, reporting progress and enforcing `limits`.
+///
+/// Progress is reported once up front, then at most every [`PROGRESS_STEP`]
+/// bytes, and once more when the body is complete.
+pub async fn read_with_progress<S, F>(
+    name: &str,
+    mut source: S,
+    limits: DownloadLimits,
+    on_progress: F,
+) -> Result<Vec<u8>, ExtensionError>
+where
+    S: DownloadSource,
+    F: Fn(DownloadProgress) + Send + Sync,
+{
+    let total = source.content_length();
+    if let Some(len) = total
+        && len > limits.max_bytes
+    {
+        return Err(too_large(len, limits.max_bytes));
+    }
+
+    let read = async {
+        let progress = |downloaded| DownloadProgress {
+            name: name.to_string(),
+            downloaded,
+            total,
+        };
+
+        let capacity = total.unwrap_or(0).min(limits.max_bytes);
+        let mut body = Vec::with_capacity(usize::try_from(capacity).unwrap_or(0));
+        let mut reported = 0u64;
+        on_progress(progress(0));
+
+        while let Some(chunk) = source.next_chunk().await? {
+            let downloaded = (body.len() + chunk.len()) as u64;
+            if downloaded > limits.max_bytes {
+                return Err(too_large(downloaded, limits.max_bytes));
+            }
+            body.extend_from_slice(&chunk);
+            if downloaded - reported >= PROGRESS_STEP {
+                reported = downloaded;
+                on_progress(progress(downloaded));
+            }
+        }
+
+        let downloaded = body.len() as u64;
+        if downloaded != reported {
+            on_progress(progress(downloaded));
+        }
+        Ok(body)
+    };
+
+    tokio::time::timeout(limits.timeout, read)
+        .await
+        .map_err(|_| {
+            ExtensionError::DownloadFailed(format!(
+                "download of '{}' timed out after {}s",
+                name,
+                limits.timeout.as_secs()
+            ))
+        })?
+}
+
+fn too_large(size: u64, max: u64) -> ExtensionError {
+    ExtensionError::InstallFailed(format!(
+        "Download too large ({} bytes, max {} bytes)",
+        size, max
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// In-memory body served in fixed chunks, optionally stalling forever.
+    struct MockSource {
+        chunks: VecDeque<Bytes>,
+        content_length: Option<u64>,
+        stall: bool,
+    }
+
+    impl MockSource {
+        fn new(size: usize, chunk_size: usize, announce_length: bool) -> Self {
+            let body = vec![7u8; size];
+            Self {
+                chunks: body
+                    .chunks(chunk_size)
+                    .map(Bytes::copy_from_slice)
+                    .collect(),
+                content_length: announce_length.then_some(size as u64),
+                stall: false,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl DownloadSource for MockSource {
+        fn content_length(&self) -> Option<u64> {
+            self.content_length
+        }
+
+        async fn next_chunk(&mut self) -> Result<Option<Bytes>, ExtensionError> {
+            if self.stall {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+            }
+            Ok(self.chunks.pop_front())
+        }
+    }
+
+    async fn download(
+        source: MockSource,
+        limits: DownloadLimits,
+    ) -> (Result<Vec<u8>, ExtensionError>, Vec<DownloadProgress>) {
+        let events = Mutex::new(Vec::new());
+        let result = read_with_progress("demo", source, limits, |p| {
+            events.lock().unwrap().push(p);
+        })
+        .await;
+        (result, events.into_inner().unwrap())
+    }
+
+    #[tokio::test]
+    async fn reports_progress_up_to_the_full_size() {
+        let size = 1024 * 1024;
+        let (result, events) = download(
+            MockSource::new(size, 64 * 1024, true),
+            DownloadLimits::default(),
+        )
+        .await;
+
+        assert_eq!(result.unwrap().len(), size);
+        // Start, one report per 256 KB, and no duplicate at the end.
+        let downloaded: Vec<u64> = events.iter().map(|p| p.downloaded).collect();
+        assert_eq!(downloaded, vec![0, 262_144, 524_288, 786_432, 1_048_576]);
+        assert!(events.iter().all(|p| p.total == Some(size as u64)));
+        assert_eq!(
+            events.last().unwrap().to_string(),
+            "Downloading demo: 1.0 MB of 1.0 MB (100%)"
+        );
+    }
+
+    #[tokio::test]
+    async fn small_body_without_length_reports_start_and_end() {
+        let (result, events) = download(
+            MockSource::new(3000, 1000, false),
+            DownloadLimits::default(),
+        )
+        .await;
+
+        assert_eq!(result.unwrap().len(), 3000);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].downloaded, 3000);
+        assert_eq!(events[1].total, None);
+        assert_eq!(events[1].to_string(), "Downloading demo: 3 KB");
+    }
+
+    #[tokio::test]
+    async fn announced_length_over_limit_is_rejected_before_reading() {
+        let limits = DownloadLimits {
+            max_bytes: 1000,
+            ..Default::default()
+        };
+        let (result, events) = download(MockSource::new(2000, 500, true), limits).await;
+
+        assert!(matches!(result, Err(ExtensionError::InstallFailed(_))));
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn streamed_body_over_limit_is_rejected() {
+        // No Content-Length: the cap has to be enforced while streaming.
+        let limits = DownloadLimits {
+            max_bytes: 1000,
+            ..Default::default()
+        };
+        let (result, events) = download(MockSource::new(2000, 400, false), limits).await;
+
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("max 1000 bytes"), "{err}");
+        assert!(events.iter().all(|p| p.downloaded <= 1000));
+    }
+
+    #[tokio::test]
+    async fn stalled_download_times_out() {
+        let mut source = MockSource::new(100, 10, true);
+        source.stall = true;
+        let limits = DownloadLimits {
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let (result, _) = download(source, limits).await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, ExtensionError::DownloadFailed(_)));
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+}
@@ -0,0 +1,144 @@
+//! Integrity verification for downloaded extension artifacts.
+//!
+//! A caller installing from a URL can pin the artifact by SHA-256, sign it
+//! with the operator's Ed25519 key (`WASM_INSTALL_PUBLIC_KEY`), or both.
+//! Verification runs on the downloaded bytes before anything is written to
+//! the tools/channels directory, so a mismatched artifact is never
+//! discoverable and can't be activated.
+
+use sha2::{Digest, Sha256};
+
+use crate::extensions::ExtensionError;
+
+/// Length of a raw Ed25519 public key.
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Expected checksum and/or signature for a downloaded artifact.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArtifactIntegrity {
+    /// Hex-encoded SHA-256 of the artifact, optionally prefixed with `sha256:`.
+    pub sha256: Option<String>,
+    /// Hex-encoded Ed25519 signature over the artifact bytes.
+    pub signature: Option<String>,
+}
+
+impl ArtifactIntegrity {
+    pub fn new(sha256: Option<String>, signature: Option<String>) -> Self {
+        Self { sha256, signature }
+    }
+
+    /// Whether any check was requested.
+    pub fn is_empty(&self) -> bool {
+        self.sha256.is_none() && self.signature.is_none()
+    }
+
+    /// Check `bytes` against every requested constraint.
+    ///
+    /// A signature can only be checked when a public key is configured; one
+    /// supplied without a key is rejected rather than silently ignored.
+    pub fn verify(
+        &self,
+        bytes: &[u8],
+        public_key: Option<&[u8; PUBLIC_KEY_LEN]>,
+    ) -> Result<(), ExtensionError> {
+        if let Some(ref expected) = self.sha256 {
+            let expected = expected.trim().to_ascii_lowercase();
+            let expected = expected.strip_prefix("sha256:").unwrap_or(&expected);
+            let actual = hex::encode(Sha256::digest(bytes));
+            if actual != expected {
+                return Err(ExtensionError::IntegrityFailed(format!(
+                    "sha256 mismatch: expected {}, got {}",
+                    expected, actual
+                )));
+            }
+        }
+
+        if let Some(ref signature) = self.signature {
+            let Some(public_key) = public_key else {
+                return Err(ExtensionError::IntegrityFailed(
+                    "signature provided but no install public key is configured \
+                     (set WASM_INSTALL_PUBLIC_KEY)"
+                        .to_string(),
+                ));
+            };
+            let signature = hex::decode(signature.trim()).map_err(|e| {
+                ExtensionError::IntegrityFailed(format!("signature is not valid hex: {}", e))
+            })?;
+            ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+                .verify(bytes, &signature)
+                .map_err(|_| {
+                    ExtensionError::IntegrityFailed(
+                        "signature does not match the configured public key".to_string(),
+                    )
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a hex-encoded Ed25519 public key.
+pub fn parse_public_key(hex_key: &str) -> Result<[u8; PUBLIC_KEY_LEN], String> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|e| format!("not valid hex: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("expected {} bytes, got {}", PUBLIC_KEY_LEN, b.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use super::*;
+
+    const ARTIFACT: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn keypair() -> (Ed25519KeyPair, [u8; PUBLIC_KEY_LEN]) {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&[7u8; 32]).unwrap();
+        let public: [u8; PUBLIC_KEY_LEN] = pair.public_key().as_ref().try_into().unwrap();
+        (pair, public)
+    }
+
+    #[test]
+    fn empty_integrity_accepts_anything() {
+        assert!(ArtifactIntegrity::default().is_empty());
+        assert!(ArtifactIntegrity::default().verify(ARTIFACT, None).is_ok());
+    }
+
+    #[test]
+    fn sha256_must_match() {
+        let digest = hex::encode(Sha256::digest(ARTIFACT));
+        let ok = ArtifactIntegrity::new(Some(format!("sha256:{}", digest.to_uppercase())), None);
+        assert!(ok.verify(ARTIFACT, None).is_ok());
+
+        let bad = ArtifactIntegrity::new(Some("00".repeat(32)), None);
+        assert!(matches!(
+            bad.verify(ARTIFACT, None),
+            Err(ExtensionError::IntegrityFailed(_))
+        ));
+    }
+
+    #[test]
+    fn signature_is_checked_against_configured_key() {
+        let (pair, public) = keypair();
+        let signature = hex::encode(pair.sign(ARTIFACT).as_ref());
+        let integrity = ArtifactIntegrity::new(None, Some(signature));
+
+        assert!(integrity.verify(ARTIFACT, Some(&public)).is_ok());
+        assert!(integrity.verify(b"tampered", Some(&public)).is_err());
+        // No key configured: refuse rather than skip the check.
+        assert!(integrity.verify(ARTIFACT, None).is_err());
+
+        let other = Ed25519KeyPair::from_seed_unchecked(&[9u8; 32]).unwrap();
+        let other_public: [u8; PUBLIC_KEY_LEN] = other.public_key().as_ref().try_into().unwrap();
+        assert!(integrity.verify(ARTIFACT, Some(&other_public)).is_err());
+    }
+
+    #[test]
+    fn parse_public_key_requires_32_bytes() {
+        let (_, public) = keypair();
+        assert_eq!(parse_public_key(&hex::encode(public)).unwrap(), public);
+        assert!(parse_public_key("abcd").is_err());
+        assert!(parse_public_key("not hex").is_err());
+    }
+}
@@ -15,10 +15,13 @@ use crate::channels::wasm::{
     RegisteredEndpoint, SharedWasmChannel, WasmChannelLoader, WasmChannelRouter, WasmChannelRuntime,
 };
 use crate::extensions::discovery::OnlineDiscovery;
+use crate::extensions::download::{DownloadLimits, DownloadProgress};
+use crate::extensions::integrity::PUBLIC_KEY_LEN;
 use crate::extensions::registry::ExtensionRegistry;
 use crate::extensions::{
-    ActivateResult, AuthResult, ExtensionError, ExtensionKind, ExtensionSource, InstallResult,
-    InstalledExtension, RegistryEntry, ResultSource, SearchResult,
+    ActivateResult, AuthResult, ExtensionError, ExtensionKind, ExtensionSource, InstallOptions,
+    InstallResult, InstalledExtension, ManifestDiff, ManifestGrants, RegistryEntry, ResultSource,
+    SearchResult,
 };
 use crate::hooks::HookRegistry;
 use crate::pairing::PairingStore;
@@ -82,6 +85,12 @@ pub struct ExtensionManager {
     store: Option<Arc<dyn crate::db::Database>>,
     /// Names of WASM channels that were successfully loaded at startup.
     active_channel_names: RwLock<HashSet<String>>,
+    /// Ed25519 key that signed artifacts are verified against.
+    install_public_key: Option<[u8; PUBLIC_KEY_LEN]>,
+    /// Size and time limits for downloaded artifacts.
+    download_limits: DownloadLimits,
+    /// Progress of in-flight downloads, for status display.
+    download_progress_tx: tokio::sync::broadcast::Sender<DownloadProgress>,
 }
 
 impl ExtensionManager {
@@ -121,9 +130,33 @@ impl ExtensionManager {
             user_id,
             store,
             active_channel_names: RwLock::new(HashSet::new()),
+            install_public_key: None,
+            download_limits: DownloadLimits::default(),
+            download_progress_tx: tokio::sync::broadcast::channel(64).0,
         }
     }
 
+    /// Set the Ed25519 public key used to verify signed extension downloads.
+    pub fn with_install_public_key(mut self, key: [u8; PUBLIC_KEY_LEN]) -> Self {
+        self.install_public_key = Some(key);
+        self
+    }
+
+    /// Override the size cap and timeout for extension downloads.
+    pub fn with_download_limits(mut self, limits: DownloadLimits) -> Self {
+        self.download_limits = limits;
+        self
+    }
+
+    /// Subscribe to progress updates for extension downloads.
+    ///
+    /// Events are dropped when nobody is subscribed or a receiver lags.
+    pub fn subscribe_download_progress(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<DownloadProgress> {
+        self.download_progress_tx.subscribe()
+    }
+
     /// Configure the channel runtime infrastructure for hot-activating WASM channels.
     ///
     /// Call after construction (and after wrapping in `Arc`) once the channel
@@ -189,13 +222,14 @@ impl ExtensionManager {
         name: &str,
         url: Option<&str>,
         kind_hint: Option<ExtensionKind>,
+        options: &InstallOptions,
     ) -> Result<InstallResult, ExtensionError> {
         tracing::info!(extension = %name, url = ?url, kind = ?kind_hint, "Installing extension");
         Self::validate_extension_name(name)?;
 
         // If we have a registry entry, use it (prefer kind_hint to resolve collisions)
         if let Some(entry) = self.registry.get_with_kind(name, kind_hint).await {
-            return self.install_from_entry(&entry).await.map_err(|e| {
+            return self.install_from_entry(&entry, options).await.map_err(|e| {
                 tracing::error!(extension = %name, error = %e, "Extension install failed");
                 e
             });
@@ -205,10 +239,17 @@ impl ExtensionManager {
         if let Some(url) = url {
             let kind = kind_hint.unwrap_or_else(|| infer_kind_from_url(url));
             return match kind {
-                ExtensionKind::McpServer => self.install_mcp_from_url(name, url).await,
-                ExtensionKind::WasmTool => self.install_wasm_tool_from_url(name, url).await,
+                ExtensionKind::McpServer => {
+                    Self::reject_integrity_for_mcp(options)?;
+                    self.install_mcp_from_url(name, url).await
+                }
+                ExtensionKind::WasmTool => {
+                    self.install_wasm_tool_from_url(name, url, options)
+                        .await
+                }
                 ExtensionKind::WasmChannel => {
-                    self.install_wasm_channel_from_url(name, url, None).await
+                    self.install_wasm_channel_from_url(name, url, None, options)
+                        .await
                 }
             }
             .map_err(|e| {
@@ -316,13 +357,14 @@ impl ExtensionManager {
                 Ok(tools) => {
                     for (name, _discovered) in tools {
                         let active = self.tool_registry.has(&name).await;
+                        let authenticated = self.check_tool_auth_status(&name).await;
 
                         extensions.push(InstalledExtension {
                             name: name.clone(),
                             kind: ExtensionKind::WasmTool,
                             description: None,
                             url: None,
-                            authenticated: true, // WASM tools don't always need auth
+                            authenticated,
                             active,
                             tools: if active { vec![name] } else { Vec::new() },
                             needs_setup: false,
@@ -493,6 +535,67 @@ impl ExtensionManager {
         }
     }
 
+    /// Revoke an extension's stored credentials without uninstalling it.
+    ///
+    /// Deletes every secret the extension authenticates with, so it reports
+    /// `authenticated: false` until the user runs auth again. Active MCP
+    /// servers are also disconnected because their client holds the old token.
+    pub async fn revoke(&self, name: &str) -> Result<String, ExtensionError> {
+        Self::validate_extension_name(name)?;
+        let kind = self.determine_installed_kind(name).await?;
+
+        let secret_names = match kind {
+            ExtensionKind::McpServer => {
+                let server = self
+                    .get_mcp_server(name)
+                    .await
+                    .map_err(|e| ExtensionError::Config(e.to_string()))?;
+
+                let tool_names: Vec<String> = self
+                    .tool_registry
+                    .list()
+                    .await
+                    .into_iter()
+                    .filter(|t| t.starts_with(&format!("{}_", name)))
+                    .collect();
+                for tool_name in &tool_names {
+                    self.tool_registry.unregister(tool_name).await;
+                }
+                self.mcp_clients.write().await.remove(name);
+
+                vec![
+                    server.token_secret_name(),
+                    server.refresh_token_secret_name(),
+                    server.client_id_secret_name(),
+                ]
+            }
+            ExtensionKind::WasmTool => self.wasm_tool_auth_secret(name).await.into_iter().collect(),
+            ExtensionKind::WasmChannel => self.wasm_channel_secret_names(name).await,
+        };
+
+        let mut revoked = 0;
+        for secret_name in &secret_names {
+            if self
+                .secrets
+                .delete(&self.user_id, secret_name)
+                .await
+                .map_err(|e| ExtensionError::Other(format!("Failed to delete secret: {}", e)))?
+            {
+                revoked += 1;
+            }
+        }
+
+        tracing::info!(extension = name, revoked, "Revoked extension credentials");
+
+        Ok(match kind {
+            ExtensionKind::WasmChannel => format!(
+                "Revoked {} credential(s) for channel '{}'. Restart IronClaw to disconnect the running channel.",
+                revoked, name
+            ),
+            _ => format!("Revoked {} credential(s) for '{}'", revoked, name),
+        })
+    }
+
     // ── MCP config helpers (DB with disk fallback) ─────────────────────
 
     async fn load_mcp_servers(
@@ -548,8 +651,11 @@ impl ExtensionManager {
     async fn install_from_entry(
         &self,
         entry: &RegistryEntry,
+        options: &InstallOptions,
     ) -> Result<InstallResult, ExtensionError> {
-        let primary_result = self.try_install_from_source(entry, &entry.source).await;
+        let primary_result = self
+            .try_install_from_source(entry, &entry.source, options)
+            .await;
         match fallback_decision(&primary_result, &entry.fallback_source) {
             FallbackDecision::Return => primary_result,
             FallbackDecision::TryFallback => {
@@ -560,7 +666,7 @@ impl ExtensionManager {
                     primary_error = %primary_err,
                     "Primary install failed, trying fallback source"
                 );
-                self.try_install_from_source(entry, fallback)
+                self.try_install_from_source(entry, fallback, options)
                     .await
                     .map_err(|fallback_err| {
                         tracing::error!(
@@ -579,7 +685,19 @@ impl ExtensionManager {
         &self,
         entry: &RegistryEntry,
         source: &ExtensionSource,
+        options: &InstallOptions,
     ) -> Result<InstallResult, ExtensionError> {
+        // Checksums and signatures describe a downloaded artifact; a source
+        // build or MCP URL has nothing to check them against.
+        let pinned = !options.integrity.is_empty() || !options.capabilities_integrity.is_empty();
+        if pinned && !matches!(source, ExtensionSource::WasmDownload { .. }) {
+            return Err(ExtensionError::IntegrityFailed(format!(
+                "'{}' is not installed from a downloaded artifact, so a checksum or signature \
+                 cannot be verified",
+                entry.name
+            )));
+        }
+
         match entry.kind {
             ExtensionKind::McpServer => {
                 let url = match source {
@@ -602,6 +720,7 @@ impl ExtensionManager {
                         &entry.name,
                         wasm_url,
                         capabilities_url.as_deref(),
+                        options,
                     )
                     .await
                 }
@@ -632,6 +751,7 @@ impl ExtensionManager {
                         &entry.name,
                         wasm_url,
                         capabilities_url.as_deref(),
+                        options,
                     )
                     .await
                 }
@@ -687,12 +807,23 @@ impl ExtensionManager {
         })
     }
 
+    fn reject_integrity_for_mcp(options: &InstallOptions) -> Result<(), ExtensionError> {
+        if options.integrity.is_empty() && options.capabilities_integrity.is_empty() {
+            return Ok(());
+        }
+        Err(ExtensionError::IntegrityFailed(
+            "MCP servers are not downloaded, so a checksum or signature cannot be verified"
+                .to_string(),
+        ))
+    }
+
     async fn install_wasm_tool_from_url(
         &self,
         name: &str,
         url: &str,
+        options: &InstallOptions,
     ) -> Result<InstallResult, ExtensionError> {
-        self.install_wasm_tool_from_url_with_caps(name, url, None)
+        self.install_wasm_tool_from_url_with_caps(name, url, None, options)
             .await
     }
 
@@ -701,9 +832,17 @@ impl ExtensionManager {
         name: &str,
         url: &str,
         capabilities_url: Option<&str>,
+        options: &InstallOptions,
     ) -> Result<InstallResult, ExtensionError> {
-        self.download_and_install_wasm(name, url, capabilities_url, &self.wasm_tools_dir)
-            .await?;
+        self.download_and_install_wasm(
+            name,
+            url,
+            capabilities_url,
+            &self.wasm_tools_dir,
+            ExtensionKind::WasmTool,
+            options,
+        )
+        .await?;
 
         Ok(InstallResult {
             name: name.to_string(),
@@ -717,9 +856,17 @@ impl ExtensionManager {
         name: &str,
         url: &str,
         capabilities_url: Option<&str>,
+        options: &InstallOptions,
     ) -> Result<InstallResult, ExtensionError> {
-        self.download_and_install_wasm(name, url, capabilities_url, &self.wasm_channels_dir)
-            .await?;
+        self.download_and_install_wasm(
+            name,
+            url,
+            capabilities_url,
+            &self.wasm_channels_dir,
+            ExtensionKind::WasmChannel,
+            options,
+        )
+        .await?;
 
         Ok(InstallResult {
             name: name.to_string(),
@@ -734,13 +881,15 @@ impl ExtensionManager {
     /// Download a WASM extension (tool or channel) from URL and install to target directory.
     ///
     /// Handles both tar.gz bundles (containing `.wasm` + `.capabilities.json`) and bare
-    /// `.wasm` files. Validates HTTPS, size limits, and file format.
+    /// `.wasm` files. Validates HTTPS, size limits, integrity, and file format.
     async fn download_and_install_wasm(
         &self,
         name: &str,
         url: &str,
         capabilities_url: Option<&str>,
         target_dir: &std::path::Path,
+        kind: ExtensionKind,
+        options: &InstallOptions,
     ) -> Result<(), ExtensionError> {
         // Require HTTPS to prevent downgrade attacks
         if !url.starts_with("https://") {
@@ -749,11 +898,8 @@ impl ExtensionManager {
             ));
         }
 
-        // 50 MB cap to prevent disk-fill DoS
-        const MAX_DOWNLOAD_SIZE: usize = 50 * 1024 * 1024;
-
         let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
+            .timeout(self.download_limits.timeout)
             .build()
             .map_err(|e| ExtensionError::DownloadFailed(e.to_string()))?;
 
@@ -778,41 +924,147 @@ impl ExtensionManager {
             )));
         }
 
-        // Check Content-Length header before downloading the full body
-        if let Some(len) = response.content_length()
-            && len as usize > MAX_DOWNLOAD_SIZE
-        {
-            return Err(ExtensionError::InstallFailed(format!(
-                "Download too large ({} bytes, max {} bytes)",
-                len, MAX_DOWNLOAD_SIZE
-            )));
-        }
+        // Stream the body so progress is visible and the size cap applies
+        // before the whole artifact is buffered.
+        let bytes = crate::extensions::download::read_with_progress(
+            name,
+            response,
+            self.download_limits,
+            |progress| {
+                let _ = self.download_progress_tx.send(progress);
+            },
+        )
+        .await?;
+
+        // A tar.gz bundle carries its own capabilities file; for a bare
+        // module, fetch the separate one now so it can be compared against
+        // the installed manifest before anything is overwritten.
+        let caps_bytes = match capabilities_url {
+            Some(caps_url) if !is_gzip(&bytes) => {
+                Self::download_capabilities(&client, name, caps_url).await
+            }
+            _ => None,
+        };
 
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| ExtensionError::DownloadFailed(e.to_string()))?;
+        self.install_wasm_bytes(
+            name,
+            &bytes,
+            caps_bytes.as_deref(),
+            target_dir,
+            kind,
+            options,
+        )
+        .await?;
 
-        if bytes.len() > MAX_DOWNLOAD_SIZE {
-            return Err(ExtensionError::InstallFailed(format!(
-                "Download too large ({} bytes, max {} bytes)",
-                bytes.len(),
-                MAX_DOWNLOAD_SIZE
-            )));
+        tracing::info!(
+            "Installed WASM extension '{}' from {} to {}",
+            name,
+            url,
+            target_dir.display()
+        );
+
+        Ok(())
+    }
+
+    /// Fetch a separately hosted capabilities file. Failures are logged and
+    /// the module is installed without one, unless the capabilities file is
+    /// pinned, in which case [`Self::install_wasm_bytes`] rejects the install.
+    async fn download_capabilities(
+        client: &reqwest::Client,
+        name: &str,
+        caps_url: &str,
+    ) -> Option<Vec<u8>> {
+        const MAX_CAPS_SIZE: usize = 1024 * 1024; // 1 MB
+        match client.get(caps_url).send().await {
+            Ok(resp) if resp.status().is_success() => match resp.bytes().await {
+                Ok(caps_bytes) if caps_bytes.len() <= MAX_CAPS_SIZE => Some(caps_bytes.to_vec()),
+                Ok(caps_bytes) => {
+                    tracing::warn!(
+                        "Capabilities file for '{}' too large ({} bytes, max {})",
+                        name,
+                        caps_bytes.len(),
+                        MAX_CAPS_SIZE
+                    );
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to download capabilities for '{}': {}", name, e);
+                    None
+                }
+            },
+            _ => {
+                tracing::warn!(
+                    "Failed to download capabilities for '{}' from {}",
+                    name,
+                    caps_url
+                );
+                None
+            }
         }
+    }
 
-        // Ensure target directory exists
-        tokio::fs::create_dir_all(target_dir)
-            .await
-            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+    /// Check a separately downloaded capabilities file against its pin.
+    ///
+    /// A tar.gz bundle's capabilities are covered by the bundle's own
+    /// checksum; a bare module's are not, so pinning only the module would
+    /// leave its grants unverified.
+    fn verify_capabilities(
+        name: &str,
+        caps_bytes: Option<&[u8]>,
+        options: &InstallOptions,
+        public_key: Option<&[u8; PUBLIC_KEY_LEN]>,
+    ) -> Result<(), ExtensionError> {
+        let result = match caps_bytes {
+            Some(caps) if !options.capabilities_integrity.is_empty() => {
+                options.capabilities_integrity.verify(caps, public_key)
+            }
+            Some(_) if !options.integrity.is_empty() => Err(ExtensionError::IntegrityFailed(
+                "the module is pinned but its separately downloaded capabilities file is not; \
+                 supply capabilities_sha256 or capabilities_signature"
+                    .to_string(),
+            )),
+            None if !options.capabilities_integrity.is_empty() => {
+                Err(ExtensionError::IntegrityFailed(
+                    "a capabilities checksum or signature was given but no capabilities file \
+                     was downloaded"
+                        .to_string(),
+                ))
+            }
+            _ => Ok(()),
+        };
+        result.inspect_err(|e| {
+            tracing::error!(extension = %name, error = %e, "Rejected extension capabilities");
+        })
+    }
 
-        let wasm_path = target_dir.join(format!("{}.wasm", name));
-        let caps_path = target_dir.join(format!("{}.capabilities.json", name));
+    /// Verify downloaded bytes and write them into `target_dir`.
+    ///
+    /// Integrity is checked before anything touches disk, so a rejected
+    /// artifact never becomes discoverable or activatable. When this replaces
+    /// an installed extension, the new capabilities file is compared against
+    /// the old one and any added grants must be approved via
+    /// [`InstallOptions::approve_capability_changes`]; until then the
+    /// installed files are left untouched.
+    async fn install_wasm_bytes(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        caps_bytes: Option<&[u8]>,
+        target_dir: &std::path::Path,
+        kind: ExtensionKind,
+        options: &InstallOptions,
+    ) -> Result<(), ExtensionError> {
+        options
+            .integrity
+            .verify(bytes, self.install_public_key.as_ref())
+            .inspect_err(|e| {
+                tracing::error!(extension = %name, error = %e, "Rejected extension download");
+            })?;
 
         // Detect format: gzip (tar.gz bundle) or bare WASM
-        if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
-            // tar.gz bundle: extract {name}.wasm and {name}.capabilities.json
-            self.extract_wasm_tar_gz(name, &bytes, &wasm_path, &caps_path)?;
+        let (wasm, caps) = if is_gzip(bytes) {
+            // tar.gz bundle: unpack {name}.wasm and {name}.capabilities.json
+            Self::extract_wasm_tar_gz(name, bytes)?
         } else {
             // Bare WASM file: validate magic number
             if bytes.len() < 4 || &bytes[..4] != b"\0asm" {
@@ -820,66 +1072,85 @@ impl ExtensionManager {
                     "Downloaded file is not a valid WASM binary (bad magic number)".to_string(),
                 ));
             }
+            Self::verify_capabilities(name, caps_bytes, options, self.install_public_key.as_ref())?;
+            (bytes.to_vec(), caps_bytes.map(<[u8]>::to_vec))
+        };
 
-            tokio::fs::write(&wasm_path, &bytes)
-                .await
-                .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+        let wasm_path = target_dir.join(format!("{}.wasm", name));
+        let caps_path = target_dir.join(format!("{}.capabilities.json", name));
 
-            // Download capabilities separately if URL provided
-            if let Some(caps_url) = capabilities_url {
-                const MAX_CAPS_SIZE: usize = 1024 * 1024; // 1 MB
-                match client.get(caps_url).send().await {
-                    Ok(resp) if resp.status().is_success() => match resp.bytes().await {
-                        Ok(caps_bytes) if caps_bytes.len() <= MAX_CAPS_SIZE => {
-                            if let Err(e) = tokio::fs::write(&caps_path, &caps_bytes).await {
-                                tracing::warn!(
-                                    "Failed to write capabilities for '{}': {}",
-                                    name,
-                                    e
-                                );
-                            }
-                        }
-                        Ok(caps_bytes) => {
-                            tracing::warn!(
-                                "Capabilities file for '{}' too large ({} bytes, max {})",
-                                name,
-                                caps_bytes.len(),
-                                MAX_CAPS_SIZE
-                            );
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to download capabilities for '{}': {}", name, e);
-                        }
-                    },
-                    _ => {
-                        tracing::warn!(
-                            "Failed to download capabilities for '{}' from {}",
-                            name,
-                            caps_url
-                        );
-                    }
-                }
+        if let Some(ref caps) = caps {
+            let diff = Self::capability_changes(kind, &wasm_path, &caps_path, caps).await?;
+            if diff.requires_approval() && !options.approve_capability_changes {
+                tracing::warn!(
+                    extension = %name,
+                    added = %diff.describe_additions(),
+                    "Extension update requests new capabilities"
+                );
+                return Err(ExtensionError::ApprovalRequired(format!(
+                    "updating '{}' grants new capabilities ({}). Re-run the install with \
+                     approve_capability_changes to accept them.",
+                    name,
+                    diff.describe_additions()
+                )));
             }
         }
 
-        tracing::info!(
-            "Installed WASM extension '{}' from {} to {}",
-            name,
-            url,
-            wasm_path.display()
-        );
+        // Ensure target directory exists
+        tokio::fs::create_dir_all(target_dir)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
 
+        tokio::fs::write(&wasm_path, &wasm)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+        if let Some(caps) = caps {
+            tokio::fs::write(&caps_path, &caps)
+                .await
+                .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+        }
         Ok(())
     }
 
-    /// Extract a tar.gz bundle into the WASM tools directory.
+    /// Compare an incoming capabilities file against the installed one.
+    ///
+    /// A fresh install has nothing to compare against and yields an empty
+    /// diff. An installed module without a capabilities file runs with no
+    /// grants, so everything the new file asks for counts as added.
+    async fn capability_changes(
+        kind: ExtensionKind,
+        wasm_path: &std::path::Path,
+        caps_path: &std::path::Path,
+        new_caps: &[u8],
+    ) -> Result<ManifestDiff, ExtensionError> {
+        if !wasm_path.exists() {
+            return Ok(ManifestDiff::default());
+        }
+
+        let new = ManifestGrants::parse(kind, new_caps).map_err(|e| {
+            ExtensionError::InstallFailed(format!("Invalid capabilities file: {}", e))
+        })?;
+        let old = match tokio::fs::read(caps_path).await {
+            Ok(old_caps) => ManifestGrants::parse(kind, &old_caps).unwrap_or_else(|e| {
+                tracing::warn!(
+                    path = %caps_path.display(),
+                    error = %e,
+                    "Installed capabilities file is unreadable, treating it as empty"
+                );
+                ManifestGrants::default()
+            }),
+            Err(_) => ManifestGrants::default(),
+        };
+
+        Ok(ManifestDiff::between(&old, &new))
+    }
+
+    /// Unpack `{name}.wasm` and, if present, `{name}.capabilities.json` from a
+    /// tar.gz bundle.
     fn extract_wasm_tar_gz(
-        &self,
         name: &str,
         bytes: &[u8],
-        target_wasm: &std::path::Path,
-        target_caps: &std::path::Path,
-    ) -> Result<(), ExtensionError> {
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), ExtensionError> {
         use flate2::read::GzDecoder;
         use tar::Archive;
 
@@ -897,7 +1168,8 @@ impl ExtensionManager {
 
         let wasm_filename = format!("{}.wasm", name);
         let caps_filename = format!("{}.capabilities.json", name);
-        let mut found_wasm = false;
+        let mut wasm = None;
+        let mut caps = None;
 
         let entries = archive
             .entries()
@@ -931,26 +1203,23 @@ impl ExtensionManager {
                 let mut data = Vec::with_capacity(entry.size() as usize);
                 std::io::Read::read_to_end(&mut entry.by_ref().take(MAX_ENTRY_SIZE), &mut data)
                     .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
-                std::fs::write(target_wasm, &data)
-                    .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
-                found_wasm = true;
+                wasm = Some(data);
             } else if filename == caps_filename {
                 let mut data = Vec::with_capacity(entry.size() as usize);
                 std::io::Read::read_to_end(&mut entry.by_ref().take(MAX_ENTRY_SIZE), &mut data)
                     .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
-                std::fs::write(target_caps, &data)
-                    .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+                caps = Some(data);
             }
         }
 
-        if !found_wasm {
+        let Some(wasm) = wasm else {
             return Err(ExtensionError::InstallFailed(format!(
                 "tar.gz archive does not contain '{}'",
                 wasm_filename
             )));
-        }
+        };
 
-        Ok(())
+        Ok((wasm, caps))
     }
 
     #[allow(dead_code)] // Used by upcoming hot-activation flow
@@ -1364,6 +1633,48 @@ impl ExtensionManager {
         })
     }
 
+    /// Secret name a WASM tool authenticates with, if its capabilities declare one.
+    async fn wasm_tool_auth_secret(&self, name: &str) -> Option<String> {
+        let cap_path = self
+            .wasm_tools_dir
+            .join(format!("{}.capabilities.json", name));
+        let cap_bytes = tokio::fs::read(&cap_path).await.ok()?;
+        let cap_file = crate::tools::wasm::CapabilitiesFile::from_bytes(&cap_bytes).ok()?;
+        cap_file.auth.map(|auth| auth.secret_name)
+    }
+
+    /// Whether a WASM tool's auth secret (if any) is stored.
+    async fn check_tool_auth_status(&self, name: &str) -> bool {
+        match self.wasm_tool_auth_secret(name).await {
+            Some(secret_name) => self
+                .secrets
+                .exists(&self.user_id, &secret_name)
+                .await
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// All secret names declared in a WASM channel's setup section.
+    async fn wasm_channel_secret_names(&self, name: &str) -> Vec<String> {
+        let cap_path = self
+            .wasm_channels_dir
+            .join(format!("{}.capabilities.json", name));
+        let Ok(cap_bytes) = tokio::fs::read(&cap_path).await else {
+            return Vec::new();
+        };
+        let Ok(cap_file) = crate::channels::wasm::ChannelCapabilitiesFile::from_bytes(&cap_bytes)
+        else {
+            return Vec::new();
+        };
+        cap_file
+            .setup
+            .required_secrets
+            .into_iter()
+            .map(|secret| secret.name)
+            .collect()
+    }
+
     /// Check whether a WASM channel has all required secrets stored.
     /// Returns `(authenticated, needs_setup)`.
     async fn check_channel_auth_status(&self, name: &str) -> (bool, bool) {
@@ -2256,6 +2567,11 @@ async fn inject_channel_credentials_from_secrets(
     Ok(count)
 }
 
+/// Whether downloaded bytes start with the gzip magic number.
+fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b
+}
+
 /// Infer the extension kind from a URL.
 fn infer_kind_from_url(url: &str) -> ExtensionKind {
     if url.ends_with(".wasm") || url.ends_with(".tar.gz") {
@@ -2311,10 +2627,18 @@ fn combine_install_errors(
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
     use crate::extensions::manager::{
-        FallbackDecision, combine_install_errors, fallback_decision, infer_kind_from_url,
+        ExtensionManager, FallbackDecision, combine_install_errors, fallback_decision,
+        infer_kind_from_url,
     };
-    use crate::extensions::{ExtensionError, ExtensionKind, ExtensionSource, InstallResult};
+    use crate::extensions::{
+        ArtifactIntegrity, ExtensionError, ExtensionKind, ExtensionSource, InstallOptions,
+        InstallResult,
+    };
+    use crate::secrets::{CreateSecretParams, InMemorySecretsStore, SecretsCrypto, SecretsStore};
 
     #[test]
     fn test_infer_kind_from_url() {
@@ -2417,4 +2741,384 @@ mod tests {
             "Expected AlreadyInstalled, got: {combined:?}"
         );
     }
+
+    // ---- credential revocation tests ----
+
+    fn manager_with_dirs(
+        tools_dir: &Path,
+        channels_dir: &Path,
+    ) -> (ExtensionManager, Arc<dyn SecretsStore + Send + Sync>) {
+        use crate::tools::ToolRegistry;
+        use crate::tools::mcp::session::McpSessionManager;
+
+        let master_key =
+            secrecy::SecretString::from("0123456789abcdef0123456789abcdef".to_string());
+        let crypto = Arc::new(SecretsCrypto::new(master_key).unwrap());
+        let secrets: Arc<dyn SecretsStore + Send + Sync> =
+            Arc::new(InMemorySecretsStore::new(crypto));
+
+        let manager = ExtensionManager::new(
+            Arc::new(McpSessionManager::new()),
+            Arc::clone(&secrets),
+            Arc::new(ToolRegistry::new()),
+            None,
+            None,
+            tools_dir.to_path_buf(),
+            channels_dir.to_path_buf(),
+            None,
+            "test".to_string(),
+            None,
+            Vec::new(),
+        );
+        (manager, secrets)
+    }
+
+    async fn is_authenticated(manager: &ExtensionManager, kind: ExtensionKind, name: &str) -> bool {
+        manager
+            .list(Some(kind), false)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|e| e.name == name)
+            .map(|e| e.authenticated)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_revoke_channel_clears_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let channels_dir = dir.path().join("channels");
+        std::fs::create_dir_all(&channels_dir).unwrap();
+        std::fs::write(channels_dir.join("revoketest.wasm"), b"").unwrap();
+        std::fs::write(
+            channels_dir.join("revoketest.capabilities.json"),
+            r#"{"name":"revoketest","setup":{"required_secrets":[
+                {"name":"revoketest_bot_token","prompt":"Bot token"}
+            ]}}"#,
+        )
+        .unwrap();
+
+        let (manager, secrets) = manager_with_dirs(&dir.path().join("tools"), &channels_dir);
+        secrets
+            .create(
+                "test",
+                CreateSecretParams::new("revoketest_bot_token", "secret-value"),
+            )
+            .await
+            .unwrap();
+        assert!(is_authenticated(&manager, ExtensionKind::WasmChannel, "revoketest").await);
+
+        let message = manager.revoke("revoketest").await.unwrap();
+        assert!(message.contains("Revoked 1 credential"), "{message}");
+
+        assert!(!is_authenticated(&manager, ExtensionKind::WasmChannel, "revoketest").await);
+        assert!(
+            !secrets
+                .exists("test", "revoketest_bot_token")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoke_wasm_tool_clears_auth_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools_dir = dir.path().join("tools");
+        std::fs::create_dir_all(&tools_dir).unwrap();
+        std::fs::write(tools_dir.join("revoketool.wasm"), b"").unwrap();
+        std::fs::write(
+            tools_dir.join("revoketool.capabilities.json"),
+            r#"{"auth":{"secret_name":"revoketool_api_key"}}"#,
+        )
+        .unwrap();
+
+        let (manager, secrets) = manager_with_dirs(&tools_dir, &dir.path().join("channels"));
+        assert!(!is_authenticated(&manager, ExtensionKind::WasmTool, "revoketool").await);
+
+        secrets
+            .create(
+                "test",
+                CreateSecretParams::new("revoketool_api_key", "sk-test"),
+            )
+            .await
+            .unwrap();
+        assert!(is_authenticated(&manager, ExtensionKind::WasmTool, "revoketool").await);
+
+        manager.revoke("revoketool").await.unwrap();
+        assert!(!is_authenticated(&manager, ExtensionKind::WasmTool, "revoketool").await);
+        assert!(!secrets.exists("test", "revoketool_api_key").await.unwrap());
+    }
+
+    // ---- install integrity tests ----
+
+    const TEST_WASM: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(bytes))
+    }
+
+    fn pinned(integrity: ArtifactIntegrity) -> InstallOptions {
+        InstallOptions {
+            integrity,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_with_matching_checksum_writes_artifact() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools_dir = dir.path().join("tools");
+        let (manager, _secrets) = manager_with_dirs(&tools_dir, &dir.path().join("channels"));
+
+        let integrity = ArtifactIntegrity::new(Some(sha256_hex(TEST_WASM)), None);
+        manager
+            .install_wasm_bytes(
+                "pinned",
+                TEST_WASM,
+                None,
+                &tools_dir,
+                ExtensionKind::WasmTool,
+                &pinned(integrity),
+            )
+            .await
+            .unwrap();
+
+        assert!(tools_dir.join("pinned.wasm").exists());
+        assert!(
+            manager
+                .list(Some(ExtensionKind::WasmTool), false)
+                .await
+                .unwrap()
+                .iter()
+                .any(|e| e.name == "pinned")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_with_mismatched_checksum_is_rejected_before_activation() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools_dir = dir.path().join("tools");
+        let (manager, _secrets) = manager_with_dirs(&tools_dir, &dir.path().join("channels"));
+
+        let integrity = ArtifactIntegrity::new(Some(sha256_hex(b"something else")), None);
+        let err = manager
+            .install_wasm_bytes(
+                "tampered",
+                TEST_WASM,
+                None,
+                &tools_dir,
+                ExtensionKind::WasmTool,
+                &pinned(integrity),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ExtensionError::IntegrityFailed(_)), "{err}");
+
+        assert!(!tools_dir.join("tampered.wasm").exists());
+        assert!(matches!(
+            manager.activate("tampered").await,
+            Err(ExtensionError::NotInstalled(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_install_checks_signature_against_configured_key() {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let pair = Ed25519KeyPair::from_seed_unchecked(&[3u8; 32]).unwrap();
+        let public: [u8; 32] = pair.public_key().as_ref().try_into().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let tools_dir = dir.path().join("tools");
+        let (manager, _secrets) = manager_with_dirs(&tools_dir, &dir.path().join("channels"));
+        let manager = manager.with_install_public_key(public);
+
+        let signed = ArtifactIntegrity::new(None, Some(hex::encode(pair.sign(TEST_WASM))));
+        manager
+            .install_wasm_bytes(
+                "signed",
+                TEST_WASM,
+                None,
+                &tools_dir,
+                ExtensionKind::WasmTool,
+                &pinned(signed),
+            )
+            .await
+            .unwrap();
+
+        let forged = ArtifactIntegrity::new(None, Some(hex::encode([0u8; 64])));
+        assert!(
+            manager
+                .install_wasm_bytes(
+                    "forged",
+                    TEST_WASM,
+                    None,
+                    &tools_dir,
+                    ExtensionKind::WasmTool,
+                    &pinned(forged)
+                )
+                .await
+                .is_err()
+        );
+        assert!(!tools_dir.join("forged.wasm").exists());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_module_requires_pinned_capabilities() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools_dir = dir.path().join("tools");
+        let (manager, _secrets) = manager_with_dirs(&tools_dir, &dir.path().join("channels"));
+        let caps: &[u8] = br#"{"http": {"allowlist": [{"host": "api.example.com"}]}}"#;
+        let install = |options: InstallOptions| {
+            let manager = &manager;
+            let tools_dir = &tools_dir;
+            async move {
+                manager
+                    .install_wasm_bytes(
+                        "capped",
+                        TEST_WASM,
+                        Some(caps),
+                        tools_dir,
+                        ExtensionKind::WasmTool,
+                        &options,
+                    )
+                    .await
+            }
+        };
+        let module = ArtifactIntegrity::new(Some(sha256_hex(TEST_WASM)), None);
+
+        // Module pinned, capabilities not: refuse.
+        let err = install(pinned(module.clone())).await.unwrap_err();
+        assert!(matches!(err, ExtensionError::IntegrityFailed(_)), "{err}");
+
+        // Tampered capabilities.
+        let err = install(InstallOptions {
+            integrity: module.clone(),
+            capabilities_integrity: ArtifactIntegrity::new(Some(sha256_hex(b"{}")), None),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ExtensionError::IntegrityFailed(_)), "{err}");
+        assert!(!tools_dir.join("capped.wasm").exists());
+
+        install(InstallOptions {
+            integrity: module,
+            capabilities_integrity: ArtifactIntegrity::new(Some(sha256_hex(caps)), None),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            std::fs::read(tools_dir.join("capped.capabilities.json")).unwrap(),
+            caps
+        );
+    }
+
+    // ---- capability diff on update ----
+
+    const CAPS_V1: &[u8] = br#"{"auth": {"secret_name": "notes_token"},
+        "http": {"allowlist": [{"host": "api.notes.example"}]}}"#;
+    const CAPS_V2: &[u8] = br#"{"auth": {"secret_name": "notes_token"},
+        "http": {"allowlist": [{"host": "api.notes.example"}, {"host": "files.notes.example"}]},
+        "secrets": {"allowed_names": ["notes_upload_key"]}}"#;
+
+    #[tokio::test]
+    async fn test_update_adding_capabilities_requires_approval() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools_dir = dir.path().join("tools");
+        let (manager, _secrets) = manager_with_dirs(&tools_dir, &dir.path().join("channels"));
+        let caps_path = tools_dir.join("notes.capabilities.json");
+        let install = |caps: &'static [u8], options: InstallOptions| {
+            let manager = &manager;
+            let tools_dir = &tools_dir;
+            async move {
+                manager
+                    .install_wasm_bytes(
+                        "notes",
+                        TEST_WASM,
+                        Some(caps),
+                        tools_dir,
+                        ExtensionKind::WasmTool,
+                        &options,
+                    )
+                    .await
+            }
+        };
+
+        // Fresh install: nothing to compare against.
+        install(CAPS_V1, InstallOptions::default()).await.unwrap();
+        // Same manifest again: no new grants.
+        install(CAPS_V1, InstallOptions::default()).await.unwrap();
+
+        let err = install(CAPS_V2, InstallOptions::default())
+            .await
+            .unwrap_err();
+        let ExtensionError::ApprovalRequired(msg) = err else {
+            panic!("expected ApprovalRequired, got {err}");
+        };
+        assert!(msg.contains("notes_upload_key"), "{msg}");
+        assert!(msg.contains("files.notes.example"), "{msg}");
+        assert_eq!(std::fs::read(&caps_path).unwrap(), CAPS_V1);
+
+        let approved = InstallOptions {
+            approve_capability_changes: true,
+            ..Default::default()
+        };
+        install(CAPS_V2, approved).await.unwrap();
+        assert_eq!(std::fs::read(&caps_path).unwrap(), CAPS_V2);
+
+        // Dropping grants is always allowed.
+        install(CAPS_V1, InstallOptions::default()).await.unwrap();
+        assert_eq!(std::fs::read(&caps_path).unwrap(), CAPS_V1);
+    }
+
+    #[tokio::test]
+    async fn test_channel_update_adding_required_secret_requires_approval() {
+        let dir = tempfile::tempdir().unwrap();
+        let channels_dir = dir.path().join("channels");
+        let (manager, _secrets) = manager_with_dirs(&dir.path().join("tools"), &channels_dir);
+
+        let v1 = br#"{"name": "chat", "setup": {"required_secrets": [
+            {"name": "chat_token", "prompt": "Token"}]}}"#;
+        let v2 = br#"{"name": "chat", "setup": {"required_secrets": [
+            {"name": "chat_token", "prompt": "Token"},
+            {"name": "chat_signing_secret", "prompt": "Signing secret"}]}}"#;
+
+        let install = |caps: &'static [u8]| {
+            let manager = &manager;
+            let channels_dir = &channels_dir;
+            async move {
+                manager
+                    .install_wasm_bytes(
+                        "chat",
+                        TEST_WASM,
+                        Some(caps),
+                        channels_dir,
+                        ExtensionKind::WasmChannel,
+                        &InstallOptions::default(),
+                    )
+                    .await
+            }
+        };
+
+        install(v1).await.unwrap();
+        let err = install(v2).await.unwrap_err();
+        assert!(
+            matches!(&err, ExtensionError::ApprovalRequired(m) if m.contains("chat_signing_secret")),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoke_unknown_extension_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let (manager, _secrets) =
+            manager_with_dirs(&dir.path().join("tools"), &dir.path().join("channels"));
+        assert!(matches!(
+            manager.revoke("does-not-exist-anywhere").await,
+            Err(ExtensionError::NotInstalled(_))
+        ));
+    }
 }
@@ -0,0 +1,238 @@
+//! Capability-manifest comparison for extension updates.
+//!
+//! Re-installing an extension over an existing one can widen what it is
+//! allowed to do: new secrets, new HTTP hosts, new tool aliases, new
+//! workspace prefixes. [`ManifestGrants`] flattens a tool or channel
+//! capabilities file into comparable sets and [`ManifestDiff`] reports what
+//! changed, so the manager can hold the update until the user approves any
+//! additions. Removals never need approval.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::channels::wasm::ChannelCapabilitiesFile;
+use crate::extensions::ExtensionKind;
+use crate::tools::wasm::CapabilitiesFile;
+
+/// Everything a manifest grants or requires, as comparable sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestGrants {
+    /// Secrets the extension requires or may read.
+    pub secrets: BTreeSet<String>,
+    /// Hosts on the HTTP allowlist.
+    pub http_hosts: BTreeSet<String>,
+    /// Tools reachable through `tool_invoke` aliases.
+    pub tools: BTreeSet<String>,
+    /// Workspace path prefixes the extension may read.
+    pub workspace_prefixes: BTreeSet<String>,
+}
+
+impl ManifestGrants {
+    /// Parse a capabilities file of the given kind. MCP servers have no
+    /// capabilities file and grant nothing here.
+    pub fn parse(kind: ExtensionKind, bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        match kind {
+            ExtensionKind::WasmTool => {
+                CapabilitiesFile::from_bytes(bytes).map(|c| Self::from_tool(&c))
+            }
+            ExtensionKind::WasmChannel => {
+                ChannelCapabilitiesFile::from_bytes(bytes).map(|c| Self::from_channel(&c))
+            }
+            ExtensionKind::McpServer => Ok(Self::default()),
+        }
+    }
+
+    /// Grants declared by a WASM tool capabilities file.
+    pub fn from_tool(caps: &CapabilitiesFile) -> Self {
+        let mut grants = Self::default();
+
+        if let Some(ref http) = caps.http {
+            grants
+                .http_hosts
+                .extend(http.allowlist.iter().map(|e| e.host.clone()));
+            grants
+                .secrets
+                .extend(http.credentials.values().map(|c| c.secret_name.clone()));
+        }
+        if let Some(ref secrets) = caps.secrets {
+            grants.secrets.extend(secrets.allowed_names.iter().cloned());
+        }
+        if let Some(ref tool_invoke) = caps.tool_invoke {
+            grants.tools.extend(tool_invoke.aliases.values().cloned());
+        }
+        if let Some(ref workspace) = caps.workspace {
+            grants
+                .workspace_prefixes
+                .extend(workspace.allowed_prefixes.iter().cloned());
+        }
+        if let Some(ref auth) = caps.auth {
+            grants.secrets.insert(auth.secret_name.clone());
+        }
+
+        grants
+    }
+
+    /// Grants declared by a WASM channel capabilities file, including the
+    /// secrets its setup wizard asks for.
+    pub fn from_channel(caps: &ChannelCapabilitiesFile) -> Self {
+        let mut grants = Self::from_tool(&caps.capabilities.tool);
+        grants.secrets.extend(
+            caps.setup
+                .required_secrets
+                .iter()
+                .map(|secret| secret.name.clone()),
+        );
+        if let Some(prefix) = caps
+            .capabilities
+            .channel
+            .as_ref()
+            .and_then(|c| c.workspace_prefix.clone())
+        {
+            grants.workspace_prefixes.insert(prefix);
+        }
+        grants
+    }
+}
+
+/// Difference between an installed manifest and its replacement.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ManifestDiff {
+    pub added_secrets: Vec<String>,
+    pub removed_secrets: Vec<String>,
+    pub added_http_hosts: Vec<String>,
+    pub removed_http_hosts: Vec<String>,
+    pub added_tools: Vec<String>,
+    pub removed_tools: Vec<String>,
+    pub added_workspace_prefixes: Vec<String>,
+    pub removed_workspace_prefixes: Vec<String>,
+}
+
+fn added(old: &BTreeSet<String>, new: &BTreeSet<String>) -> Vec<String> {
+    new.difference(old).cloned().collect()
+}
+
+impl ManifestDiff {
+    /// Compare the installed grants against the incoming ones.
+    pub fn between(old: &ManifestGrants, new: &ManifestGrants) -> Self {
+        Self {
+            added_secrets: added(&old.secrets, &new.secrets),
+            removed_secrets: added(&new.secrets, &old.secrets),
+            added_http_hosts: added(&old.http_hosts, &new.http_hosts),
+            removed_http_hosts: added(&new.http_hosts, &old.http_hosts),
+            added_tools: added(&old.tools, &new.tools),
+            removed_tools: added(&new.tools, &old.tools),
+            added_workspace_prefixes: added(&old.workspace_prefixes, &new.workspace_prefixes),
+            removed_workspace_prefixes: added(&new.workspace_prefixes, &old.workspace_prefixes),
+        }
+    }
+
+    /// Whether the new manifest asks for anything the old one didn't.
+    pub fn requires_approval(&self) -> bool {
+        !self.added_secrets.is_empty()
+            || !self.added_http_hosts.is_empty()
+            || !self.added_tools.is_empty()
+            || !self.added_workspace_prefixes.is_empty()
+    }
+
+    /// Human-readable list of the additions, e.g.
+    /// `secrets: slack_token; http hosts: api.slack.com`.
+    pub fn describe_additions(&self) -> String {
+        let sections = [
+            ("secrets", &self.added_secrets),
+            ("http hosts", &self.added_http_hosts),
+            ("tools", &self.added_tools),
+            ("workspace prefixes", &self.added_workspace_prefixes),
+        ];
+        sections
+            .iter()
+            .filter(|(_, items)| !items.is_empty())
+            .map(|(label, items)| format!("{}: {}", label, items.join(", ")))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(json: &str) -> ManifestGrants {
+        ManifestGrants::from_channel(&ChannelCapabilitiesFile::from_json(json).unwrap())
+    }
+
+    fn tool(json: &str) -> ManifestGrants {
+        ManifestGrants::from_tool(&CapabilitiesFile::from_json(json).unwrap())
+    }
+
+    #[test]
+    fn identical_manifests_need_no_approval() {
+        let json = r#"{"name":"chat","setup":{"required_secrets":[
+            {"name":"chat_token","prompt":"Token"}
+        ]}}"#;
+        let diff = ManifestDiff::between(&channel(json), &channel(json));
+        assert_eq!(diff, ManifestDiff::default());
+        assert!(!diff.requires_approval());
+    }
+
+    #[test]
+    fn added_and_removed_required_secrets() {
+        let old = channel(
+            r#"{"name":"chat","setup":{"required_secrets":[
+                {"name":"chat_token","prompt":"Token"},
+                {"name":"chat_legacy_key","prompt":"Legacy"}
+            ]}}"#,
+        );
+        let new = channel(
+            r#"{"name":"chat","setup":{"required_secrets":[
+                {"name":"chat_token","prompt":"Token"},
+                {"name":"chat_signing_secret","prompt":"Signing secret"}
+            ]}}"#,
+        );
+
+        let diff = ManifestDiff::between(&old, &new);
+        assert_eq!(diff.added_secrets, vec!["chat_signing_secret"]);
+        assert_eq!(diff.removed_secrets, vec!["chat_legacy_key"]);
+        assert!(diff.requires_approval());
+        assert_eq!(diff.describe_additions(), "secrets: chat_signing_secret");
+    }
+
+    #[test]
+    fn added_and_removed_tools_and_hosts() {
+        let old = tool(
+            r#"{
+                "http": {"allowlist": [{"host": "api.example.com"}]},
+                "tool_invoke": {"aliases": {"search": "web_search", "old": "legacy_tool"}}
+            }"#,
+        );
+        let new = tool(
+            r#"{
+                "http": {"allowlist": [{"host": "api.example.com"}, {"host": "uploads.example.com"}]},
+                "tool_invoke": {"aliases": {"search": "web_search", "run": "shell"}}
+            }"#,
+        );
+
+        let diff = ManifestDiff::between(&old, &new);
+        assert_eq!(diff.added_tools, vec!["shell"]);
+        assert_eq!(diff.removed_tools, vec!["legacy_tool"]);
+        assert_eq!(diff.added_http_hosts, vec!["uploads.example.com"]);
+        assert!(diff.removed_http_hosts.is_empty());
+        assert_eq!(
+            diff.describe_additions(),
+            "http hosts: uploads.example.com; tools: shell"
+        );
+    }
+
+    #[test]
+    fn removals_alone_do_not_need_approval() {
+        let old = tool(
+            r#"{"auth": {"secret_name": "api_key"}, "workspace": {"allowed_prefixes": ["notes/"]}}"#,
+        );
+        let new = tool("{}");
+
+        let diff = ManifestDiff::between(&old, &new);
+        assert_eq!(diff.removed_secrets, vec!["api_key"]);
+        assert_eq!(diff.removed_workspace_prefixes, vec!["notes/"]);
+        assert!(!diff.requires_approval());
+    }
+}
@@ -17,11 +17,16 @@
 //! ```
 
 pub mod discovery;
+pub mod download;
+pub mod integrity;
 pub mod manager;
+pub mod manifest_diff;
 pub mod registry;
 
 pub use discovery::OnlineDiscovery;
+pub use integrity::ArtifactIntegrity;
 pub use manager::ExtensionManager;
+pub use manifest_diff::{ManifestDiff, ManifestGrants};
 pub use registry::ExtensionRegistry;
 
 use serde::{Deserialize, Serialize};
@@ -136,6 +141,20 @@ pub struct SearchResult {
     pub validated: bool,
 }
 
+/// Caller-supplied constraints for an install or update.
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Expected checksum/signature of the downloaded artifact.
+    pub integrity: ArtifactIntegrity,
+    /// Expected checksum/signature of a separately downloaded capabilities
+    /// file. Required whenever `integrity` pins a bare module that comes
+    /// with one, since the capabilities decide what the module may access.
+    pub capabilities_integrity: ArtifactIntegrity,
+    /// Accept an update whose capabilities file grants more than the
+    /// installed one (new secrets, hosts, tools, or workspace prefixes).
+    pub approve_capability_changes: bool,
+}
+
 /// Result of installing an extension.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallResult {
@@ -235,6 +254,12 @@ pub enum ExtensionError {
     #[error("Download failed: {0}")]
     DownloadFailed(String),
 
+    #[error("Integrity check failed: {0}")]
+    IntegrityFailed(String),
+
+    #[error("Approval required: {0}")]
+    ApprovalRequired(String),
+
     #[error("Config error: {0}")]
     Config(String),
 
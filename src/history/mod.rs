@@ -14,6 +14,6 @@ pub use analytics::{JobStats, ToolStats};
 #[cfg(feature = "postgres")]
 pub use store::Store;
 pub use store::{
-    ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord, SandboxJobRecord,
-    SandboxJobSummary, SettingRow,
+    AppliedMigration, ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord,
+    SandboxJobRecord, SandboxJobSummary, SettingRow,
 };
@@ -2,10 +2,8 @@
 
 use chrono::{DateTime, Utc};
 #[cfg(feature = "postgres")]
-use deadpool_postgres::{Config, Pool, Runtime};
+use deadpool_postgres::Pool;
 use rust_decimal::Decimal;
-#[cfg(feature = "postgres")]
-use tokio_postgres::NoTls;
 use uuid::Uuid;
 
 #[cfg(feature = "postgres")]
@@ -13,6 +11,8 @@ use crate::config::DatabaseConfig;
 #[cfg(feature = "postgres")]
 use crate::context::{ActionRecord, JobContext, JobState};
 #[cfg(feature = "postgres")]
+use crate::db::postgres::{PoolMetrics, create_pool};
+#[cfg(feature = "postgres")]
 use crate::error::DatabaseError;
 
 /// Record for an LLM call to be persisted.
@@ -28,6 +28,13 @@ pub struct LlmCallRecord<'a> {
     pub purpose: Option<&'a str>,
 }
 
+/// A schema migration recorded as applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: i32,
+    pub name: String,
+}
+
 /// Database store for the agent.
 #[cfg(feature = "postgres")]
 pub struct Store {
@@ -43,15 +50,7 @@ impl Store {
 
     /// Create a new store and connect to the database.
     pub async fn new(config: &DatabaseConfig) -> Result<Self, DatabaseError> {
-        let mut cfg = Config::new();
-        cfg.url = Some(config.url().to_string());
-        cfg.pool = Some(deadpool_postgres::PoolConfig {
-            max_size: config.pool_size,
-            ..Default::default()
-        });
-
-        let pool = cfg
-            .create_pool(Some(Runtime::Tokio1), NoTls)
+        let pool = create_pool(config.url(), &config.pool)
             .map_err(|e| DatabaseError::Pool(e.to_string()))?;
 
         // Test connection
@@ -62,15 +61,48 @@ impl Store {
 
     /// Run database migrations (embedded via refinery).
     pub async fn run_migrations(&self) -> Result<(), DatabaseError> {
+        self.migrate().await.map(|_| ())
+    }
+
+    /// Apply pending migrations and return the ones applied by this call.
+    ///
+    /// Refinery records each version in `refinery_schema_history`, so
+    /// re-running is a no-op that returns an empty list.
+    pub async fn migrate(&self) -> Result<Vec<AppliedMigration>, DatabaseError> {
         use refinery::embed_migrations;
         embed_migrations!("migrations");
 
         let mut client = self.pool.get().await?;
-        migrations::runner()
+        let report = migrations::runner()
             .run_async(&mut **client)
             .await
             .map_err(|e| DatabaseError::Migration(e.to_string()))?;
-        Ok(())
+        Ok(report
+            .applied_migrations()
+            .iter()
+            .map(|m| AppliedMigration {
+                version: m.version(),
+                name: m.name().to_string(),
+            })
+            .collect())
+    }
+
+    /// All migrations recorded as applied, oldest first.
+    pub async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, DatabaseError> {
+        let conn = self.conn().await?;
+        let rows = conn
+            .query(
+                "SELECT version, name FROM refinery_schema_history ORDER BY version",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| AppliedMigration {
+                version: row.get(0),
+                name: row.get(1),
+            })
+            .collect())
     }
 
     /// Get a connection from the pool.
@@ -85,6 +117,11 @@ impl Store {
         self.pool.clone()
     }
 
+    /// Current connection-pool usage.
+    pub fn pool_metrics(&self) -> PoolMetrics {
+        PoolMetrics::from_pool(&self.pool)
+    }
+
     // ==================== Conversations ====================
 
     /// Create a new conversation.
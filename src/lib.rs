@@ -44,6 +44,7 @@ pub mod boot_screen;
 pub mod bootstrap;
 pub mod channels;
 pub mod cli;
+pub mod clock;
 pub mod config;
 pub mod context;
 pub mod db;
@@ -64,6 +65,7 @@ pub mod secrets;
 pub mod service;
 pub mod settings;
 pub mod setup;
+pub mod shutdown;
 pub mod skills;
 pub mod tools;
 pub mod tracing_fmt;
@@ -74,7 +76,7 @@ pub mod workspace;
 #[cfg(feature = "zkproxy")]
 pub mod zkproxy;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 pub mod testing;
 
 pub use config::Config;
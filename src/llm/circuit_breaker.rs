@@ -1,39 +1,59 @@
 //! Circuit breaker for LLM providers.
 //!
 //! Wraps any `LlmProvider` with a state machine that trips open after
-//! consecutive transient failures, preventing request storms against a
-//! degraded backend. Automatically probes for recovery via half-open state.
+//! consecutive transient failures, or a high transient error rate over a
+//! rolling window of calls, preventing request storms against a degraded
+//! backend. Automatically probes for recovery via half-open state.
 //!
 //! ```text
-//!   Closed ──(failures >= threshold)──► Open
+//!   Closed ──(trip policy met)──► Open
 //!     ▲                                   │
 //!     │                          (recovery timeout)
 //!     │                                   ▼
 //!     └──(probe succeeds)──── HalfOpen ──(probe fails)──► Open
 //! ```
 
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use tokio::sync::Mutex;
 
+use crate::clock::{Clock, system_clock};
 use crate::error::LlmError;
 use crate::llm::provider::{
-    CompletionRequest, CompletionResponse, LlmProvider, ModelMetadata, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
+use crate::observability::prometheus;
+
+/// What opens a closed circuit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TripPolicy {
+    /// `failure_threshold` transient failures in a row.
+    Consecutive,
+    /// More than `max_error_rate` (0.0 to 1.0) of the last `window` calls
+    /// failed transiently. Catches backends that fail often but not
+    /// back-to-back, which never reach a consecutive threshold. Nothing
+    /// trips until `window` calls have been seen.
+    ErrorRate { window: u32, max_error_rate: f64 },
+}
 
 /// Configuration for the circuit breaker.
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
-    /// Consecutive transient failures before the circuit opens.
+    /// Consecutive transient failures before the circuit opens, under
+    /// [`TripPolicy::Consecutive`].
     pub failure_threshold: u32,
     /// How long the circuit stays open before allowing a probe.
     pub recovery_timeout: Duration,
     /// Successful probes needed in half-open to close the circuit.
     pub half_open_successes_needed: u32,
+    /// When a closed circuit opens.
+    pub trip_policy: TripPolicy,
 }
 
 impl Default for CircuitBreakerConfig {
@@ -42,6 +62,7 @@ impl Default for CircuitBreakerConfig {
             failure_threshold: 5,
             recovery_timeout: Duration::from_secs(30),
             half_open_successes_needed: 2,
+            trip_policy: TripPolicy::Consecutive,
         }
     }
 }
@@ -57,12 +78,54 @@ pub enum CircuitState {
     HalfOpen,
 }
 
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Open,
+            2 => Self::HalfOpen,
+            _ => Self::Closed,
+        }
+    }
+}
+
+/// Point-in-time view of a circuit breaker, from
+/// [`CircuitBreakerProvider::snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitSnapshot {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// While open, how long until a probe call is let through. Zero means
+    /// the next call will probe.
+    pub time_until_probe: Option<Duration>,
+}
+
+/// Lock-free copy of the fields `snapshot()` reports, written whenever
+/// `BreakerState` changes.
+struct PublishedState {
+    state: AtomicU8,
+    consecutive_failures: AtomicU32,
+    /// `opened_at`, as nanoseconds since `epoch`.
+    opened_at_nanos: AtomicU64,
+    epoch: Instant,
+}
+
 /// Internal mutable state.
 struct BreakerState {
     state: CircuitState,
     consecutive_failures: u32,
     opened_at: Option<Instant>,
     half_open_successes: u32,
+    /// Outcomes of the most recent calls while closed, oldest first, `true`
+    /// for a transient failure. Only kept under [`TripPolicy::ErrorRate`].
+    recent: VecDeque<bool>,
 }
 
 impl BreakerState {
@@ -72,31 +135,115 @@ impl BreakerState {
             consecutive_failures: 0,
             opened_at: None,
             half_open_successes: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Record a closed-circuit outcome; returns whether `policy` says the
+    /// circuit should now open.
+    fn record_outcome(&mut self, failed: bool, policy: TripPolicy, failure_threshold: u32) -> bool {
+        match policy {
+            TripPolicy::Consecutive => failed && self.consecutive_failures >= failure_threshold,
+            TripPolicy::ErrorRate {
+                window,
+                max_error_rate,
+            } => {
+                let window = window.max(1) as usize;
+                self.recent.push_back(failed);
+                while self.recent.len() > window {
+                    self.recent.pop_front();
+                }
+                let failures = self.recent.iter().filter(|failed| **failed).count();
+                self.recent.len() == window && failures as f64 / window as f64 > max_error_rate
+            }
         }
     }
 }
 
 /// Wraps an `LlmProvider` with circuit breaker protection.
 ///
-/// Tracks consecutive transient failures. After `failure_threshold` failures
-/// the circuit opens and all requests are rejected for `recovery_timeout`.
+/// Tracks transient failures. Once the configured [`TripPolicy`] is met the
+/// circuit opens and all requests are rejected for `recovery_timeout`.
 /// After that timeout a probe call is allowed through (half-open); if it
 /// succeeds the circuit closes, otherwise it reopens.
 pub struct CircuitBreakerProvider {
     inner: Arc<dyn LlmProvider>,
     state: Mutex<BreakerState>,
+    published: PublishedState,
     config: CircuitBreakerConfig,
+    clock: Arc<dyn Clock>,
 }
 
 impl CircuitBreakerProvider {
     pub fn new(inner: Arc<dyn LlmProvider>, config: CircuitBreakerConfig) -> Self {
+        let clock = system_clock();
         Self {
             inner,
             state: Mutex::new(BreakerState::new()),
+            published: PublishedState {
+                state: AtomicU8::new(CircuitState::Closed as u8),
+                consecutive_failures: AtomicU32::new(0),
+                opened_at_nanos: AtomicU64::new(0),
+                epoch: clock.now(),
+            },
             config,
+            clock,
         }
     }
 
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.published.epoch = clock.now();
+        self.clock = clock;
+        self
+    }
+
+    /// Time since `instant` on this breaker's clock.
+    fn elapsed_since(&self, instant: Instant) -> Duration {
+        self.clock.now().saturating_duration_since(instant)
+    }
+
+    /// Current state, failure count and time until the next probe, read
+    /// without taking the state lock. The fields are updated one by one, so
+    /// a snapshot taken mid-transition may mix old and new values.
+    ///
+    /// An open circuit only moves to half-open when the next call arrives,
+    /// so after the recovery timeout it still reports `Open` with a zero
+    /// `time_until_probe`.
+    pub fn snapshot(&self) -> CircuitSnapshot {
+        let state = CircuitState::from_u8(self.published.state.load(Ordering::Acquire));
+        let time_until_probe = (state == CircuitState::Open).then(|| {
+            let opened_at = self.published.epoch
+                + Duration::from_nanos(self.published.opened_at_nanos.load(Ordering::Acquire));
+            self.config
+                .recovery_timeout
+                .saturating_sub(self.elapsed_since(opened_at))
+        });
+        CircuitSnapshot {
+            state,
+            consecutive_failures: self.published.consecutive_failures.load(Ordering::Acquire),
+            time_until_probe,
+        }
+    }
+
+    /// Mirror `state` into the lock-free fields read by `snapshot()`.
+    fn publish(&self, state: &BreakerState) {
+        if let Some(opened_at) = state.opened_at {
+            let nanos = opened_at
+                .saturating_duration_since(self.published.epoch)
+                .as_nanos();
+            self.published
+                .opened_at_nanos
+                .store(u64::try_from(nanos).unwrap_or(u64::MAX), Ordering::Release);
+        }
+        self.published
+            .consecutive_failures
+            .store(state.consecutive_failures, Ordering::Release);
+        self.published
+            .state
+            .store(state.state as u8, Ordering::Release);
+    }
+
     /// Current circuit state (for observability / health checks).
     pub async fn circuit_state(&self) -> CircuitState {
         self.state.lock().await.state
@@ -114,9 +261,10 @@ impl CircuitBreakerProvider {
             CircuitState::Closed | CircuitState::HalfOpen => Ok(()),
             CircuitState::Open => {
                 if let Some(opened_at) = state.opened_at {
-                    if opened_at.elapsed() >= self.config.recovery_timeout {
+                    if self.elapsed_since(opened_at) >= self.config.recovery_timeout {
                         state.state = CircuitState::HalfOpen;
                         state.half_open_successes = 0;
+                        self.publish(&state);
                         tracing::info!(
                             provider = self.inner.model_name(),
                             "Circuit breaker: Open -> HalfOpen, allowing probe"
@@ -126,14 +274,25 @@ impl CircuitBreakerProvider {
                         let remaining = self
                             .config
                             .recovery_timeout
-                            .checked_sub(opened_at.elapsed())
+                            .checked_sub(self.elapsed_since(opened_at))
                             .unwrap_or(Duration::ZERO);
+                        prometheus::record_circuit_rejection(self.inner.model_name());
+                        let cause = match self.config.trip_policy {
+                            TripPolicy::Consecutive => {
+                                format!("{} consecutive failures", state.consecutive_failures)
+                            }
+                            TripPolicy::ErrorRate {
+                                window,
+                                max_error_rate,
+                            } => format!(
+                                "error rate above {:.0}% over the last {window} calls",
+                                max_error_rate * 100.0
+                            ),
+                        };
                         Err(LlmError::RequestFailed {
                             provider: self.inner.model_name().to_string(),
                             reason: format!(
-                                "Circuit breaker open ({} consecutive failures, \
-                                 recovery in {:.0}s)",
-                                state.consecutive_failures,
+                                "Circuit breaker open ({cause}, recovery in {:.0}s)",
                                 remaining.as_secs_f64()
                             ),
                         })
@@ -141,6 +300,7 @@ impl CircuitBreakerProvider {
                 } else {
                     // opened_at should always be Some when Open; recover gracefully
                     state.state = CircuitState::Closed;
+                    self.publish(&state);
                     Ok(())
                 }
             }
@@ -153,6 +313,13 @@ impl CircuitBreakerProvider {
         match state.state {
             CircuitState::Closed => {
                 state.consecutive_failures = 0;
+                if state.record_outcome(
+                    false,
+                    self.config.trip_policy,
+                    self.config.failure_threshold,
+                ) {
+                    self.trip(&mut state);
+                }
             }
             CircuitState::HalfOpen => {
                 state.half_open_successes += 1;
@@ -160,6 +327,8 @@ impl CircuitBreakerProvider {
                     state.state = CircuitState::Closed;
                     state.consecutive_failures = 0;
                     state.opened_at = None;
+                    state.recent.clear();
+                    prometheus::record_circuit_open(self.inner.model_name(), false);
                     tracing::info!(
                         provider = self.inner.model_name(),
                         "Circuit breaker: HalfOpen -> Closed (recovered)"
@@ -173,6 +342,21 @@ impl CircuitBreakerProvider {
                 state.opened_at = None;
             }
         }
+        self.publish(&state);
+    }
+
+    /// Open a closed circuit.
+    fn trip(&self, state: &mut BreakerState) {
+        state.state = CircuitState::Open;
+        state.opened_at = Some(self.clock.now());
+        state.recent.clear();
+        prometheus::record_circuit_open(self.inner.model_name(), true);
+        tracing::warn!(
+            provider = self.inner.model_name(),
+            failures = state.consecutive_failures,
+            policy = ?self.config.trip_policy,
+            "Circuit breaker: Closed -> Open"
+        );
     }
 
     /// Record a failed call; only transient errors count toward the threshold.
@@ -185,20 +369,19 @@ impl CircuitBreakerProvider {
         match state.state {
             CircuitState::Closed => {
                 state.consecutive_failures += 1;
-                if state.consecutive_failures >= self.config.failure_threshold {
-                    state.state = CircuitState::Open;
-                    state.opened_at = Some(Instant::now());
-                    tracing::warn!(
-                        provider = self.inner.model_name(),
-                        failures = state.consecutive_failures,
-                        "Circuit breaker: Closed -> Open"
-                    );
+                if state.record_outcome(
+                    true,
+                    self.config.trip_policy,
+                    self.config.failure_threshold,
+                ) {
+                    self.trip(&mut state);
                 }
             }
             CircuitState::HalfOpen => {
                 state.state = CircuitState::Open;
-                state.opened_at = Some(Instant::now());
+                state.opened_at = Some(self.clock.now());
                 state.half_open_successes = 0;
+                prometheus::record_circuit_open(self.inner.model_name(), true);
                 tracing::warn!(
                     provider = self.inner.model_name(),
                     "Circuit breaker: HalfOpen -> Open (probe failed)"
@@ -206,6 +389,7 @@ impl CircuitBreakerProvider {
             }
             CircuitState::Open => {}
         }
+        self.publish(&state);
     }
 }
 
@@ -276,6 +460,25 @@ impl LlmProvider for CircuitBreakerProvider {
         }
     }
 
+    /// Only opening the stream counts toward the breaker; a stream that
+    /// fails part-way has already shown the backend is reachable.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        self.check_allowed().await?;
+        match self.inner.complete_stream(request).await {
+            Ok(stream) => {
+                self.record_success().await;
+                Ok(stream)
+            }
+            Err(err) => {
+                self.record_failure(&err).await;
+                Err(err)
+            }
+        }
+    }
+
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         self.inner.list_models().await
     }
@@ -299,12 +502,17 @@ impl LlmProvider for CircuitBreakerProvider {
     fn calculate_cost(&self, input_tokens: u32, output_tokens: u32) -> Decimal {
         self.inner.calculate_cost(input_tokens, output_tokens)
     }
+
+    fn circuit_snapshot(&self) -> Option<CircuitSnapshot> {
+        Some(self.snapshot())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::clock::MockClock;
     use crate::testing::StubLlm;
 
     fn make_request() -> CompletionRequest {
@@ -320,6 +528,7 @@ mod tests {
             failure_threshold: threshold,
             recovery_timeout: Duration::from_millis(50),
             half_open_successes_needed: 1,
+            trip_policy: TripPolicy::Consecutive,
         }
     }
 
@@ -362,6 +571,7 @@ mod tests {
                 failure_threshold: 1,
                 recovery_timeout: Duration::from_secs(60),
                 half_open_successes_needed: 1,
+                ..CircuitBreakerConfig::default()
             },
         );
 
@@ -401,6 +611,81 @@ mod tests {
         assert_eq!(cb.circuit_state().await, CircuitState::Open);
     }
 
+    #[tokio::test]
+    async fn recovery_timeout_on_mock_clock() {
+        let stub = Arc::new(StubLlm::failing("test"));
+        let clock = Arc::new(MockClock::new());
+        let cb = CircuitBreakerProvider::new(
+            stub.clone(),
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                recovery_timeout: Duration::from_secs(60),
+                half_open_successes_needed: 1,
+                ..CircuitBreakerConfig::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        let _ = cb.complete(make_request()).await;
+        assert_eq!(
+            cb.snapshot().time_until_probe,
+            Some(Duration::from_secs(60))
+        );
+
+        clock.advance(Duration::from_secs(59));
+        stub.set_failing(false);
+        assert!(cb.complete(make_request()).await.is_err());
+        assert_eq!(cb.snapshot().time_until_probe, Some(Duration::from_secs(1)));
+
+        clock.advance(Duration::from_secs(1));
+        cb.complete(make_request()).await.unwrap();
+        assert_eq!(cb.circuit_state().await, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn snapshot_follows_state_transitions() {
+        let stub = Arc::new(StubLlm::failing("test"));
+        let cb = CircuitBreakerProvider::new(stub.clone(), fast_config(2));
+        let closed = CircuitSnapshot {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            time_until_probe: None,
+        };
+        assert_eq!(cb.snapshot(), closed);
+
+        let _ = cb.complete(make_request()).await;
+        assert_eq!(cb.snapshot().consecutive_failures, 1);
+        assert_eq!(cb.snapshot().state, CircuitState::Closed);
+
+        let _ = cb.complete(make_request()).await;
+        let open = cb.snapshot();
+        assert_eq!(open.state, CircuitState::Open);
+        assert_eq!(open.consecutive_failures, 2);
+        let remaining = open.time_until_probe.unwrap();
+        assert!(remaining <= Duration::from_millis(50), "{remaining:?}");
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(cb.snapshot().time_until_probe, Some(Duration::ZERO));
+
+        stub.set_failing(false);
+        cb.complete(make_request()).await.unwrap();
+        assert_eq!(cb.snapshot(), closed);
+    }
+
+    #[tokio::test]
+    async fn snapshot_is_visible_through_decorators() {
+        let cb: Arc<dyn LlmProvider> = Arc::new(CircuitBreakerProvider::new(
+            Arc::new(StubLlm::failing("test")),
+            fast_config(1),
+        ));
+        let cached = crate::llm::CachedProvider::new(cb, Default::default());
+        assert_eq!(
+            cached.circuit_snapshot().map(|s| s.state),
+            Some(CircuitState::Closed)
+        );
+        assert!(StubLlm::new("hi").circuit_snapshot().is_none());
+    }
+
     #[tokio::test]
     async fn half_open_success_closes_circuit() {
         let stub = Arc::new(StubLlm::failing("test"));
@@ -486,6 +771,7 @@ mod tests {
                 failure_threshold: 1,
                 recovery_timeout: Duration::from_millis(50),
                 half_open_successes_needed: 3,
+                ..CircuitBreakerConfig::default()
             },
         );
 
@@ -509,6 +795,70 @@ mod tests {
         assert_eq!(cb.circuit_state().await, CircuitState::Closed);
     }
 
+    #[tokio::test]
+    async fn alternating_failures_trip_rate_breaker_only() {
+        let stub = Arc::new(StubLlm::new("ok").with_model_name("test"));
+        let consecutive = CircuitBreakerProvider::new(stub.clone(), fast_config(3));
+        let rate = CircuitBreakerProvider::new(
+            stub.clone(),
+            CircuitBreakerConfig {
+                trip_policy: TripPolicy::ErrorRate {
+                    window: 6,
+                    max_error_rate: 0.4,
+                },
+                ..fast_config(3)
+            },
+        );
+
+        // fail, ok, fail, ok, fail, ok: a 50% error rate, never two
+        // failures in a row.
+        for call in 0..6 {
+            stub.set_failing(call % 2 == 0);
+            let _ = consecutive.complete(make_request()).await;
+            let _ = rate.complete(make_request()).await;
+            if call < 5 {
+                assert_eq!(rate.circuit_state().await, CircuitState::Closed, "{call}");
+            }
+        }
+        assert_eq!(consecutive.circuit_state().await, CircuitState::Closed);
+        assert_eq!(rate.circuit_state().await, CircuitState::Open);
+
+        let err = rate.complete(make_request()).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("error rate above 40% over the last 6 calls"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_breaker_tolerates_rate_at_or_below_limit() {
+        let stub = Arc::new(StubLlm::new("ok").with_model_name("test"));
+        let rate = CircuitBreakerProvider::new(
+            stub.clone(),
+            CircuitBreakerConfig {
+                trip_policy: TripPolicy::ErrorRate {
+                    window: 4,
+                    max_error_rate: 0.5,
+                },
+                ..fast_config(1)
+            },
+        );
+
+        // Two failures in every four calls is exactly the limit.
+        for call in 0..12 {
+            stub.set_failing(call % 2 == 1);
+            let _ = rate.complete(make_request()).await;
+        }
+        assert_eq!(rate.circuit_state().await, CircuitState::Closed);
+
+        // Only the window counts: the next failure makes it three of the
+        // last four calls.
+        stub.set_failing(true);
+        let _ = rate.complete(make_request()).await;
+        assert_eq!(rate.circuit_state().await, CircuitState::Open);
+    }
+
     // -- Error classification tests --
 
     #[test]
@@ -67,6 +67,17 @@ pub fn model_cost(model_id: &str) -> Option<(Decimal, Decimal)> {
         | "claude-3-5-haiku-latest" => Some((dec!(0.0000008), dec!(0.000004))),
         "claude-3-haiku-20240307" => Some((dec!(0.00000025), dec!(0.00000125))),
 
+        // Google Gemini (standard-context pricing)
+        "gemini-2.5-pro" => Some((dec!(0.00000125), dec!(0.00001))),
+        "gemini-2.5-flash" => Some((dec!(0.0000003), dec!(0.0000025))),
+        "gemini-2.5-flash-lite" => Some((dec!(0.0000001), dec!(0.0000004))),
+        "gemini-2.0-flash" | "gemini-2.0-flash-001" => Some((dec!(0.0000001), dec!(0.0000004))),
+        "gemini-2.0-flash-lite" | "gemini-2.0-flash-lite-001" => {
+            Some((dec!(0.000000075), dec!(0.0000003)))
+        }
+        "gemini-1.5-pro" | "gemini-1.5-pro-002" => Some((dec!(0.00000125), dec!(0.000005))),
+        "gemini-1.5-flash" | "gemini-1.5-flash-002" => Some((dec!(0.000000075), dec!(0.0000003))),
+
         // Ollama / local models -- free
         _ if is_local_model(id) => Some((Decimal::ZERO, Decimal::ZERO)),
 
@@ -116,6 +127,17 @@ mod tests {
         assert!(output > input);
     }
 
+    #[test]
+    fn test_gemini_costs() {
+        let (input, output) = model_cost("gemini-2.5-flash").unwrap();
+        assert!(input > Decimal::ZERO);
+        assert!(output > input);
+        assert_eq!(
+            model_cost("google/gemini-2.5-pro"),
+            model_cost("gemini-2.5-pro")
+        );
+    }
+
     #[test]
     fn test_local_model_free() {
         let (input, output) = model_cost("llama3").unwrap();
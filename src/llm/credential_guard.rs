@@ -0,0 +1,285 @@
+//! `CredentialGuardProvider` decorator that scans outgoing requests for
+//! credentials.
+//!
+//! Tool outputs pass through the leak detector before they reach the
+//! conversation, but a secret can still get into the history another way
+//! (a user pasting a key, a message restored from an older thread). This
+//! pass scans every message right before it is sent, including tool call
+//! arguments, and either logs each finding or replaces it with
+//! `[REDACTED]`, as set by [`OutboundCredentialMode`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+use crate::config::OutboundCredentialMode;
+use crate::error::LlmError;
+use crate::llm::CircuitSnapshot;
+use crate::llm::provider::{
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, LlmProvider,
+    ModelMetadata, ToolCompletionRequest, ToolCompletionResponse,
+};
+use crate::safety::LeakDetector;
+
+pub struct CredentialGuardProvider {
+    inner: Arc<dyn LlmProvider>,
+    detector: Arc<LeakDetector>,
+    mode: OutboundCredentialMode,
+}
+
+impl CredentialGuardProvider {
+    /// Wrap `inner`, scanning with the built-in leak patterns.
+    pub fn new(inner: Arc<dyn LlmProvider>, mode: OutboundCredentialMode) -> Self {
+        Self {
+            inner,
+            detector: Arc::new(LeakDetector::new()),
+            mode,
+        }
+    }
+
+    /// Scan with `detector` instead of the built-in patterns.
+    pub fn with_detector(mut self, detector: Arc<LeakDetector>) -> Self {
+        self.detector = detector;
+        self
+    }
+
+    /// Scan `messages`, redacting in place under
+    /// [`OutboundCredentialMode::Redact`]. Returns the number of findings.
+    pub fn guard(&self, messages: &mut [ChatMessage]) -> usize {
+        if self.mode == OutboundCredentialMode::Off {
+            return 0;
+        }
+        let mut found = 0;
+        for (index, message) in messages.iter_mut().enumerate() {
+            found += self.guard_text(&mut message.content, index, "content");
+            for call in message.tool_calls.iter_mut().flatten() {
+                found += self.guard_json(&mut call.arguments, index, &call.name);
+            }
+        }
+        found
+    }
+
+    fn guard_text(&self, text: &mut String, message: usize, field: &str) -> usize {
+        let scan = self.detector.scan(text);
+        if scan.is_clean() {
+            return 0;
+        }
+        let redact = self.mode == OutboundCredentialMode::Redact;
+        for m in &scan.matches {
+            tracing::warn!(
+                message,
+                field,
+                pattern = %m.pattern_name,
+                severity = %m.severity,
+                preview = %m.masked_preview,
+                redacted = redact,
+                "Credential found in outgoing LLM request"
+            );
+        }
+        if redact {
+            *text = scan.redact_all(text);
+        }
+        scan.matches.len()
+    }
+
+    fn guard_json(&self, value: &mut serde_json::Value, message: usize, field: &str) -> usize {
+        match value {
+            serde_json::Value::String(s) => self.guard_text(s, message, field),
+            serde_json::Value::Array(items) => items
+                .iter_mut()
+                .map(|item| self.guard_json(item, message, field))
+                .sum(),
+            serde_json::Value::Object(map) => map
+                .values_mut()
+                .map(|item| self.guard_json(item, message, field))
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for CredentialGuardProvider {
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        self.inner.cost_per_token()
+    }
+
+    async fn complete(
+        &self,
+        mut request: CompletionRequest,
+    ) -> Result<CompletionResponse, LlmError> {
+        self.guard(&mut request.messages);
+        self.inner.complete(request).await
+    }
+
+    async fn complete_with_tools(
+        &self,
+        mut request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        self.guard(&mut request.messages);
+        self.inner.complete_with_tools(request).await
+    }
+
+    async fn complete_stream(
+        &self,
+        mut request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        self.guard(&mut request.messages);
+        self.inner.complete_stream(request).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.inner.list_models().await
+    }
+
+    async fn model_metadata(&self) -> Result<ModelMetadata, LlmError> {
+        self.inner.model_metadata().await
+    }
+
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        self.inner.effective_model_name(requested_model)
+    }
+
+    fn active_model_name(&self) -> String {
+        self.inner.active_model_name()
+    }
+
+    fn set_model(&self, model: &str) -> Result<(), LlmError> {
+        self.inner.set_model(model)
+    }
+
+    fn calculate_cost(&self, input_tokens: u32, output_tokens: u32) -> Decimal {
+        self.inner.calculate_cost(input_tokens, output_tokens)
+    }
+
+    fn circuit_snapshot(&self) -> Option<CircuitSnapshot> {
+        self.inner.circuit_snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::llm::provider::{FinishReason, ToolCall};
+
+    const API_KEY: &str = "sk-proj-abc123def456ghi789jkl012mno345pqr";
+
+    /// Records the messages of the last request it was sent.
+    #[derive(Default)]
+    struct RecordingLlm {
+        sent: Mutex<Vec<ChatMessage>>,
+    }
+
+    impl RecordingLlm {
+        fn sent(&self) -> Vec<ChatMessage> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for RecordingLlm {
+        fn model_name(&self) -> &str {
+            "recording"
+        }
+
+        fn cost_per_token(&self) -> (Decimal, Decimal) {
+            (Decimal::ZERO, Decimal::ZERO)
+        }
+
+        async fn complete(
+            &self,
+            request: CompletionRequest,
+        ) -> Result<CompletionResponse, LlmError> {
+            *self.sent.lock().unwrap() = request.messages;
+            Ok(CompletionResponse {
+                content: "ok".to_string(),
+                input_tokens: 1,
+                output_tokens: 1,
+                finish_reason: FinishReason::Stop,
+            })
+        }
+
+        async fn complete_with_tools(
+            &self,
+            request: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, LlmError> {
+            *self.sent.lock().unwrap() = request.messages;
+            Ok(ToolCompletionResponse {
+                content: Some("ok".to_string()),
+                tool_calls: Vec::new(),
+                input_tokens: 1,
+                output_tokens: 1,
+                finish_reason: FinishReason::Stop,
+            })
+        }
+    }
+
+    fn guarded(mode: OutboundCredentialMode) -> (Arc<RecordingLlm>, CredentialGuardProvider) {
+        let inner = Arc::new(RecordingLlm::default());
+        let guard = CredentialGuardProvider::new(inner.clone(), mode);
+        (inner, guard)
+    }
+
+    #[tokio::test]
+    async fn warn_mode_flags_but_sends_unchanged() {
+        let (inner, guard) = guarded(OutboundCredentialMode::Warn);
+        let mut messages = vec![ChatMessage::user(format!("my key is {API_KEY}"))];
+        assert_eq!(guard.guard(&mut messages), 1);
+        assert!(messages[0].content.contains(API_KEY));
+
+        guard
+            .complete(CompletionRequest::new(messages))
+            .await
+            .unwrap();
+        assert!(inner.sent()[0].content.contains(API_KEY));
+    }
+
+    #[tokio::test]
+    async fn redact_mode_strips_key_before_sending() {
+        let (inner, guard) = guarded(OutboundCredentialMode::Redact);
+        let mut assistant = ChatMessage::assistant("calling");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "http".to_string(),
+            arguments: serde_json::json!({"headers": {"x-api-key": API_KEY}, "retries": 2}),
+            call_id: None,
+        }]);
+        let messages = vec![
+            ChatMessage::user(format!("my key is {API_KEY}, thanks")),
+            assistant,
+        ];
+
+        guard
+            .complete_with_tools(ToolCompletionRequest::new(messages, Vec::new()))
+            .await
+            .unwrap();
+
+        let sent = inner.sent();
+        assert_eq!(sent[0].content, "my key is [REDACTED], thanks");
+        let calls = sent[1].tool_calls.as_ref().unwrap();
+        assert_eq!(
+            calls[0].arguments,
+            serde_json::json!({"headers": {"x-api-key": "[REDACTED]"}, "retries": 2})
+        );
+    }
+
+    #[test]
+    fn clean_and_disabled_requests_are_untouched() {
+        let (_, guard) = guarded(OutboundCredentialMode::Redact);
+        let mut messages = vec![ChatMessage::user("what's the weather?")];
+        assert_eq!(guard.guard(&mut messages), 0);
+        assert_eq!(messages[0].content, "what's the weather?");
+
+        let (_, guard) = guarded(OutboundCredentialMode::Off);
+        let mut messages = vec![ChatMessage::user(format!("my key is {API_KEY}"))];
+        assert_eq!(guard.guard(&mut messages), 0);
+        assert!(messages[0].content.contains(API_KEY));
+    }
+}
@@ -17,26 +17,42 @@ use std::time::{Duration, Instant};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 
+use crate::clock::{Clock, system_clock};
 use crate::error::LlmError;
 use crate::llm::provider::{
-    CompletionRequest, CompletionResponse, LlmProvider, ModelMetadata, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
 
 use crate::llm::retry::is_retryable;
 
+/// Decides whether an error is worth failing over to the next provider.
+pub type ErrorFilter = Arc<dyn Fn(&LlmError) -> bool + Send + Sync>;
+
 /// Configuration for per-provider cooldown behavior.
 ///
 /// When a provider accumulates `failure_threshold` consecutive retryable
 /// failures, it enters cooldown for `cooldown_duration`. During cooldown
 /// the provider is skipped (unless *all* providers are in cooldown, in
 /// which case the oldest-cooled one is tried).
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct CooldownConfig {
     /// How long a provider stays in cooldown after exceeding the threshold.
     pub cooldown_duration: Duration,
     /// Number of consecutive retryable failures before cooldown activates.
     pub failure_threshold: u32,
+    /// Which errors count as retryable, i.e. fail over and count towards
+    /// cooldown. `None` uses the same set as `RetryProvider`.
+    pub error_filter: Option<ErrorFilter>,
+}
+
+impl CooldownConfig {
+    fn is_failover_worthy(&self, err: &LlmError) -> bool {
+        match &self.error_filter {
+            Some(filter) => filter(err),
+            None => is_retryable(err),
+        }
+    }
 }
 
 impl Default for CooldownConfig {
@@ -44,10 +60,21 @@ impl Default for CooldownConfig {
         Self {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 3,
+            error_filter: None,
         }
     }
 }
 
+impl std::fmt::Debug for CooldownConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CooldownConfig")
+            .field("cooldown_duration", &self.cooldown_duration)
+            .field("failure_threshold", &self.failure_threshold)
+            .field("error_filter", &self.error_filter.as_ref().map(|_| "<fn>"))
+            .finish()
+    }
+}
+
 /// Per-provider cooldown state, entirely lock-free.
 ///
 /// All atomic operations use `Relaxed` ordering — consistent with the
@@ -101,7 +128,9 @@ impl ProviderCooldown {
 ///
 /// The first provider in the list is the primary. If it fails with a retryable
 /// error, the next provider is tried, and so on. Non-retryable errors
-/// (e.g. `AuthFailed`, `ContextLengthExceeded`) propagate immediately.
+/// (e.g. `AuthFailed`, `ContextLengthExceeded`) propagate immediately. What
+/// counts as retryable can be narrowed with
+/// [`with_error_filter`](Self::with_error_filter).
 ///
 /// Providers that repeatedly fail with retryable errors are temporarily
 /// placed in cooldown and skipped, reducing latency.
@@ -116,6 +145,7 @@ pub struct FailoverProvider {
     /// Reference instant for computing elapsed nanos. Shared across all
     /// cooldown timestamps so they are comparable.
     epoch: Instant,
+    clock: Arc<dyn Clock>,
     /// Cooldown configuration.
     cooldown_config: CooldownConfig,
     /// Request-scoped provider index keyed by Tokio task ID.
@@ -150,22 +180,47 @@ impl FailoverProvider {
         let cooldowns = (0..providers.len())
             .map(|_| ProviderCooldown::new())
             .collect();
+        let clock = system_clock();
         Ok(Self {
             providers,
             last_used: AtomicUsize::new(0),
             cooldowns,
-            epoch: Instant::now(),
+            epoch: clock.now(),
+            clock,
             cooldown_config,
             provider_for_task: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Fail over only on errors accepted by `filter`; anything else is
+    /// returned to the caller straight away.
+    ///
+    /// Replaces the default retryable set, e.g. to fail over on rate limits
+    /// and timeouts but not on a failure that every provider would repeat.
+    pub fn with_error_filter(
+        mut self,
+        filter: impl Fn(&LlmError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.cooldown_config.error_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.epoch = clock.now();
+        self.clock = clock;
+        self
+    }
+
     /// Nanoseconds elapsed since `self.epoch`.
     ///
     /// Truncates `u128` → `u64` (wraps after ~584 years of continuous
     /// uptime). Acceptable because `epoch` is set at construction time.
     fn now_nanos(&self) -> u64 {
-        self.epoch.elapsed().as_nanos() as u64
+        self.clock
+            .now()
+            .saturating_duration_since(self.epoch)
+            .as_nanos() as u64
     }
 
     /// Current Tokio task ID if available.
@@ -249,7 +304,7 @@ impl FailoverProvider {
                     return Ok((i, response));
                 }
                 Err(err) => {
-                    if !is_retryable(&err) {
+                    if !self.cooldown_config.is_failover_worthy(&err) {
                         return Err(err);
                     }
 
@@ -321,6 +376,22 @@ impl LlmProvider for FailoverProvider {
         Ok(response)
     }
 
+    /// Fails over only while opening the stream; once a provider has
+    /// started answering, its errors go to the caller.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let (provider_idx, stream) = self
+            .try_providers(|provider| {
+                let req = request.clone();
+                async move { provider.complete_stream(req).await }
+            })
+            .await?;
+        self.bind_provider_to_current_task(provider_idx);
+        Ok(stream)
+    }
+
     fn active_model_name(&self) -> String {
         self.providers[self.last_used.load(Ordering::Relaxed)].active_model_name()
     }
@@ -380,6 +451,7 @@ mod tests {
     use std::sync::{Mutex, RwLock};
     use std::time::Duration;
 
+    use crate::clock::MockClock;
     use crate::llm::provider::{CompletionResponse, FinishReason, ToolCompletionResponse};
 
     /// A mock LLM provider that returns a predetermined result.
@@ -649,6 +721,7 @@ mod tests {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(60),
             failure_threshold: 3,
+            ..CooldownConfig::default()
         };
         let primary = Arc::new(MultiCallMockProvider::fail_then_ok("primary", 1));
         let fallback = Arc::new(MultiCallMockProvider::always_ok("fallback"));
@@ -833,6 +906,7 @@ mod tests {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 2,
+            ..CooldownConfig::default()
         };
         let p1 = Arc::new(MultiCallMockProvider::always_fail("p1"));
         let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
@@ -864,6 +938,7 @@ mod tests {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_millis(1),
             failure_threshold: 1,
+            ..CooldownConfig::default()
         };
         // p1 fails once then succeeds (fail_then_ok with n=1 would work,
         // but we use always_fail to prove it's skipped, then swap).
@@ -896,12 +971,42 @@ mod tests {
         assert_eq!(p1.call_count(), 3);
     }
 
+    // Cooldown test 2b: Same transitions on a mock clock, no sleeping.
+    #[tokio::test]
+    async fn cooldown_expires_on_mock_clock() {
+        let config = CooldownConfig {
+            cooldown_duration: Duration::from_secs(300),
+            failure_threshold: 1,
+            ..CooldownConfig::default()
+        };
+        let p1 = Arc::new(MultiCallMockProvider::fail_then_ok("p1", 1));
+        let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
+        let clock = Arc::new(MockClock::new());
+        let failover = FailoverProvider::with_cooldown(vec![p1.clone(), p2.clone()], config)
+            .unwrap()
+            .with_clock(clock.clone());
+
+        failover.complete(make_request()).await.unwrap();
+        assert_eq!(p1.call_count(), 1);
+
+        clock.advance(Duration::from_secs(299));
+        let r = failover.complete(make_request()).await.unwrap();
+        assert_eq!(r.content, "p2 ok");
+        assert_eq!(p1.call_count(), 1, "still cooling down");
+
+        clock.advance(Duration::from_secs(1));
+        let r = failover.complete(make_request()).await.unwrap();
+        assert_eq!(r.content, "p1 ok");
+        assert_eq!(p1.call_count(), 2);
+    }
+
     // Cooldown test 3: Never skip all providers — oldest-cooled one is tried.
     #[tokio::test]
     async fn never_skip_all_providers() {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 1,
+            ..CooldownConfig::default()
         };
         // Both providers always fail.
         let p1 = Arc::new(MultiCallMockProvider::always_fail("p1"));
@@ -935,6 +1040,7 @@ mod tests {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 3,
+            ..CooldownConfig::default()
         };
         // p1 fails for calls 0,1 then succeeds on call 2+.
         let p1 = Arc::new(MultiCallMockProvider::fail_then_ok("p1", 2));
@@ -984,6 +1090,7 @@ mod tests {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 3,
+            ..CooldownConfig::default()
         };
         let p1 = Arc::new(MultiCallMockProvider::always_fail("p1"));
         let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
@@ -1024,6 +1131,7 @@ mod tests {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 1,
+            ..CooldownConfig::default()
         };
         let p1 = Arc::new(MultiCallMockProvider::always_fail_non_retryable("p1"));
         let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
@@ -1044,12 +1152,52 @@ mod tests {
         assert!(!failover.cooldowns[0].is_in_cooldown(nanos, cooldown_nanos));
     }
 
+    // Error filter: auth failures fail fast even when other kinds fail over.
+    #[tokio::test]
+    async fn error_filter_auth_failure_does_not_fail_over() {
+        let p1 = Arc::new(MultiCallMockProvider::always_fail_non_retryable("p1"));
+        let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
+
+        let failover = FailoverProvider::new(vec![p1.clone(), p2.clone()])
+            .unwrap()
+            .with_error_filter(|err| {
+                matches!(
+                    err,
+                    LlmError::RateLimited { .. }
+                        | LlmError::RequestFailed { .. }
+                        | LlmError::Http(_)
+                )
+            });
+
+        let err = failover.complete(make_request()).await.unwrap_err();
+        assert!(matches!(err, LlmError::AuthFailed { .. }));
+        assert_eq!(p1.call_count(), 1);
+        assert_eq!(p2.call_count(), 0);
+    }
+
+    // Error filter: replaces the default retryable set.
+    #[tokio::test]
+    async fn error_filter_overrides_default_retryable_set() {
+        let p1 = Arc::new(MultiCallMockProvider::always_fail("p1"));
+        let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
+
+        let failover = FailoverProvider::new(vec![p1.clone(), p2.clone()])
+            .unwrap()
+            .with_error_filter(|err| matches!(err, LlmError::RateLimited { .. }));
+
+        // RequestFailed is retryable by default, but not under this filter.
+        let err = failover.complete(make_request()).await.unwrap_err();
+        assert!(matches!(err, LlmError::RequestFailed { .. }));
+        assert_eq!(p2.call_count(), 0);
+    }
+
     // Cooldown test 7: Three providers, first in cooldown, second/third available.
     #[tokio::test]
     async fn three_providers_mixed_cooldown() {
         let config = CooldownConfig {
             cooldown_duration: Duration::from_secs(300),
             failure_threshold: 1,
+            ..CooldownConfig::default()
         };
         let p1 = Arc::new(MultiCallMockProvider::always_fail("p1"));
         let p2 = Arc::new(MultiCallMockProvider::always_ok("p2"));
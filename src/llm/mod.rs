@@ -9,31 +9,46 @@
 
 pub mod circuit_breaker;
 pub mod costs;
+pub mod credential_guard;
 pub mod failover;
 mod nearai_chat;
+pub mod observed;
 mod provider;
 mod reasoning;
 pub mod response_cache;
+pub mod responses_fallback;
 pub mod retry;
 mod rig_adapter;
+mod sampling;
 pub mod session;
+pub mod session_store;
 pub mod smart_routing;
 
-pub use circuit_breaker::{CircuitBreakerConfig, CircuitBreakerProvider};
-pub use failover::{CooldownConfig, FailoverProvider};
+pub use circuit_breaker::{
+    CircuitBreakerConfig, CircuitBreakerProvider, CircuitSnapshot, CircuitState, TripPolicy,
+};
+pub use credential_guard::CredentialGuardProvider;
+pub use failover::{CooldownConfig, ErrorFilter, FailoverProvider};
 pub use nearai_chat::{ModelInfo, NearAiChatProvider};
+pub use observed::ObservedProvider;
 pub use provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ModelMetadata,
-    Role, ToolCall, ToolCompletionRequest, ToolCompletionResponse, ToolDefinition, ToolResult,
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, FinishReason,
+    LlmProvider, ModelMetadata, Role, StreamEvent, ToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition, ToolResult,
 };
 pub use reasoning::{
     ActionPlan, Reasoning, ReasoningContext, RespondOutput, RespondResult, SILENT_REPLY_TOKEN,
-    TokenUsage, ToolSelection, is_silent_reply,
+    StreamSink, TokenUsage, ToolSelection, UsageTotals, UsageTracker, is_silent_reply,
 };
-pub use response_cache::{CachedProvider, ResponseCacheConfig};
+pub use response_cache::{CacheKeyMode, CacheStats, CachedProvider, ResponseCacheConfig};
+pub use responses_fallback::ResponsesFallbackProvider;
 pub use retry::{RetryConfig, RetryProvider};
-pub use rig_adapter::RigAdapter;
+pub use rig_adapter::{RigAdapter, SamplingSupport};
+pub use sampling::{SamplingProfile, SamplingProfiles};
 pub use session::{SessionConfig, SessionManager, create_session_manager};
+pub use session_store::{FileSessionStore, SessionStore};
+#[cfg(feature = "postgres")]
+pub use session_store::{PostgresSessionStore, select_session_store};
 pub use smart_routing::{SmartRoutingConfig, SmartRoutingProvider, TaskComplexity};
 
 use std::sync::Arc;
@@ -44,6 +59,51 @@ use secrecy::ExposeSecret;
 use crate::config::{LlmBackend, LlmConfig, NearAiConfig};
 use crate::error::LlmError;
 
+/// Default `User-Agent` sent with every LLM request (`ironclaw/<version>`).
+pub fn default_user_agent() -> String {
+    format!("ironclaw/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Headers attached to every request made by a rig-core client.
+///
+/// rig-core owns the per-request HTTP call, so only static headers can be
+/// injected here; per-request IDs are only sent by providers that build
+/// their own requests (e.g. `NearAiChatProvider`).
+fn client_headers(user_agent: &str) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    match reqwest::header::HeaderValue::from_str(user_agent) {
+        Ok(value) => {
+            headers.insert(reqwest::header::USER_AGENT, value);
+        }
+        Err(e) => {
+            tracing::warn!(user_agent, error = %e, "Ignoring invalid LLM_USER_AGENT value");
+            if let Ok(value) = reqwest::header::HeaderValue::from_str(&default_user_agent()) {
+                headers.insert(reqwest::header::USER_AGENT, value);
+            }
+        }
+    }
+    headers
+}
+
+/// HTTP client for rig-core backends, routed through `LLM_PROXY_URL` when set.
+/// Hosts listed in `NO_PROXY` (e.g. a local Ollama) are reached directly.
+fn http_client(config: &LlmConfig, provider: &str) -> Result<reqwest::Client, LlmError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(ref url) = config.proxy_url {
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| LlmError::RequestFailed {
+                provider: provider.to_string(),
+                reason: format!("Invalid LLM_PROXY_URL: {}", e),
+            })?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(|e| LlmError::RequestFailed {
+        provider: provider.to_string(),
+        reason: format!("Failed to build HTTP client: {}", e),
+    })
+}
+
 /// Create an LLM provider based on configuration.
 ///
 /// - `NearAi` backend: Uses session manager for authentication (Responses API)
@@ -54,12 +114,13 @@ pub fn create_llm_provider(
     session: Arc<SessionManager>,
 ) -> Result<Arc<dyn LlmProvider>, LlmError> {
     match config.backend {
-        LlmBackend::NearAi => create_llm_provider_with_config(&config.nearai, session),
+        LlmBackend::NearAi => create_nearai_provider(&config.nearai, &config.user_agent, session),
         LlmBackend::OpenAi => create_openai_provider(config),
         LlmBackend::Anthropic => create_anthropic_provider(config),
         LlmBackend::Ollama => create_ollama_provider(config),
         LlmBackend::OpenAiCompatible => create_openai_compatible_provider(config),
         LlmBackend::Tinfoil => create_tinfoil_provider(config),
+        LlmBackend::Gemini => create_gemini_provider(config),
     }
 }
 
@@ -70,6 +131,14 @@ pub fn create_llm_provider(
 pub fn create_llm_provider_with_config(
     config: &NearAiConfig,
     session: Arc<SessionManager>,
+) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    create_nearai_provider(config, &default_user_agent(), session)
+}
+
+fn create_nearai_provider(
+    config: &NearAiConfig,
+    user_agent: &str,
+    session: Arc<SessionManager>,
 ) -> Result<Arc<dyn LlmProvider>, LlmError> {
     let auth_mode = if config.api_key.is_some() {
         "API key"
@@ -82,7 +151,9 @@ pub fn create_llm_provider_with_config(
         auth = auth_mode,
         "Using NEAR AI (Chat Completions API)"
     );
-    Ok(Arc::new(NearAiChatProvider::new(config.clone(), session)?))
+    Ok(Arc::new(
+        NearAiChatProvider::new(config.clone(), session)?.with_user_agent(user_agent),
+    ))
 }
 
 fn create_openai_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
@@ -92,35 +163,55 @@ fn create_openai_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, Ll
 
     use rig::providers::openai;
 
-    // Use CompletionsClient (Chat Completions API) instead of the default Client
-    // (Responses API). The Responses API path in rig-core panics when tool results
-    // are sent back because ironclaw doesn't thread `call_id` through its ToolCall
-    // type. The Chat Completions API works correctly with the existing code.
-    let client: openai::CompletionsClient = if let Some(ref base_url) = oai.base_url {
+    let http = http_client(config, "openai")?;
+    // The default Client talks to the Responses API. Tool results are matched
+    // to their calls by `call_id`, which ToolCall carries through from rig.
+    let client: openai::Client = if let Some(ref base_url) = oai.base_url {
         tracing::info!(
-            "Using OpenAI direct API (chat completions, model: {}, base_url: {})",
+            "Using OpenAI direct API (responses, model: {}, base_url: {})",
             oai.model,
             base_url,
         );
         openai::Client::builder()
             .base_url(base_url)
             .api_key(oai.api_key.expose_secret())
+            .http_headers(client_headers(&config.user_agent))
+            .http_client(http)
             .build()
     } else {
         tracing::info!(
-            "Using OpenAI direct API (chat completions, model: {}, base_url: default)",
+            "Using OpenAI direct API (responses, model: {}, base_url: default)",
             oai.model,
         );
-        openai::Client::new(oai.api_key.expose_secret())
+        openai::Client::builder()
+            .api_key(oai.api_key.expose_secret())
+            .http_headers(client_headers(&config.user_agent))
+            .http_client(http)
+            .build()
     }
     .map_err(|e| LlmError::RequestFailed {
         provider: "openai".to_string(),
         reason: format!("Failed to create OpenAI client: {}", e),
-    })?
-    .completions_api();
+    })?;
 
     let model = client.completion_model(&oai.model);
-    Ok(Arc::new(RigAdapter::new(model, &oai.model)))
+    // The Responses API takes top_p but not the penalty parameters.
+    let responses = RigAdapter::new(model, &oai.model).with_sampling_support(SamplingSupport {
+        top_p: true,
+        penalties: false,
+    });
+    // Histories replayed from another backend or a previous process can carry
+    // call IDs the Responses API never issued; those turns go through Chat
+    // Completions instead of failing.
+    let chat_model = client.completions_api().completion_model(&oai.model);
+    let chat = RigAdapter::new(chat_model, &oai.model).with_sampling_support(SamplingSupport {
+        top_p: true,
+        penalties: true,
+    });
+    Ok(Arc::new(ResponsesFallbackProvider::new(
+        Arc::new(responses),
+        Arc::new(chat),
+    )))
 }
 
 fn create_anthropic_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
@@ -133,13 +224,20 @@ fn create_anthropic_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>,
 
     use rig::providers::anthropic;
 
+    let http = http_client(config, "anthropic")?;
     let client: anthropic::Client = if let Some(ref base_url) = anth.base_url {
         anthropic::Client::builder()
             .api_key(anth.api_key.expose_secret())
             .base_url(base_url)
+            .http_headers(client_headers(&config.user_agent))
+            .http_client(http)
             .build()
     } else {
-        anthropic::Client::new(anth.api_key.expose_secret())
+        anthropic::Client::builder()
+            .api_key(anth.api_key.expose_secret())
+            .http_headers(client_headers(&config.user_agent))
+            .http_client(http)
+            .build()
     }
     .map_err(|e| LlmError::RequestFailed {
         provider: "anthropic".to_string(),
@@ -152,7 +250,14 @@ fn create_anthropic_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>,
         anth.model,
         anth.base_url.as_deref().unwrap_or("default"),
     );
-    Ok(Arc::new(RigAdapter::new(model, &anth.model)))
+    // Claude models reject requests that set both temperature and top_p,
+    // and temperature is always sent, so top_p is dropped here.
+    Ok(Arc::new(
+        RigAdapter::new(model, &anth.model).with_sampling_support(SamplingSupport {
+            top_p: false,
+            penalties: false,
+        }),
+    ))
 }
 
 fn create_ollama_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
@@ -166,6 +271,8 @@ fn create_ollama_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, Ll
     let client: ollama::Client = ollama::Client::builder()
         .base_url(&oll.base_url)
         .api_key(Nothing)
+        .http_headers(client_headers(&config.user_agent))
+        .http_client(http_client(config, "ollama")?)
         .build()
         .map_err(|e| LlmError::RequestFailed {
             provider: "ollama".to_string(),
@@ -181,6 +288,28 @@ fn create_ollama_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, Ll
     Ok(Arc::new(RigAdapter::new(model, &oll.model)))
 }
 
+fn create_gemini_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let gem = config.gemini.as_ref().ok_or_else(|| LlmError::AuthFailed {
+        provider: "gemini".to_string(),
+    })?;
+
+    use rig::providers::gemini;
+
+    let client: gemini::Client = gemini::Client::builder()
+        .api_key(gem.api_key.expose_secret())
+        .http_headers(client_headers(&config.user_agent))
+        .http_client(http_client(config, "gemini")?)
+        .build()
+        .map_err(|e| LlmError::RequestFailed {
+            provider: "gemini".to_string(),
+            reason: format!("Failed to create Gemini client: {}", e),
+        })?;
+
+    let model = client.completion_model(&gem.model);
+    tracing::info!("Using Google Gemini API (model: {})", gem.model);
+    Ok(Arc::new(RigAdapter::new(model, &gem.model)))
+}
+
 const TINFOIL_BASE_URL: &str = "https://inference.tinfoil.sh/v1";
 
 fn create_tinfoil_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
@@ -196,6 +325,8 @@ fn create_tinfoil_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, L
     let client: openai::Client = openai::Client::builder()
         .base_url(TINFOIL_BASE_URL)
         .api_key(tf.api_key.expose_secret())
+        .http_headers(client_headers(&config.user_agent))
+        .http_client(http_client(config, "tinfoil")?)
         .build()
         .map_err(|e| LlmError::RequestFailed {
             provider: "tinfoil".to_string(),
@@ -207,7 +338,12 @@ fn create_tinfoil_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, L
     let client = client.completions_api();
     let model = client.completion_model(&tf.model);
     tracing::info!("Using Tinfoil private inference (model: {})", tf.model);
-    Ok(Arc::new(RigAdapter::new(model, &tf.model)))
+    Ok(Arc::new(
+        RigAdapter::new(model, &tf.model).with_sampling_support(SamplingSupport {
+            top_p: true,
+            penalties: true,
+        }),
+    ))
 }
 
 fn create_openai_compatible_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
@@ -220,7 +356,7 @@ fn create_openai_compatible_provider(config: &LlmConfig) -> Result<Arc<dyn LlmPr
 
     use rig::providers::openai;
 
-    let mut extra_headers = reqwest::header::HeaderMap::new();
+    let mut extra_headers = client_headers(&config.user_agent);
     for (key, value) in &compat.extra_headers {
         let name = match reqwest::header::HeaderName::from_bytes(key.as_bytes()) {
             Ok(n) => n,
@@ -249,6 +385,7 @@ fn create_openai_compatible_provider(config: &LlmConfig) -> Result<Arc<dyn LlmPr
                 .unwrap_or_else(|| "no-key".to_string()),
         )
         .http_headers(extra_headers)
+        .http_client(http_client(config, "openai_compatible")?)
         .build()
         .map_err(|e| LlmError::RequestFailed {
             provider: "openai_compatible".to_string(),
@@ -262,7 +399,12 @@ fn create_openai_compatible_provider(config: &LlmConfig) -> Result<Arc<dyn LlmPr
         compat.base_url,
         compat.model
     );
-    Ok(Arc::new(RigAdapter::new(model, &compat.model)))
+    Ok(Arc::new(
+        RigAdapter::new(model, &compat.model).with_sampling_support(SamplingSupport {
+            top_p: true,
+            penalties: true,
+        }),
+    ))
 }
 
 /// Create a cheap/fast LLM provider for lightweight tasks (heartbeat, routing, evaluation).
@@ -289,10 +431,9 @@ pub fn create_cheap_llm_provider(
     let mut cheap_config = config.nearai.clone();
     cheap_config.model = cheap_model.clone();
 
-    Ok(Some(Arc::new(NearAiChatProvider::new(
-        cheap_config,
-        session,
-    )?)))
+    Ok(Some(Arc::new(
+        NearAiChatProvider::new(cheap_config, session)?.with_user_agent(&config.user_agent),
+    )))
 }
 
 /// Build the full LLM provider chain with all configured wrappers.
@@ -303,17 +444,19 @@ pub fn create_cheap_llm_provider(
 /// 3. SmartRoutingProvider (cheap/primary split when cheap model is configured)
 /// 4. FailoverProvider (fallback model when primary fails)
 /// 5. CircuitBreakerProvider (fast-fail when backend is degraded)
-/// 6. CachedProvider (in-memory response cache)
+/// 6. CachedProvider (in-memory response cache; semantic matching when a
+///    threshold is configured and `embeddings` is given)
 ///
 /// Also returns a separate cheap LLM provider for heartbeat/evaluation (not
 /// part of the chain — it's a standalone provider for explicitly cheap tasks).
 ///
 /// This is the single source of truth for provider chain construction,
-/// called by both `main.rs` and `app.rs`.
+/// called by `app.rs`.
 #[allow(clippy::type_complexity)]
 pub fn build_provider_chain(
     config: &LlmConfig,
     session: Arc<SessionManager>,
+    embeddings: Option<Arc<dyn crate::workspace::EmbeddingProvider>>,
 ) -> Result<(Arc<dyn LlmProvider>, Option<Arc<dyn LlmProvider>>), LlmError> {
     let llm = create_llm_provider(config, session.clone())?;
     tracing::info!("LLM provider initialized: {}", llm.model_name());
@@ -321,6 +464,7 @@ pub fn build_provider_chain(
     // 1. Retry
     let retry_config = RetryConfig {
         max_retries: config.nearai.max_retries,
+        ..RetryConfig::default()
     };
     let llm: Arc<dyn LlmProvider> = if retry_config.max_retries > 0 {
         tracing::info!(
@@ -336,7 +480,7 @@ pub fn build_provider_chain(
     let llm: Arc<dyn LlmProvider> = if let Some(ref cheap_model) = config.nearai.cheap_model {
         let mut cheap_config = config.nearai.clone();
         cheap_config.model = cheap_model.clone();
-        let cheap = create_llm_provider_with_config(&cheap_config, session.clone())?;
+        let cheap = create_nearai_provider(&cheap_config, &config.user_agent, session.clone())?;
         let cheap: Arc<dyn LlmProvider> = if retry_config.max_retries > 0 {
             Arc::new(RetryProvider::new(cheap, retry_config.clone()))
         } else {
@@ -368,7 +512,8 @@ pub fn build_provider_chain(
         }
         let mut fallback_config = config.nearai.clone();
         fallback_config.model = fallback_model.clone();
-        let fallback = create_llm_provider_with_config(&fallback_config, session.clone())?;
+        let fallback =
+            create_nearai_provider(&fallback_config, &config.user_agent, session.clone())?;
         tracing::info!(
             primary = %llm.model_name(),
             fallback = %fallback.model_name(),
@@ -382,6 +527,7 @@ pub fn build_provider_chain(
         let cooldown_config = CooldownConfig {
             cooldown_duration: std::time::Duration::from_secs(config.nearai.failover_cooldown_secs),
             failure_threshold: config.nearai.failover_cooldown_threshold,
+            ..CooldownConfig::default()
         };
         Arc::new(FailoverProvider::with_cooldown(
             vec![llm, fallback],
@@ -392,17 +538,28 @@ pub fn build_provider_chain(
     };
 
     // 4. Circuit breaker
-    let llm: Arc<dyn LlmProvider> = if let Some(threshold) = config.nearai.circuit_breaker_threshold
-    {
+    let threshold = config.nearai.circuit_breaker_threshold;
+    let error_rate = config.nearai.circuit_breaker_error_rate;
+    let llm: Arc<dyn LlmProvider> = if threshold.is_some() || error_rate.is_some() {
+        let defaults = CircuitBreakerConfig::default();
+        let trip_policy = match error_rate {
+            Some(percent) => TripPolicy::ErrorRate {
+                window: config.nearai.circuit_breaker_window,
+                max_error_rate: percent / 100.0,
+            },
+            None => TripPolicy::Consecutive,
+        };
         let cb_config = CircuitBreakerConfig {
-            failure_threshold: threshold,
+            failure_threshold: threshold.unwrap_or(defaults.failure_threshold),
             recovery_timeout: std::time::Duration::from_secs(
                 config.nearai.circuit_breaker_recovery_secs,
             ),
-            ..CircuitBreakerConfig::default()
+            trip_policy,
+            ..defaults
         };
         tracing::info!(
-            threshold,
+            threshold = cb_config.failure_threshold,
+            policy = ?cb_config.trip_policy,
             recovery_secs = config.nearai.circuit_breaker_recovery_secs,
             "LLM circuit breaker enabled"
         );
@@ -413,16 +570,36 @@ pub fn build_provider_chain(
 
     // 5. Response cache
     let llm: Arc<dyn LlmProvider> = if config.nearai.response_cache_enabled {
+        let semantic = match (config.nearai.response_cache_semantic_threshold, embeddings) {
+            (Some(threshold), Some(embeddings)) => Some((threshold, embeddings)),
+            (Some(_), None) => {
+                tracing::warn!(
+                    "RESPONSE_CACHE_SEMANTIC_THRESHOLD is set but embeddings are disabled; \
+                     the response cache matches exactly"
+                );
+                None
+            }
+            _ => None,
+        };
         let rc_config = ResponseCacheConfig {
             ttl: std::time::Duration::from_secs(config.nearai.response_cache_ttl_secs),
             max_entries: config.nearai.response_cache_max_entries,
+            key_mode: match semantic {
+                Some((threshold, _)) => CacheKeyMode::Semantic { threshold },
+                None => CacheKeyMode::Exact,
+            },
         };
         tracing::info!(
             ttl_secs = config.nearai.response_cache_ttl_secs,
             max_entries = config.nearai.response_cache_max_entries,
+            key_mode = ?rc_config.key_mode,
             "LLM response cache enabled"
         );
-        Arc::new(CachedProvider::new(llm, rc_config))
+        let cached = CachedProvider::new(llm, rc_config);
+        match semantic {
+            Some((_, embeddings)) => Arc::new(cached.with_embeddings(embeddings)),
+            None => Arc::new(cached),
+        }
     } else {
         llm
     };
@@ -449,14 +626,18 @@ mod tests {
             base_url: "https://api.near.ai".to_string(),
             auth_base_url: "https://private.near.ai".to_string(),
             session_path: PathBuf::from("/tmp/test-session.json"),
+            session_store: crate::config::SessionStoreKind::default(),
             api_key: None,
             fallback_model: None,
             max_retries: 3,
             circuit_breaker_threshold: None,
             circuit_breaker_recovery_secs: 30,
+            circuit_breaker_error_rate: None,
+            circuit_breaker_window: 20,
             response_cache_enabled: false,
             response_cache_ttl_secs: 3600,
             response_cache_max_entries: 1000,
+            response_cache_semantic_threshold: None,
             failover_cooldown_secs: 300,
             failover_cooldown_threshold: 3,
             smart_routing_cascade: true,
@@ -472,6 +653,9 @@ mod tests {
             ollama: None,
             openai_compatible: None,
             tinfoil: None,
+            gemini: None,
+            user_agent: default_user_agent(),
+            proxy_url: None,
         }
     }
 
@@ -499,6 +683,28 @@ mod tests {
         assert_eq!(provider.unwrap().model_name(), "cheap-test-model");
     }
 
+    #[test]
+    fn test_default_user_agent_includes_version() {
+        let ua = default_user_agent();
+        assert!(ua.starts_with("ironclaw/"));
+        assert!(ua.ends_with(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_client_headers_falls_back_on_invalid_user_agent() {
+        let headers = client_headers("bad\nagent");
+        assert_eq!(
+            headers.get(reqwest::header::USER_AGENT).unwrap(),
+            default_user_agent().as_str()
+        );
+
+        let headers = client_headers("custom/1.0");
+        assert_eq!(
+            headers.get(reqwest::header::USER_AGENT).unwrap(),
+            "custom/1.0"
+        );
+    }
+
     #[test]
     fn test_create_cheap_llm_provider_ignored_for_non_nearai_backend() {
         let mut config = test_llm_config();
@@ -6,10 +6,13 @@
 //! - **Session token auth**: Otherwise, uses `SessionManager` for Bearer session token
 //!   with automatic renewal on 401 errors
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 use reqwest::Client;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::MathematicalOps;
@@ -19,11 +22,20 @@ use serde::{Deserialize, Serialize};
 use crate::config::NearAiConfig;
 use crate::error::LlmError;
 use crate::llm::provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider, Role, ToolCall,
-    ToolCompletionRequest, ToolCompletionResponse,
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, FinishReason,
+    LlmProvider, Role, StreamEvent, ToolCall, ToolCompletionRequest, ToolCompletionResponse,
 };
+use crate::llm::retry::{parse_retry_after, retry_after_from_message};
 use crate::llm::{costs, session::SessionManager};
 
+/// Header carrying a unique per-request ID for correlating with provider logs.
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Generate a fresh request ID.
+fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
 /// Information about an available model from NEAR AI API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
@@ -44,8 +56,9 @@ pub struct NearAiChatProvider {
     active_model: std::sync::RwLock<String>,
     flatten_tool_messages: bool,
     /// Per-model pricing fetched from the NEAR AI `/v1/model/list` endpoint.
-    /// Maps model ID → (input_cost_per_token, output_cost_per_token).
-    pricing: Arc<std::sync::RwLock<HashMap<String, (Decimal, Decimal)>>>,
+    pricing: Arc<std::sync::RwLock<PricingCache>>,
+    /// `User-Agent` header sent with every request.
+    user_agent: String,
 }
 
 impl NearAiChatProvider {
@@ -76,7 +89,7 @@ impl NearAiChatProvider {
             })?;
 
         let active_model = std::sync::RwLock::new(config.model.clone());
-        let pricing = Arc::new(std::sync::RwLock::new(HashMap::new()));
+        let pricing = Arc::new(std::sync::RwLock::new(PricingCache::default()));
 
         let provider = Self {
             client,
@@ -85,6 +98,7 @@ impl NearAiChatProvider {
             active_model,
             flatten_tool_messages,
             pricing,
+            user_agent: crate::llm::default_user_agent(),
         };
 
         // Fire-and-forget background pricing fetch — don't block startup.
@@ -100,10 +114,7 @@ impl NearAiChatProvider {
                 match fetch_pricing(&client, &base_url, api_key.as_ref(), &session).await {
                     Ok(map) if !map.is_empty() => {
                         tracing::info!("Loaded NEAR AI pricing for {} model(s)", map.len());
-                        match pricing.write() {
-                            Ok(mut guard) => *guard = map,
-                            Err(poisoned) => *poisoned.into_inner() = map,
-                        }
+                        write_pricing(&pricing).replace(map);
                     }
                     Ok(_) => {
                         tracing::debug!("NEAR AI pricing endpoint returned no pricing data");
@@ -121,6 +132,34 @@ impl NearAiChatProvider {
         Ok(provider)
     }
 
+    /// Override the `User-Agent` header sent with each request.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Per-token (input, output) price for `model` from NEAR AI's pricing data.
+    ///
+    /// Served from the cache filled at startup when the model is in it;
+    /// otherwise the model list is fetched again and the cache refreshed.
+    /// Returns `ModelNotAvailable` when NEAR AI publishes no price for it;
+    /// that answer is cached too, so unpriced models are not re-fetched.
+    pub async fn fetch_model_pricing(&self, model: &str) -> Result<(Decimal, Decimal), LlmError> {
+        lookup_pricing(
+            &self.client,
+            &self.config.base_url,
+            self.config.api_key.as_ref(),
+            &self.session,
+            &self.pricing,
+            model,
+        )
+        .await
+    }
+
+    fn cached_pricing(&self, model: &str) -> Option<(Decimal, Decimal)> {
+        read_pricing(&self.pricing).rates.get(model).copied()
+    }
+
     fn api_url(&self, path: &str) -> String {
         let base = self.config.base_url.trim_end_matches('/');
         let path = path.trim_start_matches('/');
@@ -176,8 +215,9 @@ impl NearAiChatProvider {
     ) -> Result<R, LlmError> {
         let url = self.api_url("chat/completions");
         let token = self.resolve_bearer_token().await?;
+        let request_id = new_request_id();
 
-        tracing::debug!("Sending request to NEAR AI Chat: {}", url);
+        tracing::debug!(request_id = %request_id, "Sending request to NEAR AI Chat: {}", url);
 
         if tracing::enabled!(tracing::Level::DEBUG)
             && let Ok(json) = serde_json::to_string(body)
@@ -190,64 +230,187 @@ impl NearAiChatProvider {
             .post(&url)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .header(REQUEST_ID_HEADER, &request_id)
             .json(body)
             .send()
             .await
             .map_err(|e| LlmError::RequestFailed {
                 provider: "nearai_chat".to_string(),
-                reason: e.to_string(),
+                reason: format!("{} (request_id: {})", e, request_id),
             })?;
 
         let status = response.status();
+        let retry_after = retry_after_header(&response);
         let response_text = response.text().await.map_err(|e| LlmError::RequestFailed {
             provider: "nearai_chat".to_string(),
             reason: format!("Failed to read response body: {}", e),
         })?;
 
-        tracing::debug!("NEAR AI Chat response status: {}", status);
+        tracing::debug!(request_id = %request_id, "NEAR AI Chat response status: {}", status);
+        if !status.is_success() {
+            tracing::warn!(
+                request_id = %request_id,
+                status = %status,
+                "NEAR AI Chat request failed"
+            );
+        }
         tracing::debug!("NEAR AI Chat response body: {}", response_text);
 
         if !status.is_success() {
-            let status_code = status.as_u16();
-
-            if status_code == 401 {
-                // For session token auth, distinguish session expired from plain auth failure
-                if !self.uses_api_key() {
-                    let lower = response_text.to_lowercase();
-                    let is_session_expired = lower.contains("session")
-                        && (lower.contains("expired") || lower.contains("invalid"));
-                    if is_session_expired {
-                        return Err(LlmError::SessionExpired {
-                            provider: "nearai_chat".to_string(),
-                        });
-                    }
+            return Err(self.status_error(status, retry_after, &response_text));
+        }
+
+        parse_response_body(&response_text)
+    }
+
+    /// Map a non-success HTTP status and body to the matching `LlmError`.
+    fn status_error(
+        &self,
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
+        response_text: &str,
+    ) -> LlmError {
+        let status_code = status.as_u16();
+
+        if status_code == 401 {
+            // For session token auth, distinguish session expired from plain auth failure
+            if !self.uses_api_key() {
+                let lower = response_text.to_lowercase();
+                let is_session_expired = lower.contains("session")
+                    && (lower.contains("expired") || lower.contains("invalid"));
+                if is_session_expired {
+                    return LlmError::SessionExpired {
+                        provider: "nearai_chat".to_string(),
+                    };
                 }
-                return Err(LlmError::AuthFailed {
-                    provider: "nearai_chat".to_string(),
-                });
             }
+            return LlmError::AuthFailed {
+                provider: "nearai_chat".to_string(),
+            };
+        }
 
-            if status_code == 429 {
-                return Err(LlmError::RateLimited {
-                    provider: "nearai_chat".to_string(),
-                    retry_after: None,
-                });
+        if status_code == 429 {
+            return LlmError::RateLimited {
+                provider: "nearai_chat".to_string(),
+                retry_after: retry_after.or_else(|| retry_after_from_message(response_text)),
+            };
+        }
+
+        if let Some(err) = parse_api_error(response_text) {
+            return err;
+        }
+
+        let truncated = crate::agent::truncate_for_preview(response_text, 512);
+        LlmError::RequestFailed {
+            provider: "nearai_chat".to_string(),
+            reason: format!("HTTP {}: {}", status, truncated),
+        }
+    }
+
+    /// Open a streaming chat completions request, renewing the session once on 401.
+    async fn send_stream_request(
+        &self,
+        body: &ChatCompletionRequest,
+    ) -> Result<reqwest::Response, LlmError> {
+        match self.send_stream_request_inner(body).await {
+            Ok(response) => Ok(response),
+            Err(LlmError::SessionExpired { .. }) if !self.uses_api_key() => {
+                self.session.handle_auth_failure().await?;
+                self.send_stream_request_inner(body).await
             }
+            Err(e) => Err(e),
+        }
+    }
 
-            let truncated = crate::agent::truncate_for_preview(&response_text, 512);
-            return Err(LlmError::RequestFailed {
+    async fn send_stream_request_inner(
+        &self,
+        body: &ChatCompletionRequest,
+    ) -> Result<reqwest::Response, LlmError> {
+        let url = self.api_url("chat/completions");
+        let token = self.resolve_bearer_token().await?;
+        let request_id = new_request_id();
+
+        tracing::debug!(
+            request_id = %request_id,
+            "Sending streaming request to NEAR AI Chat: {}",
+            url
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream")
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .header(REQUEST_ID_HEADER, &request_id)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
                 provider: "nearai_chat".to_string(),
-                reason: format!("HTTP {}: {}", status, truncated),
-            });
+                reason: format!("{} (request_id: {})", e, request_id),
+            })?;
+
+        let status = response.status();
+        tracing::debug!(request_id = %request_id, "NEAR AI Chat stream status: {}", status);
+        if !status.is_success() {
+            tracing::warn!(
+                request_id = %request_id,
+                status = %status,
+                "NEAR AI Chat streaming request failed"
+            );
+            let retry_after = retry_after_header(&response);
+            let response_text = response.text().await.unwrap_or_default();
+            return Err(self.status_error(status, retry_after, &response_text));
         }
 
-        serde_json::from_str(&response_text).map_err(|e| {
-            let truncated = crate::agent::truncate_for_preview(&response_text, 512);
-            LlmError::InvalidResponse {
-                provider: "nearai_chat".to_string(),
-                reason: format!("JSON parse error: {}. Raw: {}", e, truncated),
-            }
-        })
+        Ok(response)
+    }
+
+    /// Build the wire request for a tool completion.
+    fn build_tool_request(&self, req: ToolCompletionRequest) -> ChatCompletionRequest {
+        let model = req.model.unwrap_or_else(|| self.active_model_name());
+        let mut raw_messages = req.messages;
+        crate::llm::provider::sanitize_tool_messages(&mut raw_messages);
+        let messages: Vec<ChatCompletionMessage> =
+            raw_messages.into_iter().map(|m| m.into()).collect();
+
+        // Some OpenAI-compatible providers reject `role:"tool"` messages.
+        // When enabled, rewrite tool-call / tool-result pairs into plain text.
+        let messages = if self.flatten_tool_messages {
+            flatten_tool_messages(messages)
+        } else {
+            messages
+        };
+
+        let tools: Vec<ChatCompletionTool> = req
+            .tools
+            .into_iter()
+            .map(|t| ChatCompletionTool {
+                tool_type: "function".to_string(),
+                function: ChatCompletionFunction {
+                    description: Some(t.llm_description()),
+                    name: t.name,
+                    parameters: Some(t.parameters),
+                },
+            })
+            .collect();
+
+        ChatCompletionRequest {
+            model,
+            messages,
+            temperature: req.temperature,
+            top_p: req.top_p,
+            frequency_penalty: req.frequency_penalty,
+            presence_penalty: req.presence_penalty,
+            max_tokens: req.max_tokens,
+            tools: if tools.is_empty() { None } else { Some(tools) },
+            tool_choice: req.tool_choice,
+            stream: false,
+            stream_options: None,
+        }
     }
 
     /// Fetch available models from the NEAR AI API.
@@ -275,6 +438,8 @@ impl NearAiChatProvider {
             .client
             .get(&url)
             .header("Authorization", format!("Bearer {}", token))
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .header(REQUEST_ID_HEADER, new_request_id())
             .send()
             .await
             .map_err(|e| LlmError::RequestFailed {
@@ -386,7 +551,7 @@ impl NearAiChatProvider {
             provider: "nearai_chat".to_string(),
             reason: format!(
                 "No model names found in response: {}",
-                &response_text[..response_text.len().min(300)]
+                crate::agent::truncate_for_preview(&response_text, 300)
             ),
         })
     }
@@ -405,9 +570,14 @@ impl LlmProvider for NearAiChatProvider {
             model,
             messages,
             temperature: req.temperature,
+            top_p: req.top_p,
+            frequency_penalty: req.frequency_penalty,
+            presence_penalty: req.presence_penalty,
             max_tokens: req.max_tokens,
             tools: None,
             tool_choice: None,
+            stream: false,
+            stream_options: None,
         };
 
         let response: ChatCompletionResponse = self.send_request(&request).await?;
@@ -429,13 +599,7 @@ impl LlmProvider for NearAiChatProvider {
             .content
             .or(choice.message.reasoning_content)
             .unwrap_or_default();
-        let finish_reason = match choice.finish_reason.as_deref() {
-            Some("stop") => FinishReason::Stop,
-            Some("length") => FinishReason::Length,
-            Some("tool_calls") => FinishReason::ToolUse,
-            Some("content_filter") => FinishReason::ContentFilter,
-            _ => FinishReason::Unknown,
-        };
+        let finish_reason = parse_finish_reason(choice.finish_reason.as_deref(), false);
 
         let (input_tokens, output_tokens) = parse_usage(response.usage.as_ref());
 
@@ -451,41 +615,7 @@ impl LlmProvider for NearAiChatProvider {
         &self,
         req: ToolCompletionRequest,
     ) -> Result<ToolCompletionResponse, LlmError> {
-        let model = req.model.unwrap_or_else(|| self.active_model_name());
-        let mut raw_messages = req.messages;
-        crate::llm::provider::sanitize_tool_messages(&mut raw_messages);
-        let messages: Vec<ChatCompletionMessage> =
-            raw_messages.into_iter().map(|m| m.into()).collect();
-
-        // Some OpenAI-compatible providers reject `role:"tool"` messages.
-        // When enabled, rewrite tool-call / tool-result pairs into plain text.
-        let messages = if self.flatten_tool_messages {
-            flatten_tool_messages(messages)
-        } else {
-            messages
-        };
-
-        let tools: Vec<ChatCompletionTool> = req
-            .tools
-            .into_iter()
-            .map(|t| ChatCompletionTool {
-                tool_type: "function".to_string(),
-                function: ChatCompletionFunction {
-                    name: t.name,
-                    description: Some(t.description),
-                    parameters: Some(t.parameters),
-                },
-            })
-            .collect();
-
-        let request = ChatCompletionRequest {
-            model,
-            messages,
-            temperature: req.temperature,
-            max_tokens: req.max_tokens,
-            tools: if tools.is_empty() { None } else { Some(tools) },
-            tool_choice: req.tool_choice,
-        };
+        let request = self.build_tool_request(req);
 
         let response: ChatCompletionResponse = self.send_request(&request).await?;
 
@@ -514,23 +644,13 @@ impl LlmProvider for NearAiChatProvider {
                     id: tc.id,
                     name: tc.function.name,
                     arguments,
+                    call_id: None,
                 }
             })
             .collect();
 
-        let finish_reason = match choice.finish_reason.as_deref() {
-            Some("stop") => FinishReason::Stop,
-            Some("length") => FinishReason::Length,
-            Some("tool_calls") => FinishReason::ToolUse,
-            Some("content_filter") => FinishReason::ContentFilter,
-            _ => {
-                if !tool_calls.is_empty() {
-                    FinishReason::ToolUse
-                } else {
-                    FinishReason::Unknown
-                }
-            }
-        };
+        let finish_reason =
+            parse_finish_reason(choice.finish_reason.as_deref(), !tool_calls.is_empty());
 
         let (input_tokens, output_tokens) = parse_usage(response.usage.as_ref());
 
@@ -543,6 +663,20 @@ impl LlmProvider for NearAiChatProvider {
         })
     }
 
+    async fn complete_stream(
+        &self,
+        req: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut request = self.build_tool_request(req);
+        request.stream = true;
+        request.stream_options = Some(ChatCompletionStreamOptions {
+            include_usage: true,
+        });
+
+        let response = self.send_stream_request(&request).await?;
+        Ok(sse_event_stream(response))
+    }
+
     fn model_name(&self) -> &str {
         &self.config.model
     }
@@ -550,12 +684,9 @@ impl LlmProvider for NearAiChatProvider {
     fn cost_per_token(&self) -> (Decimal, Decimal) {
         let model = self.active_model_name();
         // Try fetched pricing first, then static lookup table, then default
-        if let Ok(guard) = self.pricing.read()
-            && let Some(&rates) = guard.get(&model)
-        {
-            return rates;
-        }
-        costs::model_cost(&model).unwrap_or_else(costs::default_cost)
+        self.cached_pricing(&model)
+            .or_else(|| costs::model_cost(&model))
+            .unwrap_or_else(costs::default_cost)
     }
 
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
@@ -583,6 +714,33 @@ impl LlmProvider for NearAiChatProvider {
                 *poisoned.into_inner() = model.to_string();
             }
         }
+
+        // Look up the new model's price in the background; cost_per_token
+        // uses the static table until it arrives.
+        if !read_pricing(&self.pricing).knows(model)
+            && let Ok(handle) = tokio::runtime::Handle::try_current()
+        {
+            let client = self.client.clone();
+            let base_url = self.config.base_url.clone();
+            let api_key = self.config.api_key.clone();
+            let session = self.session.clone();
+            let pricing = self.pricing.clone();
+            let model = model.to_string();
+            handle.spawn(async move {
+                if let Err(e) = lookup_pricing(
+                    &client,
+                    &base_url,
+                    api_key.as_ref(),
+                    &session,
+                    &pricing,
+                    &model,
+                )
+                .await
+                {
+                    tracing::debug!(model = %model, "No NEAR AI pricing (using fallback): {}", e);
+                }
+            });
+        }
         Ok(())
     }
 }
@@ -596,11 +754,27 @@ struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<ChatCompletionTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<ChatCompletionStreamOptions>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionStreamOptions {
+    /// Ask the server to send a final chunk carrying token usage.
+    include_usage: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -667,6 +841,83 @@ fn model_cost_to_decimal(mc: &ModelCost) -> Option<Decimal> {
     base.checked_mul(factor)
 }
 
+/// Pricing fetched from NEAR AI's model list.
+#[derive(Debug, Default)]
+struct PricingCache {
+    /// Maps model ID → (input_cost_per_token, output_cost_per_token).
+    rates: HashMap<String, (Decimal, Decimal)>,
+    /// Models the last fetched list had no price for.
+    unpriced: HashSet<String>,
+}
+
+impl PricingCache {
+    /// Whether the last fetch answered for `model`, priced or not.
+    fn knows(&self, model: &str) -> bool {
+        self.rates.contains_key(model) || self.unpriced.contains(model)
+    }
+
+    /// Replace the rates with a freshly fetched list.
+    fn replace(&mut self, rates: HashMap<String, (Decimal, Decimal)>) {
+        self.rates = rates;
+        self.unpriced.clear();
+    }
+}
+
+fn read_pricing(
+    cache: &std::sync::RwLock<PricingCache>,
+) -> std::sync::RwLockReadGuard<'_, PricingCache> {
+    cache
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write_pricing(
+    cache: &std::sync::RwLock<PricingCache>,
+) -> std::sync::RwLockWriteGuard<'_, PricingCache> {
+    cache
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Price for `model` from the cache, fetching the model list on a miss.
+/// A model the fresh list has no price for is remembered as unpriced.
+async fn lookup_pricing(
+    client: &Client,
+    base_url: &str,
+    api_key: Option<&secrecy::SecretString>,
+    session: &SessionManager,
+    cache: &std::sync::RwLock<PricingCache>,
+    model: &str,
+) -> Result<(Decimal, Decimal), LlmError> {
+    let not_available = || LlmError::ModelNotAvailable {
+        provider: "nearai_chat".to_string(),
+        model: model.to_string(),
+    };
+    {
+        let cached = read_pricing(cache);
+        if let Some(rates) = cached.rates.get(model) {
+            return Ok(*rates);
+        }
+        if cached.unpriced.contains(model) {
+            return Err(not_available());
+        }
+    }
+
+    let map = fetch_pricing(client, base_url, api_key, session).await?;
+    let rates = map.get(model).copied();
+    let mut cached = write_pricing(cache);
+    if !map.is_empty() {
+        cached.replace(map);
+    }
+    match rates {
+        Some(rates) => Ok(rates),
+        None => {
+            cached.unpriced.insert(model.to_string());
+            Err(not_available())
+        }
+    }
+}
+
 /// Fetch pricing from the NEAR AI `/v1/model/list` endpoint.
 ///
 /// Returns a map of model_id → (input_cost_per_token, output_cost_per_token).
@@ -714,14 +965,21 @@ async fn fetch_pricing(
         reason: format!("Failed to read pricing response: {}", e),
     })?;
 
+    Ok(parse_pricing(&body))
+}
+
+/// Parse a `/v1/model/list` body into model_id → (input, output) per-token
+/// prices. Entries without both prices are skipped; an unrecognized body
+/// yields an empty map.
+fn parse_pricing(body: &str) -> HashMap<String, (Decimal, Decimal)> {
     // Parse as {models: [...]} or {data: [...]} or direct array
     let entries: Vec<PricingModelEntry> =
-        if let Ok(resp) = serde_json::from_str::<PricingResponse>(&body) {
+        if let Ok(resp) = serde_json::from_str::<PricingResponse>(body) {
             resp.models.or(resp.data).unwrap_or_default()
-        } else if let Ok(arr) = serde_json::from_str::<Vec<PricingModelEntry>>(&body) {
+        } else if let Ok(arr) = serde_json::from_str::<Vec<PricingModelEntry>>(body) {
             arr
         } else {
-            return Ok(HashMap::new());
+            return HashMap::new();
         };
 
     let mut map = HashMap::new();
@@ -750,7 +1008,7 @@ async fn fetch_pricing(
         }
     }
 
-    Ok(map)
+    map
 }
 
 /// Rewrite tool-call / tool-result messages into plain assistant/user text.
@@ -921,6 +1179,336 @@ struct ChatCompletionUsage {
     total_tokens: Option<u64>,
 }
 
+// -- Error payloads ---------------------------------------------------------
+
+/// OpenAI-style error envelope: `{"error": {"message": ..., "type": ..., "code": ...}}`.
+#[derive(Debug, Deserialize)]
+struct ApiErrorEnvelope {
+    error: ApiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default, rename = "type")]
+    error_type: Option<String>,
+    /// Some servers send a string code, others a number.
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+}
+
+/// The response's `Retry-After` hint, if present and parseable.
+fn retry_after_header(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()
+        .and_then(parse_retry_after)
+}
+
+/// Parse an error envelope into the matching `LlmError`, if the body is one.
+///
+/// Servers occasionally return these with a 200 status, so callers check
+/// success bodies too before deserializing the expected response type.
+fn parse_api_error(body: &str) -> Option<LlmError> {
+    let envelope: ApiErrorEnvelope = serde_json::from_str(body).ok()?;
+    let err = envelope.error;
+    let code = match &err.code {
+        Some(serde_json::Value::String(c)) => Some(c.as_str()),
+        _ => None,
+    };
+    let kind = code.or(err.error_type.as_deref()).unwrap_or("unknown");
+    let message = err.message.unwrap_or_else(|| kind.to_string());
+
+    Some(match kind {
+        "rate_limit_exceeded" | "rate_limit_error" => LlmError::RateLimited {
+            provider: "nearai_chat".to_string(),
+            retry_after: retry_after_from_message(&message),
+        },
+        "invalid_api_key" | "authentication_error" => LlmError::AuthFailed {
+            provider: "nearai_chat".to_string(),
+        },
+        _ => LlmError::RequestFailed {
+            provider: "nearai_chat".to_string(),
+            reason: format!(
+                "{} ({})",
+                crate::agent::truncate_for_preview(&message, 512),
+                kind
+            ),
+        },
+    })
+}
+
+/// Deserialize a successful response body, surfacing embedded error payloads.
+fn parse_response_body<R: for<'de> Deserialize<'de>>(response_text: &str) -> Result<R, LlmError> {
+    if let Some(err) = parse_api_error(response_text) {
+        return Err(err);
+    }
+
+    serde_json::from_str(response_text).map_err(|e| {
+        let truncated = crate::agent::truncate_for_preview(response_text, 512);
+        LlmError::InvalidResponse {
+            provider: "nearai_chat".to_string(),
+            reason: format!("JSON parse error: {}. Raw: {}", e, truncated),
+        }
+    })
+}
+
+// -- Streaming (SSE) types and parsing --------------------------------------
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    #[serde(default)]
+    choices: Vec<ChatCompletionChunkChoice>,
+    #[serde(default)]
+    usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunkChoice {
+    #[serde(default)]
+    delta: ChatCompletionChunkDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChatCompletionChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ChatCompletionToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionToolCallDelta {
+    #[serde(default)]
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<ChatCompletionToolCallFunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionToolCallFunctionDelta {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+/// A tool call being assembled from streamed deltas.
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Incremental parser for OpenAI-style chat completion SSE lines.
+///
+/// Text deltas are emitted immediately. Tool-call argument fragments are
+/// accumulated per `index` and emitted as complete `ToolCall`s once the
+/// stream ends, followed by a single `StreamEvent::Done`.
+#[derive(Debug, Default)]
+struct ChatStreamParser {
+    tool_calls: BTreeMap<usize, PartialToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<ChatCompletionUsage>,
+    done: bool,
+}
+
+impl ChatStreamParser {
+    /// Feed one SSE line, returning any events it produces.
+    ///
+    /// Blank lines, comments, and non-`data:` fields are ignored.
+    fn push_line(&mut self, line: &str) -> Result<Vec<StreamEvent>, LlmError> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(Vec::new());
+        };
+        let data = data.trim_start();
+
+        if data == "[DONE]" {
+            return Ok(self.finish());
+        }
+        if self.done || data.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if let Some(err) = parse_api_error(data) {
+            return Err(err);
+        }
+
+        let chunk: ChatCompletionChunk = serde_json::from_str(data).map_err(|e| {
+            let truncated = crate::agent::truncate_for_preview(data, 512);
+            LlmError::InvalidResponse {
+                provider: "nearai_chat".to_string(),
+                reason: format!("Stream chunk parse error: {}. Raw: {}", e, truncated),
+            }
+        })?;
+
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        let mut events = Vec::new();
+        for choice in chunk.choices {
+            if let Some(text) = choice.delta.content
+                && !text.is_empty()
+            {
+                events.push(StreamEvent::TextDelta(text));
+            }
+            for delta in choice.delta.tool_calls.unwrap_or_default() {
+                let partial = self.tool_calls.entry(delta.index).or_default();
+                if let Some(id) = delta.id {
+                    partial.id = id;
+                }
+                if let Some(function) = delta.function {
+                    if let Some(name) = function.name {
+                        partial.name.push_str(&name);
+                    }
+                    if let Some(arguments) = function.arguments {
+                        partial.arguments.push_str(&arguments);
+                    }
+                }
+            }
+            if choice.finish_reason.is_some() {
+                self.finish_reason = choice.finish_reason;
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Flush accumulated tool calls and emit the terminal `Done` event.
+    ///
+    /// Idempotent: returns nothing once the stream has already finished.
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.done {
+            return Vec::new();
+        }
+        self.done = true;
+
+        let mut events: Vec<StreamEvent> = std::mem::take(&mut self.tool_calls)
+            .into_values()
+            .map(|tc| {
+                let arguments = serde_json::from_str(&tc.arguments)
+                    .unwrap_or(serde_json::Value::Object(Default::default()));
+                StreamEvent::ToolCall(ToolCall {
+                    id: tc.id,
+                    name: tc.name,
+                    arguments,
+                    call_id: None,
+                })
+            })
+            .collect();
+
+        let finish_reason = parse_finish_reason(self.finish_reason.as_deref(), !events.is_empty());
+        let (input_tokens, output_tokens) = parse_usage(self.usage.as_ref());
+        events.push(StreamEvent::Done {
+            input_tokens,
+            output_tokens,
+            finish_reason,
+        });
+        events
+    }
+}
+
+type ByteStream = Pin<Box<dyn Stream<Item = Result<bytes::Bytes, reqwest::Error>> + Send>>;
+
+struct SseState {
+    bytes: ByteStream,
+    buffer: Vec<u8>,
+    parser: ChatStreamParser,
+    pending: VecDeque<StreamEvent>,
+    finished: bool,
+}
+
+impl SseState {
+    /// Parse every complete line currently in the buffer.
+    fn drain_lines(&mut self) -> Result<(), LlmError> {
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let events = self.parser.push_line(&String::from_utf8_lossy(&line))?;
+            self.pending.extend(events);
+        }
+        Ok(())
+    }
+}
+
+/// Turn a streaming HTTP response into a `CompletionStream`.
+///
+/// Bytes are buffered until a full line is available so multi-byte UTF-8
+/// sequences split across network chunks decode correctly. A stream that
+/// ends without `[DONE]` is still terminated with a `Done` event.
+fn sse_event_stream(response: reqwest::Response) -> CompletionStream {
+    let state = SseState {
+        bytes: Box::pin(response.bytes_stream()),
+        buffer: Vec::new(),
+        parser: ChatStreamParser::default(),
+        pending: VecDeque::new(),
+        finished: false,
+    };
+
+    Box::pin(futures::stream::unfold(state, |mut st| async move {
+        loop {
+            if let Some(event) = st.pending.pop_front() {
+                return Some((Ok(event), st));
+            }
+            if st.finished {
+                return None;
+            }
+
+            match st.bytes.next().await {
+                Some(Ok(chunk)) => {
+                    st.buffer.extend_from_slice(&chunk);
+                    if let Err(e) = st.drain_lines() {
+                        st.finished = true;
+                        return Some((Err(e), st));
+                    }
+                    if st.parser.done {
+                        st.finished = true;
+                    }
+                }
+                Some(Err(e)) => {
+                    st.finished = true;
+                    return Some((
+                        Err(LlmError::RequestFailed {
+                            provider: "nearai_chat".to_string(),
+                            reason: format!("Stream read failed: {}", e),
+                        }),
+                        st,
+                    ));
+                }
+                None => {
+                    st.finished = true;
+                    st.buffer.push(b'\n');
+                    if let Err(e) = st.drain_lines() {
+                        return Some((Err(e), st));
+                    }
+                    let events = st.parser.finish();
+                    st.pending.extend(events);
+                }
+            }
+        }
+    }))
+}
+
+fn parse_finish_reason(reason: Option<&str>, has_tool_calls: bool) -> FinishReason {
+    match reason {
+        Some("stop") => FinishReason::Stop,
+        Some("length") => FinishReason::Length,
+        Some("tool_calls") => FinishReason::ToolUse,
+        Some("content_filter") => FinishReason::ContentFilter,
+        _ if has_tool_calls => FinishReason::ToolUse,
+        _ => FinishReason::Unknown,
+    }
+}
+
 fn saturate_u32(val: u64) -> u32 {
     val.min(u32::MAX as u64) as u32
 }
@@ -953,15 +1541,19 @@ mod tests {
             base_url: base_url.to_string(),
             auth_base_url: "https://private.near.ai".to_string(),
             session_path: std::path::PathBuf::from("/tmp/session.json"),
+            session_store: crate::config::SessionStoreKind::default(),
             api_key: Some(secrecy::SecretString::from("test-key".to_string())),
             cheap_model: None,
             fallback_model: None,
             max_retries: 0,
             circuit_breaker_threshold: None,
             circuit_breaker_recovery_secs: 30,
+            circuit_breaker_error_rate: None,
+            circuit_breaker_window: 20,
             response_cache_enabled: false,
             response_cache_ttl_secs: 3600,
             response_cache_max_entries: 1000,
+            response_cache_semantic_threshold: None,
             failover_cooldown_secs: 300,
             failover_cooldown_threshold: 3,
             smart_routing_cascade: true,
@@ -1027,11 +1619,13 @@ mod tests {
                 id: "call_1".to_string(),
                 name: "list_issues".to_string(),
                 arguments: serde_json::json!({"owner": "foo", "repo": "bar"}),
+                call_id: None,
             },
             ToolCall {
                 id: "call_2".to_string(),
                 name: "search".to_string(),
                 arguments: serde_json::json!({"query": "test"}),
+                call_id: None,
             },
         ];
 
@@ -1064,6 +1658,7 @@ mod tests {
             id: "call_1".to_string(),
             name: "test".to_string(),
             arguments: serde_json::json!({"key": "value"}),
+            call_id: None,
         };
         let msg = ChatMessage::assistant_with_tool_calls(None, vec![tc]);
         let chat_msg: ChatCompletionMessage = msg.into();
@@ -1190,6 +1785,224 @@ mod tests {
         assert!(text.contains("[Called tool `search`"));
     }
 
+    fn parse_sse(lines: &[&str]) -> Vec<StreamEvent> {
+        let mut parser = ChatStreamParser::default();
+        let mut events = Vec::new();
+        for line in lines {
+            events.extend(parser.push_line(line).expect("valid chunk"));
+        }
+        events
+    }
+
+    #[test]
+    fn test_stream_parser_text_and_usage() {
+        let events = parse_sse(&[
+            r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#,
+            "",
+            r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":"lo"},"finish_reason":"stop"}]}"#,
+            r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#,
+            "data: [DONE]",
+        ]);
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], StreamEvent::TextDelta(t) if t == "Hel"));
+        assert!(matches!(&events[1], StreamEvent::TextDelta(t) if t == "lo"));
+        assert!(matches!(
+            events[2],
+            StreamEvent::Done {
+                input_tokens: 12,
+                output_tokens: 3,
+                finish_reason: FinishReason::Stop,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_stream_parser_accumulates_tool_call_arguments() {
+        let events = parse_sse(&[
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"search","arguments":""}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"q\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_2","function":{"name":"echo","arguments":"{}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"rust\"}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            "data: [DONE]",
+        ]);
+
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            StreamEvent::ToolCall(tc) => {
+                assert_eq!(tc.id, "call_1");
+                assert_eq!(tc.name, "search");
+                assert_eq!(tc.arguments["q"], "rust");
+            }
+            other => panic!("expected tool call, got {:?}", other),
+        }
+        match &events[1] {
+            StreamEvent::ToolCall(tc) => {
+                assert_eq!(tc.id, "call_2");
+                assert_eq!(tc.name, "echo");
+            }
+            other => panic!("expected tool call, got {:?}", other),
+        }
+        assert!(matches!(
+            events[2],
+            StreamEvent::Done {
+                finish_reason: FinishReason::ToolUse,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_stream_parser_ignores_lines_after_done() {
+        let mut parser = ChatStreamParser::default();
+        assert_eq!(parser.push_line("data: [DONE]").unwrap().len(), 1);
+        assert!(
+            parser
+                .push_line(r#"data: {"choices":[{"delta":{"content":"late"}}]}"#)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(parser.finish().is_empty());
+    }
+
+    #[test]
+    fn test_stream_parser_rejects_malformed_chunk() {
+        let mut parser = ChatStreamParser::default();
+        let err = parser.push_line("data: {not json").unwrap_err();
+        assert!(matches!(err, LlmError::InvalidResponse { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_user_agent_and_distinct_request_ids() {
+        use axum::http::HeaderMap;
+        use axum::routing::post;
+
+        let seen: Arc<std::sync::Mutex<Vec<(String, String)>>> = Arc::default();
+        let seen_handler = seen.clone();
+        let app = axum::Router::new().route(
+            "/v1/chat/completions",
+            post(move |headers: HeaderMap| {
+                let seen = seen_handler.clone();
+                async move {
+                    let header = |name: &str| {
+                        headers
+                            .get(name)
+                            .and_then(|v| v.to_str().ok())
+                            .unwrap_or_default()
+                            .to_string()
+                    };
+                    seen.lock()
+                        .unwrap()
+                        .push((header("user-agent"), header("x-request-id")));
+                    axum::Json(serde_json::json!({
+                        "choices": [{
+                            "message": {"role": "assistant", "content": "ok"},
+                            "finish_reason": "stop"
+                        }]
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let cfg = test_nearai_config(&format!("http://{}", addr));
+        let provider = NearAiChatProvider::new(cfg, test_session())
+            .expect("provider")
+            .with_user_agent("ironclaw-test/1.2.3");
+
+        for _ in 0..2 {
+            let resp = provider
+                .complete(CompletionRequest::new(vec![ChatMessage::user("hi")]))
+                .await
+                .expect("completion");
+            assert_eq!(resp.content, "ok");
+        }
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.iter().all(|(ua, _)| ua == "ironclaw-test/1.2.3"));
+        assert!(!seen[0].1.is_empty());
+        assert_ne!(seen[0].1, seen[1].1);
+    }
+
+    #[tokio::test]
+    async fn test_429_carries_retry_after_header() {
+        use axum::http::StatusCode;
+        use axum::routing::post;
+
+        let app = axum::Router::new().route(
+            "/v1/chat/completions",
+            post(|| async {
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [("retry-after", "7")],
+                    "slow down",
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let cfg = test_nearai_config(&format!("http://{}", addr));
+        let provider = NearAiChatProvider::new(cfg, test_session()).expect("provider");
+        let err = provider
+            .complete(CompletionRequest::new(vec![ChatMessage::user("hi")]))
+            .await
+            .unwrap_err();
+        match err {
+            LlmError::RateLimited { retry_after, .. } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(7)));
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_error_payload_with_success_status_returns_err() {
+        // Regression: a 200 response carrying an error envelope instead of
+        // `choices` must surface as an error rather than a parse panic.
+        let body = r#"{"error":{"message":"tool results are not supported","type":"invalid_request_error","code":null}}"#;
+        let result: Result<ChatCompletionResponse, LlmError> = parse_response_body(body);
+        match result {
+            Err(LlmError::RequestFailed { reason, .. }) => {
+                assert!(reason.contains("tool results are not supported"));
+                assert!(reason.contains("invalid_request_error"));
+            }
+            other => panic!("expected RequestFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_error_payload_maps_known_codes() {
+        let rate =
+            parse_api_error(r#"{"error":{"message":"slow down","code":"rate_limit_exceeded"}}"#);
+        assert!(matches!(rate, Some(LlmError::RateLimited { .. })));
+
+        let auth = parse_api_error(r#"{"error":{"type":"authentication_error"}}"#);
+        assert!(matches!(auth, Some(LlmError::AuthFailed { .. })));
+
+        assert!(parse_api_error(r#"{"choices":[]}"#).is_none());
+    }
+
+    #[test]
+    fn test_malformed_response_returns_invalid_response() {
+        let result: Result<ChatCompletionResponse, LlmError> =
+            parse_response_body(r#"{"id":"x","choices":null}"#);
+        assert!(matches!(result, Err(LlmError::InvalidResponse { .. })));
+
+        let result: Result<ChatCompletionResponse, LlmError> = parse_response_body("");
+        assert!(matches!(result, Err(LlmError::InvalidResponse { .. })));
+    }
+
     #[test]
     fn test_model_cost_to_decimal_basic() {
         // amount=3, scale=6 → 3 * 10^-6 = 0.000003
@@ -1229,7 +2042,9 @@ mod tests {
         // Inject pricing directly
         {
             let mut guard = provider.pricing.write().unwrap();
-            guard.insert("test-model".to_string(), (dec!(0.000001), dec!(0.000005)));
+            guard
+                .rates
+                .insert("test-model".to_string(), (dec!(0.000001), dec!(0.000005)));
         }
 
         let (input, output) = provider.cost_per_token();
@@ -1237,6 +2052,113 @@ mod tests {
         assert_eq!(output, dec!(0.000005));
     }
 
+    #[test]
+    fn test_parse_pricing_response() {
+        let body = r#"{"models": [
+            {
+                "modelId": "zai-org/GLM-4.6",
+                "inputCostPerToken": {"amount": 85, "scale": 8},
+                "outputCostPerToken": {"amount": 33, "scale": 7},
+                "metadata": {"aliases": ["glm"]}
+            },
+            {"modelId": "free-model", "inputCostPerToken": {"amount": 0}, "outputCostPerToken": {"amount": 0}},
+            {"modelId": "unpriced-model"}
+        ]}"#;
+
+        let map = parse_pricing(body);
+        assert_eq!(
+            map.get("zai-org/GLM-4.6"),
+            Some(&(dec!(0.00000085), dec!(0.0000033)))
+        );
+        assert_eq!(map.get("glm"), map.get("zai-org/GLM-4.6"));
+        assert_eq!(map.get("free-model"), Some(&(Decimal::ZERO, Decimal::ZERO)));
+        assert!(!map.contains_key("unpriced-model"));
+        assert!(parse_pricing("not json").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_model_pricing_caches_result() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use axum::routing::get;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/v1/model/list",
+            get({
+                let hits = Arc::clone(&hits);
+                move || async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    axum::Json(serde_json::json!({"data": [{
+                        "modelId": "test-model",
+                        "inputCostPerToken": {"amount": 15, "scale": 7},
+                        "outputCostPerToken": {"amount": 6, "scale": 6}
+                    }]}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let cfg = test_nearai_config(&format!("http://{}", addr));
+        let provider = NearAiChatProvider::new(cfg, test_session()).expect("provider");
+
+        let rates = provider.fetch_model_pricing("test-model").await.unwrap();
+        assert_eq!(rates, (dec!(0.0000015), dec!(0.000006)));
+        assert_eq!(provider.cost_per_token(), rates);
+
+        // Served from the cache the second time.
+        let hits_after_first = hits.load(Ordering::SeqCst);
+        provider.fetch_model_pricing("test-model").await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), hits_after_first);
+
+        let missing = provider.fetch_model_pricing("other-model").await;
+        assert!(matches!(missing, Err(LlmError::ModelNotAvailable { .. })));
+
+        // An unpriced model is remembered rather than fetched again.
+        let hits_after_miss = hits.load(Ordering::SeqCst);
+        let missing = provider.fetch_model_pricing("other-model").await;
+        assert!(matches!(missing, Err(LlmError::ModelNotAvailable { .. })));
+        assert_eq!(hits.load(Ordering::SeqCst), hits_after_miss);
+    }
+
+    #[tokio::test]
+    async fn test_set_model_fetches_pricing() {
+        use axum::routing::get;
+
+        let app = axum::Router::new().route(
+            "/v1/model/list",
+            get(|| async {
+                axum::Json(serde_json::json!({"data": [{
+                    "modelId": "priced-model",
+                    "inputCostPerToken": {"amount": 2, "scale": 6},
+                    "outputCostPerToken": {"amount": 8, "scale": 6}
+                }]}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let cfg = test_nearai_config(&format!("http://{}", addr));
+        let provider = NearAiChatProvider::new(cfg, test_session()).expect("provider");
+        provider.set_model("priced-model").unwrap();
+
+        let expected = (dec!(0.000002), dec!(0.000008));
+        for _ in 0..100 {
+            if provider.cost_per_token() == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("set_model did not load pricing for the new model");
+    }
+
     #[test]
     fn test_cost_per_token_falls_back_to_static() {
         let mut cfg = test_nearai_config("http://127.0.0.1:8318");
@@ -0,0 +1,188 @@
+//! `ObservedProvider` decorator that reports LLM calls to an [`Observer`].
+//!
+//! Emits an `LlmRequest` event before each call, an `LlmResponse` event with
+//! the call's duration and outcome afterwards, and the tokens consumed as a
+//! `TokensUsed` metric. With a `NoopObserver` this costs nothing.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+
+use crate::error::LlmError;
+use crate::llm::CircuitSnapshot;
+use crate::llm::provider::{
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    StreamEvent, ToolCompletionRequest, ToolCompletionResponse,
+};
+use crate::observability::{Observer, ObserverEvent, ObserverMetric};
+
+pub struct ObservedProvider {
+    inner: Arc<dyn LlmProvider>,
+    provider: String,
+    observer: Arc<dyn Observer>,
+}
+
+impl ObservedProvider {
+    /// Wrap `inner`, labelling its calls with the backend name `provider`
+    /// (e.g. "nearai", "openai").
+    pub fn new(
+        inner: Arc<dyn LlmProvider>,
+        provider: impl Into<String>,
+        observer: Arc<dyn Observer>,
+    ) -> Self {
+        Self {
+            inner,
+            provider: provider.into(),
+            observer,
+        }
+    }
+
+    fn record_request(&self, model: &str, message_count: usize) {
+        self.observer.record_event(&ObserverEvent::LlmRequest {
+            provider: self.provider.clone(),
+            model: model.to_string(),
+            message_count,
+        });
+    }
+
+    fn record_response(&self, model: String, duration: Duration, result: Result<u32, &LlmError>) {
+        report_response(
+            self.observer.as_ref(),
+            &self.provider,
+            model,
+            duration,
+            result,
+        );
+    }
+}
+
+fn report_response(
+    observer: &dyn Observer,
+    provider: &str,
+    model: String,
+    duration: Duration,
+    result: Result<u32, &LlmError>,
+) {
+    observer.record_event(&ObserverEvent::LlmResponse {
+        provider: provider.to_string(),
+        model,
+        duration,
+        success: result.is_ok(),
+        error_message: result.as_ref().err().map(|e| e.to_string()),
+    });
+    if let Ok(tokens) = result {
+        observer.record_metric(&ObserverMetric::TokensUsed(u64::from(tokens)));
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ObservedProvider {
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        self.inner.cost_per_token()
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        let model = self.inner.effective_model_name(request.model.as_deref());
+        self.record_request(&model, request.messages.len());
+        let start = Instant::now();
+        let result = self.inner.complete(request).await;
+        self.record_response(
+            model,
+            start.elapsed(),
+            result
+                .as_ref()
+                .map(|r| r.input_tokens.saturating_add(r.output_tokens)),
+        );
+        result
+    }
+
+    async fn complete_with_tools(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        let model = self.inner.effective_model_name(request.model.as_deref());
+        self.record_request(&model, request.messages.len());
+        let start = Instant::now();
+        let result = self.inner.complete_with_tools(request).await;
+        self.record_response(
+            model,
+            start.elapsed(),
+            result
+                .as_ref()
+                .map(|r| r.input_tokens.saturating_add(r.output_tokens)),
+        );
+        result
+    }
+
+    /// The response is reported when the stream ends, so its duration
+    /// covers the whole reply rather than the time to the first token.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let model = self.inner.effective_model_name(request.model.as_deref());
+        self.record_request(&model, request.messages.len());
+        let start = Instant::now();
+        let stream = match self.inner.complete_stream(request).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                self.record_response(model, start.elapsed(), Err(&err));
+                return Err(err);
+            }
+        };
+
+        let observer = Arc::clone(&self.observer);
+        let provider = self.provider.clone();
+        let mut model = Some(model);
+        Ok(Box::pin(stream.inspect(move |event| {
+            let result = match event {
+                Ok(StreamEvent::Done {
+                    input_tokens,
+                    output_tokens,
+                    ..
+                }) => Ok(input_tokens.saturating_add(*output_tokens)),
+                Err(err) => Err(err),
+                Ok(_) => return,
+            };
+            // Report once, on the first terminal event.
+            if let Some(model) = model.take() {
+                report_response(observer.as_ref(), &provider, model, start.elapsed(), result);
+            }
+        })))
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.inner.list_models().await
+    }
+
+    async fn model_metadata(&self) -> Result<ModelMetadata, LlmError> {
+        self.inner.model_metadata().await
+    }
+
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        self.inner.effective_model_name(requested_model)
+    }
+
+    fn active_model_name(&self) -> String {
+        self.inner.active_model_name()
+    }
+
+    fn set_model(&self, model: &str) -> Result<(), LlmError> {
+        self.inner.set_model(model)
+    }
+
+    fn calculate_cost(&self, input_tokens: u32, output_tokens: u32) -> Decimal {
+        self.inner.calculate_cost(input_tokens, output_tokens)
+    }
+
+    fn circuit_snapshot(&self) -> Option<CircuitSnapshot> {
+        self.inner.circuit_snapshot()
+    }
+}
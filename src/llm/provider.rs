@@ -1,10 +1,15 @@
 //! LLM provider trait and types.
 
+use std::pin::Pin;
+
 use async_trait::async_trait;
+use futures::Stream;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::error::LlmError;
+use crate::llm::CircuitSnapshot;
+use crate::llm::sampling::SamplingProfile;
 
 /// Role in a conversation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -109,6 +114,11 @@ pub struct CompletionRequest {
     pub model: Option<String>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Penalize tokens by how often they already appear (OpenAI-style, -2.0 to 2.0).
+    pub frequency_penalty: Option<f32>,
+    /// Penalize tokens that have appeared at all (OpenAI-style, -2.0 to 2.0).
+    pub presence_penalty: Option<f32>,
     pub stop_sequences: Option<Vec<String>>,
     /// Opaque metadata passed through to the provider (e.g. thread_id for chaining).
     pub metadata: std::collections::HashMap<String, String>,
@@ -122,6 +132,9 @@ impl CompletionRequest {
             model: None,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             stop_sequences: None,
             metadata: std::collections::HashMap::new(),
         }
@@ -144,6 +157,36 @@ impl CompletionRequest {
         self.temperature = Some(temperature);
         self
     }
+
+    /// Set top-p (nucleus sampling).
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set frequency penalty.
+    pub fn with_frequency_penalty(mut self, penalty: f32) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Set presence penalty.
+    pub fn with_presence_penalty(mut self, penalty: f32) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Fill temperature and top-p from a profile where not already set.
+    ///
+    /// The profile's top-p is only added when the caller set neither value,
+    /// so an explicit temperature is never sent alongside an implied top-p.
+    pub fn with_sampling(mut self, profile: SamplingProfile) -> Self {
+        if self.temperature.is_none() && self.top_p.is_none() {
+            self.top_p = profile.top_p();
+        }
+        self.temperature.get_or_insert(profile.temperature());
+        self
+    }
 }
 
 /// Response from a chat completion.
@@ -156,7 +199,8 @@ pub struct CompletionResponse {
 }
 
 /// Why the completion finished.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FinishReason {
     Stop,
     Length,
@@ -171,6 +215,23 @@ pub struct ToolDefinition {
     pub name: String,
     pub description: String,
     pub parameters: serde_json::Value,
+    /// JSON Schema of the tool's structured result, if it declares one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_schema: Option<serde_json::Value>,
+}
+
+impl ToolDefinition {
+    /// Description sent to the model, with the result schema appended so
+    /// it knows the shape of what the tool returns.
+    pub fn llm_description(&self) -> String {
+        match &self.result_schema {
+            Some(schema) => format!(
+                "{}\n\nReturns JSON matching this schema: {}",
+                self.description, schema
+            ),
+            None => self.description.clone(),
+        }
+    }
 }
 
 /// A tool call requested by the LLM.
@@ -179,6 +240,11 @@ pub struct ToolCall {
     pub id: String,
     pub name: String,
     pub arguments: serde_json::Value,
+    /// Provider-side correlation ID, when distinct from `id`. The OpenAI
+    /// Responses API gives each function call an item `id` and a `call_id`,
+    /// and tool results must echo the `call_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
 }
 
 /// Result of a tool execution to send back to the LLM.
@@ -199,6 +265,11 @@ pub struct ToolCompletionRequest {
     pub model: Option<String>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Penalize tokens by how often they already appear (OpenAI-style, -2.0 to 2.0).
+    pub frequency_penalty: Option<f32>,
+    /// Penalize tokens that have appeared at all (OpenAI-style, -2.0 to 2.0).
+    pub presence_penalty: Option<f32>,
     /// How to handle tool use: "auto", "required", or "none".
     pub tool_choice: Option<String>,
     /// Opaque metadata passed through to the provider (e.g. thread_id for chaining).
@@ -214,6 +285,9 @@ impl ToolCompletionRequest {
             model: None,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             tool_choice: None,
             metadata: std::collections::HashMap::new(),
         }
@@ -237,6 +311,36 @@ impl ToolCompletionRequest {
         self
     }
 
+    /// Set top-p (nucleus sampling).
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set frequency penalty.
+    pub fn with_frequency_penalty(mut self, penalty: f32) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Set presence penalty.
+    pub fn with_presence_penalty(mut self, penalty: f32) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Fill temperature and top-p from a profile where not already set.
+    ///
+    /// The profile's top-p is only added when the caller set neither value,
+    /// so an explicit temperature is never sent alongside an implied top-p.
+    pub fn with_sampling(mut self, profile: SamplingProfile) -> Self {
+        if self.temperature.is_none() && self.top_p.is_none() {
+            self.top_p = profile.top_p();
+        }
+        self.temperature.get_or_insert(profile.temperature());
+        self
+    }
+
     /// Set tool choice mode.
     pub fn with_tool_choice(mut self, choice: impl Into<String>) -> Self {
         self.tool_choice = Some(choice.into());
@@ -256,6 +360,24 @@ pub struct ToolCompletionResponse {
     pub finish_reason: FinishReason,
 }
 
+/// An incremental event emitted by a streaming completion.
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    /// A fragment of assistant text.
+    TextDelta(String),
+    /// A tool call whose arguments have been fully accumulated.
+    ToolCall(ToolCall),
+    /// Terminal event carrying token usage and the finish reason.
+    Done {
+        input_tokens: u32,
+        output_tokens: u32,
+        finish_reason: FinishReason,
+    },
+}
+
+/// Stream of events produced by `LlmProvider::complete_stream`.
+pub type CompletionStream = Pin<Box<dyn Stream<Item = Result<StreamEvent, LlmError>> + Send>>;
+
 /// Metadata about a model returned by the provider's API.
 #[derive(Debug, Clone)]
 pub struct ModelMetadata {
@@ -282,6 +404,34 @@ pub trait LlmProvider: Send + Sync {
         request: ToolCompletionRequest,
     ) -> Result<ToolCompletionResponse, LlmError>;
 
+    /// Complete with tool use support, streaming the response as it arrives.
+    ///
+    /// The stream always ends with a single `StreamEvent::Done`. Default
+    /// implementation buffers a `complete_with_tools()` call and replays it
+    /// as events, so providers without native streaming still work.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let response = self.complete_with_tools(request).await?;
+        let mut events = Vec::new();
+        if let Some(content) = response.content.filter(|c| !c.is_empty()) {
+            events.push(Ok(StreamEvent::TextDelta(content)));
+        }
+        events.extend(
+            response
+                .tool_calls
+                .into_iter()
+                .map(|tc| Ok(StreamEvent::ToolCall(tc))),
+        );
+        events.push(Ok(StreamEvent::Done {
+            input_tokens: response.input_tokens,
+            output_tokens: response.output_tokens,
+            finish_reason: response.finish_reason,
+        }));
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
     /// List available models from the provider.
     /// Default implementation returns empty list.
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
@@ -328,6 +478,12 @@ pub trait LlmProvider: Send + Sync {
         let (input_cost, output_cost) = self.cost_per_token();
         input_cost * Decimal::from(input_tokens) + output_cost * Decimal::from(output_tokens)
     }
+
+    /// State of the circuit breaker in this provider chain, if there is one.
+    /// Decorators above the breaker forward this to their inner provider.
+    fn circuit_snapshot(&self) -> Option<CircuitSnapshot> {
+        None
+    }
 }
 
 /// Sanitize a message list to ensure tool_use / tool_result integrity.
@@ -392,6 +548,7 @@ mod tests {
             id: "call_1".to_string(),
             name: "echo".to_string(),
             arguments: serde_json::json!({}),
+            call_id: None,
         };
         let mut messages = vec![
             ChatMessage::user("hello"),
@@ -435,6 +592,7 @@ mod tests {
             id: "call_1".to_string(),
             name: "echo".to_string(),
             arguments: serde_json::json!({}),
+            call_id: None,
         };
         let mut messages = vec![
             ChatMessage::user("test"),
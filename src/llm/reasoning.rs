@@ -2,13 +2,18 @@
 
 use std::sync::{Arc, LazyLock};
 
+use async_trait::async_trait;
+use futures::StreamExt;
 use regex::Regex;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::channels::FormatCapabilities;
 use crate::error::LlmError;
 
 use crate::llm::{
-    ChatMessage, CompletionRequest, LlmProvider, ToolCall, ToolCompletionRequest, ToolDefinition,
+    ChatMessage, CompletionRequest, CompletionStream, LlmProvider, SamplingProfiles, StreamEvent,
+    ToolCall, ToolCompletionRequest, ToolCompletionResponse, ToolDefinition,
 };
 use crate::safety::SafetyLayer;
 
@@ -65,6 +70,10 @@ pub struct ReasoningContext {
     /// When true, force a text-only response (ignore available tools).
     /// Used by the agentic loop to guarantee termination near the iteration limit.
     pub force_text: bool,
+    /// Model to use for requests made with this context instead of the
+    /// provider's active model, e.g. a stronger model for a hard question.
+    /// Ignored, with a warning, when the provider can't switch per request.
+    pub model_override: Option<String>,
 }
 
 impl ReasoningContext {
@@ -77,6 +86,7 @@ impl ReasoningContext {
             current_state: None,
             metadata: std::collections::HashMap::new(),
             force_text: false,
+            model_override: None,
         }
     }
 
@@ -109,6 +119,15 @@ impl ReasoningContext {
         self.metadata = metadata;
         self
     }
+
+    /// Route requests made with this context to `model`.
+    pub fn with_model_override(mut self, model: impl Into<String>) -> Self {
+        let model = model.into();
+        if !model.is_empty() {
+            self.model_override = Some(model);
+        }
+        self
+    }
 }
 
 impl Default for ReasoningContext {
@@ -177,6 +196,57 @@ impl TokenUsage {
     }
 }
 
+/// Accumulated token usage and cost across several LLM calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageTotals {
+    pub calls: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Cost in USD.
+    pub cost: Decimal,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Running totals for a multi-call unit of work, such as one agentic turn.
+///
+/// Clones share the same totals, so one tracker can be handed to several
+/// `Reasoning` instances or parallel jobs and read back at the end.
+#[derive(Debug, Clone, Default)]
+pub struct UsageTracker {
+    totals: Arc<std::sync::Mutex<UsageTotals>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one call's usage, priced with the provider's `cost_per_token()`
+    /// rates. Returns the cost of this call.
+    pub fn record(&self, usage: TokenUsage, cost_per_token: (Decimal, Decimal)) -> Decimal {
+        let (input_rate, output_rate) = cost_per_token;
+        let cost = input_rate * Decimal::from(usage.input_tokens)
+            + output_rate * Decimal::from(usage.output_tokens);
+
+        let mut totals = self.totals.lock().unwrap_or_else(|e| e.into_inner());
+        totals.calls += 1;
+        totals.input_tokens += u64::from(usage.input_tokens);
+        totals.output_tokens += u64::from(usage.output_tokens);
+        totals.cost += cost;
+        cost
+    }
+
+    /// Snapshot of everything recorded so far.
+    pub fn totals(&self) -> UsageTotals {
+        *self.totals.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
 /// Result of a response with potential tool calls.
 ///
 /// Used by the agent loop to handle tool execution before returning a final response.
@@ -200,6 +270,15 @@ pub struct RespondOutput {
     pub usage: TokenUsage,
 }
 
+/// Receives assistant text as a streamed reply arrives.
+///
+/// Fragments are raw model output: the reply returned afterwards is the
+/// cleaned, authoritative version.
+#[async_trait]
+pub trait StreamSink: Send + Sync {
+    async fn text_delta(&self, delta: &str);
+}
+
 /// Reasoning engine for the agent.
 pub struct Reasoning {
     llm: Arc<dyn LlmProvider>,
@@ -211,10 +290,18 @@ pub struct Reasoning {
     skill_context: Option<String>,
     /// Channel name (e.g. "discord", "telegram") for formatting hints.
     channel: Option<String>,
+    /// What the channel can render, reported by the channel itself.
+    format: Option<FormatCapabilities>,
     /// Model name for runtime context.
     model_name: Option<String>,
     /// Whether this is a group chat context.
     is_group_chat: bool,
+    /// Optional accumulator fed after every `respond_with_tools` call.
+    usage_tracker: Option<UsageTracker>,
+    /// Sampling profiles for planning, evaluation and replies.
+    sampling: SamplingProfiles,
+    /// Optional receiver for reply text while it streams in.
+    stream_sink: Option<Arc<dyn StreamSink>>,
 }
 
 impl Reasoning {
@@ -226,8 +313,12 @@ impl Reasoning {
             workspace_system_prompt: None,
             skill_context: None,
             channel: None,
+            format: None,
             model_name: None,
             is_group_chat: false,
+            usage_tracker: None,
+            sampling: SamplingProfiles::default(),
+            stream_sink: None,
         }
     }
 
@@ -262,6 +353,13 @@ impl Reasoning {
         self
     }
 
+    /// Set the channel's formatting capabilities, used for formatting hints
+    /// when the channel has no built-in ones.
+    pub fn with_format_capabilities(mut self, format: FormatCapabilities) -> Self {
+        self.format = Some(format);
+        self
+    }
+
     /// Set the model name for runtime context.
     pub fn with_model_name(mut self, name: impl Into<String>) -> Self {
         let n = name.into();
@@ -277,6 +375,31 @@ impl Reasoning {
         self
     }
 
+    /// Accumulate token usage and cost from every `respond_with_tools` call
+    /// into `tracker`.
+    pub fn with_usage_tracker(mut self, tracker: UsageTracker) -> Self {
+        self.usage_tracker = Some(tracker);
+        self
+    }
+
+    /// Set the sampling profiles for planning, evaluation and replies.
+    pub fn with_sampling_profiles(mut self, sampling: SamplingProfiles) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Forward reply text to `sink` as it streams in from the provider.
+    pub fn with_stream_sink(mut self, sink: Arc<dyn StreamSink>) -> Self {
+        self.stream_sink = Some(sink);
+        self
+    }
+
+    fn track_usage(&self, usage: TokenUsage) {
+        if let Some(ref tracker) = self.usage_tracker {
+            tracker.record(usage, self.llm.cost_per_token());
+        }
+    }
+
     /// Run a simple LLM completion with automatic response cleaning.
     ///
     /// This is the preferred entry point for code paths that call the LLM
@@ -309,9 +432,10 @@ impl Reasoning {
             )));
         }
 
-        let request = CompletionRequest::new(messages)
+        let mut request = CompletionRequest::new(messages)
             .with_max_tokens(2048)
-            .with_temperature(0.3);
+            .with_sampling(self.sampling.planning);
+        request.model = self.request_model(context);
 
         let response = self.llm.complete(request).await?;
 
@@ -345,6 +469,7 @@ impl Reasoning {
                 .with_max_tokens(1024)
                 .with_tool_choice("auto");
         request.metadata = context.metadata.clone();
+        request.model = self.request_model(context);
 
         let response = self.llm.complete_with_tools(request).await?;
 
@@ -403,9 +528,10 @@ Respond in JSON format:
             )));
         }
 
-        let request = CompletionRequest::new(messages)
+        let mut request = CompletionRequest::new(messages)
             .with_max_tokens(1024)
-            .with_temperature(0.1);
+            .with_sampling(self.sampling.evaluation);
+        request.model = self.request_model(context);
 
         let response = self.llm.complete(request).await?;
 
@@ -458,15 +584,18 @@ Respond in JSON format:
         if !effective_tools.is_empty() {
             let mut request = ToolCompletionRequest::new(messages, effective_tools)
                 .with_max_tokens(4096)
-                .with_temperature(0.7)
+                .with_sampling(self.sampling.chat)
                 .with_tool_choice("auto");
             request.metadata = context.metadata.clone();
+            request.model = self.request_model(context);
 
-            let response = self.llm.complete_with_tools(request).await?;
+            let stream = self.llm.complete_stream(request).await?;
+            let response = self.collect_stream(stream).await?;
             let usage = TokenUsage {
                 input_tokens: response.input_tokens,
                 output_tokens: response.output_tokens,
             };
+            self.track_usage(usage);
 
             // If there were tool calls, return them for execution
             if !response.tool_calls.is_empty() {
@@ -525,10 +654,17 @@ Respond in JSON format:
             // No tools, use simple completion
             let mut request = CompletionRequest::new(messages)
                 .with_max_tokens(4096)
-                .with_temperature(0.7);
+                .with_sampling(self.sampling.chat);
             request.metadata = context.metadata.clone();
+            request.model = self.request_model(context);
 
             let response = self.llm.complete(request).await?;
+            let usage = TokenUsage {
+                input_tokens: response.input_tokens,
+                output_tokens: response.output_tokens,
+            };
+            self.track_usage(usage);
+
             let cleaned = clean_response(&response.content);
             let final_text = if cleaned.trim().is_empty() {
                 tracing::warn!(
@@ -541,14 +677,65 @@ Respond in JSON format:
             };
             Ok(RespondOutput {
                 result: RespondResult::Text(final_text),
-                usage: TokenUsage {
-                    input_tokens: response.input_tokens,
-                    output_tokens: response.output_tokens,
-                },
+                usage,
             })
         }
     }
 
+    /// Drain a completion stream into a response, passing text fragments to
+    /// the stream sink as they arrive.
+    async fn collect_stream(
+        &self,
+        mut stream: CompletionStream,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        while let Some(event) = stream.next().await {
+            match event? {
+                StreamEvent::TextDelta(delta) => {
+                    if let Some(ref sink) = self.stream_sink {
+                        sink.text_delta(&delta).await;
+                    }
+                    content.push_str(&delta);
+                }
+                StreamEvent::ToolCall(call) => tool_calls.push(call),
+                StreamEvent::Done {
+                    input_tokens,
+                    output_tokens,
+                    finish_reason,
+                } => {
+                    return Ok(ToolCompletionResponse {
+                        content: (!content.is_empty()).then_some(content),
+                        tool_calls,
+                        input_tokens,
+                        output_tokens,
+                        finish_reason,
+                    });
+                }
+            }
+        }
+        Err(LlmError::InvalidResponse {
+            provider: self.llm.model_name().to_string(),
+            reason: "stream ended without a final event".to_string(),
+        })
+    }
+
+    /// The model to request for `context`: its override when the provider
+    /// honors per-request models, otherwise `None` so the active model is
+    /// used.
+    fn request_model(&self, context: &ReasoningContext) -> Option<String> {
+        let requested = context.model_override.as_deref()?;
+        if self.llm.effective_model_name(Some(requested)) == requested {
+            return Some(requested.to_string());
+        }
+        tracing::warn!(
+            requested_model = requested,
+            active_model = %self.llm.active_model_name(),
+            "Provider does not support per-request model overrides; using the active model"
+        );
+        None
+    }
+
     fn build_planning_prompt(&self, context: &ReasoningContext) -> String {
         let tools_desc = if context.available_tools.is_empty() {
             "No tools available.".to_string()
@@ -669,6 +856,7 @@ Example:
 - Do not narrate routine, low-risk tool calls; just call the tool
 - Narrate only when it helps: multi-step work, sensitive actions, or when the user asks
 - For multi-step tasks, call independent tools in parallel when possible
+- If a call needs another call from the same response to finish first, add "_depends_on": [<0-based position of that call>] to its arguments
 - If a tool fails, explain the error briefly and try an alternative approach
 
 ## Safety
@@ -714,7 +902,7 @@ Example:
             Some(c) => c,
             None => return String::new(),
         };
-        let hints = match channel {
+        let known = match channel {
             "discord" => {
                 "\
 - No markdown tables (Discord renders them as plaintext). Use bullet lists instead.\n\
@@ -734,9 +922,38 @@ Example:
 - No markdown tables. Use Slack formatting: *bold*, _italic_, `code`.\n\
 - Prefer threaded replies when responding to older messages."
             }
-            _ => return String::new(),
+            _ => "",
         };
-        format!("\n\n## Channel Formatting ({})\n{}", channel, hints)
+
+        let mut hints: Vec<String> = known.lines().map(str::to_string).collect();
+        if let Some(format) = self.format {
+            if hints.is_empty() {
+                if !format.supports_markdown {
+                    hints.push("- Plain text only; markdown is shown literally.".to_string());
+                } else {
+                    if !format.supports_tables {
+                        hints.push("- No markdown tables. Use bullet lists instead.".to_string());
+                    }
+                    if !format.supports_code_blocks {
+                        hints.push("- No fenced code blocks. Use inline `code`.".to_string());
+                    }
+                }
+            }
+            if let Some(max) = format.max_message_length {
+                hints.push(format!(
+                    "- Replies over {} characters are split into several messages; keep them short.",
+                    max
+                ));
+            }
+        }
+        if hints.is_empty() {
+            return String::new();
+        }
+        format!(
+            "\n\n## Channel Formatting ({})\n{}",
+            channel,
+            hints.join("\n")
+        )
     }
 
     fn build_runtime_section(&self) -> String {
@@ -1051,6 +1268,7 @@ fn recover_tool_calls_from_content(
                     id: format!("recovered_{}", calls.len()),
                     name: name.to_string(),
                     arguments,
+                    call_id: None,
                 });
                 continue;
             }
@@ -1062,6 +1280,7 @@ fn recover_tool_calls_from_content(
                     id: format!("recovered_{}", calls.len()),
                     name: name.to_string(),
                     arguments: serde_json::Value::Object(Default::default()),
+                    call_id: None,
                 });
             }
         }
@@ -1699,6 +1918,7 @@ That's my plan."#;
                 name: n.to_string(),
                 description: String::new(),
                 parameters: serde_json::json!({}),
+                result_schema: None,
             })
             .collect()
     }
@@ -1777,4 +1997,317 @@ That's my plan."#;
         assert_eq!(calls.len(), 1);
         assert_eq!(calls[0].name, "tool_list");
     }
+
+    // ---- Usage tracking ----
+
+    #[test]
+    fn test_usage_tracker_accumulates_tokens_and_cost() {
+        use rust_decimal_macros::dec;
+
+        let tracker = UsageTracker::new();
+        let rates = (dec!(0.000002), dec!(0.000008));
+
+        let first = tracker.record(
+            TokenUsage {
+                input_tokens: 1000,
+                output_tokens: 100,
+            },
+            rates,
+        );
+        assert_eq!(first, dec!(0.0028));
+        tracker.record(
+            TokenUsage {
+                input_tokens: 500,
+                output_tokens: 50,
+            },
+            rates,
+        );
+
+        let totals = tracker.totals();
+        assert_eq!(totals.calls, 2);
+        assert_eq!(totals.input_tokens, 1500);
+        assert_eq!(totals.output_tokens, 150);
+        assert_eq!(totals.total_tokens(), 1650);
+        assert_eq!(totals.cost, dec!(0.0042));
+    }
+
+    #[test]
+    fn test_usage_tracker_clones_share_totals_across_threads() {
+        use rust_decimal_macros::dec;
+
+        let tracker = UsageTracker::new();
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let tracker = tracker.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        tracker.record(
+                            TokenUsage {
+                                input_tokens: 10,
+                                output_tokens: 5,
+                            },
+                            (dec!(0.001), dec!(0.002)),
+                        );
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let totals = tracker.totals();
+        assert_eq!(totals.calls, 800);
+        assert_eq!(totals.input_tokens, 8000);
+        assert_eq!(totals.output_tokens, 4000);
+        assert_eq!(totals.cost, dec!(16));
+    }
+
+    #[tokio::test]
+    async fn test_respond_with_tools_feeds_usage_tracker() {
+        use crate::config::SafetyConfig;
+        use crate::testing::StubLlm;
+
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        }));
+        let tracker = UsageTracker::new();
+        let reasoning = Reasoning::new(Arc::new(StubLlm::default()), safety)
+            .with_usage_tracker(tracker.clone());
+
+        let context = ReasoningContext::new().with_messages(vec![ChatMessage::user("hi")]);
+        reasoning.respond_with_tools(&context).await.unwrap();
+        let context = context.with_tools(make_tools(&["shell"]));
+        reasoning.respond_with_tools(&context).await.unwrap();
+
+        let totals = tracker.totals();
+        assert_eq!(totals.calls, 2);
+        assert_eq!(totals.input_tokens, 20);
+        assert_eq!(totals.output_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn test_model_override_applies_to_one_request() {
+        use crate::config::SafetyConfig;
+        use crate::testing::MockLlmProvider;
+
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        }));
+        let context = ReasoningContext::new().with_messages(vec![ChatMessage::user("hi")]);
+        let strong = ReasoningContext::new()
+            .with_messages(vec![ChatMessage::user("hard question")])
+            .with_model_override("strong-model");
+
+        let llm = Arc::new(
+            MockLlmProvider::new()
+                .with_text("a")
+                .with_text("b")
+                .with_text("c"),
+        );
+        let reasoning = Reasoning::new(llm.clone(), safety.clone());
+        reasoning.respond_with_tools(&strong).await.unwrap();
+        reasoning.respond_with_tools(&context).await.unwrap();
+        reasoning
+            .respond_with_tools(&strong.with_tools(make_tools(&["shell"])))
+            .await
+            .unwrap();
+        let models: Vec<_> = llm.requests().into_iter().map(|r| r.model).collect();
+        assert_eq!(models, vec![Some("strong-model".to_string()), None]);
+        assert_eq!(
+            llm.tool_requests()[0].model.as_deref(),
+            Some("strong-model")
+        );
+        assert_eq!(llm.active_model_name(), "mock-model");
+
+        // A provider that can't switch per request gets the active model.
+        let fixed = Arc::new(MockLlmProvider::new().with_fixed_model().with_text("a"));
+        let reasoning = Reasoning::new(fixed.clone(), safety);
+        let strong = ReasoningContext::new()
+            .with_messages(vec![ChatMessage::user("hard question")])
+            .with_model_override("strong-model");
+        reasoning.respond_with_tools(&strong).await.unwrap();
+        assert_eq!(fixed.requests()[0].model, None);
+    }
+
+    #[tokio::test]
+    async fn test_model_override_through_wrapped_providers() {
+        use crate::llm::{RetryConfig, RetryProvider, SmartRoutingConfig, SmartRoutingProvider};
+        use crate::testing::MockLlmProvider;
+
+        let safety = Arc::new(SafetyLayer::new(&crate::config::SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        }));
+        let strong = ReasoningContext::new()
+            .with_messages(vec![ChatMessage::user("hard question")])
+            .with_model_override("strong-model");
+        let retry = |inner: Arc<MockLlmProvider>| -> Arc<dyn LlmProvider> {
+            Arc::new(RetryProvider::new(inner, RetryConfig::default()))
+        };
+        let routed = |primary: Arc<MockLlmProvider>, cheap: Arc<MockLlmProvider>| {
+            Arc::new(SmartRoutingProvider::new(
+                primary,
+                cheap,
+                SmartRoutingConfig::default(),
+            )) as Arc<dyn LlmProvider>
+        };
+
+        // The wrappers report what the inner provider does with the
+        // override, rather than claiming every override is honored.
+        let switching = Arc::new(MockLlmProvider::new().with_text("a"));
+        Reasoning::new(retry(switching.clone()), safety.clone())
+            .respond_with_tools(&strong)
+            .await
+            .unwrap();
+        assert_eq!(
+            switching.requests()[0].model.as_deref(),
+            Some("strong-model")
+        );
+
+        let fixed = Arc::new(MockLlmProvider::new().with_fixed_model().with_text("a"));
+        Reasoning::new(retry(fixed.clone()), safety.clone())
+            .respond_with_tools(&strong)
+            .await
+            .unwrap();
+        assert_eq!(fixed.requests()[0].model, None);
+
+        // Smart routing may send the request to either model, so both must
+        // honor the override.
+        let llm = routed(
+            Arc::new(MockLlmProvider::new()),
+            Arc::new(MockLlmProvider::new().with_fixed_model()),
+        );
+        assert_eq!(llm.effective_model_name(Some("strong-model")), "mock-model");
+
+        let llm = routed(
+            Arc::new(MockLlmProvider::new()),
+            Arc::new(MockLlmProvider::new()),
+        );
+        assert_eq!(
+            llm.effective_model_name(Some("strong-model")),
+            "strong-model"
+        );
+    }
+
+    /// Provider that only answers through `complete_stream`.
+    struct StreamingLlm;
+
+    #[async_trait]
+    impl LlmProvider for StreamingLlm {
+        fn model_name(&self) -> &str {
+            "streaming"
+        }
+
+        fn cost_per_token(&self) -> (Decimal, Decimal) {
+            (Decimal::ZERO, Decimal::ZERO)
+        }
+
+        async fn complete(
+            &self,
+            _request: CompletionRequest,
+        ) -> Result<crate::llm::CompletionResponse, LlmError> {
+            unreachable!("tool requests must stream")
+        }
+
+        async fn complete_with_tools(
+            &self,
+            _request: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, LlmError> {
+            unreachable!("tool requests must stream")
+        }
+
+        async fn complete_stream(
+            &self,
+            _request: ToolCompletionRequest,
+        ) -> Result<CompletionStream, LlmError> {
+            let events = vec![
+                Ok(StreamEvent::TextDelta("Hel".to_string())),
+                Ok(StreamEvent::TextDelta("lo".to_string())),
+                Ok(StreamEvent::Done {
+                    input_tokens: 3,
+                    output_tokens: 2,
+                    finish_reason: crate::llm::FinishReason::Stop,
+                }),
+            ];
+            Ok(Box::pin(futures::stream::iter(events)))
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(std::sync::Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl StreamSink for RecordingSink {
+        async fn text_delta(&self, delta: &str) {
+            self.0.lock().unwrap().push(delta.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_respond_with_tools_streams_through_wrapped_provider() {
+        use crate::config::SafetyConfig;
+        use crate::llm::{
+            CachedProvider, CircuitBreakerConfig, CircuitBreakerProvider, ResponseCacheConfig,
+            RetryConfig, RetryProvider,
+        };
+
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        }));
+        let llm: Arc<dyn LlmProvider> = Arc::new(StreamingLlm);
+        let llm = Arc::new(RetryProvider::new(llm, RetryConfig::default()));
+        let llm = Arc::new(CircuitBreakerProvider::new(
+            llm,
+            CircuitBreakerConfig::default(),
+        ));
+        let llm = Arc::new(CachedProvider::new(llm, ResponseCacheConfig::default()));
+        let sink = Arc::new(RecordingSink::default());
+        let reasoning = Reasoning::new(llm, safety).with_stream_sink(sink.clone());
+
+        let context = ReasoningContext::new()
+            .with_messages(vec![ChatMessage::user("hi")])
+            .with_tools(make_tools(&["shell"]));
+        let output = reasoning.respond_with_tools(&context).await.unwrap();
+
+        assert!(matches!(output.result, RespondResult::Text(ref t) if t == "Hello"));
+        assert_eq!(output.usage.output_tokens, 2);
+        assert_eq!(*sink.0.lock().unwrap(), vec!["Hel", "lo"]);
+    }
+
+    #[test]
+    fn test_channel_section_uses_format_capabilities() {
+        use crate::config::SafetyConfig;
+        use crate::testing::StubLlm;
+
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
+            injection_check_enabled: false,
+            ..Default::default()
+        }));
+        let reasoning = |channel: &str, format: Option<FormatCapabilities>| {
+            let r =
+                Reasoning::new(Arc::new(StubLlm::default()), safety.clone()).with_channel(channel);
+            match format {
+                Some(f) => r.with_format_capabilities(f),
+                None => r,
+            }
+        };
+
+        assert_eq!(reasoning("sms", None).build_channel_section(), "");
+        assert_eq!(
+            reasoning("repl", Some(FormatCapabilities::markdown())).build_channel_section(),
+            ""
+        );
+
+        let plain = reasoning("sms", Some(FormatCapabilities::plain())).build_channel_section();
+        assert!(plain.contains("Plain text only"), "{plain}");
+
+        let telegram = reasoning("telegram", FormatCapabilities::for_platform("telegram"))
+            .build_channel_section();
+        assert!(telegram.contains("Telegram strips them"), "{telegram}");
+        assert!(telegram.contains("over 4096 characters"), "{telegram}");
+    }
 }
@@ -4,6 +4,10 @@
 //! by a SHA-256 hash of the messages and model name. Tool-calling
 //! requests are never cached since they can trigger side effects.
 //!
+//! With [`CacheKeyMode::Semantic`] and an embedding provider, a request
+//! that misses the exact key can still hit an entry whose prompt is close
+//! enough in embedding space.
+//!
 //! ```text
 //! ┌──────────────────────────────────────────────────┐
 //! │               CachedProvider                      │
@@ -12,11 +16,13 @@
 //! │                                  store response   │
 //! │                                                    │
 //! │  complete_with_tools() ──► always call inner       │
+//! │  complete_stream()     ──► always call inner       │
 //! └──────────────────────────────────────────────────┘
 //! ```
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
@@ -24,11 +30,39 @@ use rust_decimal::Decimal;
 use sha2::{Digest, Sha256};
 use tokio::sync::Mutex;
 
+use crate::clock::{Clock, system_clock};
 use crate::error::LlmError;
+use crate::llm::CircuitSnapshot;
 use crate::llm::provider::{
-    CompletionRequest, CompletionResponse, LlmProvider, ModelMetadata, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
+use crate::observability::prometheus;
+use crate::workspace::EmbeddingProvider;
+
+/// How a request is matched against cached entries.
+///
+/// [`Exact`](Self::Exact) only returns a response cached for an identical
+/// request, so it is never wrong. [`Semantic`](Self::Semantic) trades some
+/// of that accuracy for hit rate: prompts that differ in a detail that
+/// matters (a number, a negation, a name) can embed almost identically, so
+/// a response to a slightly different question may be returned. It also
+/// costs latency on every exact miss: one embedding call (a network round
+/// trip for remote providers) plus a scan of all cached embeddings under
+/// the cache lock. It suits repetitive, low-stakes prompts such as
+/// heartbeats and routing decisions; keep the threshold high (0.95 or
+/// more) and leave it off for conversational turns.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CacheKeyMode {
+    /// Hit only on byte-identical messages and parameters (default).
+    #[default]
+    Exact,
+    /// Also hit when the prompt's embedding has cosine similarity of at
+    /// least `threshold` with a cached prompt for the same model and
+    /// parameters. Needs [`CachedProvider::with_embeddings`]; without
+    /// embeddings, or when embedding fails, matching is exact.
+    Semantic { threshold: f32 },
+}
 
 /// Configuration for the response cache.
 #[derive(Debug, Clone)]
@@ -37,6 +71,8 @@ pub struct ResponseCacheConfig {
     pub ttl: Duration,
     /// Maximum number of cached entries before LRU eviction.
     pub max_entries: usize,
+    /// How requests are matched; see [`CacheKeyMode`] for the tradeoffs.
+    pub key_mode: CacheKeyMode,
 }
 
 impl Default for ResponseCacheConfig {
@@ -44,15 +80,53 @@ impl Default for ResponseCacheConfig {
         Self {
             ttl: Duration::from_secs(3600), // 1 hour
             max_entries: 1000,
+            key_mode: CacheKeyMode::Exact,
+        }
+    }
+}
+
+/// Cumulative cache counters, for tuning `ttl` and `max_entries`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache (exact or semantic).
+    pub hits: u64,
+    /// Lookups that went to the inner provider.
+    pub misses: u64,
+    /// Live entries dropped to stay within `max_entries`.
+    pub evictions: u64,
+    /// Entries dropped because they outlived `ttl`.
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that hit, or 0 before any lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
         }
     }
 }
 
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
 struct CacheEntry {
     response: CompletionResponse,
     created_at: Instant,
     last_accessed: Instant,
     hit_count: u64,
+    /// Model and parameters, without messages; semantic hits must match it.
+    params_key: String,
+    /// Prompt embedding, when semantic matching computed one.
+    embedding: Option<Vec<f32>>,
 }
 
 /// LLM provider wrapper that caches `complete()` responses.
@@ -63,6 +137,9 @@ pub struct CachedProvider {
     inner: Arc<dyn LlmProvider>,
     cache: Mutex<HashMap<String, CacheEntry>>,
     config: ResponseCacheConfig,
+    embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    counters: CacheCounters,
+    clock: Arc<dyn Clock>,
 }
 
 impl CachedProvider {
@@ -72,6 +149,37 @@ impl CachedProvider {
             inner,
             cache: Mutex::new(HashMap::new()),
             config,
+            embeddings: None,
+            counters: CacheCounters::default(),
+            clock: system_clock(),
+        }
+    }
+
+    /// Embed prompts with `provider` for [`CacheKeyMode::Semantic`].
+    pub fn with_embeddings(mut self, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embeddings = Some(provider);
+        self
+    }
+
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Embed the prompt if semantic matching is configured. `None` means
+    /// fall back to exact matching.
+    async fn embed_prompt(&self, request: &CompletionRequest) -> Option<(Vec<f32>, f32)> {
+        let CacheKeyMode::Semantic { threshold } = self.config.key_mode else {
+            return None;
+        };
+        let embedder = self.embeddings.as_ref()?;
+        match embedder.embed(&prompt_text(request)).await {
+            Ok(embedding) => Some((embedding, threshold)),
+            Err(e) => {
+                tracing::debug!("Response cache embedding failed, using exact match: {}", e);
+                None
+            }
         }
     }
 
@@ -90,6 +198,16 @@ impl CachedProvider {
         self.cache.lock().await.values().map(|e| e.hit_count).sum()
     }
 
+    /// Counters since construction. Lock-free; `clear()` does not reset them.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            expirations: self.counters.expirations.load(Ordering::Relaxed),
+        }
+    }
+
     /// Clear all cached entries.
     pub async fn clear(&self) {
         self.cache.lock().await.clear();
@@ -112,7 +230,21 @@ fn cache_key(model: &str, request: &CompletionRequest) -> String {
         hasher.update(json.as_bytes());
     }
 
-    // Include response-affecting parameters so different temperatures,
+    hash_params(&mut hasher, request);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Like [`cache_key`] but without the messages: requests that may share a
+/// semantic hit.
+fn params_key(model: &str, request: &CompletionRequest) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hash_params(&mut hasher, request);
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash_params(hasher: &mut Sha256, request: &CompletionRequest) {
+    // Include response-affecting parameters so different sampling settings,
     // max_tokens, or stop sequences produce distinct cache keys.
     hasher.update(b"|");
     if let Some(max_tokens) = request.max_tokens {
@@ -123,14 +255,46 @@ fn cache_key(model: &str, request: &CompletionRequest) -> String {
         hasher.update(temp.to_le_bytes());
     }
     hasher.update(b"|");
+    for param in [
+        request.top_p,
+        request.frequency_penalty,
+        request.presence_penalty,
+    ] {
+        if let Some(value) = param {
+            hasher.update(value.to_le_bytes());
+        }
+        hasher.update(b"|");
+    }
     if let Some(ref stops) = request.stop_sequences {
         for s in stops {
             hasher.update(s.as_bytes());
             hasher.update(b"\x00");
         }
     }
+}
 
-    format!("{:x}", hasher.finalize())
+/// The text embedded for semantic matching: every message, in order.
+fn prompt_text(request: &CompletionRequest) -> String {
+    request
+        .messages
+        .iter()
+        .map(|m| m.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cosine similarity of two vectors; 0 when lengths differ or either is zero.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
 }
 
 #[async_trait]
@@ -146,7 +310,7 @@ impl LlmProvider for CachedProvider {
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
         let effective_model = self.inner.effective_model_name(request.model.as_deref());
         let key = cache_key(&effective_model, &request);
-        let now = Instant::now();
+        let now = self.clock.now();
 
         // Check cache
         {
@@ -156,14 +320,49 @@ impl LlmProvider for CachedProvider {
                     entry.last_accessed = now;
                     entry.hit_count += 1;
                     tracing::debug!(hits = entry.hit_count, "response cache hit");
+                    self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                    prometheus::record_cache_lookup(&effective_model, true);
                     return Ok(entry.response.clone());
                 }
                 // Expired, remove it
                 guard.remove(&key);
+                self.counters.expirations.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // Exact miss: look for a close enough prompt with the same parameters
+        let params = params_key(&effective_model, &request);
+        let embedded = self.embed_prompt(&request).await;
+        if let Some((ref embedding, threshold)) = embedded {
+            let mut guard = self.cache.lock().await;
+            let best = guard
+                .values_mut()
+                .filter(|entry| {
+                    entry.params_key == params
+                        && now.duration_since(entry.created_at) < self.config.ttl
+                })
+                .filter_map(|entry| {
+                    let similarity = cosine_similarity(embedding, entry.embedding.as_deref()?);
+                    (similarity >= threshold).then_some((similarity, entry))
+                })
+                .max_by(|(a, _), (b, _)| a.total_cmp(b));
+            if let Some((similarity, entry)) = best {
+                entry.last_accessed = now;
+                entry.hit_count += 1;
+                tracing::debug!(
+                    hits = entry.hit_count,
+                    similarity,
+                    "response cache semantic hit"
+                );
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                prometheus::record_cache_lookup(&effective_model, true);
+                return Ok(entry.response.clone());
             }
         }
 
         // Cache miss, call the real provider
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+        prometheus::record_cache_lookup(&effective_model, false);
         let response = self.inner.complete(request).await?;
 
         // Store in cache
@@ -171,7 +370,11 @@ impl LlmProvider for CachedProvider {
             let mut guard = self.cache.lock().await;
 
             // Evict expired entries
+            let before = guard.len();
             guard.retain(|_, entry| now.duration_since(entry.created_at) < self.config.ttl);
+            self.counters
+                .expirations
+                .fetch_add((before - guard.len()) as u64, Ordering::Relaxed);
 
             // LRU eviction if over capacity
             while guard.len() >= self.config.max_entries {
@@ -182,6 +385,7 @@ impl LlmProvider for CachedProvider {
 
                 if let Some(k) = oldest_key {
                     guard.remove(&k);
+                    self.counters.evictions.fetch_add(1, Ordering::Relaxed);
                 } else {
                     break;
                 }
@@ -194,6 +398,8 @@ impl LlmProvider for CachedProvider {
                     created_at: now,
                     last_accessed: now,
                     hit_count: 0,
+                    params_key: params,
+                    embedding: embedded.map(|(embedding, _)| embedding),
                 },
             );
         }
@@ -209,6 +415,14 @@ impl LlmProvider for CachedProvider {
         self.inner.complete_with_tools(request).await
     }
 
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        // Same as complete_with_tools: never cached.
+        self.inner.complete_stream(request).await
+    }
+
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         self.inner.list_models().await
     }
@@ -228,13 +442,19 @@ impl LlmProvider for CachedProvider {
     fn set_model(&self, model: &str) -> Result<(), LlmError> {
         self.inner.set_model(model)
     }
+
+    fn circuit_snapshot(&self) -> Option<CircuitSnapshot> {
+        self.inner.circuit_snapshot()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::clock::MockClock;
     use crate::llm::provider::ChatMessage;
     use crate::llm::response_cache::*;
     use crate::testing::StubLlm;
+    use crate::workspace::EmbeddingError;
 
     fn simple_request() -> CompletionRequest {
         CompletionRequest {
@@ -242,6 +462,9 @@ mod tests {
             model: None,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             stop_sequences: None,
             metadata: Default::default(),
         }
@@ -253,6 +476,9 @@ mod tests {
             model: None,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             stop_sequences: None,
             metadata: Default::default(),
         }
@@ -308,6 +534,7 @@ mod tests {
             ResponseCacheConfig {
                 ttl: Duration::from_secs(60),
                 max_entries: 100,
+                ..ResponseCacheConfig::default()
             },
         );
 
@@ -344,6 +571,7 @@ mod tests {
             ResponseCacheConfig {
                 ttl: Duration::from_millis(1),
                 max_entries: 100,
+                ..ResponseCacheConfig::default()
             },
         );
 
@@ -358,6 +586,30 @@ mod tests {
         assert_eq!(stub.calls(), 2);
     }
 
+    #[tokio::test]
+    async fn ttl_expiry_on_mock_clock() {
+        let stub = Arc::new(StubLlm::new("cached response"));
+        let clock = Arc::new(MockClock::new());
+        let cached = CachedProvider::new(
+            stub.clone(),
+            ResponseCacheConfig {
+                ttl: Duration::from_secs(3600),
+                ..ResponseCacheConfig::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        cached.complete(simple_request()).await.unwrap();
+        clock.advance(Duration::from_secs(3599));
+        cached.complete(simple_request()).await.unwrap();
+        assert_eq!(stub.calls(), 1, "still fresh");
+
+        clock.advance(Duration::from_secs(1));
+        cached.complete(simple_request()).await.unwrap();
+        assert_eq!(stub.calls(), 2);
+        assert_eq!(cached.stats().expirations, 1);
+    }
+
     #[tokio::test]
     async fn lru_eviction_removes_oldest() {
         let stub = Arc::new(StubLlm::new("cached response"));
@@ -366,6 +618,7 @@ mod tests {
             ResponseCacheConfig {
                 ttl: Duration::from_secs(60),
                 max_entries: 2,
+                ..ResponseCacheConfig::default()
             },
         );
 
@@ -380,6 +633,9 @@ mod tests {
             model: None,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             stop_sequences: None,
             metadata: Default::default(),
         };
@@ -399,6 +655,9 @@ mod tests {
             model: None,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             tool_choice: None,
             metadata: Default::default(),
         };
@@ -419,6 +678,7 @@ mod tests {
             ResponseCacheConfig {
                 ttl: Duration::from_secs(60),
                 max_entries: 100,
+                ..ResponseCacheConfig::default()
             },
         );
 
@@ -462,6 +722,43 @@ mod tests {
         assert_eq!(cached.len().await, 2);
     }
 
+    #[tokio::test]
+    async fn stats_count_hits_misses_evictions_and_expirations() {
+        let stub = Arc::new(StubLlm::new("cached response"));
+        let clock = Arc::new(MockClock::new());
+        let cached = CachedProvider::new(
+            stub.clone(),
+            ResponseCacheConfig {
+                ttl: Duration::from_secs(60),
+                max_entries: 1,
+                ..ResponseCacheConfig::default()
+            },
+        )
+        .with_clock(clock.clone());
+        assert_eq!(cached.stats().hit_rate(), 0.0);
+
+        cached.complete(simple_request()).await.unwrap(); // miss
+        cached.complete(simple_request()).await.unwrap(); // hit
+        cached.complete(different_request()).await.unwrap(); // miss, evicts "hello"
+        assert_eq!(
+            cached.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                evictions: 1,
+                expirations: 0,
+            }
+        );
+
+        clock.advance(Duration::from_secs(60));
+        cached.complete(different_request()).await.unwrap(); // expired, miss
+        let stats = cached.stats();
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.expirations, 1);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.hit_rate(), 0.25);
+    }
+
     #[test]
     fn default_config_is_reasonable() {
         let cfg = ResponseCacheConfig::default();
@@ -469,6 +766,117 @@ mod tests {
         assert_eq!(cfg.max_entries, 1000);
     }
 
+    /// Embeds text as its letter frequencies, so prompts that differ only
+    /// in case and punctuation are identical.
+    struct LetterEmbeddings {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for LetterEmbeddings {
+        fn dimension(&self) -> usize {
+            26
+        }
+
+        fn model_name(&self) -> &str {
+            "letters"
+        }
+
+        fn max_input_length(&self) -> usize {
+            10_000
+        }
+
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+            if self.fail {
+                return Err(EmbeddingError::AuthFailed);
+            }
+            let mut counts = vec![0.0; 26];
+            for c in text
+                .to_ascii_lowercase()
+                .bytes()
+                .filter(u8::is_ascii_lowercase)
+            {
+                counts[usize::from(c - b'a')] += 1.0;
+            }
+            Ok(counts)
+        }
+    }
+
+    fn semantic_config() -> ResponseCacheConfig {
+        ResponseCacheConfig {
+            key_mode: CacheKeyMode::Semantic { threshold: 0.95 },
+            ..ResponseCacheConfig::default()
+        }
+    }
+
+    fn user_request(text: &str) -> CompletionRequest {
+        CompletionRequest::new(vec![ChatMessage::user(text)])
+    }
+
+    #[tokio::test]
+    async fn semantic_mode_hits_near_duplicate_prompt() {
+        let stub = Arc::new(StubLlm::new("all quiet"));
+        let cached = CachedProvider::new(stub.clone(), semantic_config())
+            .with_embeddings(Arc::new(LetterEmbeddings { fail: false }));
+
+        cached
+            .complete(user_request("Heartbeat: anything to report?"))
+            .await
+            .unwrap();
+        let r = cached
+            .complete(user_request("heartbeat - anything to report"))
+            .await
+            .unwrap();
+        assert_eq!(stub.calls(), 1);
+        assert_eq!(r.content, "all quiet");
+        assert_eq!(cached.total_hits().await, 1);
+
+        // Unrelated prompt misses.
+        cached.complete(user_request("zzz")).await.unwrap();
+        assert_eq!(stub.calls(), 2);
+
+        // Same prompt with different parameters misses too.
+        let mut hot = user_request("Heartbeat: anything to report?");
+        hot.temperature = Some(1.0);
+        cached.complete(hot).await.unwrap();
+        assert_eq!(stub.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn semantic_mode_falls_back_to_exact_without_embeddings() {
+        for embeddings in [None, Some(LetterEmbeddings { fail: true })] {
+            let stub = Arc::new(StubLlm::new("all quiet"));
+            let mut cached = CachedProvider::new(stub.clone(), semantic_config());
+            if let Some(embeddings) = embeddings {
+                cached = cached.with_embeddings(Arc::new(embeddings));
+            }
+
+            cached
+                .complete(user_request("Heartbeat: anything to report?"))
+                .await
+                .unwrap();
+            cached
+                .complete(user_request("heartbeat - anything to report"))
+                .await
+                .unwrap();
+            assert_eq!(stub.calls(), 2);
+
+            cached
+                .complete(user_request("Heartbeat: anything to report?"))
+                .await
+                .unwrap();
+            assert_eq!(stub.calls(), 2);
+        }
+    }
+
+    #[test]
+    fn cosine_similarity_handles_degenerate_vectors() {
+        assert!((cosine_similarity(&[1.0, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 1.0]), 0.0);
+    }
+
     #[tokio::test]
     async fn delegates_model_name() {
         let stub = Arc::new(StubLlm::new("cached response"));
@@ -0,0 +1,171 @@
+//! Chat Completions fallback for providers on the OpenAI Responses API.
+//!
+//! The Responses API is stricter than Chat Completions about tool calls: a
+//! tool result must name the `call_id` of a call the API itself issued, and
+//! histories built on another backend (or replayed after a restart) can
+//! carry IDs it has never seen. [`ResponsesFallbackProvider`] sends every
+//! request to the Responses model first and, when it is rejected with a
+//! tool-call error, retries the same request through Chat Completions.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+use crate::error::LlmError;
+use crate::llm::provider::{
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    ToolCompletionRequest, ToolCompletionResponse,
+};
+
+/// Whether `err` is the Responses API rejecting a tool call or tool result.
+pub(crate) fn is_responses_tool_call_error(err: &LlmError) -> bool {
+    let (LlmError::RequestFailed { reason, .. } | LlmError::InvalidResponse { reason, .. }) = err
+    else {
+        return false;
+    };
+    let reason = reason.to_lowercase();
+    reason.contains("call_id") || reason.contains("function_call")
+}
+
+/// Sends requests to `responses` and retries those it rejects with a
+/// tool-call error through `chat`. Both must serve the same model.
+pub struct ResponsesFallbackProvider {
+    responses: Arc<dyn LlmProvider>,
+    chat: Arc<dyn LlmProvider>,
+}
+
+impl ResponsesFallbackProvider {
+    pub fn new(responses: Arc<dyn LlmProvider>, chat: Arc<dyn LlmProvider>) -> Self {
+        Self { responses, chat }
+    }
+
+    fn log_downgrade(&self, err: &LlmError) {
+        tracing::warn!(
+            model = %self.responses.model_name(),
+            error = %err,
+            "Responses API rejected a tool call; retrying through Chat Completions"
+        );
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ResponsesFallbackProvider {
+    fn model_name(&self) -> &str {
+        self.responses.model_name()
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        self.responses.cost_per_token()
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        match self.responses.complete(request.clone()).await {
+            Err(err) if is_responses_tool_call_error(&err) => {
+                self.log_downgrade(&err);
+                self.chat.complete(request).await
+            }
+            other => other,
+        }
+    }
+
+    async fn complete_with_tools(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        match self.responses.complete_with_tools(request.clone()).await {
+            Err(err) if is_responses_tool_call_error(&err) => {
+                self.log_downgrade(&err);
+                self.chat.complete_with_tools(request).await
+            }
+            other => other,
+        }
+    }
+
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        match self.responses.complete_stream(request.clone()).await {
+            Err(err) if is_responses_tool_call_error(&err) => {
+                self.log_downgrade(&err);
+                self.chat.complete_stream(request).await
+            }
+            other => other,
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.responses.list_models().await
+    }
+
+    async fn model_metadata(&self) -> Result<ModelMetadata, LlmError> {
+        self.responses.model_metadata().await
+    }
+
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        self.responses.effective_model_name(requested_model)
+    }
+
+    fn active_model_name(&self) -> String {
+        self.responses.active_model_name()
+    }
+
+    fn set_model(&self, model: &str) -> Result<(), LlmError> {
+        self.responses.set_model(model)?;
+        self.chat.set_model(model)
+    }
+
+    fn calculate_cost(&self, input_tokens: u32, output_tokens: u32) -> Decimal {
+        self.responses.calculate_cost(input_tokens, output_tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::ChatMessage;
+    use crate::testing::MockLlmProvider;
+
+    fn tool_request() -> ToolCompletionRequest {
+        ToolCompletionRequest::new(vec![ChatMessage::user("what time is it?")], Vec::new())
+    }
+
+    fn rejected(reason: &str) -> LlmError {
+        LlmError::RequestFailed {
+            provider: "gpt-4o".to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_call_rejection_is_retried_through_chat_completions() {
+        let responses = Arc::new(MockLlmProvider::new().with_error(rejected(
+            "No tool call found for function call output with call_id call_abc123.",
+        )));
+        let chat = Arc::new(MockLlmProvider::new().with_tool_call("time", serde_json::json!({})));
+        let provider = ResponsesFallbackProvider::new(responses.clone(), chat.clone());
+
+        let response = provider.complete_with_tools(tool_request()).await.unwrap();
+
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "time");
+        assert_eq!(responses.calls(), 1);
+        assert_eq!(chat.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let responses = Arc::new(MockLlmProvider::new().with_error(rejected("upstream 500")));
+        let chat = Arc::new(MockLlmProvider::new().with_text("unused"));
+        let provider = ResponsesFallbackProvider::new(responses, chat.clone());
+
+        let err = provider
+            .complete_with_tools(tool_request())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("upstream 500"), "{err}");
+        assert_eq!(chat.calls(), 0);
+    }
+}
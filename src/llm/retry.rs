@@ -3,19 +3,21 @@
 //! Provides:
 //! - `is_retryable()` — `LlmError`-level retryability classification (shared with `failover.rs`)
 //! - `retry_backoff_delay()` — exponential backoff with jitter
+//! - `retry_delay()` — backoff, stretched to honor a provider's `Retry-After`
 //! - `RetryProvider` — decorator that wraps any `LlmProvider` with automatic retries
 
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use rand::Rng;
+use regex::Regex;
 use rust_decimal::Decimal;
 
 use crate::error::LlmError;
 use crate::llm::provider::{
-    CompletionRequest, CompletionResponse, LlmProvider, ModelMetadata, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
 
 /// Returns `true` if the `LlmError` is transient and the request should be retried.
@@ -66,17 +68,83 @@ pub(crate) fn retry_backoff_delay(attempt: u32) -> Duration {
     Duration::from_millis(delay_ms)
 }
 
+/// Delay before retrying after `err`, or `None` to give up now.
+///
+/// Rate-limit errors that carry a `Retry-After` hint wait at least that
+/// long; retrying sooner would only be rejected again. A hint longer than
+/// `max_retry_after` is not waited out: the error goes back to the caller,
+/// so a failover wrapper can move on to another provider.
+pub(crate) fn retry_delay(
+    err: &LlmError,
+    attempt: u32,
+    max_retry_after: Duration,
+) -> Option<Duration> {
+    let backoff = retry_backoff_delay(attempt);
+    match err {
+        LlmError::RateLimited {
+            retry_after: Some(hint),
+            ..
+        } if *hint > max_retry_after => None,
+        LlmError::RateLimited {
+            retry_after: Some(hint),
+            ..
+        } => Some(backoff.max(*hint)),
+        _ => Some(backoff),
+    }
+}
+
+/// Parse an HTTP `Retry-After` header value: either delay-seconds or an
+/// HTTP-date. Dates in the past yield a zero delay.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Matches retry hints in rate-limit messages, e.g. OpenAI's
+/// "Please try again in 1.5s" or "retry after 20 seconds".
+static RETRY_HINT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:try again|retry)(?: after| in)+\s+(\d+(?:\.\d+)?)\s*(ms|milliseconds?|s|secs?|seconds?)\b")
+        .expect("RETRY_HINT_RE")
+});
+
+/// Extract a retry hint from an error message, for clients (like rig) that
+/// surface the response body but drop its headers.
+pub(crate) fn retry_after_from_message(message: &str) -> Option<Duration> {
+    let caps = RETRY_HINT_RE.captures(message)?;
+    let amount: f64 = caps[1].parse().ok()?;
+    let secs = if caps[2].to_lowercase().starts_with('m') {
+        amount / 1000.0
+    } else {
+        amount
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
 /// Configuration for the retry decorator.
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
     /// Maximum number of retry attempts (not counting the initial attempt).
     /// Default: 3.
     pub max_retries: u32,
+    /// Longest provider `Retry-After` worth waiting for; longer hints fail
+    /// the call instead. Default: 60 seconds.
+    pub max_retry_after: Duration,
 }
 
 impl Default for RetryConfig {
     fn default() -> Self {
-        Self { max_retries: 3 }
+        Self {
+            max_retries: 3,
+            max_retry_after: Duration::from_secs(60),
+        }
     }
 }
 
@@ -86,8 +154,9 @@ impl Default for RetryConfig {
 /// On non-transient errors (`AuthFailed`, `ContextLengthExceeded`, `SessionExpired`),
 /// returns immediately.
 ///
-/// Special handling for `RateLimited { retry_after }`: uses the provider-suggested
-/// duration if available, otherwise falls back to standard backoff.
+/// Special handling for `RateLimited { retry_after }`: waits for the longer of
+/// the provider-suggested duration and the standard backoff, unless the
+/// suggestion exceeds [`RetryConfig::max_retry_after`].
 pub struct RetryProvider {
     inner: Arc<dyn LlmProvider>,
     config: RetryConfig,
@@ -121,12 +190,14 @@ impl LlmProvider for RetryProvider {
                         return Err(err);
                     }
 
-                    let delay = match &err {
-                        LlmError::RateLimited {
-                            retry_after: Some(duration),
-                            ..
-                        } => *duration,
-                        _ => retry_backoff_delay(attempt),
+                    let Some(delay) = retry_delay(&err, attempt, self.config.max_retry_after)
+                    else {
+                        tracing::warn!(
+                            provider = %self.inner.model_name(),
+                            error = %err,
+                            "Retry-After exceeds the retry wait limit; not retrying"
+                        );
+                        return Err(err);
                     };
 
                     tracing::warn!(
@@ -165,12 +236,14 @@ impl LlmProvider for RetryProvider {
                         return Err(err);
                     }
 
-                    let delay = match &err {
-                        LlmError::RateLimited {
-                            retry_after: Some(duration),
-                            ..
-                        } => *duration,
-                        _ => retry_backoff_delay(attempt),
+                    let Some(delay) = retry_delay(&err, attempt, self.config.max_retry_after)
+                    else {
+                        tracing::warn!(
+                            provider = %self.inner.model_name(),
+                            error = %err,
+                            "Retry-After exceeds the retry wait limit; not retrying"
+                        );
+                        return Err(err);
                     };
 
                     tracing::warn!(
@@ -194,6 +267,54 @@ impl LlmProvider for RetryProvider {
         }))
     }
 
+    /// Retries opening the stream; errors after the first event are
+    /// returned to the caller, since part of the reply may already be shown.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut last_error: Option<LlmError> = None;
+
+        for attempt in 0..=self.config.max_retries {
+            let req = request.clone();
+            match self.inner.complete_stream(req).await {
+                Ok(stream) => return Ok(stream),
+                Err(err) => {
+                    if !is_retryable(&err) || attempt == self.config.max_retries {
+                        return Err(err);
+                    }
+
+                    let Some(delay) = retry_delay(&err, attempt, self.config.max_retry_after)
+                    else {
+                        tracing::warn!(
+                            provider = %self.inner.model_name(),
+                            error = %err,
+                            "Retry-After exceeds the retry wait limit; not retrying"
+                        );
+                        return Err(err);
+                    };
+
+                    tracing::warn!(
+                        provider = %self.inner.model_name(),
+                        attempt = attempt + 1,
+                        max_retries = self.config.max_retries,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %err,
+                        "Retrying after transient error (stream)"
+                    );
+
+                    last_error = Some(err);
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| LlmError::RequestFailed {
+            provider: self.inner.model_name().to_string(),
+            reason: "retry loop exited unexpectedly".to_string(),
+        }))
+    }
+
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         self.inner.list_models().await
     }
@@ -202,6 +323,10 @@ impl LlmProvider for RetryProvider {
         self.inner.model_metadata().await
     }
 
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        self.inner.effective_model_name(requested_model)
+    }
+
     fn active_model_name(&self) -> String {
         self.inner.active_model_name()
     }
@@ -230,7 +355,10 @@ mod tests {
     }
 
     fn fast_config(max_retries: u32) -> RetryConfig {
-        RetryConfig { max_retries }
+        RetryConfig {
+            max_retries,
+            ..RetryConfig::default()
+        }
     }
 
     // -- Backoff delay tests --
@@ -273,6 +401,96 @@ mod tests {
         assert!(delay.as_millis() >= 100);
     }
 
+    #[test]
+    fn test_retry_delay_honors_longer_retry_after() {
+        let limited = |secs| LlmError::RateLimited {
+            provider: "p".into(),
+            retry_after: Some(Duration::from_secs(secs)),
+        };
+        let max = Duration::from_secs(60);
+        // Hint longer than backoff wins.
+        assert_eq!(
+            retry_delay(&limited(30), 0, max),
+            Some(Duration::from_secs(30))
+        );
+        // Hint shorter than backoff: backoff wins (attempt 2 is at least 3s).
+        assert!(retry_delay(&limited(1), 2, max).unwrap() >= Duration::from_secs(3));
+        // Hint beyond the limit: give up rather than sleep for an hour.
+        assert_eq!(retry_delay(&limited(3600), 0, max), None);
+        // No hint: plain backoff.
+        let plain = LlmError::RateLimited {
+            provider: "p".into(),
+            retry_after: None,
+        };
+        assert!(retry_delay(&plain, 0, max).unwrap() <= Duration::from_millis(1250));
+    }
+
+    #[test]
+    fn test_parse_retry_after_header() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after(" 1.5 "),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let future = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let parsed = parse_retry_after(&future).unwrap();
+        assert!(parsed > Duration::from_secs(80) && parsed <= Duration::from_secs(90));
+        assert_eq!(parse_retry_after("-5"), None);
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_retry_after_from_message() {
+        assert_eq!(
+            retry_after_from_message(
+                "Rate limit reached for gpt-4o. Please try again in 1.5s. Visit ..."
+            ),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            retry_after_from_message("Please try again in 250ms."),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            retry_after_from_message("Too many requests, retry after 20 seconds"),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(retry_after_from_message("Too many requests"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_retry_after_before_retrying() {
+        // 1.5s hint exceeds the attempt-0 backoff ceiling of 1.25s.
+        let stub = Arc::new(StubLlm::failing("test").with_error_kind(
+            crate::testing::StubErrorKind::RateLimited(Some(Duration::from_millis(1500))),
+        ));
+        let retry = RetryProvider::new(stub.clone(), fast_config(1));
+
+        let started = tokio::time::Instant::now();
+        let err = retry.complete(make_request()).await.unwrap_err();
+        assert!(matches!(err, LlmError::RateLimited { .. }));
+        assert_eq!(stub.calls(), 2);
+        assert!(started.elapsed() >= Duration::from_millis(1500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_after_beyond_limit_fails_without_waiting() {
+        let stub = Arc::new(StubLlm::failing("test").with_error_kind(
+            crate::testing::StubErrorKind::RateLimited(Some(Duration::from_secs(3600))),
+        ));
+        let retry = RetryProvider::new(stub.clone(), fast_config(3));
+
+        let started = tokio::time::Instant::now();
+        let err = retry.complete(make_request()).await.unwrap_err();
+        assert!(matches!(err, LlmError::RateLimited { .. }));
+        assert_eq!(stub.calls(), 1);
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
     // -- is_retryable() classification tests --
 
     #[test]
@@ -4,29 +4,32 @@
 //! `Arc<dyn LlmProvider>` without changing any of the agent, reasoning, or tool code.
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use rig::OneOrMany;
 use rig::completion::{
-    AssistantContent, CompletionModel, CompletionRequest as RigRequest,
+    AssistantContent, CompletionModel, CompletionRequest as RigRequest, GetTokenUsage,
     ToolDefinition as RigToolDefinition, Usage as RigUsage,
 };
 use rig::message::{
     Message as RigMessage, ToolChoice as RigToolChoice, ToolFunction, ToolResult as RigToolResult,
     ToolResultContent, UserContent,
 };
+use rig::streaming::StreamedAssistantContent;
 use rust_decimal::Decimal;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json::Value as JsonValue;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::error::LlmError;
 use crate::llm::costs;
 use crate::llm::provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider,
-    ToolCall as IronToolCall, ToolCompletionRequest, ToolCompletionResponse,
-    ToolDefinition as IronToolDefinition,
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, FinishReason,
+    LlmProvider, StreamEvent, ToolCall as IronToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition as IronToolDefinition,
 };
+use crate::llm::retry::retry_after_from_message;
 
 /// Adapter that wraps a rig-core `CompletionModel` and implements `LlmProvider`.
 pub struct RigAdapter<M: CompletionModel> {
@@ -34,6 +37,7 @@ pub struct RigAdapter<M: CompletionModel> {
     model_name: String,
     input_cost: Decimal,
     output_cost: Decimal,
+    sampling_support: SamplingSupport,
 }
 
 impl<M: CompletionModel> RigAdapter<M> {
@@ -47,8 +51,27 @@ impl<M: CompletionModel> RigAdapter<M> {
             model_name: name,
             input_cost,
             output_cost,
+            sampling_support: SamplingSupport::default(),
         }
     }
+
+    /// Declare which optional sampling parameters the backend accepts.
+    pub fn with_sampling_support(mut self, support: SamplingSupport) -> Self {
+        self.sampling_support = support;
+        self
+    }
+}
+
+/// Optional sampling parameters a backend accepts beyond temperature.
+///
+/// rig-core has no native fields for these, so they travel as additional
+/// params merged into the request body. Some APIs reject unknown fields,
+/// so anything not declared here is dropped rather than sent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SamplingSupport {
+    pub top_p: bool,
+    /// `frequency_penalty` and `presence_penalty`.
+    pub penalties: bool,
 }
 
 // -- Type conversion helpers --
@@ -216,6 +239,13 @@ fn make_nullable(schema: &mut JsonValue) {
 fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<RigMessage>) {
     let mut preamble: Option<String> = None;
     let mut history = Vec::new();
+    // Responses-style APIs match a tool result to its call by `call_id`,
+    // which may differ from the call's `id`; remember it per call.
+    let mut call_ids: HashMap<String, String> = HashMap::new();
+    // Tool call IDs from the latest assistant turn still awaiting a result,
+    // in call order. A result that arrives without an ID is paired with the
+    // next one, so generated IDs on both sides match.
+    let mut pending_calls: VecDeque<String> = VecDeque::new();
 
     for msg in messages {
         match msg.role {
@@ -239,15 +269,24 @@ fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<RigMessage
                     if !msg.content.is_empty() {
                         contents.push(AssistantContent::text(&msg.content));
                     }
+                    pending_calls.clear();
                     for (idx, tc) in tool_calls.iter().enumerate() {
                         let tool_call_id =
                             normalized_tool_call_id(Some(tc.id.as_str()), history.len() + idx);
+                        let call_id = tc
+                            .call_id
+                            .as_deref()
+                            .map(str::trim)
+                            .filter(|id| !id.is_empty())
+                            .map_or_else(|| tool_call_id.clone(), str::to_string);
+                        call_ids.insert(tool_call_id.clone(), call_id.clone());
+                        pending_calls.push_back(tool_call_id.clone());
                         contents.push(AssistantContent::ToolCall(
                             rig::message::ToolCall::new(
-                                tool_call_id.clone(),
+                                tool_call_id,
                                 ToolFunction::new(tc.name.clone(), tc.arguments.clone()),
                             )
-                            .with_call_id(tool_call_id),
+                            .with_call_id(call_id),
                         ));
                     }
                     if let Ok(many) = OneOrMany::many(contents) {
@@ -265,11 +304,23 @@ fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<RigMessage
             }
             crate::llm::Role::Tool => {
                 // Tool result message: wrap as User { ToolResult }
-                let tool_id = normalized_tool_call_id(msg.tool_call_id.as_deref(), history.len());
+                let tool_id = match msg.tool_call_id.as_deref().map(str::trim) {
+                    Some(id) if !id.is_empty() => {
+                        pending_calls.retain(|pending| pending != id);
+                        id.to_string()
+                    }
+                    _ => pending_calls
+                        .pop_front()
+                        .unwrap_or_else(|| normalized_tool_call_id(None, history.len())),
+                };
+                let call_id = call_ids
+                    .get(&tool_id)
+                    .cloned()
+                    .unwrap_or_else(|| tool_id.clone());
                 history.push(RigMessage::User {
                     content: OneOrMany::one(UserContent::ToolResult(RigToolResult {
-                        id: tool_id.clone(),
-                        call_id: Some(tool_id),
+                        id: tool_id,
+                        call_id: Some(call_id),
                         content: OneOrMany::one(ToolResultContent::text(&msg.content)),
                     })),
                 });
@@ -280,6 +331,28 @@ fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<RigMessage
     (preamble, history)
 }
 
+/// Map a rig completion error to an `LlmError`.
+///
+/// rig drops the response headers, so a `Retry-After` on a 429 is lost; the
+/// retry hint most providers also put in the error message is used instead.
+fn map_rig_error(provider: &str, err: impl std::fmt::Display) -> LlmError {
+    let reason = err.to_string();
+    let lower = reason.to_lowercase();
+    let has_429 = reason
+        .split(|c: char| !c.is_ascii_digit())
+        .any(|n| n == "429");
+    if has_429 || lower.contains("rate limit") || lower.contains("too many requests") {
+        return LlmError::RateLimited {
+            provider: provider.to_string(),
+            retry_after: retry_after_from_message(&reason),
+        };
+    }
+    LlmError::RequestFailed {
+        provider: provider.to_string(),
+        reason,
+    }
+}
+
 /// Responses-style providers require a non-empty tool call ID.
 fn normalized_tool_call_id(raw: Option<&str>, seed: usize) -> String {
     match raw.map(str::trim).filter(|id| !id.is_empty()) {
@@ -297,7 +370,7 @@ fn convert_tools(tools: &[IronToolDefinition]) -> Vec<RigToolDefinition> {
         .iter()
         .map(|t| RigToolDefinition {
             name: t.name.clone(),
-            description: t.description.clone(),
+            description: t.llm_description(),
             parameters: normalize_schema_strict(&t.parameters),
         })
         .collect()
@@ -333,6 +406,7 @@ fn extract_response(
                     id: tc.id.clone(),
                     name: tc.function.name.clone(),
                     arguments: tc.function.arguments.clone(),
+                    call_id: tc.call_id.clone(),
                 });
             }
             // Reasoning and Image variants are not mapped to IronClaw types
@@ -360,6 +434,30 @@ fn saturate_u32(val: u64) -> u32 {
     val.min(u32::MAX as u64) as u32
 }
 
+/// Drop a tool choice the request cannot honor.
+///
+/// Providers reject a tool choice without tools, with errors that don't say
+/// why. "auto" and "none" mean the same as sending no choice; "required"
+/// can't be satisfied at all, so it is dropped with a warning rather than
+/// failing a request that retrying would not fix.
+fn effective_tool_choice(
+    model_name: &str,
+    tools: &[RigToolDefinition],
+    tool_choice: Option<RigToolChoice>,
+) -> Option<RigToolChoice> {
+    match tool_choice {
+        Some(RigToolChoice::Required) if tools.is_empty() => {
+            tracing::warn!(
+                model = %model_name,
+                "tool_choice is \"required\" but the request has no tools; sending no tool choice"
+            );
+            None
+        }
+        Some(_) if tools.is_empty() => None,
+        choice => choice,
+    }
+}
+
 /// Build a rig-core CompletionRequest from our internal types.
 fn build_rig_request(
     preamble: Option<String>,
@@ -368,6 +466,7 @@ fn build_rig_request(
     tool_choice: Option<RigToolChoice>,
     temperature: Option<f32>,
     max_tokens: Option<u32>,
+    additional_params: Option<JsonValue>,
 ) -> Result<RigRequest, LlmError> {
     // rig-core requires at least one message in chat_history
     if history.is_empty() {
@@ -387,15 +486,104 @@ fn build_rig_request(
         temperature: temperature.map(|t| t as f64),
         max_tokens: max_tokens.map(|t| t as u64),
         tool_choice,
-        additional_params: None,
+        additional_params,
     })
 }
 
+/// Collect the sampling parameters that are set and supported into rig's
+/// additional params. Returns `None` when there is nothing to send.
+fn sampling_params(
+    support: SamplingSupport,
+    top_p: Option<f32>,
+    frequency_penalty: Option<f32>,
+    presence_penalty: Option<f32>,
+) -> Option<JsonValue> {
+    let mut params = serde_json::Map::new();
+    if support.top_p
+        && let Some(p) = top_p
+    {
+        params.insert("top_p".to_string(), serde_json::json!(p));
+    }
+    if support.penalties {
+        if let Some(p) = frequency_penalty {
+            params.insert("frequency_penalty".to_string(), serde_json::json!(p));
+        }
+        if let Some(p) = presence_penalty {
+            params.insert("presence_penalty".to_string(), serde_json::json!(p));
+        }
+    }
+    (!params.is_empty()).then_some(JsonValue::Object(params))
+}
+
+/// Translates rig-core streaming chunks into `StreamEvent`s.
+///
+/// Kept free of rig's streaming types so the event mapping (tool-name
+/// normalization, finish reason, usage) can be tested without a live model.
+struct RigStreamMapper {
+    known_tool_names: HashSet<String>,
+    saw_tool_call: bool,
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+impl RigStreamMapper {
+    fn new(known_tool_names: HashSet<String>) -> Self {
+        Self {
+            known_tool_names,
+            saw_tool_call: false,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    fn text(&self, text: &str) -> Option<StreamEvent> {
+        if text.is_empty() {
+            None
+        } else {
+            Some(StreamEvent::TextDelta(text.to_string()))
+        }
+    }
+
+    fn tool_call(
+        &mut self,
+        id: &str,
+        call_id: Option<&str>,
+        name: &str,
+        arguments: JsonValue,
+    ) -> StreamEvent {
+        self.saw_tool_call = true;
+        StreamEvent::ToolCall(IronToolCall {
+            id: id.to_string(),
+            name: normalize_tool_name(name, &self.known_tool_names),
+            arguments,
+            call_id: call_id.map(str::to_string),
+        })
+    }
+
+    fn usage(&mut self, usage: &RigUsage) {
+        self.input_tokens = saturate_u32(usage.input_tokens);
+        self.output_tokens = saturate_u32(usage.output_tokens);
+    }
+
+    fn done(&self) -> StreamEvent {
+        StreamEvent::Done {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            finish_reason: if self.saw_tool_call {
+                FinishReason::ToolUse
+            } else {
+                FinishReason::Stop
+            },
+        }
+    }
+}
+
 #[async_trait]
 impl<M> LlmProvider for RigAdapter<M>
 where
     M: CompletionModel + Send + Sync + 'static,
     M::Response: Send + Sync + Serialize + DeserializeOwned,
+    M::StreamingResponse: Send + 'static,
 {
     fn model_name(&self) -> &str {
         &self.model_name
@@ -427,16 +615,19 @@ where
             None,
             request.temperature,
             request.max_tokens,
+            sampling_params(
+                self.sampling_support,
+                request.top_p,
+                request.frequency_penalty,
+                request.presence_penalty,
+            ),
         )?;
 
-        let response =
-            self.model
-                .completion(rig_req)
-                .await
-                .map_err(|e| LlmError::RequestFailed {
-                    provider: self.model_name.clone(),
-                    reason: e.to_string(),
-                })?;
+        let response = self
+            .model
+            .completion(rig_req)
+            .await
+            .map_err(|e| map_rig_error(&self.model_name, e))?;
 
         let (text, _tool_calls, finish) = extract_response(&response.choice, &response.usage);
 
@@ -469,7 +660,11 @@ where
         crate::llm::provider::sanitize_tool_messages(&mut messages);
         let (preamble, history) = convert_messages(&messages);
         let tools = convert_tools(&request.tools);
-        let tool_choice = convert_tool_choice(request.tool_choice.as_deref());
+        let tool_choice = effective_tool_choice(
+            &self.model_name,
+            &tools,
+            convert_tool_choice(request.tool_choice.as_deref()),
+        );
 
         let rig_req = build_rig_request(
             preamble,
@@ -478,16 +673,19 @@ where
             tool_choice,
             request.temperature,
             request.max_tokens,
+            sampling_params(
+                self.sampling_support,
+                request.top_p,
+                request.frequency_penalty,
+                request.presence_penalty,
+            ),
         )?;
 
-        let response =
-            self.model
-                .completion(rig_req)
-                .await
-                .map_err(|e| LlmError::RequestFailed {
-                    provider: self.model_name.clone(),
-                    reason: e.to_string(),
-                })?;
+        let response = self
+            .model
+            .completion(rig_req)
+            .await
+            .map_err(|e| map_rig_error(&self.model_name, e))?;
 
         let (text, mut tool_calls, finish) = extract_response(&response.choice, &response.usage);
 
@@ -513,6 +711,106 @@ where
         })
     }
 
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        if let Some(requested_model) = request.model.as_deref()
+            && requested_model != self.model_name.as_str()
+        {
+            tracing::warn!(
+                requested_model = requested_model,
+                active_model = %self.model_name,
+                "Per-request model override is not supported for this provider; using configured model"
+            );
+        }
+
+        let known_tool_names: HashSet<String> =
+            request.tools.iter().map(|t| t.name.clone()).collect();
+
+        let mut messages = request.messages;
+        crate::llm::provider::sanitize_tool_messages(&mut messages);
+        let (preamble, history) = convert_messages(&messages);
+        let tools = convert_tools(&request.tools);
+        let tool_choice = effective_tool_choice(
+            &self.model_name,
+            &tools,
+            convert_tool_choice(request.tool_choice.as_deref()),
+        );
+
+        let rig_req = build_rig_request(
+            preamble,
+            history,
+            tools,
+            tool_choice,
+            request.temperature,
+            request.max_tokens,
+            sampling_params(
+                self.sampling_support,
+                request.top_p,
+                request.frequency_penalty,
+                request.presence_penalty,
+            ),
+        )?;
+
+        let provider = self.model_name.clone();
+        let chunks = self
+            .model
+            .stream(rig_req)
+            .await
+            .map_err(|e| map_rig_error(&provider, e))?;
+
+        let mapper = RigStreamMapper::new(known_tool_names);
+        let state = (chunks, mapper, provider, false);
+
+        // Text and tool calls are forwarded as rig yields them; reasoning and
+        // partial tool-call deltas are dropped since rig also emits the
+        // assembled tool call. The stream always ends with one `Done`.
+        Ok(Box::pin(futures::stream::unfold(
+            state,
+            |(mut chunks, mut mapper, provider, finished)| async move {
+                if finished {
+                    return None;
+                }
+                loop {
+                    match chunks.next().await {
+                        Some(Ok(StreamedAssistantContent::Text(text))) => {
+                            if let Some(event) = mapper.text(&text.text) {
+                                return Some((Ok(event), (chunks, mapper, provider, false)));
+                            }
+                        }
+                        Some(Ok(StreamedAssistantContent::ToolCall(tc))) => {
+                            let event = mapper.tool_call(
+                                &tc.id,
+                                tc.call_id.as_deref(),
+                                &tc.function.name,
+                                tc.function.arguments,
+                            );
+                            return Some((Ok(event), (chunks, mapper, provider, false)));
+                        }
+                        Some(Ok(StreamedAssistantContent::Final(response))) => {
+                            if let Some(usage) = response.token_usage() {
+                                mapper.usage(&usage);
+                            }
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            let err = LlmError::RequestFailed {
+                                provider: provider.clone(),
+                                reason: format!("Stream read failed: {}", e),
+                            };
+                            return Some((Err(err), (chunks, mapper, provider, true)));
+                        }
+                        None => {
+                            let done = mapper.done();
+                            return Some((Ok(done), (chunks, mapper, provider, true)));
+                        }
+                    }
+                }
+            },
+        )))
+    }
+
     fn active_model_name(&self) -> String {
         self.model_name.clone()
     }
@@ -608,6 +906,7 @@ mod tests {
             id: "call_1".to_string(),
             name: "search".to_string(),
             arguments: serde_json::json!({"query": "test"}),
+            call_id: None,
         };
         let msg = ChatMessage::assistant_with_tool_calls(Some("thinking".to_string()), vec![tc]);
         let messages = vec![msg];
@@ -660,6 +959,7 @@ mod tests {
                     "query": {"type": "string"}
                 }
             }),
+            result_schema: None,
         }];
         let rig_tools = convert_tools(&tools);
         assert_eq!(rig_tools.len(), 1);
@@ -667,6 +967,21 @@ mod tests {
         assert_eq!(rig_tools[0].description, "Search the web");
     }
 
+    #[test]
+    fn test_convert_tools_advertises_result_schema() {
+        let tools = vec![IronToolDefinition {
+            name: "weather".to_string(),
+            description: "Current weather".to_string(),
+            parameters: serde_json::json!({"type": "object", "properties": {}}),
+            result_schema: Some(serde_json::json!({"type": "object"})),
+        }];
+        let rig_tools = convert_tools(&tools);
+        assert_eq!(
+            rig_tools[0].description,
+            "Current weather\n\nReturns JSON matching this schema: {\"type\":\"object\"}"
+        );
+    }
+
     #[test]
     fn test_convert_tool_choice() {
         assert!(matches!(
@@ -711,12 +1026,64 @@ mod tests {
         assert_eq!(finish, FinishReason::ToolUse);
     }
 
+    #[test]
+    fn test_extract_response_preserves_call_id() {
+        let tc = AssistantContent::ToolCall(
+            rig::message::ToolCall::new(
+                "fc_1".to_string(),
+                ToolFunction::new("search".to_string(), serde_json::json!({"q": "test"})),
+            )
+            .with_call_id("call_abc".to_string()),
+        );
+        let content = OneOrMany::one(tc);
+        let (_text, calls, _finish) = extract_response(&content, &RigUsage::new());
+        assert_eq!(calls[0].id, "fc_1");
+        assert_eq!(calls[0].call_id.as_deref(), Some("call_abc"));
+    }
+
+    #[test]
+    fn test_convert_messages_round_trips_distinct_call_id() {
+        let tc = IronToolCall {
+            id: "fc_1".to_string(),
+            name: "search".to_string(),
+            arguments: serde_json::json!({"query": "test"}),
+            call_id: Some("call_abc".to_string()),
+        };
+        let messages = vec![
+            ChatMessage::assistant_with_tool_calls(None, vec![tc]),
+            ChatMessage::tool_result("fc_1", "search", "result text"),
+        ];
+        let (_preamble, history) = convert_messages(&messages);
+
+        match &history[0] {
+            RigMessage::Assistant { content, .. } => match content.first() {
+                AssistantContent::ToolCall(tc) => {
+                    assert_eq!(tc.id, "fc_1");
+                    assert_eq!(tc.call_id.as_deref(), Some("call_abc"));
+                }
+                other => panic!("Expected tool call content, got: {:?}", other),
+            },
+            other => panic!("Expected Assistant message, got: {:?}", other),
+        }
+        match &history[1] {
+            RigMessage::User { content } => match content.first() {
+                UserContent::ToolResult(r) => {
+                    assert_eq!(r.id, "fc_1");
+                    assert_eq!(r.call_id.as_deref(), Some("call_abc"));
+                }
+                other => panic!("Expected tool result content, got: {:?}", other),
+            },
+            other => panic!("Expected User message, got: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_assistant_tool_call_empty_id_gets_generated() {
         let tc = IronToolCall {
             id: "".to_string(),
             name: "search".to_string(),
             arguments: serde_json::json!({"query": "test"}),
+            call_id: None,
         };
         let messages = vec![ChatMessage::assistant_with_tool_calls(None, vec![tc])];
         let (_preamble, history) = convert_messages(&messages);
@@ -746,6 +1113,7 @@ mod tests {
             id: "   ".to_string(),
             name: "search".to_string(),
             arguments: serde_json::json!({"query": "test"}),
+            call_id: None,
         };
         let messages = vec![ChatMessage::assistant_with_tool_calls(None, vec![tc])];
         let (_preamble, history) = convert_messages(&messages);
@@ -776,6 +1144,7 @@ mod tests {
             id: "".to_string(),
             name: "search".to_string(),
             arguments: serde_json::json!({"query": "test"}),
+            call_id: None,
         };
         let assistant_msg = ChatMessage::assistant_with_tool_calls(None, vec![tc]);
         let tool_result_msg = ChatMessage {
@@ -821,17 +1190,187 @@ mod tests {
             "tool result call_id must not be empty"
         );
 
-        // NOTE: With the current seed-based generation, these IDs will differ
-        // because the assistant tool call uses seed=0 (history.len() at that
-        // point) and the tool result uses seed=1 (history.len() after the
-        // assistant message was pushed). This documents the current behavior.
-        // A future improvement could thread the assistant's generated ID into
-        // the tool result for exact matching.
-        assert_ne!(
+        assert_eq!(
             assistant_call_id, tool_result_call_id,
-            "Current impl generates different IDs for assistant call and tool result \
-             because seeds differ; this documents the known limitation"
+            "tool result without an id must reuse the assistant's generated id"
+        );
+    }
+
+    #[test]
+    fn test_missing_result_ids_pair_with_calls_in_order() {
+        let call = |id: &str| IronToolCall {
+            id: id.to_string(),
+            name: "search".to_string(),
+            arguments: serde_json::json!({}),
+            call_id: None,
+        };
+        let result = |id: Option<&str>| ChatMessage {
+            role: crate::llm::Role::Tool,
+            content: "ok".to_string(),
+            tool_call_id: id.map(str::to_string),
+            name: Some("search".to_string()),
+            tool_calls: None,
+        };
+        let messages = vec![
+            ChatMessage::assistant_with_tool_calls(None, vec![call(""), call("call_b"), call("")]),
+            result(Some("call_b")),
+            result(None),
+            result(None),
+        ];
+        let (_preamble, history) = convert_messages(&messages);
+
+        let call_ids: Vec<String> = match &history[0] {
+            RigMessage::Assistant { content, .. } => content
+                .iter()
+                .filter_map(|c| match c {
+                    AssistantContent::ToolCall(tc) => Some(tc.id.clone()),
+                    _ => None,
+                })
+                .collect(),
+            other => panic!("Expected Assistant message, got: {:?}", other),
+        };
+        let result_ids: Vec<String> = history[1..]
+            .iter()
+            .map(|m| match m {
+                RigMessage::User { content } => match content.first() {
+                    UserContent::ToolResult(r) => r.id.clone(),
+                    other => panic!("Expected ToolResult, got: {:?}", other),
+                },
+                other => panic!("Expected User message, got: {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(call_ids[1], "call_b");
+        assert_eq!(
+            result_ids,
+            vec![
+                call_ids[1].clone(),
+                call_ids[0].clone(),
+                call_ids[2].clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_rig_error_detects_rate_limits() {
+        let err = map_rig_error(
+            "gpt-4o",
+            "ProviderError: Rate limit reached for gpt-4o. Please try again in 2.5s.",
         );
+        match err {
+            LlmError::RateLimited { retry_after, .. } => {
+                assert_eq!(retry_after, Some(std::time::Duration::from_millis(2500)));
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+
+        let err = map_rig_error("claude", "HttpError: 429 Too Many Requests");
+        assert!(matches!(
+            err,
+            LlmError::RateLimited {
+                retry_after: None,
+                ..
+            }
+        ));
+
+        let err = map_rig_error("claude", "HttpError: 500 Internal Server Error");
+        assert!(matches!(err, LlmError::RequestFailed { .. }));
+    }
+
+    #[test]
+    fn test_sampling_params_reach_request() {
+        let all = SamplingSupport {
+            top_p: true,
+            penalties: true,
+        };
+        let params = sampling_params(all, Some(0.5), Some(0.25), Some(-1.0));
+        let req = build_rig_request(
+            None,
+            vec![RigMessage::user("hi")],
+            Vec::new(),
+            None,
+            Some(0.5),
+            None,
+            params,
+        )
+        .unwrap();
+
+        assert_eq!(req.temperature, Some(0.5));
+        assert_eq!(
+            req.additional_params,
+            Some(serde_json::json!({
+                "top_p": 0.5,
+                "frequency_penalty": 0.25,
+                "presence_penalty": -1.0
+            }))
+        );
+    }
+
+    #[test]
+    fn test_unset_and_unsupported_sampling_params_omitted() {
+        let all = SamplingSupport {
+            top_p: true,
+            penalties: true,
+        };
+        assert_eq!(sampling_params(all, None, None, None), None);
+
+        let top_p_only = SamplingSupport {
+            top_p: true,
+            penalties: false,
+        };
+        assert_eq!(
+            sampling_params(top_p_only, Some(0.5), Some(0.25), Some(0.25)),
+            Some(serde_json::json!({ "top_p": 0.5 }))
+        );
+        assert_eq!(
+            sampling_params(SamplingSupport::default(), Some(0.5), Some(0.25), None),
+            None
+        );
+
+        let req = build_rig_request(
+            None,
+            vec![RigMessage::user("hi")],
+            Vec::new(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(req.temperature.is_none());
+        assert!(req.additional_params.is_none());
+    }
+
+    #[test]
+    fn test_empty_tools_drops_tool_choice() {
+        // "required" can't be met without tools; it is dropped rather than
+        // failing the request with an error the retry layer would repeat.
+        for choice in [None, Some("none"), Some("auto"), Some("required")] {
+            assert!(
+                effective_tool_choice("test-model", &[], convert_tool_choice(choice)).is_none(),
+                "{choice:?} should be dropped"
+            );
+        }
+
+        let tools = convert_tools(&[IronToolDefinition {
+            name: "search".to_string(),
+            description: "Search".to_string(),
+            parameters: serde_json::json!({"type": "object", "properties": {}}),
+            result_schema: None,
+        }]);
+        let choice =
+            effective_tool_choice("test-model", &tools, convert_tool_choice(Some("required")));
+        let req = build_rig_request(
+            None,
+            vec![RigMessage::user("hi")],
+            tools,
+            choice,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(matches!(req.tool_choice, Some(RigToolChoice::Required)));
     }
 
     #[test]
@@ -869,4 +1408,59 @@ mod tests {
         let known = HashSet::from(["echo".to_string()]);
         assert_eq!(normalize_tool_name("other_tool", &known), "other_tool");
     }
+
+    // -- RigStreamMapper tests --
+
+    #[test]
+    fn test_stream_mapper_text_then_done() {
+        let mut mapper = RigStreamMapper::new(HashSet::new());
+        assert!(mapper.text("").is_none());
+        assert!(matches!(mapper.text("Hel"), Some(StreamEvent::TextDelta(t)) if t == "Hel"));
+
+        let mut usage = RigUsage::new();
+        usage.input_tokens = 12;
+        usage.output_tokens = 3;
+        mapper.usage(&usage);
+        match mapper.done() {
+            StreamEvent::Done {
+                input_tokens,
+                output_tokens,
+                finish_reason,
+            } => {
+                assert_eq!(input_tokens, 12);
+                assert_eq!(output_tokens, 3);
+                assert_eq!(finish_reason, FinishReason::Stop);
+            }
+            other => panic!("expected Done, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_stream_mapper_tool_call_normalizes_name_and_sets_tool_use() {
+        let known = HashSet::from(["echo".to_string()]);
+        let mut mapper = RigStreamMapper::new(known);
+
+        let event = mapper.tool_call(
+            "fc_1",
+            Some("call_1"),
+            "proxy_echo",
+            serde_json::json!({"x": 1}),
+        );
+        match event {
+            StreamEvent::ToolCall(tc) => {
+                assert_eq!(tc.id, "fc_1");
+                assert_eq!(tc.call_id.as_deref(), Some("call_1"));
+                assert_eq!(tc.name, "echo");
+                assert_eq!(tc.arguments["x"], 1);
+            }
+            other => panic!("expected ToolCall, got {other:?}"),
+        }
+        assert!(matches!(
+            mapper.done(),
+            StreamEvent::Done {
+                finish_reason: FinishReason::ToolUse,
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,151 @@
+//! Named sampling profiles for LLM requests.
+//!
+//! Different kinds of work want different sampling: heartbeat checks and
+//! routines should give the same answer every time, while conversational
+//! replies benefit from some variety. A [`SamplingProfile`] bundles a
+//! temperature and an optional top-p, and [`SamplingProfiles`] picks one per
+//! logical task. Profiles only fill in values the caller left unset, so an
+//! explicit `with_temperature` / `with_top_p` on a request always wins. Some
+//! providers (Anthropic) reject requests that set both, so a profile's top-p
+//! is left out when the caller chose a temperature.
+
+/// A named temperature with an optional top-p.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingProfile {
+    /// Temperature 0: the same prompt gives the same answer.
+    Deterministic,
+    /// Low temperature for summaries and plans.
+    Balanced,
+    /// Higher temperature for open-ended conversation.
+    Creative,
+}
+
+impl SamplingProfile {
+    pub fn temperature(self) -> f32 {
+        match self {
+            Self::Deterministic => 0.0,
+            Self::Balanced => 0.3,
+            Self::Creative => 0.7,
+        }
+    }
+
+    /// Top-p to send, if the profile narrows it. `None` leaves the
+    /// provider default (no nucleus cut-off).
+    pub fn top_p(self) -> Option<f32> {
+        match self {
+            Self::Deterministic => None,
+            Self::Balanced => Some(0.9),
+            Self::Creative => Some(0.95),
+        }
+    }
+}
+
+impl std::str::FromStr for SamplingProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "deterministic" => Ok(Self::Deterministic),
+            "balanced" => Ok(Self::Balanced),
+            "creative" => Ok(Self::Creative),
+            _ => Err(format!(
+                "invalid sampling profile '{}', expected one of: deterministic, balanced, creative",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SamplingProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deterministic => write!(f, "deterministic"),
+            Self::Balanced => write!(f, "balanced"),
+            Self::Creative => write!(f, "creative"),
+        }
+    }
+}
+
+/// Which sampling profile each logical task uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingProfiles {
+    /// Replies to the user.
+    pub chat: SamplingProfile,
+    /// Job plans and next-step suggestions.
+    pub planning: SamplingProfile,
+    /// Judging whether a job or action succeeded.
+    pub evaluation: SamplingProfile,
+    /// Conversation summaries and context compaction.
+    pub summary: SamplingProfile,
+    /// Periodic heartbeat checks.
+    pub heartbeat: SamplingProfile,
+    /// Scheduled and event-triggered routines.
+    pub routine: SamplingProfile,
+}
+
+impl Default for SamplingProfiles {
+    fn default() -> Self {
+        Self {
+            chat: SamplingProfile::Creative,
+            planning: SamplingProfile::Balanced,
+            evaluation: SamplingProfile::Deterministic,
+            summary: SamplingProfile::Balanced,
+            heartbeat: SamplingProfile::Deterministic,
+            routine: SamplingProfile::Deterministic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::llm::{ChatMessage, CompletionRequest, SamplingProfile, ToolCompletionRequest};
+
+    #[test]
+    fn test_profile_sets_parameters() {
+        let req = CompletionRequest::new(vec![ChatMessage::user("hi")])
+            .with_sampling(SamplingProfile::Deterministic);
+        assert_eq!(req.temperature, Some(0.0));
+        assert_eq!(req.top_p, None);
+
+        let req = ToolCompletionRequest::new(vec![ChatMessage::user("hi")], Vec::new())
+            .with_sampling(SamplingProfile::Creative);
+        assert_eq!(req.temperature, Some(0.7));
+        assert_eq!(req.top_p, Some(0.95));
+    }
+
+    #[test]
+    fn test_explicit_values_override_profile() {
+        // Explicit values win whether they are set before or after the profile.
+        let req = CompletionRequest::new(vec![ChatMessage::user("hi")])
+            .with_temperature(1.2)
+            .with_sampling(SamplingProfile::Creative);
+        assert_eq!(req.temperature, Some(1.2));
+        // An explicit temperature is not paired with the profile's top-p.
+        assert_eq!(req.top_p, None);
+
+        let req = CompletionRequest::new(vec![ChatMessage::user("hi")])
+            .with_top_p(0.8)
+            .with_sampling(SamplingProfile::Creative);
+        assert_eq!(req.temperature, Some(0.7));
+        assert_eq!(req.top_p, Some(0.8));
+
+        let req = ToolCompletionRequest::new(vec![ChatMessage::user("hi")], Vec::new())
+            .with_sampling(SamplingProfile::Balanced)
+            .with_top_p(0.5);
+        assert_eq!(req.temperature, Some(0.3));
+        assert_eq!(req.top_p, Some(0.5));
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(
+            "Deterministic".parse::<SamplingProfile>(),
+            Ok(SamplingProfile::Deterministic)
+        );
+        assert_eq!(
+            "creative".parse::<SamplingProfile>(),
+            Ok(SamplingProfile::Creative)
+        );
+        assert!("spicy".parse::<SamplingProfile>().is_err());
+    }
+}
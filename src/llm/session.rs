@@ -1,8 +1,8 @@
 //! Session management for NEAR AI authentication.
 //!
 //! Handles session token persistence, expiration detection, and renewal via
-//! OAuth flow. Tokens are stored in `~/.ironclaw/session.json` and refreshed
-//! automatically when expired.
+//! OAuth flow. Tokens are stored through a [`SessionStore`], by default in
+//! `~/.ironclaw/session.json`, and refreshed automatically when expired.
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, RwLock};
 
 use crate::error::LlmError;
+use crate::llm::session_store::{FileSessionStore, SESSION_SETTINGS_KEY, SessionStore};
 
 /// Session data persisted to disk.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +61,8 @@ pub struct SessionManager {
     token: RwLock<Option<SecretString>>,
     /// Prevents thundering herd during concurrent 401s.
     renewal_lock: Mutex<()>,
+    /// Where the session is persisted (the session file by default).
+    session_store: RwLock<Arc<dyn SessionStore>>,
     /// Optional database store for persisting session to the settings table.
     store: RwLock<Option<Arc<dyn crate::db::Database>>>,
     /// User ID for DB settings (default: "default").
@@ -70,6 +73,7 @@ impl SessionManager {
     /// Create a new session manager and load any existing token from disk.
     pub fn new(config: SessionConfig) -> Self {
         let manager = Self {
+            session_store: RwLock::new(Arc::new(FileSessionStore::new(&config.session_path))),
             config,
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(30))
@@ -101,6 +105,7 @@ impl SessionManager {
     /// Create a session manager and load token asynchronously.
     pub async fn new_async(config: SessionConfig) -> Self {
         let manager = Self {
+            session_store: RwLock::new(Arc::new(FileSessionStore::new(&config.session_path))),
             config,
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(30))
@@ -134,6 +139,22 @@ impl SessionManager {
         }
     }
 
+    /// Persist sessions with `store` instead of the session file, and load
+    /// any session it already holds.
+    pub async fn set_session_store(&self, store: Arc<dyn SessionStore>) {
+        *self.session_store.write().await = store;
+        if let Err(e) = self.load_session().await {
+            tracing::debug!("No session in new session store: {}", e);
+        }
+    }
+
+    /// Forget the current session, both in memory and in the session store.
+    pub async fn clear_session(&self) -> Result<(), LlmError> {
+        *self.token.write().await = None;
+        let store = self.session_store.read().await.clone();
+        store.delete().await
+    }
+
     /// Get the current session token, returning an error if not authenticated.
     pub async fn get_token(&self) -> Result<SecretString, LlmError> {
         let guard = self.token.read().await;
@@ -432,7 +453,7 @@ impl SessionManager {
         Ok(())
     }
 
-    /// Save session data to disk and (if available) to the database.
+    /// Save session data to the session store and (if available) to the database.
     async fn save_session(&self, token: &str, auth_provider: Option<&str>) -> Result<(), LlmError> {
         let session = SessionData {
             session_token: token.to_string(),
@@ -440,55 +461,9 @@ impl SessionManager {
             auth_provider: auth_provider.map(String::from),
         };
 
-        // Save to disk (always, as bootstrap fallback)
-        if let Some(parent) = self.config.session_path.parent() {
-            tokio::fs::create_dir_all(parent).await.map_err(|e| {
-                LlmError::Io(std::io::Error::new(
-                    e.kind(),
-                    format!("Failed to create session directory: {}", e),
-                ))
-            })?;
-        }
-
-        let json =
-            serde_json::to_string_pretty(&session).map_err(|e| LlmError::SessionRenewalFailed {
-                provider: "nearai".to_string(),
-                reason: format!("Failed to serialize session: {}", e),
-            })?;
-
-        tokio::fs::write(&self.config.session_path, json)
-            .await
-            .map_err(|e| {
-                LlmError::Io(std::io::Error::new(
-                    e.kind(),
-                    format!(
-                        "Failed to write session file {}: {}",
-                        self.config.session_path.display(),
-                        e
-                    ),
-                ))
-            })?;
-
-        // Restrictive permissions: session file contains a secret token
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let perms = std::fs::Permissions::from_mode(0o600);
-            tokio::fs::set_permissions(&self.config.session_path, perms)
-                .await
-                .map_err(|e| {
-                    LlmError::Io(std::io::Error::new(
-                        e.kind(),
-                        format!(
-                            "Failed to set permissions on {}: {}",
-                            self.config.session_path.display(),
-                            e
-                        ),
-                    ))
-                })?;
-        }
-
-        tracing::debug!("Session saved to {}", self.config.session_path.display());
+        let session_store = self.session_store.read().await.clone();
+        session_store.save(&session).await?;
+        tracing::debug!("Session saved to {}", session_store.location());
 
         // Also save to DB if a store is attached
         if let Some(ref store) = *self.store.read().await {
@@ -496,7 +471,7 @@ impl SessionManager {
             let session_json = serde_json::to_value(&session)
                 .unwrap_or(serde_json::Value::String(token.to_string()));
             if let Err(e) = store
-                .set_setting(&user_id, "nearai.session_token", &session_json)
+                .set_setting(&user_id, SESSION_SETTINGS_KEY, &session_json)
                 .await
             {
                 tracing::warn!("Failed to save session to DB: {}", e);
@@ -520,12 +495,12 @@ impl SessionManager {
 
         let user_id = self.user_id.read().await.clone();
         let value = if let Some(value) = store
-            .get_setting(&user_id, "nearai.session_token")
+            .get_setting(&user_id, SESSION_SETTINGS_KEY)
             .await
             .map_err(|e| LlmError::SessionRenewalFailed {
-            provider: "nearai".to_string(),
-            reason: format!("DB query failed: {}", e),
-        })? {
+                provider: "nearai".to_string(),
+                reason: format!("DB query failed: {}", e),
+            })? {
             value
         } else {
             // Try the legacy key. Only warn if it actually exists (real
@@ -567,25 +542,15 @@ impl SessionManager {
         Ok(())
     }
 
-    /// Load session data from disk.
+    /// Load session data from the session store.
     async fn load_session(&self) -> Result<(), LlmError> {
-        let data = tokio::fs::read_to_string(&self.config.session_path)
-            .await
-            .map_err(|e| {
-                LlmError::Io(std::io::Error::new(
-                    e.kind(),
-                    format!(
-                        "Failed to read session file {}: {}",
-                        self.config.session_path.display(),
-                        e
-                    ),
-                ))
-            })?;
-
-        let session: SessionData =
-            serde_json::from_str(&data).map_err(|e| LlmError::SessionRenewalFailed {
+        let store = self.session_store.read().await.clone();
+        let session = store
+            .load()
+            .await?
+            .ok_or_else(|| LlmError::SessionRenewalFailed {
                 provider: "nearai".to_string(),
-                reason: format!("Failed to parse session file: {}", e),
+                reason: format!("No session in {}", store.location()),
             })?;
 
         {
@@ -595,7 +560,7 @@ impl SessionManager {
 
         tracing::info!(
             "Loaded session from {} (created: {})",
-            self.config.session_path.display(),
+            store.location(),
             session.created_at
         );
 
@@ -0,0 +1,287 @@
+//! Where NEAR AI session tokens are persisted.
+//!
+//! [`SessionManager`](crate::llm::SessionManager) reads and writes its
+//! token through a [`SessionStore`]. The default [`FileSessionStore`]
+//! keeps it in `~/.ironclaw/session.json`; [`PostgresSessionStore`] keeps
+//! it in the database so every replica of a deployment shares one login.
+
+#[cfg(feature = "postgres")]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "postgres")]
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+#[cfg(feature = "postgres")]
+use crate::config::SessionStoreKind;
+use crate::error::LlmError;
+use crate::llm::session::SessionData;
+
+/// Settings key the session is stored under, shared with the database
+/// mirror set up by `SessionManager::attach_store`.
+pub const SESSION_SETTINGS_KEY: &str = "nearai.session_token";
+
+/// Persistence for a single session.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// The stored session, or `None` if nothing has been saved.
+    async fn load(&self) -> Result<Option<SessionData>, LlmError>;
+
+    /// Replace the stored session.
+    async fn save(&self, session: &SessionData) -> Result<(), LlmError>;
+
+    /// Remove the stored session. Succeeds if there was none.
+    async fn delete(&self) -> Result<(), LlmError>;
+
+    /// Human-readable location, for logs.
+    fn location(&self) -> String;
+}
+
+/// Stores the session as JSON in a file readable only by the owner.
+pub struct FileSessionStore {
+    path: PathBuf,
+}
+
+impl FileSessionStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn io_error(&self, action: &str, e: std::io::Error) -> LlmError {
+        LlmError::Io(std::io::Error::new(
+            e.kind(),
+            format!("Failed to {} {}: {}", action, self.path.display(), e),
+        ))
+    }
+}
+
+#[async_trait]
+impl SessionStore for FileSessionStore {
+    async fn load(&self) -> Result<Option<SessionData>, LlmError> {
+        let data = match tokio::fs::read_to_string(&self.path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(self.io_error("read session file", e)),
+        };
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| LlmError::SessionRenewalFailed {
+                provider: "nearai".to_string(),
+                reason: format!("Failed to parse session file: {}", e),
+            })
+    }
+
+    async fn save(&self, session: &SessionData) -> Result<(), LlmError> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| self.io_error("create session directory for", e))?;
+        }
+
+        let json =
+            serde_json::to_string_pretty(session).map_err(|e| LlmError::SessionRenewalFailed {
+                provider: "nearai".to_string(),
+                reason: format!("Failed to serialize session: {}", e),
+            })?;
+
+        tokio::fs::write(&self.path, json)
+            .await
+            .map_err(|e| self.io_error("write session file", e))?;
+
+        // Restrictive permissions: session file contains a secret token
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let perms = std::fs::Permissions::from_mode(0o600);
+            tokio::fs::set_permissions(&self.path, perms)
+                .await
+                .map_err(|e| self.io_error("set permissions on", e))?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self) -> Result<(), LlmError> {
+        match tokio::fs::remove_file(&self.path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(self.io_error("remove session file", e)),
+        }
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+/// The store `kind` names. `Postgres` needs `pool`; without one the
+/// session file at `session_path` is kept so the login isn't lost.
+#[cfg(feature = "postgres")]
+pub fn select_session_store(
+    kind: SessionStoreKind,
+    session_path: &Path,
+    pool: Option<&deadpool_postgres::Pool>,
+    user_id: &str,
+) -> Arc<dyn SessionStore> {
+    match (kind, pool) {
+        (SessionStoreKind::Postgres, Some(pool)) => {
+            Arc::new(PostgresSessionStore::new(pool.clone(), user_id))
+        }
+        (SessionStoreKind::Postgres, None) => {
+            tracing::warn!(
+                "NEARAI_SESSION_STORE=postgres needs the PostgreSQL backend; keeping the session file"
+            );
+            Arc::new(FileSessionStore::new(session_path))
+        }
+        (SessionStoreKind::File, _) => Arc::new(FileSessionStore::new(session_path)),
+    }
+}
+
+/// Stores the session in the `settings` table, under
+/// [`SESSION_SETTINGS_KEY`] for the given user.
+#[cfg(feature = "postgres")]
+pub struct PostgresSessionStore {
+    pool: deadpool_postgres::Pool,
+    user_id: String,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresSessionStore {
+    pub fn new(pool: deadpool_postgres::Pool, user_id: impl Into<String>) -> Self {
+        Self {
+            pool,
+            user_id: user_id.into(),
+        }
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Object, LlmError> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| db_error("get connection", e))
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn db_error(action: &str, e: impl std::fmt::Display) -> LlmError {
+    LlmError::SessionRenewalFailed {
+        provider: "nearai".to_string(),
+        reason: format!("Failed to {} for session store: {}", action, e),
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl SessionStore for PostgresSessionStore {
+    async fn load(&self) -> Result<Option<SessionData>, LlmError> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                "SELECT value FROM settings WHERE user_id = $1 AND key = $2",
+                &[&self.user_id, &SESSION_SETTINGS_KEY],
+            )
+            .await
+            .map_err(|e| db_error("load session", e))?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let value: serde_json::Value = row.get(0);
+        serde_json::from_value(value)
+            .map(Some)
+            .map_err(|e| LlmError::SessionRenewalFailed {
+                provider: "nearai".to_string(),
+                reason: format!("Failed to parse DB session: {}", e),
+            })
+    }
+
+    async fn save(&self, session: &SessionData) -> Result<(), LlmError> {
+        let value = serde_json::to_value(session).map_err(|e| LlmError::SessionRenewalFailed {
+            provider: "nearai".to_string(),
+            reason: format!("Failed to serialize session: {}", e),
+        })?;
+        let client = self.client().await?;
+        client
+            .execute(
+                r#"
+                INSERT INTO settings (user_id, key, value, updated_at)
+                VALUES ($1, $2, $3, NOW())
+                ON CONFLICT (user_id, key) DO UPDATE SET
+                    value = EXCLUDED.value,
+                    updated_at = NOW()
+                "#,
+                &[&self.user_id, &SESSION_SETTINGS_KEY, &value],
+            )
+            .await
+            .map_err(|e| db_error("save session", e))?;
+        Ok(())
+    }
+
+    async fn delete(&self) -> Result<(), LlmError> {
+        let client = self.client().await?;
+        client
+            .execute(
+                "DELETE FROM settings WHERE user_id = $1 AND key = $2",
+                &[&self.user_id, &SESSION_SETTINGS_KEY],
+            )
+            .await
+            .map_err(|e| db_error("delete session", e))?;
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        format!("database settings ({})", self.user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn session(token: &str) -> SessionData {
+        SessionData {
+            session_token: token.to_string(),
+            created_at: Utc::now(),
+            auth_provider: Some("github".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn file_store_round_trips_token() {
+        let dir = tempdir().unwrap();
+        let store = FileSessionStore::new(dir.path().join("nested").join("session.json"));
+        assert!(store.load().await.unwrap().is_none());
+
+        store.save(&session("sess_abc")).await.unwrap();
+        let loaded = store.load().await.unwrap().unwrap();
+        assert_eq!(loaded.session_token, "sess_abc");
+        assert_eq!(loaded.auth_provider.as_deref(), Some("github"));
+
+        store.delete().await.unwrap();
+        assert!(store.load().await.unwrap().is_none());
+        store.delete().await.unwrap();
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn stores_are_selectable_at_runtime() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        // Pools connect lazily, so no server is needed to build the store.
+        let pool =
+            crate::db::postgres::create_pool("postgres://localhost/unused", &Default::default())
+                .unwrap();
+
+        let file = select_session_store(SessionStoreKind::File, &path, Some(&pool), "default");
+        assert_eq!(file.location(), path.display().to_string());
+
+        let db = select_session_store(SessionStoreKind::Postgres, &path, Some(&pool), "default");
+        assert_eq!(db.location(), "database settings (default)");
+
+        let no_pool = select_session_store(SessionStoreKind::Postgres, &path, None, "default");
+        assert_eq!(no_pool.location(), path.display().to_string());
+    }
+}
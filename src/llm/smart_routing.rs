@@ -15,8 +15,8 @@ use rust_decimal::Decimal;
 
 use crate::error::LlmError;
 use crate::llm::provider::{
-    CompletionRequest, CompletionResponse, LlmProvider, ModelMetadata, Role, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata, Role,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
 
 /// Classification of a request's complexity, determining which model handles it.
@@ -340,6 +340,20 @@ impl LlmProvider for SmartRoutingProvider {
         self.primary.complete_with_tools(request).await
     }
 
+    /// Streamed tool use goes to the primary model, like `complete_with_tools`.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        self.stats.total_requests.fetch_add(1, Ordering::Relaxed);
+        self.stats.primary_requests.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(
+            model = %self.primary.model_name(),
+            "Smart routing: Streamed tool use -> primary model (always)"
+        );
+        self.primary.complete_stream(request).await
+    }
+
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         self.primary.list_models().await
     }
@@ -348,6 +362,17 @@ impl LlmProvider for SmartRoutingProvider {
         self.primary.model_metadata().await
     }
 
+    /// A request can go to either model, so an override only holds when
+    /// both honor it; otherwise the primary's active model is reported.
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        let primary = self.primary.effective_model_name(requested_model);
+        if requested_model.is_some() && self.cheap.effective_model_name(requested_model) != primary
+        {
+            return self.primary.active_model_name();
+        }
+        primary
+    }
+
     fn active_model_name(&self) -> String {
         self.primary.active_model_name()
     }
@@ -6,11 +6,14 @@ use clap::Parser;
 use tracing_subscriber::EnvFilter;
 
 use ironclaw::{
-    agent::{Agent, AgentDeps},
+    agent::{
+        Agent, AgentDeps,
+        transcript::{TranscriptHook, TranscriptLlm, TranscriptRecorder},
+    },
     app::{AppBuilder, AppBuilderFlags},
     channels::{
-        ChannelManager, GatewayChannel, HttpChannel, ReplChannel, SignalChannel, WebhookServer,
-        WebhookServerConfig,
+        ChannelManager, GatewayChannel, HttpChannel, QuietHours, ReplChannel, SignalChannel,
+        WebhookServer, WebhookServerConfig,
         wasm::{
             RegisteredEndpoint, SharedWasmChannel, WasmChannelLoader, WasmChannelRouter,
             WasmChannelRuntime, WasmChannelRuntimeConfig, create_wasm_channel_router,
@@ -30,6 +33,7 @@ use ironclaw::{
     },
     pairing::PairingStore,
     secrets::SecretsStore,
+    shutdown::{DEFAULT_DRAIN_DEADLINE, Shutdown, ShutdownStage, wait_for_signal},
 };
 
 #[cfg(any(feature = "postgres", feature = "libsql"))]
@@ -78,6 +82,12 @@ async fn main() -> anyhow::Result<()> {
             init_cli_tracing();
             return run_service_command(service_cmd);
         }
+        Some(Command::Migrate) => {
+            init_cli_tracing();
+            let _ = dotenvy::dotenv();
+            ironclaw::bootstrap::load_ironclaw_env();
+            return ironclaw::cli::run_migrate_command().await;
+        }
         Some(Command::Doctor) => {
             init_cli_tracing();
             let _ = dotenvy::dotenv();
@@ -114,22 +124,27 @@ async fn main() -> anyhow::Result<()> {
         Some(Command::Onboard {
             skip_auth,
             channels_only,
+            dry_run,
+            from_file,
         }) => {
             let _ = dotenvy::dotenv();
             ironclaw::bootstrap::load_ironclaw_env();
 
             #[cfg(any(feature = "postgres", feature = "libsql"))]
             {
-                let config = SetupConfig {
-                    skip_auth: *skip_auth,
-                    channels_only: *channels_only,
+                let mut wizard = match from_file {
+                    Some(path) => SetupWizard::from_file(path)?,
+                    None => SetupWizard::with_config(SetupConfig {
+                        skip_auth: *skip_auth,
+                        channels_only: *channels_only,
+                        dry_run: *dry_run,
+                    }),
                 };
-                let mut wizard = SetupWizard::with_config(config);
                 wizard.run().await?;
             }
             #[cfg(not(any(feature = "postgres", feature = "libsql")))]
             {
-                let _ = (skip_auth, channels_only);
+                let _ = (skip_auth, channels_only, dry_run, from_file);
                 eprintln!("Onboarding wizard requires the 'postgres' or 'libsql' feature.");
             }
             return Ok(());
@@ -308,7 +323,15 @@ async fn main() -> anyhow::Result<()> {
 
     // ── Channel setup ──────────────────────────────────────────────────
 
-    let channels = ChannelManager::new();
+    let mut channels = ChannelManager::new()
+        .with_startup_health_check(config.channels.startup_health_check)
+        .with_broadcast_throttle(config.channels.broadcast_throttle.clone())
+        .with_split_numbering(config.channels.number_split_messages)
+        .with_inbound_dedup(config.channels.inbound_dedup_ttl)
+        .with_backpressure(config.channels.backpressure.clone());
+    if let Some(quiet_hours) = config.channels.quiet_hours {
+        channels = channels.with_quiet_hours(QuietHours::new(quiet_hours));
+    }
     let mut channel_names: Vec<String> = Vec::new();
     let mut loaded_wasm_channel_names: Vec<String> = Vec::new();
     #[allow(clippy::type_complexity)]
@@ -328,6 +351,10 @@ async fn main() -> anyhow::Result<()> {
     } else {
         None
     };
+    let repl_channel = match (repl_channel, &components.workspace) {
+        (Some(repl), Some(ws)) => Some(repl.with_workspace(Arc::clone(ws))),
+        (repl, _) => repl,
+    };
 
     if let Some(repl) = repl_channel {
         channels.add(Box::new(repl)).await;
@@ -509,14 +536,53 @@ async fn main() -> anyhow::Result<()> {
             if let Some(ref tx) = job_event_tx {
                 let mut rx = tx.subscribe();
                 let gw_state = Arc::clone(gw.state());
+                let store = components.db.clone();
                 tokio::spawn(async move {
-                    while let Ok((_job_id, event)) = rx.recv().await {
-                        gw_state.sse.broadcast(event);
+                    // Job events go only to the user who owns the job.
+                    let mut owners = std::collections::HashMap::<uuid::Uuid, String>::new();
+                    while let Ok((job_id, event)) = rx.recv().await {
+                        if !owners.contains_key(&job_id) {
+                            let owner = match store {
+                                Some(ref db) => db
+                                    .get_sandbox_job(job_id)
+                                    .await
+                                    .ok()
+                                    .flatten()
+                                    .map(|job| job.user_id),
+                                None => None,
+                            };
+                            owners
+                                .insert(job_id, owner.unwrap_or_else(|| gw_state.user_id.clone()));
+                        }
+                        gw_state.sse.broadcast(&owners[&job_id], event);
                     }
                 });
             }
         }
 
+        // Spawned after the last `with_*` call: each one rebuilds the state,
+        // and with it the SSE manager.
+        if let Some(ref ext_mgr) = components.extension_manager {
+            // Surface extension download progress as status events.
+            let mut rx = ext_mgr.subscribe_download_progress();
+            let gw_state = Arc::clone(gw.state());
+            tokio::spawn(async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(progress) => gw_state.sse.broadcast(
+                            &gw_state.user_id,
+                            ironclaw::channels::web::types::SseEvent::Status {
+                                message: progress.to_string(),
+                                thread_id: None,
+                            },
+                        ),
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+
         gateway_url = Some(format!(
             "http://{}:{}/?token={}",
             gw_config.host,
@@ -597,10 +663,71 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Channel runtime wired into extension manager for hot-activation");
     }
 
+    let mut shutdown = Shutdown::new(DEFAULT_DRAIN_DEADLINE);
+    {
+        let channels = Arc::clone(&channels);
+        shutdown.register(ShutdownStage::Channels, "channels", move || async move {
+            channels.shutdown_all().await.map_err(|e| e.to_string())
+        });
+    }
+    #[cfg(feature = "zkproxy")]
+    if let Some(proxy) = components.safety.zk_proxy().cloned() {
+        let worker = Arc::clone(&proxy);
+        shutdown.register(
+            ShutdownStage::Worker,
+            "zkproxy worker",
+            move || async move {
+                worker.shutdown().await;
+                Ok(())
+            },
+        );
+        shutdown.register(
+            ShutdownStage::Audit,
+            "zkproxy audit log",
+            move || async move { proxy.flush_audit() },
+        );
+    }
+    if let Some(db) = components.db.clone() {
+        shutdown.register(
+            ShutdownStage::Database,
+            "database pool",
+            move || async move {
+                db.close().await;
+                Ok(())
+            },
+        );
+    }
+
+    let mut llm = components.llm;
+    let mut cheap_llm = components.cheap_llm;
+    if let Some(ref path) = config.agent.transcript_path {
+        match TranscriptRecorder::create(path) {
+            Ok(recorder) => {
+                let recorder = Arc::new(recorder);
+                components
+                    .hooks
+                    .register(Arc::new(TranscriptHook::new(Arc::clone(&recorder))))
+                    .await;
+                let flushed = Arc::clone(&recorder);
+                shutdown.register(ShutdownStage::Audit, "transcript", move || async move {
+                    flushed.flush().map_err(|e| e.to_string())
+                });
+                components.safety.set_observer(Arc::clone(&recorder) as _);
+                cheap_llm = cheap_llm
+                    .map(|cheap| Arc::new(TranscriptLlm::new(cheap, Arc::clone(&recorder))) as _);
+                llm = Arc::new(TranscriptLlm::new(llm, recorder));
+                tracing::info!(path = %path.display(), "Recording agent transcript");
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Failed to open transcript file: {}", e);
+            }
+        }
+    }
+
     let deps = AgentDeps {
         store: components.db,
-        llm: components.llm,
-        cheap_llm: components.cheap_llm,
+        llm,
+        cheap_llm,
         safety: components.safety,
         tools: components.tools,
         workspace: components.workspace,
@@ -621,12 +748,27 @@ async fn main() -> anyhow::Result<()> {
         Some(config.routines.clone()),
         Some(components.context_manager),
         Some(session_manager),
-    );
-
-    agent.run().await?;
+    )
+    .with_shutdown(shutdown.handle());
+
+    let signal_handle = shutdown.handle();
+    tokio::spawn(async move {
+        wait_for_signal().await;
+        tracing::info!("Shutdown signal received");
+        signal_handle.trigger();
+    });
+
+    tokio::select! {
+        result = agent.run() => result?,
+        _ = shutdown.drain_expired() => {
+            tracing::warn!("In-flight turns did not finish in time, stopping agent");
+        }
+    }
 
     // ── Shutdown ────────────────────────────────────────────────────────
 
+    shutdown.run().await;
+
     if let Some(ref mut server) = webhook_server {
         server.shutdown().await;
     }
@@ -665,9 +807,7 @@ async fn run_memory_command(mem_cmd: &ironclaw::cli::MemoryCommand) -> anyhow::R
     })
     .await;
 
-    let embeddings = config
-        .embeddings
-        .create_provider(&config.llm.nearai.base_url, session);
+    let embeddings = config.embeddings.create_provider(&config.llm, session);
 
     // Warn if libSQL backend is used with non-1536 embedding dimension.
     if config.database.backend == ironclaw::config::DatabaseBackend::LibSql
@@ -6,26 +6,29 @@
 //! |---------|-------------|
 //! | `noop`  | Zero overhead, discards everything (default) |
 //! | `log`   | Emits structured events via `tracing` |
+//! | `prometheus` | Records into a Prometheus registry served on `/metrics` |
 //! | `multi` | Fan-out to multiple backends simultaneously |
 //!
 //! The [`create_observer`] factory builds the right backend from
-//! [`ObservabilityConfig`]. Future backends (OpenTelemetry, Prometheus)
-//! can be added by implementing [`Observer`].
+//! [`ObservabilityConfig`]. Future backends (OpenTelemetry) can be added
+//! by implementing [`Observer`].
 
 mod log;
 mod multi;
 mod noop;
+pub mod prometheus;
 pub mod traits;
 
 pub use self::log::LogObserver;
 pub use self::multi::MultiObserver;
 pub use self::noop::NoopObserver;
+pub use self::prometheus::PrometheusObserver;
 pub use self::traits::{Observer, ObserverEvent, ObserverMetric};
 
 /// Configuration for the observability backend.
 #[derive(Debug, Clone)]
 pub struct ObservabilityConfig {
-    /// Backend name: "none", "noop", "log".
+    /// Backend name: "none", "noop", "log", "prometheus".
     pub backend: String,
 }
 
@@ -40,10 +43,19 @@ impl Default for ObservabilityConfig {
 /// Create an observer from configuration.
 ///
 /// Returns a [`NoopObserver`] for "none"/"noop" (or unknown values),
-/// and a [`LogObserver`] for "log".
+/// a [`LogObserver`] for "log", and a [`PrometheusObserver`] for
+/// "prometheus" (installing the global recorder; falls back to no-op if
+/// that fails).
 pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
     match config.backend.as_str() {
         "log" => Box::new(LogObserver),
+        "prometheus" => match prometheus::install() {
+            Ok(_) => Box::new(PrometheusObserver),
+            Err(e) => {
+                tracing::warn!("{}", e);
+                Box::new(NoopObserver)
+            }
+        },
         _ => Box::new(NoopObserver),
     }
 }
@@ -79,12 +91,21 @@ mod tests {
     #[test]
     fn factory_returns_noop_for_unknown() {
         let cfg = ObservabilityConfig {
-            backend: "prometheus".into(),
+            backend: "otel".into(),
         };
         let obs = create_observer(&cfg);
         assert_eq!(obs.name(), "noop");
     }
 
+    #[test]
+    fn factory_returns_prometheus_for_prometheus() {
+        let cfg = ObservabilityConfig {
+            backend: "prometheus".into(),
+        };
+        let obs = create_observer(&cfg);
+        assert_eq!(obs.name(), "prometheus");
+    }
+
     #[test]
     fn factory_returns_log_for_log() {
         let cfg = ObservabilityConfig {
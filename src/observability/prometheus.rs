@@ -0,0 +1,228 @@
+//! Prometheus observer backed by the `metrics` crate.
+//!
+//! [`install`] registers a process-wide Prometheus recorder; the web
+//! gateway renders it on `/metrics`. [`PrometheusObserver`] maps observer
+//! events onto that recorder, and layers that have no observer (response
+//! cache, circuit breaker, tool execution, zkproxy) call the `record_*`
+//! helpers below directly. The helpers are no-ops until a recorder is
+//! installed. Gauges that are sampled rather than event-driven (database
+//! pool usage) register a collector that [`render`] runs before each scrape.
+//!
+//! Label conventions: `provider`, `model`, `tool`, `channel`, plus
+//! `outcome` (`success`/`error`) where a call can fail.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
+
+pub const LLM_REQUESTS_TOTAL: &str = "llm_requests_total";
+pub const LLM_REQUEST_DURATION_SECONDS: &str = "llm_request_duration_seconds";
+pub const LLM_TOKENS_TOTAL: &str = "llm_tokens_total";
+pub const LLM_CACHE_LOOKUPS_TOTAL: &str = "llm_cache_lookups_total";
+pub const LLM_CIRCUIT_OPEN: &str = "llm_circuit_open";
+pub const LLM_CIRCUIT_REJECTIONS_TOTAL: &str = "llm_circuit_rejections_total";
+pub const TOOL_CALLS_TOTAL: &str = "tool_calls_total";
+pub const TOOL_CALL_DURATION_SECONDS: &str = "tool_call_duration_seconds";
+pub const CHANNEL_MESSAGES_TOTAL: &str = "channel_messages_total";
+pub const ERRORS_TOTAL: &str = "errors_total";
+pub const ACTIVE_JOBS: &str = "active_jobs";
+pub const QUEUE_DEPTH: &str = "queue_depth";
+pub const ZKPROXY_DECISIONS_TOTAL: &str = "zkproxy_decisions_total";
+pub const ZKPROXY_STAGE_DURATION_SECONDS: &str = "zkproxy_stage_duration_seconds";
+pub const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+pub const DB_POOL_WAITERS: &str = "db_pool_waiters";
+pub const DB_POOL_MAX_SIZE: &str = "db_pool_max_size";
+
+static HANDLE: OnceLock<Result<PrometheusHandle, String>> = OnceLock::new();
+
+type Collector = Box<dyn Fn() + Send + Sync>;
+
+static COLLECTORS: Mutex<Vec<Collector>> = Mutex::new(Vec::new());
+
+/// Install the global Prometheus recorder, or return the one already
+/// installed. Fails if another `metrics` recorder was installed first.
+pub fn install() -> Result<PrometheusHandle, String> {
+    HANDLE
+        .get_or_init(|| {
+            PrometheusBuilder::new()
+                .install_recorder()
+                .map_err(|e| format!("Failed to install Prometheus recorder: {e}"))
+        })
+        .clone()
+}
+
+/// The installed recorder's handle, if [`install`] succeeded.
+pub fn handle() -> Option<PrometheusHandle> {
+    HANDLE.get().and_then(|h| h.as_ref().ok()).cloned()
+}
+
+/// Run `collector` before every scrape to refresh sampled gauges.
+pub fn register_collector(collector: impl Fn() + Send + Sync + 'static) {
+    COLLECTORS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(Box::new(collector));
+}
+
+/// Render the exposition text, or `None` if no recorder is installed.
+pub fn render() -> Option<String> {
+    let handle = handle()?;
+    for collector in COLLECTORS.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        collector();
+    }
+    Some(handle.render())
+}
+
+fn outcome(success: bool) -> &'static str {
+    if success { "success" } else { "error" }
+}
+
+/// One tool execution.
+pub fn record_tool_call(tool: &str, duration: Duration, success: bool) {
+    counter!(TOOL_CALLS_TOTAL, "tool" => tool.to_string(), "outcome" => outcome(success))
+        .increment(1);
+    histogram!(TOOL_CALL_DURATION_SECONDS, "tool" => tool.to_string())
+        .record(duration.as_secs_f64());
+}
+
+/// A message received from (`inbound`) or sent to (`outbound`) a channel.
+pub fn record_channel_message(channel: &str, direction: &'static str) {
+    counter!(
+        CHANNEL_MESSAGES_TOTAL,
+        "channel" => channel.to_string(),
+        "direction" => direction
+    )
+    .increment(1);
+}
+
+/// One response-cache lookup.
+pub fn record_cache_lookup(model: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    counter!(LLM_CACHE_LOOKUPS_TOTAL, "model" => model.to_string(), "result" => result)
+        .increment(1);
+}
+
+/// The circuit breaker for `provider` opened (`true`) or closed.
+pub fn record_circuit_open(provider: &str, open: bool) {
+    gauge!(LLM_CIRCUIT_OPEN, "provider" => provider.to_string()).set(if open { 1.0 } else { 0.0 });
+}
+
+/// A call refused because the circuit for `provider` is open.
+pub fn record_circuit_rejection(provider: &str) {
+    counter!(LLM_CIRCUIT_REJECTIONS_TOTAL, "provider" => provider.to_string()).increment(1);
+}
+
+/// Current database connection-pool usage.
+pub fn record_db_pool(in_use: usize, idle: usize, waiters: usize, max_size: usize) {
+    gauge!(DB_POOL_CONNECTIONS, "state" => "in_use").set(in_use as f64);
+    gauge!(DB_POOL_CONNECTIONS, "state" => "idle").set(idle as f64);
+    gauge!(DB_POOL_WAITERS).set(waiters as f64);
+    gauge!(DB_POOL_MAX_SIZE).set(max_size as f64);
+}
+
+/// One zkproxy guard decision and its per-stage timings in milliseconds.
+pub fn record_zkproxy_decision(blocked: bool, stages_ms: &[(&'static str, f64)]) {
+    let decision = if blocked { "blocked" } else { "allowed" };
+    counter!(ZKPROXY_DECISIONS_TOTAL, "decision" => decision).increment(1);
+    for (stage, ms) in stages_ms {
+        histogram!(ZKPROXY_STAGE_DURATION_SECONDS, "stage" => *stage).record(ms / 1000.0);
+    }
+}
+
+/// Observer that records into the global Prometheus recorder.
+pub struct PrometheusObserver;
+
+impl Observer for PrometheusObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        match event {
+            ObserverEvent::LlmRequest {
+                provider, model, ..
+            } => {
+                counter!(
+                    LLM_REQUESTS_TOTAL,
+                    "provider" => provider.clone(),
+                    "model" => model.clone()
+                )
+                .increment(1);
+            }
+            ObserverEvent::LlmResponse {
+                provider,
+                model,
+                duration,
+                success,
+                ..
+            } => {
+                histogram!(
+                    LLM_REQUEST_DURATION_SECONDS,
+                    "provider" => provider.clone(),
+                    "model" => model.clone(),
+                    "outcome" => outcome(*success)
+                )
+                .record(duration.as_secs_f64());
+            }
+            ObserverEvent::ToolCallEnd {
+                tool,
+                duration,
+                success,
+            } => record_tool_call(tool, *duration, *success),
+            ObserverEvent::ChannelMessage { channel, direction } => {
+                counter!(
+                    CHANNEL_MESSAGES_TOTAL,
+                    "channel" => channel.clone(),
+                    "direction" => direction.clone()
+                )
+                .increment(1);
+            }
+            ObserverEvent::Error { component, .. } => {
+                counter!(ERRORS_TOTAL, "component" => component.clone()).increment(1);
+            }
+            ObserverEvent::AgentStart { .. }
+            | ObserverEvent::ToolCallStart { .. }
+            | ObserverEvent::TurnComplete
+            | ObserverEvent::HeartbeatTick
+            | ObserverEvent::AgentEnd { .. } => {}
+        }
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        match metric {
+            ObserverMetric::RequestLatency(_) => {
+                // Covered by the labelled LlmResponse histogram.
+            }
+            ObserverMetric::TokensUsed(n) => counter!(LLM_TOKENS_TOTAL).increment(*n),
+            ObserverMetric::ActiveJobs(n) => gauge!(ACTIVE_JOBS).set(*n as f64),
+            ObserverMetric::QueueDepth(n) => gauge!(QUEUE_DEPTH).set(*n as f64),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "prometheus"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn llm_requests_are_rendered_with_labels() {
+        let handle = install().unwrap();
+        PrometheusObserver.record_event(&ObserverEvent::LlmRequest {
+            provider: "nearai".into(),
+            model: "prom-test-model".into(),
+            message_count: 2,
+        });
+
+        let text = handle.render();
+        assert!(
+            text.lines().any(|l| l.starts_with(LLM_REQUESTS_TOTAL)
+                && l.contains(r#"provider="nearai""#)
+                && l.contains(r#"model="prom-test-model""#)),
+            "{text}"
+        );
+    }
+}
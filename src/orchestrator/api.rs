@@ -146,6 +146,9 @@ async fn llm_complete(
         model: req.model,
         max_tokens: req.max_tokens,
         temperature: req.temperature,
+        top_p: req.top_p,
+        frequency_penalty: req.frequency_penalty,
+        presence_penalty: req.presence_penalty,
         stop_sequences: req.stop_sequences,
         metadata: std::collections::HashMap::new(),
     };
@@ -174,6 +177,9 @@ async fn llm_complete_with_tools(
         model: req.model,
         max_tokens: req.max_tokens,
         temperature: req.temperature,
+        top_p: req.top_p,
+        frequency_penalty: req.frequency_penalty,
+        presence_penalty: req.presence_penalty,
         tool_choice: req.tool_choice,
         metadata: std::collections::HashMap::new(),
     };
@@ -0,0 +1,145 @@
+//! Locating wrapped sections for [`crate::safety::SafetyLayer::scan_nested`].
+//!
+//! Two wrappers are recognised: [`crate::safety::wrap_external_content`]
+//! with the default delimiters, and `SafetyLayer::wrap_for_llm`. Custom
+//! [`crate::safety::ExternalContentWrapper`] delimiters are not.
+
+use std::ops::Range;
+
+use crate::safety::escape_xml_content;
+use crate::safety::external::{DEFAULT_BEGIN, DEFAULT_END};
+
+const TOOL_OUTPUT_OPEN: &str = "<tool_output ";
+const TOOL_OUTPUT_CLOSE: &str = "\n</tool_output>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SectionKind {
+    /// `--- BEGIN EXTERNAL CONTENT ---` ... `--- END EXTERNAL CONTENT ---`
+    External,
+    /// `<tool_output ...>` ... `</tool_output>`, body XML-escaped.
+    ToolOutput,
+}
+
+impl SectionKind {
+    /// The body as it was before wrapping.
+    pub(super) fn decode(&self, body: &str) -> String {
+        match self {
+            Self::External => body.to_string(),
+            Self::ToolOutput => body
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&"),
+        }
+    }
+
+    /// Inverse of [`decode`](Self::decode).
+    pub(super) fn encode(&self, content: &str) -> String {
+        match self {
+            Self::External => content.to_string(),
+            Self::ToolOutput => escape_xml_content(content),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Section {
+    pub kind: SectionKind,
+    /// Where the opening marker starts.
+    pub start: usize,
+    /// The wrapped body, excluding the markers.
+    pub body: Range<usize>,
+}
+
+/// The first complete wrapped section in `content`, if any.
+pub(super) fn find_section(content: &str) -> Option<Section> {
+    match (find_external(content), find_tool_output(content)) {
+        (Some(a), Some(b)) => Some(if a.start <= b.start { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// An external-content block, matching nested BEGIN/END pairs.
+fn find_external(content: &str) -> Option<Section> {
+    let begin = format!("{DEFAULT_BEGIN}\n");
+    let start = content.find(&begin)?;
+    let body_start = start + begin.len();
+
+    let mut depth = 1;
+    let mut pos = body_start;
+    loop {
+        let next_end = pos + content[pos..].find(DEFAULT_END)?;
+        match content[pos..].find(&begin).map(|i| pos + i) {
+            Some(next_begin) if next_begin < next_end => {
+                depth += 1;
+                pos = next_begin + begin.len();
+            }
+            _ => {
+                depth -= 1;
+                if depth == 0 {
+                    // `wrap` puts a newline between the body and the end marker.
+                    let body_end = if content[..next_end].ends_with('\n') {
+                        next_end - 1
+                    } else {
+                        next_end
+                    };
+                    return Some(Section {
+                        kind: SectionKind::External,
+                        start,
+                        body: body_start..body_end.max(body_start),
+                    });
+                }
+                pos = next_end + DEFAULT_END.len();
+            }
+        }
+    }
+}
+
+/// A `<tool_output>` block. Its body is escaped, so it cannot contain a
+/// literal closing tag of a nested block.
+fn find_tool_output(content: &str) -> Option<Section> {
+    let start = content.find(TOOL_OUTPUT_OPEN)?;
+    let body_start = start + content[start..].find(">\n")? + 2;
+    let body_end = body_start + content[body_start - 1..].find(TOOL_OUTPUT_CLOSE)? - 1;
+    Some(Section {
+        kind: SectionKind::ToolOutput,
+        start,
+        body: body_start..body_end.max(body_start),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_outermost_external_section() {
+        let inner = crate::safety::wrap_external_content("webhook", "payload");
+        let outer = crate::safety::wrap_external_content("email", &format!("fwd:\n{inner}"));
+
+        let section = find_section(&outer).unwrap();
+        assert_eq!(section.kind, SectionKind::External);
+        assert_eq!(&outer[section.body], format!("fwd:\n{inner}"));
+    }
+
+    #[test]
+    fn finds_tool_output_body_and_round_trips_escaping() {
+        let wrapped =
+            "<tool_output name=\"t\" sanitized=\"true\">\na &lt;b&gt; &amp; c\n</tool_output>";
+        let section = find_section(wrapped).unwrap();
+        assert_eq!(section.kind, SectionKind::ToolOutput);
+        let body = &wrapped[section.body];
+        assert_eq!(body, "a &lt;b&gt; &amp; c");
+        let decoded = SectionKind::ToolOutput.decode(body);
+        assert_eq!(decoded, "a <b> & c");
+        assert_eq!(SectionKind::ToolOutput.encode(&decoded), body);
+
+        let empty = "<tool_output name=\"t\" sanitized=\"true\">\n\n</tool_output>";
+        assert_eq!(find_section(empty).unwrap().body.len(), 0);
+    }
+
+    #[test]
+    fn unterminated_sections_are_ignored() {
+        assert!(find_section("--- BEGIN EXTERNAL CONTENT ---\nno end").is_none());
+        assert!(find_section("<tool_output name=\"t\">\nno close").is_none());
+    }
+}
@@ -1,11 +1,16 @@
 //! Safety policy rules.
 
 use std::cmp::Ordering;
+use std::path::Path;
 
 use regex::Regex;
+use serde::Deserialize;
+
+use crate::safety::SafetyInitError;
 
 /// Severity level for safety issues.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Severity {
     Low,
     Medium,
@@ -77,7 +82,8 @@ impl PolicyRule {
 }
 
 /// Action to take when a policy is violated.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PolicyAction {
     /// Log a warning but allow.
     Warn,
@@ -124,6 +130,51 @@ impl Policy {
     pub fn rules(&self) -> &[PolicyRule] {
         &self.rules
     }
+
+    /// The default policy extended with the rules in a JSON file, or a TOML
+    /// file when the path ends in `.toml`:
+    ///
+    /// ```json
+    /// { "rules": [{ "id": "no_internal_hosts", "description": "...",
+    ///   "pattern": "corp\\.internal", "severity": "high", "action": "block" }] }
+    /// ```
+    ///
+    /// Every pattern is compiled here; the first invalid one fails the load
+    /// with its rule ID.
+    pub fn from_file(path: &Path) -> Result<Self, SafetyInitError> {
+        #[derive(Deserialize)]
+        struct PolicyFile {
+            rules: Vec<RuleSpec>,
+        }
+
+        #[derive(Deserialize)]
+        struct RuleSpec {
+            id: String,
+            #[serde(default)]
+            description: String,
+            pattern: String,
+            severity: Severity,
+            action: PolicyAction,
+        }
+
+        let file: PolicyFile = crate::safety::read_rules_file(path)?;
+        let mut policy = Self::default();
+        for spec in file.rules {
+            let pattern = Regex::new(&spec.pattern).map_err(|source| SafetyInitError::Pattern {
+                path: path.to_path_buf(),
+                name: spec.id.clone(),
+                source,
+            })?;
+            policy.add_rule(PolicyRule {
+                id: spec.id,
+                description: spec.description,
+                severity: spec.severity,
+                pattern,
+                action: spec.action,
+            });
+        }
+        Ok(policy)
+    }
 }
 
 impl Default for Policy {
@@ -31,6 +31,17 @@ pub struct InjectionWarning {
     pub description: String,
 }
 
+/// A regex that flags a potential injection attempt.
+///
+/// Matches are reported as [`InjectionWarning`]s carrying the pattern's name.
+#[derive(Debug, Clone)]
+pub struct InjectionPattern {
+    pub name: String,
+    pub regex: Regex,
+    pub severity: Severity,
+    pub description: String,
+}
+
 /// Sanitizer for external data.
 pub struct Sanitizer {
     /// Fast pattern matcher for known injection patterns.
@@ -38,7 +49,7 @@ pub struct Sanitizer {
     /// Patterns with their metadata.
     patterns: Vec<PatternInfo>,
     /// Regex patterns for more complex detection.
-    regex_patterns: Vec<RegexPattern>,
+    regex_patterns: Vec<InjectionPattern>,
 }
 
 struct PatternInfo {
@@ -47,16 +58,16 @@ struct PatternInfo {
     description: String,
 }
 
-struct RegexPattern {
-    regex: Regex,
-    name: String,
-    severity: Severity,
-    description: String,
-}
-
 impl Sanitizer {
     /// Create a new sanitizer with default patterns.
     pub fn new() -> Self {
+        Self::with_patterns(default_patterns())
+    }
+
+    /// Create a sanitizer that checks `regex_patterns` instead of the default
+    /// regex patterns. The built-in keyword list (role markers, special
+    /// tokens, "ignore previous", ...) is always checked.
+    pub fn with_patterns(regex_patterns: Vec<InjectionPattern>) -> Self {
         let patterns = vec![
             // Direct instruction injection
             PatternInfo {
@@ -162,34 +173,6 @@ impl Sanitizer {
             .build(&pattern_strings)
             .expect("Failed to build pattern matcher");
 
-        // Regex patterns for more complex detection
-        let regex_patterns = vec![
-            RegexPattern {
-                regex: Regex::new(r"(?i)base64[:\s]+[A-Za-z0-9+/=]{50,}").unwrap(),
-                name: "base64_payload".to_string(),
-                severity: Severity::Medium,
-                description: "Potential encoded payload".to_string(),
-            },
-            RegexPattern {
-                regex: Regex::new(r"(?i)eval\s*\(").unwrap(),
-                name: "eval_call".to_string(),
-                severity: Severity::High,
-                description: "Potential code evaluation attempt".to_string(),
-            },
-            RegexPattern {
-                regex: Regex::new(r"(?i)exec\s*\(").unwrap(),
-                name: "exec_call".to_string(),
-                severity: Severity::High,
-                description: "Potential code execution attempt".to_string(),
-            },
-            RegexPattern {
-                regex: Regex::new(r"\x00").unwrap(),
-                name: "null_byte".to_string(),
-                severity: Severity::Critical,
-                description: "Null byte injection attempt".to_string(),
-            },
-        ];
-
         Self {
             pattern_matcher,
             patterns,
@@ -197,6 +180,16 @@ impl Sanitizer {
         }
     }
 
+    /// Add a regex pattern on top of the current set.
+    pub fn add_pattern(&mut self, pattern: InjectionPattern) {
+        self.regex_patterns.push(pattern);
+    }
+
+    /// The regex patterns this sanitizer checks.
+    pub fn patterns(&self) -> &[InjectionPattern] {
+        &self.regex_patterns
+    }
+
     /// Sanitize content by detecting and escaping potential injection attempts.
     pub fn sanitize(&self, content: &str) -> SanitizedOutput {
         let mut warnings = Vec::new();
@@ -290,6 +283,36 @@ impl Default for Sanitizer {
     }
 }
 
+/// Default regex patterns for more complex detection.
+fn default_patterns() -> Vec<InjectionPattern> {
+    vec![
+        InjectionPattern {
+            regex: Regex::new(r"(?i)base64[:\s]+[A-Za-z0-9+/=]{50,}").unwrap(),
+            name: "base64_payload".to_string(),
+            severity: Severity::Medium,
+            description: "Potential encoded payload".to_string(),
+        },
+        InjectionPattern {
+            regex: Regex::new(r"(?i)eval\s*\(").unwrap(),
+            name: "eval_call".to_string(),
+            severity: Severity::High,
+            description: "Potential code evaluation attempt".to_string(),
+        },
+        InjectionPattern {
+            regex: Regex::new(r"(?i)exec\s*\(").unwrap(),
+            name: "exec_call".to_string(),
+            severity: Severity::High,
+            description: "Potential code execution attempt".to_string(),
+        },
+        InjectionPattern {
+            regex: Regex::new(r"\x00").unwrap(),
+            name: "null_byte".to_string(),
+            severity: Severity::Critical,
+            description: "Null byte injection attempt".to_string(),
+        },
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -331,6 +354,44 @@ mod tests {
         assert!(!result.was_modified);
     }
 
+    fn jailbreak_pattern() -> InjectionPattern {
+        InjectionPattern {
+            name: "dan_jailbreak".to_string(),
+            regex: Regex::new(r"(?i)\bDAN mode\b").unwrap(),
+            severity: Severity::High,
+            description: "Known jailbreak prompt".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_add_pattern_extends_defaults() {
+        let mut sanitizer = Sanitizer::new();
+        let defaults = sanitizer.patterns().len();
+        sanitizer.add_pattern(jailbreak_pattern());
+        assert_eq!(sanitizer.patterns().len(), defaults + 1);
+
+        let warnings = sanitizer.detect("Enable DAN mode and eval(payload)");
+        let dan = warnings
+            .iter()
+            .find(|w| w.pattern == "dan_jailbreak")
+            .unwrap();
+        assert_eq!(dan.severity, Severity::High);
+        assert_eq!(dan.description, "Known jailbreak prompt");
+        assert!(warnings.iter().any(|w| w.pattern == "eval_call"));
+    }
+
+    #[test]
+    fn test_with_patterns_replaces_regex_defaults() {
+        let sanitizer = Sanitizer::with_patterns(vec![jailbreak_pattern()]);
+        assert_eq!(sanitizer.patterns().len(), 1);
+
+        let warnings = sanitizer.detect("DAN mode: eval(payload), ignore previous");
+        assert!(warnings.iter().any(|w| w.pattern == "dan_jailbreak"));
+        assert!(!warnings.iter().any(|w| w.pattern == "eval_call"));
+        // Keyword detection is unaffected.
+        assert!(warnings.iter().any(|w| w.pattern == "ignore previous"));
+    }
+
     #[test]
     fn test_escape_null_bytes() {
         let sanitizer = Sanitizer::new();
@@ -27,6 +27,22 @@ pub struct Settings {
     #[serde(default)]
     pub database_pool_size: Option<usize>,
 
+    /// Seconds to wait for a free pooled connection (0 = no limit).
+    #[serde(default)]
+    pub database_pool_wait_timeout_secs: Option<u64>,
+
+    /// Seconds allowed to open a new connection (0 = no limit).
+    #[serde(default)]
+    pub database_pool_create_timeout_secs: Option<u64>,
+
+    /// Seconds allowed for the health check on a reused connection (0 = no limit).
+    #[serde(default)]
+    pub database_pool_recycle_timeout_secs: Option<u64>,
+
+    /// Verify each pooled connection with a test query before reuse.
+    #[serde(default)]
+    pub database_pool_health_check: Option<bool>,
+
     /// Path to local libSQL database file.
     #[serde(default)]
     pub libsql_path: Option<String>,
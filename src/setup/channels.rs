@@ -190,12 +190,27 @@ pub async fn setup_telegram(
         // Validate the token
         print_info("Validating bot token...");
 
-        match validate_telegram_token(&token).await {
-            Ok(username) => {
-                print_success(&format!(
-                    "Bot validated: @{}",
-                    username.as_deref().unwrap_or("unknown")
-                ));
+        match validate_telegram_token_live(&token).await {
+            Ok(check) => {
+                let username = match check {
+                    TelegramTokenCheck::Verified(username) => {
+                        print_success(&format!(
+                            "Bot validated: @{}",
+                            username.as_deref().unwrap_or("unknown")
+                        ));
+                        username
+                    }
+                    TelegramTokenCheck::FormatOnly { reason } => {
+                        print_error(&format!(
+                            "Could not reach Telegram ({}). The token looks well-formed but was not verified.",
+                            reason
+                        ));
+                        if !confirm("Save it anyway?", false)? {
+                            continue;
+                        }
+                        None
+                    }
+                };
 
                 // Save to database
                 secrets.save_secret("telegram_bot_token", &token).await?;
@@ -590,32 +605,108 @@ async fn setup_telegram_webhook_secret(
     Ok(Some(secret))
 }
 
+/// Outcome of a live Telegram token check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelegramTokenCheck {
+    /// Telegram accepted the token. Carries the bot's username.
+    Verified(Option<String>),
+    /// Telegram could not be reached, so only the token format was checked.
+    FormatOnly { reason: String },
+}
+
+/// Check that a Telegram bot token has the `<bot id>:<secret>` shape issued
+/// by @BotFather. Does not contact Telegram.
+pub fn validate_telegram_token_format(token: &SecretString) -> Result<(), ChannelSetupError> {
+    let invalid = || {
+        ChannelSetupError::Validation(
+            "Token should look like 123456789:ABC-DEF... (bot ID, colon, 35-character secret)"
+                .to_string(),
+        )
+    };
+
+    let (bot_id, secret) = token
+        .expose_secret()
+        .trim()
+        .split_once(':')
+        .ok_or_else(invalid)?;
+    let id_ok = !bot_id.is_empty() && bot_id.chars().all(|c| c.is_ascii_digit());
+    let secret_ok = secret.len() == 35
+        && secret
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if id_ok && secret_ok {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
 /// Validate a Telegram bot token by calling the getMe API.
 ///
 /// Returns the bot's username if valid.
 pub async fn validate_telegram_token(
     token: &SecretString,
+) -> Result<Option<String>, ChannelSetupError> {
+    telegram_get_me("https://api.telegram.org", token).await
+}
+
+/// Validate a Telegram bot token by calling the getMe API, tolerating an
+/// unreachable Telegram.
+///
+/// A token Telegram rejects is an error. If Telegram cannot be reached, the
+/// check falls back to [`validate_telegram_token_format`] and returns
+/// [`TelegramTokenCheck::FormatOnly`] so the caller can warn the user.
+pub async fn validate_telegram_token_live(
+    token: &SecretString,
+) -> Result<TelegramTokenCheck, ChannelSetupError> {
+    validate_telegram_token_at("https://api.telegram.org", token).await
+}
+
+async fn validate_telegram_token_at(
+    api_base: &str,
+    token: &SecretString,
+) -> Result<TelegramTokenCheck, ChannelSetupError> {
+    match telegram_get_me(api_base, token).await {
+        Ok(username) => Ok(TelegramTokenCheck::Verified(username)),
+        Err(ChannelSetupError::Network(reason)) => {
+            tracing::warn!("Telegram token check fell back to format only: {}", reason);
+            validate_telegram_token_format(token)?;
+            Ok(TelegramTokenCheck::FormatOnly { reason })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Call getMe and return the bot's username.
+///
+/// Rejected tokens are `Validation` errors; anything that prevents an answer
+/// (connection failures, timeouts, server errors) is a `Network` error.
+async fn telegram_get_me(
+    api_base: &str,
+    token: &SecretString,
 ) -> Result<Option<String>, ChannelSetupError> {
     let client = Client::builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| ChannelSetupError::Network(format!("Failed to create HTTP client: {}", e)))?;
 
-    let url = format!(
-        "https://api.telegram.org/bot{}/getMe",
-        token.expose_secret()
-    );
+    let url = format!("{}/bot{}/getMe", api_base, token.expose_secret());
 
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| ChannelSetupError::Network(format!("Request failed: {}", e)))?;
+    let response =
+        client.get(&url).send().await.map_err(|e| {
+            ChannelSetupError::Network(format!("Request failed: {}", e.without_url()))
+        })?;
 
-    if !response.status().is_success() {
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::NOT_FOUND {
+        return Err(ChannelSetupError::Validation(
+            "Telegram rejected the token (revoked or mistyped)".to_string(),
+        ));
+    }
+    if !status.is_success() {
         return Err(ChannelSetupError::Network(format!(
             "API returned status {}",
-            response.status()
+            status
         )));
     }
 
@@ -627,8 +718,8 @@ pub async fn validate_telegram_token(
     if body.ok {
         Ok(body.result.and_then(|u| u.username))
     } else {
-        Err(ChannelSetupError::Network(
-            "Telegram API returned error".to_string(),
+        Err(ChannelSetupError::Validation(
+            "Telegram rejected the token".to_string(),
         ))
     }
 }
@@ -996,7 +1087,100 @@ fn generate_secret_with_length(length: usize) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::setup::channels::generate_webhook_secret;
+    use secrecy::SecretString;
+
+    use crate::setup::channels::{
+        ChannelSetupError, TelegramTokenCheck, generate_webhook_secret, validate_telegram_token_at,
+        validate_telegram_token_format,
+    };
+
+    const TOKEN: &str = "123456789:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw_";
+
+    async fn mock_telegram(status: u16, body: serde_json::Value) -> String {
+        use axum::http::StatusCode;
+        use axum::routing::get;
+
+        let app = axum::Router::new().route(
+            "/{bot}/getMe",
+            get(move || async move {
+                (
+                    StatusCode::from_u16(status).unwrap(),
+                    axum::Json(body.clone()),
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_validate_telegram_token_format() {
+        assert!(validate_telegram_token_format(&SecretString::from(TOKEN)).is_ok());
+        for bad in [
+            "",
+            "AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw_",
+            "abc:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw_",
+            "123456789:too-short",
+            "123456789:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsa!",
+        ] {
+            assert!(
+                validate_telegram_token_format(&SecretString::from(bad)).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_check_returns_username() {
+        let base = mock_telegram(
+            200,
+            serde_json::json!({
+                "ok": true,
+                "result": {"id": 123456789, "first_name": "Claw", "username": "claw_bot"}
+            }),
+        )
+        .await;
+
+        let check = validate_telegram_token_at(&base, &SecretString::from(TOKEN))
+            .await
+            .unwrap();
+        assert_eq!(
+            check,
+            TelegramTokenCheck::Verified(Some("claw_bot".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_live_check_rejects_revoked_token() {
+        let base = mock_telegram(
+            401,
+            serde_json::json!({"ok": false, "error_code": 401, "description": "Unauthorized"}),
+        )
+        .await;
+
+        let result = validate_telegram_token_at(&base, &SecretString::from(TOKEN)).await;
+        assert!(matches!(result, Err(ChannelSetupError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_live_check_falls_back_to_format_when_unreachable() {
+        // Bind then drop a listener so the port is (almost certainly) closed.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let check = validate_telegram_token_at(&base, &SecretString::from(TOKEN))
+            .await
+            .unwrap();
+        assert!(matches!(check, TelegramTokenCheck::FormatOnly { .. }));
+
+        let malformed = validate_telegram_token_at(&base, &SecretString::from("nope")).await;
+        assert!(matches!(malformed, Err(ChannelSetupError::Validation(_))));
+    }
 
     #[test]
     fn test_generate_webhook_secret() {
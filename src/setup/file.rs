@@ -0,0 +1,346 @@
+//! Setup file for non-interactive onboarding.
+//!
+//! A TOML or JSON file (picked by extension) that answers the wizard's
+//! questions up front, so CI and reproducible deployments can run
+//! `ironclaw onboard --from-file ironclaw-setup.toml` and teams can keep
+//! their agent configuration in git:
+//!
+//! ```toml
+//! secrets = "env"
+//!
+//! [database]
+//! backend = "libsql"
+//! path = "/var/lib/ironclaw/ironclaw.db"
+//!
+//! [llm]
+//! backend = "anthropic"
+//! model = "claude-sonnet-4-5"
+//!
+//! [channels]
+//! http_enabled = true
+//! http_port = 8080
+//!
+//! [tunnel]
+//! provider = "cloudflare"
+//! ```
+//!
+//! `database` and `llm` are required. `embeddings`, `channels`, `tunnel`
+//! and `heartbeat` take the same fields as the matching [`Settings`]
+//! sections and fall back to their defaults.
+//!
+//! Every value in the file is applied as written, replacing what an earlier
+//! run saved, even when it equals the default. Sections the file leaves out
+//! keep their saved values.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::config::LlmBackend;
+use crate::settings::{
+    ChannelSettings, EmbeddingsSettings, HeartbeatSettings, KeySource, Settings, TunnelSettings,
+};
+use crate::setup::wizard::SetupError;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SetupFile {
+    #[serde(default)]
+    database: Option<DatabaseSection>,
+    #[serde(default)]
+    llm: Option<LlmSection>,
+    /// Where the secrets master key comes from. Keychain storage needs an
+    /// interactive session, so files usually say `"env"` or `"none"`.
+    #[serde(default)]
+    secrets: Option<KeySource>,
+    #[serde(default)]
+    embeddings: Option<EmbeddingsSettings>,
+    #[serde(default)]
+    channels: Option<ChannelSettings>,
+    #[serde(default)]
+    tunnel: Option<TunnelSettings>,
+    #[serde(default)]
+    heartbeat: Option<HeartbeatSettings>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DatabaseSection {
+    backend: Option<String>,
+    /// PostgreSQL connection URL.
+    url: Option<String>,
+    /// libSQL database file.
+    path: Option<String>,
+    /// Turso URL for libSQL remote replica sync.
+    turso_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LlmSection {
+    backend: Option<String>,
+    model: Option<String>,
+    /// Endpoint for `ollama` and `openai_compatible`.
+    base_url: Option<String>,
+}
+
+impl SetupFile {
+    /// Read and parse a setup file.
+    pub(crate) fn load(path: &Path) -> Result<Self, SetupError> {
+        let raw = std::fs::read_to_string(path).map_err(|e| {
+            SetupError::Config(format!("Cannot read setup file {}: {}", path.display(), e))
+        })?;
+        Self::parse(path, &raw)
+    }
+
+    fn parse(path: &Path, raw: &str) -> Result<Self, SetupError> {
+        let invalid =
+            |e: String| SetupError::Config(format!("Invalid setup file {}: {}", path.display(), e));
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(raw).map_err(|e| invalid(e.to_string())),
+            Some("json") => serde_json::from_str(raw).map_err(|e| invalid(e.to_string())),
+            _ => Err(SetupError::Config(format!(
+                "Setup file {} must have a .toml or .json extension",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Check required fields and turn the file into wizard settings.
+    pub(crate) fn into_settings(self) -> Result<Settings, SetupError> {
+        let mut settings = Settings::default();
+        self.apply(&mut settings)?;
+        Ok(settings)
+    }
+
+    /// Check required fields and write the file's values over `settings`.
+    pub(crate) fn apply(self, settings: &mut Settings) -> Result<(), SetupError> {
+        let database = self.database.ok_or_else(|| missing("database"))?;
+        let backend = database
+            .backend
+            .ok_or_else(|| missing("database.backend"))?;
+        match backend.as_str() {
+            "postgres" => {
+                let url = database.url.ok_or_else(|| {
+                    SetupError::Config(
+                        "Setup file is missing database.url (required for the postgres backend)"
+                            .to_string(),
+                    )
+                })?;
+                settings.database_url = Some(url);
+            }
+            "libsql" => {
+                let path = database.path.unwrap_or_else(|| {
+                    crate::config::default_libsql_path()
+                        .to_string_lossy()
+                        .to_string()
+                });
+                settings.libsql_path = Some(path);
+                settings.libsql_url = database.turso_url;
+            }
+            other => {
+                return Err(SetupError::Config(format!(
+                    "Unknown database.backend '{}' in setup file (expected postgres or libsql)",
+                    other
+                )));
+            }
+        }
+        settings.database_backend = Some(backend);
+
+        let llm = self.llm.ok_or_else(|| missing("llm"))?;
+        let backend = llm.backend.ok_or_else(|| missing("llm.backend"))?;
+        let backend = LlmBackend::from_str(&backend)
+            .map_err(|e| SetupError::Config(format!("Setup file has an {}", e)))?;
+        settings.selected_model = Some(llm.model.ok_or_else(|| missing("llm.model"))?);
+        match backend {
+            LlmBackend::Ollama => settings.ollama_base_url = llm.base_url,
+            LlmBackend::OpenAiCompatible => {
+                settings.openai_compatible_base_url = Some(llm.base_url.ok_or_else(|| {
+                    SetupError::Config(
+                        "Setup file is missing llm.base_url (required for openai_compatible)"
+                            .to_string(),
+                    )
+                })?);
+            }
+            _ => {}
+        }
+        settings.llm_backend = Some(backend.to_string());
+
+        settings.secrets_master_key_source = match self.secrets {
+            Some(source) => source,
+            None if std::env::var("SECRETS_MASTER_KEY").is_ok() => KeySource::Env,
+            None => KeySource::None,
+        };
+        if let Some(embeddings) = self.embeddings {
+            settings.embeddings = embeddings;
+        }
+        if let Some(channels) = self.channels {
+            settings.channels = channels;
+        }
+        if let Some(tunnel) = self.tunnel {
+            settings.tunnel = tunnel;
+        }
+        if let Some(heartbeat) = self.heartbeat {
+            settings.heartbeat = heartbeat;
+        }
+
+        Ok(())
+    }
+}
+
+fn missing(field: &str) -> SetupError {
+    SetupError::Config(format!("Setup file is missing required field '{}'", field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str, raw: &str) -> Result<Settings, SetupError> {
+        SetupFile::parse(Path::new(name), raw)?.into_settings()
+    }
+
+    #[test]
+    fn test_toml_setup_file() {
+        let settings = parse(
+            "setup.toml",
+            r#"
+secrets = "env"
+
+[database]
+backend = "postgres"
+url = "postgres://localhost/ironclaw"
+
+[llm]
+backend = "anthropic"
+model = "claude-sonnet-4-5"
+
+[channels]
+http_enabled = true
+http_port = 9000
+
+[tunnel]
+provider = "cloudflare"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.database_backend.as_deref(), Some("postgres"));
+        assert_eq!(
+            settings.database_url.as_deref(),
+            Some("postgres://localhost/ironclaw")
+        );
+        assert_eq!(settings.llm_backend.as_deref(), Some("anthropic"));
+        assert_eq!(
+            settings.selected_model.as_deref(),
+            Some("claude-sonnet-4-5")
+        );
+        assert_eq!(settings.secrets_master_key_source, KeySource::Env);
+        assert!(settings.channels.http_enabled);
+        assert_eq!(settings.channels.http_port, Some(9000));
+        assert!(settings.channels.wasm_channels_enabled);
+        assert_eq!(settings.tunnel.provider.as_deref(), Some("cloudflare"));
+    }
+
+    #[test]
+    fn test_json_setup_file() {
+        let settings = parse(
+            "setup.json",
+            r#"{
+                "database": {"backend": "libsql", "path": "/tmp/agent.db"},
+                "llm": {"backend": "ollama", "model": "llama3", "base_url": "http://gpu:11434"},
+                "secrets": "none"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.libsql_path.as_deref(), Some("/tmp/agent.db"));
+        assert_eq!(
+            settings.ollama_base_url.as_deref(),
+            Some("http://gpu:11434")
+        );
+        assert_eq!(settings.secrets_master_key_source, KeySource::None);
+    }
+
+    #[test]
+    fn test_missing_required_fields() {
+        let cases = [
+            (
+                "[llm]\nbackend = \"openai\"\nmodel = \"gpt-4o\"\n",
+                "database",
+            ),
+            (
+                "[database]\nbackend = \"libsql\"\n[llm]\nbackend = \"openai\"\n",
+                "llm.model",
+            ),
+            (
+                "[database]\nbackend = \"postgres\"\n[llm]\nbackend = \"openai\"\nmodel = \"gpt-4o\"\n",
+                "database.url",
+            ),
+            (
+                "[database]\nbackend = \"libsql\"\n[llm]\nbackend = \"openai_compatible\"\nmodel = \"m\"\n",
+                "llm.base_url",
+            ),
+        ];
+
+        for (raw, field) in cases {
+            match parse("setup.toml", raw) {
+                Err(SetupError::Config(msg)) => {
+                    assert!(msg.contains(field), "{field}: unexpected message {msg}")
+                }
+                other => panic!("{field}: expected config error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_fields_and_extensions() {
+        let typo = parse(
+            "setup.toml",
+            "[database]\nbackend = \"libsql\"\n[llm]\nbackend = \"openai\"\nmodle = \"gpt-4o\"\n",
+        );
+        assert!(matches!(typo, Err(SetupError::Config(msg)) if msg.contains("modle")));
+
+        let yaml = parse("setup.yaml", "database: {}");
+        assert!(matches!(yaml, Err(SetupError::Config(msg)) if msg.contains(".toml or .json")));
+
+        let backend = parse(
+            "setup.toml",
+            "[database]\nbackend = \"libsql\"\n[llm]\nbackend = \"bedrock\"\nmodel = \"m\"\n",
+        );
+        assert!(matches!(backend, Err(SetupError::Config(msg)) if msg.contains("bedrock")));
+    }
+
+    #[test]
+    fn test_accepts_every_llm_backend() {
+        for backend in ["tinfoil", "gemini", "claude"] {
+            let raw = format!(
+                "[database]\nbackend = \"libsql\"\n[llm]\nbackend = \"{backend}\"\nmodel = \"m\"\n"
+            );
+            let settings = parse("setup.toml", &raw).unwrap();
+            let expected = LlmBackend::from_str(backend).unwrap().to_string();
+            assert_eq!(settings.llm_backend, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_file_values_override_saved_settings_even_when_default() {
+        let mut saved = Settings::default();
+        saved.channels.http_enabled = true;
+        saved.channels.http_port = Some(9000);
+        saved.tunnel.provider = Some("ngrok".to_string());
+
+        let file = SetupFile::parse(
+            Path::new("setup.toml"),
+            "[database]\nbackend = \"libsql\"\n[llm]\nbackend = \"openai\"\nmodel = \"gpt-4o\"\n\n[channels]\nhttp_enabled = false\n",
+        )
+        .unwrap();
+        file.apply(&mut saved).unwrap();
+
+        assert!(!saved.channels.http_enabled);
+        assert_eq!(saved.channels.http_port, None);
+        // Sections the file leaves out keep their saved values.
+        assert_eq!(saved.tunnel.provider.as_deref(), Some("ngrok"));
+    }
+}
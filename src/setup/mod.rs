@@ -20,13 +20,16 @@
 //! ```
 
 mod channels;
+#[cfg(any(feature = "postgres", feature = "libsql"))]
+mod file;
 mod prompts;
 #[cfg(any(feature = "postgres", feature = "libsql"))]
 mod wizard;
 
 pub use channels::{
-    ChannelSetupError, SecretsContext, setup_http, setup_telegram, setup_tunnel,
-    validate_telegram_token,
+    ChannelSetupError, SecretsContext, TelegramTokenCheck, setup_http, setup_telegram,
+    setup_tunnel, validate_telegram_token, validate_telegram_token_format,
+    validate_telegram_token_live,
 };
 pub use prompts::{
     confirm, input, optional_input, print_error, print_header, print_info, print_step,
@@ -12,23 +12,25 @@
 //! 9. Heartbeat (background tasks)
 
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
 
-#[cfg(feature = "postgres")]
-use deadpool_postgres::{Config as PoolConfig, Runtime};
 use secrecy::{ExposeSecret, SecretString};
-#[cfg(feature = "postgres")]
-use tokio_postgres::NoTls;
 
 use crate::channels::wasm::{
     ChannelCapabilitiesFile, available_channel_names, install_bundled_channel,
 };
+#[cfg(feature = "postgres")]
+use crate::config::DatabasePoolConfig;
+#[cfg(feature = "postgres")]
+use crate::db::postgres::create_pool;
 use crate::llm::{SessionConfig, SessionManager};
 use crate::secrets::{SecretsCrypto, SecretsStore};
 use crate::settings::{KeySource, Settings};
 use crate::setup::channels::{
     SecretsContext, setup_http, setup_signal, setup_telegram, setup_tunnel, setup_wasm_channel,
 };
+use crate::setup::file::SetupFile;
 use crate::setup::prompts::{
     confirm, input, optional_input, print_error, print_header, print_info, print_step,
     print_success, secret_input, select_many, select_one,
@@ -74,6 +76,9 @@ pub struct SetupConfig {
     pub skip_auth: bool,
     /// Only reconfigure channels.
     pub channels_only: bool,
+    /// Walk through the prompts and print the resulting configuration
+    /// without connecting to the database or writing anything.
+    pub dry_run: bool,
 }
 
 /// Interactive setup wizard for IronClaw.
@@ -91,6 +96,8 @@ pub struct SetupWizard {
     secrets_crypto: Option<Arc<SecretsCrypto>>,
     /// Cached API key from provider setup (used by model fetcher without env mutation).
     llm_api_key: Option<SecretString>,
+    /// Setup file to apply instead of prompting.
+    setup_file: Option<SetupFile>,
 }
 
 impl SetupWizard {
@@ -106,6 +113,7 @@ impl SetupWizard {
             db_backend: None,
             secrets_crypto: None,
             llm_api_key: None,
+            setup_file: None,
         }
     }
 
@@ -121,9 +129,24 @@ impl SetupWizard {
             db_backend: None,
             secrets_crypto: None,
             llm_api_key: None,
+            setup_file: None,
         }
     }
 
+    /// Create a wizard that applies a setup file instead of prompting.
+    ///
+    /// The file is read and validated here, so a missing or malformed field
+    /// fails before anything is written. The format is described in
+    /// `src/setup/file.rs`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SetupError> {
+        let file = SetupFile::load(path.as_ref())?;
+        let settings = file.clone().into_settings()?;
+        let mut wizard = Self::new();
+        wizard.settings = settings;
+        wizard.setup_file = Some(file);
+        Ok(wizard)
+    }
+
     /// Set the session manager (for reusing existing auth).
     pub fn with_session(mut self, session: Arc<SessionManager>) -> Self {
         self.session_manager = Some(session);
@@ -139,10 +162,16 @@ impl SetupWizard {
     pub async fn run(&mut self) -> Result<(), SetupError> {
         print_header("IronClaw Setup Wizard");
 
+        if let Some(file) = self.setup_file.take() {
+            return self.run_from_file(file).await;
+        }
+
         if self.config.channels_only {
             // Channels-only mode: reconnect to existing DB and load settings
             // before running the channel step, so secrets and save work.
-            self.reconnect_existing_db().await?;
+            if !self.config.dry_run {
+                self.reconnect_existing_db().await?;
+            }
             print_step(1, 1, "Channel Configuration");
             self.step_channels().await?;
         } else {
@@ -214,6 +243,51 @@ impl SetupWizard {
         Ok(())
     }
 
+    /// Apply settings loaded by [`from_file`](Self::from_file): connect to
+    /// the configured database, run migrations, and save.
+    async fn run_from_file(&mut self, file: SetupFile) -> Result<(), SetupError> {
+        let file_settings = self.settings.clone();
+
+        match file_settings.database_backend.as_deref() {
+            #[cfg(feature = "postgres")]
+            Some("postgres") => {
+                let url = file_settings.database_url.as_deref().unwrap_or_default();
+                print_info(&format!("Connecting to {}", mask_password_in_url(url)));
+                self.test_database_connection_postgres(url).await?;
+                self.run_migrations_postgres().await?;
+            }
+            #[cfg(feature = "libsql")]
+            Some("libsql") => {
+                let path = file_settings.libsql_path.as_deref().unwrap_or_default();
+                let token = std::env::var("LIBSQL_AUTH_TOKEN").ok();
+                print_info(&format!("Opening {}", path));
+                self.test_database_connection_libsql(
+                    path,
+                    file_settings.libsql_url.as_deref(),
+                    token.as_deref(),
+                )
+                .await?;
+                self.run_migrations_libsql().await?;
+            }
+            other => {
+                return Err(SetupError::Config(format!(
+                    "Database backend '{}' is not available in this build",
+                    other.unwrap_or_default()
+                )));
+            }
+        }
+        print_success("Database connection successful");
+
+        // Keep settings saved by earlier runs (e.g. via `ironclaw config
+        // set`) unless the file sets them. File values are applied as
+        // written, so one equal to the default still replaces a saved value.
+        self.settings = Settings::default();
+        self.try_load_existing_settings().await;
+        file.apply(&mut self.settings)?;
+
+        self.save_and_summarize().await
+    }
+
     /// Reconnect to the existing database and load settings.
     ///
     /// Used by channels-only mode (and future single-step modes) so that
@@ -544,15 +618,14 @@ impl SetupWizard {
     /// Test PostgreSQL connection and store the pool.
     #[cfg(feature = "postgres")]
     async fn test_database_connection_postgres(&mut self, url: &str) -> Result<(), SetupError> {
-        let mut cfg = PoolConfig::new();
-        cfg.url = Some(url.to_string());
-        cfg.pool = Some(deadpool_postgres::PoolConfig {
-            max_size: 5,
-            ..Default::default()
-        });
+        if self.config.dry_run {
+            print_info("Dry run: skipping database connection");
+            return Ok(());
+        }
 
-        let pool = cfg
-            .create_pool(Some(Runtime::Tokio1), NoTls)
+        let pool_config = DatabasePoolConfig::resolve(&self.settings)
+            .map_err(|e| SetupError::Database(e.to_string()))?;
+        let pool = create_pool(url, &pool_config)
             .map_err(|e| SetupError::Database(format!("Failed to create pool: {}", e)))?;
 
         let _ = pool
@@ -573,7 +646,11 @@ impl SetupWizard {
         turso_token: Option<&str>,
     ) -> Result<(), SetupError> {
         use crate::db::libsql::LibSqlBackend;
-        use std::path::Path;
+
+        if self.config.dry_run {
+            print_info("Dry run: skipping database connection");
+            return Ok(());
+        }
 
         let db_path = Path::new(path);
 
@@ -688,6 +765,12 @@ impl SetupWizard {
         match choice {
             0 => {
                 // Generate and store in keychain
+                if self.config.dry_run {
+                    self.settings.secrets_master_key_source = KeySource::Keychain;
+                    print_info("Dry run: not generating or storing a master key");
+                    return Ok(());
+                }
+
                 print_info("Generating master key...");
                 let key = crate::secrets::keychain::generate_master_key();
 
@@ -821,6 +904,11 @@ impl SetupWizard {
             }
         }
 
+        if self.config.dry_run {
+            print_info("Dry run: skipping NEAR AI authentication");
+            return Ok(());
+        }
+
         // Create session manager if we don't have one
         let session = if let Some(ref s) = self.session_manager {
             Arc::clone(s)
@@ -1074,11 +1162,11 @@ impl SetupWizard {
                     .ollama_base_url
                     .as_deref()
                     .unwrap_or("http://localhost:11434");
-                let models = fetch_ollama_models(base_url).await;
-                if models.is_empty() {
+                let list = fetch_ollama_models(base_url).await;
+                if list.models.is_empty() {
                     print_info("No models found. Pull one first: ollama pull llama3");
                 }
-                self.select_from_model_list(&models)?;
+                self.select_from_model_list(&list)?;
             }
             "openai_compatible" => {
                 // No standard API for listing models on arbitrary endpoints
@@ -1111,9 +1199,9 @@ impl SetupWizard {
                 ];
 
                 let models = if fetched.is_empty() {
-                    default_models
+                    ModelList::defaults(default_models)
                 } else {
-                    fetched.iter().map(|m| (m.clone(), m.clone())).collect()
+                    ModelList::fetched(fetched.iter().map(|m| (m.clone(), m.clone())).collect())
                 };
                 self.select_from_model_list(&models)?;
             }
@@ -1124,8 +1212,10 @@ impl SetupWizard {
 
     /// Present a model list to the user, with a "Custom model ID" escape hatch.
     ///
-    /// Each entry is `(model_id, display_label)`.
-    fn select_from_model_list(&mut self, models: &[(String, String)]) -> Result<(), SetupError> {
+    /// A custom ID missing from a fetched list needs confirming, to catch
+    /// typos before the first LLM call does.
+    fn select_from_model_list(&mut self, list: &ModelList) -> Result<(), SetupError> {
+        let models = &list.models;
         println!("Available models:");
         println!();
 
@@ -1142,6 +1232,12 @@ impl SetupWizard {
                     println!("Model ID cannot be empty.");
                     continue;
                 }
+                if let Some(warning) = list.unknown_model_warning(&trimmed) {
+                    print_error(&warning);
+                    if !confirm("Use it anyway?", false).map_err(SetupError::Io)? {
+                        continue;
+                    }
+                }
                 break trimmed;
             }
         } else {
@@ -1176,14 +1272,18 @@ impl SetupWizard {
                 base_url,
                 auth_base_url,
                 session_path: crate::llm::session::default_session_path(),
+                session_store: crate::config::SessionStoreKind::default(),
                 api_key: None,
                 fallback_model: None,
                 max_retries: 3,
                 circuit_breaker_threshold: None,
                 circuit_breaker_recovery_secs: 30,
+                circuit_breaker_error_rate: None,
+                circuit_breaker_window: 20,
                 response_cache_enabled: false,
                 response_cache_ttl_secs: 3600,
                 response_cache_max_entries: 1000,
+                response_cache_semantic_threshold: None,
                 failover_cooldown_secs: 300,
                 failover_cooldown_threshold: 3,
                 smart_routing_cascade: true,
@@ -1193,6 +1293,9 @@ impl SetupWizard {
             ollama: None,
             openai_compatible: None,
             tinfoil: None,
+            gemini: None,
+            user_agent: crate::llm::default_user_agent(),
+            proxy_url: None,
         };
 
         match create_llm_provider(&config, session) {
@@ -1284,7 +1387,16 @@ impl SetupWizard {
     }
 
     /// Initialize secrets context for channel setup.
+    ///
+    /// Always fails in dry-run mode, so callers fall back to the same path
+    /// they take when secrets are not configured.
     async fn init_secrets_context(&mut self) -> Result<SecretsContext, SetupError> {
+        if self.config.dry_run {
+            return Err(SetupError::Config(
+                "dry run, secrets are not stored".to_string(),
+            ));
+        }
+
         // Get crypto (should be set from step 2, or load from keychain/env)
         let crypto = if let Some(ref c) = self.secrets_crypto {
             Arc::clone(c)
@@ -1485,35 +1597,39 @@ impl SetupWizard {
         // Install selected channels that aren't already on disk
         let mut any_installed = false;
 
-        // Try bundled channels first (pre-compiled artifacts from channels-src/)
-        if let Some(installed) = install_selected_bundled_channels(
-            &channels_dir,
-            &selected_wasm_channels,
-            &installed_names,
-        )
-        .await?
-            && !installed.is_empty()
-        {
-            print_success(&format!(
-                "Installed bundled channels: {}",
-                installed.join(", ")
-            ));
-            any_installed = true;
-        }
+        if self.config.dry_run {
+            print_info("Dry run: skipping channel installation");
+        } else {
+            // Try bundled channels first (pre-compiled artifacts from channels-src/)
+            if let Some(installed) = install_selected_bundled_channels(
+                &channels_dir,
+                &selected_wasm_channels,
+                &installed_names,
+            )
+            .await?
+                && !installed.is_empty()
+            {
+                print_success(&format!(
+                    "Installed bundled channels: {}",
+                    installed.join(", ")
+                ));
+                any_installed = true;
+            }
 
-        let installed_from_registry = install_selected_registry_channels(
-            &channels_dir,
-            &selected_wasm_channels,
-            &installed_names,
-        )
-        .await;
+            let installed_from_registry = install_selected_registry_channels(
+                &channels_dir,
+                &selected_wasm_channels,
+                &installed_names,
+            )
+            .await;
 
-        if !installed_from_registry.is_empty() {
-            print_success(&format!(
-                "Built from registry: {}",
-                installed_from_registry.join(", ")
-            ));
-            any_installed = true;
+            if !installed_from_registry.is_empty() {
+                print_success(&format!(
+                    "Built from registry: {}",
+                    installed_from_registry.join(", ")
+                ));
+                any_installed = true;
+            }
         }
 
         // Re-discover after installs
@@ -1697,6 +1813,11 @@ impl SetupWizard {
             return Ok(());
         }
 
+        if self.config.dry_run {
+            print_info("Dry run: skipping tool installation");
+            return Ok(());
+        }
+
         // Install selected tools that aren't already on disk
         let repo_root = catalog.root().parent().unwrap_or(catalog.root());
         let installer = crate::registry::installer::RegistryInstaller::new(
@@ -2064,6 +2185,10 @@ impl SetupWizard {
     /// Silently ignores errors (e.g., DB not connected yet before step 1
     /// completes). This is best-effort incremental persistence.
     async fn persist_after_step(&self) {
+        if self.config.dry_run {
+            return;
+        }
+
         // Write bootstrap .env (always possible)
         if let Err(e) = self.write_bootstrap_env() {
             tracing::debug!("Could not write bootstrap env after step: {}", e);
@@ -2147,22 +2272,28 @@ impl SetupWizard {
     async fn save_and_summarize(&mut self) -> Result<(), SetupError> {
         self.settings.onboard_completed = true;
 
-        // Final persist (idempotent — earlier incremental saves already wrote
-        // most settings, but this ensures onboard_completed is saved).
-        let saved = self.persist_settings().await?;
+        if self.config.dry_run {
+            println!();
+            print_info("Dry run: nothing was saved");
+            println!();
+        } else {
+            // Final persist (idempotent — earlier incremental saves already wrote
+            // most settings, but this ensures onboard_completed is saved).
+            let saved = self.persist_settings().await?;
 
-        if !saved {
-            return Err(SetupError::Database(
-                "No database connection, cannot save settings".to_string(),
-            ));
-        }
+            if !saved {
+                return Err(SetupError::Database(
+                    "No database connection, cannot save settings".to_string(),
+                ));
+            }
 
-        // Write bootstrap env (also idempotent)
-        self.write_bootstrap_env()?;
+            // Write bootstrap env (also idempotent)
+            self.write_bootstrap_env()?;
 
-        println!();
-        print_success("Configuration saved to database");
-        println!();
+            println!();
+            print_success("Configuration saved to database");
+            println!();
+        }
 
         // Print summary
         println!("Configuration Summary:");
@@ -2311,10 +2442,67 @@ fn mask_password_in_url(url: &str) -> String {
     format!("{}{}:****{}", scheme, username, after_at)
 }
 
+/// Models to offer in the model picker.
+struct ModelList {
+    /// `(model_id, display_label)` pairs.
+    models: Vec<(String, String)>,
+    /// Whether `models` came from the provider. When false it holds
+    /// built-in defaults, which say nothing about what the provider serves.
+    fetched: bool,
+}
+
+impl ModelList {
+    fn fetched(models: Vec<(String, String)>) -> Self {
+        Self {
+            models,
+            fetched: true,
+        }
+    }
+
+    fn defaults(models: Vec<(String, String)>) -> Self {
+        Self {
+            models,
+            fetched: false,
+        }
+    }
+
+    /// Warning for a custom model ID the provider didn't list, with a
+    /// suggestion when it differs from a listed ID only in case or
+    /// punctuation (`gpt4o` vs `gpt-4o`). `None` if the ID is listed or
+    /// there is no fetched list to check against.
+    fn unknown_model_warning(&self, model_id: &str) -> Option<String> {
+        if !self.fetched
+            || self.models.is_empty()
+            || self.models.iter().any(|(id, _)| id == model_id)
+        {
+            return None;
+        }
+
+        let normalize = |id: &str| {
+            id.chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .collect::<String>()
+        };
+        let wanted = normalize(model_id);
+        let suggestion = self
+            .models
+            .iter()
+            .find(|(id, _)| normalize(id) == wanted)
+            .map(|(id, _)| format!(" Did you mean '{}'?", id))
+            .unwrap_or_default();
+
+        Some(format!(
+            "'{}' is not in the provider's model list.{}",
+            model_id, suggestion
+        ))
+    }
+}
+
 /// Fetch models from the Anthropic API.
 ///
-/// Returns `(model_id, display_label)` pairs. Falls back to static defaults on error.
-async fn fetch_anthropic_models(cached_key: Option<&str>) -> Vec<(String, String)> {
+/// Falls back to static defaults on error.
+async fn fetch_anthropic_models(cached_key: Option<&str>) -> ModelList {
     let static_defaults = vec![
         (
             "claude-opus-4-6".into(),
@@ -2333,7 +2521,7 @@ async fn fetch_anthropic_models(cached_key: Option<&str>) -> Vec<(String, String
 
     let api_key = match api_key {
         Some(k) => k,
-        None => return static_defaults,
+        None => return ModelList::defaults(static_defaults),
     };
 
     let client = reqwest::Client::new();
@@ -2346,7 +2534,7 @@ async fn fetch_anthropic_models(cached_key: Option<&str>) -> Vec<(String, String
         .await
     {
         Ok(r) if r.status().is_success() => r,
-        _ => return static_defaults,
+        _ => return ModelList::defaults(static_defaults),
     };
 
     #[derive(serde::Deserialize)]
@@ -2370,19 +2558,19 @@ async fn fetch_anthropic_models(cached_key: Option<&str>) -> Vec<(String, String
                 })
                 .collect();
             if models.is_empty() {
-                return static_defaults;
+                return ModelList::defaults(static_defaults);
             }
             models.sort_by(|a, b| a.0.cmp(&b.0));
-            models
+            ModelList::fetched(models)
         }
-        Err(_) => static_defaults,
+        Err(_) => ModelList::defaults(static_defaults),
     }
 }
 
 /// Fetch models from the OpenAI API.
 ///
-/// Returns `(model_id, display_label)` pairs. Falls back to static defaults on error.
-async fn fetch_openai_models(cached_key: Option<&str>) -> Vec<(String, String)> {
+/// Falls back to static defaults on error.
+async fn fetch_openai_models(cached_key: Option<&str>) -> ModelList {
     let static_defaults = vec![
         (
             "gpt-5.3-codex".into(),
@@ -2409,7 +2597,7 @@ async fn fetch_openai_models(cached_key: Option<&str>) -> Vec<(String, String)>
 
     let api_key = match api_key {
         Some(k) => k,
-        None => return static_defaults,
+        None => return ModelList::defaults(static_defaults),
     };
 
     let client = reqwest::Client::new();
@@ -2421,7 +2609,7 @@ async fn fetch_openai_models(cached_key: Option<&str>) -> Vec<(String, String)>
         .await
     {
         Ok(r) if r.status().is_success() => r,
-        _ => return static_defaults,
+        _ => return ModelList::defaults(static_defaults),
     };
 
     #[derive(serde::Deserialize)]
@@ -2445,12 +2633,12 @@ async fn fetch_openai_models(cached_key: Option<&str>) -> Vec<(String, String)>
                 })
                 .collect();
             if models.is_empty() {
-                return static_defaults;
+                return ModelList::defaults(static_defaults);
             }
             sort_openai_models(&mut models);
-            models
+            ModelList::fetched(models)
         }
-        Err(_) => static_defaults,
+        Err(_) => ModelList::defaults(static_defaults),
     }
 }
 
@@ -2521,8 +2709,8 @@ fn sort_openai_models(models: &mut [(String, String)]) {
 
 /// Fetch installed models from a local Ollama instance.
 ///
-/// Returns `(model_name, display_label)` pairs. Falls back to static defaults on error.
-async fn fetch_ollama_models(base_url: &str) -> Vec<(String, String)> {
+/// Falls back to static defaults on error.
+async fn fetch_ollama_models(base_url: &str) -> ModelList {
     let static_defaults = vec![
         ("llama3".into(), "llama3".into()),
         ("mistral".into(), "mistral".into()),
@@ -2539,10 +2727,10 @@ async fn fetch_ollama_models(base_url: &str) -> Vec<(String, String)> {
         .await
     {
         Ok(r) if r.status().is_success() => r,
-        Ok(_) => return static_defaults,
+        Ok(_) => return ModelList::defaults(static_defaults),
         Err(_) => {
             print_info("Could not connect to Ollama. Is it running?");
-            return static_defaults;
+            return ModelList::defaults(static_defaults);
         }
     };
 
@@ -2566,11 +2754,11 @@ async fn fetch_ollama_models(base_url: &str) -> Vec<(String, String)> {
                 })
                 .collect();
             if models.is_empty() {
-                return static_defaults;
+                return ModelList::defaults(static_defaults);
             }
-            models
+            ModelList::fetched(models)
         }
-        Err(_) => static_defaults,
+        Err(_) => ModelList::defaults(static_defaults),
     }
 }
 
@@ -2864,11 +3052,53 @@ mod tests {
         let config = SetupConfig {
             skip_auth: true,
             channels_only: false,
+            dry_run: false,
         };
         let wizard = SetupWizard::with_config(config);
         assert!(wizard.config.skip_auth);
     }
 
+    #[test]
+    fn test_wizard_from_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("setup.toml");
+        std::fs::write(
+            &path,
+            "[database]\nbackend = \"libsql\"\npath = \"/tmp/agent.db\"\n\n[llm]\nbackend = \"openai\"\nmodel = \"gpt-4o\"\n",
+        )
+        .unwrap();
+
+        let wizard = SetupWizard::from_file(&path).unwrap();
+        assert!(wizard.setup_file.is_some());
+        assert_eq!(wizard.settings.llm_backend.as_deref(), Some("openai"));
+        assert_eq!(wizard.settings.selected_model.as_deref(), Some("gpt-4o"));
+
+        let missing = SetupWizard::from_file(dir.path().join("absent.toml"));
+        assert!(matches!(missing, Err(SetupError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_skips_secrets_and_database() {
+        let mut wizard = SetupWizard::with_config(SetupConfig {
+            dry_run: true,
+            ..SetupConfig::default()
+        });
+
+        assert!(wizard.init_secrets_context().await.is_err());
+
+        #[cfg(feature = "libsql")]
+        {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("dry-run.db");
+            wizard
+                .test_database_connection_libsql(path.to_str().unwrap(), None, None)
+                .await
+                .unwrap();
+            assert!(wizard.db_backend.is_none());
+            assert!(!path.exists());
+        }
+    }
+
     #[test]
     #[cfg(feature = "postgres")]
     fn test_mask_password_in_url() {
@@ -2956,7 +3186,9 @@ mod tests {
     async fn test_fetch_anthropic_models_static_fallback() {
         // With no API key, should return static defaults
         let _guard = EnvGuard::clear("ANTHROPIC_API_KEY");
-        let models = fetch_anthropic_models(None).await;
+        let list = fetch_anthropic_models(None).await;
+        assert!(!list.fetched);
+        let models = list.models;
         assert!(!models.is_empty());
         assert!(
             models.iter().any(|(id, _)| id.contains("claude")),
@@ -2967,7 +3199,9 @@ mod tests {
     #[tokio::test]
     async fn test_fetch_openai_models_static_fallback() {
         let _guard = EnvGuard::clear("OPENAI_API_KEY");
-        let models = fetch_openai_models(None).await;
+        let list = fetch_openai_models(None).await;
+        assert!(!list.fetched);
+        let models = list.models;
         assert!(!models.is_empty());
         assert_eq!(models[0].0, "gpt-5.3-codex");
         assert!(
@@ -3016,7 +3250,42 @@ mod tests {
     async fn test_fetch_ollama_models_unreachable_fallback() {
         // Point at a port nothing listens on
         let models = fetch_ollama_models("http://127.0.0.1:1").await;
-        assert!(!models.is_empty(), "should fall back to static defaults");
+        assert!(!models.fetched);
+        assert!(
+            !models.models.is_empty(),
+            "should fall back to static defaults"
+        );
+    }
+
+    #[test]
+    fn test_unknown_model_warning() {
+        let models = vec![
+            ("gpt-4o".to_string(), "GPT-4o".to_string()),
+            ("o3".to_string(), "o3".to_string()),
+        ];
+
+        let fetched = ModelList::fetched(models.clone());
+        assert!(fetched.unknown_model_warning("gpt-4o").is_none());
+        assert_eq!(
+            fetched.unknown_model_warning("gpt4o").as_deref(),
+            Some("'gpt4o' is not in the provider's model list. Did you mean 'gpt-4o'?")
+        );
+        assert_eq!(
+            fetched.unknown_model_warning("gpt-9").as_deref(),
+            Some("'gpt-9' is not in the provider's model list.")
+        );
+
+        // Built-in defaults and empty lists are not authoritative.
+        assert!(
+            ModelList::defaults(models)
+                .unknown_model_warning("gpt4o")
+                .is_none()
+        );
+        assert!(
+            ModelList::fetched(Vec::new())
+                .unknown_model_warning("gpt4o")
+                .is_none()
+        );
     }
 
     #[tokio::test]
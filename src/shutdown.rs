@@ -0,0 +1,374 @@
+//! Coordinated graceful shutdown.
+//!
+//! [`Shutdown`] tears the agent down in a fixed order, logging each step:
+//!
+//! 1. Stop accepting input: the token behind [`ShutdownHandle`] is
+//!    cancelled and the agent loop stops reading channel messages.
+//! 2. Drain: wait for in-flight turns to finish, up to a deadline.
+//! 3. Run the registered hooks stage by stage (see [`ShutdownStage`]):
+//!    channels, zkproxy worker, audit logs, database.
+//!
+//! A failing hook is logged and reported; later stages still run.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// How long in-flight turns get to finish once shutdown starts.
+pub const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Teardown stages, in the order their hooks run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownStage {
+    /// Close channel connections; no turn needs them any more.
+    Channels,
+    /// Stop the zkproxy worker process.
+    Worker,
+    /// Flush audit logs and transcripts.
+    Audit,
+    /// Close the database pool.
+    Database,
+}
+
+impl ShutdownStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Channels => "channels",
+            Self::Worker => "worker",
+            Self::Audit => "audit",
+            Self::Database => "database",
+        }
+    }
+}
+
+#[derive(Default)]
+struct TurnCount {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+/// Counts the turns currently being processed.
+#[derive(Clone, Default)]
+pub struct InFlightTurns {
+    inner: Arc<TurnCount>,
+}
+
+impl InFlightTurns {
+    /// Mark a turn as started; it ends when the guard is dropped.
+    pub fn begin(&self) -> TurnGuard {
+        self.inner.count.fetch_add(1, Ordering::SeqCst);
+        TurnGuard {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.inner.count.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no turn is in flight. Returns `false` if `deadline`
+    /// passed first.
+    pub async fn wait_idle(&self, deadline: Duration) -> bool {
+        let idle = async {
+            loop {
+                // Created before the check so a wakeup in between is not lost.
+                let notified = self.inner.idle.notified();
+                if self.count() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(deadline, idle).await.is_ok()
+    }
+}
+
+/// Keeps a turn counted in [`InFlightTurns`] while alive.
+pub struct TurnGuard {
+    inner: Arc<TurnCount>,
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        if self.inner.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// The parts of a [`Shutdown`] that running subsystems hold on to.
+#[derive(Clone, Default)]
+pub struct ShutdownHandle {
+    token: CancellationToken,
+    turns: InFlightTurns,
+}
+
+impl ShutdownHandle {
+    /// Cancelled as soon as shutdown starts.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    pub fn turns(&self) -> &InFlightTurns {
+        &self.turns
+    }
+
+    /// Start shutting down: input is no longer accepted.
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+type HookFn = Box<dyn FnOnce() -> BoxFuture<'static, Result<(), String>> + Send>;
+
+struct Hook {
+    stage: ShutdownStage,
+    name: String,
+    run: HookFn,
+}
+
+/// Outcome of [`Shutdown::run`].
+#[derive(Debug)]
+pub struct ShutdownReport {
+    /// Whether every in-flight turn finished before the drain deadline.
+    pub drained: bool,
+    /// Hooks that returned an error, by stage and name.
+    pub failures: Vec<(ShutdownStage, String, String)>,
+}
+
+/// Runs the shutdown sequence described in the module docs.
+pub struct Shutdown {
+    handle: ShutdownHandle,
+    drain_deadline: Duration,
+    hooks: Vec<Hook>,
+}
+
+impl Shutdown {
+    pub fn new(drain_deadline: Duration) -> Self {
+        Self {
+            handle: ShutdownHandle::default(),
+            drain_deadline,
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn handle(&self) -> ShutdownHandle {
+        self.handle.clone()
+    }
+
+    /// Register `hook` to run during `stage`. Hooks within a stage run in
+    /// registration order.
+    pub fn register<F, Fut>(&mut self, stage: ShutdownStage, name: impl Into<String>, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.hooks.push(Hook {
+            stage,
+            name: name.into(),
+            run: Box::new(move || Box::pin(hook())),
+        });
+    }
+
+    /// Resolves only if shutdown has started and turns are still in flight
+    /// after the drain deadline, i.e. when they should be abandoned.
+    pub async fn drain_expired(&self) {
+        self.handle.token.cancelled().await;
+        if self.handle.turns.wait_idle(self.drain_deadline).await {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Run the whole sequence. Safe to call whether or not shutdown was
+    /// already triggered.
+    pub async fn run(mut self) -> ShutdownReport {
+        tracing::info!("Shutdown: no longer accepting channel input");
+        self.handle.trigger();
+
+        let in_flight = self.handle.turns.count();
+        if in_flight > 0 {
+            tracing::info!(
+                in_flight,
+                "Shutdown: waiting up to {:?} for in-flight turns",
+                self.drain_deadline
+            );
+        }
+        let drained = self.handle.turns.wait_idle(self.drain_deadline).await;
+        if !drained {
+            tracing::warn!(
+                remaining = self.handle.turns.count(),
+                "Shutdown: drain deadline passed, abandoning in-flight turns"
+            );
+        }
+
+        // Stable sort: registration order is kept within a stage.
+        self.hooks.sort_by_key(|hook| hook.stage);
+        let mut failures = Vec::new();
+        for hook in self.hooks {
+            let stage = hook.stage.as_str();
+            tracing::info!(stage, hook = %hook.name, "Shutdown: stopping {}", hook.name);
+            if let Err(e) = (hook.run)().await {
+                tracing::warn!(stage, hook = %hook.name, "Shutdown: {} failed: {}", hook.name, e);
+                failures.push((hook.stage, hook.name, e));
+            }
+        }
+
+        tracing::info!("Shutdown: complete");
+        ShutdownReport { drained, failures }
+    }
+}
+
+/// Wait for Ctrl+C or, on Unix, SIGTERM.
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                return;
+            }
+            Err(e) => tracing::warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::time::Instant;
+
+    use super::*;
+
+    fn recording_hook(
+        log: &Arc<Mutex<Vec<String>>>,
+        name: &'static str,
+        result: Result<(), String>,
+    ) -> impl FnOnce() -> BoxFuture<'static, Result<(), String>> + Send + 'static {
+        let log = Arc::clone(log);
+        move || {
+            Box::pin(async move {
+                log.lock().unwrap().push(name.to_string());
+                result
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn hooks_run_in_stage_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut shutdown = Shutdown::new(Duration::from_secs(1));
+        let handle = shutdown.handle();
+
+        shutdown.register(
+            ShutdownStage::Database,
+            "pool",
+            recording_hook(&log, "pool", Ok(())),
+        );
+        shutdown.register(
+            ShutdownStage::Audit,
+            "audit",
+            recording_hook(&log, "audit", Err("disk full".into())),
+        );
+        shutdown.register(
+            ShutdownStage::Worker,
+            "worker",
+            recording_hook(&log, "worker", Ok(())),
+        );
+        let check = handle.clone();
+        let channel_log = Arc::clone(&log);
+        shutdown.register(ShutdownStage::Channels, "channels", move || async move {
+            assert!(
+                check.is_triggered(),
+                "input must stop before channels close"
+            );
+            channel_log.lock().unwrap().push("channels".to_string());
+            Ok(())
+        });
+
+        let report = shutdown.run().await;
+        assert!(report.drained);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["channels", "worker", "audit", "pool"]
+        );
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].0, ShutdownStage::Audit);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hooks_wait_for_in_flight_turns() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut shutdown = Shutdown::new(Duration::from_secs(5));
+        shutdown.register(
+            ShutdownStage::Channels,
+            "channels",
+            recording_hook(&log, "channels", Ok(())),
+        );
+
+        let turn = shutdown.handle().turns().begin();
+        let turn_log = Arc::clone(&log);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            turn_log.lock().unwrap().push("turn done".to_string());
+            drop(turn);
+        });
+
+        let started = Instant::now();
+        let report = shutdown.run().await;
+        assert!(report.drained);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(*log.lock().unwrap(), vec!["turn done", "channels"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_deadline_is_respected() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut shutdown = Shutdown::new(Duration::from_millis(100));
+        shutdown.register(
+            ShutdownStage::Database,
+            "pool",
+            recording_hook(&log, "pool", Ok(())),
+        );
+        let _stuck = shutdown.handle().turns().begin();
+
+        let started = Instant::now();
+        let report = shutdown.run().await;
+        assert!(!report.drained);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(started.elapsed() < Duration::from_millis(200));
+        assert_eq!(*log.lock().unwrap(), vec!["pool"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_expired_fires_only_for_stuck_turns() {
+        let shutdown = Shutdown::new(Duration::from_millis(50));
+        let handle = shutdown.handle();
+        handle.trigger();
+        let idle = tokio::time::timeout(Duration::from_millis(200), shutdown.drain_expired()).await;
+        assert!(idle.is_err(), "nothing in flight, so nothing to abandon");
+
+        let _stuck = handle.turns().begin();
+        let started = Instant::now();
+        tokio::time::timeout(Duration::from_secs(5), shutdown.drain_expired())
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+}
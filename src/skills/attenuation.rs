@@ -124,6 +124,7 @@ mod tests {
             name: name.to_string(),
             description: format!("{} tool", name),
             parameters: serde_json::json!({}),
+            result_schema: None,
         }
     }
 
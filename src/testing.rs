@@ -2,9 +2,14 @@
 //!
 //! Provides:
 //! - [`StubLlm`]: A configurable LLM provider that returns a fixed response
+//! - [`MockLlmProvider`]: An LLM provider that replays scripted responses
+//!   and records the requests it receives
 //! - [`TestHarnessBuilder`]: Builder for wiring `AgentDeps` with defaults
 //! - [`TestHarness`]: The assembled components ready for use in tests
 //!
+//! Compiled for this crate's own tests, and for downstream crates when the
+//! `testing` feature is enabled.
+//!
 //! # Usage
 //!
 //! ```rust,no_run
@@ -17,8 +22,9 @@
 //! }
 //! ```
 
-use std::sync::Arc;
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use async_trait::async_trait;
 use rust_decimal::Decimal;
@@ -27,8 +33,8 @@ use crate::agent::AgentDeps;
 use crate::db::Database;
 use crate::error::LlmError;
 use crate::llm::{
-    CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ToolCall,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
 use crate::tools::ToolRegistry;
 
@@ -59,6 +65,8 @@ pub enum StubErrorKind {
     Transient,
     /// Non-transient error (`LlmError::ContextLengthExceeded`).
     NonTransient,
+    /// Rate limit (`LlmError::RateLimited`) with an optional retry hint.
+    RateLimited(Option<std::time::Duration>),
 }
 
 /// A configurable LLM provider stub for tests.
@@ -112,6 +120,12 @@ impl StubLlm {
         }
     }
 
+    /// Set the error produced when failing.
+    pub fn with_error_kind(mut self, kind: StubErrorKind) -> Self {
+        self.error_kind = kind;
+        self
+    }
+
     /// Set the model name.
     pub fn with_model_name(mut self, name: impl Into<String>) -> Self {
         self.model_name = name.into();
@@ -138,6 +152,10 @@ impl StubLlm {
                 used: 100_000,
                 limit: 50_000,
             },
+            StubErrorKind::RateLimited(retry_after) => LlmError::RateLimited {
+                provider: self.model_name.clone(),
+                retry_after,
+            },
         }
     }
 }
@@ -189,6 +207,190 @@ impl LlmProvider for StubLlm {
     }
 }
 
+/// One scripted reply queued on a [`MockLlmProvider`].
+enum MockResponse {
+    Text(String),
+    ToolCalls(Vec<ToolCall>),
+    Error(LlmError),
+}
+
+/// An LLM provider that replays scripted responses in order.
+///
+/// Each call to `complete` or `complete_with_tools` pops the next queued
+/// response and records the request, so tests can drive a full agent turn
+/// and then assert on the messages and tools the agent sent. Running out of
+/// scripted responses is an error rather than a hang or a default reply.
+pub struct MockLlmProvider {
+    model_name: String,
+    responses: Mutex<VecDeque<MockResponse>>,
+    requests: Mutex<Vec<CompletionRequest>>,
+    tool_requests: Mutex<Vec<ToolCompletionRequest>>,
+    next_call_id: AtomicU32,
+    fixed_model: bool,
+}
+
+impl MockLlmProvider {
+    /// Create a provider with an empty script.
+    pub fn new() -> Self {
+        Self {
+            model_name: "mock-model".to_string(),
+            responses: Mutex::new(VecDeque::new()),
+            requests: Mutex::new(Vec::new()),
+            tool_requests: Mutex::new(Vec::new()),
+            next_call_id: AtomicU32::new(0),
+            fixed_model: false,
+        }
+    }
+
+    /// Set the model name reported by the provider.
+    pub fn with_model_name(mut self, name: impl Into<String>) -> Self {
+        self.model_name = name.into();
+        self
+    }
+
+    /// Ignore per-request model overrides, like providers whose model is
+    /// fixed when they are built.
+    pub fn with_fixed_model(mut self) -> Self {
+        self.fixed_model = true;
+        self
+    }
+
+    /// Queue a plain text response.
+    pub fn with_text(self, text: impl Into<String>) -> Self {
+        self.push(MockResponse::Text(text.into()));
+        self
+    }
+
+    /// Queue a response that calls a single tool.
+    pub fn with_tool_call(self, name: impl Into<String>, arguments: serde_json::Value) -> Self {
+        self.with_tool_calls(vec![(name.into(), arguments)])
+    }
+
+    /// Queue a response that calls several tools at once.
+    pub fn with_tool_calls(self, calls: Vec<(String, serde_json::Value)>) -> Self {
+        let calls = calls
+            .into_iter()
+            .map(|(name, arguments)| ToolCall {
+                id: format!("call_{}", self.next_call_id.fetch_add(1, Ordering::Relaxed)),
+                name,
+                arguments,
+                call_id: None,
+            })
+            .collect();
+        self.push(MockResponse::ToolCalls(calls));
+        self
+    }
+
+    /// Queue an error.
+    pub fn with_error(self, error: LlmError) -> Self {
+        self.push(MockResponse::Error(error));
+        self
+    }
+
+    /// Requests received through `complete`, oldest first.
+    pub fn requests(&self) -> Vec<CompletionRequest> {
+        lock(&self.requests).clone()
+    }
+
+    /// Requests received through `complete_with_tools`, oldest first.
+    pub fn tool_requests(&self) -> Vec<ToolCompletionRequest> {
+        lock(&self.tool_requests).clone()
+    }
+
+    /// Total number of calls made to the provider.
+    pub fn calls(&self) -> usize {
+        lock(&self.requests).len() + lock(&self.tool_requests).len()
+    }
+
+    /// Number of scripted responses not yet consumed.
+    pub fn remaining(&self) -> usize {
+        lock(&self.responses).len()
+    }
+
+    fn push(&self, response: MockResponse) {
+        lock(&self.responses).push_back(response);
+    }
+
+    fn next_response(&self) -> Result<MockResponse, LlmError> {
+        lock(&self.responses)
+            .pop_front()
+            .ok_or_else(|| LlmError::RequestFailed {
+                provider: self.model_name.clone(),
+                reason: "no scripted responses left".to_string(),
+            })
+    }
+}
+
+impl Default for MockLlmProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lock a mutex, ignoring poisoning from a panicked test thread.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[async_trait]
+impl LlmProvider for MockLlmProvider {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        (Decimal::ZERO, Decimal::ZERO)
+    }
+
+    fn effective_model_name(&self, requested_model: Option<&str>) -> String {
+        match requested_model {
+            Some(model) if !self.fixed_model => model.to_string(),
+            _ => self.active_model_name(),
+        }
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        lock(&self.requests).push(request);
+        match self.next_response()? {
+            MockResponse::Text(content) => Ok(CompletionResponse {
+                content,
+                input_tokens: 10,
+                output_tokens: 5,
+                finish_reason: FinishReason::Stop,
+            }),
+            MockResponse::ToolCalls(_) => Err(LlmError::InvalidResponse {
+                provider: self.model_name.clone(),
+                reason: "scripted tool call, but complete() was called without tools".to_string(),
+            }),
+            MockResponse::Error(e) => Err(e),
+        }
+    }
+
+    async fn complete_with_tools(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        lock(&self.tool_requests).push(request);
+        match self.next_response()? {
+            MockResponse::Text(content) => Ok(ToolCompletionResponse {
+                content: Some(content),
+                tool_calls: Vec::new(),
+                input_tokens: 10,
+                output_tokens: 5,
+                finish_reason: FinishReason::Stop,
+            }),
+            MockResponse::ToolCalls(tool_calls) => Ok(ToolCompletionResponse {
+                content: None,
+                tool_calls,
+                input_tokens: 10,
+                output_tokens: 5,
+                finish_reason: FinishReason::ToolUse,
+            }),
+            MockResponse::Error(e) => Err(e),
+        }
+    }
+}
+
 /// Assembled test components.
 pub struct TestHarness {
     /// The agent dependencies, ready for use.
@@ -269,10 +471,8 @@ impl TestHarnessBuilder {
         });
 
         let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
-            max_output_length: 100_000,
             injection_check_enabled: false,
-            #[cfg(feature = "zkproxy")]
-            zkproxy: crate::zkproxy::ZkProxyConfig::default(),
+            ..Default::default()
         }));
 
         let hooks = Arc::new(HookRegistry::new());
@@ -280,6 +480,7 @@ impl TestHarnessBuilder {
         let cost_guard = Arc::new(CostGuard::new(CostGuardConfig {
             max_cost_per_day_cents: None,
             max_actions_per_hour: None,
+            auto_downgrade_at_budget_fraction: None,
         }));
 
         let deps = AgentDeps {
@@ -354,4 +555,76 @@ mod tests {
         assert_eq!(response.content, "hello world");
         assert_eq!(response.finish_reason, FinishReason::Stop);
     }
+
+    #[tokio::test]
+    async fn test_mock_llm_replays_script_in_order() {
+        use crate::llm::{ChatMessage, ToolDefinition};
+
+        let llm = MockLlmProvider::new()
+            .with_tool_call("echo", serde_json::json!({"message": "hi"}))
+            .with_text("all done");
+        let tools = vec![ToolDefinition {
+            name: "echo".to_string(),
+            description: "Echo a message".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+            result_schema: None,
+        }];
+
+        let first = llm
+            .complete_with_tools(ToolCompletionRequest::new(
+                vec![ChatMessage::user("say hi")],
+                tools.clone(),
+            ))
+            .await
+            .expect("first call");
+        assert_eq!(first.finish_reason, FinishReason::ToolUse);
+        assert_eq!(first.tool_calls.len(), 1);
+        assert_eq!(first.tool_calls[0].name, "echo");
+        assert_eq!(first.tool_calls[0].id, "call_0");
+        assert_eq!(first.tool_calls[0].arguments["message"], "hi");
+
+        let second = llm
+            .complete_with_tools(ToolCompletionRequest::new(
+                vec![ChatMessage::user("say hi")],
+                tools,
+            ))
+            .await
+            .expect("second call");
+        assert_eq!(second.content.as_deref(), Some("all done"));
+        assert!(second.tool_calls.is_empty());
+
+        let sent = llm.tool_requests();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].messages[0].content, "say hi");
+        assert_eq!(sent[0].tools[0].name, "echo");
+        assert_eq!(llm.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_errors_when_script_runs_out() {
+        let llm = MockLlmProvider::new().with_text("only one");
+
+        let response = llm
+            .complete(CompletionRequest::new(vec![]))
+            .await
+            .expect("scripted text");
+        assert_eq!(response.content, "only one");
+
+        let err = llm
+            .complete(CompletionRequest::new(vec![]))
+            .await
+            .expect_err("script exhausted");
+        assert!(matches!(err, LlmError::RequestFailed { .. }));
+        assert_eq!(llm.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_rejects_tool_call_without_tools() {
+        let llm = MockLlmProvider::new().with_tool_call("echo", serde_json::json!({}));
+        let err = llm
+            .complete(CompletionRequest::new(vec![]))
+            .await
+            .expect_err("tool call needs complete_with_tools");
+        assert!(matches!(err, LlmError::InvalidResponse { .. }));
+    }
 }
@@ -14,6 +14,10 @@ impl Tool for EchoTool {
         "echo"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Echoes back the input message. Useful for testing tool execution."
     }
@@ -1,14 +1,14 @@
 //! Agent-callable tools for managing extensions (MCP servers and WASM tools).
 //!
-//! These six tools let the LLM search, install, authenticate, activate, list,
-//! and remove extensions entirely through conversation.
+//! These tools let the LLM search, install, authenticate, activate, list,
+//! remove, and revoke extensions entirely through conversation.
 
 use std::sync::Arc;
 
 use async_trait::async_trait;
 
 use crate::context::JobContext;
-use crate::extensions::{ExtensionKind, ExtensionManager};
+use crate::extensions::{ArtifactIntegrity, ExtensionKind, ExtensionManager, InstallOptions};
 use crate::tools::tool::{ApprovalRequirement, Tool, ToolError, ToolOutput, require_str};
 
 // ── tool_search ──────────────────────────────────────────────────────────
@@ -29,6 +29,10 @@ impl Tool for ToolSearchTool {
         "tool_search"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Search for available extensions to add new capabilities. Extensions include \
          channels (Telegram, Slack, Discord — for messaging), tools, and MCP servers. \
@@ -121,6 +125,27 @@ impl Tool for ToolInstallTool {
                     "type": "string",
                     "enum": ["mcp_server", "wasm_tool", "wasm_channel"],
                     "description": "Extension type (auto-detected if omitted)"
+                },
+                "sha256": {
+                    "type": "string",
+                    "description": "Expected hex SHA-256 of the downloaded artifact; install fails on mismatch"
+                },
+                "signature": {
+                    "type": "string",
+                    "description": "Hex Ed25519 signature of the artifact, verified against the configured install key"
+                },
+                "capabilities_sha256": {
+                    "type": "string",
+                    "description": "Expected hex SHA-256 of the separately downloaded capabilities file; required when a bare module is pinned"
+                },
+                "capabilities_signature": {
+                    "type": "string",
+                    "description": "Hex Ed25519 signature of the separately downloaded capabilities file"
+                },
+                "approve_capability_changes": {
+                    "type": "boolean",
+                    "description": "Accept an update that grants the extension new secrets, hosts, tools, or workspace access. Only set after the user has reviewed the listed changes.",
+                    "default": false
                 }
             },
             "required": ["name"]
@@ -148,9 +173,33 @@ impl Tool for ToolInstallTool {
                 _ => None,
             });
 
+        let options = InstallOptions {
+            integrity: ArtifactIntegrity::new(
+                params
+                    .get("sha256")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                params
+                    .get("signature")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            ),
+            capabilities_integrity: ArtifactIntegrity::new(
+                params
+                    .get("capabilities_sha256")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                params
+                    .get("capabilities_signature")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            ),
+            approve_capability_changes: approves_capability_changes(&params),
+        };
+
         let result = self
             .manager
-            .install(name, url, kind_hint)
+            .install(name, url, kind_hint, &options)
             .await
             .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
 
@@ -160,11 +209,23 @@ impl Tool for ToolInstallTool {
         Ok(ToolOutput::success(output, start.elapsed()))
     }
 
-    fn requires_approval(&self, _params: &serde_json::Value) -> ApprovalRequirement {
-        ApprovalRequirement::UnlessAutoApproved
+    fn requires_approval(&self, params: &serde_json::Value) -> ApprovalRequirement {
+        // Widening an extension's grants is never auto-approved.
+        if approves_capability_changes(params) {
+            ApprovalRequirement::Always
+        } else {
+            ApprovalRequirement::UnlessAutoApproved
+        }
     }
 }
 
+fn approves_capability_changes(params: &serde_json::Value) -> bool {
+    params
+        .get("approve_capability_changes")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
 // ── tool_auth ────────────────────────────────────────────────────────────
 
 pub struct ToolAuthTool {
@@ -372,6 +433,10 @@ impl Tool for ToolListTool {
         "tool_list"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List extensions with their authentication and activation status. \
          Set include_available:true to also show registry entries not yet installed."
@@ -496,6 +561,71 @@ impl Tool for ToolRemoveTool {
     }
 }
 
+// ── tool_revoke ──────────────────────────────────────────────────────────
+
+pub struct ToolRevokeTool {
+    manager: Arc<ExtensionManager>,
+}
+
+impl ToolRevokeTool {
+    pub fn new(manager: Arc<ExtensionManager>) -> Self {
+        Self { manager }
+    }
+}
+
+#[async_trait]
+impl Tool for ToolRevokeTool {
+    fn name(&self) -> &str {
+        "tool_revoke"
+    }
+
+    fn description(&self) -> &str {
+        "Revoke an extension's stored credentials without uninstalling it. \
+         Use tool_list to see which extensions are authenticated; run tool_auth \
+         again to reconnect."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Extension name to revoke credentials for"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+
+        let name = require_str(&params, "name")?;
+
+        let message = self
+            .manager
+            .revoke(name)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+
+        let output = serde_json::json!({
+            "name": name,
+            "message": message,
+        });
+
+        Ok(ToolOutput::success(output, start.elapsed()))
+    }
+
+    fn requires_approval(&self, _params: &serde_json::Value) -> ApprovalRequirement {
+        ApprovalRequirement::UnlessAutoApproved
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -522,9 +652,20 @@ mod tests {
             tool.requires_approval(&serde_json::json!({})),
             ApprovalRequirement::UnlessAutoApproved
         );
+        assert_eq!(
+            tool.requires_approval(
+                &serde_json::json!({"name": "x", "approve_capability_changes": true})
+            ),
+            ApprovalRequirement::Always
+        );
         let schema = tool.parameters_schema();
         assert!(schema["properties"].get("name").is_some());
         assert!(schema["properties"].get("url").is_some());
+        assert!(
+            schema["properties"]
+                .get("approve_capability_changes")
+                .is_some()
+        );
     }
 
     #[test]
@@ -588,6 +729,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tool_revoke_schema() {
+        use crate::tools::tool::ApprovalRequirement;
+        let tool = ToolRevokeTool {
+            manager: test_manager_stub(),
+        };
+        assert_eq!(tool.name(), "tool_revoke");
+        assert!(tool.parameters_schema()["properties"].get("name").is_some());
+        assert_eq!(
+            tool.requires_approval(&serde_json::json!({})),
+            ApprovalRequirement::UnlessAutoApproved
+        );
+    }
+
     /// Create a stub manager for schema tests (these don't call execute).
     fn test_manager_stub() -> Arc<ExtensionManager> {
         use crate::secrets::{InMemorySecretsStore, SecretsCrypto};
@@ -12,7 +12,7 @@ use tokio::fs;
 
 use crate::context::JobContext;
 use crate::tools::tool::{
-    ApprovalRequirement, Tool, ToolDomain, ToolError, ToolOutput, require_str,
+    ApprovalRequirement, ResourceAccess, Tool, ToolDomain, ToolError, ToolOutput, require_str,
 };
 use crate::workspace::paths as ws_paths;
 
@@ -82,7 +82,7 @@ fn normalize_lexical(path: &Path) -> PathBuf {
 /// and then verify it lives under the canonical base. This prevents escapes through
 /// non-existent parent directories where `canonicalize()` would fall back to the
 /// raw (un-normalized) path.
-fn validate_path(path_str: &str, base_dir: Option<&Path>) -> Result<PathBuf, ToolError> {
+pub(crate) fn validate_path(path_str: &str, base_dir: Option<&Path>) -> Result<PathBuf, ToolError> {
     let path = PathBuf::from(path_str);
 
     // Resolve to absolute path
@@ -173,6 +173,10 @@ impl Tool for ReadFileTool {
         "read_file"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Read a file from the LOCAL FILESYSTEM. NOT for workspace memory paths \
          (use memory_read for those). Returns file content as text. \
@@ -274,6 +278,14 @@ impl Tool for ReadFileTool {
     fn domain(&self) -> ToolDomain {
         ToolDomain::Container
     }
+
+    fn resource_access(&self, params: &serde_json::Value) -> Vec<ResourceAccess> {
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        vec![ResourceAccess::read("file", path)]
+    }
 }
 
 /// Write file contents tool.
@@ -386,6 +398,14 @@ impl Tool for WriteFileTool {
         ToolDomain::Container
     }
 
+    fn resource_access(&self, params: &serde_json::Value) -> Vec<ResourceAccess> {
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        vec![ResourceAccess::write("file", path)]
+    }
+
     fn rate_limit_config(&self) -> Option<crate::tools::tool::ToolRateLimitConfig> {
         Some(crate::tools::tool::ToolRateLimitConfig::new(20, 200))
     }
@@ -414,6 +434,10 @@ impl Tool for ListDirTool {
         "list_dir"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List contents of a directory on the LOCAL FILESYSTEM. NOT for workspace memory \
          (use memory_tree for that). Shows files and subdirectories with their sizes."
@@ -487,7 +511,8 @@ impl Tool for ListDirTool {
             "truncated": truncated
         });
 
-        Ok(ToolOutput::success(result, start.elapsed()))
+        let output = ToolOutput::success(result, start.elapsed());
+        Ok(if truncated { output.partial() } else { output })
     }
 
     fn requires_sanitization(&self) -> bool {
@@ -501,6 +526,11 @@ impl Tool for ListDirTool {
     fn domain(&self) -> ToolDomain {
         ToolDomain::Container
     }
+
+    fn resource_access(&self, params: &serde_json::Value) -> Vec<ResourceAccess> {
+        let path = params.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        vec![ResourceAccess::read("file", path)]
+    }
 }
 
 /// Recursively list directory contents.
@@ -715,6 +745,14 @@ impl Tool for ApplyPatchTool {
         ToolDomain::Container
     }
 
+    fn resource_access(&self, params: &serde_json::Value) -> Vec<ResourceAccess> {
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        vec![ResourceAccess::write("file", path)]
+    }
+
     fn rate_limit_config(&self) -> Option<crate::tools::tool::ToolRateLimitConfig> {
         Some(crate::tools::tool::ToolRateLimitConfig::new(20, 200))
     }
@@ -775,6 +775,10 @@ impl Tool for ListJobsTool {
         "list_jobs"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List all jobs or filter by status. Shows job IDs, titles, and current status."
     }
@@ -868,6 +872,10 @@ impl Tool for JobStatusTool {
         "job_status"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Check the status and details of a specific job by its ID."
     }
@@ -1047,6 +1055,10 @@ impl Tool for JobEventsTool {
         "job_events"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Read the event log for a sandbox job. Shows messages, tool calls, results, \
          and status changes from the container. Use this to check what Claude Code \
@@ -14,6 +14,10 @@ impl Tool for JsonTool {
         "json"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Parse, query, and transform JSON data. Supports JSONPath-like queries."
     }
@@ -17,7 +17,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use crate::context::JobContext;
-use crate::tools::tool::{Tool, ToolError, ToolOutput, require_str};
+use crate::tools::tool::{ResourceAccess, Tool, ToolError, ToolOutput, require_str};
 use crate::workspace::{Workspace, paths};
 
 /// Identity files that the LLM must not overwrite via tool calls.
@@ -48,6 +48,10 @@ impl Tool for MemorySearchTool {
         "memory_search"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Search past memories, decisions, and context. MUST be called before answering \
          questions about prior work, decisions, dates, people, preferences, or todos. \
@@ -112,6 +116,10 @@ impl Tool for MemorySearchTool {
     fn requires_sanitization(&self) -> bool {
         false // Internal memory, trusted content
     }
+
+    fn resource_access(&self, _params: &serde_json::Value) -> Vec<ResourceAccess> {
+        vec![ResourceAccess::read("memory", "")]
+    }
 }
 
 /// Tool for writing to workspace memory.
@@ -281,6 +289,12 @@ impl Tool for MemoryWriteTool {
         false // Internal tool
     }
 
+    fn resource_access(&self, _params: &serde_json::Value) -> Vec<ResourceAccess> {
+        // Targets like "daily_log" resolve to dated paths, so treat any
+        // write as touching the whole workspace.
+        vec![ResourceAccess::write("memory", "")]
+    }
+
     fn rate_limit_config(&self) -> Option<crate::tools::tool::ToolRateLimitConfig> {
         Some(crate::tools::tool::ToolRateLimitConfig::new(20, 200))
     }
@@ -306,6 +320,10 @@ impl Tool for MemoryReadTool {
         "memory_read"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Read a file from the workspace memory (database-backed storage). \
          Use this to read files shown by memory_tree. NOT for local filesystem files \
@@ -354,6 +372,14 @@ impl Tool for MemoryReadTool {
     fn requires_sanitization(&self) -> bool {
         false // Internal memory
     }
+
+    fn resource_access(&self, params: &serde_json::Value) -> Vec<ResourceAccess> {
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        vec![ResourceAccess::read("memory", path)]
+    }
 }
 
 /// Tool for viewing workspace structure as a tree.
@@ -420,6 +446,10 @@ impl Tool for MemoryTreeTool {
         "memory_tree"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "View the workspace memory structure as a tree (database-backed storage). \
          Use memory_read to read files shown here, NOT read_file. \
@@ -473,6 +503,14 @@ impl Tool for MemoryTreeTool {
     fn requires_sanitization(&self) -> bool {
         false // Internal tool
     }
+
+    fn resource_access(&self, params: &serde_json::Value) -> Vec<ResourceAccess> {
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        vec![ResourceAccess::read("memory", path)]
+    }
 }
 
 #[cfg(all(test, feature = "postgres"))]
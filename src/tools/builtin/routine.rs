@@ -283,6 +283,10 @@ impl Tool for RoutineListTool {
         "routine_list"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List all routines with their status, trigger info, and next fire time."
     }
@@ -551,6 +555,10 @@ impl Tool for RoutineHistoryTool {
         "routine_history"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "View the execution history of a routine. Shows recent runs with status, duration, and results."
     }
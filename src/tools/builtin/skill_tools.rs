@@ -30,6 +30,10 @@ impl Tool for SkillListTool {
         "skill_list"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List all loaded skills with their trust level, source, and activation keywords."
     }
@@ -129,6 +133,10 @@ impl Tool for SkillSearchTool {
         "skill_search"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Search for skills in the ClawHub catalog and among locally loaded skills."
     }
@@ -15,6 +15,10 @@ impl Tool for TimeTool {
         "time"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Get current time, convert timezones, or calculate time differences."
     }
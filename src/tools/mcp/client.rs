@@ -546,6 +546,10 @@ impl Tool for McpToolWrapper {
             ApprovalRequirement::Never
         }
     }
+
+    fn is_read_only(&self) -> bool {
+        self.tool.is_read_only()
+    }
 }
 
 #[cfg(test)]
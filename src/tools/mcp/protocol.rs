@@ -73,6 +73,13 @@ impl McpTool {
             .map(|a| a.destructive_hint)
             .unwrap_or(false)
     }
+
+    /// Check if the server marks this tool read-only (and not destructive).
+    pub fn is_read_only(&self) -> bool {
+        self.annotations
+            .as_ref()
+            .is_some_and(|a| a.read_only_hint && !a.destructive_hint)
+    }
 }
 
 /// Request to an MCP server.
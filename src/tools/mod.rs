@@ -10,10 +10,12 @@
 pub mod builder;
 pub mod builtin;
 pub mod mcp;
+pub mod ordering;
 pub mod rate_limiter;
 pub mod wasm;
 
 mod registry;
+mod schema;
 mod tool;
 
 pub use builder::{
@@ -23,4 +25,8 @@ pub use builder::{
 };
 pub use rate_limiter::RateLimiter;
 pub use registry::ToolRegistry;
-pub use tool::{ApprovalRequirement, Tool, ToolDomain, ToolError, ToolOutput, ToolRateLimitConfig};
+pub use schema::validate_result;
+pub use tool::{
+    ApprovalRequirement, ResourceAccess, Tool, ToolDomain, ToolError, ToolOutput,
+    ToolRateLimitConfig, ToolResultStatus,
+};
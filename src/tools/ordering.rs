@@ -0,0 +1,221 @@
+//! Execution order for tool calls emitted in a single model response.
+//!
+//! Calls run in parallel unless one depends on an earlier one. A dependency
+//! exists when:
+//! - the model lists the earlier call in a `_depends_on` argument (by its
+//!   0-based position in the response; see [`resolve_depends_on`]), or
+//! - both calls touch an overlapping resource and at least one of them
+//!   writes it (see [`Tool::resource_access`](crate::tools::Tool::resource_access)).
+//!
+//! [`execution_waves`] groups the calls into waves: calls in the same wave
+//! run concurrently, and each wave starts after the previous one finishes.
+//! Dependencies only point backwards, so the plan is always acyclic and
+//! the same input always yields the same waves.
+
+use crate::tools::{ResourceAccess, ToolRegistry};
+
+/// Argument key the model can use to name earlier calls that must finish
+/// first. Stripped from the arguments before the tool runs.
+pub const DEPENDS_ON_KEY: &str = "_depends_on";
+
+/// What the planner needs to know about one tool call.
+#[derive(Debug, Clone, Default)]
+pub struct PlannedCall {
+    /// Tool call ID assigned by the provider.
+    pub id: String,
+    /// IDs of earlier calls named in the `_depends_on` hint.
+    pub depends_on: Vec<String>,
+    /// Resources the call reads or writes.
+    pub access: Vec<ResourceAccess>,
+}
+
+impl PlannedCall {
+    /// Describe a call whose `_depends_on` hint has already been taken out
+    /// of `arguments`, asking the tool what resources it touches.
+    pub async fn new(
+        registry: &ToolRegistry,
+        id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        depends_on: Vec<String>,
+    ) -> Self {
+        let access = match registry.get(tool_name).await {
+            Some(tool) => tool.resource_access(arguments),
+            None => Vec::new(),
+        };
+        Self {
+            id: id.to_string(),
+            depends_on,
+            access,
+        }
+    }
+
+    fn depends_on(&self, earlier: &PlannedCall) -> bool {
+        self.depends_on.contains(&earlier.id)
+            || self
+                .access
+                .iter()
+                .any(|a| earlier.access.iter().any(|b| a.conflicts_with(b)))
+    }
+}
+
+/// Rewrite each call's `_depends_on` hint as a list of earlier call IDs.
+///
+/// The model can only refer to calls by position (numbers, 0-based) since
+/// IDs are assigned by the provider; IDs are accepted too. Run this once
+/// over a full response so hints stay valid when some of its calls are
+/// executed later, e.g. after an approval. References to the call itself,
+/// later calls, or unknown IDs are dropped.
+pub fn resolve_depends_on<'a>(
+    calls: impl IntoIterator<Item = (&'a str, &'a mut serde_json::Value)>,
+) {
+    let mut earlier: Vec<&str> = Vec::new();
+    for (id, arguments) in calls {
+        if let Some(hint) = arguments
+            .as_object_mut()
+            .and_then(|obj| obj.get_mut(DEPENDS_ON_KEY))
+        {
+            let refs = match hint {
+                serde_json::Value::Array(items) => std::mem::take(items),
+                other => vec![other.take()],
+            };
+            let ids = refs
+                .into_iter()
+                .filter_map(|r| match r {
+                    serde_json::Value::Number(n) => n
+                        .as_u64()
+                        .and_then(|i| earlier.get(i as usize))
+                        .map(|id| id.to_string()),
+                    serde_json::Value::String(id) => earlier.contains(&id.as_str()).then_some(id),
+                    _ => None,
+                })
+                .map(serde_json::Value::String)
+                .collect();
+            *hint = serde_json::Value::Array(ids);
+        }
+        earlier.push(id);
+    }
+}
+
+/// Remove a resolved `_depends_on` hint from a call's arguments, returning
+/// the IDs it names.
+pub fn take_depends_on(arguments: &mut serde_json::Value) -> Vec<String> {
+    match arguments
+        .as_object_mut()
+        .and_then(|obj| obj.remove(DEPENDS_ON_KEY))
+    {
+        Some(serde_json::Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Group calls into waves that can each run in parallel.
+///
+/// Returns positions into `calls`. Every call lands in the wave after the
+/// latest wave holding one of its dependencies; within a wave, calls keep
+/// their original order.
+pub fn execution_waves(calls: &[PlannedCall]) -> Vec<Vec<usize>> {
+    let mut wave_of: Vec<usize> = Vec::with_capacity(calls.len());
+    for (pos, call) in calls.iter().enumerate() {
+        let wave = calls[..pos]
+            .iter()
+            .zip(&wave_of)
+            .filter(|(earlier, _)| call.depends_on(earlier))
+            .map(|(_, wave)| wave + 1)
+            .max()
+            .unwrap_or(0);
+        wave_of.push(wave);
+    }
+
+    let mut waves: Vec<Vec<usize>> = Vec::new();
+    for (pos, wave) in wave_of.into_iter().enumerate() {
+        if waves.len() <= wave {
+            waves.resize_with(wave + 1, Vec::new);
+        }
+        waves[wave].push(pos);
+    }
+    waves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(index: usize, access: Vec<ResourceAccess>) -> PlannedCall {
+        PlannedCall {
+            id: format!("call_{}", index),
+            depends_on: Vec::new(),
+            access,
+        }
+    }
+
+    #[test]
+    fn test_independent_calls_share_a_wave() {
+        let calls = vec![
+            call(0, vec![ResourceAccess::read("file", "a.txt")]),
+            call(1, vec![ResourceAccess::read("file", "a.txt")]),
+            call(2, vec![ResourceAccess::write("file", "b.txt")]),
+            call(3, Vec::new()),
+        ];
+        assert_eq!(execution_waves(&calls), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_read_before_write_serializes() {
+        let calls = vec![
+            call(0, vec![ResourceAccess::read("file", "./src/lib.rs")]),
+            call(1, vec![ResourceAccess::write("file", "src/lib.rs")]),
+            call(2, vec![ResourceAccess::read("file", "src/lib.rs")]),
+            call(3, vec![ResourceAccess::read("file", "README.md")]),
+        ];
+        assert_eq!(execution_waves(&calls), vec![vec![0, 3], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn test_directory_covers_its_contents() {
+        let calls = vec![
+            call(0, vec![ResourceAccess::write("file", "src/new.rs")]),
+            call(1, vec![ResourceAccess::read("file", "src/")]),
+            call(2, vec![ResourceAccess::read("file", "srcfoo")]),
+            call(3, vec![ResourceAccess::read("memory", "src")]),
+            call(4, vec![ResourceAccess::write("memory", "")]),
+        ];
+        assert_eq!(execution_waves(&calls), vec![vec![0, 2, 3], vec![1, 4]]);
+    }
+
+    #[test]
+    fn test_explicit_hints() {
+        let ids = ["call_a", "call_b", "call_c", "call_d"];
+        let mut args = vec![
+            serde_json::json!({"_depends_on": [1]}),
+            serde_json::json!({"path": "x"}),
+            serde_json::json!({"_depends_on": [1, "call_a", "call_z", true]}),
+            serde_json::json!({"_depends_on": 2}),
+        ];
+        resolve_depends_on(ids.iter().copied().zip(args.iter_mut()));
+        // Forward references are dropped, so the plan can't have cycles.
+        assert_eq!(args[0], serde_json::json!({"_depends_on": []}));
+        assert_eq!(
+            args[2],
+            serde_json::json!({"_depends_on": ["call_b", "call_a"]})
+        );
+
+        let calls: Vec<PlannedCall> = ids
+            .iter()
+            .zip(args.iter_mut())
+            .map(|(id, args)| PlannedCall {
+                id: id.to_string(),
+                depends_on: take_depends_on(args),
+                access: Vec::new(),
+            })
+            .collect();
+        assert_eq!(args[2], serde_json::json!({}));
+        assert_eq!(args[1], serde_json::json!({"path": "x"}));
+        assert_eq!(calls[3].depends_on, vec!["call_c"]);
+        assert_eq!(execution_waves(&calls), vec![vec![0, 1], vec![2], vec![3]]);
+        assert!(execution_waves(&[]).is_empty());
+    }
+}
@@ -18,10 +18,12 @@
 //! This is acceptable for v1; future versions may persist to the database.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use tokio::sync::RwLock;
 
+use crate::clock::{Clock, system_clock};
 use crate::tools::tool::ToolRateLimitConfig;
 
 const MINUTE_SECS: u64 = 60;
@@ -76,29 +78,24 @@ struct WindowState {
 }
 
 impl WindowState {
-    fn new() -> Self {
+    fn new(now: Instant) -> Self {
         Self {
-            window_start: Instant::now(),
+            window_start: now,
             count: 0,
         }
     }
 
     /// Check if the window has expired and reset if needed.
-    fn maybe_reset(&mut self, window_duration: Duration) {
-        if self.window_start.elapsed() >= window_duration {
-            self.window_start = Instant::now();
+    fn maybe_reset(&mut self, now: Instant, window_duration: Duration) {
+        if now.saturating_duration_since(self.window_start) >= window_duration {
+            self.window_start = now;
             self.count = 0;
         }
     }
 
     /// Time until window resets.
-    fn time_until_reset(&self, window_duration: Duration) -> Duration {
-        let elapsed = self.window_start.elapsed();
-        if elapsed >= window_duration {
-            Duration::ZERO
-        } else {
-            window_duration - elapsed
-        }
+    fn time_until_reset(&self, now: Instant, window_duration: Duration) -> Duration {
+        window_duration.saturating_sub(now.saturating_duration_since(self.window_start))
     }
 }
 
@@ -110,10 +107,10 @@ struct ToolRateLimitState {
 }
 
 impl ToolRateLimitState {
-    fn new() -> Self {
+    fn new(now: Instant) -> Self {
         Self {
-            minute_window: WindowState::new(),
-            hour_window: WindowState::new(),
+            minute_window: WindowState::new(now),
+            hour_window: WindowState::new(now),
         }
     }
 }
@@ -125,6 +122,7 @@ impl ToolRateLimitState {
 /// checked before every built-in tool execution.
 pub struct RateLimiter {
     state: RwLock<HashMap<(String, String), ToolRateLimitState>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl RateLimiter {
@@ -132,9 +130,16 @@ impl RateLimiter {
     pub fn new() -> Self {
         Self {
             state: RwLock::new(HashMap::new()),
+            clock: system_clock(),
         }
     }
 
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Shared logic: reset windows, check limits, and optionally record the request.
     async fn check_internal(
         &self,
@@ -144,24 +149,27 @@ impl RateLimiter {
         record: bool,
     ) -> RateLimitResult {
         let key = (user_id.to_string(), tool_name.to_string());
+        let now = self.clock.now();
 
         let mut state = self.state.write().await;
-        let tool_state = state.entry(key).or_insert_with(ToolRateLimitState::new);
+        let tool_state = state
+            .entry(key)
+            .or_insert_with(|| ToolRateLimitState::new(now));
 
         // Reset windows if expired.
         tool_state
             .minute_window
-            .maybe_reset(Duration::from_secs(MINUTE_SECS));
+            .maybe_reset(now, Duration::from_secs(MINUTE_SECS));
         tool_state
             .hour_window
-            .maybe_reset(Duration::from_secs(HOUR_SECS));
+            .maybe_reset(now, Duration::from_secs(HOUR_SECS));
 
         // Check minute limit.
         if tool_state.minute_window.count >= config.requests_per_minute {
             return RateLimitResult::Limited {
                 retry_after: tool_state
                     .minute_window
-                    .time_until_reset(Duration::from_secs(MINUTE_SECS)),
+                    .time_until_reset(now, Duration::from_secs(MINUTE_SECS)),
                 limit_type: LimitType::PerMinute,
             };
         }
@@ -171,7 +179,7 @@ impl RateLimiter {
             return RateLimitResult::Limited {
                 retry_after: tool_state
                     .hour_window
-                    .time_until_reset(Duration::from_secs(HOUR_SECS)),
+                    .time_until_reset(now, Duration::from_secs(HOUR_SECS)),
                 limit_type: LimitType::PerHour,
             };
         }
@@ -260,8 +268,55 @@ impl From<RateLimitResult> for Result<(), RateLimitError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
     use crate::tools::tool::ToolRateLimitConfig;
 
+    #[tokio::test]
+    async fn test_windows_reset_on_mock_clock() {
+        let clock = Arc::new(MockClock::new());
+        let limiter = RateLimiter::new().with_clock(clock.clone());
+        let config = ToolRateLimitConfig::new(2, 3);
+
+        limiter.check_and_record("user1", "shell", &config).await;
+        limiter.check_and_record("user1", "shell", &config).await;
+        match limiter.check_and_record("user1", "shell", &config).await {
+            RateLimitResult::Limited {
+                limit_type,
+                retry_after,
+            } => {
+                assert_eq!(limit_type, LimitType::PerMinute);
+                assert_eq!(retry_after, Duration::from_secs(60));
+            }
+            _ => panic!("Expected limited"),
+        }
+
+        clock.advance(Duration::from_secs(60));
+        assert!(
+            limiter
+                .check_and_record("user1", "shell", &config)
+                .await
+                .is_allowed()
+        );
+        match limiter.check_and_record("user1", "shell", &config).await {
+            RateLimitResult::Limited {
+                limit_type,
+                retry_after,
+            } => {
+                assert_eq!(limit_type, LimitType::PerHour);
+                assert_eq!(retry_after, Duration::from_secs(3540));
+            }
+            _ => panic!("Expected limited"),
+        }
+
+        clock.advance(Duration::from_secs(3540));
+        assert!(
+            limiter
+                .check_and_record("user1", "shell", &config)
+                .await
+                .is_allowed()
+        );
+    }
+
     #[tokio::test]
     async fn test_allowed_within_limits() {
         let limiter = RateLimiter::new();
@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use tokio::sync::RwLock;
 
+use crate::clock::Clock;
 use crate::context::ContextManager;
 use crate::db::Database;
 use crate::extensions::ExtensionManager;
@@ -20,10 +21,10 @@ use crate::tools::builtin::{
     JobStatusTool, JsonTool, ListDirTool, ListJobsTool, MemoryReadTool, MemorySearchTool,
     MemoryTreeTool, MemoryWriteTool, PromptQueue, ReadFileTool, ShellTool, SkillInstallTool,
     SkillListTool, SkillRemoveTool, SkillSearchTool, TimeTool, ToolActivateTool, ToolAuthTool,
-    ToolInstallTool, ToolListTool, ToolRemoveTool, ToolSearchTool, WriteFileTool,
+    ToolInstallTool, ToolListTool, ToolRemoveTool, ToolRevokeTool, ToolSearchTool, WriteFileTool,
 };
-use crate::tools::rate_limiter::RateLimiter;
-use crate::tools::tool::{Tool, ToolDomain};
+use crate::tools::rate_limiter::{RateLimitResult, RateLimiter};
+use crate::tools::tool::{Tool, ToolDomain, ToolRateLimitConfig};
 use crate::tools::wasm::{
     Capabilities, OAuthRefreshConfig, ResourceLimits, SharedCredentialRegistry, WasmError,
     WasmStorageError, WasmToolRuntime, WasmToolStore, WasmToolWrapper,
@@ -58,6 +59,7 @@ const PROTECTED_TOOL_NAMES: &[&str] = &[
     "tool_activate",
     "tool_list",
     "tool_remove",
+    "tool_revoke",
     "routine_create",
     "routine_list",
     "routine_update",
@@ -80,6 +82,9 @@ pub struct ToolRegistry {
     secrets_store: Option<Arc<dyn SecretsStore + Send + Sync>>,
     /// Shared rate limiter for built-in tool invocations.
     rate_limiter: RateLimiter,
+    /// Configured limits, keyed by tool name, that replace a tool's own
+    /// `rate_limit_config`.
+    rate_limits: HashMap<String, ToolRateLimitConfig>,
 }
 
 impl ToolRegistry {
@@ -91,9 +96,24 @@ impl ToolRegistry {
             credential_registry: None,
             secrets_store: None,
             rate_limiter: RateLimiter::new(),
+            rate_limits: HashMap::new(),
         }
     }
 
+    /// Set per-tool rate limits, e.g. to stay within an external API's quota.
+    /// They replace the limit a tool declares for itself and apply to tools
+    /// that declare none. Like a tool's own limit, each is counted per user.
+    pub fn with_rate_limits(mut self, limits: HashMap<String, ToolRateLimitConfig>) -> Self {
+        self.rate_limits = limits;
+        self
+    }
+
+    /// Read time for rate limiting from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.rate_limiter = RateLimiter::new().with_clock(clock);
+        self
+    }
+
     /// Create a registry with credential injection support.
     pub fn with_credentials(
         mut self,
@@ -115,6 +135,43 @@ impl ToolRegistry {
         &self.rate_limiter
     }
 
+    /// The rate limit for `tool`: the configured one if set, otherwise the
+    /// tool's own.
+    pub fn rate_limit_for(&self, tool: &dyn Tool) -> Option<ToolRateLimitConfig> {
+        self.rate_limits
+            .get(tool.name())
+            .cloned()
+            .or_else(|| tool.rate_limit_config())
+    }
+
+    /// Count a call to `tool` by `user_id` against its rate limit.
+    ///
+    /// Returns `RateLimited` without recording the call once the limit is
+    /// reached, so the caller can hand the model a throttled result instead
+    /// of running the tool.
+    pub async fn check_rate_limit(
+        &self,
+        user_id: &str,
+        tool: &dyn Tool,
+    ) -> Result<(), crate::error::ToolError> {
+        let Some(config) = self.rate_limit_for(tool) else {
+            return Ok(());
+        };
+        match self
+            .rate_limiter
+            .check_and_record(user_id, tool.name(), &config)
+            .await
+        {
+            RateLimitResult::Allowed { .. } => Ok(()),
+            RateLimitResult::Limited { retry_after, .. } => {
+                Err(crate::error::ToolError::RateLimited {
+                    name: tool.name().to_string(),
+                    retry_after: Some(retry_after),
+                })
+            }
+        }
+    }
+
     /// Register a tool. Rejects dynamic tools that try to shadow a built-in name.
     pub async fn register(&self, tool: Arc<dyn Tool>) {
         let name = tool.name().to_string();
@@ -184,6 +241,24 @@ impl ToolRegistry {
                 name: tool.name().to_string(),
                 description: tool.description().to_string(),
                 parameters: tool.parameters_schema(),
+                result_schema: tool.result_schema(),
+            })
+            .collect()
+    }
+
+    /// Get tool definitions for tools that only read (see
+    /// [`Tool::is_read_only`]).
+    pub async fn read_only_tool_definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .read()
+            .await
+            .values()
+            .filter(|tool| tool.is_read_only())
+            .map(|tool| ToolDefinition {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+                parameters: tool.parameters_schema(),
+                result_schema: tool.result_schema(),
             })
             .collect()
     }
@@ -198,6 +273,7 @@ impl ToolRegistry {
                 name: tool.name().to_string(),
                 description: tool.description().to_string(),
                 parameters: tool.parameters_schema(),
+                result_schema: tool.result_schema(),
             })
             .collect()
     }
@@ -246,6 +322,7 @@ impl ToolRegistry {
                 name: tool.name().to_string(),
                 description: tool.description().to_string(),
                 parameters: tool.parameters_schema(),
+                result_schema: tool.result_schema(),
             })
             .collect()
     }
@@ -335,7 +412,7 @@ impl ToolRegistry {
         tracing::info!("Registered {} job management tools", job_tool_count);
     }
 
-    /// Register extension management tools (search, install, auth, activate, list, remove).
+    /// Register extension management tools (search, install, auth, activate, list, remove, revoke).
     ///
     /// These allow the LLM to manage MCP servers and WASM tools through conversation.
     pub fn register_extension_tools(&self, manager: Arc<ExtensionManager>) {
@@ -344,8 +421,9 @@ impl ToolRegistry {
         self.register_sync(Arc::new(ToolAuthTool::new(Arc::clone(&manager))));
         self.register_sync(Arc::new(ToolActivateTool::new(Arc::clone(&manager))));
         self.register_sync(Arc::new(ToolListTool::new(Arc::clone(&manager))));
-        self.register_sync(Arc::new(ToolRemoveTool::new(manager)));
-        tracing::info!("Registered 6 extension management tools");
+        self.register_sync(Arc::new(ToolRemoveTool::new(Arc::clone(&manager))));
+        self.register_sync(Arc::new(ToolRevokeTool::new(manager)));
+        tracing::info!("Registered 7 extension management tools");
     }
 
     /// Register skill management tools (list, search, install, remove).
@@ -644,6 +722,60 @@ mod tests {
         assert_eq!(defs[0].name, "echo");
     }
 
+    #[tokio::test]
+    async fn test_read_only_tool_definitions_drop_mutating_tools() {
+        let registry = ToolRegistry::new();
+        registry.register_builtin_tools();
+
+        let names: Vec<String> = registry
+            .read_only_tool_definitions()
+            .await
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert!(names.contains(&"echo".to_string()));
+        assert!(names.contains(&"time".to_string()));
+        assert!(!names.contains(&"http".to_string()));
+        assert!(registry.tool_definitions().await.len() > names.len());
+    }
+
+    #[tokio::test]
+    async fn test_configured_rate_limit_applies_per_tool_and_user() {
+        use crate::clock::MockClock;
+
+        let clock = Arc::new(MockClock::new());
+        let registry = ToolRegistry::new()
+            .with_rate_limits(HashMap::from([(
+                "echo".to_string(),
+                ToolRateLimitConfig::new(2, 100),
+            )]))
+            .with_clock(clock.clone());
+        registry.register_builtin_tools();
+        let echo = registry.get("echo").await.unwrap();
+        let time = registry.get("time").await.unwrap();
+
+        assert!(registry.check_rate_limit("u1", echo.as_ref()).await.is_ok());
+        assert!(registry.check_rate_limit("u1", echo.as_ref()).await.is_ok());
+        let err = registry
+            .check_rate_limit("u1", echo.as_ref())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::ToolError::RateLimited { ref name, retry_after: Some(after) }
+                if name == "echo" && after == std::time::Duration::from_secs(60)
+        ));
+
+        // Other tools and other users keep their own budgets.
+        for _ in 0..5 {
+            assert!(registry.check_rate_limit("u1", time.as_ref()).await.is_ok());
+        }
+        assert!(registry.check_rate_limit("u2", echo.as_ref()).await.is_ok());
+
+        clock.advance(std::time::Duration::from_secs(60));
+        assert!(registry.check_rate_limit("u1", echo.as_ref()).await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_builtin_tool_cannot_be_shadowed() {
         let registry = ToolRegistry::new();
@@ -0,0 +1,164 @@
+//! Checking structured tool results against a tool's declared result schema.
+//!
+//! Covers the subset of JSON Schema that tool authors use to describe their
+//! output: `type` (single or list), `enum`, `properties`, `required`,
+//! `additionalProperties: false` and `items`. Other keywords are ignored, so
+//! a richer schema is accepted but only partially enforced.
+
+use serde_json::Value;
+
+/// Check `value` against `schema`.
+///
+/// Returns one message per violation, each prefixed with the JSON path of
+/// the offending value (`$` is the root). An empty list means the value
+/// conforms.
+pub fn validate_result(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "$", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| matches_type(t, value)) {
+            errors.push(format!(
+                "{path}: expected {}, got {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum")
+        && !options.contains(value)
+    {
+        errors.push(format!("{path}: value is not one of the allowed options"));
+    }
+
+    if let Value::Object(fields) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    errors.push(format!("{path}: missing required field '{name}'"));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+        for (name, field) in fields {
+            let field_path = format!("{path}.{name}");
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => check(field_schema, field, &field_path, errors),
+                None if closed => errors.push(format!("{field_path}: unexpected field")),
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check(item_schema, item, &format!("{path}[{i}]"), errors);
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn weather_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string" },
+                "temp_c": { "type": "number" },
+                "conditions": { "type": "string", "enum": ["sunny", "cloudy", "rain"] },
+                "hourly": { "type": "array", "items": { "type": "integer" } }
+            },
+            "required": ["city", "temp_c"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_conforming_result() {
+        let value = json!({
+            "city": "Lisbon",
+            "temp_c": 21.5,
+            "conditions": "sunny",
+            "hourly": [20, 21, 22]
+        });
+        assert!(validate_result(&weather_schema(), &value).is_empty());
+    }
+
+    #[test]
+    fn test_reports_each_violation_with_path() {
+        let value = json!({
+            "temp_c": "warm",
+            "conditions": "snow",
+            "hourly": [20, 21.5],
+            "humidity": 80
+        });
+        // Field order depends on serde_json's map implementation.
+        let mut errors = validate_result(&weather_schema(), &value);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$.conditions: value is not one of the allowed options",
+                "$.hourly[1]: expected integer, got number",
+                "$.humidity: unexpected field",
+                "$.temp_c: expected number, got string",
+                "$: missing required field 'city'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_list_and_unknown_keywords() {
+        let schema = json!({ "type": ["string", "null"], "format": "date" });
+        assert!(validate_result(&schema, &json!(null)).is_empty());
+        assert!(validate_result(&schema, &json!("2026-01-01")).is_empty());
+        assert_eq!(
+            validate_result(&schema, &json!(3)),
+            vec!["$: expected string or null, got number"]
+        );
+    }
+}
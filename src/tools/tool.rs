@@ -28,6 +28,60 @@ impl ApprovalRequirement {
     }
 }
 
+/// A resource a tool invocation reads or writes.
+///
+/// Resources are `/`-separated paths under a scheme, e.g. `file:src/main.rs`
+/// or `memory:daily`. A path covers everything beneath it, and an empty path
+/// covers the whole scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceAccess {
+    Read(String),
+    Write(String),
+}
+
+impl ResourceAccess {
+    /// Read access to `path` under `scheme`.
+    pub fn read(scheme: &str, path: &str) -> Self {
+        Self::Read(format!("{}:{}", scheme, normalize_resource_path(path)))
+    }
+
+    /// Write access to `path` under `scheme`.
+    pub fn write(scheme: &str, path: &str) -> Self {
+        Self::Write(format!("{}:{}", scheme, normalize_resource_path(path)))
+    }
+
+    fn resource(&self) -> &str {
+        match self {
+            Self::Read(r) | Self::Write(r) => r,
+        }
+    }
+
+    /// Whether two accesses must not run concurrently: they touch
+    /// overlapping resources and at least one of them writes.
+    pub fn conflicts_with(&self, other: &ResourceAccess) -> bool {
+        let writes = matches!(self, Self::Write(_)) || matches!(other, Self::Write(_));
+        writes && resources_overlap(self.resource(), other.resource())
+    }
+}
+
+fn normalize_resource_path(path: &str) -> &str {
+    let mut path = path.trim();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    let path = path.trim_end_matches('/');
+    if path == "." { "" } else { path }
+}
+
+fn resources_overlap(a: &str, b: &str) -> bool {
+    let covers = |outer: &str, inner: &str| {
+        inner.strip_prefix(outer).is_some_and(|rest| {
+            rest.is_empty() || outer.ends_with(['/', ':']) || rest.starts_with('/')
+        })
+    };
+    covers(a, b) || covers(b, a)
+}
+
 /// Per-tool rate limit configuration for built-in tool invocations.
 ///
 /// Controls how many times a tool can be invoked per user, per time window.
@@ -98,6 +152,38 @@ pub enum ToolError {
     Sandbox(String),
 }
 
+/// How a tool call ended, as recorded in the turn history.
+///
+/// Lets the agent retry retryable failures and the UI tell a partial
+/// result apart from a complete one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ToolResultStatus {
+    /// The tool completed and returned its full output.
+    #[default]
+    Success,
+    /// The tool failed. `retryable` is set when the same call may succeed
+    /// if attempted again (e.g. after a rate limit).
+    Error { retryable: bool },
+    /// The tool returned some output but could not finish.
+    Partial,
+    /// The tool did not finish within its execution timeout.
+    Timeout,
+}
+
+impl ToolResultStatus {
+    /// Classify a failed tool call.
+    pub fn from_error(err: &crate::error::Error) -> Self {
+        match err {
+            crate::error::Error::Tool(crate::error::ToolError::Timeout { .. }) => Self::Timeout,
+            crate::error::Error::Tool(crate::error::ToolError::RateLimited { .. }) => {
+                Self::Error { retryable: true }
+            }
+            _ => Self::Error { retryable: false },
+        }
+    }
+}
+
 /// Output from a tool execution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolOutput {
@@ -110,6 +196,9 @@ pub struct ToolOutput {
     /// Raw output before sanitization (for debugging).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw: Option<String>,
+    /// Whether the tool produced its full output.
+    #[serde(default)]
+    pub status: ToolResultStatus,
 }
 
 impl ToolOutput {
@@ -120,6 +209,7 @@ impl ToolOutput {
             cost: None,
             duration,
             raw: None,
+            status: ToolResultStatus::Success,
         }
     }
 
@@ -130,6 +220,7 @@ impl ToolOutput {
             cost: None,
             duration,
             raw: None,
+            status: ToolResultStatus::Success,
         }
     }
 
@@ -144,6 +235,18 @@ impl ToolOutput {
         self.raw = Some(raw.into());
         self
     }
+
+    /// Mark the output as partial: the tool returned what it had but could
+    /// not finish (e.g. a truncated listing).
+    pub fn partial(mut self) -> Self {
+        self.status = ToolResultStatus::Partial;
+        self
+    }
+
+    /// Whether the tool could only partially produce this output.
+    pub fn is_partial(&self) -> bool {
+        self.status == ToolResultStatus::Partial
+    }
 }
 
 /// Definition of a tool's parameters using JSON Schema.
@@ -222,6 +325,18 @@ pub trait Tool: Send + Sync {
         ApprovalRequirement::Never
     }
 
+    /// Whether this tool only reads: it changes no files, memory, jobs or
+    /// external state.
+    ///
+    /// In read-only mode (`AGENT_READ_ONLY` or a session override) only
+    /// these tools are offered to the model, and calls to any other tool
+    /// are rejected.
+    ///
+    /// Default: `false`, so tools that don't opt in are withheld.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
     /// Maximum time this tool is allowed to run before the caller kills it.
     /// Override for long-running tools like sandbox execution.
     /// Default: 60 seconds.
@@ -255,6 +370,28 @@ pub trait Tool: Send + Sync {
         None
     }
 
+    /// Resources this invocation reads or writes.
+    ///
+    /// When the model emits several tool calls at once, calls whose accesses
+    /// conflict run in the order they were emitted; the rest run in parallel.
+    ///
+    /// Default: empty (independent of every other call).
+    fn resource_access(&self, _params: &serde_json::Value) -> Vec<ResourceAccess> {
+        Vec::new()
+    }
+
+    /// JSON Schema describing the tool's result, for tools that return
+    /// structured JSON.
+    ///
+    /// When set, the schema is advertised to the model and each result is
+    /// checked against it before being passed back; results that don't
+    /// match are flagged in the tool-result message.
+    ///
+    /// Default: `None` (result is treated as free-form).
+    fn result_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+
     /// Get the tool schema for LLM function calling.
     fn schema(&self) -> ToolSchema {
         ToolSchema {
@@ -409,4 +546,87 @@ mod tests {
         assert!(ApprovalRequirement::UnlessAutoApproved.is_required());
         assert!(ApprovalRequirement::Always.is_required());
     }
+
+    #[test]
+    fn test_tool_result_status_serialization() {
+        let cases = [
+            (
+                ToolResultStatus::Success,
+                serde_json::json!({"kind": "success"}),
+            ),
+            (
+                ToolResultStatus::Error { retryable: true },
+                serde_json::json!({"kind": "error", "retryable": true}),
+            ),
+            (
+                ToolResultStatus::Error { retryable: false },
+                serde_json::json!({"kind": "error", "retryable": false}),
+            ),
+            (
+                ToolResultStatus::Partial,
+                serde_json::json!({"kind": "partial"}),
+            ),
+            (
+                ToolResultStatus::Timeout,
+                serde_json::json!({"kind": "timeout"}),
+            ),
+        ];
+
+        for (status, expected) in cases {
+            assert_eq!(serde_json::to_value(status).unwrap(), expected);
+            let back: ToolResultStatus = serde_json::from_value(expected).unwrap();
+            assert_eq!(back, status);
+        }
+    }
+
+    #[test]
+    fn test_tool_result_status_from_error() {
+        let timeout: crate::error::Error = crate::error::ToolError::Timeout {
+            name: "shell".to_string(),
+            timeout: Duration::from_secs(30),
+        }
+        .into();
+        assert_eq!(
+            ToolResultStatus::from_error(&timeout),
+            ToolResultStatus::Timeout
+        );
+
+        let limited: crate::error::Error = crate::error::ToolError::RateLimited {
+            name: "http".to_string(),
+            retry_after: None,
+        }
+        .into();
+        assert_eq!(
+            ToolResultStatus::from_error(&limited),
+            ToolResultStatus::Error { retryable: true }
+        );
+
+        let failed: crate::error::Error = crate::error::ToolError::ExecutionFailed {
+            name: "shell".to_string(),
+            reason: "exit 1".to_string(),
+        }
+        .into();
+        assert_eq!(
+            ToolResultStatus::from_error(&failed),
+            ToolResultStatus::Error { retryable: false }
+        );
+    }
+
+    #[test]
+    fn test_tool_output_status_defaults_to_success() {
+        let output = ToolOutput::text("done", Duration::from_millis(5));
+        assert!(!output.is_partial());
+
+        let partial = output.partial();
+        assert!(partial.is_partial());
+
+        // Outputs serialized before the status field existed still load.
+        let legacy: ToolOutput = serde_json::from_value(serde_json::json!({
+            "result": "done",
+            "cost": null,
+            "duration": {"secs": 0, "nanos": 5000000},
+        }))
+        .unwrap();
+        assert_eq!(legacy.status, ToolResultStatus::Success);
+    }
 }
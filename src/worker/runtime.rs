@@ -74,12 +74,7 @@ impl WorkerRuntime {
             "proxied".to_string(),
         ));
 
-        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
-            max_output_length: 100_000,
-            injection_check_enabled: true,
-            #[cfg(feature = "zkproxy")]
-            zkproxy: crate::zkproxy::ZkProxyConfig::default(),
-        }));
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig::default()));
 
         let tools = Arc::new(ToolRegistry::new());
         // Register only container-safe tools
@@ -411,13 +406,8 @@ Work independently to complete this job. Report when done."#,
 
         // Validate params
         let validation = self.safety.validator().validate_tool_params(params);
-        if !validation.is_valid {
-            let details = validation
-                .errors
-                .iter()
-                .map(|e| format!("{}: {}", e.field, e.message))
-                .collect::<Vec<_>>()
-                .join("; ");
+        if !validation.is_valid() {
+            let details = validation.describe_errors();
             return Err(format!("invalid parameters: {}", details));
         }
 
@@ -0,0 +1,144 @@
+//! Read-through cache for workspace documents.
+//!
+//! Holds recently read documents keyed by normalized path, up to a fixed
+//! number of entries. [`Workspace`](super::Workspace) invalidates a path
+//! after every write, append, or delete to it, so a read that follows a
+//! write always goes back to the database.
+//!
+//! The cache only sees writes made through the same `Workspace`. The agent
+//! shares one instance across its tools and the web gateway, so that holds
+//! in practice; other processes writing the same rows are not observed.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::workspace::document::MemoryDocument;
+
+pub(crate) struct DocumentCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// Monotonic counter for least-recently-used eviction.
+    tick: u64,
+    /// Bumped on every invalidation. A read that started before a write
+    /// must not repopulate the cache with what it fetched.
+    generation: u64,
+}
+
+struct CacheEntry {
+    doc: MemoryDocument,
+    last_used: u64,
+}
+
+impl DocumentCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<MemoryDocument> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(path)?;
+        entry.last_used = tick;
+        Some(entry.doc.clone())
+    }
+
+    /// Current generation; pass it back to [`insert`](Self::insert).
+    pub(crate) fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Cache `doc` if nothing was invalidated since `generation` was read.
+    pub(crate) fn insert(&self, path: &str, doc: MemoryDocument, generation: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        if state.generation != generation {
+            return;
+        }
+        if !state.entries.contains_key(path) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.tick += 1;
+        let last_used = state.tick;
+        state
+            .entries
+            .insert(path.to_string(), CacheEntry { doc, last_used });
+    }
+
+    pub(crate) fn invalidate(&self, path: &str) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.entries.remove(path);
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn doc(path: &str) -> MemoryDocument {
+        MemoryDocument {
+            id: Uuid::new_v4(),
+            user_id: "u".to_string(),
+            agent_id: None,
+            path: path.to_string(),
+            content: format!("content of {path}"),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = DocumentCache::new(2);
+        let g = cache.generation();
+        cache.insert("a.md", doc("a.md"), g);
+        cache.insert("b.md", doc("b.md"), g);
+        assert!(cache.get("a.md").is_some());
+
+        cache.insert("c.md", doc("c.md"), g);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("a.md").is_some());
+        assert!(cache.get("b.md").is_none());
+        assert!(cache.get("c.md").is_some());
+    }
+
+    #[test]
+    fn read_racing_a_write_is_not_cached() {
+        let cache = DocumentCache::new(4);
+        let g = cache.generation();
+        cache.invalidate("a.md");
+        cache.insert("a.md", doc("a.md"), g);
+        assert!(cache.get("a.md").is_none());
+    }
+}
@@ -4,8 +4,11 @@
 //! Similar concepts have similar vectors, enabling semantic search.
 
 use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 
+use crate::config::OpenAiCompatibleConfig;
+
 /// Error type for embedding operations.
 #[derive(Debug, thiserror::Error)]
 pub enum EmbeddingError {
@@ -295,8 +298,6 @@ impl EmbeddingProvider for NearAiEmbeddings {
     }
 
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
-        use secrecy::ExposeSecret;
-
         if texts.is_empty() {
             return Ok(Vec::new());
         }
@@ -471,6 +472,148 @@ impl EmbeddingProvider for OllamaEmbeddings {
     }
 }
 
+/// Embedding provider for any OpenAI-compatible `/embeddings` endpoint
+/// (vLLM, LiteLLM, LM Studio, OpenRouter, ...).
+///
+/// Usually built with [`OpenAiCompatibleEmbeddings::from_llm_config`] so
+/// embeddings go to the same server, with the same key and headers, as
+/// chat completions.
+pub struct OpenAiCompatibleEmbeddings {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<SecretString>,
+    extra_headers: Vec<(String, String)>,
+    model: String,
+    dimension: usize,
+}
+
+impl OpenAiCompatibleEmbeddings {
+    /// Create a provider for `base_url`, which includes the version
+    /// segment (e.g. `http://localhost:1234/v1`).
+    ///
+    /// Defaults to `text-embedding-3-small` (1536 dimensions).
+    pub fn new(base_url: impl Into<String>, api_key: Option<SecretString>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into(),
+            api_key,
+            extra_headers: Vec::new(),
+            model: "text-embedding-3-small".to_string(),
+            dimension: 1536,
+        }
+    }
+
+    /// Reuse the base URL, API key and extra headers of the
+    /// OpenAI-compatible LLM backend.
+    pub fn from_llm_config(config: &OpenAiCompatibleConfig) -> Self {
+        Self::new(&config.base_url, config.api_key.clone())
+            .with_extra_headers(config.extra_headers.clone())
+    }
+
+    /// Use a specific model with a given dimension.
+    pub fn with_model(mut self, model: impl Into<String>, dimension: usize) -> Self {
+        self.model = model.into();
+        self.dimension = dimension;
+        self
+    }
+
+    /// Send these headers with every request.
+    pub fn with_extra_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.extra_headers = headers;
+        self
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiCompatibleEmbeddings {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn max_input_length(&self) -> usize {
+        32_000
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        if text.len() > self.max_input_length() {
+            return Err(EmbeddingError::TextTooLong {
+                length: text.len(),
+                max: self.max_input_length(),
+            });
+        }
+
+        let embeddings = self.embed_batch(&[text.to_string()]).await?;
+        embeddings
+            .into_iter()
+            .next()
+            .ok_or_else(|| EmbeddingError::InvalidResponse("No embedding returned".to_string()))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let request = OpenAiEmbeddingRequest {
+            model: &self.model,
+            input: texts,
+        };
+
+        let url = format!("{}/embeddings", self.base_url.trim_end_matches('/'));
+
+        let mut builder = self.client.post(&url).json(&request);
+        if let Some(ref api_key) = self.api_key {
+            builder = builder.bearer_auth(api_key.expose_secret());
+        }
+        for (name, value) in &self.extra_headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let response = builder.send().await?;
+
+        let status = response.status();
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(EmbeddingError::AuthFailed);
+        }
+
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(std::time::Duration::from_secs);
+            return Err(EmbeddingError::RateLimited { retry_after });
+        }
+
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(EmbeddingError::HttpError(format!(
+                "Status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let result: OpenAiEmbeddingResponse = response.json().await.map_err(|e| {
+            EmbeddingError::InvalidResponse(format!("Failed to parse response: {}", e))
+        })?;
+
+        if result.data.len() != texts.len() {
+            return Err(EmbeddingError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                result.data.len()
+            )));
+        }
+
+        Ok(result.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
 /// A mock embedding provider for testing.
 ///
 /// Generates deterministic embeddings based on text hash.
@@ -530,6 +673,8 @@ impl EmbeddingProvider for MockEmbeddings {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
 
     #[tokio::test]
@@ -580,4 +725,62 @@ mod tests {
         assert_eq!(provider.dimension(), 3072);
         assert_eq!(provider.model_name(), "text-embedding-3-large");
     }
+
+    #[tokio::test]
+    async fn test_openai_compatible_embeddings_against_mock_server() {
+        use axum::http::HeaderMap;
+        use axum::routing::post;
+
+        let seen: Arc<std::sync::Mutex<Vec<String>>> = Arc::default();
+        let seen_handler = seen.clone();
+        let app = axum::Router::new().route(
+            "/v1/embeddings",
+            post(
+                move |headers: HeaderMap, axum::Json(body): axum::Json<serde_json::Value>| {
+                    let seen = seen_handler.clone();
+                    async move {
+                        for name in ["authorization", "x-team"] {
+                            let value = headers
+                                .get(name)
+                                .and_then(|v| v.to_str().ok())
+                                .unwrap_or_default();
+                            seen.lock().unwrap().push(value.to_string());
+                        }
+                        assert_eq!(body["model"], "embed-small");
+                        let data: Vec<_> = body["input"]
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .enumerate()
+                            .map(|(i, _)| serde_json::json!({"embedding": [i as f32, 1.0, 0.5]}))
+                            .collect();
+                        axum::Json(serde_json::json!({ "data": data }))
+                    }
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let config = OpenAiCompatibleConfig {
+            base_url: format!("http://{}/v1/", addr),
+            api_key: Some(SecretString::from("sk-local".to_string())),
+            model: "chat-model".to_string(),
+            extra_headers: vec![("X-Team".to_string(), "search".to_string())],
+        };
+        let provider =
+            OpenAiCompatibleEmbeddings::from_llm_config(&config).with_model("embed-small", 3);
+
+        let texts = vec!["hello".to_string(), "world".to_string()];
+        let embeddings = provider.embed_batch(&texts).await.unwrap();
+        assert_eq!(embeddings, vec![vec![0.0, 1.0, 0.5], vec![1.0, 1.0, 0.5]]);
+        assert_eq!(provider.embed("hi").await.unwrap(), vec![0.0, 1.0, 0.5]);
+        assert_eq!(
+            seen.lock().unwrap()[..2],
+            ["Bearer sk-local".to_string(), "search".to_string()]
+        );
+    }
 }
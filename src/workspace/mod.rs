@@ -40,6 +40,7 @@
 //! 3. **Self-documenting**: Use README.md files to describe directory structure
 //! 4. **Hybrid search**: Vector similarity + BM25 full-text via RRF
 
+mod cache;
 mod chunker;
 mod document;
 mod embeddings;
@@ -51,13 +52,15 @@ mod search;
 pub use chunker::{ChunkConfig, chunk_document};
 pub use document::{MemoryChunk, MemoryDocument, WorkspaceEntry, paths};
 pub use embeddings::{
-    EmbeddingProvider, MockEmbeddings, NearAiEmbeddings, OllamaEmbeddings, OpenAiEmbeddings,
+    EmbeddingError, EmbeddingProvider, MockEmbeddings, NearAiEmbeddings, OllamaEmbeddings,
+    OpenAiCompatibleEmbeddings, OpenAiEmbeddings,
 };
 #[cfg(feature = "postgres")]
 pub use repository::Repository;
 pub use search::{RankedResult, SearchConfig, SearchResult, reciprocal_rank_fusion};
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use chrono::{NaiveDate, Utc};
 #[cfg(feature = "postgres")]
@@ -65,11 +68,13 @@ use deadpool_postgres::Pool;
 use uuid::Uuid;
 
 use crate::error::WorkspaceError;
+use crate::workspace::cache::DocumentCache;
 
 /// Internal storage abstraction for Workspace.
 ///
 /// Allows Workspace to work with either a PostgreSQL `Repository` (the original
 /// path) or any `Database` trait implementation (e.g. libSQL backend).
+#[derive(Clone)]
 enum WorkspaceStorage {
     /// PostgreSQL-backed repository (uses connection pool directly).
     #[cfg(feature = "postgres")]
@@ -244,6 +249,14 @@ impl WorkspaceStorage {
             }
         }
     }
+
+    async fn rebuild_search_index(&self) -> Result<(), WorkspaceError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Repo(repo) => repo.rebuild_search_index().await,
+            Self::Db(db) => db.rebuild_search_index().await,
+        }
+    }
 }
 
 /// Default template seeded into HEARTBEAT.md on first access.
@@ -285,6 +298,11 @@ pub struct Workspace {
     storage: WorkspaceStorage,
     /// Embedding provider for semantic search.
     embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    /// Set once a failed search has triggered a full index rebuild, so a
+    /// persistently failing query can't rebuild on every call.
+    search_index_rebuilt: AtomicBool,
+    /// Recently read documents, when enabled via `with_read_cache`.
+    read_cache: Option<DocumentCache>,
 }
 
 impl Workspace {
@@ -296,6 +314,8 @@ impl Workspace {
             agent_id: None,
             storage: WorkspaceStorage::Repo(Repository::new(pool)),
             embeddings: None,
+            search_index_rebuilt: AtomicBool::new(false),
+            read_cache: None,
         }
     }
 
@@ -308,6 +328,22 @@ impl Workspace {
             agent_id: None,
             storage: WorkspaceStorage::Db(db),
             embeddings: None,
+            search_index_rebuilt: AtomicBool::new(false),
+            read_cache: None,
+        }
+    }
+
+    /// A workspace over the same storage and embeddings, scoped to `user_id`.
+    ///
+    /// The read cache is not shared: it is keyed by path, not by user.
+    pub fn for_user(&self, user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            agent_id: self.agent_id,
+            storage: self.storage.clone(),
+            embeddings: self.embeddings.clone(),
+            search_index_rebuilt: AtomicBool::new(false),
+            read_cache: None,
         }
     }
 
@@ -323,6 +359,15 @@ impl Workspace {
         self
     }
 
+    /// Cache up to `capacity` documents read by path.
+    ///
+    /// Writes, appends, and deletes through this workspace invalidate the
+    /// affected path, so reads never see stale content from this instance.
+    pub fn with_read_cache(mut self, capacity: usize) -> Self {
+        self.read_cache = Some(DocumentCache::new(capacity));
+        self
+    }
+
     /// Get the user ID.
     pub fn user_id(&self) -> &str {
         &self.user_id
@@ -346,9 +391,23 @@ impl Workspace {
     /// ```
     pub async fn read(&self, path: &str) -> Result<MemoryDocument, WorkspaceError> {
         let path = normalize_path(path);
-        self.storage
+        let Some(cache) = &self.read_cache else {
+            return self
+                .storage
+                .get_document_by_path(&self.user_id, self.agent_id, &path)
+                .await;
+        };
+
+        if let Some(doc) = cache.get(&path) {
+            return Ok(doc);
+        }
+        let generation = cache.generation();
+        let doc = self
+            .storage
             .get_document_by_path(&self.user_id, self.agent_id, &path)
-            .await
+            .await?;
+        cache.insert(&path, doc.clone(), generation);
+        Ok(doc)
     }
 
     /// Write (create or update) a file.
@@ -362,9 +421,19 @@ impl Workspace {
     /// ```
     pub async fn write(&self, path: &str, content: &str) -> Result<MemoryDocument, WorkspaceError> {
         let path = normalize_path(path);
+        let result = self.write_document(&path, content).await;
+        self.invalidate_cached(&path);
+        result
+    }
+
+    async fn write_document(
+        &self,
+        path: &str,
+        content: &str,
+    ) -> Result<MemoryDocument, WorkspaceError> {
         let doc = self
             .storage
-            .get_or_create_document_by_path(&self.user_id, self.agent_id, &path)
+            .get_or_create_document_by_path(&self.user_id, self.agent_id, path)
             .await?;
         self.storage.update_document(doc.id, content).await?;
         self.reindex_document(doc.id).await?;
@@ -379,9 +448,15 @@ impl Workspace {
     /// Adds a newline separator between existing and new content.
     pub async fn append(&self, path: &str, content: &str) -> Result<(), WorkspaceError> {
         let path = normalize_path(path);
+        let result = self.append_document(&path, content).await;
+        self.invalidate_cached(&path);
+        result
+    }
+
+    async fn append_document(&self, path: &str, content: &str) -> Result<(), WorkspaceError> {
         let doc = self
             .storage
-            .get_or_create_document_by_path(&self.user_id, self.agent_id, &path)
+            .get_or_create_document_by_path(&self.user_id, self.agent_id, path)
             .await?;
 
         let new_content = if doc.content.is_empty() {
@@ -414,9 +489,19 @@ impl Workspace {
     /// Also deletes associated chunks.
     pub async fn delete(&self, path: &str) -> Result<(), WorkspaceError> {
         let path = normalize_path(path);
-        self.storage
+        let result = self
+            .storage
             .delete_document_by_path(&self.user_id, self.agent_id, &path)
-            .await
+            .await;
+        self.invalidate_cached(&path);
+        result
+    }
+
+    /// Drop `path` from the read cache after a mutation, successful or not.
+    fn invalidate_cached(&self, path: &str) {
+        if let Some(cache) = &self.read_cache {
+            cache.invalidate(path);
+        }
     }
 
     /// List files and directories in a path.
@@ -502,6 +587,12 @@ impl Workspace {
     /// This is for important facts, decisions, and preferences worth
     /// remembering long-term.
     pub async fn append_memory(&self, entry: &str) -> Result<(), WorkspaceError> {
+        let result = self.append_memory_entry(entry).await;
+        self.invalidate_cached(paths::MEMORY);
+        result
+    }
+
+    async fn append_memory_entry(&self, entry: &str) -> Result<(), WorkspaceError> {
         // Use double newline for memory entries (semantic separation)
         let doc = self.memory().await?;
         let new_content = if doc.content.is_empty() {
@@ -626,7 +717,8 @@ impl Workspace {
             None
         };
 
-        self.storage
+        let result = self
+            .storage
             .hybrid_search(
                 &self.user_id,
                 self.agent_id,
@@ -634,11 +726,41 @@ impl Workspace {
                 embedding.as_deref(),
                 &config,
             )
-            .await
+            .await;
+
+        // Fall back to a from-scratch rebuild once per workspace if the
+        // incrementally-maintained index is unusable, then retry.
+        match result {
+            Err(WorkspaceError::SearchFailed { reason })
+                if config.use_fts && !self.search_index_rebuilt.swap(true, Ordering::SeqCst) =>
+            {
+                tracing::warn!("Memory search failed ({}), rebuilding search index", reason);
+                self.storage.rebuild_search_index().await?;
+                self.storage
+                    .hybrid_search(
+                        &self.user_id,
+                        self.agent_id,
+                        query,
+                        embedding.as_deref(),
+                        &config,
+                    )
+                    .await
+            }
+            other => other,
+        }
     }
 
     // ==================== Indexing ====================
 
+    /// Rebuild the full-text search index from scratch.
+    ///
+    /// Writes and deletes keep the index current incrementally, so this is
+    /// only needed to recover from an index that has drifted or been
+    /// corrupted.
+    pub async fn rebuild_search_index(&self) -> Result<(), WorkspaceError> {
+        self.storage.rebuild_search_index().await
+    }
+
     /// Re-index a document (chunk and generate embeddings).
     async fn reindex_document(&self, document_id: Uuid) -> Result<(), WorkspaceError> {
         // Get the document
@@ -647,12 +769,10 @@ impl Workspace {
         // Chunk the content
         let chunks = chunk_document(&doc.content, ChunkConfig::default());
 
-        // Delete old chunks
-        self.storage.delete_chunks(document_id).await?;
-
-        // Insert new chunks
-        for (index, content) in chunks.into_iter().enumerate() {
-            // Generate embedding if provider available
+        // Generate embeddings before touching the index so the window where
+        // the document has no chunks (and is unsearchable) stays short.
+        let mut embedded = Vec::with_capacity(chunks.len());
+        for content in chunks {
             let embedding = if let Some(ref provider) = self.embeddings {
                 match provider.embed(&content).await {
                     Ok(emb) => Some(emb),
@@ -664,7 +784,12 @@ impl Workspace {
             } else {
                 None
             };
+            embedded.push((content, embedding));
+        }
 
+        // Replace old chunks; the FTS index follows via triggers / generated columns
+        self.storage.delete_chunks(document_id).await?;
+        for (index, (content, embedding)) in embedded.into_iter().enumerate() {
             self.storage
                 .insert_chunk(document_id, index as i32, &content, embedding.as_deref())
                 .await?;
@@ -868,4 +993,85 @@ mod tests {
         assert_eq!(normalize_directory("/"), "");
         assert_eq!(normalize_directory(""), "");
     }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_search_index_tracks_writes_and_deletes() {
+        let (db, _dir) = crate::testing::test_db().await;
+        let workspace = Workspace::new_with_db("search_user", db);
+
+        workspace
+            .write("notes/zebra.md", "The quartzite zebra grazes at dawn.")
+            .await
+            .unwrap();
+        let results = workspace.search("quartzite", 5).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].content.contains("quartzite"));
+
+        // Overwriting replaces the indexed chunks rather than appending.
+        workspace
+            .write("notes/zebra.md", "The basalt zebra sleeps at noon.")
+            .await
+            .unwrap();
+        assert!(workspace.search("quartzite", 5).await.unwrap().is_empty());
+        assert_eq!(workspace.search("basalt", 5).await.unwrap().len(), 1);
+
+        // Deleting removes it from results without any rebuild.
+        workspace.delete("notes/zebra.md").await.unwrap();
+        assert!(workspace.search("basalt", 5).await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_read_cache_serves_repeat_reads_until_write() {
+        let (db, _dir) = crate::testing::test_db().await;
+        let workspace = Workspace::new_with_db("cache_user", db.clone()).with_read_cache(8);
+
+        let doc = workspace.write("MEMORY.md", "first").await.unwrap();
+        assert_eq!(workspace.read("MEMORY.md").await.unwrap().content, "first");
+
+        // Change the row behind the workspace's back: a cached read can't see it.
+        db.update_document(doc.id, "changed elsewhere")
+            .await
+            .unwrap();
+        assert_eq!(workspace.read("/MEMORY.md").await.unwrap().content, "first");
+
+        // Every kind of write through the workspace invalidates the path.
+        workspace.write("MEMORY.md", "second").await.unwrap();
+        assert_eq!(workspace.read("MEMORY.md").await.unwrap().content, "second");
+
+        workspace.append_memory("third").await.unwrap();
+        assert_eq!(
+            workspace.read("MEMORY.md").await.unwrap().content,
+            "second\n\nthird"
+        );
+
+        workspace.delete("MEMORY.md").await.unwrap();
+        assert!(matches!(
+            workspace.read("MEMORY.md").await,
+            Err(WorkspaceError::DocumentNotFound { .. })
+        ));
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_rebuild_search_index_preserves_results() {
+        let (db, _dir) = crate::testing::test_db().await;
+        let workspace = Workspace::new_with_db("rebuild_user", db);
+
+        workspace
+            .write("a.md", "Obsidian lanterns line the corridor.")
+            .await
+            .unwrap();
+        workspace
+            .write("b.md", "Nothing relevant here.")
+            .await
+            .unwrap();
+
+        workspace.rebuild_search_index().await.unwrap();
+
+        let results = workspace.search("obsidian", 5).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].content.contains("Obsidian"));
+    }
 }
@@ -15,6 +15,7 @@ use crate::workspace::document::{MemoryChunk, MemoryDocument, WorkspaceEntry};
 use crate::workspace::search::{RankedResult, SearchConfig, SearchResult, reciprocal_rank_fusion};
 
 /// Database repository for workspace operations.
+#[derive(Clone)]
 pub struct Repository {
     pool: Pool,
 }
@@ -386,6 +387,22 @@ impl Repository {
             .collect())
     }
 
+    /// Rebuild the full-text index.
+    ///
+    /// `content_tsv` is a generated column, so rows are always current; this
+    /// only recovers from a bloated or corrupted GIN index.
+    pub async fn rebuild_search_index(&self) -> Result<(), WorkspaceError> {
+        let conn = self.conn().await?;
+
+        conn.execute("REINDEX INDEX idx_memory_chunks_tsv", &[])
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Reindex failed: {}", e),
+            })?;
+
+        Ok(())
+    }
+
     // ==================== Search Operations ====================
 
     /// Perform hybrid search combining FTS and vector similarity.
@@ -3,11 +3,13 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::zkproxy::tee::TeeBackend;
 use crate::zkproxy::types::{AttestationReport, TimingBreakdown};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ZkAuditEntry {
     pub timestamp: String,
     pub request_id: String,
@@ -20,20 +22,98 @@ pub struct ZkAuditEntry {
     pub feature_vector: Vec<f32>,
     pub timing: TimingBreakdown,
     pub guard_model_hash: String,
+    pub cached: bool,
+}
+
+/// A line of the audit log that could not be parsed.
+#[derive(Debug)]
+pub struct AuditLineError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+/// Everything read back from an audit log.
+#[derive(Debug, Default)]
+pub struct AuditLogContents {
+    pub entries: Vec<ZkAuditEntry>,
+    /// Malformed lines, skipped rather than failing the whole read.
+    pub errors: Vec<AuditLineError>,
+}
+
+/// An entry whose TEE attestation does not check out.
+#[derive(Debug)]
+pub struct AuditMismatch {
+    pub request_id: String,
+    pub reason: String,
+}
+
+/// Re-verify the TEE attestation of every entry that has one.
+///
+/// Flags attestations that `tee` rejects and attestations issued for a
+/// different proof than the one the entry records. Entries without an
+/// attestation (TEE disabled, cached decisions) are skipped.
+pub fn verify_entries(entries: &[ZkAuditEntry], tee: &dyn TeeBackend) -> Vec<AuditMismatch> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let report = entry.tee_attestation.as_ref()?;
+            let reason = if report.proof_hash != entry.proof_hash {
+                format!(
+                    "attestation covers proof {} but the entry records {}",
+                    report.proof_hash, entry.proof_hash
+                )
+            } else if !tee.verify_attestation(report) {
+                format!("{} backend rejected the attestation", tee.name())
+            } else {
+                return None;
+            };
+            Some(AuditMismatch {
+                request_id: entry.request_id.clone(),
+                reason,
+            })
+        })
+        .collect()
 }
 
 pub struct ZkAuditLog {
     path: PathBuf,
     enabled: bool,
+    sample_rate: f64,
 }
 
 impl ZkAuditLog {
     pub fn new(path: PathBuf, enabled: bool) -> Self {
-        Self { path, enabled }
+        Self {
+            path,
+            enabled,
+            sample_rate: 1.0,
+        }
+    }
+
+    /// Only log this fraction of allowed decisions; blocked ones are always logged.
+    pub fn with_sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Deterministic sampling: the same request id always gets the same answer.
+    fn should_log(&self, entry: &ZkAuditEntry) -> bool {
+        if !entry.decision || self.sample_rate >= 1.0 {
+            return true;
+        }
+        if self.sample_rate <= 0.0 {
+            return false;
+        }
+        let digest = Sha256::digest(entry.request_id.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        let fraction = u64::from_be_bytes(bytes) as f64 / u64::MAX as f64;
+        fraction < self.sample_rate
     }
 
     pub fn log(&self, entry: &ZkAuditEntry) -> Result<(), String> {
-        if !self.enabled {
+        if !self.enabled || !self.should_log(entry) {
             return Ok(());
         }
 
@@ -55,6 +135,63 @@ impl ZkAuditLog {
         Ok(())
     }
 
+    /// Sync written entries to disk. Each entry is written through its own
+    /// handle, so this only has to make the file durable.
+    pub fn flush(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        match std::fs::File::open(&self.path) {
+            Ok(file) => file
+                .sync_all()
+                .map_err(|e| format!("Failed to sync audit log: {e}")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to open audit log: {e}")),
+        }
+    }
+
+    /// Read every entry back, collecting malformed lines instead of failing.
+    ///
+    /// A log that does not exist yet reads as empty.
+    pub fn read_entries(&self) -> Result<AuditLogContents, String> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(AuditLogContents::default());
+            }
+            Err(e) => return Err(format!("Failed to read audit log: {e}")),
+        };
+
+        let mut contents = AuditLogContents::default();
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(entry) => contents.entries.push(entry),
+                Err(e) => contents.errors.push(AuditLineError {
+                    line: i + 1,
+                    message: e.to_string(),
+                }),
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Read every well-formed entry, logging and skipping malformed lines.
+    pub fn read_all(&self) -> Result<Vec<ZkAuditEntry>, String> {
+        let contents = self.read_entries()?;
+        for error in &contents.errors {
+            tracing::warn!(
+                "Skipping malformed ZK audit log line {} in {}: {}",
+                error.line,
+                self.path.display(),
+                error.message
+            );
+        }
+        Ok(contents.entries)
+    }
+
     pub fn create_entry(
         user_id: &str,
         decision: bool,
@@ -78,6 +215,129 @@ impl ZkAuditLog {
             feature_vector,
             timing,
             guard_model_hash: guard_model_hash.to_string(),
+            cached: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(allowed: bool) -> ZkAuditEntry {
+        ZkAuditLog::create_entry(
+            "user",
+            allowed,
+            if allowed { 0.1 } else { 0.9 },
+            "abcd",
+            true,
+            None,
+            vec![0.0; 3],
+            TimingBreakdown {
+                feature_extraction_ms: 0.0,
+                witness_ms: 0.0,
+                prove_ms: 0.0,
+                verify_ms: 0.0,
+                total_ms: 0.0,
+            },
+            "",
+        )
+    }
+
+    fn logged_lines(log: &ZkAuditLog, path: &std::path::Path) -> usize {
+        for _ in 0..5 {
+            log.log(&entry(true)).unwrap();
+        }
+        log.log(&entry(false)).unwrap();
+        std::fs::read_to_string(path)
+            .map(|s| s.lines().count())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn zero_sample_rate_logs_only_blocked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = ZkAuditLog::new(path.clone(), true).with_sample_rate(0.0);
+
+        assert_eq!(logged_lines(&log, &path), 1);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("\"decision\":false"));
+    }
+
+    #[test]
+    fn full_sample_rate_logs_everything() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = ZkAuditLog::new(path.clone(), true).with_sample_rate(1.0);
+
+        assert_eq!(logged_lines(&log, &path), 6);
+    }
+
+    #[test]
+    fn read_back_skips_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = ZkAuditLog::new(path.clone(), true);
+
+        log.log(&entry(true)).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{not json\n")
+            .unwrap();
+        log.log(&entry(false)).unwrap();
+
+        let contents = log.read_entries().unwrap();
+        assert_eq!(contents.entries.len(), 2);
+        assert!(contents.entries[0].decision);
+        assert!(!contents.entries[1].decision);
+        assert_eq!(contents.errors.len(), 1);
+        assert_eq!(contents.errors[0].line, 2);
+
+        assert_eq!(log.read_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn missing_log_reads_as_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = ZkAuditLog::new(dir.path().join("none.jsonl"), true);
+        assert!(log.read_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn verify_entries_flags_bad_attestations() {
+        use crate::zkproxy::tee::NoopTee;
+
+        let tee = NoopTee;
+        let attested = |hash: &str| {
+            let mut e = entry(true);
+            e.proof_hash = hash.to_string();
+            e.tee_attestation = Some(tee.attest(&hex::decode(hash).unwrap()).unwrap());
+            e
+        };
+
+        let good = attested("abcd");
+        let mut forged = attested("abcd");
+        if let Some(report) = forged.tee_attestation.as_mut() {
+            report.signature = "00".repeat(32);
+        }
+        let mut swapped = attested("abcd");
+        swapped.proof_hash = "ef01".to_string();
+        let unattested = entry(true);
+
+        let mismatches = verify_entries(&[good, forged, swapped, unattested], &tee);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].reason.contains("rejected"));
+        assert!(mismatches[1].reason.contains("ef01"));
+    }
+
+    #[test]
+    fn sampling_is_deterministic_per_request() {
+        let log = ZkAuditLog::new(PathBuf::from("/unused"), true).with_sample_rate(0.5);
+        let e = entry(true);
+        let first = log.should_log(&e);
+        assert!((0..10).all(|_| log.should_log(&e) == first));
+    }
+}
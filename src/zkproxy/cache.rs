@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::clock::{Clock, system_clock};
+use crate::zkproxy::types::GuardDecision;
+
+struct CacheEntry {
+    decision: GuardDecision,
+    created_at: Instant,
+    last_accessed: Instant,
+}
+
+/// Content-hash → `GuardDecision` cache so identical content (e.g. the same
+/// tool output seen twice) skips feature extraction and proving entirely.
+///
+/// Entries expire after `ttl`; the least recently used entry is evicted
+/// once `max_entries` is reached. A `max_entries` of 0 disables caching.
+pub struct DecisionCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    ttl: Duration,
+    max_entries: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl DecisionCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            max_entries,
+            clock: system_clock(),
+        }
+    }
+
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn key(content: &str) -> String {
+        format!("{:x}", Sha256::digest(content.as_bytes()))
+    }
+
+    /// Return the cached decision for `key`, marked as `cached`, if still fresh.
+    pub async fn get(&self, key: &str) -> Option<GuardDecision> {
+        if self.max_entries == 0 {
+            return None;
+        }
+        let now = self.clock.now();
+        let mut guard = self.entries.lock().await;
+        if let Some(entry) = guard.get_mut(key) {
+            if now.duration_since(entry.created_at) < self.ttl {
+                entry.last_accessed = now;
+                let mut decision = entry.decision.clone();
+                decision.cached = true;
+                return Some(decision);
+            }
+            guard.remove(key);
+        }
+        None
+    }
+
+    pub async fn insert(&self, key: String, decision: GuardDecision) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let now = self.clock.now();
+        let mut guard = self.entries.lock().await;
+
+        guard.retain(|_, entry| now.duration_since(entry.created_at) < self.ttl);
+
+        while guard.len() >= self.max_entries {
+            let oldest_key = guard
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_accessed)
+                .map(|(k, _)| k.clone());
+            match oldest_key {
+                Some(k) => {
+                    guard.remove(&k);
+                }
+                None => break,
+            }
+        }
+
+        guard.insert(
+            key,
+            CacheEntry {
+                decision,
+                created_at: now,
+                last_accessed: now,
+            },
+        );
+    }
+
+    pub async fn len(&self) -> usize {
+        self.entries.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.entries.lock().await.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::zkproxy::types::TimingBreakdown;
+
+    fn decision(score: f64) -> GuardDecision {
+        GuardDecision {
+            allowed: score < 0.5,
+            score,
+            proof_hash: "abcd".to_string(),
+            proof_verified: true,
+            timing: TimingBreakdown {
+                feature_extraction_ms: 0.0,
+                witness_ms: 0.0,
+                prove_ms: 0.0,
+                verify_ms: 0.0,
+                total_ms: 0.0,
+            },
+            tee_attestation: None,
+            cached: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn identical_content_hits() {
+        let cache = DecisionCache::new(Duration::from_secs(60), 10);
+        let key = DecisionCache::key("same tool output");
+        cache.insert(key, decision(0.2)).await;
+
+        let hit = cache
+            .get(&DecisionCache::key("same tool output"))
+            .await
+            .unwrap();
+        assert!(hit.cached);
+        assert_eq!(hit.score, 0.2);
+    }
+
+    #[tokio::test]
+    async fn distinct_content_misses() {
+        let cache = DecisionCache::new(Duration::from_secs(60), 10);
+        cache
+            .insert(DecisionCache::key("first"), decision(0.2))
+            .await;
+        assert!(cache.get(&DecisionCache::key("second")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_recomputed() {
+        let clock = Arc::new(MockClock::new());
+        let cache = DecisionCache::new(Duration::from_secs(60), 10).with_clock(clock.clone());
+        let key = DecisionCache::key("content");
+        cache.insert(key.clone(), decision(0.2)).await;
+        clock.advance(Duration::from_secs(59));
+        assert!(cache.get(&key).await.is_some());
+        clock.advance(Duration::from_secs(1));
+
+        assert!(cache.get(&key).await.is_none());
+        assert!(cache.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_at_capacity() {
+        let clock = Arc::new(MockClock::new());
+        let cache = DecisionCache::new(Duration::from_secs(60), 2).with_clock(clock.clone());
+        cache.insert("a".to_string(), decision(0.1)).await;
+        clock.advance(Duration::from_secs(1));
+        cache.insert("b".to_string(), decision(0.2)).await;
+        clock.advance(Duration::from_secs(1));
+        assert!(cache.get("a").await.is_some());
+        cache.insert("c".to_string(), decision(0.3)).await;
+
+        assert_eq!(cache.len().await, 2);
+        assert!(cache.get("a").await.is_some());
+        assert!(cache.get("b").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn zero_capacity_disables_cache() {
+        let cache = DecisionCache::new(Duration::from_secs(60), 0);
+        cache.insert("a".to_string(), decision(0.1)).await;
+        assert!(cache.get("a").await.is_none());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -9,6 +10,35 @@ pub struct ZkProxyConfig {
     pub worker_script: PathBuf,
     pub threshold: f64,
     pub tee_enabled: bool,
+    /// Attestation backend used when `tee_enabled`: `noop` (self-signed,
+    /// for development) or `sev-snp`. Default: `noop`.
+    pub tee_backend: String,
+    /// `snpguest` binary used by the `sev-snp` backend.
+    pub snp_tool: String,
+    /// Directory holding the AMD ARK/ASK/VCEK certificates for `sev-snp`.
+    pub snp_certs_dir: PathBuf,
+    /// Coarse tuning aid: scale factors applied to individual feature
+    /// indices before the vector is sent to the worker. Lets analysts
+    /// boost or suppress a feature without retraining the model. Empty
+    /// means no adjustment.
+    pub feature_weights: HashMap<usize, f32>,
+    /// Fraction of *allowed* decisions written to the audit log (0.0-1.0).
+    /// Blocked decisions are always logged. Default: 1.0.
+    pub audit_sample_rate: f64,
+    /// How long a content-hash decision stays cached. Default: 300s.
+    pub decision_cache_ttl_secs: u64,
+    /// Max cached decisions; 0 disables the cache. Default: 1000.
+    pub decision_cache_max_entries: usize,
+    /// Max cached feature vectors; 0 (the default) disables the cache.
+    pub feature_cache_capacity: usize,
+    /// Times a single worker call may restart a dead or hung worker before
+    /// failing. Default: 1.
+    pub worker_max_restarts: u32,
+    /// How long a worker call waits for its response. Default: 120s.
+    pub worker_call_timeout_secs: u64,
+    /// How long to wait for a (re)started worker to report ready.
+    /// Default: 30s.
+    pub worker_startup_timeout_secs: u64,
 }
 
 impl Default for ZkProxyConfig {
@@ -21,6 +51,17 @@ impl Default for ZkProxyConfig {
             worker_script: PathBuf::from("zkproxy/zkproxy_worker.py"),
             threshold: 0.5,
             tee_enabled: false,
+            tee_backend: "noop".to_string(),
+            snp_tool: "snpguest".to_string(),
+            snp_certs_dir: PathBuf::from("zkproxy/snp-certs"),
+            feature_weights: HashMap::new(),
+            audit_sample_rate: 1.0,
+            decision_cache_ttl_secs: 300,
+            decision_cache_max_entries: 1000,
+            feature_cache_capacity: 0,
+            worker_max_restarts: 1,
+            worker_call_timeout_secs: 120,
+            worker_startup_timeout_secs: 30,
         }
     }
 }
@@ -51,6 +92,75 @@ impl ZkProxyConfig {
             tee_enabled: std::env::var("ZKPROXY_TEE_ENABLED")
                 .map(|v| v == "true" || v == "1")
                 .unwrap_or(false),
+            tee_backend: std::env::var("ZKPROXY_TEE_BACKEND")
+                .unwrap_or_else(|_| "noop".to_string()),
+            snp_tool: std::env::var("ZKPROXY_SNP_TOOL").unwrap_or_else(|_| "snpguest".to_string()),
+            snp_certs_dir: std::env::var("ZKPROXY_SNP_CERTS_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("zkproxy/snp-certs")),
+            feature_weights: std::env::var("ZKPROXY_FEATURE_WEIGHTS")
+                .map(|v| parse_feature_weights(&v))
+                .unwrap_or_default(),
+            audit_sample_rate: std::env::var("ZKPROXY_AUDIT_SAMPLE_RATE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .map(|r| r.clamp(0.0, 1.0))
+                .unwrap_or(1.0),
+            decision_cache_ttl_secs: std::env::var("ZKPROXY_DECISION_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            decision_cache_max_entries: std::env::var("ZKPROXY_DECISION_CACHE_MAX_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1000),
+            feature_cache_capacity: std::env::var("ZKPROXY_FEATURE_CACHE_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            worker_max_restarts: std::env::var("ZKPROXY_WORKER_MAX_RESTARTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+            worker_call_timeout_secs: std::env::var("ZKPROXY_WORKER_CALL_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(120),
+            worker_startup_timeout_secs: std::env::var("ZKPROXY_WORKER_STARTUP_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
         }
     }
 }
+
+/// Parse `index:weight` pairs, e.g. `"0:1.5,3:0"`. Malformed entries are skipped.
+fn parse_feature_weights(value: &str) -> HashMap<usize, f32> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (index, weight) = pair.trim().split_once(':')?;
+            let parsed = (index.trim().parse().ok(), weight.trim().parse().ok());
+            match parsed {
+                (Some(index), Some(weight)) => Some((index, weight)),
+                _ => {
+                    tracing::warn!("Ignoring malformed ZKPROXY_FEATURE_WEIGHTS entry: {pair}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_feature_weights() {
+        let weights = parse_feature_weights("0:1.5, 3:0,bogus,4:x");
+        assert_eq!(weights.len(), 2);
+        assert_eq!(weights[&0], 1.5);
+        assert_eq!(weights[&3], 0.0);
+    }
+}
@@ -1,13 +1,53 @@
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::path::Path;
+use std::sync::Mutex;
 
+use lru::LruCache;
 use regex::Regex;
+use sha2::{Digest, Sha256};
 
 use crate::zkproxy::types::{FeatureConfig, FeatureSpec};
 
+/// Per-feature breakdown returned by `FeatureExtractor::extract_debug`.
+#[derive(Debug, Clone)]
+pub struct FeatureDebugEntry {
+    pub name: String,
+    pub kind: String,
+    pub index: usize,
+    /// Value before normalization (match count, byte length, entropy bits, ...).
+    pub raw: f32,
+    /// Value actually sent to the model.
+    pub normalized: f32,
+    /// Whether the normalized value hit its 1.0 ceiling.
+    pub clamped: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureDebug {
+    pub features: Vec<FeatureDebugEntry>,
+}
+
+impl FeatureDebug {
+    /// True when no feature fired at all.
+    pub fn all_zero(&self) -> bool {
+        self.features.iter().all(|f| f.normalized == 0.0)
+    }
+
+    pub fn clamped(&self) -> impl Iterator<Item = &FeatureDebugEntry> {
+        self.features.iter().filter(|f| f.clamped)
+    }
+}
+
+/// Below this many inputs per thread, `extract_batch` stays on the caller's
+/// thread; spawning costs more than the regex work saves.
+const MIN_INPUTS_PER_THREAD: usize = 8;
+
 pub struct FeatureExtractor {
     config: FeatureConfig,
     compiled_regexes: HashMap<usize, Vec<Regex>>,
+    /// Feature vectors keyed by SHA-256 of model hash + content.
+    cache: Option<Mutex<LruCache<[u8; 32], Vec<f32>>>>,
 }
 
 impl FeatureExtractor {
@@ -34,19 +74,56 @@ impl FeatureExtractor {
         Ok(Self {
             config,
             compiled_regexes,
+            cache: None,
         })
     }
 
+    /// Remember the feature vectors of the last `capacity` distinct inputs,
+    /// so re-scanning the same content (retries, failover) skips the regexes.
+    /// A capacity of 0 disables the cache.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = NonZeroUsize::new(capacity).map(|cap| Mutex::new(LruCache::new(cap)));
+        self
+    }
+
+    /// Cache key. The model hash is included so entries computed under a
+    /// different feature config never match.
+    fn cache_key(&self, content: &str) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.config.model_hash_sha256.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(content.as_bytes());
+        hasher.finalize().into()
+    }
+
     pub fn extract(&self, content: &str) -> Vec<f32> {
+        let Some(cache) = &self.cache else {
+            return self.extract_uncached(content);
+        };
+        let key = self.cache_key(content);
+        if let Some(features) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return features.clone();
+        }
+        let features = self.extract_uncached(content);
+        cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .put(key, features.clone());
+        features
+    }
+
+    /// Number of feature vectors currently cached.
+    pub fn cached_len(&self) -> usize {
+        self.cache
+            .as_ref()
+            .map_or(0, |c| c.lock().unwrap_or_else(|e| e.into_inner()).len())
+    }
+
+    fn extract_uncached(&self, content: &str) -> Vec<f32> {
         let mut features = vec![0.0f32; self.config.input_features];
 
         for feat in &self.config.features {
-            let value = match feat.kind.as_str() {
-                "regex_count" => self.extract_regex_count(feat, content),
-                "string_match" => self.extract_string_match(feat, content),
-                "builtin" => self.extract_builtin(feat, content),
-                _ => 0.0,
-            };
+            let (_, value) = self.compute(feat, content);
             if feat.index < features.len() {
                 features[feat.index] = value;
             }
@@ -55,6 +132,81 @@ impl FeatureExtractor {
         features
     }
 
+    /// `extract` for each input, in order. Large batches are spread over
+    /// scoped threads sharing the compiled regexes; the result for each input
+    /// is identical to calling `extract` on it.
+    pub fn extract_batch(&self, contents: &[&str]) -> Vec<Vec<f32>> {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(contents.len() / MIN_INPUTS_PER_THREAD);
+        if threads <= 1 {
+            return contents.iter().map(|c| self.extract(c)).collect();
+        }
+
+        let chunk_size = contents.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = contents
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || chunk.iter().map(|c| self.extract(c)).collect::<Vec<_>>())
+                })
+                .collect();
+            handles
+                .into_iter()
+                .zip(contents.chunks(chunk_size))
+                .flat_map(|(handle, chunk)| {
+                    // A panicking worker falls back to extracting its chunk here.
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| chunk.iter().map(|c| self.extract(c)).collect())
+                })
+                .collect()
+        })
+    }
+
+    /// Like `extract`, but reports raw (pre-normalization) values next to the
+    /// normalized ones so saturated features are visible.
+    pub fn extract_debug(&self, content: &str) -> FeatureDebug {
+        let features = self
+            .config
+            .features
+            .iter()
+            .map(|feat| {
+                let (raw, normalized) = self.compute(feat, content);
+                FeatureDebugEntry {
+                    name: feat.name.clone(),
+                    kind: feat.kind.clone(),
+                    index: feat.index,
+                    raw,
+                    normalized,
+                    clamped: normalized >= 1.0,
+                }
+            })
+            .collect();
+        FeatureDebug { features }
+    }
+
+    fn compute(&self, feat: &FeatureSpec, content: &str) -> (f32, f32) {
+        match feat.kind.as_str() {
+            "regex_count" => self.extract_regex_count(feat, content),
+            "string_match" => self.extract_string_match(feat, content),
+            "builtin" => self.extract_builtin(feat, content),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Scale the given feature indices in place. Indices outside the vector
+    /// are ignored. Values are not re-clamped, so a weight above 1.0 can push
+    /// a feature past the range the model was trained on.
+    pub fn apply_weights(features: &mut [f32], weights: &HashMap<usize, f32>) {
+        for (&index, &weight) in weights {
+            if let Some(value) = features.get_mut(index) {
+                *value *= weight;
+            }
+        }
+    }
+
     pub fn threshold(&self) -> f64 {
         self.config.threshold
     }
@@ -67,73 +219,69 @@ impl FeatureExtractor {
         self.config.input_features
     }
 
-    fn extract_regex_count(&self, feat: &FeatureSpec, content: &str) -> f32 {
+    /// Raw count and normalized value for a count-style feature.
+    fn saturate_count(count: usize) -> (f32, f32) {
+        let raw = count as f32;
+        (raw, raw.min(10.0) / 10.0)
+    }
+
+    fn extract_regex_count(&self, feat: &FeatureSpec, content: &str) -> (f32, f32) {
         if let Some(regexes) = self.compiled_regexes.get(&feat.index) {
             let count: usize = regexes.iter().map(|r| r.find_iter(content).count()).sum();
-            (count as f32).min(10.0) / 10.0
+            Self::saturate_count(count)
         } else {
-            0.0
+            (0.0, 0.0)
         }
     }
 
-    fn extract_string_match(&self, feat: &FeatureSpec, content: &str) -> f32 {
+    fn extract_string_match(&self, feat: &FeatureSpec, content: &str) -> (f32, f32) {
         let lower = content.to_lowercase();
         let count: usize = feat
             .strings
             .iter()
             .map(|s| lower.matches(&s.to_lowercase()).count())
             .sum();
-        (count as f32).min(10.0) / 10.0
+        Self::saturate_count(count)
     }
 
-    fn extract_builtin(&self, feat: &FeatureSpec, content: &str) -> f32 {
-        match feat.name.as_str() {
-            "normalized_length" => (content.len() as f32 / 1000.0).min(1.0),
-            "digit_ratio" => {
-                if content.is_empty() {
-                    return 0.0;
-                }
-                content.chars().filter(|c| c.is_ascii_digit()).count() as f32
-                    / content.len() as f32
-            }
-            "whitespace_ratio" => {
-                if content.is_empty() {
-                    return 0.0;
-                }
-                content.chars().filter(|c| c.is_whitespace()).count() as f32
-                    / content.len() as f32
+    fn extract_builtin(&self, feat: &FeatureSpec, content: &str) -> (f32, f32) {
+        let ratio = |matching: usize| {
+            if content.is_empty() {
+                return (0.0, 0.0);
             }
-            "uppercase_ratio" => {
-                if content.is_empty() {
-                    return 0.0;
-                }
-                content.chars().filter(|c| c.is_uppercase()).count() as f32
-                    / content.len() as f32
+            let r = matching as f32 / content.len() as f32;
+            (r, r)
+        };
+        match feat.name.as_str() {
+            "normalized_length" => {
+                let raw = content.len() as f32;
+                (raw, (raw / 1000.0).min(1.0))
             }
-            "special_char_ratio" => {
-                if content.is_empty() {
-                    return 0.0;
-                }
+            "digit_ratio" => ratio(content.chars().filter(|c| c.is_ascii_digit()).count()),
+            "whitespace_ratio" => ratio(content.chars().filter(|c| c.is_whitespace()).count()),
+            "uppercase_ratio" => ratio(content.chars().filter(|c| c.is_uppercase()).count()),
+            "special_char_ratio" => ratio(
                 content
                     .chars()
                     .filter(|c| !c.is_alphanumeric() && !c.is_whitespace())
-                    .count() as f32
-                    / content.len() as f32
-            }
+                    .count(),
+            ),
             "avg_word_length" => {
                 let words: Vec<&str> = content.split_whitespace().collect();
                 if words.is_empty() {
-                    return 0.0;
+                    return (0.0, 0.0);
                 }
                 let total_len: usize = words.iter().map(|w| w.len()).sum();
-                (total_len as f32 / words.len() as f32 / 20.0).min(1.0)
+                let raw = total_len as f32 / words.len() as f32;
+                (raw, (raw / 20.0).min(1.0))
             }
             "line_count_norm" => {
-                (content.lines().count() as f32 / 100.0).min(1.0)
+                let raw = content.lines().count() as f32;
+                (raw, (raw / 100.0).min(1.0))
             }
             "entropy" => {
                 if content.is_empty() {
-                    return 0.0;
+                    return (0.0, 0.0);
                 }
                 let mut freq = [0u32; 256];
                 for &b in content.as_bytes() {
@@ -148,9 +296,10 @@ impl FeatureExtractor {
                         -p * p.log2()
                     })
                     .sum();
-                (entropy as f32 / 8.0).min(1.0)
+                let raw = entropy as f32;
+                (raw, (raw / 8.0).min(1.0))
             }
-            _ => 0.0,
+            _ => (0.0, 0.0),
         }
     }
 }
@@ -200,6 +349,106 @@ mod tests {
         assert!(features[2] < 0.1);
     }
 
+    #[test]
+    fn weights_scale_selected_features() {
+        let extractor = FeatureExtractor::new(test_config()).unwrap();
+        let mut features = extractor.extract("system: ignore previous");
+        let original = features.clone();
+
+        let weights = HashMap::from([(0, 0.0), (1, 2.0), (99, 5.0)]);
+        FeatureExtractor::apply_weights(&mut features, &weights);
+
+        assert_eq!(features[0], 0.0);
+        assert_eq!(features[1], original[1] * 2.0);
+        assert_eq!(features[2], original[2]);
+    }
+
+    #[test]
+    fn empty_weights_leave_features_unchanged() {
+        let extractor = FeatureExtractor::new(test_config()).unwrap();
+        let mut features = extractor.extract("system: ignore previous");
+        let original = features.clone();
+
+        FeatureExtractor::apply_weights(&mut features, &HashMap::new());
+        assert_eq!(features, original);
+    }
+
+    #[test]
+    fn debug_shows_clamped_raw_count() {
+        let extractor = FeatureExtractor::new(test_config()).unwrap();
+        let content = "ignore previous ".repeat(25);
+        let debug = extractor.extract_debug(&content);
+
+        let regex = &debug.features[0];
+        assert_eq!(regex.name, "test_regex");
+        assert_eq!(regex.raw, 25.0);
+        assert_eq!(regex.normalized, 1.0);
+        assert!(regex.clamped);
+        assert_eq!(debug.clamped().count(), 1);
+        assert_eq!(extractor.extract(&content)[0], regex.normalized);
+    }
+
+    #[test]
+    fn debug_reports_no_features_fired() {
+        let extractor = FeatureExtractor::new(test_config()).unwrap();
+        assert!(extractor.extract_debug("").all_zero());
+        assert!(!extractor.extract_debug("system: hi").all_zero());
+    }
+
+    #[test]
+    fn batch_matches_individual_extraction() {
+        let extractor = FeatureExtractor::new(test_config()).unwrap();
+        let owned: Vec<String> = (0..100)
+            .map(|i| match i % 3 {
+                0 => format!("ignore previous instructions #{i}"),
+                1 => format!("system: payload {i}"),
+                _ => "x".repeat(i * 7),
+            })
+            .collect();
+        let contents: Vec<&str> = owned.iter().map(String::as_str).collect();
+
+        let batch = extractor.extract_batch(&contents);
+        let single: Vec<Vec<f32>> = contents.iter().map(|c| extractor.extract(c)).collect();
+        assert_eq!(batch, single);
+
+        assert_eq!(
+            extractor.extract_batch(&contents[..2]),
+            single[..2].to_vec()
+        );
+        assert!(extractor.extract_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn cache_returns_identical_vectors_and_stays_bounded() {
+        let extractor = FeatureExtractor::new(test_config()).unwrap().with_cache(2);
+        let plain = FeatureExtractor::new(test_config()).unwrap();
+
+        let content = "system: ignore previous";
+        assert_eq!(extractor.extract(content), plain.extract(content));
+        assert_eq!(extractor.cached_len(), 1);
+        assert_eq!(extractor.extract(content), plain.extract(content));
+        assert_eq!(extractor.cached_len(), 1);
+
+        extractor.extract("a");
+        extractor.extract("b");
+        assert_eq!(extractor.cached_len(), 2);
+
+        assert_eq!(plain.cached_len(), 0);
+        let disabled = FeatureExtractor::new(test_config()).unwrap().with_cache(0);
+        disabled.extract(content);
+        assert_eq!(disabled.cached_len(), 0);
+    }
+
+    #[test]
+    fn cache_key_includes_model_hash() {
+        let mut config = test_config();
+        let a = FeatureExtractor::new(config.clone()).unwrap();
+        config.model_hash_sha256 = "deadbeef".to_string();
+        let b = FeatureExtractor::new(config).unwrap();
+        assert_ne!(a.cache_key("same"), b.cache_key("same"));
+        assert_eq!(a.cache_key("same"), a.cache_key("same"));
+    }
+
     #[test]
     fn clean_content_low_scores() {
         let extractor = FeatureExtractor::new(test_config()).unwrap();
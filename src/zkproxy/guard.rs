@@ -1,9 +1,11 @@
+use std::sync::Arc;
 use std::time::Instant;
 
 use crate::zkproxy::audit::ZkAuditLog;
+use crate::zkproxy::cache::DecisionCache;
 use crate::zkproxy::config::ZkProxyConfig;
 use crate::zkproxy::feature::FeatureExtractor;
-use crate::zkproxy::tee::{NoopTee, TeeBackend};
+use crate::zkproxy::tee::{TeeBackend, backend_from_config};
 use crate::zkproxy::types::{GuardDecision, ProofResult, TimingBreakdown};
 use crate::zkproxy::worker::PersistentWorker;
 
@@ -12,15 +14,23 @@ pub struct ZkProxy {
     extractor: FeatureExtractor,
     config: ZkProxyConfig,
     audit: ZkAuditLog,
-    tee: Box<dyn TeeBackend>,
+    tee: Arc<dyn TeeBackend>,
+    cache: DecisionCache,
 }
 
 impl ZkProxy {
     pub async fn new(config: ZkProxyConfig) -> Result<Self, String> {
-        let extractor = FeatureExtractor::from_config_file(&config.config_path)?;
-
-        let worker =
-            PersistentWorker::new(&config.python_bin, &config.worker_script).await?;
+        let extractor = FeatureExtractor::from_config_file(&config.config_path)?
+            .with_cache(config.feature_cache_capacity);
+
+        let worker = PersistentWorker::new(
+            &config.python_bin,
+            &config.worker_script,
+            std::time::Duration::from_secs(config.worker_call_timeout_secs),
+            std::time::Duration::from_secs(config.worker_startup_timeout_secs),
+        )
+        .await?
+        .with_max_restarts(config.worker_max_restarts);
 
         let health = worker.health().await?;
         tracing::info!(
@@ -29,9 +39,17 @@ impl ZkProxy {
         );
 
         let audit_path = config.model_path.with_extension("audit.jsonl");
-        let audit = ZkAuditLog::new(audit_path, true);
+        let audit = ZkAuditLog::new(audit_path, true).with_sample_rate(config.audit_sample_rate);
+
+        let tee: Arc<dyn TeeBackend> = backend_from_config(&config)?.into();
+        if config.tee_enabled {
+            tracing::info!("ZkProxy TEE attestation backend: {}", tee.name());
+        }
 
-        let tee: Box<dyn TeeBackend> = Box::new(NoopTee);
+        let cache = DecisionCache::new(
+            std::time::Duration::from_secs(config.decision_cache_ttl_secs),
+            config.decision_cache_max_entries,
+        );
 
         Ok(Self {
             worker,
@@ -39,14 +57,36 @@ impl ZkProxy {
             config,
             audit,
             tee,
+            cache,
         })
     }
 
     pub async fn guard_check(&self, content: &str, user_id: &str) -> Result<GuardDecision, String> {
         let t_start = Instant::now();
 
+        let cache_key = DecisionCache::key(content);
+        if let Some(decision) = self.cache.get(&cache_key).await {
+            let mut entry = ZkAuditLog::create_entry(
+                user_id,
+                decision.allowed,
+                decision.score,
+                &decision.proof_hash,
+                decision.proof_verified,
+                decision.tee_attestation.clone(),
+                Vec::new(),
+                decision.timing.clone(),
+                self.extractor.model_hash(),
+            );
+            entry.cached = true;
+            if let Err(e) = self.audit.log(&entry) {
+                tracing::warn!("Failed to write ZK audit log: {e}");
+            }
+            return Ok(decision);
+        }
+
         let t_feat = Instant::now();
-        let features = self.extractor.extract(content);
+        let mut features = self.extractor.extract(content);
+        FeatureExtractor::apply_weights(&mut features, &self.config.feature_weights);
         let feat_ms = t_feat.elapsed().as_secs_f64() * 1000.0;
 
         let params = serde_json::json!({
@@ -68,10 +108,32 @@ impl ZkProxy {
         };
 
         let allowed = proof_result.score < self.config.threshold;
+        crate::observability::prometheus::record_zkproxy_decision(
+            !allowed,
+            &[
+                ("feature_extraction", timing.feature_extraction_ms),
+                ("witness", timing.witness_ms),
+                ("prove", timing.prove_ms),
+                ("verify", timing.verify_ms),
+                ("total", timing.total_ms),
+            ],
+        );
 
         let tee_attestation = if self.config.tee_enabled {
             let hash_bytes = hex::decode(&proof_result.proof_hash).unwrap_or_default();
-            Some(self.tee.attest(&hash_bytes))
+            // Backends may shell out or touch the filesystem; keep that off
+            // the async workers.
+            let tee = Arc::clone(&self.tee);
+            let attested = tokio::task::spawn_blocking(move || tee.attest(&hash_bytes))
+                .await
+                .unwrap_or_else(|e| Err(format!("attestation task failed: {e}")));
+            match attested {
+                Ok(report) => Some(report),
+                Err(e) => {
+                    tracing::warn!("TEE attestation via {} failed: {e}", self.tee.name());
+                    None
+                }
+            }
         } else {
             None
         };
@@ -83,7 +145,9 @@ impl ZkProxy {
             proof_verified: proof_result.verified,
             timing: timing.clone(),
             tee_attestation: tee_attestation.clone(),
+            cached: false,
         };
+        self.cache.insert(cache_key, decision.clone()).await;
 
         let entry = ZkAuditLog::create_entry(
             user_id,
@@ -113,6 +177,25 @@ impl ZkProxy {
         Ok(())
     }
 
+    /// Check that the worker process is up and answering, restarting it if
+    /// it has died. Suitable for a periodic liveness probe.
+    pub async fn health_check(&self) -> Result<(), String> {
+        self.worker
+            .check_health()
+            .await
+            .map_err(|e| format!("ZkProxy unhealthy: {e}"))
+    }
+
+    /// Stop the worker process; subsequent guard checks fail.
+    pub async fn shutdown(&self) {
+        self.worker.shutdown().await;
+    }
+
+    /// Make every audit entry written so far durable.
+    pub fn flush_audit(&self) -> Result<(), String> {
+        self.audit.flush()
+    }
+
     pub fn extractor(&self) -> &FeatureExtractor {
         &self.extractor
     }
@@ -1,18 +1,36 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
 use chrono::Utc;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
 
+use crate::zkproxy::config::ZkProxyConfig;
 use crate::zkproxy::types::AttestationReport;
 
 pub trait TeeBackend: Send + Sync {
-    fn attest(&self, proof_hash: &[u8]) -> AttestationReport;
+    fn attest(&self, proof_hash: &[u8]) -> Result<AttestationReport, String>;
     fn verify_attestation(&self, report: &AttestationReport) -> bool;
     fn name(&self) -> &str;
 }
 
+/// Build the backend named by `config.tee_backend`.
+pub fn backend_from_config(config: &ZkProxyConfig) -> Result<Box<dyn TeeBackend>, String> {
+    match config.tee_backend.as_str() {
+        "noop" => Ok(Box::new(NoopTee)),
+        "sev-snp" => Ok(Box::new(SnpTee::new(
+            &config.snp_tool,
+            config.snp_certs_dir.clone(),
+        ))),
+        other => Err(format!(
+            "unknown TEE backend '{other}' (expected 'noop' or 'sev-snp')"
+        )),
+    }
+}
+
 pub struct NoopTee;
 
 impl TeeBackend for NoopTee {
-    fn attest(&self, proof_hash: &[u8]) -> AttestationReport {
+    fn attest(&self, proof_hash: &[u8]) -> Result<AttestationReport, String> {
         let timestamp = Utc::now().to_rfc3339();
         let mut hasher = Sha256::new();
         hasher.update(proof_hash);
@@ -20,12 +38,12 @@ impl TeeBackend for NoopTee {
         hasher.update(b"noop-tee-self-signed");
         let signature = hex::encode(hasher.finalize());
 
-        AttestationReport {
+        Ok(AttestationReport {
             proof_hash: hex::encode(proof_hash),
             timestamp,
             backend: "noop".to_string(),
             signature,
-        }
+        })
     }
 
     fn verify_attestation(&self, report: &AttestationReport) -> bool {
@@ -43,6 +61,152 @@ impl TeeBackend for NoopTee {
     }
 }
 
+/// Size of a SEV-SNP attestation report.
+const SNP_REPORT_LEN: usize = 0x4A0;
+
+/// Offset of the 64-byte guest-supplied `REPORT_DATA` field in the report.
+const SNP_REPORT_DATA_OFFSET: usize = 0x50;
+
+/// AMD SEV-SNP attestation via the `snpguest` tool.
+///
+/// `attest` asks the guest firmware (through `/dev/sev-guest`) for a report
+/// whose `REPORT_DATA` is SHA-512 over the proof hash and timestamp, and
+/// stores the raw report hex-encoded in `signature`. `verify_attestation`
+/// recomputes `REPORT_DATA`, then has `snpguest` check the ARK -> ASK -> VCEK
+/// chain in `certs_dir` and the report signature against the VCEK.
+///
+/// `certs_dir` must already hold the AMD certificates, e.g. from
+/// `snpguest fetch ca pem <dir> milan` and `snpguest fetch vcek pem <dir> <report>`.
+///
+/// The tool runs synchronously and blocks the calling thread; async callers
+/// go through `tokio::task::spawn_blocking` (see `ZkProxy::guard_check`).
+pub struct SnpTee {
+    tool: String,
+    certs_dir: PathBuf,
+}
+
+impl SnpTee {
+    pub fn new(tool: impl Into<String>, certs_dir: PathBuf) -> Self {
+        Self {
+            tool: tool.into(),
+            certs_dir,
+        }
+    }
+
+    fn report_data(proof_hash: &[u8], timestamp: &str) -> [u8; 64] {
+        let mut hasher = Sha512::new();
+        hasher.update(proof_hash);
+        hasher.update(timestamp.as_bytes());
+        hasher.finalize().into()
+    }
+
+    fn run(&self, args: &[&Path]) -> Result<(), String> {
+        let output = Command::new(&self.tool)
+            .args(args)
+            .output()
+            .map_err(|e| format!("failed to run {}: {e}", self.tool))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} {} failed: {}",
+                self.tool,
+                args.first()
+                    .map(|a| a.display().to_string())
+                    .unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    fn verify(&self, report: &AttestationReport) -> Result<(), String> {
+        let raw = hex::decode(&report.signature).map_err(|e| format!("report is not hex: {e}"))?;
+        if raw.len() != SNP_REPORT_LEN {
+            return Err(format!(
+                "report is {} bytes, expected {SNP_REPORT_LEN}",
+                raw.len()
+            ));
+        }
+        let proof_hash =
+            hex::decode(&report.proof_hash).map_err(|e| format!("proof hash is not hex: {e}"))?;
+        let expected = Self::report_data(&proof_hash, &report.timestamp);
+        if raw[SNP_REPORT_DATA_OFFSET..SNP_REPORT_DATA_OFFSET + 64] != expected {
+            return Err("report data does not match the proof hash".to_string());
+        }
+
+        let work = WorkDir::new()?;
+        let report_path = work.path().join("report.bin");
+        std::fs::write(&report_path, &raw).map_err(|e| e.to_string())?;
+        self.run(&[Path::new("verify"), Path::new("certs"), &self.certs_dir])?;
+        self.run(&[
+            Path::new("verify"),
+            Path::new("attestation"),
+            &self.certs_dir,
+            &report_path,
+        ])
+    }
+}
+
+impl TeeBackend for SnpTee {
+    fn attest(&self, proof_hash: &[u8]) -> Result<AttestationReport, String> {
+        let timestamp = Utc::now().to_rfc3339();
+        let work = WorkDir::new()?;
+        let request_path = work.path().join("request.bin");
+        let report_path = work.path().join("report.bin");
+        std::fs::write(&request_path, Self::report_data(proof_hash, &timestamp))
+            .map_err(|e| e.to_string())?;
+
+        self.run(&[Path::new("report"), &report_path, &request_path])?;
+        let raw = std::fs::read(&report_path).map_err(|e| e.to_string())?;
+
+        Ok(AttestationReport {
+            proof_hash: hex::encode(proof_hash),
+            timestamp,
+            backend: "sev-snp".to_string(),
+            signature: hex::encode(raw),
+        })
+    }
+
+    fn verify_attestation(&self, report: &AttestationReport) -> bool {
+        if report.backend != "sev-snp" {
+            return false;
+        }
+        match self.verify(report) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("SEV-SNP attestation rejected: {e}");
+                false
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "sev-snp"
+    }
+}
+
+/// Scratch directory for the files `snpguest` reads and writes, removed on drop.
+struct WorkDir(PathBuf);
+
+impl WorkDir {
+    fn new() -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!("ironclaw-snp-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&path)
+            .map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,7 +215,7 @@ mod tests {
     fn noop_tee_roundtrip() {
         let tee = NoopTee;
         let proof_hash = b"test_proof_hash_123";
-        let report = tee.attest(proof_hash);
+        let report = tee.attest(proof_hash).unwrap();
         assert!(tee.verify_attestation(&report));
         assert_eq!(report.backend, "noop");
     }
@@ -59,9 +223,82 @@ mod tests {
     #[test]
     fn noop_tee_rejects_tampered() {
         let tee = NoopTee;
-        let report = tee.attest(b"real_hash");
+        let report = tee.attest(b"real_hash").unwrap();
         let mut tampered = report;
         tampered.proof_hash = hex::encode(b"fake_hash");
         assert!(!tee.verify_attestation(&tampered));
     }
+
+    #[test]
+    fn backend_is_selected_from_config() {
+        let mut config = ZkProxyConfig::default();
+        assert_eq!(backend_from_config(&config).unwrap().name(), "noop");
+
+        config.tee_backend = "sev-snp".to_string();
+        assert_eq!(backend_from_config(&config).unwrap().name(), "sev-snp");
+
+        config.tee_backend = "sgx".to_string();
+        assert!(backend_from_config(&config).is_err());
+    }
+
+    #[test]
+    fn snp_attest_fails_without_the_tool() {
+        let tee = SnpTee::new("/nonexistent/snpguest", PathBuf::from("certs"));
+        let err = tee.attest(b"hash").unwrap_err();
+        assert!(err.contains("failed to run"), "{err}");
+    }
+
+    /// Stand-in for `snpguest`: `report` embeds the request at the
+    /// REPORT_DATA offset of a zeroed report; `verify` exits with `verify_exit`.
+    #[cfg(unix)]
+    fn fake_snpguest(dir: &Path, verify_exit: u8) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = dir.join(format!("snpguest-{verify_exit}"));
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\n\
+                 case \"$1\" in\n\
+                 report) {{ head -c 80 /dev/zero; cat \"$3\"; head -c 1040 /dev/zero; }} > \"$2\" ;;\n\
+                 verify) exit {verify_exit} ;;\n\
+                 *) exit 2 ;;\n\
+                 esac\n"
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script.display().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn snp_report_binds_the_proof_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let tee = SnpTee::new(fake_snpguest(dir.path(), 0), dir.path().to_path_buf());
+
+        let report = tee.attest(b"proof").unwrap();
+        assert_eq!(report.backend, "sev-snp");
+        assert_eq!(report.signature.len(), SNP_REPORT_LEN * 2);
+        assert!(tee.verify_attestation(&report));
+
+        let mut tampered = report.clone();
+        tampered.proof_hash = hex::encode(b"other proof");
+        assert!(!tee.verify_attestation(&tampered));
+
+        let mut noop = report;
+        noop.backend = "noop".to_string();
+        assert!(!tee.verify_attestation(&noop));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn snp_verification_requires_the_cert_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let attester = SnpTee::new(fake_snpguest(dir.path(), 0), dir.path().to_path_buf());
+        let report = attester.attest(b"proof").unwrap();
+
+        let verifier = SnpTee::new(fake_snpguest(dir.path(), 1), dir.path().to_path_buf());
+        assert!(!verifier.verify_attestation(&report));
+    }
 }
@@ -27,6 +27,9 @@ pub struct GuardDecision {
     pub proof_verified: bool,
     pub timing: TimingBreakdown,
     pub tee_attestation: Option<AttestationReport>,
+    /// True when served from the content-hash decision cache.
+    #[serde(default)]
+    pub cached: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
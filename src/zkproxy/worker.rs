@@ -1,61 +1,246 @@
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, Command};
-use tokio::sync::Mutex;
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{Mutex, mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 
 use crate::zkproxy::types::{JsonRpcRequest, JsonRpcResponse};
 
+/// Delay before the first restart; doubled for each further attempt.
+const RESTART_BACKOFF: std::time::Duration = std::time::Duration::from_millis(100);
+const MAX_RESTART_BACKOFF: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkerError {
+    #[error("Worker call cancelled")]
+    Cancelled,
+    #[error("Worker response timeout")]
+    Timeout,
+    #[error("Worker process exited")]
+    Exited,
+    #[error("Worker shut down")]
+    ShutDown,
+    #[error("Worker unavailable after {attempts} restart attempt(s): {reason}")]
+    Unavailable { attempts: u32, reason: String },
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl From<String> for WorkerError {
+    fn from(reason: String) -> Self {
+        Self::Failed(reason)
+    }
+}
+
+impl From<&str> for WorkerError {
+    fn from(reason: &str) -> Self {
+        Self::Failed(reason.to_string())
+    }
+}
+
+/// Calls waiting for a response, keyed by JSON-RPC id.
+#[derive(Default)]
+struct Pending {
+    waiters: HashMap<u64, oneshot::Sender<JsonRpcResponse>>,
+    /// Set once the reader task has stopped; no response can arrive after.
+    closed: bool,
+    /// When the worker last answered any request.
+    last_answer: Option<tokio::time::Instant>,
+}
+
+/// The I/O tasks of one worker process.
+///
+/// A writer task owns stdin and writes the lines queued on `writer`; a
+/// reader task owns stdout and hands each response to the waiter registered
+/// under its id. Calls therefore overlap instead of taking turns on the
+/// pipes.
+#[derive(Clone)]
+struct Connection {
+    /// Incremented on every (re)spawn, so concurrent callers that saw the
+    /// same failure restart the worker only once.
+    generation: u64,
+    writer: mpsc::UnboundedSender<String>,
+    pending: Arc<std::sync::Mutex<Pending>>,
+}
+
+impl Connection {
+    fn start(generation: u64, stdin: ChildStdin, reader: BufReader<ChildStdout>) -> Self {
+        let (writer, rx) = mpsc::unbounded_channel();
+        let pending = Arc::new(std::sync::Mutex::new(Pending::default()));
+        tokio::spawn(write_loop(stdin, rx));
+        tokio::spawn(read_loop(reader, Arc::clone(&pending)));
+        Self {
+            generation,
+            writer,
+            pending,
+        }
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, Pending> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register a waiter for `id`; fails if the worker's stdout has closed.
+    fn register(&self, id: u64) -> Result<oneshot::Receiver<JsonRpcResponse>, WorkerError> {
+        let mut pending = self.lock_pending();
+        if pending.closed {
+            return Err(WorkerError::Exited);
+        }
+        let (tx, rx) = oneshot::channel();
+        pending.waiters.insert(id, tx);
+        Ok(rx)
+    }
+
+    /// Whether the worker has answered any request since `since`.
+    fn answered_since(&self, since: tokio::time::Instant) -> bool {
+        self.lock_pending()
+            .last_answer
+            .is_some_and(|at| at >= since)
+    }
+
+    /// Drop the waiter for `id`; a late response for it is then discarded.
+    fn forget(&self, id: u64) {
+        self.lock_pending().waiters.remove(&id);
+    }
+
+    fn send(&self, line: String) -> Result<(), WorkerError> {
+        self.writer.send(line).map_err(|_| WorkerError::Exited)
+    }
+}
+
+async fn write_loop(mut stdin: ChildStdin, mut rx: mpsc::UnboundedReceiver<String>) {
+    while let Some(line) = rx.recv().await {
+        let written = async {
+            stdin.write_all(format!("{line}\n").as_bytes()).await?;
+            stdin.flush().await
+        };
+        if let Err(e) = written.await {
+            // The reader sees EOF when the process is gone and fails the
+            // waiters; nothing more can be written either way.
+            tracing::debug!("Failed to write to zkproxy worker stdin: {e}");
+            return;
+        }
+    }
+}
+
+async fn read_loop(mut reader: BufReader<ChildStdout>, pending: Arc<std::sync::Mutex<Pending>>) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line).await {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                tracing::debug!("Failed to read from zkproxy worker: {e}");
+                break;
+            }
+        }
+
+        let response: JsonRpcResponse = match serde_json::from_str(&line) {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Invalid zkproxy worker response: {e} -- raw: {line}");
+                continue;
+            }
+        };
+        let waiter = {
+            let mut pending = pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.last_answer = Some(tokio::time::Instant::now());
+            pending.waiters.remove(&response.id)
+        };
+        match waiter {
+            Some(tx) => {
+                let _ = tx.send(response);
+            }
+            None => tracing::debug!(id = response.id, "Discarding stale zkproxy worker response"),
+        }
+    }
+
+    // Dropping the senders wakes every remaining waiter with `Exited`.
+    let mut pending = pending.lock().unwrap_or_else(|e| e.into_inner());
+    pending.closed = true;
+    pending.waiters.clear();
+}
+
 pub struct PersistentWorker {
     child: Mutex<Option<Child>>,
-    stdin: Mutex<Option<tokio::process::ChildStdin>>,
-    reader: Mutex<Option<BufReader<tokio::process::ChildStdout>>>,
+    conn: Mutex<Option<Connection>>,
     python_bin: String,
     worker_script: String,
     request_id: AtomicU64,
+    generation: AtomicU64,
+    stopped: AtomicBool,
+    max_restarts: u32,
+    call_timeout: Duration,
+    startup_timeout: Duration,
 }
 
 impl PersistentWorker {
-    pub async fn new(python_bin: &str, worker_script: &Path) -> Result<Self, String> {
+    /// Spawn the worker and wait up to `startup_timeout` for it to report
+    /// ready. Each call then waits up to `call_timeout` for its response.
+    pub async fn new(
+        python_bin: &str,
+        worker_script: &Path,
+        call_timeout: Duration,
+        startup_timeout: Duration,
+    ) -> Result<Self, String> {
         let worker = Self {
             child: Mutex::new(None),
-            stdin: Mutex::new(None),
-            reader: Mutex::new(None),
+            conn: Mutex::new(None),
             python_bin: python_bin.to_string(),
             worker_script: worker_script.to_string_lossy().to_string(),
             request_id: AtomicU64::new(1),
+            generation: AtomicU64::new(0),
+            stopped: AtomicBool::new(false),
+            max_restarts: 1,
+            call_timeout,
+            startup_timeout,
         };
-        worker.spawn().await?;
-        worker.wait_for_startup().await?;
+        let conn = worker.spawn().await?;
+        *worker.conn.lock().await = Some(conn);
         Ok(worker)
     }
 
-    async fn spawn(&self) -> Result<(), String> {
+    /// How many times a single call may restart a dead or unresponsive
+    /// worker before giving up with `WorkerError::Unavailable`. Default: 1.
+    pub fn with_max_restarts(mut self, max_restarts: u32) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Start the process, wait for its startup message, then hand its pipes
+    /// to the connection's writer and reader tasks.
+    async fn spawn(&self) -> Result<Connection, String> {
         let mut cmd = Command::new(&self.python_bin);
         cmd.arg(&self.worker_script)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
 
-        let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn worker: {e}"))?;
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to spawn worker: {e}"))?;
 
         let stdin = child.stdin.take().ok_or("Failed to get worker stdin")?;
         let stdout = child.stdout.take().ok_or("Failed to get worker stdout")?;
 
         *self.child.lock().await = Some(child);
-        *self.stdin.lock().await = Some(stdin);
-        *self.reader.lock().await = Some(BufReader::new(stdout));
 
-        Ok(())
-    }
+        let mut reader = BufReader::new(stdout);
+        self.wait_for_startup(&mut reader).await?;
 
-    async fn wait_for_startup(&self) -> Result<(), String> {
-        let mut reader_guard = self.reader.lock().await;
-        let reader = reader_guard.as_mut().ok_or("No reader available")?;
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        Ok(Connection::start(generation, stdin, reader))
+    }
 
+    async fn wait_for_startup(&self, reader: &mut BufReader<ChildStdout>) -> Result<(), String> {
         let mut line = String::new();
-        tokio::time::timeout(std::time::Duration::from_secs(30), reader.read_line(&mut line))
+        tokio::time::timeout(self.startup_timeout, reader.read_line(&mut line))
             .await
             .map_err(|_| "Worker startup timeout".to_string())?
             .map_err(|e| format!("Failed to read startup message: {e}"))?;
@@ -63,7 +248,11 @@ impl PersistentWorker {
         let msg: serde_json::Value =
             serde_json::from_str(&line).map_err(|e| format!("Invalid startup message: {e}"))?;
 
-        if msg.get("params").and_then(|p| p.get("status")).and_then(|s| s.as_str()) != Some("ready")
+        if msg
+            .get("params")
+            .and_then(|p| p.get("status"))
+            .and_then(|s| s.as_str())
+            != Some("ready")
         {
             return Err(format!("Unexpected startup message: {line}"));
         }
@@ -76,67 +265,208 @@ impl PersistentWorker {
         method: &str,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, String> {
+        self.call_cancellable(method, params, &CancellationToken::new())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Like `call`, but stops waiting as soon as `cancel` fires.
+    ///
+    /// Calls are multiplexed over the worker's pipes by JSON-RPC id, so any
+    /// number may be in flight at once and a slow request does not hold up
+    /// the others.
+    ///
+    /// On cancellation a `cancel` notification is sent to the worker and
+    /// `WorkerError::Cancelled` is returned. The worker's eventual response
+    /// no longer has a waiter and is discarded.
+    ///
+    /// If the worker has died or stops answering, it is restarted with
+    /// exponential backoff and the request is sent again, up to
+    /// `max_restarts` times; after that `WorkerError::Unavailable` is
+    /// returned. Errors reported by a live worker are not retried, and
+    /// neither is a timeout while the worker kept answering other calls:
+    /// restarting would only kill those.
+    pub async fn call_cancellable(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<serde_json::Value, WorkerError> {
+        let mut attempts = 0;
+        loop {
+            if cancel.is_cancelled() {
+                return Err(WorkerError::Cancelled);
+            }
+            if self.stopped.load(Ordering::Relaxed) {
+                return Err(WorkerError::ShutDown);
+            }
+
+            let conn = self.conn.lock().await.clone();
+            let generation = conn.as_ref().map(|c| c.generation);
+            let reason = match conn {
+                Some(conn) if self.is_alive().await => {
+                    let sent_at = tokio::time::Instant::now();
+                    match self.call_once(&conn, method, params.clone(), cancel).await {
+                        Err(WorkerError::Timeout) if conn.answered_since(sent_at) => {
+                            tracing::warn!(method, "ZkProxy worker call timed out");
+                            return Err(WorkerError::Timeout);
+                        }
+                        Err(e @ (WorkerError::Timeout | WorkerError::Exited)) => e.to_string(),
+                        Err(WorkerError::Failed(reason)) if !self.is_alive().await => reason,
+                        other => return other,
+                    }
+                }
+                _ => WorkerError::Exited.to_string(),
+            };
+
+            if attempts >= self.max_restarts {
+                return Err(WorkerError::Unavailable { attempts, reason });
+            }
+            let backoff = RESTART_BACKOFF
+                .saturating_mul(2u32.saturating_pow(attempts))
+                .min(MAX_RESTART_BACKOFF);
+            attempts += 1;
+            tracing::warn!(
+                method,
+                attempt = attempts,
+                "ZkProxy worker failed ({reason}), restarting in {backoff:?}"
+            );
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(WorkerError::Cancelled),
+                _ = tokio::time::sleep(backoff) => {}
+            }
+            if let Err(e) = self.restart_from(generation).await {
+                tracing::warn!("ZkProxy worker restart failed: {e}");
+            }
+        }
+    }
+
+    /// Send one request and wait for its response, without any recovery.
+    async fn call_once(
+        &self,
+        conn: &Connection,
+        method: &str,
+        params: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<serde_json::Value, WorkerError> {
         let id = self.request_id.fetch_add(1, Ordering::Relaxed);
         let request = JsonRpcRequest::new(id, method, params);
         let request_line =
             serde_json::to_string(&request).map_err(|e| format!("Serialize error: {e}"))?;
 
-        let mut stdin_guard = self.stdin.lock().await;
-        let stdin = stdin_guard.as_mut().ok_or("Worker stdin unavailable")?;
-        stdin
-            .write_all(format!("{request_line}\n").as_bytes())
-            .await
-            .map_err(|e| format!("Failed to write to worker: {e}"))?;
-        stdin
-            .flush()
-            .await
-            .map_err(|e| format!("Failed to flush worker stdin: {e}"))?;
-        drop(stdin_guard);
-
-        let mut reader_guard = self.reader.lock().await;
-        let reader = reader_guard.as_mut().ok_or("Worker stdout unavailable")?;
+        let rx = conn.register(id)?;
+        if let Err(e) = conn.send(request_line) {
+            conn.forget(id);
+            return Err(e);
+        }
 
-        let mut response_line = String::new();
-        tokio::time::timeout(
-            std::time::Duration::from_secs(120),
-            reader.read_line(&mut response_line),
-        )
-        .await
-        .map_err(|_| "Worker response timeout".to_string())?
-        .map_err(|e| format!("Failed to read from worker: {e}"))?;
+        let outcome = tokio::select! {
+            _ = cancel.cancelled() => None,
+            res = tokio::time::timeout(self.call_timeout, rx) => Some(res),
+        };
 
-        let response: JsonRpcResponse = serde_json::from_str(&response_line)
-            .map_err(|e| format!("Invalid response: {e} -- raw: {response_line}"))?;
+        let response = match outcome {
+            None => {
+                conn.forget(id);
+                Self::notify_cancel(conn, id);
+                return Err(WorkerError::Cancelled);
+            }
+            Some(Err(_)) => {
+                conn.forget(id);
+                return Err(WorkerError::Timeout);
+            }
+            Some(Ok(Err(_))) => return Err(WorkerError::Exited),
+            Some(Ok(Ok(response))) => response,
+        };
 
         if let Some(err) = response.error {
-            return Err(format!("Worker error {}: {}", err.code, err.message));
+            return Err(format!("Worker error {}: {}", err.code, err.message).into());
         }
 
-        response.result.ok_or_else(|| "Empty result from worker".to_string())
+        response
+            .result
+            .ok_or_else(|| "Empty result from worker".into())
+    }
+
+    /// Best-effort `cancel` notification; failures are only logged.
+    fn notify_cancel(conn: &Connection, id: u64) {
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "cancel",
+            "params": { "request_id": id },
+        });
+        if conn.send(notification.to_string()).is_err() {
+            tracing::debug!("Failed to send cancel notification to worker: writer closed");
+        }
     }
 
     pub async fn health(&self) -> Result<serde_json::Value, String> {
         self.call("health", serde_json::json!({})).await
     }
 
+    /// Whether the worker process is still running (it has not exited and
+    /// has not been reaped).
     pub async fn is_alive(&self) -> bool {
-        let guard = self.child.lock().await;
-        if let Some(child) = guard.as_ref() {
-            child.id().is_some()
-        } else {
-            false
+        let mut guard = self.child.lock().await;
+        match guard.as_mut() {
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    /// Liveness probe: restart the process if it has died, then require the
+    /// `health` method to report an `ok` (or `ready`) status.
+    pub async fn check_health(&self) -> Result<(), String> {
+        if !self.is_alive().await {
+            tracing::warn!("ZkProxy worker process is not running, restarting");
+            self.restart()
+                .await
+                .map_err(|e| format!("Worker restart failed: {e}"))?;
+        }
+        let health = self.health().await?;
+        match health.get("status").and_then(|s| s.as_str()) {
+            Some("ok" | "ready") => Ok(()),
+            Some(status) => Err(format!("Worker not ready: status '{status}'")),
+            None => Err(format!("Worker health response has no status: {health}")),
         }
     }
 
     pub async fn restart(&self) -> Result<(), String> {
+        self.restart_from(None).await
+    }
+
+    /// Restart the worker, unless `stale` is given and the connection has
+    /// already been replaced since that generation was observed.
+    async fn restart_from(&self, stale: Option<u64>) -> Result<(), String> {
+        let mut conn_guard = self.conn.lock().await;
+        if self.stopped.load(Ordering::Relaxed) {
+            return Err("Worker shut down".to_string());
+        }
+        if let (Some(stale), Some(current)) = (stale, conn_guard.as_ref()) {
+            if current.generation != stale {
+                return Ok(());
+            }
+        }
+        *conn_guard = None;
         {
             let mut child_guard = self.child.lock().await;
             if let Some(mut child) = child_guard.take() {
                 let _ = child.kill().await;
             }
         }
-        self.spawn().await?;
-        self.wait_for_startup().await
+        *conn_guard = Some(self.spawn().await?);
+        Ok(())
+    }
+
+    /// Stop the worker for good. Calls still in flight fail with `Exited`,
+    /// later ones with `ShutDown`, and no restart is attempted.
+    pub async fn shutdown(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        let mut conn_guard = self.conn.lock().await;
+        *conn_guard = None;
+        if let Some(mut child) = self.child.lock().await.take() {
+            let _ = child.kill().await;
+        }
     }
 }
 
@@ -149,3 +479,261 @@ impl Drop for PersistentWorker {
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const STARTUP: &str =
+        r#"echo '{"jsonrpc":"2.0","method":"startup","params":{"status":"ready"}}'"#;
+
+    async fn mock_worker(body: &str) -> (PersistentWorker, tempfile::NamedTempFile) {
+        let mut script = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut script, format!("{STARTUP}\n{body}\n").as_bytes()).unwrap();
+        let worker = PersistentWorker::new(
+            "sh",
+            script.path(),
+            Duration::from_secs(120),
+            Duration::from_secs(30),
+        )
+        .await
+        .unwrap();
+        (worker, script)
+    }
+
+    #[tokio::test]
+    async fn cancel_before_response_returns_cancelled() {
+        let (worker, _script) = mock_worker("while read line; do sleep 30; done").await;
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+
+        let result = worker
+            .call_cancellable("guard_check", serde_json::json!({}), &cancel)
+            .await;
+        assert!(matches!(result, Err(WorkerError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn stale_response_after_cancel_is_skipped() {
+        let body = r#"i=0
+while read line; do
+  case "$line" in
+    *'"id"'*)
+      i=$((i+1))
+      [ "$i" -eq 1 ] && sleep 0.3
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$i,\"result\":{\"n\":$i}}"
+      ;;
+  esac
+done"#;
+        let (worker, _script) = mock_worker(body).await;
+
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+        let first = worker
+            .call_cancellable("health", serde_json::json!({}), &cancel)
+            .await;
+        assert!(matches!(first, Err(WorkerError::Cancelled)));
+
+        let second = worker.call("health", serde_json::json!({})).await.unwrap();
+        assert_eq!(second["n"], 2);
+    }
+
+    #[tokio::test]
+    async fn slow_call_does_not_block_later_calls() {
+        // `slow` is answered in the background after 0.5s, anything else
+        // immediately, so responses come back out of order.
+        let body = r#"while read line; do
+  id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+  case "$line" in
+    *'"method":"slow"'*)
+      (sleep 0.5; echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"method\":\"slow\"}}") &
+      ;;
+    *)
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"method\":\"fast\"}}"
+      ;;
+  esac
+done"#;
+        let (worker, _script) = mock_worker(body).await;
+        let worker = Arc::new(worker);
+
+        let slow = tokio::spawn({
+            let worker = Arc::clone(&worker);
+            async move { worker.call("slow", serde_json::json!({})).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let fast = worker.call("fast", serde_json::json!({})).await.unwrap();
+        assert_eq!(fast["method"], "fast");
+        assert!(!slow.is_finished());
+
+        let slow = slow.await.unwrap().unwrap();
+        assert_eq!(slow["method"], "slow");
+    }
+
+    /// Answers every request with the given `health` result.
+    fn health_body(result: &str) -> String {
+        format!(
+            r#"i=0
+while read line; do
+  i=$((i+1))
+  echo '{{"jsonrpc":"2.0","id":'$i',"result":{result}}}'
+done"#
+        )
+    }
+
+    #[tokio::test]
+    async fn healthy_worker_passes_check() {
+        let (worker, _script) = mock_worker(&health_body(r#"{"status":"ok"}"#)).await;
+        assert!(worker.is_alive().await);
+        worker.check_health().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn non_ready_status_fails_check() {
+        let (worker, _script) = mock_worker(&health_body(r#"{"status":"loading"}"#)).await;
+        let err = worker.check_health().await.unwrap_err();
+        assert!(err.contains("loading"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn dead_worker_is_restarted() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("started-once");
+        // The first process exits right after startup; the restarted one serves.
+        let body = format!(
+            "if [ ! -e '{}' ]; then touch '{}'; exit 0; fi\n{}",
+            marker.display(),
+            marker.display(),
+            health_body(r#"{"status":"ok"}"#)
+        );
+        let (worker, _script) = mock_worker(&body).await;
+
+        for _ in 0..50 {
+            if !worker.is_alive().await {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!worker.is_alive().await);
+
+        worker.check_health().await.unwrap();
+        assert!(worker.is_alive().await);
+    }
+
+    #[tokio::test]
+    async fn call_recovers_after_worker_is_killed() {
+        let (worker, _script) = mock_worker(&health_body(r#"{"status":"ok"}"#)).await;
+        worker.health().await.unwrap();
+
+        if let Some(child) = worker.child.lock().await.as_mut() {
+            child.start_kill().unwrap();
+        }
+
+        let health = worker.health().await.unwrap();
+        assert_eq!(health["status"], "ok");
+        assert!(worker.is_alive().await);
+    }
+
+    #[tokio::test]
+    async fn call_timeout_is_configurable() {
+        let mut script = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut script,
+            format!("{STARTUP}\nwhile read line; do sleep 30; done\n").as_bytes(),
+        )
+        .unwrap();
+        let worker = PersistentWorker::new(
+            "sh",
+            script.path(),
+            Duration::from_millis(100),
+            Duration::from_secs(30),
+        )
+        .await
+        .unwrap()
+        .with_max_restarts(0);
+
+        let started = std::time::Instant::now();
+        let err = worker.health().await.unwrap_err();
+        assert!(err.contains("timeout"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn timeout_does_not_restart_a_worker_that_still_answers() {
+        // `hang` is never answered, anything else is answered immediately.
+        let body = r#"while read line; do
+  id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+  case "$line" in
+    *'"method":"hang"'*) ;;
+    *) echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"status\":\"ok\"}}" ;;
+  esac
+done"#;
+        let mut script = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut script, format!("{STARTUP}\n{body}\n").as_bytes()).unwrap();
+        let worker = Arc::new(
+            PersistentWorker::new(
+                "sh",
+                script.path(),
+                Duration::from_millis(300),
+                Duration::from_secs(30),
+            )
+            .await
+            .unwrap(),
+        );
+
+        let hang = tokio::spawn({
+            let worker = Arc::clone(&worker);
+            async move {
+                worker
+                    .call_cancellable("hang", serde_json::json!({}), &CancellationToken::new())
+                    .await
+            }
+        });
+        while !hang.is_finished() {
+            worker.health().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let result = hang.await.unwrap();
+        assert!(matches!(result, Err(WorkerError::Timeout)), "{result:?}");
+        assert_eq!(worker.generation.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_worker_without_restart() {
+        let (worker, _script) = mock_worker(&health_body(r#"{"status":"ok"}"#)).await;
+        worker.health().await.unwrap();
+
+        worker.shutdown().await;
+        assert!(!worker.is_alive().await);
+
+        let result = worker
+            .call_cancellable("health", serde_json::json!({}), &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(WorkerError::ShutDown)), "{result:?}");
+        assert!(!worker.is_alive().await);
+    }
+
+    #[tokio::test]
+    async fn persistent_failure_reports_unavailable() {
+        // Every process exits right after startup.
+        let (worker, _script) = mock_worker("exit 0").await;
+        let worker = worker.with_max_restarts(2);
+
+        let result = worker
+            .call_cancellable("health", serde_json::json!({}), &CancellationToken::new())
+            .await;
+        match result {
+            Err(WorkerError::Unavailable { attempts, .. }) => assert_eq!(attempts, 2),
+            other => panic!("expected Unavailable, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "postgres")]
+//! Integration tests for the PostgreSQL migration runner.
+//!
+//! Requires a running PostgreSQL with pgvector extension.
+//! Set DATABASE_URL=postgres://localhost/ironclaw_test
+
+use ironclaw::history::Store;
+
+fn get_pool() -> deadpool_postgres::Pool {
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://localhost/ironclaw_test".to_string());
+
+    let config: tokio_postgres::Config = database_url.parse().expect("Invalid DATABASE_URL");
+
+    let mgr = deadpool_postgres::Manager::new(config, tokio_postgres::NoTls);
+    deadpool_postgres::Pool::builder(mgr)
+        .max_size(2)
+        .build()
+        .expect("Failed to create pool")
+}
+
+/// Number of versioned migrations shipped in `migrations/`.
+fn shipped_migrations() -> usize {
+    std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+        .expect("migrations dir")
+        .filter_map(Result::ok)
+        .filter(|e| e.file_name().to_string_lossy().starts_with('V'))
+        .count()
+}
+
+#[tokio::test]
+async fn test_migrations_apply_and_are_idempotent() {
+    let pool = get_pool();
+    if let Err(e) = pool.get().await {
+        eprintln!("skipping: database unavailable ({e})");
+        return;
+    }
+    let store = Store::from_pool(pool);
+
+    // First run may apply some or none, depending on the database's state.
+    store
+        .migrate()
+        .await
+        .expect("migrations should apply cleanly");
+
+    let applied = store.applied_migrations().await.expect("history readable");
+    assert_eq!(applied.len(), shipped_migrations());
+    let versions: Vec<i32> = applied.iter().map(|m| m.version).collect();
+    let expected: Vec<i32> = (1..=applied.len() as i32).collect();
+    assert_eq!(versions, expected);
+
+    // Second run has nothing left to do.
+    let rerun = store.migrate().await.expect("re-run should succeed");
+    assert!(rerun.is_empty(), "re-run applied {:?}", rerun);
+    assert_eq!(
+        store.applied_migrations().await.expect("history readable"),
+        applied
+    );
+}
@@ -92,6 +92,7 @@ impl LlmProvider for MockLlmProvider {
                     id: "call_mock_001".to_string(),
                     name: tool.name.clone(),
                     arguments: serde_json::json!({"test": true}),
+                    call_id: None,
                 }],
                 input_tokens: 15,
                 output_tokens: 8,
@@ -198,6 +199,7 @@ async fn start_test_server_with_provider(
         skill_registry: None,
         skill_catalog: None,
         chat_rate_limiter: ironclaw::channels::web::server::RateLimiter::new(30, 60),
+        idempotency: ironclaw::channels::web::idempotency::IdempotencyStore::default(),
         registry_entries: Vec::new(),
         cost_guard: None,
         startup_time: std::time::Instant::now(),
@@ -686,6 +688,7 @@ async fn test_no_llm_provider_returns_503() {
         skill_registry: None,
         skill_catalog: None,
         chat_rate_limiter: ironclaw::channels::web::server::RateLimiter::new(30, 60),
+        idempotency: ironclaw::channels::web::idempotency::IdempotencyStore::default(),
         registry_entries: Vec::new(),
         cost_guard: None,
         startup_time: std::time::Instant::now(),
@@ -56,6 +56,7 @@ async fn start_test_server() -> (
         skill_registry: None,
         skill_catalog: None,
         chat_rate_limiter: ironclaw::channels::web::server::RateLimiter::new(30, 60),
+        idempotency: ironclaw::channels::web::idempotency::IdempotencyStore::default(),
         registry_entries: Vec::new(),
         cost_guard: None,
         startup_time: std::time::Instant::now(),
@@ -1,16 +1,21 @@
 """Persistent ZK proxy worker.
 
 Reads JSON-RPC requests from stdin, writes responses to stdout.
-Keeps JSTprove loaded and circuits cached across requests.
+Keeps JSTprove loaded and circuits cached across requests. Requests run
+concurrently on a thread pool and are answered by id as they finish.
 """
 from __future__ import annotations
 
 import hashlib
 import json
+import os
 import sys
 import tempfile
+import threading
 import time
 import traceback
+import uuid
+from concurrent.futures import ThreadPoolExecutor
 from pathlib import Path
 from typing import Any
 
@@ -25,8 +30,13 @@ class ZkProxyWorker:
     def __init__(self) -> None:
         self.jst = JSTprove()
         self._compiled: dict[str, Path] = {}
+        self._compile_lock = threading.Lock()
         self._work_dir = Path(tempfile.mkdtemp(prefix="zkproxy_"))
 
+    def _call_dir(self) -> Path:
+        """A fresh directory for one call's files, so concurrent calls don't collide."""
+        return Path(tempfile.mkdtemp(dir=self._work_dir))
+
     def handle(self, request: dict[str, Any]) -> dict[str, Any]:
         method = request.get("method", "")
         params = request.get("params", {})
@@ -73,10 +83,12 @@ class ZkProxyWorker:
         circuit_dir = self._work_dir / key
         circuit_dir.mkdir(parents=True, exist_ok=True)
 
-        data = self.jst.circuitization_pipeline(
-            model_path=model_path,
-            output_path=circuit_dir,
-        )
+        # Compiling the same model twice at once would write the same files.
+        with self._compile_lock:
+            data = self.jst.circuitization_pipeline(
+                model_path=model_path,
+                output_path=circuit_dir,
+            )
 
         circuit_path = data.get("circuit_path") or str(next(circuit_dir.glob("*_circuit.txt"), ""))
         if circuit_path:
@@ -89,12 +101,13 @@ class ZkProxyWorker:
             "artifacts": {k: str(v) for k, v in data.items() if isinstance(v, (str, Path))},
         }
 
-    def _witness(self, params: dict) -> dict:
+    def _witness(self, params: dict, call_dir: Path | None = None) -> dict:
         model_path = params["model_path"]
         features = params["features"]
 
-        input_file = self._work_dir / "witness_input.json"
-        output_file = self._work_dir / "witness_output.json"
+        call_dir = call_dir or self._call_dir()
+        input_file = call_dir / "witness_input.json"
+        output_file = call_dir / "witness_output.json"
 
         input_data = {"input_data": [features]}
         with input_file.open("w") as f:
@@ -114,7 +127,7 @@ class ZkProxyWorker:
     def _prove(self, params: dict) -> dict:
         witness_path = Path(params["witness_path"])
         circuit_path = Path(params["circuit_path"])
-        proof_path = self._work_dir / f"proof_{time.time_ns()}.bin"
+        proof_path = self._work_dir / f"proof_{uuid.uuid4().hex}.bin"
 
         ok, result = self.jst.prove(
             witness_path=witness_path,
@@ -149,9 +162,10 @@ class ZkProxyWorker:
         features = params["features"]
 
         timings: dict[str, float] = {}
+        call_dir = self._call_dir()
 
         t0 = time.perf_counter()
-        witness_result = self._witness({"model_path": model_path, "features": features})
+        witness_result = self._witness({"model_path": model_path, "features": features}, call_dir)
         timings["witness_ms"] = round((time.perf_counter() - t0) * 1000, 3)
 
         if not witness_result.get("success"):
@@ -173,7 +187,7 @@ class ZkProxyWorker:
                 "note": "no compiled circuit available, skipping prove/verify",
             }
 
-        witness_bin = self._work_dir / "witness_input_witness.bin"
+        witness_bin = call_dir / "witness_input_witness.bin"
         if not witness_bin.exists():
             return {
                 "success": True,
@@ -205,8 +219,8 @@ class ZkProxyWorker:
         verify_result = self._verify({
             "proof_path": prove_result["proof_path"],
             "circuit_path": str(circuit_path),
-            "input_path": str(self._work_dir / "witness_input.json"),
-            "output_path": str(self._work_dir / "witness_output.json"),
+            "input_path": str(call_dir / "witness_input.json"),
+            "output_path": str(call_dir / "witness_output.json"),
             "witness_path": str(witness_bin),
         })
         timings["verify_ms"] = round((time.perf_counter() - t0) * 1000, 3)
@@ -227,8 +241,31 @@ class ZkProxyWorker:
         return {"jsonrpc": "2.0", "id": req_id, "error": err}
 
 
+class ResponseWriter:
+    """Writes one JSON line per response; responses from pool threads never interleave."""
+
+    def __init__(self) -> None:
+        self._lock = threading.Lock()
+        self._cancelled: set[Any] = set()
+
+    def cancel(self, req_id: Any) -> None:
+        with self._lock:
+            self._cancelled.add(req_id)
+
+    def write(self, response: dict[str, Any]) -> None:
+        with self._lock:
+            # The host has stopped waiting for a cancelled request.
+            if response.get("id") in self._cancelled:
+                self._cancelled.discard(response.get("id"))
+                return
+            sys.stdout.write(json.dumps(response) + "\n")
+            sys.stdout.flush()
+
+
 def main() -> None:
     worker = ZkProxyWorker()
+    writer = ResponseWriter()
+    pool = ThreadPoolExecutor(max_workers=int(os.environ.get("ZKPROXY_WORKER_THREADS", "4")))
 
     startup_msg = json.dumps({"jsonrpc": "2.0", "method": "startup", "params": {"status": "ready"}})
     sys.stdout.write(startup_msg + "\n")
@@ -242,14 +279,22 @@ def main() -> None:
         try:
             request = json.loads(line)
         except json.JSONDecodeError as e:
-            response = ZkProxyWorker._error(0, -32700, f"Parse error: {e}")
-            sys.stdout.write(json.dumps(response) + "\n")
-            sys.stdout.flush()
+            writer.write(ZkProxyWorker._error(0, -32700, f"Parse error: {e}"))
             continue
 
-        response = worker.handle(request)
-        sys.stdout.write(json.dumps(response) + "\n")
-        sys.stdout.flush()
+        # Notifications (no "id") never get a response. A running request
+        # can't be interrupted, so a "cancel" only drops its response.
+        if "id" not in request:
+            if request.get("method") == "cancel":
+                req_id = request.get("params", {}).get("request_id")
+                writer.cancel(req_id)
+                sys.stderr.write(f"zkproxy: host cancelled request {req_id}\n")
+                sys.stderr.flush()
+            continue
+
+        pool.submit(lambda request=request: writer.write(worker.handle(request)))
+
+    pool.shutdown(wait=True)
 
 
 if __name__ == "__main__":